@@ -1,10 +1,22 @@
+use alloy_primitives::{Address, U256};
+use alloy_rpc_types_eth::Log;
+use alloy_sol_types::SolEvent;
 use pretty_assertions::assert_eq;
 use starknet_api::block::{BlockHash, BlockNumber};
 use starknet_api::felt;
+use starknet_api::transaction::fields::Fee;
+use starknet_types_core::felt::Felt;
 
-use crate::ethereum_base_layer_contract::{EthereumBaseLayerConfig, EthereumBaseLayerContract};
+use crate::ethereum_base_layer_contract::{
+    l1_events,
+    u256_to_felt,
+    EthereumBaseLayerConfig,
+    EthereumBaseLayerContract,
+    EthereumBaseLayerError,
+    LogMessageToL2,
+};
 use crate::test_utils::get_test_ethereum_node;
-use crate::BaseLayerContract;
+use crate::{BaseLayerContract, L1Event};
 
 // TODO: move to global test_utils crate and use everywhere instead of relying on the
 // confusing `#[ignore]` api to mark slow tests.
@@ -43,3 +55,94 @@ async fn latest_proved_block_ethereum() {
         assert_eq!(latest_block, expected);
     }
 }
+
+#[tokio::test]
+// Note: the test requires ganache-cli installed, otherwise it is ignored.
+async fn latest_l1_block_ethereum() {
+    if !in_ci() {
+        return;
+    }
+
+    let (node_handle, starknet_contract_address) = get_test_ethereum_node();
+    let config = EthereumBaseLayerConfig {
+        node_url: node_handle.0.endpoint().parse().unwrap(),
+        starknet_contract_address,
+    };
+    let contract = EthereumBaseLayerContract::new(config).unwrap();
+
+    let latest_block = contract.latest_l1_block(0).await.unwrap().unwrap();
+    let final_block = contract.latest_l1_block(5).await.unwrap().unwrap();
+    assert_eq!(final_block.number + 5, latest_block.number);
+    assert!(final_block.timestamp <= latest_block.timestamp);
+    assert_eq!(contract.latest_l1_block(1000).await.unwrap(), None);
+}
+
+#[tokio::test]
+// Note: the test requires ganache-cli installed, otherwise it is ignored.
+async fn state_updates_ethereum() {
+    if !in_ci() {
+        return;
+    }
+
+    let (node_handle, starknet_contract_address) = get_test_ethereum_node();
+    let config = EthereumBaseLayerConfig {
+        node_url: node_handle.0.endpoint().parse().unwrap(),
+        starknet_contract_address,
+    };
+    let contract = EthereumBaseLayerContract::new(config).unwrap();
+
+    let latest_block_number = contract.latest_l1_block(0).await.unwrap().unwrap().number;
+    let state_updates = contract.state_updates(0..=latest_block_number).await.unwrap();
+    let proved_blocks: Vec<_> = state_updates
+        .iter()
+        .map(|state_update| (state_update.block_number, state_update.block_hash))
+        .collect();
+    assert_eq!(
+        proved_blocks,
+        [
+            (BlockNumber(100), BlockHash(felt!("0x100"))),
+            (BlockNumber(200), BlockHash(felt!("0x200"))),
+            (BlockNumber(300), BlockHash(felt!("0x300"))),
+        ]
+    );
+    assert!(state_updates.is_sorted_by_key(|state_update| state_update.l1_block_number));
+}
+
+#[test]
+fn u256_to_felt_rejects_values_out_of_the_field() {
+    let max_felt = U256::from_be_bytes(Felt::MAX.to_bytes_be());
+    assert_eq!(u256_to_felt("Event", max_felt).unwrap(), Felt::MAX);
+    assert!(matches!(
+        u256_to_felt("Event", max_felt + U256::from(1)),
+        Err(EthereumBaseLayerError::OutOfRange { event: "Event", .. })
+    ));
+    assert!(matches!(
+        u256_to_felt("Event", U256::MAX),
+        Err(EthereumBaseLayerError::OutOfRange { event: "Event", .. })
+    ));
+}
+
+fn log_message_to_l2(fee: U256) -> Log {
+    let event = LogMessageToL2 {
+        fromAddress: Address::ZERO,
+        toAddress: U256::from(1),
+        selector: U256::from(2),
+        payload: vec![U256::from(3)],
+        nonce: U256::from(4),
+        fee,
+    };
+    Log {
+        inner: alloy_primitives::Log { address: Address::ZERO, data: event.encode_log_data() },
+        ..Default::default()
+    }
+}
+
+#[test]
+fn l1_events_skip_malformed_logs() {
+    let logs = [log_message_to_l2(U256::from(5)), log_message_to_l2(U256::MAX)];
+
+    let events = l1_events(&logs);
+
+    assert_eq!(events.len(), 1);
+    assert!(matches!(events[0], L1Event::LogMessageToL2 { fee: Fee(5), .. }));
+}
@@ -1,13 +1,16 @@
 use std::collections::BTreeMap;
 use std::future::IntoFuture;
+use std::ops::RangeInclusive;
 
 use alloy_contract::{ContractInstance, Interface};
 use alloy_dyn_abi::SolType;
 use alloy_json_rpc::RpcError;
 pub(crate) use alloy_primitives::Address as EthereumContractAddress;
+use alloy_primitives::{B256, U256};
 use alloy_provider::network::Ethereum;
 use alloy_provider::{Provider, ProviderBuilder, RootProvider};
-use alloy_sol_types::sol_data;
+use alloy_rpc_types_eth::{BlockNumberOrTag, Filter, Log};
+use alloy_sol_types::{sol, sol_data, SolEvent};
 use alloy_transport::TransportErrorKind;
 use alloy_transport_http::{Client, Http};
 use async_trait::async_trait;
@@ -15,11 +18,44 @@ use papyrus_config::dumping::{ser_param, ser_required_param, SerializeConfig};
 use papyrus_config::{ParamPath, ParamPrivacyInput, SerializationType, SerializedParam};
 use serde::{Deserialize, Serialize};
 use starknet_api::block::{BlockHash, BlockNumber};
+use starknet_api::core::{ContractAddress, EntryPointSelector, EthAddress, GlobalRoot, Nonce};
 use starknet_api::hash::StarkHash;
-use starknet_types_core::felt;
+use starknet_api::transaction::fields::Fee;
+use starknet_types_core::felt::{self, Felt};
+use tracing::warn;
 use url::Url;
 
-use crate::BaseLayerContract;
+use crate::metrics::BASE_LAYER_MALFORMED_EVENTS;
+use crate::{
+    BaseLayerContract,
+    L1BlockNumber,
+    L1BlockReference,
+    L1Event,
+    L1ToL2Message,
+    StateUpdate,
+};
+
+// The relevant events of the Starknet core contract.
+sol! {
+    event LogMessageToL2(
+        address indexed fromAddress,
+        uint256 indexed toAddress,
+        uint256 indexed selector,
+        uint256[] payload,
+        uint256 nonce,
+        uint256 fee
+    );
+
+    event ConsumedMessageToL2(
+        address indexed fromAddress,
+        uint256 indexed toAddress,
+        uint256 indexed selector,
+        uint256[] payload,
+        uint256 nonce
+    );
+
+    event LogStateUpdate(uint256 globalRoot, int256 blockNumber, uint256 blockHash);
+}
 
 #[derive(thiserror::Error, Debug)]
 pub enum EthereumBaseLayerError {
@@ -27,6 +63,10 @@ pub enum EthereumBaseLayerError {
     Contract(#[from] alloy_contract::Error),
     #[error(transparent)]
     FeltParseError(#[from] felt::FromStrError),
+    #[error("Log without a block number: {0:?}")]
+    MissingLogBlockNumber(Box<Log>),
+    #[error("Value out of range in a {event} event: {value}")]
+    OutOfRange { event: &'static str, value: String },
     #[error(transparent)]
     RpcError(#[from] RpcError<TransportErrorKind>),
     #[error(transparent)]
@@ -124,4 +164,173 @@ impl BaseLayerContract for EthereumBaseLayerContract {
             BlockHash(StarkHash::from_hex(&state_block_hash.to_string())?),
         )))
     }
+
+    async fn latest_l1_block(
+        &self,
+        finality: u64,
+    ) -> Result<Option<L1BlockReference>, Self::Error> {
+        let ethereum_block_number =
+            self.contract.provider().get_block_number().await?.checked_sub(finality);
+        let Some(ethereum_block_number) = ethereum_block_number else {
+            return Ok(None);
+        };
+
+        let block = self
+            .contract
+            .provider()
+            .get_block_by_number(BlockNumberOrTag::Number(ethereum_block_number), false)
+            .await?;
+        Ok(block.map(|block| L1BlockReference {
+            number: block.header.number,
+            timestamp: block.header.timestamp,
+        }))
+    }
+
+    async fn events(
+        &self,
+        block_range: RangeInclusive<L1BlockNumber>,
+    ) -> Result<Vec<L1Event>, Self::Error> {
+        let logs = self
+            .logs(
+                block_range,
+                vec![LogMessageToL2::SIGNATURE_HASH, ConsumedMessageToL2::SIGNATURE_HASH],
+            )
+            .await?;
+
+        Ok(l1_events(&logs))
+    }
+
+    async fn state_updates(
+        &self,
+        block_range: RangeInclusive<L1BlockNumber>,
+    ) -> Result<Vec<StateUpdate>, Self::Error> {
+        let logs = self.logs(block_range, vec![LogStateUpdate::SIGNATURE_HASH]).await?;
+
+        logs.into_iter()
+            .map(|log| {
+                let l1_block_number = log.block_number.ok_or_else(|| {
+                    EthereumBaseLayerError::MissingLogBlockNumber(log.clone().into())
+                })?;
+                let event = log.log_decode::<LogStateUpdate>()?.inner.data;
+                let block_number = u64::try_from(event.blockNumber).map_err(|_| {
+                    EthereumBaseLayerError::OutOfRange {
+                        event: LogStateUpdate::SIGNATURE,
+                        value: event.blockNumber.to_string(),
+                    }
+                })?;
+
+                Ok(StateUpdate {
+                    l1_block_number,
+                    block_number: BlockNumber(block_number),
+                    block_hash: BlockHash(u256_to_felt(
+                        LogStateUpdate::SIGNATURE,
+                        event.blockHash,
+                    )?),
+                    global_root: GlobalRoot(u256_to_felt(
+                        LogStateUpdate::SIGNATURE,
+                        event.globalRoot,
+                    )?),
+                })
+            })
+            .collect()
+    }
+}
+
+impl EthereumBaseLayerContract {
+    async fn logs(
+        &self,
+        block_range: RangeInclusive<L1BlockNumber>,
+        event_signatures: Vec<B256>,
+    ) -> Result<Vec<Log>, EthereumBaseLayerError> {
+        let filter = Filter::new()
+            .address(*self.contract.address())
+            .from_block(*block_range.start())
+            .to_block(*block_range.end())
+            .event_signature(event_signatures);
+
+        Ok(self.contract.provider().get_logs(&filter).await?)
+    }
+}
+
+// A malformed log is skipped rather than failing the whole range, since fetching the range again
+// would fail on it again.
+pub(crate) fn l1_events(logs: &[Log]) -> Vec<L1Event> {
+    logs.iter()
+        .filter_map(|log| match l1_event(log) {
+            Ok(event) => Some(event),
+            Err(err) => {
+                warn!("Skipping a malformed L1 event {:?}: {}", log, err);
+                metrics::increment_counter!(BASE_LAYER_MALFORMED_EVENTS);
+                None
+            }
+        })
+        .collect()
+}
+
+fn l1_event(log: &Log) -> Result<L1Event, EthereumBaseLayerError> {
+    match log.topic0() {
+        Some(&LogMessageToL2::SIGNATURE_HASH) => {
+            let event = log.log_decode::<LogMessageToL2>()?.inner.data;
+            let fee =
+                u128::try_from(event.fee).map_err(|_| EthereumBaseLayerError::OutOfRange {
+                    event: LogMessageToL2::SIGNATURE,
+                    value: event.fee.to_string(),
+                })?;
+            let message = l1_to_l2_message(
+                LogMessageToL2::SIGNATURE,
+                event.fromAddress,
+                event.toAddress,
+                event.selector,
+                &event.payload,
+                event.nonce,
+            )?;
+            Ok(L1Event::LogMessageToL2 { message, fee: Fee(fee) })
+        }
+        _ => {
+            let event = log.log_decode::<ConsumedMessageToL2>()?.inner.data;
+            Ok(L1Event::ConsumedMessageToL2(l1_to_l2_message(
+                ConsumedMessageToL2::SIGNATURE,
+                event.fromAddress,
+                event.toAddress,
+                event.selector,
+                &event.payload,
+                event.nonce,
+            )?))
+        }
+    }
+}
+
+// The event name is only used to report values that don't fit their L2 types.
+fn l1_to_l2_message(
+    event: &'static str,
+    from_address: EthereumContractAddress,
+    to_address: U256,
+    selector: U256,
+    payload: &[U256],
+    nonce: U256,
+) -> Result<L1ToL2Message, EthereumBaseLayerError> {
+    let to_address = ContractAddress::try_from(u256_to_felt(event, to_address)?)
+        .map_err(|_| EthereumBaseLayerError::OutOfRange { event, value: to_address.to_string() })?;
+    Ok(L1ToL2Message {
+        from_address: EthAddress::try_from(Felt::from_bytes_be_slice(from_address.as_slice()))
+            .expect("An Ethereum address fits in a felt."),
+        to_address,
+        entry_point_selector: EntryPointSelector(u256_to_felt(event, selector)?),
+        payload: payload
+            .iter()
+            .map(|value| u256_to_felt(event, *value))
+            .collect::<Result<_, _>>()?,
+        nonce: Nonce(u256_to_felt(event, nonce)?),
+    })
+}
+
+// Fails on values that are not smaller than the field prime, instead of reducing them.
+pub(crate) fn u256_to_felt(
+    event: &'static str,
+    value: U256,
+) -> Result<Felt, EthereumBaseLayerError> {
+    if value > U256::from_be_bytes(Felt::MAX.to_bytes_be()) {
+        return Err(EthereumBaseLayerError::OutOfRange { event, value: value.to_string() });
+    }
+    Ok(Felt::from_bytes_be(&value.to_be_bytes()))
 }
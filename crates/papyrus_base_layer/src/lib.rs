@@ -1,7 +1,25 @@
+use std::iter;
+use std::ops::RangeInclusive;
+use std::sync::Arc;
+
 use async_trait::async_trait;
 use starknet_api::block::{BlockHash, BlockNumber};
+use starknet_api::core::{
+    ChainId,
+    ContractAddress,
+    EntryPointSelector,
+    EthAddress,
+    GlobalRoot,
+    Nonce,
+};
+use starknet_api::executable_transaction::L1HandlerTransaction;
+use starknet_api::transaction::fields::{Calldata, Fee};
+use starknet_api::transaction::{TransactionHasher, TransactionVersion};
+use starknet_api::StarknetApiError;
+use starknet_types_core::felt::Felt;
 
 pub mod ethereum_base_layer_contract;
+pub mod metrics;
 
 #[cfg(any(feature = "testing", test))]
 pub mod test_utils;
@@ -9,6 +27,8 @@ pub mod test_utils;
 #[cfg(test)]
 mod base_layer_test;
 
+pub type L1BlockNumber = u64;
+
 /// Interface for getting data from the Starknet base contract.
 #[async_trait]
 pub trait BaseLayerContract {
@@ -20,4 +40,81 @@ pub trait BaseLayerContract {
         &self,
         finality: u64,
     ) -> Result<Option<(BlockNumber, BlockHash)>, Self::Error>;
+
+    /// Get the latest base layer block, `finality` blocks back from the tip. Returns `None` if the
+    /// base layer has fewer blocks than `finality`.
+    async fn latest_l1_block(&self, finality: u64)
+    -> Result<Option<L1BlockReference>, Self::Error>;
+
+    /// Get the L1 to L2 messaging events emitted in the given base layer block range, in order.
+    async fn events(
+        &self,
+        block_range: RangeInclusive<L1BlockNumber>,
+    ) -> Result<Vec<L1Event>, Self::Error>;
+
+    /// Get the Starknet state updates logged in the given base layer block range, in order.
+    async fn state_updates(
+        &self,
+        block_range: RangeInclusive<L1BlockNumber>,
+    ) -> Result<Vec<StateUpdate>, Self::Error>;
+}
+
+/// The number and timestamp of a base layer block.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct L1BlockReference {
+    pub number: L1BlockNumber,
+    pub timestamp: u64,
+}
+
+/// A message sent from L1 to L2 through the Starknet core contract.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct L1ToL2Message {
+    pub from_address: EthAddress,
+    pub to_address: ContractAddress,
+    pub entry_point_selector: EntryPointSelector,
+    pub payload: Vec<Felt>,
+    pub nonce: Nonce,
+}
+
+impl L1ToL2Message {
+    /// The L1 handler transaction that delivers this message on L2.
+    pub fn to_l1_handler_tx(
+        &self,
+        chain_id: &ChainId,
+        paid_fee_on_l1: Fee,
+    ) -> Result<L1HandlerTransaction, StarknetApiError> {
+        // The sender's address is passed to the L1 handler as the first calldata argument.
+        let calldata = Calldata(Arc::new(
+            iter::once(self.from_address.into()).chain(self.payload.iter().copied()).collect(),
+        ));
+        let tx = starknet_api::transaction::L1HandlerTransaction {
+            version: TransactionVersion::ZERO,
+            nonce: self.nonce,
+            contract_address: self.to_address,
+            entry_point_selector: self.entry_point_selector,
+            calldata,
+        };
+        let tx_hash = tx.calculate_transaction_hash(chain_id, &tx.version)?;
+
+        Ok(L1HandlerTransaction { tx, tx_hash, paid_fee_on_l1 })
+    }
+}
+
+/// Messaging events emitted by the Starknet core contract.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum L1Event {
+    /// A message was sent to L2, along with the fee paid for it on L1.
+    LogMessageToL2 { message: L1ToL2Message, fee: Fee },
+    /// A message was consumed on L1, after the L2 block including its L1 handler was proved.
+    ConsumedMessageToL2(L1ToL2Message),
+}
+
+/// A Starknet state update, as logged by the Starknet core contract.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct StateUpdate {
+    /// The base layer block in which the state update was logged.
+    pub l1_block_number: L1BlockNumber,
+    pub block_number: BlockNumber,
+    pub block_hash: BlockHash,
+    pub global_root: GlobalRoot,
 }
@@ -0,0 +1,2 @@
+/// The number of L1 events that were skipped since they failed to be parsed.
+pub const BASE_LAYER_MALFORMED_EVENTS: &str = "base_layer_malformed_events";
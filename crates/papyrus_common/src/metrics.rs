@@ -27,12 +27,28 @@ pub const PAPYRUS_HEADER_LATENCY_SEC: &str = "papyrus_header_latency";
 /// The number of peers this node is connected to.
 pub const PAPYRUS_NUM_CONNECTED_PEERS: &str = "papyrus_num_connected_peers";
 
+/// The number of connections this node has open, labeled by the transport they use.
+pub const PAPYRUS_NUM_CONNECTIONS: &str = "papyrus_num_connections";
+
 /// The number of active sessions this peer has in which it sends data.
 pub const PAPYRUS_NUM_ACTIVE_INBOUND_SESSIONS: &str = "papyrus_num_active_inbound_sessions";
 
 /// The number of active sessions this peer has in which it requests data.
 pub const PAPYRUS_NUM_ACTIVE_OUTBOUND_SESSIONS: &str = "papyrus_num_active_outbound_sessions";
 
+/// The number of connections denied for exceeding the connection limits, labeled by whether they
+/// were inbound or outbound.
+pub const PAPYRUS_NUM_DENIED_CONNECTIONS: &str = "papyrus_num_denied_connections";
+
+/// The number of inbound queries dropped for exceeding the rate limit of their peer, labeled by
+/// protocol.
+pub const PAPYRUS_NUM_RATE_LIMITED_QUERIES: &str = "papyrus_num_rate_limited_queries";
+
+/// The number of broadcasted messages dropped for exceeding the rate limit of their originating
+/// peer, labeled by topic hash.
+pub const PAPYRUS_NUM_RATE_LIMITED_BROADCASTED_MESSAGES: &str =
+    "papyrus_num_rate_limited_broadcasted_messages";
+
 // TODO: consider making this value non static and add a way to change this while the app is
 // running. e.g via a monitoring endpoint.
 /// Global variable set by the main config to enable collecting profiling metrics.
@@ -43,3 +59,6 @@ pub const PAPYRUS_CONSENSUS_HEIGHT: &str = "papyrus_consensus_height";
 
 /// The number of times consensus has progressed due to the sync protocol.
 pub const PAPYRUS_CONSENSUS_SYNC_COUNT: &str = "papyrus_consensus_sync_count";
+
+/// The number of distinct pieces of evidence of validators signing conflicting messages.
+pub const PAPYRUS_CONSENSUS_EVIDENCE_COUNT: &str = "papyrus_consensus_evidence_count";
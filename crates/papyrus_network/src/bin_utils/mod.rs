@@ -30,10 +30,9 @@ where
         .with_tokio()
         .with_tcp(Default::default(), noise::Config::new, yamux::Config::default)
         .expect("Error building TCP transport")
+        .with_quic()
         .with_dns()
         .expect("Error building DNS transport")
-        // TODO: quic transpot does not work (failure appears in the command line when running in debug mode)
-        // .with_quic()
         .with_behaviour(|key| behaviour(key.clone()))
         .expect("Error while building the swarm")
         .with_swarm_config(|cfg| cfg.with_idle_connection_timeout(idle_connection_timeout))
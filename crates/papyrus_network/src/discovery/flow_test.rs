@@ -12,6 +12,7 @@ use libp2p_swarm_test::SwarmExt;
 use starknet_api::core::ChainId;
 
 use super::{Behaviour, DiscoveryConfig};
+use crate::limits::ConnectionLimitsConfig;
 use crate::mixed_behaviour;
 use crate::mixed_behaviour::{BridgedBehaviour, MixedBehaviour};
 use crate::peer_manager::PeerManagerConfig;
@@ -34,6 +35,7 @@ impl DiscoveryMixedBehaviour {
             None,
             DiscoveryConfig::default(),
             PeerManagerConfig::default(),
+            ConnectionLimitsConfig::default(),
         );
         Self {
             identify: mixed_behaviour.identify,
@@ -1,19 +1,25 @@
+use core::net::Ipv4Addr;
 use std::time::Duration;
 
 use futures::{FutureExt, StreamExt};
 use libp2p::core::multiaddr::Protocol;
+use libp2p::identity::Keypair;
 use libp2p::swarm::SwarmEvent;
-use libp2p::{Multiaddr, Swarm};
+use libp2p::{Multiaddr, PeerId, Swarm};
 use libp2p_swarm_test::SwarmExt;
 use starknet_api::core::ChainId;
 
+use crate::bin_utils::build_swarm;
 use crate::discovery::DiscoveryConfig;
 use crate::gossipsub_impl::Topic;
+use crate::limits::{ConnectionLimitsConfig, InboundRateLimitsConfig};
 use crate::mixed_behaviour::MixedBehaviour;
-use crate::network_manager::{BroadcastTopicClientTrait, GenericNetworkManager};
+use crate::network_manager::test_utils::create_connected_network_configs;
+use crate::network_manager::{BroadcastTopicClientTrait, GenericNetworkManager, NetworkManager};
 use crate::peer_manager::PeerManagerConfig;
 use crate::sqmr;
 use crate::sqmr::Bytes;
+use crate::utils::Transport;
 
 const TIMEOUT: Duration = Duration::from_secs(5);
 
@@ -27,6 +33,7 @@ async fn create_swarm(bootstrap_peer_multiaddr: Option<Multiaddr>) -> Swarm<Mixe
             None,
             DiscoveryConfig::default(),
             PeerManagerConfig::default(),
+            ConnectionLimitsConfig::default(),
         )
     });
     // Not using SwarmExt::listen because it panics if the swarm emits other events
@@ -46,10 +53,40 @@ async fn create_swarm(bootstrap_peer_multiaddr: Option<Multiaddr>) -> Swarm<Mixe
     swarm
 }
 
+// Creates a swarm which listens only on a local QUIC address.
+async fn create_quic_swarm(bootstrap_peer_multiaddr: Option<Multiaddr>) -> Swarm<MixedBehaviour> {
+    let mut swarm = build_swarm(
+        vec!["/ip4/127.0.0.1/udp/0/quic-v1".to_owned()],
+        Duration::from_secs(60),
+        None,
+        |keypair| {
+            MixedBehaviour::new(
+                keypair,
+                bootstrap_peer_multiaddr,
+                sqmr::Config::default(),
+                ChainId::Mainnet,
+                None,
+                DiscoveryConfig::default(),
+                PeerManagerConfig::default(),
+                ConnectionLimitsConfig::default(),
+            )
+        },
+    );
+    let address = swarm
+        .wait(|event| match event {
+            SwarmEvent::NewListenAddr { address, .. } => Some(address),
+            _ => None,
+        })
+        .await;
+    swarm.add_external_address(address);
+
+    swarm
+}
+
 fn create_network_manager(
     swarm: Swarm<MixedBehaviour>,
 ) -> GenericNetworkManager<Swarm<MixedBehaviour>> {
-    GenericNetworkManager::generic_new(swarm, None)
+    GenericNetworkManager::generic_new(swarm, Vec::new(), InboundRateLimitsConfig::default())
 }
 
 const BUFFER_SIZE: usize = 100;
@@ -129,3 +166,93 @@ async fn broadcast_subscriber_end_to_end_test() {
         }
     }
 }
+
+#[tokio::test]
+async fn broadcast_over_quic() {
+    let topic = Topic::new("TOPIC");
+    let mut swarm1 = create_quic_swarm(None).await;
+    let swarm1_multiaddr = swarm1.external_addresses().next().unwrap().clone();
+    assert_eq!(Transport::of(&swarm1_multiaddr), Transport::Quic);
+    let swarm1_multiaddr = swarm1_multiaddr.with_p2p(*swarm1.local_peer_id()).unwrap();
+    let mut swarm2 = create_quic_swarm(Some(swarm1_multiaddr)).await;
+
+    let wait_for_connection = |event| match event {
+        SwarmEvent::ConnectionEstablished { endpoint, .. } => {
+            Some(Transport::of(endpoint.get_remote_address()))
+        }
+        _ => None,
+    };
+    let (transport1, transport2) =
+        tokio::join!(swarm1.wait(wait_for_connection), swarm2.wait(wait_for_connection));
+    assert_eq!(transport1, Transport::Quic);
+    assert_eq!(transport2, Transport::Quic);
+
+    let mut network_manager1 = create_network_manager(swarm1);
+    let mut network_manager2 = create_network_manager(swarm2);
+    let mut subscriber_channels1 =
+        network_manager1.register_broadcast_topic::<Number>(topic.clone(), BUFFER_SIZE).unwrap();
+    let subscriber_channels2 =
+        network_manager2.register_broadcast_topic::<Number>(topic, BUFFER_SIZE).unwrap();
+
+    tokio::select! {
+        _ = network_manager1.run() => panic!("network manager ended"),
+        _ = network_manager2.run() => panic!("network manager ended"),
+        result = tokio::time::timeout(
+            TIMEOUT, async move {
+                // TODO(shahak): Remove this sleep once we fix the bug of broadcasting while there
+                // are no peers.
+                tokio::time::sleep(Duration::from_secs(1)).await;
+                let number = Number(1);
+                let mut broadcast_client2 = subscriber_channels2.broadcasted_messages_receiver;
+                subscriber_channels1.broadcast_topic_client.broadcast_message(number).await.unwrap();
+                let (received_number, _report_callback) = broadcast_client2.next().await.unwrap();
+                assert_eq!(received_number.unwrap(), number);
+            }
+        ) => {
+            result.unwrap()
+        }
+    }
+}
+
+// Unlike the test above, the nodes are created from their configs, as in production. They listen on
+// both transports, but the bootstrap peer is only known by its QUIC address.
+#[tokio::test]
+async fn network_managers_connect_over_quic() {
+    let topic = Topic::new("TOPIC");
+    let mut configs = create_connected_network_configs(2);
+    let secret_key0 = configs[0].secret_key.clone().unwrap();
+    let peer_id0 =
+        PeerId::from_public_key(&Keypair::ed25519_from_bytes(secret_key0).unwrap().public());
+    configs[1].bootstrap_peer_multiaddr = Some(
+        Multiaddr::empty()
+            .with(Protocol::Ip4(Ipv4Addr::LOCALHOST))
+            .with(Protocol::Udp(configs[0].quic_port))
+            .with(Protocol::QuicV1)
+            .with(Protocol::P2p(peer_id0)),
+    );
+    let mut network_manager1 = NetworkManager::new(configs.pop().unwrap(), None);
+    let mut network_manager0 = NetworkManager::new(configs.pop().unwrap(), None);
+    let mut subscriber_channels0 =
+        network_manager0.register_broadcast_topic::<Number>(topic.clone(), BUFFER_SIZE).unwrap();
+    let subscriber_channels1 =
+        network_manager1.register_broadcast_topic::<Number>(topic, BUFFER_SIZE).unwrap();
+
+    tokio::select! {
+        _ = network_manager0.run() => panic!("network manager ended"),
+        _ = network_manager1.run() => panic!("network manager ended"),
+        result = tokio::time::timeout(
+            TIMEOUT, async move {
+                // TODO(shahak): Remove this sleep once we fix the bug of broadcasting while there
+                // are no peers.
+                tokio::time::sleep(Duration::from_secs(1)).await;
+                let number = Number(1);
+                let mut broadcast_client1 = subscriber_channels1.broadcasted_messages_receiver;
+                subscriber_channels0.broadcast_topic_client.broadcast_message(number).await.unwrap();
+                let (received_number, _report_callback) = broadcast_client1.next().await.unwrap();
+                assert_eq!(received_number.unwrap(), number);
+            }
+        ) => {
+            result.unwrap()
+        }
+    }
+}
@@ -1,10 +1,10 @@
-use libp2p::gossipsub::TopicHash;
+use libp2p::gossipsub::{MessageId, TopicHash};
 use libp2p::{gossipsub, PeerId};
 use tracing::error;
 
-use crate::mixed_behaviour;
 use crate::mixed_behaviour::BridgedBehaviour;
 use crate::sqmr::Bytes;
+use crate::{mixed_behaviour, peer_manager};
 
 #[cfg(test)]
 pub type Topic = gossipsub::IdentTopic;
@@ -14,15 +14,23 @@ pub type Topic = gossipsub::Sha256Topic;
 #[derive(Debug)]
 pub enum ExternalEvent {
     #[allow(dead_code)]
-    Received { originated_peer_id: PeerId, message: Bytes, topic_hash: TopicHash },
+    Received {
+        originated_peer_id: PeerId,
+        // The peer that sent the message to this node, which isn't necessarily its originator.
+        propagation_source: PeerId,
+        message_id: MessageId,
+        message: Bytes,
+        topic_hash: TopicHash,
+    },
 }
 
 impl From<gossipsub::Event> for mixed_behaviour::Event {
     fn from(event: gossipsub::Event) -> Self {
         match event {
             gossipsub::Event::Message {
+                propagation_source,
+                message_id,
                 message: gossipsub::Message { data, topic, source, .. },
-                ..
             } => {
                 let Some(originated_peer_id) = source else {
                     error!(
@@ -36,6 +44,8 @@ impl From<gossipsub::Event> for mixed_behaviour::Event {
                 mixed_behaviour::Event::ExternalEvent(mixed_behaviour::ExternalEvent::GossipSub(
                     ExternalEvent::Received {
                         originated_peer_id,
+                        propagation_source,
+                        message_id,
                         message: data,
                         topic_hash: topic,
                     },
@@ -48,6 +58,34 @@ impl From<gossipsub::Event> for mixed_behaviour::Event {
     }
 }
 
+/// Returns the gossipsub peer scoring parameters. The only score component that is taken into
+/// account besides the gossipsub defaults is the application specific score, which is derived from
+/// the misconduct score the peer manager gives to the peer.
+pub(crate) fn peer_score_params() -> (gossipsub::PeerScoreParams, gossipsub::PeerScoreThresholds) {
+    (
+        gossipsub::PeerScoreParams { app_specific_weight: 1.0, ..Default::default() },
+        gossipsub::PeerScoreThresholds::default(),
+    )
+}
+
+// A peer with no misconduct has an application score of 0, and a malicious peer reaches the
+// graylist threshold, where all of its messages are ignored.
+fn application_score(misconduct_score: f64) -> f64 {
+    misconduct_score * gossipsub::PeerScoreThresholds::default().graylist_threshold
+}
+
 impl BridgedBehaviour for gossipsub::Behaviour {
-    fn on_other_behaviour_event(&mut self, _event: &mixed_behaviour::ToOtherBehaviourEvent) {}
+    fn on_other_behaviour_event(&mut self, event: &mixed_behaviour::ToOtherBehaviourEvent) {
+        if let mixed_behaviour::ToOtherBehaviourEvent::PeerManager(
+            peer_manager::ToOtherBehaviourEvent::MisconductScoreChanged {
+                peer_id,
+                misconduct_score,
+            },
+        ) = event
+        {
+            // Returns false if the peer isn't known to gossipsub, in which case there's nothing
+            // to penalize.
+            self.set_application_score(peer_id, application_score(*misconduct_score));
+        }
+    }
 }
@@ -7,6 +7,7 @@ mod discovery;
 #[cfg(test)]
 mod e2e_broadcast_test;
 pub mod gossipsub_impl;
+mod limits;
 mod mixed_behaviour;
 pub mod network_manager;
 mod peer_manager;
@@ -20,6 +21,7 @@ use std::time::Duration;
 
 use discovery::DiscoveryConfig;
 use libp2p::Multiaddr;
+use limits::{ConnectionLimitsConfig, InboundRateLimitsConfig};
 use papyrus_config::converters::{
     deserialize_optional_vec_u8,
     deserialize_seconds_to_duration,
@@ -54,7 +56,11 @@ pub struct NetworkConfig {
     pub advertised_multiaddr: Option<Multiaddr>,
     pub chain_id: ChainId,
     pub discovery_config: DiscoveryConfig,
+    #[validate]
     pub peer_manager_config: PeerManagerConfig,
+    pub connection_limits_config: ConnectionLimitsConfig,
+    #[validate]
+    pub inbound_rate_limits_config: InboundRateLimitsConfig,
 }
 
 impl SerializeConfig for NetworkConfig {
@@ -112,12 +118,21 @@ impl SerializeConfig for NetworkConfig {
             "advertised_multiaddr",
             "The external address other peers see this node. If this is set, the node will not \
              try to find out which addresses it has and will write this address as external \
-             instead",
+             instead. If this is a TCP address, the QUIC address on quic_port of the same host is \
+             advertised as well",
             ParamPrivacyInput::Public,
         ));
         config.extend(append_sub_config_name(self.discovery_config.dump(), "discovery_config"));
         config
             .extend(append_sub_config_name(self.peer_manager_config.dump(), "peer_manager_config"));
+        config.extend(append_sub_config_name(
+            self.connection_limits_config.dump(),
+            "connection_limits_config",
+        ));
+        config.extend(append_sub_config_name(
+            self.inbound_rate_limits_config.dump(),
+            "inbound_rate_limits_config",
+        ));
         config
     }
 }
@@ -135,6 +150,8 @@ impl Default for NetworkConfig {
             chain_id: ChainId::Mainnet,
             discovery_config: DiscoveryConfig::default(),
             peer_manager_config: PeerManagerConfig::default(),
+            connection_limits_config: ConnectionLimitsConfig::default(),
+            inbound_rate_limits_config: InboundRateLimitsConfig::default(),
         }
     }
 }
@@ -0,0 +1,216 @@
+#[cfg(test)]
+#[path = "limits_test.rs"]
+mod limits_test;
+
+use std::collections::{BTreeMap, HashMap};
+use std::hash::Hash;
+use std::time::{Duration, Instant};
+
+use libp2p::connection_limits::ConnectionLimits;
+use libp2p::PeerId;
+use papyrus_config::dumping::{append_sub_config_name, ser_param, SerializeConfig};
+use papyrus_config::{ParamPath, ParamPrivacyInput, SerializedParam};
+use serde::{Deserialize, Serialize};
+use validator::Validate;
+
+#[derive(Copy, Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct ConnectionLimitsConfig {
+    pub max_connections: u32,
+    pub max_inbound_connections: u32,
+    pub max_outbound_connections: u32,
+    pub max_connections_per_peer: u32,
+}
+
+impl Default for ConnectionLimitsConfig {
+    fn default() -> Self {
+        Self {
+            max_connections: 200,
+            max_inbound_connections: 150,
+            max_outbound_connections: 100,
+            // A peer may be connected both over TCP and over QUIC, and both sides may dial at
+            // the same time.
+            max_connections_per_peer: 4,
+        }
+    }
+}
+
+impl SerializeConfig for ConnectionLimitsConfig {
+    fn dump(&self) -> BTreeMap<ParamPath, SerializedParam> {
+        BTreeMap::from([
+            ser_param(
+                "max_connections",
+                &self.max_connections,
+                "The maximal number of established connections.",
+                ParamPrivacyInput::Public,
+            ),
+            ser_param(
+                "max_inbound_connections",
+                &self.max_inbound_connections,
+                "The maximal number of established connections that other peers opened.",
+                ParamPrivacyInput::Public,
+            ),
+            ser_param(
+                "max_outbound_connections",
+                &self.max_outbound_connections,
+                "The maximal number of established connections that this node opened.",
+                ParamPrivacyInput::Public,
+            ),
+            ser_param(
+                "max_connections_per_peer",
+                &self.max_connections_per_peer,
+                "The maximal number of established connections with a single peer.",
+                ParamPrivacyInput::Public,
+            ),
+        ])
+    }
+}
+
+impl From<ConnectionLimitsConfig> for ConnectionLimits {
+    fn from(config: ConnectionLimitsConfig) -> Self {
+        ConnectionLimits::default()
+            .with_max_established(Some(config.max_connections))
+            .with_max_established_incoming(Some(config.max_inbound_connections))
+            .with_max_established_outgoing(Some(config.max_outbound_connections))
+            .with_max_established_per_peer(Some(config.max_connections_per_peer))
+    }
+}
+
+/// The configuration of a token bucket. A peer can send up to `capacity` messages at once, and
+/// afterwards `refill_per_second` messages per second.
+#[derive(Copy, Clone, Debug, Serialize, Deserialize, PartialEq, Validate)]
+pub struct RateLimitConfig {
+    #[validate(range(min = 1))]
+    pub capacity: u32,
+    #[validate(range(min = 1))]
+    pub refill_per_second: u32,
+}
+
+impl SerializeConfig for RateLimitConfig {
+    fn dump(&self) -> BTreeMap<ParamPath, SerializedParam> {
+        BTreeMap::from([
+            ser_param(
+                "capacity",
+                &self.capacity,
+                "The maximal number of messages a peer can send at once.",
+                ParamPrivacyInput::Public,
+            ),
+            ser_param(
+                "refill_per_second",
+                &self.refill_per_second,
+                "The number of messages per second a peer can send after using up the capacity.",
+                ParamPrivacyInput::Public,
+            ),
+        ])
+    }
+}
+
+/// Rate limits on the messages other peers send to this node. Each peer has a separate limit for
+/// each protocol and for each topic.
+#[derive(Copy, Clone, Debug, Serialize, Deserialize, PartialEq, Validate)]
+pub struct InboundRateLimitsConfig {
+    #[validate]
+    pub queries: RateLimitConfig,
+    #[validate]
+    pub broadcasted_messages: RateLimitConfig,
+}
+
+impl Default for InboundRateLimitsConfig {
+    fn default() -> Self {
+        Self {
+            queries: RateLimitConfig { capacity: 100, refill_per_second: 10 },
+            broadcasted_messages: RateLimitConfig { capacity: 1000, refill_per_second: 100 },
+        }
+    }
+}
+
+impl SerializeConfig for InboundRateLimitsConfig {
+    fn dump(&self) -> BTreeMap<ParamPath, SerializedParam> {
+        let mut dump = append_sub_config_name(self.queries.dump(), "queries");
+        dump.append(&mut append_sub_config_name(
+            self.broadcasted_messages.dump(),
+            "broadcasted_messages",
+        ));
+        dump
+    }
+}
+
+// The interval in which the buckets that were refilled are removed, so that the buckets of peers
+// that stopped sending messages don't accumulate.
+const IDLE_BUCKETS_EVICTION_INTERVAL: Duration = Duration::from_secs(60);
+
+struct TokenBucket {
+    tokens: f64,
+    last_refill: Instant,
+}
+
+/// Token buckets of each peer, one per key (e.g protocol).
+pub(crate) struct RateLimiter<K: Eq + Hash> {
+    config: RateLimitConfig,
+    buckets: HashMap<(PeerId, K), TokenBucket>,
+    last_eviction: Instant,
+}
+
+impl<K: Eq + Hash> RateLimiter<K> {
+    pub fn new(config: RateLimitConfig) -> Self {
+        Self { config, buckets: HashMap::new(), last_eviction: get_instant_now() }
+    }
+
+    /// Takes a token from the bucket of the given peer and key. Returns false if the bucket is
+    /// empty, meaning the message should be rejected.
+    pub fn try_acquire(&mut self, peer_id: PeerId, key: K) -> bool {
+        let capacity = f64::from(self.config.capacity);
+        let refill_per_second = f64::from(self.config.refill_per_second);
+        let now = get_instant_now();
+        if now.saturating_duration_since(self.last_eviction) >= IDLE_BUCKETS_EVICTION_INTERVAL {
+            self.evict_idle_buckets(now);
+        }
+        let bucket = self
+            .buckets
+            .entry((peer_id, key))
+            .or_insert_with(|| TokenBucket { tokens: capacity, last_refill: now });
+        let elapsed = now.saturating_duration_since(bucket.last_refill).as_secs_f64();
+        bucket.tokens = capacity.min(bucket.tokens + elapsed * refill_per_second);
+        bucket.last_refill = now;
+        if bucket.tokens < 1.0 {
+            return false;
+        }
+        bucket.tokens -= 1.0;
+        true
+    }
+
+    /// Removes the buckets of the given peer that are full. Buckets that aren't full are kept so
+    /// that a peer can't reset its limits by reconnecting.
+    pub fn forget_peer(&mut self, peer_id: PeerId) {
+        let now = get_instant_now();
+        let config = self.config;
+        self.buckets.retain(|(bucket_peer_id, _), bucket| {
+            *bucket_peer_id != peer_id || !bucket.is_full(config, now)
+        });
+    }
+
+    // A full bucket behaves like a bucket that doesn't exist, so removing it doesn't change the
+    // limits.
+    fn evict_idle_buckets(&mut self, now: Instant) {
+        let config = self.config;
+        self.buckets.retain(|_, bucket| !bucket.is_full(config, now));
+        self.last_eviction = now;
+    }
+}
+
+impl TokenBucket {
+    fn is_full(&self, config: RateLimitConfig, now: Instant) -> bool {
+        let elapsed = now.saturating_duration_since(self.last_refill).as_secs_f64();
+        self.tokens + elapsed * f64::from(config.refill_per_second) >= f64::from(config.capacity)
+    }
+}
+
+#[cfg(not(test))]
+fn get_instant_now() -> Instant {
+    Instant::now()
+}
+
+// In tests we simulate time passing using tokio, so we need to use tokio's Instant instead of std.
+#[cfg(test)]
+fn get_instant_now() -> Instant {
+    tokio::time::Instant::now().into_std()
+}
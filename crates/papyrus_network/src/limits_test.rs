@@ -0,0 +1,93 @@
+use std::time::Duration;
+
+use libp2p::PeerId;
+use validator::Validate;
+
+use crate::limits::{
+    InboundRateLimitsConfig,
+    RateLimitConfig,
+    RateLimiter,
+    IDLE_BUCKETS_EVICTION_INTERVAL,
+};
+use crate::NetworkConfig;
+
+const CONFIG: RateLimitConfig = RateLimitConfig { capacity: 2, refill_per_second: 1 };
+
+#[tokio::test]
+async fn rate_limiter_rejects_after_capacity_and_refills() {
+    tokio::time::pause();
+    let mut rate_limiter = RateLimiter::new(CONFIG);
+    let peer_id = PeerId::random();
+
+    assert!(rate_limiter.try_acquire(peer_id, "protocol"));
+    assert!(rate_limiter.try_acquire(peer_id, "protocol"));
+    assert!(!rate_limiter.try_acquire(peer_id, "protocol"));
+
+    tokio::time::advance(Duration::from_secs(1)).await;
+    assert!(rate_limiter.try_acquire(peer_id, "protocol"));
+    assert!(!rate_limiter.try_acquire(peer_id, "protocol"));
+}
+
+#[tokio::test]
+async fn rate_limiter_has_separate_buckets_per_peer_and_key() {
+    tokio::time::pause();
+    let mut rate_limiter = RateLimiter::new(CONFIG);
+    let peer_id = PeerId::random();
+    let other_peer_id = PeerId::random();
+
+    assert!(rate_limiter.try_acquire(peer_id, "protocol"));
+    assert!(rate_limiter.try_acquire(peer_id, "protocol"));
+    assert!(!rate_limiter.try_acquire(peer_id, "protocol"));
+
+    assert!(rate_limiter.try_acquire(peer_id, "other_protocol"));
+    assert!(rate_limiter.try_acquire(other_peer_id, "protocol"));
+}
+
+#[tokio::test]
+async fn forgetting_peer_keeps_its_limit_until_refilled() {
+    tokio::time::pause();
+    let mut rate_limiter = RateLimiter::new(CONFIG);
+    let peer_id = PeerId::random();
+
+    assert!(rate_limiter.try_acquire(peer_id, "protocol"));
+    assert!(rate_limiter.try_acquire(peer_id, "protocol"));
+    rate_limiter.forget_peer(peer_id);
+    assert!(!rate_limiter.try_acquire(peer_id, "protocol"));
+
+    tokio::time::advance(Duration::from_secs(2)).await;
+    rate_limiter.forget_peer(peer_id);
+    assert!(rate_limiter.buckets.is_empty());
+}
+
+#[tokio::test]
+async fn idle_buckets_are_evicted() {
+    tokio::time::pause();
+    let mut rate_limiter = RateLimiter::new(CONFIG);
+    let idle_peer_id = PeerId::random();
+    let peer_id = PeerId::random();
+
+    assert!(rate_limiter.try_acquire(idle_peer_id, "protocol"));
+    tokio::time::advance(IDLE_BUCKETS_EVICTION_INTERVAL).await;
+    assert!(rate_limiter.try_acquire(peer_id, "protocol"));
+    assert_eq!(rate_limiter.buckets.len(), 1);
+    assert!(rate_limiter.buckets.contains_key(&(peer_id, "protocol")));
+}
+
+#[test]
+fn zero_rate_limits_are_invalid() {
+    for rate_limit_config in [
+        RateLimitConfig { capacity: 0, ..CONFIG },
+        RateLimitConfig { refill_per_second: 0, ..CONFIG },
+    ] {
+        assert!(rate_limit_config.validate().is_err());
+        let config = NetworkConfig {
+            inbound_rate_limits_config: InboundRateLimitsConfig {
+                queries: rate_limit_config,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        assert!(config.validate().is_err());
+    }
+    assert!(NetworkConfig::default().validate().is_ok());
+}
@@ -5,12 +5,13 @@ use libp2p::kad::store::MemoryStore;
 use libp2p::swarm::behaviour::toggle::Toggle;
 use libp2p::swarm::dial_opts::DialOpts;
 use libp2p::swarm::NetworkBehaviour;
-use libp2p::{gossipsub, identify, kad, Multiaddr, PeerId, StreamProtocol};
+use libp2p::{connection_limits, gossipsub, identify, kad, Multiaddr, PeerId, StreamProtocol};
 use starknet_api::core::ChainId;
 
 use crate::discovery::identify_impl::{IdentifyToOtherBehaviourEvent, IDENTIFY_PROTOCOL_VERSION};
 use crate::discovery::kad_impl::KadToOtherBehaviourEvent;
 use crate::discovery::DiscoveryConfig;
+use crate::limits::ConnectionLimitsConfig;
 use crate::peer_manager::PeerManagerConfig;
 use crate::{discovery, gossipsub_impl, peer_manager, sqmr};
 
@@ -20,6 +21,9 @@ const ONE_MEGA: usize = 1 << 20;
 #[derive(NetworkBehaviour)]
 #[behaviour(out_event = "Event")]
 pub struct MixedBehaviour {
+    // Placed first so that connections exceeding the limits are denied before the other
+    // behaviours handle them.
+    pub connection_limits: connection_limits::Behaviour,
     pub peer_manager: peer_manager::PeerManager,
     pub discovery: Toggle<discovery::Behaviour>,
     pub identify: identify::Behaviour,
@@ -51,6 +55,13 @@ pub enum ToOtherBehaviourEvent {
     Sqmr(sqmr::ToOtherBehaviourEvent),
 }
 
+// The connection limits behaviour doesn't emit events.
+impl From<void::Void> for Event {
+    fn from(event: void::Void) -> Self {
+        void::unreachable(event)
+    }
+}
+
 pub trait BridgedBehaviour {
     fn on_other_behaviour_event(&mut self, event: &ToOtherBehaviourEvent);
 }
@@ -58,6 +69,7 @@ pub trait BridgedBehaviour {
 impl MixedBehaviour {
     // TODO: get config details from network manager config
     /// Panics if bootstrap_peer_multiaddr doesn't have a peer id.
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         keypair: Keypair,
         bootstrap_peer_multiaddr: Option<Multiaddr>,
@@ -66,6 +78,7 @@ impl MixedBehaviour {
         node_version: Option<String>,
         discovery_config: DiscoveryConfig,
         peer_manager_config: PeerManagerConfig,
+        connection_limits_config: ConnectionLimitsConfig,
     ) -> Self {
         let public_key = keypair.public();
         let local_peer_id = PeerId::from_public_key(&public_key);
@@ -75,6 +88,7 @@ impl MixedBehaviour {
                 .expect("Failed to create StreamProtocol from a string that starts with /"),
         ]);
         Self {
+            connection_limits: connection_limits::Behaviour::new(connection_limits_config.into()),
             peer_manager: peer_manager::PeerManager::new(peer_manager_config),
             discovery: bootstrap_peer_multiaddr
                 .map(|bootstrap_peer_multiaddr| {
@@ -104,18 +118,32 @@ impl MixedBehaviour {
                 kademlia_config,
             ),
             sqmr: sqmr::Behaviour::new(streamed_bytes_config),
-            gossipsub: gossipsub::Behaviour::new(
-                gossipsub::MessageAuthenticity::Signed(keypair),
-                gossipsub::ConfigBuilder::default()
-                    .max_transmit_size(ONE_MEGA)
-                    .build()
-                    .expect("Failed to build gossipsub config"),
-            )
-            .unwrap_or_else(|err_string| {
-                panic!(
-                    "Failed creating gossipsub behaviour due to the following error: {err_string}"
-                )
-            }),
+            gossipsub: new_gossipsub_behaviour(keypair),
         }
     }
 }
+
+fn new_gossipsub_behaviour(keypair: Keypair) -> gossipsub::Behaviour {
+    let mut gossipsub = gossipsub::Behaviour::new(
+        gossipsub::MessageAuthenticity::Signed(keypair),
+        // Messages are propagated only after the network manager reports them as accepted, so
+        // that the ones it ignores, e.g. due to rate limiting, aren't propagated.
+        gossipsub::ConfigBuilder::default()
+            .max_transmit_size(ONE_MEGA)
+            .validate_messages()
+            .build()
+            .expect("Failed to build gossipsub config"),
+    )
+    .unwrap_or_else(|err_string| {
+        panic!("Failed creating gossipsub behaviour due to the following error: {err_string}")
+    });
+    let (peer_score_params, peer_score_thresholds) = gossipsub_impl::peer_score_params();
+    gossipsub.with_peer_score(peer_score_params, peer_score_thresholds).unwrap_or_else(
+        |err_string| {
+            panic!(
+                "Failed enabling gossipsub peer scoring due to the following error: {err_string}"
+            )
+        },
+    );
+    gossipsub
+}
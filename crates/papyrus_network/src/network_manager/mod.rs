@@ -6,6 +6,7 @@ mod test;
 pub mod test_utils;
 
 use std::collections::HashMap;
+use std::iter;
 use std::pin::Pin;
 use std::task::{Context, Poll};
 
@@ -16,10 +17,10 @@ use futures::future::{ready, BoxFuture, Ready};
 use futures::sink::With;
 use futures::stream::{FuturesUnordered, Map, Stream};
 use futures::{pin_mut, FutureExt, Sink, SinkExt, StreamExt};
-use libp2p::gossipsub::{SubscriptionError, TopicHash};
-use libp2p::swarm::SwarmEvent;
-use libp2p::{Multiaddr, PeerId, StreamProtocol, Swarm};
-use metrics::gauge;
+use libp2p::gossipsub::{MessageAcceptance, SubscriptionError, TopicHash};
+use libp2p::swarm::{DialError, ListenError, SwarmEvent};
+use libp2p::{connection_limits, Multiaddr, PeerId, StreamProtocol, Swarm};
+use metrics::{gauge, increment_counter};
 use papyrus_common::metrics as papyrus_metrics;
 use papyrus_network_types::network_types::{BroadcastedMessageMetadata, OpaquePeerId};
 use sqmr::Bytes;
@@ -28,10 +29,12 @@ use tracing::{debug, error, info, trace, warn};
 use self::swarm_trait::SwarmTrait;
 use crate::bin_utils::build_swarm;
 use crate::gossipsub_impl::Topic;
+use crate::limits::{InboundRateLimitsConfig, RateLimiter};
 use crate::mixed_behaviour::{self, BridgedBehaviour};
+pub use crate::peer_manager::{ReputationModifier, MALICIOUS, UNRESPONSIVE};
 use crate::sqmr::behaviour::SessionError;
 use crate::sqmr::{self, InboundSessionId, OutboundSessionId, SessionId};
-use crate::utils::{is_localhost, StreamHashMap};
+use crate::utils::{is_localhost, to_quic_address, StreamHashMap, Transport};
 use crate::{gossipsub_impl, NetworkConfig};
 
 #[derive(thiserror::Error, Debug)]
@@ -43,9 +46,25 @@ pub enum NetworkError {
 // TODO: Understand whats the correct thing to do here.
 const MESSAGE_METADATA_BUFFER_SIZE: usize = 100000;
 
+const TRANSPORT_LABEL: &str = "transport";
+const DIRECTION_LABEL: &str = "direction";
+const PROTOCOL_LABEL: &str = "protocol";
+const TOPIC_HASH_LABEL: &str = "topic_hash";
+/// A report on data that a peer sends only if it's malicious, e.g. data that can't be parsed or has
+/// an invalid signature.
+pub const MALICIOUS_REPORT: ReputationModifier =
+    ReputationModifier::Misconduct { misconduct_score: MALICIOUS };
+
 pub struct GenericNetworkManager<SwarmT: SwarmTrait> {
     swarm: SwarmT,
     inbound_protocol_to_buffer_size: HashMap<StreamProtocol, usize>,
+    // Maps each version of a protocol registered as a server to the latest version of that
+    // protocol and to the converter of the version.
+    sqmr_inbound_protocol_versions: HashMap<StreamProtocol, (StreamProtocol, SqmrVersionConverter)>,
+    // Maps the latest version of each protocol registered as a client to all of its versions,
+    // ordered from the latest to the oldest.
+    sqmr_outbound_protocol_versions: HashMap<StreamProtocol, Vec<StreamProtocol>>,
+    sqmr_outbound_version_converters: HashMap<StreamProtocol, SqmrVersionConverter>,
     sqmr_inbound_response_receivers: StreamHashMap<InboundSessionId, ResponsesReceiver>,
     sqmr_inbound_payload_senders: HashMap<StreamProtocol, SqmrServerSender>,
     sqmr_outbound_payload_receivers: StreamHashMap<StreamProtocol, SqmrClientReceiver>,
@@ -56,15 +75,19 @@ pub struct GenericNetworkManager<SwarmT: SwarmTrait> {
     // Each receiver has a matching sender and vice versa (i.e the maps have the same keys).
     messages_to_broadcast_receivers: StreamHashMap<TopicHash, Receiver<Bytes>>,
     broadcasted_messages_senders: HashMap<TopicHash, Sender<(Bytes, BroadcastedMessageMetadata)>>,
-    reported_peer_receivers: FuturesUnordered<BoxFuture<'static, Option<PeerId>>>,
-    advertised_multiaddr: Option<Multiaddr>,
-    reported_peers_receiver: Receiver<PeerId>,
-    reported_peers_sender: Sender<PeerId>,
+    reported_peer_receivers:
+        FuturesUnordered<BoxFuture<'static, Option<(PeerId, ReputationModifier)>>>,
+    advertised_multiaddrs: Vec<Multiaddr>,
+    reported_peers_receiver: Receiver<ReportedPeer>,
+    reported_peers_sender: Sender<ReportedPeer>,
     continue_propagation_sender: Sender<BroadcastedMessageMetadata>,
     continue_propagation_receiver: Receiver<BroadcastedMessageMetadata>,
     // Fields for metrics
     num_active_inbound_sessions: usize,
     num_active_outbound_sessions: usize,
+    num_connections_per_transport: HashMap<Transport, usize>,
+    inbound_queries_rate_limiter: RateLimiter<StreamProtocol>,
+    broadcasted_messages_rate_limiter: RateLimiter<TopicHash>,
 }
 
 impl<SwarmT: SwarmTrait> GenericNetworkManager<SwarmT> {
@@ -79,8 +102,8 @@ impl<SwarmT: SwarmTrait> GenericNetworkManager<SwarmT> {
                 Some((topic_hash, message)) = self.messages_to_broadcast_receivers.next() => {
                     self.broadcast_message(message.expect("A broadcast channel should not be terminated."), topic_hash);
                 }
-                Some(Some(peer_id)) = self.reported_peer_receivers.next() => self.swarm.report_peer_as_malicious(peer_id),
-                Some(peer_id) = self.reported_peers_receiver.next() => self.swarm.report_peer_as_malicious(peer_id),
+                Some(Some((peer_id, reason))) = self.reported_peer_receivers.next() => self.swarm.report_peer(peer_id, reason),
+                Some((peer_id, reason)) = self.reported_peers_receiver.next() => self.swarm.report_peer(peer_id, reason),
                 Some(broadcasted_message_metadata) = self.continue_propagation_receiver.next() => {
                     self.swarm.continue_propagation(broadcasted_message_metadata);
                 }
@@ -88,13 +111,24 @@ impl<SwarmT: SwarmTrait> GenericNetworkManager<SwarmT> {
         }
     }
 
-    // TODO(shahak): remove the advertised_multiaddr arg once we manage external addresses
+    // TODO(shahak): remove the advertised_multiaddrs arg once we manage external addresses
     // in a behaviour.
-    pub(crate) fn generic_new(mut swarm: SwarmT, advertised_multiaddr: Option<Multiaddr>) -> Self {
+    pub(crate) fn generic_new(
+        mut swarm: SwarmT,
+        advertised_multiaddrs: Vec<Multiaddr>,
+        inbound_rate_limits_config: InboundRateLimitsConfig,
+    ) -> Self {
         gauge!(papyrus_metrics::PAPYRUS_NUM_CONNECTED_PEERS, 0f64);
+        for transport in [Transport::Tcp, Transport::Quic] {
+            gauge!(
+                papyrus_metrics::PAPYRUS_NUM_CONNECTIONS,
+                0f64,
+                TRANSPORT_LABEL => transport.as_str()
+            );
+        }
         let reported_peer_receivers = FuturesUnordered::new();
         reported_peer_receivers.push(futures::future::pending().boxed());
-        if let Some(address) = advertised_multiaddr.clone() {
+        for address in advertised_multiaddrs.iter().cloned() {
             swarm.add_external_address(address);
         }
         let (reported_peers_sender, reported_peers_receiver) =
@@ -104,6 +138,9 @@ impl<SwarmT: SwarmTrait> GenericNetworkManager<SwarmT> {
         Self {
             swarm,
             inbound_protocol_to_buffer_size: HashMap::new(),
+            sqmr_inbound_protocol_versions: HashMap::new(),
+            sqmr_outbound_protocol_versions: HashMap::new(),
+            sqmr_outbound_version_converters: HashMap::new(),
             sqmr_inbound_response_receivers: StreamHashMap::new(HashMap::new()),
             sqmr_inbound_payload_senders: HashMap::new(),
             sqmr_outbound_payload_receivers: StreamHashMap::new(HashMap::new()),
@@ -112,17 +149,21 @@ impl<SwarmT: SwarmTrait> GenericNetworkManager<SwarmT> {
             messages_to_broadcast_receivers: StreamHashMap::new(HashMap::new()),
             broadcasted_messages_senders: HashMap::new(),
             reported_peer_receivers,
-            advertised_multiaddr,
+            advertised_multiaddrs,
             reported_peers_receiver,
             reported_peers_sender,
             continue_propagation_sender,
             continue_propagation_receiver,
             num_active_inbound_sessions: 0,
             num_active_outbound_sessions: 0,
+            num_connections_per_transport: HashMap::new(),
+            inbound_queries_rate_limiter: RateLimiter::new(inbound_rate_limits_config.queries),
+            broadcasted_messages_rate_limiter: RateLimiter::new(
+                inbound_rate_limits_config.broadcasted_messages,
+            ),
         }
     }
 
-    // TODO: Support multiple protocols where they're all different versions of the same protocol
     pub fn register_sqmr_protocol_server<Query, Response>(
         &mut self,
         protocol: String,
@@ -134,9 +175,40 @@ impl<SwarmT: SwarmTrait> GenericNetworkManager<SwarmT> {
         <Query as TryFrom<Bytes>>::Error: Clone,
         Response: 'static,
     {
-        let protocol = StreamProtocol::try_from_owned(protocol)
-            .expect("Could not parse protocol into StreamProtocol.");
-        self.swarm.add_new_supported_inbound_protocol(protocol.clone());
+        self.register_versioned_sqmr_protocol_server(
+            vec![SqmrProtocolVersion::latest(protocol)],
+            buffer_size,
+        )
+    }
+
+    /// Register a server for several versions of the same protocol. The versions are ordered from
+    /// the latest to the oldest, and the queries and responses the server handles are of the
+    /// latest version. Queries of older versions and responses to them are converted using the
+    /// converter of the version.
+    /// Panics if no versions are given or if one of the versions is already registered.
+    pub fn register_versioned_sqmr_protocol_server<Query, Response>(
+        &mut self,
+        versions: Vec<SqmrProtocolVersion>,
+        buffer_size: usize,
+    ) -> SqmrServerReceiver<Query, Response>
+    where
+        Bytes: From<Response>,
+        Query: TryFrom<Bytes> + Clone,
+        <Query as TryFrom<Bytes>>::Error: Clone,
+        Response: 'static,
+    {
+        let versions = parse_sqmr_protocol_versions(versions);
+        let (protocol, _) = versions.first().cloned().expect("No protocol versions were given.");
+        for (version, converter) in versions {
+            self.swarm.add_new_supported_inbound_protocol(version.clone());
+            if self
+                .sqmr_inbound_protocol_versions
+                .insert(version.clone(), (protocol.clone(), converter))
+                .is_some()
+            {
+                panic!("Protocol '{}' has already been registered as a server.", version);
+            }
+        }
         if let Some(_old_buffer_size) =
             self.inbound_protocol_to_buffer_size.insert(protocol.clone(), buffer_size)
         {
@@ -158,7 +230,6 @@ impl<SwarmT: SwarmTrait> GenericNetworkManager<SwarmT> {
 
     /// Register a new subscriber for sending a single query and receiving multiple responses.
     /// Panics if the given protocol is already subscribed.
-    // TODO: Support multiple protocols where they're all different versions of the same protocol
     // TODO: Seperate query and response buffer sizes.
     pub fn register_sqmr_protocol_client<Query, Response>(
         &mut self,
@@ -171,9 +242,38 @@ impl<SwarmT: SwarmTrait> GenericNetworkManager<SwarmT> {
         <Response as TryFrom<Bytes>>::Error: 'static + Send,
         Query: 'static,
     {
-        let protocol = StreamProtocol::try_from_owned(protocol)
-            .expect("Could not parse protocol into StreamProtocol.");
-        self.swarm.add_new_supported_inbound_protocol(protocol.clone());
+        self.register_versioned_sqmr_protocol_client(
+            vec![SqmrProtocolVersion::latest(protocol)],
+            buffer_size,
+        )
+    }
+
+    /// Register a client for several versions of the same protocol. The versions are ordered from
+    /// the latest to the oldest, and each query is sent in the latest version the responding peer
+    /// supports. The queries and responses the client handles are of the latest version, and are
+    /// converted to and from older versions using the converter of the version.
+    /// Panics if no versions are given or if one of the versions is already registered.
+    pub fn register_versioned_sqmr_protocol_client<Query, Response>(
+        &mut self,
+        versions: Vec<SqmrProtocolVersion>,
+        buffer_size: usize,
+    ) -> SqmrClientSender<Query, Response>
+    where
+        Bytes: From<Query>,
+        Response: TryFrom<Bytes> + 'static + Send,
+        <Response as TryFrom<Bytes>>::Error: 'static + Send,
+        Query: 'static,
+    {
+        let versions = parse_sqmr_protocol_versions(versions);
+        let (protocol, _) = versions.first().cloned().expect("No protocol versions were given.");
+        for (version, converter) in versions.iter() {
+            self.swarm.add_new_supported_inbound_protocol(version.clone());
+            if self.sqmr_outbound_version_converters.insert(version.clone(), *converter).is_some() {
+                panic!("Protocol '{}' has already been registered as a client.", version);
+            }
+        }
+        self.sqmr_outbound_protocol_versions
+            .insert(protocol.clone(), versions.into_iter().map(|(version, _)| version).collect());
         let (payload_sender, payload_receiver) = futures::channel::mpsc::channel(buffer_size);
 
         let insert_result = self
@@ -232,11 +332,13 @@ impl<SwarmT: SwarmTrait> GenericNetworkManager<SwarmT> {
         let messages_to_broadcast_sender =
             messages_to_broadcast_sender.with(messages_to_broadcast_fn);
 
-        let reported_messages_fn: fn(
-            BroadcastedMessageMetadata,
-        ) -> Ready<Result<PeerId, SendError>> = |broadcasted_message_metadata| {
-            ready(Ok(broadcasted_message_metadata.originator_id.private_get_peer_id()))
-        };
+        let reported_messages_fn: fn(ReportedMessage) -> Ready<Result<ReportedPeer, SendError>> =
+            |(broadcasted_message_metadata, reason)| {
+                ready(Ok((
+                    broadcasted_message_metadata.originator_id.private_get_peer_id(),
+                    reason,
+                )))
+            };
         let reported_messages_sender =
             self.reported_peers_sender.clone().with(reported_messages_fn);
 
@@ -255,14 +357,16 @@ impl<SwarmT: SwarmTrait> GenericNetworkManager<SwarmT> {
     fn handle_swarm_event(&mut self, event: SwarmEvent<mixed_behaviour::Event>) {
         #[allow(clippy::as_conversions)] // FIXME: use int metrics so `as f64` may be removed.
         match event {
-            SwarmEvent::ConnectionEstablished { peer_id, .. } => {
-                debug!("Connected to peer id: {peer_id:?}");
+            SwarmEvent::ConnectionEstablished { peer_id, endpoint, .. } => {
+                let transport = Transport::of(endpoint.get_remote_address());
+                debug!("Connected to peer id: {peer_id:?} over {}", transport.as_str());
                 gauge!(
                     papyrus_metrics::PAPYRUS_NUM_CONNECTED_PEERS,
                     self.swarm.num_connected_peers() as f64
                 );
+                self.report_connection_change_to_metrics(transport, true);
             }
-            SwarmEvent::ConnectionClosed { peer_id, cause, .. } => {
+            SwarmEvent::ConnectionClosed { peer_id, endpoint, num_established, cause, .. } => {
                 match cause {
                     Some(connection_error) => {
                         debug!("Connection to {peer_id:?} closed due to {connection_error:?}.")
@@ -273,11 +377,27 @@ impl<SwarmT: SwarmTrait> GenericNetworkManager<SwarmT> {
                     papyrus_metrics::PAPYRUS_NUM_CONNECTED_PEERS,
                     self.swarm.num_connected_peers() as f64
                 );
+                self.report_connection_change_to_metrics(
+                    Transport::of(endpoint.get_remote_address()),
+                    false,
+                );
+                if num_established == 0 {
+                    self.inbound_queries_rate_limiter.forget_peer(peer_id);
+                    self.broadcasted_messages_rate_limiter.forget_peer(peer_id);
+                }
             }
             SwarmEvent::Behaviour(event) => {
                 self.handle_behaviour_event(event);
             }
             SwarmEvent::OutgoingConnectionError { connection_id, peer_id, error } => {
+                if let DialError::Denied { cause } = &error {
+                    if cause.downcast_ref::<connection_limits::Exceeded>().is_some() {
+                        increment_counter!(
+                            papyrus_metrics::PAPYRUS_NUM_DENIED_CONNECTIONS,
+                            DIRECTION_LABEL => "outbound"
+                        );
+                    }
+                }
                 warn!(
                     "Outgoing connection error. connection id: {connection_id:?}, requested peer \
                      id: {peer_id:?}, error: {error:?}"
@@ -291,6 +411,14 @@ impl<SwarmT: SwarmTrait> GenericNetworkManager<SwarmT> {
             } => {
                 // No need to panic here since this is a result of another peer trying to dial to us
                 // and failing. Other peers are welcome to retry.
+                if let ListenError::Denied { cause } = &error {
+                    if cause.downcast_ref::<connection_limits::Exceeded>().is_some() {
+                        increment_counter!(
+                            papyrus_metrics::PAPYRUS_NUM_DENIED_CONNECTIONS,
+                            DIRECTION_LABEL => "inbound"
+                        );
+                    }
+                }
                 warn!(
                     "Incoming connection error. connection id: {connection_id:?}, local addr: \
                      {local_addr:?}, send back addr: {send_back_addr:?}, error: {error:?}"
@@ -298,7 +426,7 @@ impl<SwarmT: SwarmTrait> GenericNetworkManager<SwarmT> {
             }
             SwarmEvent::NewListenAddr { address, .. } => {
                 // TODO(shahak): Find a better way to filter private addresses.
-                if !is_localhost(&address) && self.advertised_multiaddr.is_none() {
+                if !is_localhost(&address) && self.advertised_multiaddrs.is_empty() {
                     self.swarm.add_external_address(address);
                 }
             }
@@ -366,7 +494,13 @@ impl<SwarmT: SwarmTrait> GenericNetworkManager<SwarmT> {
                 outbound_session_id,
                 response,
                 peer_id,
-            } => self.handle_sqmr_event_received_response(outbound_session_id, peer_id, response),
+                protocol_name,
+            } => self.handle_sqmr_event_received_response(
+                outbound_session_id,
+                peer_id,
+                protocol_name,
+                response,
+            ),
             sqmr::behaviour::ExternalEvent::SessionFailed { session_id, error } => {
                 self.handle_sqmr_event_session_failed(session_id, error)
             }
@@ -389,19 +523,57 @@ impl<SwarmT: SwarmTrait> GenericNetworkManager<SwarmT> {
             papyrus_metrics::PAPYRUS_NUM_ACTIVE_INBOUND_SESSIONS,
             self.num_active_inbound_sessions as f64
         );
-        let (report_sender, report_receiver) = oneshot::channel::<()>();
+        let Some((protocol, converter)) =
+            self.sqmr_inbound_protocol_versions.get(&protocol_name).cloned()
+        else {
+            return;
+        };
+        // All the versions of a protocol share the same limit.
+        if !self.inbound_queries_rate_limiter.try_acquire(peer_id, protocol.clone()) {
+            warn!(
+                "Peer {peer_id:?} exceeded the rate limit of queries for {protocol}. Dropping its \
+                 query."
+            );
+            increment_counter!(
+                papyrus_metrics::PAPYRUS_NUM_RATE_LIMITED_QUERIES,
+                PROTOCOL_LABEL => protocol.to_string()
+            );
+            self.drop_inbound_session(inbound_session_id);
+            return;
+        }
+        let Some(query) = (converter.query_from_version)(query) else {
+            warn!(
+                "Received a query of {protocol_name} from peer {peer_id:?} that can't be \
+                 converted to {protocol}. Reporting the peer and dropping the session."
+            );
+            self.swarm.report_peer(peer_id, MALICIOUS_REPORT);
+            self.drop_inbound_session(inbound_session_id);
+            return;
+        };
+        let (report_sender, report_receiver) = oneshot::channel::<ReputationModifier>();
         self.handle_new_report_receiver(peer_id, report_receiver);
         // TODO: consider returning error instead of panic.
-        let Some(query_sender) = self.sqmr_inbound_payload_senders.get_mut(&protocol_name) else {
+        let Some(query_sender) = self.sqmr_inbound_payload_senders.get_mut(&protocol) else {
             return;
         };
         let (responses_sender, responses_receiver) = futures::channel::mpsc::channel(
             *self
                 .inbound_protocol_to_buffer_size
-                .get(&protocol_name)
+                .get(&protocol)
                 .expect("A protocol is registered in NetworkManager but it has no buffer size."),
         );
         let responses_sender = Box::new(responses_sender);
+        // A response that can't be converted to the negotiated version ends the session.
+        let response_into_version = converter.response_into_version;
+        let responses_receiver = responses_receiver.scan((), move |(), response| {
+            ready(response_into_version(response).or_else(|| {
+                warn!(
+                    "Failed to convert a response to {protocol_name}. Closing session \
+                     {inbound_session_id:?}."
+                );
+                None
+            }))
+        });
         self.sqmr_inbound_response_receivers.insert(
             inbound_session_id,
             // Adding a None at the end of the stream so that we will receive a message
@@ -424,6 +596,7 @@ impl<SwarmT: SwarmTrait> GenericNetworkManager<SwarmT> {
         &mut self,
         outbound_session_id: OutboundSessionId,
         peer_id: PeerId,
+        protocol_name: StreamProtocol,
         response: Vec<u8>,
     ) {
         trace!(
@@ -435,6 +608,20 @@ impl<SwarmT: SwarmTrait> GenericNetworkManager<SwarmT> {
         {
             self.handle_new_report_receiver(peer_id, report_receiver)
         }
+        let Some(response) = self
+            .sqmr_outbound_version_converters
+            .get(&protocol_name)
+            .and_then(|converter| (converter.response_from_version)(response))
+        else {
+            warn!(
+                "Received a response of {protocol_name} from peer {peer_id:?} that can't be \
+                 converted to the latest version. Reporting the peer and ignoring the rest of \
+                 session {outbound_session_id:?}."
+            );
+            self.swarm.report_peer(peer_id, MALICIOUS_REPORT);
+            self.sqmr_outbound_response_senders.remove(&outbound_session_id);
+            return;
+        };
         if let Some(response_sender) =
             self.sqmr_outbound_response_senders.get_mut(&outbound_session_id)
         {
@@ -453,8 +640,14 @@ impl<SwarmT: SwarmTrait> GenericNetworkManager<SwarmT> {
     fn handle_sqmr_event_session_failed(&mut self, session_id: SessionId, error: SessionError) {
         error!("Session {session_id:?} failed on {error:?}");
         self.report_session_removed_to_metrics(session_id);
-        // TODO: Handle reputation and retry.
+        // TODO: Handle retry.
         if let SessionId::OutboundSessionId(outbound_session_id) = session_id {
+            if let SessionError::Timeout { .. } = error {
+                self.swarm.report_session(
+                    outbound_session_id,
+                    ReputationModifier::Misconduct { misconduct_score: UNRESPONSIVE },
+                );
+            }
             self.sqmr_outbound_response_senders.remove(&outbound_session_id);
             if let Some(_report_receiver) =
                 self.sqmr_outbound_report_receivers_awaiting_assignment.remove(&outbound_session_id)
@@ -484,28 +677,66 @@ impl<SwarmT: SwarmTrait> GenericNetworkManager<SwarmT> {
     }
 
     fn handle_gossipsub_behaviour_event(&mut self, event: gossipsub_impl::ExternalEvent) {
-        let gossipsub_impl::ExternalEvent::Received { originated_peer_id, message, topic_hash } =
-            event;
+        let gossipsub_impl::ExternalEvent::Received {
+            originated_peer_id,
+            propagation_source,
+            message_id,
+            message,
+            topic_hash,
+        } = event;
+        // The limit is on the peer that sent the message to us, since it's the one that chose to
+        // send it.
+        if !self
+            .broadcasted_messages_rate_limiter
+            .try_acquire(propagation_source, topic_hash.clone())
+        {
+            warn!(
+                "Peer {propagation_source:?} exceeded the rate limit of messages for the topic \
+                 with hash {topic_hash:?}. Ignoring its message."
+            );
+            increment_counter!(
+                papyrus_metrics::PAPYRUS_NUM_RATE_LIMITED_BROADCASTED_MESSAGES,
+                TOPIC_HASH_LABEL => topic_hash.to_string()
+            );
+            self.swarm.report_message_validation_result(
+                &message_id,
+                &propagation_source,
+                MessageAcceptance::Ignore,
+            );
+            return;
+        }
         let broadcasted_message_metadata = BroadcastedMessageMetadata {
             originator_id: OpaquePeerId::private_new(originated_peer_id),
+            propagation_source: OpaquePeerId::private_new(propagation_source),
         };
         let Some(sender) = self.broadcasted_messages_senders.get_mut(&topic_hash) else {
             error!(
                 "Received a message from a topic we're not subscribed to with hash {topic_hash:?}"
             );
+            self.swarm.report_message_validation_result(
+                &message_id,
+                &propagation_source,
+                MessageAcceptance::Ignore,
+            );
             return;
         };
         let send_result = sender.try_send((message, broadcasted_message_metadata));
-        if let Err(e) = send_result {
-            if e.is_disconnected() {
+        // Gossipsub validates messages through this node, so a message is propagated only once
+        // it's accepted. Messages that weren't delivered aren't propagated either.
+        let acceptance = match send_result {
+            Ok(()) => MessageAcceptance::Accept,
+            Err(e) if e.is_disconnected() => {
                 panic!("Receiver was dropped. This should never happen.")
-            } else if e.is_full() {
+            }
+            Err(_) => {
                 warn!(
                     "Receiver buffer is full. Dropping broadcasted message for topic with hash: \
                      {topic_hash:?}."
                 );
+                MessageAcceptance::Ignore
             }
-        }
+        };
+        self.swarm.report_message_validation_result(&message_id, &propagation_source, acceptance);
     }
 
     fn handle_response_for_inbound_query(&mut self, res: (InboundSessionId, Option<Bytes>)) {
@@ -537,8 +768,29 @@ impl<SwarmT: SwarmTrait> GenericNetworkManager<SwarmT> {
         protocol: StreamProtocol,
         client_payload: SqmrClientPayload,
     ) {
-        let SqmrClientPayload { query, report_receiver, responses_sender } = client_payload;
-        match self.swarm.send_query(query, PeerId::random(), protocol.clone()) {
+        let SqmrClientPayload { query, report_receiver, responses_sender, preferred_peer } =
+            client_payload;
+        let queries: Vec<_> = self
+            .sqmr_outbound_protocol_versions
+            .get(&protocol)
+            .expect("A protocol is registered in NetworkManager but it has no versions.")
+            .iter()
+            .filter_map(|version| {
+                let converter = self
+                    .sqmr_outbound_version_converters
+                    .get(version)
+                    .expect("A protocol is registered in NetworkManager but it has no converter.");
+                (converter.query_into_version)(query.clone()).map(|query| (version.clone(), query))
+            })
+            .collect();
+        if queries.is_empty() {
+            warn!(
+                "Failed to convert a query of {protocol} to any of its versions. Returning empty \
+                 response to sync subscriber."
+            );
+            return;
+        }
+        match self.swarm.send_query(queries, preferred_peer) {
             #[allow(clippy::as_conversions)] // FIXME: use int metrics so `as f64` may be removed.
             Ok(outbound_session_id) => {
                 debug!(
@@ -567,6 +819,18 @@ impl<SwarmT: SwarmTrait> GenericNetworkManager<SwarmT> {
         self.swarm.broadcast_message(message, topic_hash);
     }
 
+    // Drops an inbound session so that the peer that opened it sees it as failed rather than as a
+    // query with no results.
+    fn drop_inbound_session(&mut self, inbound_session_id: InboundSessionId) {
+        match self.swarm.drop_session(inbound_session_id.into()) {
+            // A dropped session doesn't emit an event that it finished.
+            Ok(()) => self.report_session_removed_to_metrics(inbound_session_id.into()),
+            Err(e) => error!(
+                "Failed to drop session. Session id: {inbound_session_id:?} not found error: {e:?}"
+            ),
+        }
+    }
+
     fn report_session_removed_to_metrics(&mut self, session_id: SessionId) {
         #[allow(clippy::as_conversions)] // FIXME: use int metrics so `as f64` may be removed.
         match session_id {
@@ -586,11 +850,27 @@ impl<SwarmT: SwarmTrait> GenericNetworkManager<SwarmT> {
             }
         }
     }
-    fn handle_new_report_receiver(&self, peer_id: PeerId, report_receiver: oneshot::Receiver<()>) {
+
+    #[allow(clippy::as_conversions)] // FIXME: use int metrics so `as f64` may be removed.
+    fn report_connection_change_to_metrics(&mut self, transport: Transport, is_established: bool) {
+        let num_connections = self.num_connections_per_transport.entry(transport).or_default();
+        if is_established {
+            *num_connections += 1;
+        } else {
+            *num_connections = num_connections.saturating_sub(1);
+        }
+        gauge!(
+            papyrus_metrics::PAPYRUS_NUM_CONNECTIONS,
+            *num_connections as f64,
+            TRANSPORT_LABEL => transport.as_str()
+        );
+    }
+
+    fn handle_new_report_receiver(&self, peer_id: PeerId, report_receiver: ReportReceiver) {
         self.reported_peer_receivers.push(
             report_receiver
                 .map(move |result| match result {
-                    Ok(_) => Some(peer_id),
+                    Ok(reason) => Some((peer_id, reason)),
                     Err(_) => None,
                 })
                 .boxed(),
@@ -617,7 +897,7 @@ impl NetworkManager {
     pub fn new(config: NetworkConfig, node_version: Option<String>) -> Self {
         let NetworkConfig {
             tcp_port,
-            quic_port: _,
+            quic_port,
             session_timeout,
             idle_connection_timeout,
             bootstrap_peer_multiaddr,
@@ -626,11 +906,12 @@ impl NetworkManager {
             chain_id,
             discovery_config,
             peer_manager_config,
+            connection_limits_config,
+            inbound_rate_limits_config,
         } = config;
 
         let listen_addresses = vec![
-            // TODO: uncomment once quic transport works.
-            // format!("/ip4/0.0.0.0/udp/{quic_port}/quic-v1"),
+            format!("/ip4/0.0.0.0/udp/{quic_port}/quic-v1"),
             format!("/ip4/0.0.0.0/tcp/{tcp_port}"),
         ];
 
@@ -643,14 +924,21 @@ impl NetworkManager {
                 node_version,
                 discovery_config,
                 peer_manager_config,
+                connection_limits_config,
             )
         });
-        let advertised_multiaddr = advertised_multiaddr.map(|address| {
-            address
-                .with_p2p(*swarm.local_peer_id())
-                .expect("advertised_multiaddr has a peer id different than the local peer id")
-        });
-        Self::generic_new(swarm, advertised_multiaddr)
+        let advertised_multiaddrs = advertised_multiaddr
+            .into_iter()
+            .flat_map(|address| {
+                let address = address
+                    .with_p2p(*swarm.local_peer_id())
+                    .expect("advertised_multiaddr has a peer id different than the local peer id");
+                // Peers may connect to us over either transport.
+                let quic_address = to_quic_address(&address, quic_port);
+                iter::once(address).chain(quic_address)
+            })
+            .collect();
+        Self::generic_new(swarm, advertised_multiaddrs, inbound_rate_limits_config)
     }
 
     pub fn get_local_peer_id(&self) -> String {
@@ -658,8 +946,57 @@ impl NetworkManager {
     }
 }
 
-pub type ReportSender = oneshot::Sender<()>;
-type ReportReceiver = oneshot::Receiver<()>;
+/// Converts a message of one version of a protocol to another version. Returns None if the message
+/// can't be represented in the other version.
+pub type SqmrMessageConverterFn = fn(Bytes) -> Option<Bytes>;
+
+/// Converts the queries and responses of a version of an SQMR protocol to and from the latest
+/// version of that protocol.
+#[derive(Clone, Copy, Debug)]
+pub struct SqmrVersionConverter {
+    pub query_into_version: SqmrMessageConverterFn,
+    pub query_from_version: SqmrMessageConverterFn,
+    pub response_into_version: SqmrMessageConverterFn,
+    pub response_from_version: SqmrMessageConverterFn,
+}
+
+impl SqmrVersionConverter {
+    /// The converter of the latest version, which leaves the messages unchanged.
+    pub const IDENTITY: Self = Self {
+        query_into_version: Some,
+        query_from_version: Some,
+        response_into_version: Some,
+        response_from_version: Some,
+    };
+}
+
+#[derive(Clone, Debug)]
+pub struct SqmrProtocolVersion {
+    pub protocol: String,
+    pub converter: SqmrVersionConverter,
+}
+
+impl SqmrProtocolVersion {
+    pub fn latest(protocol: String) -> Self {
+        Self { protocol, converter: SqmrVersionConverter::IDENTITY }
+    }
+}
+
+fn parse_sqmr_protocol_versions(
+    versions: Vec<SqmrProtocolVersion>,
+) -> Vec<(StreamProtocol, SqmrVersionConverter)> {
+    versions
+        .into_iter()
+        .map(|SqmrProtocolVersion { protocol, converter }| {
+            let protocol = StreamProtocol::try_from_owned(protocol)
+                .expect("Could not parse protocol into StreamProtocol.");
+            (protocol, converter)
+        })
+        .collect()
+}
+
+pub type ReportSender = oneshot::Sender<ReputationModifier>;
+type ReportReceiver = oneshot::Receiver<ReputationModifier>;
 
 type GenericSender<T> = Box<dyn Sink<T, Error = SendError> + Unpin + Send>;
 // Box<S> implements Stream only if S: Stream + Unpin
@@ -720,14 +1057,36 @@ where
         &mut self,
         query: Query,
     ) -> Result<ClientResponsesManager<Response>, SendError> {
-        let (report_sender, report_receiver) = oneshot::channel::<()>();
+        self.send_query_with_preferred_peer(query, None).await
+    }
+
+    /// Send a query that should preferably be answered by the peer that sent us the given
+    /// broadcasted message. Unlike the message's originator, that peer was connected to us when the
+    /// message arrived. If it can't be queried, e.g., since it disconnected or got blocked, another
+    /// connected peer is chosen.
+    pub async fn send_new_query_to_propagation_source(
+        &mut self,
+        query: Query,
+        broadcasted_message_metadata: &BroadcastedMessageMetadata,
+    ) -> Result<ClientResponsesManager<Response>, SendError> {
+        let preferred_peer = broadcasted_message_metadata.propagation_source.private_get_peer_id();
+        self.send_query_with_preferred_peer(query, Some(preferred_peer)).await
+    }
+
+    async fn send_query_with_preferred_peer(
+        &mut self,
+        query: Query,
+        preferred_peer: Option<PeerId>,
+    ) -> Result<ClientResponsesManager<Response>, SendError> {
+        let (report_sender, report_receiver) = oneshot::channel::<ReputationModifier>();
         let (responses_sender, responses_receiver) =
             futures::channel::mpsc::channel(self.buffer_size);
         let responses_receiver = Box::new(responses_receiver);
         let query = Bytes::from(query);
         let responses_sender =
             Box::new(responses_sender.with(|response| ready(Ok(Response::try_from(response)))));
-        let payload = SqmrClientPayload { query, report_receiver, responses_sender };
+        let payload =
+            SqmrClientPayload { query, report_receiver, responses_sender, preferred_peer };
         self.sender.send(payload).await?;
         Ok(ClientResponsesManager { report_sender, responses_receiver })
     }
@@ -739,10 +1098,10 @@ pub struct ClientResponsesManager<Response: TryFrom<Bytes>> {
 }
 
 impl<Response: TryFrom<Bytes>> ClientResponsesManager<Response> {
-    /// Use this function to report peer as malicious
-    pub fn report_peer(self) {
+    /// Use this function to report the peer that sent the responses for misbehaving.
+    pub fn report_peer(self, reason: ReputationModifier) {
         warn!("Reporting peer");
-        if let Err(e) = self.report_sender.send(()) {
+        if let Err(e) = self.report_sender.send(reason) {
             error!("Failed to report peer. Error: {e:?}");
         }
     }
@@ -762,6 +1121,7 @@ pub struct SqmrClientPayload {
     query: Bytes,
     report_receiver: ReportReceiver,
     responses_sender: ResponsesSender,
+    preferred_peer: Option<PeerId>,
 }
 
 pub struct SqmrServerReceiver<Query, Response>
@@ -803,9 +1163,9 @@ where
         &self.query
     }
 
-    pub fn report_peer(self) {
+    pub fn report_peer(self, reason: ReputationModifier) {
         debug!("Reporting peer from server to network");
-        if let Err(e) = self.report_sender.send(()) {
+        if let Err(e) = self.report_sender.send(reason) {
             error!("Failed to report peer. Error: {e:?}");
         }
     }
@@ -853,6 +1213,7 @@ pub trait BroadcastTopicClientTrait<T> {
     async fn report_peer(
         &mut self,
         broadcasted_message_metadata: BroadcastedMessageMetadata,
+        reason: ReputationModifier,
     ) -> Result<(), SendError>;
     async fn continue_propagation(
         &mut self,
@@ -863,7 +1224,7 @@ pub trait BroadcastTopicClientTrait<T> {
 #[derive(Clone)]
 pub struct BroadcastTopicClient<T: TryFrom<Bytes>> {
     messages_to_broadcast_sender: BroadcastTopicSender<T, Bytes>,
-    reported_messages_sender: BroadcastTopicSender<BroadcastedMessageMetadata, PeerId>,
+    reported_messages_sender: ReportedMessagesSender,
     continue_propagation_sender: Sender<BroadcastedMessageMetadata>,
 }
 
@@ -871,7 +1232,7 @@ impl<T: TryFrom<Bytes>> BroadcastTopicClient<T> {
     // TODO(matan): Remove once consensus_manager no longer needs to build fake channels.
     pub fn new(
         messages_to_broadcast_sender: BroadcastTopicSender<T, Bytes>,
-        reported_messages_sender: BroadcastTopicSender<BroadcastedMessageMetadata, PeerId>,
+        reported_messages_sender: ReportedMessagesSender,
         continue_propagation_sender: Sender<BroadcastedMessageMetadata>,
     ) -> Self {
         BroadcastTopicClient {
@@ -891,8 +1252,9 @@ impl<T: TryFrom<Bytes> + Send> BroadcastTopicClientTrait<T> for BroadcastTopicCl
     async fn report_peer(
         &mut self,
         broadcasted_message_metadata: BroadcastedMessageMetadata,
+        reason: ReputationModifier,
     ) -> Result<(), SendError> {
-        self.reported_messages_sender.send(broadcasted_message_metadata).await
+        self.reported_messages_sender.send((broadcasted_message_metadata, reason)).await
     }
 
     async fn continue_propagation(
@@ -903,6 +1265,13 @@ impl<T: TryFrom<Bytes> + Send> BroadcastTopicClientTrait<T> for BroadcastTopicCl
     }
 }
 
+/// A broadcasted message reported by the user along with the reason it was reported for.
+pub type ReportedMessage = (BroadcastedMessageMetadata, ReputationModifier);
+/// The peer that originated a reported message along with the reason it was reported for.
+pub type ReportedPeer = (PeerId, ReputationModifier);
+
+pub type ReportedMessagesSender = BroadcastTopicSender<ReportedMessage, ReportedPeer>;
+
 pub type BroadcastTopicSender<T, Message> = With<
     Sender<Message>,
     Message,
@@ -1,5 +1,5 @@
 use futures::stream::Stream;
-use libp2p::gossipsub::{SubscriptionError, TopicHash};
+use libp2p::gossipsub::{MessageAcceptance, MessageId, SubscriptionError, TopicHash};
 use libp2p::swarm::dial_opts::DialOpts;
 use libp2p::swarm::{DialError, NetworkBehaviour, SwarmEvent};
 use libp2p::{Multiaddr, PeerId, StreamProtocol, Swarm};
@@ -8,7 +8,7 @@ use tracing::{info, warn};
 use super::BroadcastedMessageMetadata;
 use crate::gossipsub_impl::Topic;
 use crate::mixed_behaviour;
-use crate::peer_manager::{ReputationModifier, MALICIOUS};
+use crate::peer_manager::ReputationModifier;
 use crate::sqmr::behaviour::{PeerNotConnected, SessionIdNotFoundError};
 use crate::sqmr::{Bytes, InboundSessionId, OutboundSessionId, SessionId};
 
@@ -21,11 +21,13 @@ pub trait SwarmTrait: Stream<Item = Event> + Unpin {
         inbound_session_id: InboundSessionId,
     ) -> Result<(), SessionIdNotFoundError>;
 
+    /// Sends a query given in each version of its protocol, ordered from the most preferred version
+    /// to the least preferred one. If a preferred peer is given, the query is sent to it if
+    /// possible.
     fn send_query(
         &mut self,
-        query: Vec<u8>,
-        peer_id: PeerId,
-        protocol: StreamProtocol,
+        queries: Vec<(StreamProtocol, Bytes)>,
+        preferred_peer: Option<PeerId>,
     ) -> Result<OutboundSessionId, PeerNotConnected>;
 
     fn dial(&mut self, peer_multiaddr: Multiaddr) -> Result<(), DialError>;
@@ -37,6 +39,10 @@ pub trait SwarmTrait: Stream<Item = Event> + Unpin {
         session_id: InboundSessionId,
     ) -> Result<(), SessionIdNotFoundError>;
 
+    /// Drops the session without finishing it, so the other peer sees it as failed. No events
+    /// are emitted for the session afterwards.
+    fn drop_session(&mut self, session_id: SessionId) -> Result<(), SessionIdNotFoundError>;
+
     fn behaviour_mut(&mut self) -> &mut mixed_behaviour::MixedBehaviour;
 
     fn get_peer_id_from_session_id(
@@ -50,12 +56,24 @@ pub trait SwarmTrait: Stream<Item = Event> + Unpin {
 
     fn broadcast_message(&mut self, message: Bytes, topic_hash: TopicHash);
 
-    // TODO: change this to report_peer and add an argument for the score.
-    fn report_peer_as_malicious(&mut self, peer_id: PeerId);
+    fn report_peer(&mut self, peer_id: PeerId, reason: ReputationModifier);
+
+    fn report_session(
+        &mut self,
+        outbound_session_id: OutboundSessionId,
+        reason: ReputationModifier,
+    );
 
     fn add_new_supported_inbound_protocol(&mut self, protocol_name: StreamProtocol);
 
     fn continue_propagation(&mut self, message_metadata: BroadcastedMessageMetadata);
+
+    fn report_message_validation_result(
+        &mut self,
+        message_id: &MessageId,
+        propagation_source: &PeerId,
+        acceptance: MessageAcceptance,
+    );
 }
 
 impl SwarmTrait for Swarm<mixed_behaviour::MixedBehaviour> {
@@ -67,14 +85,12 @@ impl SwarmTrait for Swarm<mixed_behaviour::MixedBehaviour> {
         self.behaviour_mut().sqmr.send_response(response, inbound_session_id)
     }
 
-    // TODO: change this function signature
     fn send_query(
         &mut self,
-        query: Vec<u8>,
-        _peer_id: PeerId,
-        protocol: StreamProtocol,
+        queries: Vec<(StreamProtocol, Bytes)>,
+        preferred_peer: Option<PeerId>,
     ) -> Result<OutboundSessionId, PeerNotConnected> {
-        Ok(self.behaviour_mut().sqmr.start_query(query, protocol))
+        Ok(self.behaviour_mut().sqmr.start_query(queries, preferred_peer))
     }
 
     fn dial(&mut self, peer_multiaddr: Multiaddr) -> Result<(), DialError> {
@@ -91,6 +107,10 @@ impl SwarmTrait for Swarm<mixed_behaviour::MixedBehaviour> {
         self.behaviour_mut().sqmr.close_inbound_session(session_id)
     }
 
+    fn drop_session(&mut self, session_id: SessionId) -> Result<(), SessionIdNotFoundError> {
+        self.behaviour_mut().sqmr.drop_session(session_id)
+    }
+
     fn behaviour_mut(&mut self) -> &mut mixed_behaviour::MixedBehaviour {
         self.behaviour_mut()
     }
@@ -126,11 +146,16 @@ impl SwarmTrait for Swarm<mixed_behaviour::MixedBehaviour> {
         }
     }
 
-    fn report_peer_as_malicious(&mut self, peer_id: PeerId) {
-        let _ = self
-            .behaviour_mut()
-            .peer_manager
-            .report_peer(peer_id, ReputationModifier::Misconduct { misconduct_score: MALICIOUS });
+    fn report_peer(&mut self, peer_id: PeerId, reason: ReputationModifier) {
+        let _ = self.behaviour_mut().peer_manager.report_peer(peer_id, reason);
+    }
+
+    fn report_session(
+        &mut self,
+        outbound_session_id: OutboundSessionId,
+        reason: ReputationModifier,
+    ) {
+        let _ = self.behaviour_mut().peer_manager.report_session(outbound_session_id, reason);
     }
 
     fn add_new_supported_inbound_protocol(&mut self, protocol: StreamProtocol) {
@@ -139,4 +164,18 @@ impl SwarmTrait for Swarm<mixed_behaviour::MixedBehaviour> {
 
     // TODO(shahak): Implement this function.
     fn continue_propagation(&mut self, _message_metadata: BroadcastedMessageMetadata) {}
+
+    fn report_message_validation_result(
+        &mut self,
+        message_id: &MessageId,
+        propagation_source: &PeerId,
+        acceptance: MessageAcceptance,
+    ) {
+        // Returns an error only when accepting a message fails to be forwarded.
+        let _ = self.behaviour_mut().gossipsub.report_message_validation_result(
+            message_id,
+            propagation_source,
+            acceptance,
+        );
+    }
 }
@@ -5,6 +5,7 @@ use std::task::{Context, Poll};
 use std::time::Duration;
 use std::vec;
 
+use assert_matches::assert_matches;
 use deadqueue::unlimited::Queue;
 use futures::channel::mpsc::{unbounded, UnboundedSender};
 use futures::channel::oneshot;
@@ -13,7 +14,7 @@ use futures::stream::Stream;
 use futures::{pin_mut, Future, SinkExt, StreamExt};
 use lazy_static::lazy_static;
 use libp2p::core::ConnectedPoint;
-use libp2p::gossipsub::{SubscriptionError, TopicHash};
+use libp2p::gossipsub::{MessageAcceptance, MessageId, SubscriptionError, TopicHash};
 use libp2p::swarm::ConnectionId;
 use libp2p::{Multiaddr, PeerId, StreamProtocol};
 use tokio::select;
@@ -21,12 +22,19 @@ use tokio::sync::Mutex;
 use tokio::time::sleep;
 
 use super::swarm_trait::{Event, SwarmTrait};
-use super::{BroadcastTopicChannels, GenericNetworkManager};
+use super::{
+    BroadcastTopicChannels,
+    GenericNetworkManager,
+    SqmrProtocolVersion,
+    SqmrVersionConverter,
+};
 use crate::gossipsub_impl::{self, Topic};
+use crate::limits::{InboundRateLimitsConfig, RateLimitConfig};
 use crate::mixed_behaviour;
 use crate::network_manager::{BroadcastTopicClientTrait, ServerQueryManager};
-use crate::sqmr::behaviour::{PeerNotConnected, SessionIdNotFoundError};
-use crate::sqmr::{Bytes, GenericEvent, InboundSessionId, OutboundSessionId};
+use crate::peer_manager::{ReputationModifier, UNRESPONSIVE};
+use crate::sqmr::behaviour::{PeerNotConnected, SessionError, SessionIdNotFoundError};
+use crate::sqmr::{Bytes, GenericEvent, InboundSessionId, OutboundSessionId, SessionId};
 
 const TIMEOUT: Duration = Duration::from_secs(1);
 
@@ -41,9 +49,16 @@ struct MockSwarm {
     pub pending_events: Queue<Event>,
     pub subscribed_topics: HashSet<TopicHash>,
     broadcasted_messages_senders: Vec<UnboundedSender<(Bytes, TopicHash)>>,
-    reported_peer_senders: Vec<UnboundedSender<PeerId>>,
+    reported_peer_senders: Vec<UnboundedSender<(PeerId, ReputationModifier)>>,
+    reported_session_senders: Vec<UnboundedSender<(OutboundSessionId, ReputationModifier)>>,
+    // MessageAcceptance isn't Clone, so the results can be sent to a single stream.
+    dropped_session_senders: Vec<UnboundedSender<SessionId>>,
+    message_validation_result_sender:
+        Option<UnboundedSender<(MessageId, PeerId, MessageAcceptance)>>,
     supported_inbound_protocols_senders: Vec<UnboundedSender<StreamProtocol>>,
     inbound_session_id_to_response_sender: HashMap<InboundSessionId, UnboundedSender<Bytes>>,
+    // Protocols the simulated remote peers don't support when negotiating an outbound session.
+    pub unsupported_outbound_protocols: HashSet<StreamProtocol>,
     next_outbound_session_id: usize,
     first_polled_event_notifier: Option<oneshot::Sender<()>>,
 }
@@ -89,12 +104,36 @@ impl MockSwarm {
         receiver
     }
 
-    pub fn get_reported_peers_stream(&mut self) -> impl Stream<Item = PeerId> {
+    pub fn get_reported_peers_stream(
+        &mut self,
+    ) -> impl Stream<Item = (PeerId, ReputationModifier)> {
         let (sender, receiver) = unbounded();
         self.reported_peer_senders.push(sender);
         receiver
     }
 
+    pub fn get_reported_sessions_stream(
+        &mut self,
+    ) -> impl Stream<Item = (OutboundSessionId, ReputationModifier)> {
+        let (sender, receiver) = unbounded();
+        self.reported_session_senders.push(sender);
+        receiver
+    }
+
+    pub fn get_message_validation_results_stream(
+        &mut self,
+    ) -> impl Stream<Item = (MessageId, PeerId, MessageAcceptance)> {
+        let (sender, receiver) = unbounded();
+        self.message_validation_result_sender = Some(sender);
+        receiver
+    }
+
+    pub fn get_dropped_sessions_stream(&mut self) -> impl Stream<Item = SessionId> {
+        let (sender, receiver) = unbounded();
+        self.dropped_session_senders.push(sender);
+        receiver
+    }
+
     pub fn get_supported_inbound_protocol(&mut self) -> impl Stream<Item = StreamProtocol> {
         let (sender, receiver) = unbounded();
         self.supported_inbound_protocols_senders.push(sender);
@@ -106,6 +145,7 @@ impl MockSwarm {
         query: Vec<u8>,
         outbound_session_id: OutboundSessionId,
         peer_id: PeerId,
+        protocol_name: StreamProtocol,
     ) {
         for response in query {
             self.pending_events.push(Event::Behaviour(mixed_behaviour::Event::ExternalEvent(
@@ -113,6 +153,7 @@ impl MockSwarm {
                     response: vec![response],
                     outbound_session_id,
                     peer_id,
+                    protocol_name: protocol_name.clone(),
                 }),
             )));
         }
@@ -135,15 +176,20 @@ impl SwarmTrait for MockSwarm {
 
     fn send_query(
         &mut self,
-        query: Vec<u8>,
-        peer_id: PeerId,
-        _protocol: StreamProtocol,
+        queries: Vec<(StreamProtocol, Bytes)>,
+        preferred_peer: Option<PeerId>,
     ) -> Result<OutboundSessionId, PeerNotConnected> {
+        let peer_id = preferred_peer.unwrap_or_else(PeerId::random);
         let outbound_session_id = OutboundSessionId { value: self.next_outbound_session_id };
+        let (protocol_name, query) = queries
+            .into_iter()
+            .find(|(protocol_name, _)| !self.unsupported_outbound_protocols.contains(protocol_name))
+            .expect("None of the protocols of the query are supported");
         self.create_response_events_for_query_each_num_becomes_response(
             query,
             outbound_session_id,
             peer_id,
+            protocol_name,
         );
         self.next_outbound_session_id += 1;
         Ok(outbound_session_id)
@@ -168,6 +214,13 @@ impl SwarmTrait for MockSwarm {
         Ok(())
     }
 
+    fn drop_session(&mut self, session_id: SessionId) -> Result<(), SessionIdNotFoundError> {
+        for sender in &self.dropped_session_senders {
+            sender.unbounded_send(session_id).unwrap();
+        }
+        Ok(())
+    }
+
     fn behaviour_mut(&mut self) -> &mut mixed_behaviour::MixedBehaviour {
         unimplemented!()
     }
@@ -185,9 +238,19 @@ impl SwarmTrait for MockSwarm {
         }
     }
 
-    fn report_peer_as_malicious(&mut self, peer_id: PeerId) {
+    fn report_peer(&mut self, peer_id: PeerId, reason: ReputationModifier) {
         for sender in &self.reported_peer_senders {
-            sender.unbounded_send(peer_id).unwrap();
+            sender.unbounded_send((peer_id, reason)).unwrap();
+        }
+    }
+
+    fn report_session(
+        &mut self,
+        outbound_session_id: OutboundSessionId,
+        reason: ReputationModifier,
+    ) {
+        for sender in &self.reported_session_senders {
+            sender.unbounded_send((outbound_session_id, reason)).unwrap();
         }
     }
     fn add_new_supported_inbound_protocol(&mut self, protocol_name: StreamProtocol) {
@@ -207,10 +270,43 @@ impl SwarmTrait for MockSwarm {
     fn continue_propagation(&mut self, _message_metadata: super::BroadcastedMessageMetadata) {
         unimplemented!()
     }
+
+    fn report_message_validation_result(
+        &mut self,
+        message_id: &MessageId,
+        propagation_source: &PeerId,
+        acceptance: MessageAcceptance,
+    ) {
+        if let Some(sender) = &self.message_validation_result_sender {
+            sender.unbounded_send((message_id.clone(), *propagation_source, acceptance)).unwrap();
+        }
+    }
 }
 
 const BUFFER_SIZE: usize = 100;
 const SIGNED_BLOCK_HEADER_PROTOCOL: StreamProtocol = StreamProtocol::new("/starknet/headers/1");
+const SIGNED_BLOCK_HEADER_PROTOCOL_V2: StreamProtocol = StreamProtocol::new("/starknet/headers/2");
+
+// Simulates the messages of /starknet/headers/1 being encoded differently than the messages of
+// /starknet/headers/2.
+const V1_CONVERTER: SqmrVersionConverter = SqmrVersionConverter {
+    query_into_version: |query| Some(query.into_iter().rev().collect()),
+    query_from_version: |query| Some(query.into_iter().rev().collect()),
+    response_into_version: |response| Some(response.into_iter().map(|byte| byte + 100).collect()),
+    response_from_version: |response| {
+        response.into_iter().map(|byte| byte.checked_sub(100)).collect()
+    },
+};
+
+fn get_versions() -> Vec<SqmrProtocolVersion> {
+    vec![
+        SqmrProtocolVersion::latest(SIGNED_BLOCK_HEADER_PROTOCOL_V2.to_string()),
+        SqmrProtocolVersion {
+            protocol: SIGNED_BLOCK_HEADER_PROTOCOL.to_string(),
+            converter: V1_CONVERTER,
+        },
+    ]
+}
 
 #[tokio::test]
 async fn register_sqmr_protocol_client_and_use_channels() {
@@ -222,7 +318,11 @@ async fn register_sqmr_protocol_client_and_use_channels() {
     mock_swarm.first_polled_event_notifier = Some(event_notifier);
 
     // network manager to register subscriber
-    let mut network_manager = GenericNetworkManager::generic_new(mock_swarm, None);
+    let mut network_manager = GenericNetworkManager::generic_new(
+        mock_swarm,
+        Vec::new(),
+        InboundRateLimitsConfig::default(),
+    );
 
     // register subscriber and send payload
     let mut payload_sender = network_manager.register_sqmr_protocol_client::<Vec<u8>, Vec<u8>>(
@@ -284,7 +384,11 @@ async fn process_incoming_query() {
     let get_responses_fut = mock_swarm.get_responses_sent_to_inbound_session(inbound_session_id);
     let mut get_supported_inbound_protocol_fut = mock_swarm.get_supported_inbound_protocol();
 
-    let mut network_manager = GenericNetworkManager::generic_new(mock_swarm, None);
+    let mut network_manager = GenericNetworkManager::generic_new(
+        mock_swarm,
+        Vec::new(),
+        InboundRateLimitsConfig::default(),
+    );
 
     let mut inbound_payload_receiver = network_manager
         .register_sqmr_protocol_server::<Vec<u8>, Vec<u8>>(protocol.to_string(), BUFFER_SIZE);
@@ -320,7 +424,11 @@ async fn broadcast_message() {
     let mut mock_swarm = MockSwarm::default();
     let mut messages_we_broadcasted_stream = mock_swarm.stream_messages_we_broadcasted();
 
-    let mut network_manager = GenericNetworkManager::generic_new(mock_swarm, None);
+    let mut network_manager = GenericNetworkManager::generic_new(
+        mock_swarm,
+        Vec::new(),
+        InboundRateLimitsConfig::default(),
+    );
 
     let mut broadcast_topic_client = network_manager
         .register_broadcast_topic(topic.clone(), BUFFER_SIZE)
@@ -345,18 +453,25 @@ async fn receive_broadcasted_message_and_report_it() {
     let topic = Topic::new("TOPIC");
     let message = vec![1u8, 2u8, 3u8];
     let originated_peer_id = PeerId::random();
+    let reason = ReputationModifier::Misconduct { misconduct_score: UNRESPONSIVE };
 
     let mut mock_swarm = MockSwarm::default();
     mock_swarm.pending_events.push(Event::Behaviour(mixed_behaviour::Event::ExternalEvent(
         mixed_behaviour::ExternalEvent::GossipSub(gossipsub_impl::ExternalEvent::Received {
             originated_peer_id,
+            propagation_source: originated_peer_id,
+            message_id: MessageId::new(&message),
             message: message.clone(),
             topic_hash: topic.hash(),
         }),
     )));
     let mut reported_peer_receiver = mock_swarm.get_reported_peers_stream();
 
-    let mut network_manager = GenericNetworkManager::generic_new(mock_swarm, None);
+    let mut network_manager = GenericNetworkManager::generic_new(
+        mock_swarm,
+        Vec::new(),
+        InboundRateLimitsConfig::default(),
+    );
 
     let BroadcastTopicChannels {
         mut broadcast_topic_client,
@@ -372,10 +487,16 @@ async fn receive_broadcasted_message_and_report_it() {
             let result = broadcasted_messages_receiver.next().await;
             let (message_result, broadcasted_message_metadata) = result.unwrap();
             assert_eq!(message, message_result.unwrap());
-            broadcast_topic_client.report_peer(broadcasted_message_metadata).await.unwrap();
+            broadcast_topic_client
+                .report_peer(broadcasted_message_metadata, reason)
+                .await
+                .unwrap();
             reported_peer_receiver.next().await
         }) => {
-            assert_eq!(originated_peer_id, reported_peer_result.unwrap().unwrap());
+            assert_eq!(
+                (originated_peer_id, reason),
+                reported_peer_result.unwrap().unwrap()
+            );
         }
     }
 }
@@ -393,3 +514,219 @@ fn get_test_connection_established_event(mock_peer_id: PeerId) -> Event {
         established_in: Duration::from_secs(0),
     }
 }
+
+#[tokio::test]
+async fn timed_out_outbound_session_is_reported() {
+    let outbound_session_id = OutboundSessionId { value: 0 };
+
+    let mut mock_swarm = MockSwarm::default();
+    mock_swarm.pending_events.push(Event::Behaviour(mixed_behaviour::Event::ExternalEvent(
+        mixed_behaviour::ExternalEvent::Sqmr(GenericEvent::SessionFailed {
+            session_id: outbound_session_id.into(),
+            error: SessionError::Timeout { session_timeout: TIMEOUT },
+        }),
+    )));
+    let mut reported_sessions_stream = mock_swarm.get_reported_sessions_stream();
+
+    let network_manager = GenericNetworkManager::generic_new(
+        mock_swarm,
+        Vec::new(),
+        InboundRateLimitsConfig::default(),
+    );
+
+    tokio::select! {
+        _ = network_manager.run() => panic!("network manager ended"),
+        result = tokio::time::timeout(TIMEOUT, reported_sessions_stream.next()) => {
+            assert_eq!(
+                result.unwrap().unwrap(),
+                (
+                    outbound_session_id,
+                    ReputationModifier::Misconduct { misconduct_score: UNRESPONSIVE }
+                )
+            );
+        }
+    }
+}
+
+#[tokio::test]
+async fn rate_limited_inbound_query_is_dropped() {
+    let protocol: StreamProtocol = SIGNED_BLOCK_HEADER_PROTOCOL;
+    let peer_id = PeerId::random();
+    let allowed_inbound_session_id = InboundSessionId { value: 0 };
+    let rate_limited_inbound_session_id = InboundSessionId { value: 1 };
+
+    let mut mock_swarm = MockSwarm::default();
+    for inbound_session_id in [allowed_inbound_session_id, rate_limited_inbound_session_id] {
+        mock_swarm.pending_events.push(Event::Behaviour(mixed_behaviour::Event::ExternalEvent(
+            mixed_behaviour::ExternalEvent::Sqmr(GenericEvent::NewInboundSession {
+                query: VEC1.clone(),
+                inbound_session_id,
+                peer_id,
+                protocol_name: protocol.clone(),
+            }),
+        )));
+    }
+    let mut dropped_sessions = mock_swarm.get_dropped_sessions_stream();
+
+    let inbound_rate_limits_config = InboundRateLimitsConfig {
+        queries: RateLimitConfig { capacity: 1, refill_per_second: 0 },
+        ..Default::default()
+    };
+    let mut network_manager =
+        GenericNetworkManager::generic_new(mock_swarm, Vec::new(), inbound_rate_limits_config);
+    let mut inbound_payload_receiver = network_manager
+        .register_sqmr_protocol_server::<Vec<u8>, Vec<u8>>(protocol.to_string(), BUFFER_SIZE);
+
+    tokio::select! {
+        _ = network_manager.run() => panic!("network manager ended"),
+        result = tokio::time::timeout(TIMEOUT, async {
+            // The rate limited session is dropped so that the peer sees it as failed.
+            assert_eq!(
+                dropped_sessions.next().await.unwrap(),
+                SessionId::from(rate_limited_inbound_session_id)
+            );
+            inbound_payload_receiver.next().await.unwrap()
+        }) => {
+            let server_query_manager = result.unwrap();
+            assert_eq!(server_query_manager.query().as_ref().unwrap(), &*VEC1);
+            assert!(inbound_payload_receiver.next().now_or_never().flatten().is_none());
+        }
+    }
+}
+
+#[tokio::test]
+async fn rate_limited_broadcasted_message_is_ignored() {
+    let topic = Topic::new("TOPIC");
+    let originated_peer_id = PeerId::random();
+    let propagation_source = PeerId::random();
+    let allowed_message = VEC1.clone();
+    let rate_limited_message = VEC2.clone();
+
+    let mut mock_swarm = MockSwarm::default();
+    for message in [allowed_message.clone(), rate_limited_message.clone()] {
+        mock_swarm.pending_events.push(Event::Behaviour(mixed_behaviour::Event::ExternalEvent(
+            mixed_behaviour::ExternalEvent::GossipSub(gossipsub_impl::ExternalEvent::Received {
+                originated_peer_id,
+                propagation_source,
+                message_id: MessageId::new(&message),
+                message,
+                topic_hash: topic.hash(),
+            }),
+        )));
+    }
+    let mut message_validation_results = mock_swarm.get_message_validation_results_stream();
+
+    let inbound_rate_limits_config = InboundRateLimitsConfig {
+        broadcasted_messages: RateLimitConfig { capacity: 1, refill_per_second: 0 },
+        ..Default::default()
+    };
+    let mut network_manager =
+        GenericNetworkManager::generic_new(mock_swarm, Vec::new(), inbound_rate_limits_config);
+    // The client is kept so that the network manager doesn't see the channels as terminated.
+    let BroadcastTopicChannels {
+        mut broadcasted_messages_receiver,
+        broadcast_topic_client: _client,
+    } = network_manager.register_broadcast_topic::<Bytes>(topic.clone(), BUFFER_SIZE).unwrap();
+
+    tokio::select! {
+        _ = network_manager.run() => panic!("network manager ended"),
+        result = tokio::time::timeout(TIMEOUT, async {
+            let (message_result, metadata) = broadcasted_messages_receiver.next().await.unwrap();
+            assert_eq!(message_result.unwrap(), allowed_message);
+            assert_eq!(metadata.originator_id.private_get_peer_id(), originated_peer_id);
+            assert_eq!(metadata.propagation_source.private_get_peer_id(), propagation_source);
+            let allowed_message_validation_result =
+                message_validation_results.next().await.unwrap();
+            let rate_limited_message_validation_result =
+                message_validation_results.next().await.unwrap();
+            assert!(broadcasted_messages_receiver.next().now_or_never().is_none());
+            (allowed_message_validation_result, rate_limited_message_validation_result)
+        }) => {
+            let (allowed_message_validation_result, rate_limited_message_validation_result) =
+                result.unwrap();
+            let (message_id, source, acceptance) = allowed_message_validation_result;
+            assert_eq!(message_id, MessageId::new(&allowed_message));
+            assert_eq!(source, propagation_source);
+            assert_matches!(acceptance, MessageAcceptance::Accept);
+            let (message_id, source, acceptance) = rate_limited_message_validation_result;
+            assert_eq!(message_id, MessageId::new(&rate_limited_message));
+            assert_eq!(source, propagation_source);
+            assert_matches!(acceptance, MessageAcceptance::Ignore);
+        }
+    }
+}
+
+#[tokio::test]
+async fn versioned_client_uses_negotiated_version() {
+    let mut mock_swarm = MockSwarm::default();
+    mock_swarm.unsupported_outbound_protocols.insert(SIGNED_BLOCK_HEADER_PROTOCOL_V2);
+
+    let mut network_manager = GenericNetworkManager::generic_new(
+        mock_swarm,
+        Vec::new(),
+        InboundRateLimitsConfig::default(),
+    );
+    let mut payload_sender = network_manager
+        .register_versioned_sqmr_protocol_client::<Vec<u8>, Vec<u8>>(get_versions(), BUFFER_SIZE);
+
+    tokio::select! {
+        _ = network_manager.run() => panic!("network manager ended"),
+        result = tokio::time::timeout(TIMEOUT, async {
+            let client_responses_manager =
+                payload_sender.send_new_query(vec![101, 102, 103]).await.unwrap();
+            client_responses_manager
+                .take(3)
+                .map(|response| response.unwrap())
+                .collect::<Vec<_>>()
+                .await
+        }) => {
+            // The mock swarm responds with each byte of the query it received, which was converted
+            // to the first version, and the responses are converted back from it.
+            assert_eq!(result.unwrap(), vec![vec![3], vec![2], vec![1]]);
+        }
+    }
+}
+
+#[tokio::test]
+async fn versioned_server_converts_messages_of_older_version() {
+    let inbound_session_id = InboundSessionId { value: 0 };
+    let mut mock_swarm = MockSwarm::default();
+    mock_swarm.pending_events.push(Event::Behaviour(mixed_behaviour::Event::ExternalEvent(
+        mixed_behaviour::ExternalEvent::Sqmr(GenericEvent::NewInboundSession {
+            query: vec![1, 2, 3],
+            inbound_session_id,
+            peer_id: PeerId::random(),
+            protocol_name: SIGNED_BLOCK_HEADER_PROTOCOL,
+        }),
+    )));
+    let get_responses_fut = mock_swarm.get_responses_sent_to_inbound_session(inbound_session_id);
+    let mut get_supported_inbound_protocol_fut = mock_swarm.get_supported_inbound_protocol();
+
+    let mut network_manager = GenericNetworkManager::generic_new(
+        mock_swarm,
+        Vec::new(),
+        InboundRateLimitsConfig::default(),
+    );
+    let mut inbound_payload_receiver = network_manager
+        .register_versioned_sqmr_protocol_server::<Vec<u8>, Vec<u8>>(get_versions(), BUFFER_SIZE);
+
+    let supported_inbound_protocols =
+        get_supported_inbound_protocol_fut.by_ref().take(2).collect::<Vec<_>>().await;
+    assert_eq!(
+        supported_inbound_protocols,
+        vec![SIGNED_BLOCK_HEADER_PROTOCOL_V2, SIGNED_BLOCK_HEADER_PROTOCOL]
+    );
+
+    tokio::select! {
+        _ = network_manager.run() => panic!("network manager ended"),
+        result = tokio::time::timeout(TIMEOUT, async {
+            let mut server_query_manager = inbound_payload_receiver.next().await.unwrap();
+            assert_eq!(server_query_manager.query().as_ref().unwrap(), &vec![3, 2, 1]);
+            server_query_manager.send_response(vec![1, 2]).await.unwrap();
+            drop(server_query_manager);
+            get_responses_fut.await
+        }) => {
+            assert_eq!(result.unwrap(), vec![vec![101, 102]]);
+        }
+    }
+}
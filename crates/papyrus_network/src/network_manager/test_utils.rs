@@ -19,6 +19,9 @@ use super::{
     GenericReceiver,
     NetworkManager,
     ReportReceiver,
+    ReportedMessage,
+    ReportedPeer,
+    ReputationModifier,
     ServerQueryManager,
     ServerResponsesSender,
     SqmrClientPayload,
@@ -73,7 +76,7 @@ where
     Query: TryFrom<Bytes>,
     Response: Send + 'static,
 {
-    let (report_sender, report_receiver) = oneshot::channel::<()>();
+    let (report_sender, report_receiver) = oneshot::channel::<ReputationModifier>();
     let (responses_sender, responses_receiver) = futures::channel::mpsc::channel::<Response>(1);
     let responses_sender = ServerResponsesSender { sender: Box::new(responses_sender) };
     let responses_receiver = Box::new(responses_receiver);
@@ -106,9 +109,9 @@ where
 
     let (reported_messages_sender, mock_reported_messages_receiver) =
         futures::channel::mpsc::channel(CHANNEL_BUFFER_SIZE);
-    let reported_messages_fn: fn(BroadcastedMessageMetadata) -> Ready<Result<PeerId, SendError>> =
-        |broadcasted_message_metadata| {
-            ready(Ok(broadcasted_message_metadata.originator_id.private_get_peer_id()))
+    let reported_messages_fn: fn(ReportedMessage) -> Ready<Result<ReportedPeer, SendError>> =
+        |(broadcasted_message_metadata, reason)| {
+            ready(Ok((broadcasted_message_metadata.originator_id.private_get_peer_id(), reason)))
         };
     let reported_messages_sender = reported_messages_sender.with(reported_messages_fn);
 
@@ -149,22 +152,27 @@ where
 }
 
 pub fn create_connected_network_configs(n: usize) -> Vec<NetworkConfig> {
-    let mut ports = find_n_free_ports(n);
+    // Each config listens on both a TCP and a QUIC port.
+    let mut ports = find_n_free_ports(2 * n);
+    let mut quic_ports = ports.split_off(n);
     let port0 = ports.remove(0);
+    let quic_port0 = quic_ports.remove(0);
 
     let secret_key0 = [1u8; 32];
     let public_key0 = Keypair::ed25519_from_bytes(secret_key0).unwrap().public();
 
     let config0 = NetworkConfig {
         tcp_port: port0,
+        quic_port: quic_port0,
         secret_key: Some(secret_key0.to_vec()),
         ..Default::default()
     };
     let mut configs = Vec::with_capacity(n);
     configs.push(config0);
-    for port in ports.iter() {
+    for (port, quic_port) in ports.into_iter().zip(quic_ports) {
         configs.push(NetworkConfig {
-            tcp_port: *port,
+            tcp_port: port,
+            quic_port,
             bootstrap_peer_multiaddr: Some(
                 Multiaddr::empty()
                     .with(Protocol::Ip4(Ipv4Addr::LOCALHOST))
@@ -210,8 +218,9 @@ impl<Query: TryFrom<Bytes>, Response: TryFrom<Bytes>> MockClientResponsesManager
         &self.query
     }
 
-    pub async fn assert_reported(self, timeout: Duration) {
-        tokio::time::timeout(timeout, self.report_receiver).await.unwrap().unwrap();
+    /// Asserts that the peer was reported and returns the reason it was reported for.
+    pub async fn assert_reported(self, timeout: Duration) -> ReputationModifier {
+        tokio::time::timeout(timeout, self.report_receiver).await.unwrap().unwrap()
     }
 
     pub async fn send_response(&mut self, response: Response) -> Result<(), SendError> {
@@ -227,7 +236,7 @@ where
     Bytes: From<Response>,
 {
     fn from(payload: SqmrClientPayload) -> Self {
-        let SqmrClientPayload { query, report_receiver, responses_sender } = payload;
+        let SqmrClientPayload { query, report_receiver, responses_sender, .. } = payload;
         let query = Query::try_from(query);
         let responses_sender =
             Box::new(responses_sender.with(|response: Response| ready(Ok(Bytes::from(response)))));
@@ -257,7 +266,7 @@ pub type MockMessagesToBroadcastReceiver<T> = Map<Receiver<Bytes>, fn(Bytes) ->
 pub struct BroadcastNetworkMock<T: TryFrom<Bytes>> {
     pub broadcasted_messages_sender: MockBroadcastedMessagesSender<T>,
     pub messages_to_broadcast_receiver: MockMessagesToBroadcastReceiver<T>,
-    pub reported_messages_receiver: Receiver<PeerId>,
+    pub reported_messages_receiver: Receiver<ReportedPeer>,
     pub continue_propagation_receiver: Receiver<BroadcastedMessageMetadata>,
 }
 
@@ -27,6 +27,10 @@ pub enum ToOtherBehaviourEvent {
     PeerBlacklisted {
         peer_id: PeerId,
     },
+    MisconductScoreChanged {
+        peer_id: PeerId,
+        misconduct_score: f64,
+    },
 }
 
 impl NetworkBehaviour for PeerManager {
@@ -69,10 +73,15 @@ impl NetworkBehaviour for PeerManager {
     fn handle_established_outbound_connection(
         &mut self,
         _connection_id: libp2p::swarm::ConnectionId,
-        _peer: libp2p::PeerId,
+        peer: libp2p::PeerId,
         _addr: &libp2p::Multiaddr,
         _role_override: libp2p::core::Endpoint,
     ) -> Result<libp2p::swarm::THandler<Self>, libp2p::swarm::ConnectionDenied> {
+        if self.peers.get(&peer).is_some_and(|known_peer| known_peer.is_blocked()) {
+            return Err(libp2p::swarm::ConnectionDenied::new(PeerManagerError::PeerIsBlocked(
+                peer,
+            )));
+        }
         Ok(dummy::ConnectionHandler)
     }
 
@@ -102,9 +111,13 @@ impl NetworkBehaviour for PeerManager {
                     );
                     return;
                 }
-                let res = self.report_peer(peer_id, super::ReputationModifier::Unstable);
-                if res.is_err() {
-                    warn!("Dial failure of an unknown peer. peer id: {}", peer_id)
+                // A dial denied by one of our behaviours (e.g due to connection limits) doesn't
+                // mean the peer is unstable.
+                if !matches!(error, DialError::Denied { .. }) {
+                    let res = self.report_peer(peer_id, super::ReputationModifier::Unstable);
+                    if res.is_err() {
+                        warn!("Dial failure of an unknown peer. peer id: {}", peer_id)
+                    }
                 }
                 // Re-assign a peer to the session so that a SessionAssgined Event will be emitted.
                 // TODO: test this case
@@ -179,6 +192,15 @@ impl NetworkBehaviour for PeerManager {
         if let Some(event) = self.pending_events.pop() {
             return Poll::Ready(event);
         }
+        if let Some(sleep_future) = &mut self.sleep_waiting_for_misconduct_score_update {
+            if sleep_future.as_mut().poll(cx).is_ready() {
+                self.sleep_waiting_for_misconduct_score_update = None;
+                self.update_misconduct_scores();
+                if let Some(event) = self.pending_events.pop() {
+                    return Poll::Ready(event);
+                }
+            }
+        }
         if let Some(sleep_future) = &mut self.sleep_waiting_for_unblocked_peer {
             ready!(sleep_future.as_mut().poll(cx));
             for outbound_session_id in std::mem::take(&mut self.sessions_received_when_no_peers) {
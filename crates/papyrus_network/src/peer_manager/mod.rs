@@ -1,10 +1,10 @@
-use std::collections::{BTreeMap, HashMap};
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::time::Duration;
 
 use futures::future::BoxFuture;
 use futures::FutureExt;
 use libp2p::swarm::dial_opts::DialOpts;
-use libp2p::swarm::ToSwarm;
+use libp2p::swarm::{CloseConnection, ToSwarm};
 use libp2p::PeerId;
 use papyrus_config::converters::{
     deserialize_milliseconds_to_duration,
@@ -14,7 +14,8 @@ use papyrus_config::dumping::{ser_param, SerializeConfig};
 use papyrus_config::{ParamPath, ParamPrivacyInput, SerializedParam};
 use peer::Peer;
 use serde::{Deserialize, Serialize};
-use tracing::info;
+use tracing::{debug, info};
+use validator::{Validate, ValidationError};
 
 pub use self::behaviour_impl::ToOtherBehaviourEvent;
 use self::peer::PeerTrait;
@@ -29,12 +30,17 @@ pub(crate) mod peer;
 mod test;
 
 pub const MALICIOUS: f64 = 1.0;
+/// The misconduct score of a peer that didn't respond to a query in time.
+pub const UNRESPONSIVE: f64 = 0.1;
 
-#[cfg_attr(test, derive(Debug, PartialEq))]
-#[derive(Clone, Copy)]
+// The interval in which decayed misconduct scores are published to the other behaviours.
+const MISCONDUCT_SCORE_UPDATE_INTERVAL: Duration = Duration::from_secs(10);
+
+#[derive(Clone, Copy, Debug, PartialEq)]
 pub enum ReputationModifier {
-    /// misconduct_score is in the range [0, 1]. When a peer's total misconduct_score reaches 1, it
-    /// is considered malicious.
+    /// misconduct_score is in the range [0, 1]. The misconduct scores of a peer are summed and
+    /// decay over time. When a peer's total misconduct_score reaches 1, it is considered
+    /// malicious, disconnected and blacklisted.
     Misconduct {
         misconduct_score: f64,
     },
@@ -52,14 +58,26 @@ pub struct PeerManager {
     peers_pending_dial_with_sessions: HashMap<PeerId, Vec<OutboundSessionId>>,
     sessions_received_when_no_peers: Vec<OutboundSessionId>,
     sleep_waiting_for_unblocked_peer: Option<BoxFuture<'static, ()>>,
+    peers_with_misconduct_score: HashSet<PeerId>,
+    sleep_waiting_for_misconduct_score_update: Option<BoxFuture<'static, ()>>,
 }
 
-#[derive(Debug, Clone, Deserialize, Serialize, PartialEq)]
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq, Validate)]
 pub struct PeerManagerConfig {
     #[serde(deserialize_with = "deserialize_seconds_to_duration")]
     malicious_timeout_seconds: Duration,
     #[serde(deserialize_with = "deserialize_milliseconds_to_duration")]
     unstable_timeout_millis: Duration,
+    #[validate(custom = "validate_positive_duration")]
+    #[serde(deserialize_with = "deserialize_seconds_to_duration")]
+    misconduct_score_half_life_seconds: Duration,
+}
+
+fn validate_positive_duration(duration: &Duration) -> Result<(), ValidationError> {
+    if duration.is_zero() {
+        return Err(ValidationError::new("The duration must be positive"));
+    }
+    Ok(())
 }
 
 #[derive(thiserror::Error, Debug)]
@@ -78,6 +96,7 @@ impl Default for PeerManagerConfig {
             // 1 year.
             malicious_timeout_seconds: Duration::from_secs(3600 * 24 * 365),
             unstable_timeout_millis: Duration::from_millis(1000),
+            misconduct_score_half_life_seconds: Duration::from_secs(600),
         }
     }
 }
@@ -97,6 +116,13 @@ impl SerializeConfig for PeerManagerConfig {
                 "The duration in milliseconds a peer blacklisted after being reported as unstable.",
                 ParamPrivacyInput::Public,
             ),
+            ser_param(
+                "misconduct_score_half_life_seconds",
+                &self.misconduct_score_half_life_seconds.as_secs(),
+                "The duration in seconds it takes for the misconduct score of a peer to decay to \
+                 half of its value. Must be positive.",
+                ParamPrivacyInput::Public,
+            ),
         ])
     }
 }
@@ -114,6 +140,8 @@ impl PeerManager {
             peers_pending_dial_with_sessions: HashMap::new(),
             sessions_received_when_no_peers: Vec::new(),
             sleep_waiting_for_unblocked_peer: None,
+            peers_with_misconduct_score: HashSet::new(),
+            sleep_waiting_for_misconduct_score_update: None,
         }
     }
 
@@ -132,6 +160,39 @@ impl PeerManager {
         self.peers.get_mut(&peer_id)
     }
 
+    /// Assign the given peer to the session if it's connected and unblocked. Otherwise, assign a
+    /// peer as usual, in round robin, even though it might not hold what the preferred peer was
+    /// chosen for. The querying side handles this like any other peer that can't answer.
+    fn assign_preferred_peer_to_session(
+        &mut self,
+        outbound_session_id: OutboundSessionId,
+        preferred_peer: PeerId,
+    ) -> Option<PeerId> {
+        let Some(connection_id) = self
+            .peers
+            .get(&preferred_peer)
+            .filter(|peer| !peer.is_blocked())
+            .and_then(|peer| peer.connection_ids().first().copied())
+        else {
+            debug!(
+                "Preferred peer {preferred_peer:?} is not available for {outbound_session_id:?}. \
+                 Assigning another peer."
+            );
+            return self.assign_peer_to_session(outbound_session_id);
+        };
+        info!(
+            "Session {:?} assigned to preferred peer {:?} with connection id: {:?}",
+            outbound_session_id, preferred_peer, connection_id
+        );
+        self.session_to_peer_map.insert(outbound_session_id, preferred_peer);
+        self.pending_events.push(ToSwarm::GenerateEvent(ToOtherBehaviourEvent::SessionAssigned {
+            outbound_session_id,
+            peer_id: preferred_peer,
+            connection_id,
+        }));
+        Some(preferred_peer)
+    }
+
     // TODO(shahak): Remove return value and use events in tests.
     // TODO(shahak): Split this function for readability.
     fn assign_peer_to_session(&mut self, outbound_session_id: OutboundSessionId) -> Option<PeerId> {
@@ -215,28 +276,74 @@ impl PeerManager {
         peer_id: PeerId,
         reason: ReputationModifier,
     ) -> Result<(), PeerManagerError> {
-        self.pending_events
-            .push(ToSwarm::GenerateEvent(ToOtherBehaviourEvent::PeerBlacklisted { peer_id }));
-        if let Some(peer) = self.peers.get_mut(&peer_id) {
-            match reason {
-                ReputationModifier::Misconduct { misconduct_score } => {
-                    peer.report(misconduct_score);
-                    if peer.is_malicious() {
-                        peer.blacklist_peer(self.config.malicious_timeout_seconds);
-                        peer.reset_misconduct_score();
+        let Some(peer) = self.peers.get_mut(&peer_id) else {
+            return Err(PeerManagerError::NoSuchPeer(peer_id));
+        };
+        match reason {
+            ReputationModifier::Misconduct { misconduct_score } => {
+                let half_life = self.config.misconduct_score_half_life_seconds;
+                peer.report(misconduct_score, half_life);
+                self.pending_events.push(ToSwarm::GenerateEvent(
+                    ToOtherBehaviourEvent::MisconductScoreChanged {
+                        peer_id,
+                        misconduct_score: peer.misconduct_score(half_life),
+                    },
+                ));
+                if peer.is_malicious() {
+                    peer.blacklist_peer(self.config.malicious_timeout_seconds);
+                    peer.reset_misconduct_score();
+                    self.peers_with_misconduct_score.remove(&peer_id);
+                    self.pending_events.push(ToSwarm::GenerateEvent(
+                        ToOtherBehaviourEvent::PeerBlacklisted { peer_id },
+                    ));
+                    self.pending_events.push(ToSwarm::CloseConnection {
+                        peer_id,
+                        connection: CloseConnection::All,
+                    });
+                } else {
+                    self.peers_with_misconduct_score.insert(peer_id);
+                    if self.sleep_waiting_for_misconduct_score_update.is_none() {
+                        self.sleep_waiting_for_misconduct_score_update =
+                            Some(tokio::time::sleep(MISCONDUCT_SCORE_UPDATE_INTERVAL).boxed());
                     }
                 }
-                ReputationModifier::Unstable => {
-                    peer.blacklist_peer(self.config.unstable_timeout_millis);
-                }
             }
-            Ok(())
-        } else {
-            Err(PeerManagerError::NoSuchPeer(peer_id))
+            ReputationModifier::Unstable => {
+                peer.blacklist_peer(self.config.unstable_timeout_millis);
+                self.pending_events.push(ToSwarm::GenerateEvent(
+                    ToOtherBehaviourEvent::PeerBlacklisted { peer_id },
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    // Publishes the decayed misconduct scores so that the other behaviours stop penalizing peers
+    // whose misconduct was forgotten.
+    fn update_misconduct_scores(&mut self) {
+        let half_life = self.config.misconduct_score_half_life_seconds;
+        let mut peers_with_misconduct_score = HashSet::new();
+        for peer_id in std::mem::take(&mut self.peers_with_misconduct_score) {
+            let Some(peer) = self.peers.get_mut(&peer_id) else {
+                continue;
+            };
+            peer.decay_misconduct_score(half_life);
+            let misconduct_score = peer.misconduct_score(half_life);
+            self.pending_events.push(ToSwarm::GenerateEvent(
+                ToOtherBehaviourEvent::MisconductScoreChanged { peer_id, misconduct_score },
+            ));
+            if misconduct_score > 0f64 {
+                peers_with_misconduct_score.insert(peer_id);
+            }
         }
+        if !peers_with_misconduct_score.is_empty() {
+            self.sleep_waiting_for_misconduct_score_update =
+                Some(tokio::time::sleep(MISCONDUCT_SCORE_UPDATE_INTERVAL).boxed());
+        }
+        self.peers_with_misconduct_score = peers_with_misconduct_score;
     }
 
-    fn report_session(
+    pub(crate) fn report_session(
         &mut self,
         outbound_session_id: OutboundSessionId,
         reason: ReputationModifier,
@@ -259,10 +366,18 @@ impl BridgedBehaviour for PeerManager {
     fn on_other_behaviour_event(&mut self, event: &mixed_behaviour::ToOtherBehaviourEvent) {
         match event {
             mixed_behaviour::ToOtherBehaviourEvent::Sqmr(
-                sqmr::ToOtherBehaviourEvent::RequestPeerAssignment { outbound_session_id },
-            ) => {
-                self.assign_peer_to_session(*outbound_session_id);
-            }
+                sqmr::ToOtherBehaviourEvent::RequestPeerAssignment {
+                    outbound_session_id,
+                    preferred_peer,
+                },
+            ) => match preferred_peer {
+                Some(preferred_peer) => {
+                    self.assign_preferred_peer_to_session(*outbound_session_id, *preferred_peer);
+                }
+                None => {
+                    self.assign_peer_to_session(*outbound_session_id);
+                }
+            },
             mixed_behaviour::ToOtherBehaviourEvent::Identify(
                 IdentifyToOtherBehaviourEvent::FoundListenAddresses { peer_id, listen_addresses },
             )
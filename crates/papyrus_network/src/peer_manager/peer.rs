@@ -26,7 +26,16 @@ pub trait PeerTrait {
 
     fn reset_misconduct_score(&mut self);
 
-    fn report(&mut self, misconduct_score: f64);
+    /// Adds the given score to the misconduct score of the peer, after decaying it with the given
+    /// half life.
+    fn report(&mut self, misconduct_score: f64, half_life: Duration);
+
+    /// Returns the misconduct score of the peer, decayed with the given half life. Doesn't change
+    /// the stored score.
+    fn misconduct_score(&self, half_life: Duration) -> f64;
+
+    /// Decays the stored misconduct score with the given half life. Negligible scores are zeroed.
+    fn decay_misconduct_score(&mut self, half_life: Duration);
 
     fn is_malicious(&self) -> bool;
 }
@@ -38,8 +47,12 @@ pub struct Peer {
     timed_out_until: Instant,
     connection_ids: Vec<ConnectionId>,
     misconduct_score: f64,
+    misconduct_score_updated_at: Instant,
 }
 
+// Misconduct scores that decayed below this value are considered to be zero.
+const NEGLIGIBLE_MISCONDUCT_SCORE: f64 = 0.001;
+
 impl PeerTrait for Peer {
     fn new(peer_id: PeerId, multiaddr: Multiaddr) -> Self {
         Self {
@@ -48,6 +61,7 @@ impl PeerTrait for Peer {
             timed_out_until: get_instant_now(),
             connection_ids: Vec::new(),
             misconduct_score: 0f64,
+            misconduct_score_updated_at: get_instant_now(),
         }
     }
 
@@ -96,10 +110,23 @@ impl PeerTrait for Peer {
         self.misconduct_score = 0f64;
     }
 
-    fn report(&mut self, misconduct_score: f64) {
+    fn report(&mut self, misconduct_score: f64, half_life: Duration) {
+        self.decay_misconduct_score(half_life);
         self.misconduct_score += misconduct_score;
     }
 
+    fn misconduct_score(&self, half_life: Duration) -> f64 {
+        let elapsed = get_instant_now().saturating_duration_since(self.misconduct_score_updated_at);
+        let decayed_score =
+            self.misconduct_score * 0.5f64.powf(elapsed.as_secs_f64() / half_life.as_secs_f64());
+        if decayed_score < NEGLIGIBLE_MISCONDUCT_SCORE { 0f64 } else { decayed_score }
+    }
+
+    fn decay_misconduct_score(&mut self, half_life: Duration) {
+        self.misconduct_score = self.misconduct_score(half_life);
+        self.misconduct_score_updated_at = get_instant_now();
+    }
+
     fn is_malicious(&self) -> bool {
         1.0f64 <= self.misconduct_score
     }
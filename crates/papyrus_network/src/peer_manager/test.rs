@@ -9,9 +9,10 @@ use assert_matches::assert_matches;
 use futures::future::poll_fn;
 use futures::{FutureExt, Stream, StreamExt};
 use libp2p::swarm::behaviour::ConnectionEstablished;
-use libp2p::swarm::{ConnectionId, NetworkBehaviour, ToSwarm};
+use libp2p::swarm::{CloseConnection, ConnectionId, NetworkBehaviour, ToSwarm};
 use libp2p::{Multiaddr, PeerId};
 use tokio::time::sleep;
+use validator::Validate;
 use void::Void;
 
 use super::behaviour_impl::ToOtherBehaviourEvent;
@@ -19,7 +20,13 @@ use crate::discovery::identify_impl::IdentifyToOtherBehaviourEvent;
 use crate::mixed_behaviour;
 use crate::mixed_behaviour::BridgedBehaviour;
 use crate::peer_manager::peer::{Peer, PeerTrait};
-use crate::peer_manager::{PeerManager, PeerManagerConfig, ReputationModifier, MALICIOUS};
+use crate::peer_manager::{
+    PeerManager,
+    PeerManagerConfig,
+    ReputationModifier,
+    MALICIOUS,
+    MISCONDUCT_SCORE_UPDATE_INTERVAL,
+};
 use crate::sqmr::OutboundSessionId;
 
 impl Unpin for PeerManager {}
@@ -118,6 +125,54 @@ fn peer_assignment_round_robin() {
     }
 }
 
+#[test]
+fn preferred_peer_assignment() {
+    let mut peer_manager = PeerManager::new(PeerManagerConfig::default());
+
+    let connected_peer_id = PeerId::random();
+    let mut connected_peer = Peer::new(connected_peer_id, Multiaddr::empty());
+    connected_peer.add_connection_id(ConnectionId::new_unchecked(0));
+    peer_manager.add_peer(connected_peer);
+    let not_connected_peer_id = PeerId::random();
+    peer_manager.add_peer(Peer::new(not_connected_peer_id, Multiaddr::empty()));
+
+    // The preferred peer is chosen every time, regardless of the round robin.
+    for value in 0..2 {
+        let outbound_session_id = OutboundSessionId { value };
+        assert_eq!(
+            peer_manager.assign_preferred_peer_to_session(outbound_session_id, connected_peer_id),
+            Some(connected_peer_id)
+        );
+        assert_matches!(
+            peer_manager.next().now_or_never(),
+            Some(Some(ToSwarm::GenerateEvent(ToOtherBehaviourEvent::SessionAssigned {
+                outbound_session_id: event_outbound_session_id,
+                peer_id,
+                connection_id,
+            }))) if event_outbound_session_id == outbound_session_id
+                && peer_id == connected_peer_id
+                && connection_id == ConnectionId::new_unchecked(0)
+        );
+    }
+
+    // A preferred peer that isn't connected or known is replaced by another peer.
+    for preferred_peer in [not_connected_peer_id, PeerId::random()] {
+        assert!(
+            peer_manager
+                .assign_preferred_peer_to_session(OutboundSessionId { value: 2 }, preferred_peer)
+                .is_some()
+        );
+    }
+
+    // A blocked preferred peer is replaced by another peer.
+    peer_manager.report_peer(connected_peer_id, ReputationModifier::Unstable).unwrap();
+    assert_eq!(
+        peer_manager
+            .assign_preferred_peer_to_session(OutboundSessionId { value: 3 }, connected_peer_id),
+        Some(not_connected_peer_id)
+    );
+}
+
 #[tokio::test]
 async fn peer_assignment_no_peers() {
     // Create a new peer manager
@@ -156,8 +211,11 @@ async fn peer_assignment_no_unblocked_peers() {
     const BLOCKED_UNTIL: Duration = Duration::from_secs(5);
     const TIMEOUT: Duration = Duration::from_secs(1);
     // Create a new peer manager
-    let config =
-        PeerManagerConfig { malicious_timeout_seconds: TIMEOUT, unstable_timeout_millis: TIMEOUT };
+    let config = PeerManagerConfig {
+        malicious_timeout_seconds: TIMEOUT,
+        unstable_timeout_millis: TIMEOUT,
+        ..Default::default()
+    };
     let mut peer_manager: PeerManager = PeerManager::new(config.clone());
 
     // Create a session
@@ -240,6 +298,101 @@ async fn peer_block_released_after_timeout() {
     assert!(!peer.is_blocked());
 }
 
+#[tokio::test]
+async fn misconduct_score_decays_over_time() {
+    const HALF_LIFE: Duration = Duration::from_secs(10);
+    tokio::time::pause();
+    let mut peer = Peer::new(PeerId::random(), Multiaddr::empty());
+    peer.report(0.5, HALF_LIFE);
+    assert_eq!(peer.misconduct_score(HALF_LIFE), 0.5);
+
+    tokio::time::advance(HALF_LIFE).await;
+    assert_eq!(peer.misconduct_score(HALF_LIFE), 0.25);
+
+    // The decayed score is the base for new reports.
+    peer.report(0.5, HALF_LIFE);
+    assert_eq!(peer.misconduct_score(HALF_LIFE), 0.75);
+    assert!(!peer.is_malicious());
+}
+
+#[tokio::test]
+async fn accumulated_misconduct_disconnects_and_blacklists_peer() {
+    tokio::time::pause();
+    let mut peer_manager = PeerManager::new(PeerManagerConfig::default());
+    let peer_id = PeerId::random();
+    let mut peer = Peer::new(peer_id, Multiaddr::empty());
+    peer.add_connection_id(ConnectionId::new_unchecked(0));
+    peer_manager.add_peer(peer);
+
+    peer_manager
+        .report_peer(peer_id, ReputationModifier::Misconduct { misconduct_score: 0.5 })
+        .unwrap();
+    assert!(!peer_manager.get_mut_peer(peer_id).unwrap().is_blocked());
+    assert_matches!(
+        peer_manager.next().now_or_never().unwrap().unwrap(),
+        ToSwarm::GenerateEvent(ToOtherBehaviourEvent::MisconductScoreChanged {
+            peer_id: event_peer_id,
+            misconduct_score,
+        }) if event_peer_id == peer_id && misconduct_score == 0.5
+    );
+
+    peer_manager
+        .report_peer(peer_id, ReputationModifier::Misconduct { misconduct_score: 0.5 })
+        .unwrap();
+    assert!(peer_manager.get_mut_peer(peer_id).unwrap().is_blocked());
+    let events = [
+        peer_manager.next().now_or_never().unwrap().unwrap(),
+        peer_manager.next().now_or_never().unwrap().unwrap(),
+        peer_manager.next().now_or_never().unwrap().unwrap(),
+    ];
+    assert!(events.iter().any(|event| matches!(
+        event,
+        ToSwarm::CloseConnection { peer_id: event_peer_id, connection: CloseConnection::All }
+        if *event_peer_id == peer_id
+    )));
+    assert!(events.iter().any(|event| matches!(
+        event,
+        ToSwarm::GenerateEvent(ToOtherBehaviourEvent::PeerBlacklisted { peer_id: event_peer_id })
+        if *event_peer_id == peer_id
+    )));
+    assert!(events.iter().any(|event| matches!(
+        event,
+        ToSwarm::GenerateEvent(ToOtherBehaviourEvent::MisconductScoreChanged {
+            peer_id: event_peer_id,
+            misconduct_score,
+        }) if *event_peer_id == peer_id && *misconduct_score == 1.0
+    )));
+    assert!(peer_manager.next().now_or_never().is_none());
+}
+
+#[tokio::test]
+async fn decayed_misconduct_score_is_published() {
+    let half_life = MISCONDUCT_SCORE_UPDATE_INTERVAL;
+    let config =
+        PeerManagerConfig { misconduct_score_half_life_seconds: half_life, ..Default::default() };
+    let mut peer_manager = PeerManager::new(config);
+    let peer_id = PeerId::random();
+    tokio::time::pause();
+    peer_manager.add_peer(Peer::new(peer_id, Multiaddr::empty()));
+
+    peer_manager
+        .report_peer(peer_id, ReputationModifier::Misconduct { misconduct_score: 0.5 })
+        .unwrap();
+    // Consume the event of the report.
+    peer_manager.next().now_or_never().unwrap().unwrap();
+    assert!(peer_manager.next().now_or_never().is_none());
+
+    tokio::time::advance(MISCONDUCT_SCORE_UPDATE_INTERVAL).await;
+    // The timer may fire slightly after the interval.
+    assert_matches!(
+        peer_manager.next().await.unwrap(),
+        ToSwarm::GenerateEvent(ToOtherBehaviourEvent::MisconductScoreChanged {
+            peer_id: event_peer_id,
+            misconduct_score,
+        }) if event_peer_id == peer_id && (misconduct_score - 0.25).abs() < 0.001
+    );
+}
+
 #[test]
 fn report_peer_on_unknown_peer_id() {
     // Create a new peer manager
@@ -487,3 +640,13 @@ fn identify_on_unknown_peer_is_added_to_peer_manager() {
     assert!(res_peer_id.peer_id() == peer_id);
     assert!(res_peer_id.multiaddr() == address);
 }
+
+#[test]
+fn zero_misconduct_score_half_life_is_invalid() {
+    let config = PeerManagerConfig {
+        misconduct_score_half_life_seconds: Duration::ZERO,
+        ..Default::default()
+    };
+    assert!(config.validate().is_err());
+    assert!(PeerManagerConfig::default().validate().is_ok());
+}
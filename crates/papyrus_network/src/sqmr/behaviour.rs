@@ -58,9 +58,12 @@ impl From<GenericEvent<HandlerSessionError>> for GenericEvent<SessionError> {
                 peer_id,
                 protocol_name,
             } => Self::NewInboundSession { query, inbound_session_id, peer_id, protocol_name },
-            GenericEvent::ReceivedResponse { outbound_session_id, response, peer_id } => {
-                Self::ReceivedResponse { outbound_session_id, response, peer_id }
-            }
+            GenericEvent::ReceivedResponse {
+                outbound_session_id,
+                response,
+                peer_id,
+                protocol_name,
+            } => Self::ReceivedResponse { outbound_session_id, response, peer_id, protocol_name },
             GenericEvent::SessionFailed {
                 session_id,
                 error: HandlerSessionError::Timeout { session_timeout },
@@ -88,7 +91,7 @@ pub type ExternalEvent = GenericEvent<SessionError>;
 
 #[derive(Debug)]
 pub enum ToOtherBehaviourEvent {
-    RequestPeerAssignment { outbound_session_id: OutboundSessionId },
+    RequestPeerAssignment { outbound_session_id: OutboundSessionId, preferred_peer: Option<PeerId> },
 }
 
 #[derive(Debug)]
@@ -113,7 +116,8 @@ pub struct Behaviour {
     next_inbound_session_id: Arc<AtomicUsize>,
     dropped_sessions: HashSet<SessionId>,
     wakers_waiting_for_event: Vec<Waker>,
-    outbound_sessions_pending_peer_assignment: HashMap<OutboundSessionId, (Bytes, StreamProtocol)>,
+    outbound_sessions_pending_peer_assignment:
+        HashMap<OutboundSessionId, Vec<(StreamProtocol, Bytes)>>,
     supported_inbound_protocols: HashSet<StreamProtocol>,
 }
 
@@ -133,19 +137,23 @@ impl Behaviour {
     }
 
     /// Assign some peer and start a query. Return the id of the new session.
+    /// The query is given encoded in each of the versions of the protocol, ordered from the most
+    /// preferred version to the least preferred one. The most preferred version the peer supports
+    /// is negotiated.
+    /// Start a new outbound session. If a preferred peer is given, the session will be assigned to
+    /// it if possible.
     pub fn start_query(
         &mut self,
-        query: Bytes,
-        protocol_name: StreamProtocol,
+        queries: Vec<(StreamProtocol, Bytes)>,
+        preferred_peer: Option<PeerId>,
     ) -> OutboundSessionId {
         let outbound_session_id = self.next_outbound_session_id;
         self.next_outbound_session_id.value += 1;
 
-        self.outbound_sessions_pending_peer_assignment
-            .insert(outbound_session_id, (query, protocol_name));
+        self.outbound_sessions_pending_peer_assignment.insert(outbound_session_id, queries);
         info!("Requesting peer assignment for outbound session: {:?}.", outbound_session_id);
         self.add_event_to_queue(ToSwarm::GenerateEvent(Event::ToOtherBehaviourEvent(
-            ToOtherBehaviourEvent::RequestPeerAssignment { outbound_session_id },
+            ToOtherBehaviourEvent::RequestPeerAssignment { outbound_session_id, preferred_peer },
         )));
 
         outbound_session_id
@@ -315,6 +323,9 @@ impl NetworkBehaviour for Behaviour {
             }
             RequestToBehaviourEvent::NotifySessionDropped { session_id } => {
                 self.dropped_sessions.remove(&session_id);
+                // The handler won't report that the session finished, so this is the last event
+                // of the session.
+                self.session_id_to_peer_id_and_connection_id.remove(&session_id);
             }
         }
     }
@@ -348,7 +359,7 @@ impl BridgedBehaviour for Behaviour {
         self.session_id_to_peer_id_and_connection_id
             .insert((*outbound_session_id).into(), (*peer_id, *connection_id));
 
-        let Some((query, protocol_name)) =
+        let Some(queries) =
             self.outbound_sessions_pending_peer_assignment.remove(outbound_session_id)
         else {
             error!(
@@ -362,9 +373,8 @@ impl BridgedBehaviour for Behaviour {
             peer_id: *peer_id,
             handler: NotifyHandler::One(*connection_id),
             event: RequestFromBehaviourEvent::CreateOutboundSession {
-                query,
+                queries,
                 outbound_session_id: *outbound_session_id,
-                protocol_name,
             },
         });
     }
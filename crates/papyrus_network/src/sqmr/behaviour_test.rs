@@ -81,6 +81,7 @@ fn simulate_received_response(
             response,
             outbound_session_id,
             peer_id,
+            protocol_name: PROTOCOL_NAME.clone(),
         }),
     );
 }
@@ -132,7 +133,8 @@ async fn validate_request_peer_assignment_event(
     assert_matches!(
         event,
         ToSwarm::GenerateEvent(Event::ToOtherBehaviourEvent(ToOtherBehaviourEvent::RequestPeerAssignment {
-                outbound_session_id: event_outbound_session_id
+                outbound_session_id: event_outbound_session_id,
+                preferred_peer: None,
             },
         )) if outbound_session_id == event_outbound_session_id
     );
@@ -149,12 +151,11 @@ async fn validate_create_outbound_session_event(
         event,
         ToSwarm::NotifyHandler {
             peer_id: event_peer_id,
-            event: RequestFromBehaviourEvent::CreateOutboundSession { query: event_query, outbound_session_id: event_outbound_session_id, protocol_name },
+            event: RequestFromBehaviourEvent::CreateOutboundSession { queries, outbound_session_id: event_outbound_session_id },
             ..
         } if *peer_id == event_peer_id
             && *outbound_session_id == event_outbound_session_id
-            && *query == event_query
-            && protocol_name == PROTOCOL_NAME.clone()
+            && queries == vec![(PROTOCOL_NAME.clone(), query.clone())]
     );
 }
 
@@ -190,8 +191,8 @@ async fn validate_received_response_event(
         event,
         ToSwarm::GenerateEvent(Event::External(ExternalEvent::ReceivedResponse {
             response: event_response, outbound_session_id: event_outbound_session_id,
-            peer_id: event_peer_id,
-        })) if event_response == *response && event_outbound_session_id == outbound_session_id && peer_id == event_peer_id
+            peer_id: event_peer_id, protocol_name,
+        })) if event_response == *response && event_outbound_session_id == outbound_session_id && peer_id == event_peer_id && protocol_name == PROTOCOL_NAME.clone()
     );
 }
 
@@ -315,7 +316,8 @@ async fn create_and_process_outbound_session() {
 
     let peer_id = PeerId::random();
 
-    let outbound_session_id = behaviour.start_query(QUERY.clone(), PROTOCOL_NAME.clone());
+    let outbound_session_id =
+        behaviour.start_query(vec![(PROTOCOL_NAME.clone(), QUERY.clone())], None);
 
     validate_request_peer_assignment_event(&mut behaviour, outbound_session_id).await;
     validate_no_events(&mut behaviour);
@@ -350,7 +352,8 @@ async fn connection_closed() {
     let peer_id = PeerId::random();
 
     // Add an outbound session on the connection.
-    let outbound_session_id = behaviour.start_query(QUERY.clone(), PROTOCOL_NAME.clone());
+    let outbound_session_id =
+        behaviour.start_query(vec![(PROTOCOL_NAME.clone(), QUERY.clone())], None);
     // Consume the event to request peer assignment.
     behaviour.next().await.unwrap();
     simulate_peer_assigned(&mut behaviour, peer_id, outbound_session_id);
@@ -397,7 +400,8 @@ async fn drop_outbound_session() {
 
     let peer_id = PeerId::random();
 
-    let outbound_session_id = behaviour.start_query(QUERY.clone(), PROTOCOL_NAME.clone());
+    let outbound_session_id =
+        behaviour.start_query(vec![(PROTOCOL_NAME.clone(), QUERY.clone())], None);
     // Consume the event to request peer assignment.
     behaviour.next().await.unwrap();
     simulate_peer_assigned(&mut behaviour, peer_id, outbound_session_id);
@@ -448,6 +452,26 @@ async fn drop_inbound_session() {
     // so if it will the behaviour might output them.
 }
 
+#[tokio::test]
+async fn dropped_inbound_session_is_forgotten() {
+    let mut behaviour = Behaviour::new(Config::get_test_config());
+
+    let peer_id = PeerId::random();
+    let inbound_session_id = InboundSessionId::default();
+
+    simulate_new_inbound_session(&mut behaviour, peer_id, inbound_session_id, QUERY.clone());
+
+    // Consume the event that a new inbound session was created.
+    behaviour.next().await.unwrap();
+
+    behaviour.drop_session(inbound_session_id.into()).unwrap();
+    validate_request_drop_session_event(&mut behaviour, &peer_id, inbound_session_id.into()).await;
+
+    simulate_session_dropped(&mut behaviour, peer_id, inbound_session_id.into());
+
+    behaviour.close_inbound_session(inbound_session_id).unwrap_err();
+}
+
 #[test]
 fn close_non_existing_session_fails() {
     let mut behaviour = Behaviour::new(Config::get_test_config());
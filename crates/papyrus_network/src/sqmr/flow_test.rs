@@ -20,6 +20,8 @@ const NUM_MESSAGES_PER_SESSION: usize = 5;
 
 pub const PROTOCOL_NAME: StreamProtocol = StreamProtocol::new("/example");
 pub const OTHER_PROTOCOL_NAME: StreamProtocol = StreamProtocol::new("/other");
+// A newer version of PROTOCOL_NAME that no peer supports, so that PROTOCOL_NAME is negotiated.
+pub const UNSUPPORTED_PROTOCOL_NAME: StreamProtocol = StreamProtocol::new("/example/unsupported");
 
 type SwarmEventAlias<BehaviourTrait> = SwarmEvent<<BehaviourTrait as NetworkBehaviour>::ToSwarm>;
 
@@ -68,8 +70,11 @@ fn start_query_and_update_map(
 ) {
     let outbound_peer_id = *outbound_swarm.local_peer_id();
     let outbound_session_id = outbound_swarm.behaviour_mut().start_query(
-        get_bytes_from_query_indices(outbound_peer_id, inbound_peer_id),
-        PROTOCOL_NAME,
+        vec![
+            (UNSUPPORTED_PROTOCOL_NAME, Bytes::new()),
+            (PROTOCOL_NAME, get_bytes_from_query_indices(outbound_peer_id, inbound_peer_id)),
+        ],
+        None,
     );
     outbound_session_id_to_peer_id.insert((outbound_peer_id, outbound_session_id), inbound_peer_id);
 }
@@ -130,6 +135,7 @@ fn check_request_peer_assignment_event_and_return_session_id(
     };
     let Event::ToOtherBehaviourEvent(ToOtherBehaviourEvent::RequestPeerAssignment {
         outbound_session_id,
+        ..
     }) = event
     else {
         panic!("Got unexpected event {:?} when expecting RequestPeerAssignment", event);
@@ -173,10 +179,12 @@ fn check_received_response_event(
         outbound_session_id: _outbound_session_id,
         response,
         peer_id: inbound_peer_id,
+        protocol_name,
     }) = event
     else {
         panic!("Got unexpected event {:?} when expecting ReceivedResponse", event);
     };
+    assert_eq!(protocol_name, PROTOCOL_NAME);
     assert_eq!(
         outbound_session_id_to_peer_id[&(outbound_peer_id, _outbound_session_id)],
         inbound_peer_id
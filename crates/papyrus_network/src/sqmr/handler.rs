@@ -36,9 +36,8 @@ use super::{Bytes, Config, GenericEvent, InboundSessionId, OutboundSessionId, Se
 #[derive(Debug)]
 pub enum RequestFromBehaviourEvent {
     CreateOutboundSession {
-        query: Bytes,
+        queries: Vec<(StreamProtocol, Bytes)>,
         outbound_session_id: OutboundSessionId,
-        protocol_name: StreamProtocol,
     },
     SendResponse {
         response: Bytes,
@@ -81,7 +80,7 @@ pub struct Handler {
     peer_id: PeerId,
     id_to_inbound_session: HashMap<InboundSessionId, InboundSession>,
     id_to_outbound_session:
-        HashMap<OutboundSessionId, BoxStream<'static, Result<Bytes, io::Error>>>,
+        HashMap<OutboundSessionId, (StreamProtocol, BoxStream<'static, Result<Bytes, io::Error>>)>,
     // TODO(shahak): Use deadqueue if using a VecDeque is a bug (libp2p uses VecDeque, so we opened
     // an issue on it https://github.com/libp2p/rust-libp2p/issues/5147)
     pending_events: VecDeque<HandlerEvent<Self>>,
@@ -202,6 +201,7 @@ impl ConnectionHandler for Handler {
 
         // Handle outbound sessions.
         self.id_to_outbound_session.retain(|outbound_session_id, outbound_session| {
+            let (protocol_name, outbound_session) = outbound_session;
             match outbound_session.poll_next_unpin(cx) {
                 Poll::Ready(Some(Ok(response))) => {
                     self.pending_events.push_back(ConnectionHandlerEvent::NotifyBehaviour(
@@ -209,6 +209,7 @@ impl ConnectionHandler for Handler {
                             outbound_session_id: *outbound_session_id,
                             response,
                             peer_id: self.peer_id,
+                            protocol_name: protocol_name.clone(),
                         }),
                     ));
                     true
@@ -246,11 +247,7 @@ impl ConnectionHandler for Handler {
 
     fn on_behaviour_event(&mut self, event: Self::FromBehaviour) {
         match event {
-            RequestFromBehaviourEvent::CreateOutboundSession {
-                query,
-                outbound_session_id,
-                protocol_name,
-            } => {
+            RequestFromBehaviourEvent::CreateOutboundSession { queries, outbound_session_id } => {
                 // TODO(shahak) Consider extracting to a utility function to prevent forgetfulness
                 // of the timeout.
 
@@ -258,7 +255,7 @@ impl ConnectionHandler for Handler {
                 // on_behaviour_event. See https://github.com/libp2p/rust-libp2p/issues/5147
                 self.pending_events.push_back(ConnectionHandlerEvent::OutboundSubstreamRequest {
                     protocol: SubstreamProtocol::new(
-                        OutboundProtocol { query, protocol_name },
+                        OutboundProtocol { queries },
                         outbound_session_id,
                     )
                     .with_timeout(self.config.session_timeout),
@@ -331,7 +328,7 @@ impl ConnectionHandler for Handler {
     ) {
         match event {
             ConnectionEvent::FullyNegotiatedOutbound(FullyNegotiatedOutbound {
-                protocol: mut read_stream,
+                protocol: (mut read_stream, protocol_name),
                 info: outbound_session_id,
             }) => {
                 if self.dropped_outbound_sessions_non_negotiated.remove(&outbound_session_id) {
@@ -339,22 +336,25 @@ impl ConnectionHandler for Handler {
                 }
                 self.id_to_outbound_session.insert(
                     outbound_session_id,
-                    stream! {
-                        loop {
-                            let result_opt = read_message(&mut read_stream).await;
-                            let result = match result_opt {
-                                Ok(Some(response)) => Ok(response),
-                                Ok(None) => break,
-                                Err(error) => Err(error),
-                            };
-                            let is_err = result.is_err();
-                            yield result;
-                            if is_err {
-                                break;
+                    (
+                        protocol_name,
+                        stream! {
+                            loop {
+                                let result_opt = read_message(&mut read_stream).await;
+                                let result = match result_opt {
+                                    Ok(Some(response)) => Ok(response),
+                                    Ok(None) => break,
+                                    Err(error) => Err(error),
+                                };
+                                let is_err = result.is_err();
+                                yield result;
+                                if is_err {
+                                    break;
+                                }
                             }
                         }
-                    }
-                    .boxed(),
+                        .boxed(),
+                    ),
                 );
             }
             ConnectionEvent::FullyNegotiatedInbound(FullyNegotiatedInbound {
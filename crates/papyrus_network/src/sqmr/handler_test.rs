@@ -65,9 +65,8 @@ fn simulate_request_to_send_query_from_swarm(
     outbound_session_id: OutboundSessionId,
 ) {
     handler.on_behaviour_event(RequestFromBehaviourEvent::CreateOutboundSession {
-        query,
+        queries: vec![(PROTOCOL_NAME.clone(), query)],
         outbound_session_id,
-        protocol_name: PROTOCOL_NAME.clone(),
     });
 }
 
@@ -101,7 +100,10 @@ fn simulate_negotiated_outbound_session_from_swarm(
     outbound_session_id: OutboundSessionId,
 ) {
     handler.on_connection_event(ConnectionEvent::FullyNegotiatedOutbound(
-        FullyNegotiatedOutbound { protocol: outbound_stream.split().0, info: outbound_session_id },
+        FullyNegotiatedOutbound {
+            protocol: (outbound_stream.split().0, PROTOCOL_NAME.clone()),
+            info: outbound_session_id,
+        },
     ));
 }
 
@@ -151,11 +153,11 @@ async fn validate_received_response_event(
         ConnectionHandlerEvent::NotifyBehaviour(
             RequestToBehaviourEvent::GenerateEvent(
                 GenericEvent::ReceivedResponse {
-                    response: event_response, outbound_session_id: event_outbound_session_id, peer_id : event_peer_id
+                    response: event_response, outbound_session_id: event_outbound_session_id, peer_id : event_peer_id, protocol_name
 
                 }
             )
-        ) if event_response == *response &&  event_outbound_session_id == outbound_session_id && event_peer_id == handler.peer_id
+        ) if event_response == *response &&  event_outbound_session_id == outbound_session_id && event_peer_id == handler.peer_id && protocol_name == *PROTOCOL_NAME
     );
 }
 
@@ -214,7 +216,7 @@ async fn validate_request_to_swarm_new_outbound_session_to_swarm_event(
     assert_matches!(
         event,
         ConnectionHandlerEvent::OutboundSubstreamRequest{ protocol }
-        if protocol.upgrade().query == *query && *protocol.info() == outbound_session_id
+        if protocol.upgrade().queries == vec![(PROTOCOL_NAME.clone(), query.clone())] && *protocol.info() == outbound_session_id
     );
 }
 
@@ -53,6 +53,8 @@ pub enum GenericEvent<SessionError> {
         outbound_session_id: OutboundSessionId,
         response: Bytes,
         peer_id: PeerId,
+        /// The version of the protocol that was negotiated for the session.
+        protocol_name: StreamProtocol,
     },
     SessionFailed {
         session_id: SessionId,
@@ -2,7 +2,7 @@
 #[path = "protocol_test.rs"]
 mod protocol_test;
 
-use std::{io, iter};
+use std::io;
 
 use futures::future::BoxFuture;
 use futures::io::{ReadHalf, WriteHalf};
@@ -52,16 +52,18 @@ where
 
 #[derive(Debug)]
 pub struct OutboundProtocol {
-    pub query: Bytes,
-    pub protocol_name: StreamProtocol,
+    /// The query encoded in each of the versions of the protocol we support, ordered from the most
+    /// preferred version to the least preferred one. The first version the other peer supports is
+    /// negotiated and its query is sent.
+    pub queries: Vec<(StreamProtocol, Bytes)>,
 }
 
 impl UpgradeInfo for OutboundProtocol {
     type Info = StreamProtocol;
-    type InfoIter = iter::Once<Self::Info>;
+    type InfoIter = Vec<Self::Info>;
 
     fn protocol_info(&self) -> Self::InfoIter {
-        iter::once(self.protocol_name.clone())
+        self.queries.iter().map(|(protocol_name, _)| protocol_name.clone()).collect()
     }
 }
 
@@ -69,15 +71,27 @@ impl<Stream> OutboundUpgrade<Stream> for OutboundProtocol
 where
     Stream: AsyncRead + AsyncWrite + Unpin + Send + 'static,
 {
-    type Output = ReadHalf<Stream>;
+    type Output = (ReadHalf<Stream>, StreamProtocol);
     type Error = io::Error;
     type Future = BoxFuture<'static, Result<Self::Output, Self::Error>>;
 
-    fn upgrade_outbound(self, stream: Stream, _: Self::Info) -> Self::Future {
+    fn upgrade_outbound(self, stream: Stream, protocol_name: Self::Info) -> Self::Future {
         async move {
+            let query = self
+                .queries
+                .into_iter()
+                .find_map(|(query_protocol_name, query)| {
+                    (query_protocol_name == protocol_name).then_some(query)
+                })
+                .ok_or_else(|| {
+                    io::Error::new(
+                        io::ErrorKind::InvalidInput,
+                        format!("Negotiated protocol {protocol_name} that we didn't offer."),
+                    )
+                })?;
             let (read_half, write_half) = stream.split();
-            write_message_without_length_prefix(&self.query, write_half).await?;
-            Ok(read_half)
+            write_message_without_length_prefix(&query, write_half).await?;
+            Ok((read_half, protocol_name))
         }
         .boxed()
     }
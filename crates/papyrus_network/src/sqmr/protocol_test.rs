@@ -8,17 +8,22 @@ use super::{InboundProtocol, OutboundProtocol};
 use crate::test_utils::{dummy_data, get_connected_streams};
 
 pub const PROTOCOL_NAME: StreamProtocol = StreamProtocol::new("/example/1.0.0");
+pub const OTHER_PROTOCOL_NAME: StreamProtocol = StreamProtocol::new("/example/2.0.0");
 
 #[test]
 fn outbound_protocol_info() {
-    let outbound_protocol =
-        OutboundProtocol { query: Default::default(), protocol_name: PROTOCOL_NAME };
-    assert_eq!(outbound_protocol.protocol_info().collect::<Vec<_>>(), vec![PROTOCOL_NAME]);
+    let outbound_protocol = OutboundProtocol {
+        queries: vec![
+            (OTHER_PROTOCOL_NAME, Default::default()),
+            (PROTOCOL_NAME, Default::default()),
+        ],
+    };
+    assert_eq!(outbound_protocol.protocol_info(), vec![OTHER_PROTOCOL_NAME, PROTOCOL_NAME]);
 }
 
 #[test]
 fn inbound_protocol_info() {
-    let protocol_names = vec![PROTOCOL_NAME, StreamProtocol::new("/example/2.0.0")];
+    let protocol_names = vec![PROTOCOL_NAME, OTHER_PROTOCOL_NAME];
     let inbound_protocol = InboundProtocol::new(protocol_names.clone());
     assert_eq!(inbound_protocol.protocol_info(), protocol_names);
 }
@@ -28,7 +33,7 @@ async fn positive_flow() {
     let (inbound_stream, outbound_stream, _) = get_connected_streams().await;
 
     let query = vec![1u8, 2u8, 3u8];
-    let outbound_protocol = OutboundProtocol { query: query.clone(), protocol_name: PROTOCOL_NAME };
+    let outbound_protocol = OutboundProtocol { queries: vec![(PROTOCOL_NAME, query.clone())] };
     let inbound_protocol = InboundProtocol::new(vec![PROTOCOL_NAME]);
 
     tokio::join!(
@@ -42,8 +47,9 @@ async fn positive_flow() {
             }
         },
         async move {
-            let mut stream =
+            let (mut stream, protocol_name) =
                 outbound_protocol.upgrade_outbound(outbound_stream, PROTOCOL_NAME).await.unwrap();
+            assert_eq!(protocol_name, PROTOCOL_NAME);
             for expected_response in dummy_data() {
                 let response = read_message(&mut stream).await.unwrap().unwrap();
                 assert_eq!(response, expected_response);
@@ -55,7 +61,7 @@ async fn positive_flow() {
 #[tokio::test]
 async fn inbound_dropped() {
     let (inbound_stream, outbound_stream, _) = get_connected_streams().await;
-    let outbound_protocol = OutboundProtocol { query: vec![0u8], protocol_name: PROTOCOL_NAME };
+    let outbound_protocol = OutboundProtocol { queries: vec![(PROTOCOL_NAME, vec![0u8])] };
 
     drop(inbound_stream);
 
@@ -64,3 +70,28 @@ async fn inbound_dropped() {
 
     assert!(outbound_protocol.upgrade_outbound(outbound_stream, PROTOCOL_NAME).await.is_err());
 }
+
+#[tokio::test]
+async fn outbound_sends_query_of_negotiated_protocol() {
+    let (inbound_stream, outbound_stream, _) = get_connected_streams().await;
+
+    let query = vec![1u8, 2u8, 3u8];
+    let other_query = vec![4u8, 5u8];
+    let outbound_protocol = OutboundProtocol {
+        queries: vec![(OTHER_PROTOCOL_NAME, other_query.clone()), (PROTOCOL_NAME, query.clone())],
+    };
+    let inbound_protocol = InboundProtocol::new(vec![PROTOCOL_NAME]);
+
+    tokio::join!(
+        async move {
+            let (received_query, _stream, _) =
+                inbound_protocol.upgrade_inbound(inbound_stream, PROTOCOL_NAME).await.unwrap();
+            assert_eq!(query, received_query);
+        },
+        async move {
+            let (_stream, protocol_name) =
+                outbound_protocol.upgrade_outbound(outbound_stream, PROTOCOL_NAME).await.unwrap();
+            assert_eq!(protocol_name, PROTOCOL_NAME);
+        }
+    );
+}
@@ -1,3 +1,7 @@
+#[cfg(test)]
+#[path = "utils_test.rs"]
+mod utils_test;
+
 use core::net::Ipv4Addr;
 use std::collections::hash_map::{Keys, ValuesMut};
 use std::collections::HashMap;
@@ -86,3 +90,52 @@ pub fn is_localhost(address: &Multiaddr) -> bool {
     };
     ip4_address == Ipv4Addr::LOCALHOST
 }
+
+/// The transport a connection uses.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Transport {
+    Tcp,
+    Quic,
+    // E.g. the memory transport used in tests.
+    Other,
+}
+
+impl Transport {
+    /// The transport used to connect to `address`.
+    pub fn of(address: &Multiaddr) -> Self {
+        let mut transport = Transport::Other;
+        for protocol in address.iter() {
+            match protocol {
+                Protocol::QuicV1 => return Transport::Quic,
+                Protocol::Tcp(_) => transport = Transport::Tcp,
+                _ => {}
+            }
+        }
+        transport
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Transport::Tcp => "tcp",
+            Transport::Quic => "quic",
+            Transport::Other => "other",
+        }
+    }
+}
+
+/// Returns the QUIC address on `quic_port` of the host in the TCP address `address`, or None if
+/// `address` is not a TCP address.
+pub fn to_quic_address(address: &Multiaddr, quic_port: u16) -> Option<Multiaddr> {
+    let mut is_tcp = false;
+    let quic_address = address
+        .iter()
+        .flat_map(|protocol| match protocol {
+            Protocol::Tcp(_) => {
+                is_tcp = true;
+                vec![Protocol::Udp(quic_port), Protocol::QuicV1]
+            }
+            protocol => vec![protocol],
+        })
+        .collect();
+    is_tcp.then_some(quic_address)
+}
@@ -0,0 +1,29 @@
+use libp2p::Multiaddr;
+
+use crate::utils::{to_quic_address, Transport};
+
+fn multiaddr(address: &str) -> Multiaddr {
+    address.parse().unwrap()
+}
+
+#[test]
+fn transport_of_address() {
+    assert_eq!(Transport::of(&multiaddr("/ip4/1.2.3.4/tcp/10000")), Transport::Tcp);
+    assert_eq!(Transport::of(&multiaddr("/dns/example.com/tcp/10000")), Transport::Tcp);
+    assert_eq!(Transport::of(&multiaddr("/ip4/1.2.3.4/udp/10001/quic-v1")), Transport::Quic);
+    assert_eq!(Transport::of(&multiaddr("/memory/1234")), Transport::Other);
+}
+
+#[test]
+fn quic_address_of_tcp_address() {
+    const PEER_ID: &str = "12D3KooWQYhTNQdmr3ArTeUHRYzFg94BKyTkoWBDWez9kSCVe2Xo";
+    assert_eq!(
+        to_quic_address(&multiaddr("/ip4/1.2.3.4/tcp/10000"), 10001),
+        Some(multiaddr("/ip4/1.2.3.4/udp/10001/quic-v1"))
+    );
+    assert_eq!(
+        to_quic_address(&multiaddr(&format!("/dns/example.com/tcp/10000/p2p/{PEER_ID}")), 10001),
+        Some(multiaddr(&format!("/dns/example.com/udp/10001/quic-v1/p2p/{PEER_ID}")))
+    );
+    assert_eq!(to_quic_address(&multiaddr("/ip4/1.2.3.4/udp/10001/quic-v1"), 10001), None);
+}
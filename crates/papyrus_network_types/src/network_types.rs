@@ -4,7 +4,10 @@ use serde::{Deserialize, Serialize};
 // TODO(alonl): remove clone
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash)]
 pub struct BroadcastedMessageMetadata {
+    // The peer that published the message.
     pub originator_id: OpaquePeerId,
+    // The peer that sent the message to us, which may be a peer that relayed it.
+    pub propagation_source: OpaquePeerId,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash)]
@@ -5,6 +5,8 @@
 //! 1. TestConfig - these are prefixed with `--test.` in the command.
 //! 2. NodeConfig - any argument lacking the above prefix is assumed to be in NodeConfig.
 
+use std::sync::Arc;
+
 use clap::Parser;
 use futures::stream::StreamExt;
 use papyrus_consensus::config::ConsensusConfig;
@@ -56,6 +58,7 @@ fn build_consensus(
     storage_reader: StorageReader,
     network_manager: &mut NetworkManager,
 ) -> anyhow::Result<Option<JoinHandle<anyhow::Result<()>>>> {
+    let signer = consensus_config.signer()?;
     let network_channels = network_manager.register_broadcast_topic(
         Topic::new(consensus_config.network_topic.clone()),
         BUFFER_SIZE,
@@ -66,8 +69,9 @@ fn build_consensus(
     let context = PapyrusConsensusContext::new(
         storage_reader.clone(),
         network_channels.broadcast_topic_client.clone(),
-        consensus_config.num_validators,
+        Arc::new(consensus_config.validator_set()),
         Some(sync_channels.broadcast_topic_client),
+        signer.clone(),
     );
     let sync_receiver =
         sync_channels.broadcasted_messages_receiver.map(|(vote, _report_sender)| {
@@ -90,6 +94,8 @@ fn build_consensus(
             context,
             consensus_config.start_height,
             consensus_config.validator_id,
+            signer,
+            consensus_config.wal_path.clone(),
             consensus_config.consensus_delay,
             consensus_config.timeouts.clone(),
             broadcast_channels,
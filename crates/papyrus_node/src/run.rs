@@ -14,7 +14,9 @@ use papyrus_config::presentation::get_config_presentation;
 use papyrus_config::validators::config_validate;
 use papyrus_consensus::config::ConsensusConfig;
 use papyrus_consensus::stream_handler::StreamHandler;
+use papyrus_consensus::validator_set::ValidatorSetProvider;
 use papyrus_consensus_orchestrator::papyrus_consensus_context::PapyrusConsensusContext;
+use papyrus_consensus_orchestrator::staking_validator_set_provider::StakingValidatorSetProvider;
 use papyrus_monitoring_gateway::MonitoringServer;
 use papyrus_network::gossipsub_impl::Topic;
 use papyrus_network::network_manager::{BroadcastTopicChannels, NetworkManager};
@@ -188,6 +190,7 @@ fn spawn_consensus(
     };
     let config = config.clone();
     debug!("Consensus configuration: {config:?}");
+    let signer = config.signer()?;
 
     let network_channels = network_manager
         .register_broadcast_topic(Topic::new(config.network_topic.clone()), BUFFER_SIZE)?;
@@ -202,12 +205,22 @@ fn spawn_consensus(
     let (outbound_internal_sender, inbound_internal_receiver, _) =
         StreamHandler::get_channels(inbound_network_receiver, outbound_network_sender);
 
+    let validator_set_provider: Arc<dyn ValidatorSetProvider> =
+        match config.staking_contract_address {
+            Some(staking_contract_address) => Arc::new(StakingValidatorSetProvider::new(
+                storage_reader.clone(),
+                staking_contract_address,
+                config.validator_set(),
+            )),
+            None => Arc::new(config.validator_set()),
+        };
     let context = PapyrusConsensusContext::new(
         storage_reader.clone(),
         network_channels.broadcast_topic_client.clone(),
         outbound_internal_sender,
-        config.num_validators,
+        validator_set_provider,
         None,
+        signer.clone(),
     );
 
     Ok(tokio::spawn(async move {
@@ -217,6 +230,8 @@ fn spawn_consensus(
             // TODO(Asmaa): replace with the correct value.
             config.start_height,
             config.validator_id,
+            signer,
+            config.wal_path.clone(),
             config.consensus_delay,
             config.timeouts.clone(),
             network_channels.into(),
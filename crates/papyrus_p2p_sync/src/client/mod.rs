@@ -135,9 +135,6 @@ impl Default for P2PSyncClientConfig {
 
 #[derive(thiserror::Error, Debug)]
 pub enum P2PSyncClientError {
-    // TODO(shahak): Remove this and report to network on invalid data once that's possible.
-    #[error("Network returned more responses than expected for a query.")]
-    TooManyResponses,
     #[error(
         "Encountered an old header in the storage at {block_number:?} that's missing the field \
          {missing_field}. Re-sync the node from {block_number:?} from a node that provides this \
@@ -6,7 +6,11 @@ use futures::channel::mpsc::Receiver;
 use futures::future::BoxFuture;
 use futures::stream::BoxStream;
 use futures::StreamExt;
-use papyrus_network::network_manager::{ClientResponsesManager, SqmrClientSender};
+use papyrus_network::network_manager::{
+    ClientResponsesManager,
+    ReputationModifier,
+    SqmrClientSender,
+};
 use papyrus_protobuf::converters::ProtobufConversionError;
 use papyrus_protobuf::sync::{BlockHashOrNumber, DataOrFin, Direction, Query};
 use papyrus_storage::header::HeaderStorageReader;
@@ -20,6 +24,11 @@ use super::{P2PSyncClientError, STEP};
 
 pub type DataStreamResult = Result<Box<dyn BlockData>, P2PSyncClientError>;
 
+// Sync data that violates the protocol may also be sent by a buggy peer, so it takes a few such
+// reports to blacklist the peer.
+const BAD_SYNC_DATA_REPORT: ReputationModifier =
+    ReputationModifier::Misconduct { misconduct_score: 0.5 };
+
 pub(crate) trait BlockData: Send {
     fn write_to_storage(
         // This is Box<Self> in order to allow using it with `Box<dyn BlockData>`.
@@ -92,7 +101,6 @@ where
                     current_block_number.0,
                     end_block_number,
                 );
-                // TODO(shahak): Use the report callback.
                 let mut client_response_manager = sqmr_sender
                     .send_new_query(
                         TQuery::from(Query {
@@ -124,7 +132,7 @@ where
                                  peer and retrying query.",
                                 Self::TYPE_DESCRIPTION, current_block_number, err
                             );
-                            client_response_manager.report_peer();
+                            client_response_manager.report_peer(BAD_SYNC_DATA_REPORT);
                             continue 'send_query_and_parse_responses;
                         },
                         Err(ParseDataError::Fatal(err)) => {
@@ -147,7 +155,13 @@ where
                     Some(Ok(DataOrFin(None))) => {
                         debug!("Query sent to network for {:?} finished", Self::TYPE_DESCRIPTION);
                     },
-                    Some(_) => Err(P2PSyncClientError::TooManyResponses)?,
+                    Some(_) => {
+                        warn!(
+                            "Query for {:?} returned more responses than requested. Reporting peer.",
+                            Self::TYPE_DESCRIPTION
+                        );
+                        client_response_manager.report_peer(BAD_SYNC_DATA_REPORT);
+                    },
                     None => Err(P2PSyncClientError::ReceiverChannelTerminated {
                         type_description: Self::TYPE_DESCRIPTION
                     })?,
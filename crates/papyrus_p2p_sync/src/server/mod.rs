@@ -2,7 +2,7 @@ use std::fmt::Debug;
 
 use futures::StreamExt;
 use papyrus_common::pending_classes::ApiContractClass;
-use papyrus_network::network_manager::{ServerQueryManager, SqmrServerReceiver};
+use papyrus_network::network_manager::{ServerQueryManager, SqmrServerReceiver, MALICIOUS_REPORT};
 use papyrus_protobuf::converters::ProtobufConversionError;
 use papyrus_protobuf::sync::{
     BlockHashOrNumber,
@@ -181,7 +181,7 @@ fn register_query<Data, TQuery>(
         }
         Err(error) => {
             error!("Failed to parse inbound query: {error:?}");
-            server_query_manager.report_peer()
+            server_query_manager.report_peer(MALICIOUS_REPORT)
         }
     }
 }
@@ -1,5 +1,7 @@
+use serde::{Deserialize, Serialize};
 use starknet_api::block::{BlockHash, BlockNumber};
 use starknet_api::core::ContractAddress;
+use starknet_api::crypto::utils::Signature;
 use starknet_api::transaction::{Transaction, TransactionHash};
 
 use crate::converters::ProtobufConversionError;
@@ -15,26 +17,28 @@ pub struct Proposal {
     pub valid_round: Option<u32>,
 }
 
-#[derive(Debug, Default, Hash, Clone, Eq, PartialEq)]
+#[derive(Debug, Default, Hash, Clone, Eq, PartialEq, Serialize, Deserialize)]
 pub enum VoteType {
     Prevote,
     #[default]
     Precommit,
 }
 
-#[derive(Debug, Default, Hash, Clone, Eq, PartialEq)]
+#[derive(Debug, Default, Hash, Clone, Eq, PartialEq, Serialize, Deserialize)]
 pub struct Vote {
     pub vote_type: VoteType,
     pub height: u64,
     pub round: u32,
     pub block_hash: Option<BlockHash>,
     pub voter: ContractAddress,
+    pub signature: Signature,
 }
 
 #[derive(Debug, Clone, Hash, Eq, PartialEq)]
 pub enum ConsensusMessage {
     Proposal(Proposal), // To be deprecated
     Vote(Vote),
+    Misbehavior(Misbehavior),
 }
 
 impl ConsensusMessage {
@@ -42,10 +46,59 @@ impl ConsensusMessage {
         match self {
             ConsensusMessage::Proposal(proposal) => proposal.height,
             ConsensusMessage::Vote(vote) => vote.height,
+            ConsensusMessage::Misbehavior(misbehavior) => misbehavior.height(),
         }
     }
 }
 
+/// Proof that a validator signed two conflicting messages, which an honest validator never does.
+#[derive(Debug, Clone, Hash, Eq, PartialEq)]
+pub enum Misbehavior {
+    ConflictingVotes(ConflictingVotes),
+    ConflictingProposals(ConflictingProposals),
+}
+
+impl Misbehavior {
+    pub fn height(&self) -> u64 {
+        match self {
+            Misbehavior::ConflictingVotes(votes) => votes.first.height,
+            Misbehavior::ConflictingProposals(proposals) => proposals.height.0,
+        }
+    }
+
+    pub fn round(&self) -> u32 {
+        match self {
+            Misbehavior::ConflictingVotes(votes) => votes.first.round,
+            Misbehavior::ConflictingProposals(proposals) => proposals.round,
+        }
+    }
+
+    /// The validator which signed the conflicting messages.
+    pub fn offender(&self) -> ContractAddress {
+        match self {
+            Misbehavior::ConflictingVotes(votes) => votes.first.voter,
+            Misbehavior::ConflictingProposals(proposals) => proposals.proposer,
+        }
+    }
+}
+
+/// Two votes by the same voter, of the same type, height and round, for different blocks.
+#[derive(Debug, Clone, Hash, Eq, PartialEq)]
+pub struct ConflictingVotes {
+    pub first: Vote,
+    pub second: Vote,
+}
+
+/// Two proposal fins by the same proposer, for the same height and round, with different content.
+#[derive(Debug, Clone, Hash, Eq, PartialEq)]
+pub struct ConflictingProposals {
+    pub height: BlockNumber,
+    pub round: u32,
+    pub proposer: ContractAddress,
+    pub first: ProposalFin,
+    pub second: ProposalFin,
+}
+
 #[derive(Debug, Clone, Hash, Eq, PartialEq)]
 pub enum StreamMessageBody<T> {
     Content(T),
@@ -60,7 +113,7 @@ pub struct StreamMessage<T: Into<Vec<u8>> + TryFrom<Vec<u8>, Error = ProtobufCon
 }
 
 /// This message must be sent first when proposing a new block.
-#[derive(Default, Debug, Clone, PartialEq)]
+#[derive(Default, Debug, Clone, Hash, Eq, PartialEq)]
 pub struct ProposalInit {
     /// The height of the consensus (block number).
     pub height: BlockNumber,
@@ -70,6 +123,8 @@ pub struct ProposalInit {
     pub valid_round: Option<u32>,
     /// Address of the one who proposed the block.
     pub proposer: ContractAddress,
+    /// The proposer's signature on the fields above.
+    pub signature: Signature,
 }
 
 /// There is one or more batches of transactions in a proposed block.
@@ -83,11 +138,14 @@ pub struct TransactionBatch {
 }
 
 /// The proposal is done when receiving this fin message, which contains the block hash.
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, Hash, Eq, PartialEq)]
 pub struct ProposalFin {
     /// The block hash of the proposed block.
     /// TODO(guyn): Consider changing the content ID
     pub proposal_content_id: BlockHash,
+    /// The proposer's signature on the content ID, bound to the height, round and proposer given
+    /// in the `ProposalInit`.
+    pub signature: Signature,
 }
 
 /// A part of the proposal.
@@ -116,6 +174,21 @@ impl TryInto<ProposalInit> for ProposalPart {
     }
 }
 
+impl TryInto<ProposalFin> for ProposalPart {
+    type Error = ProtobufConversionError;
+
+    fn try_into(self: ProposalPart) -> Result<ProposalFin, Self::Error> {
+        match self {
+            ProposalPart::Fin(fin) => Ok(fin),
+            _ => Err(ProtobufConversionError::WrongEnumVariant {
+                type_description: "ProposalPart",
+                expected: "Fin",
+                value_as_str: format!("{:?}", self),
+            }),
+        }
+    }
+}
+
 impl From<ProposalInit> for ProposalPart {
     fn from(value: ProposalInit) -> Self {
         ProposalPart::Init(value)
@@ -5,12 +5,16 @@ use std::convert::{TryFrom, TryInto};
 
 use prost::Message;
 use starknet_api::block::{BlockHash, BlockNumber};
+use starknet_api::crypto::utils::Signature;
 use starknet_api::hash::StarkHash;
 use starknet_api::transaction::{Transaction, TransactionHash};
 use starknet_types_core::felt::Felt;
 
 use crate::consensus::{
+    ConflictingProposals,
+    ConflictingVotes,
     ConsensusMessage,
+    Misbehavior,
     Proposal,
     ProposalFin,
     ProposalInit,
@@ -102,8 +106,12 @@ impl TryFrom<protobuf::Vote> for Vote {
             .voter
             .ok_or(ProtobufConversionError::MissingField { field_description: "voter" })?
             .try_into()?;
+        let signature = value
+            .signature
+            .ok_or(ProtobufConversionError::MissingField { field_description: "signature" })?
+            .try_into()?;
 
-        Ok(Vote { vote_type, height, round, block_hash, voter })
+        Ok(Vote { vote_type, height, round, block_hash, voter, signature })
     }
 }
 
@@ -120,6 +128,7 @@ impl From<Vote> for protobuf::Vote {
             round: value.round,
             block_hash: value.block_hash.map(|hash| hash.0.into()),
             voter: Some(value.voter.into()),
+            signature: Some(value.signature.into()),
         }
     }
 }
@@ -210,7 +219,11 @@ impl TryFrom<protobuf::ProposalInit> for ProposalInit {
             .proposer
             .ok_or(ProtobufConversionError::MissingField { field_description: "proposer" })?
             .try_into()?;
-        Ok(ProposalInit { height: BlockNumber(height), round, valid_round, proposer })
+        let signature = value
+            .signature
+            .ok_or(ProtobufConversionError::MissingField { field_description: "signature" })?
+            .try_into()?;
+        Ok(ProposalInit { height: BlockNumber(height), round, valid_round, proposer, signature })
     }
 }
 
@@ -221,6 +234,7 @@ impl From<ProposalInit> for protobuf::ProposalInit {
             round: value.round,
             valid_round: value.valid_round,
             proposer: Some(value.proposer.into()),
+            signature: Some(value.signature.into()),
         }
     }
 }
@@ -266,18 +280,50 @@ impl TryFrom<protobuf::ProposalFin> for ProposalFin {
             })?
             .try_into()?;
         let proposal_content_id = BlockHash(proposal_content_id);
-        Ok(ProposalFin { proposal_content_id })
+        let signature = value
+            .signature
+            .ok_or(ProtobufConversionError::MissingField { field_description: "signature" })?
+            .try_into()?;
+        Ok(ProposalFin { proposal_content_id, signature })
     }
 }
 
 impl From<ProposalFin> for protobuf::ProposalFin {
     fn from(value: ProposalFin) -> Self {
-        protobuf::ProposalFin { proposal_content_id: Some(value.proposal_content_id.0.into()) }
+        protobuf::ProposalFin {
+            proposal_content_id: Some(value.proposal_content_id.0.into()),
+            signature: Some(value.signature.into()),
+        }
     }
 }
 
 auto_impl_into_and_try_from_vec_u8!(ProposalFin, protobuf::ProposalFin);
 
+impl TryFrom<protobuf::ConsensusSignature> for Signature {
+    type Error = ProtobufConversionError;
+    fn try_from(value: protobuf::ConsensusSignature) -> Result<Self, Self::Error> {
+        let r = value
+            .r
+            .ok_or(ProtobufConversionError::MissingField {
+                field_description: "ConsensusSignature::r",
+            })?
+            .try_into()?;
+        let s = value
+            .s
+            .ok_or(ProtobufConversionError::MissingField {
+                field_description: "ConsensusSignature::s",
+            })?
+            .try_into()?;
+        Ok(Signature { r, s })
+    }
+}
+
+impl From<Signature> for protobuf::ConsensusSignature {
+    fn from(value: Signature) -> Self {
+        protobuf::ConsensusSignature { r: Some(value.r.into()), s: Some(value.s.into()) }
+    }
+}
+
 impl TryFrom<protobuf::ProposalPart> for ProposalPart {
     type Error = ProtobufConversionError;
     fn try_from(value: protobuf::ProposalPart) -> Result<Self, Self::Error> {
@@ -327,6 +373,9 @@ impl TryFrom<protobuf::ConsensusMessage> for ConsensusMessage {
         match message {
             Message::Proposal(proposal) => Ok(ConsensusMessage::Proposal(proposal.try_into()?)),
             Message::Vote(vote) => Ok(ConsensusMessage::Vote(vote.try_into()?)),
+            Message::Misbehavior(misbehavior) => {
+                Ok(ConsensusMessage::Misbehavior(misbehavior.try_into()?))
+            }
         }
     }
 }
@@ -340,8 +389,109 @@ impl From<ConsensusMessage> for protobuf::ConsensusMessage {
             ConsensusMessage::Vote(vote) => protobuf::ConsensusMessage {
                 message: Some(protobuf::consensus_message::Message::Vote(vote.into())),
             },
+            ConsensusMessage::Misbehavior(misbehavior) => protobuf::ConsensusMessage {
+                message: Some(protobuf::consensus_message::Message::Misbehavior(
+                    misbehavior.into(),
+                )),
+            },
         }
     }
 }
 
 auto_impl_into_and_try_from_vec_u8!(ConsensusMessage, protobuf::ConsensusMessage);
+
+impl TryFrom<protobuf::ConflictingVotes> for ConflictingVotes {
+    type Error = ProtobufConversionError;
+
+    fn try_from(value: protobuf::ConflictingVotes) -> Result<Self, Self::Error> {
+        let first = value
+            .first
+            .ok_or(ProtobufConversionError::MissingField { field_description: "first" })?
+            .try_into()?;
+        let second = value
+            .second
+            .ok_or(ProtobufConversionError::MissingField { field_description: "second" })?
+            .try_into()?;
+        Ok(ConflictingVotes { first, second })
+    }
+}
+
+impl From<ConflictingVotes> for protobuf::ConflictingVotes {
+    fn from(value: ConflictingVotes) -> Self {
+        protobuf::ConflictingVotes {
+            first: Some(value.first.into()),
+            second: Some(value.second.into()),
+        }
+    }
+}
+
+impl TryFrom<protobuf::ConflictingProposals> for ConflictingProposals {
+    type Error = ProtobufConversionError;
+
+    fn try_from(value: protobuf::ConflictingProposals) -> Result<Self, Self::Error> {
+        let height = BlockNumber(value.height);
+        let round = value.round;
+        let proposer = value
+            .proposer
+            .ok_or(ProtobufConversionError::MissingField { field_description: "proposer" })?
+            .try_into()?;
+        let first = value
+            .first
+            .ok_or(ProtobufConversionError::MissingField { field_description: "first" })?
+            .try_into()?;
+        let second = value
+            .second
+            .ok_or(ProtobufConversionError::MissingField { field_description: "second" })?
+            .try_into()?;
+        Ok(ConflictingProposals { height, round, proposer, first, second })
+    }
+}
+
+impl From<ConflictingProposals> for protobuf::ConflictingProposals {
+    fn from(value: ConflictingProposals) -> Self {
+        protobuf::ConflictingProposals {
+            height: value.height.0,
+            round: value.round,
+            proposer: Some(value.proposer.into()),
+            first: Some(value.first.into()),
+            second: Some(value.second.into()),
+        }
+    }
+}
+
+impl TryFrom<protobuf::Misbehavior> for Misbehavior {
+    type Error = ProtobufConversionError;
+
+    fn try_from(value: protobuf::Misbehavior) -> Result<Self, Self::Error> {
+        use protobuf::misbehavior::Evidence;
+
+        let Some(evidence) = value.evidence else {
+            return Err(ProtobufConversionError::MissingField { field_description: "evidence" });
+        };
+
+        match evidence {
+            Evidence::ConflictingVotes(votes) => {
+                Ok(Misbehavior::ConflictingVotes(votes.try_into()?))
+            }
+            Evidence::ConflictingProposals(proposals) => {
+                Ok(Misbehavior::ConflictingProposals(proposals.try_into()?))
+            }
+        }
+    }
+}
+
+impl From<Misbehavior> for protobuf::Misbehavior {
+    fn from(value: Misbehavior) -> Self {
+        use protobuf::misbehavior::Evidence;
+
+        let evidence = match value {
+            Misbehavior::ConflictingVotes(votes) => Evidence::ConflictingVotes(votes.into()),
+            Misbehavior::ConflictingProposals(proposals) => {
+                Evidence::ConflictingProposals(proposals.into())
+            }
+        };
+        protobuf::Misbehavior { evidence: Some(evidence) }
+    }
+}
+
+auto_impl_into_and_try_from_vec_u8!(Misbehavior, protobuf::Misbehavior);
@@ -13,6 +13,7 @@ use starknet_api::transaction::{
 
 use crate::consensus::{
     ConsensusMessage,
+    Misbehavior,
     Proposal,
     ProposalFin,
     ProposalInit,
@@ -93,6 +94,17 @@ fn convert_vote_to_vec_u8_and_back() {
     assert_eq!(vote, res_data);
 }
 
+#[test]
+fn convert_misbehavior_to_vec_u8_and_back() {
+    let mut rng = get_rng();
+
+    let misbehavior = Misbehavior::get_test_instance(&mut rng);
+
+    let bytes_data: Vec<u8> = misbehavior.clone().into();
+    let res_data = Misbehavior::try_from(bytes_data).unwrap();
+    assert_eq!(misbehavior, res_data);
+}
+
 #[test]
 fn convert_proposal_to_vec_u8_and_back() {
     let mut rng = get_rng();
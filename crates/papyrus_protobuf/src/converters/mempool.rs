@@ -0,0 +1,99 @@
+#[cfg(test)]
+#[path = "mempool_test.rs"]
+mod mempool_test;
+
+use prost::Message;
+use starknet_api::transaction::TransactionHash;
+
+use super::ProtobufConversionError;
+use crate::mempool::{MempoolTransactionsQuery, NewTransactionHashes, RpcTransactionWrapper};
+use crate::sync::DataOrFin;
+use crate::{auto_impl_into_and_try_from_vec_u8, protobuf};
+
+fn transaction_hashes_from_protobuf(
+    hashes: Vec<protobuf::Hash>,
+) -> Result<Vec<TransactionHash>, ProtobufConversionError> {
+    hashes
+        .into_iter()
+        .map(|hash| starknet_api::hash::StarkHash::try_from(hash).map(TransactionHash))
+        .collect()
+}
+
+fn transaction_hashes_to_protobuf(hashes: Vec<TransactionHash>) -> Vec<protobuf::Hash> {
+    hashes.into_iter().map(|hash| hash.0.into()).collect()
+}
+
+impl TryFrom<protobuf::NewTransactionHashes> for NewTransactionHashes {
+    type Error = ProtobufConversionError;
+    fn try_from(value: protobuf::NewTransactionHashes) -> Result<Self, Self::Error> {
+        Ok(NewTransactionHashes(transaction_hashes_from_protobuf(value.transaction_hashes)?))
+    }
+}
+
+impl From<NewTransactionHashes> for protobuf::NewTransactionHashes {
+    fn from(value: NewTransactionHashes) -> Self {
+        protobuf::NewTransactionHashes {
+            transaction_hashes: transaction_hashes_to_protobuf(value.0),
+        }
+    }
+}
+
+auto_impl_into_and_try_from_vec_u8!(NewTransactionHashes, protobuf::NewTransactionHashes);
+
+impl TryFrom<protobuf::MempoolTransactionsRequest> for MempoolTransactionsQuery {
+    type Error = ProtobufConversionError;
+    fn try_from(value: protobuf::MempoolTransactionsRequest) -> Result<Self, Self::Error> {
+        Ok(MempoolTransactionsQuery(transaction_hashes_from_protobuf(value.transaction_hashes)?))
+    }
+}
+
+impl From<MempoolTransactionsQuery> for protobuf::MempoolTransactionsRequest {
+    fn from(value: MempoolTransactionsQuery) -> Self {
+        protobuf::MempoolTransactionsRequest {
+            transaction_hashes: transaction_hashes_to_protobuf(value.0),
+        }
+    }
+}
+
+auto_impl_into_and_try_from_vec_u8!(MempoolTransactionsQuery, protobuf::MempoolTransactionsRequest);
+
+impl TryFrom<protobuf::MempoolTransactionsResponse> for DataOrFin<RpcTransactionWrapper> {
+    type Error = ProtobufConversionError;
+    fn try_from(value: protobuf::MempoolTransactionsResponse) -> Result<Self, Self::Error> {
+        let Some(transaction_message) = value.transaction_message else {
+            return Err(ProtobufConversionError::MissingField {
+                field_description: "MempoolTransactionsResponse::transaction_message",
+            });
+        };
+
+        match transaction_message {
+            protobuf::mempool_transactions_response::TransactionMessage::Transaction(
+                transaction,
+            ) => Ok(DataOrFin(Some(transaction.try_into()?))),
+            protobuf::mempool_transactions_response::TransactionMessage::Fin(_) => {
+                Ok(DataOrFin(None))
+            }
+        }
+    }
+}
+
+impl From<DataOrFin<RpcTransactionWrapper>> for protobuf::MempoolTransactionsResponse {
+    fn from(value: DataOrFin<RpcTransactionWrapper>) -> Self {
+        let transaction_message = match value.0 {
+            Some(transaction) => {
+                protobuf::mempool_transactions_response::TransactionMessage::Transaction(
+                    transaction.into(),
+                )
+            }
+            None => {
+                protobuf::mempool_transactions_response::TransactionMessage::Fin(protobuf::Fin {})
+            }
+        };
+        protobuf::MempoolTransactionsResponse { transaction_message: Some(transaction_message) }
+    }
+}
+
+auto_impl_into_and_try_from_vec_u8!(
+    DataOrFin<RpcTransactionWrapper>,
+    protobuf::MempoolTransactionsResponse
+);
@@ -0,0 +1,50 @@
+use papyrus_test_utils::{get_rng, GetTestInstance};
+use starknet_api::block::GasPrice;
+use starknet_api::execution_resources::GasAmount;
+use starknet_api::rpc_transaction::{RpcInvokeTransaction, RpcInvokeTransactionV3, RpcTransaction};
+use starknet_api::transaction::fields::{AllResourceBounds, ResourceBounds};
+use starknet_api::transaction::TransactionHash;
+use starknet_types_core::felt::Felt;
+
+use crate::mempool::{MempoolTransactionsQuery, NewTransactionHashes, RpcTransactionWrapper};
+use crate::sync::DataOrFin;
+
+fn transaction_hashes() -> Vec<TransactionHash> {
+    vec![TransactionHash(Felt::ONE), TransactionHash(Felt::TWO), TransactionHash(Felt::THREE)]
+}
+
+#[test]
+fn convert_new_transaction_hashes_to_vec_u8_and_back() {
+    let new_transaction_hashes = NewTransactionHashes(transaction_hashes());
+
+    let bytes_data = Vec::<u8>::from(new_transaction_hashes.clone());
+    let res_data = NewTransactionHashes::try_from(bytes_data).unwrap();
+    assert_eq!(new_transaction_hashes, res_data);
+}
+
+#[test]
+fn convert_mempool_transactions_query_to_vec_u8_and_back() {
+    let query = MempoolTransactionsQuery(transaction_hashes());
+
+    let bytes_data = Vec::<u8>::from(query.clone());
+    let res_data = MempoolTransactionsQuery::try_from(bytes_data).unwrap();
+    assert_eq!(query, res_data);
+}
+
+#[test]
+fn convert_mempool_transactions_response_to_vec_u8_and_back() {
+    let mut rpc_transaction = RpcInvokeTransactionV3::get_test_instance(&mut get_rng());
+    // If all the resource bounds are 0, the deserialized value will be interpreted as L1Gas.
+    rpc_transaction.resource_bounds = AllResourceBounds {
+        l1_gas: ResourceBounds { max_amount: GasAmount(0x5), max_price_per_unit: GasPrice(0x6) },
+        ..Default::default()
+    };
+    let transaction =
+        RpcTransactionWrapper(RpcTransaction::Invoke(RpcInvokeTransaction::V3(rpc_transaction)));
+
+    for response in [DataOrFin(Some(transaction)), DataOrFin(None)] {
+        let bytes_data = Vec::<u8>::from(response.clone());
+        let res_data = DataOrFin::<RpcTransactionWrapper>::try_from(bytes_data).unwrap();
+        assert_eq!(response, res_data);
+    }
+}
@@ -4,6 +4,7 @@ mod common;
 pub mod consensus;
 mod event;
 mod header;
+mod mempool;
 mod receipt;
 pub mod rpc_transaction;
 mod state_diff;
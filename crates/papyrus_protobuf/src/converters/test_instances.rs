@@ -2,10 +2,14 @@ use papyrus_test_utils::{auto_impl_get_test_instance, get_number_of_variants, Ge
 use rand::Rng;
 use starknet_api::block::{BlockHash, BlockNumber};
 use starknet_api::core::ContractAddress;
+use starknet_api::crypto::utils::Signature;
 use starknet_api::transaction::{Transaction, TransactionHash};
 
 use crate::consensus::{
+    ConflictingProposals,
+    ConflictingVotes,
     ConsensusMessage,
+    Misbehavior,
     Proposal,
     ProposalFin,
     ProposalInit,
@@ -21,6 +25,22 @@ auto_impl_get_test_instance! {
     pub enum ConsensusMessage {
         Proposal(Proposal) = 0,
         Vote(Vote) = 1,
+        Misbehavior(Misbehavior) = 2,
+    }
+    pub enum Misbehavior {
+        ConflictingVotes(ConflictingVotes) = 0,
+        ConflictingProposals(ConflictingProposals) = 1,
+    }
+    pub struct ConflictingVotes {
+        pub first: Vote,
+        pub second: Vote,
+    }
+    pub struct ConflictingProposals {
+        pub height: BlockNumber,
+        pub round: u32,
+        pub proposer: ContractAddress,
+        pub first: ProposalFin,
+        pub second: ProposalFin,
     }
     pub struct Proposal {
         pub height: u64,
@@ -36,6 +56,7 @@ auto_impl_get_test_instance! {
         pub round: u32,
         pub block_hash: Option<BlockHash>,
         pub voter: ContractAddress,
+        pub signature: Signature,
     }
     pub enum VoteType {
         Prevote = 0,
@@ -46,9 +67,11 @@ auto_impl_get_test_instance! {
         pub round: u32,
         pub valid_round: Option<u32>,
         pub proposer: ContractAddress,
+        pub signature: Signature,
     }
     pub struct ProposalFin {
         pub proposal_content_id: BlockHash,
+        pub signature: Signature,
     }
     pub struct TransactionBatch {
         pub transactions: Vec<Transaction>,
@@ -1,4 +1,16 @@
 use starknet_api::rpc_transaction::RpcTransaction;
+use starknet_api::transaction::TransactionHash;
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct RpcTransactionWrapper(pub RpcTransaction);
+
+/// An announcement of transactions that the sender has in its mempool. Peers that don't know some
+/// of these transactions request them with a [`MempoolTransactionsQuery`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct NewTransactionHashes(pub Vec<TransactionHash>);
+
+/// A query for the mempool transactions with the given hashes. It is answered with a
+/// `DataOrFin<RpcTransactionWrapper>` for each of these transactions that the responder has,
+/// followed by a fin.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MempoolTransactionsQuery(pub Vec<TransactionHash>);
@@ -90,6 +90,7 @@ pub mod header;
 pub mod mmap_file;
 mod serialization;
 pub mod state;
+pub mod state_tries;
 mod version;
 
 mod deprecated;
@@ -151,11 +152,12 @@ use crate::db::{
 use crate::header::StorageBlockHeader;
 use crate::mmap_file::MMapFileStats;
 use crate::state::data::IndexedDeprecatedContractClass;
+use crate::state_tries::StateTriesRoots;
 use crate::version::{VersionStorageReader, VersionStorageWriter};
 
 // For more details on the storage version, see the module documentation.
 /// The current version of the storage state code.
-pub const STORAGE_VERSION_STATE: Version = Version { major: 4, minor: 0 };
+pub const STORAGE_VERSION_STATE: Version = Version { major: 4, minor: 1 };
 /// The current version of the storage blocks code.
 pub const STORAGE_VERSION_BLOCKS: Version = Version { major: 4, minor: 0 };
 
@@ -187,6 +189,8 @@ pub fn open_storage(
         nonces: db_writer.create_common_prefix_table("nonces")?,
         file_offsets: db_writer.create_simple_table("file_offsets")?,
         state_diffs: db_writer.create_simple_table("state_diffs")?,
+        state_tries_facts: db_writer.create_simple_table("state_tries_facts")?,
+        state_tries_roots: db_writer.create_simple_table("state_tries_roots")?,
         transaction_hash_to_idx: db_writer.create_simple_table("transaction_hash_to_idx")?,
         transaction_metadata: db_writer.create_simple_table("transaction_metadata")?,
 
@@ -534,6 +538,8 @@ struct_field_names! {
         nonces: TableIdentifier<(ContractAddress, BlockNumber), VersionZeroWrapper<Nonce>, CommonPrefix>,
         file_offsets: TableIdentifier<OffsetKind, NoVersionValueWrapper<usize>, SimpleTable>,
         state_diffs: TableIdentifier<BlockNumber, VersionZeroWrapper<LocationInFile>, SimpleTable>,
+        state_tries_facts: TableIdentifier<Vec<u8>, NoVersionValueWrapper<Vec<u8>>, SimpleTable>,
+        state_tries_roots: TableIdentifier<BlockNumber, VersionZeroWrapper<StateTriesRoots>, SimpleTable>,
         transaction_hash_to_idx: TableIdentifier<TransactionHash, NoVersionValueWrapper<TransactionIndex>, SimpleTable>,
         // TODO(dvir): consider not saving transaction hash and calculating it from the transaction on demand.
         transaction_metadata: TableIdentifier<TransactionIndex, VersionZeroWrapper<TransactionMetadata>, SimpleTable>,
@@ -665,6 +671,7 @@ pub(crate) enum MarkerKind {
     Class,
     CompiledClass,
     BaseLayerBlock,
+    StateTries,
 }
 
 pub(crate) type MarkersTable<'env> =
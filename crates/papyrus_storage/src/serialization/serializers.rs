@@ -142,6 +142,7 @@ use crate::mmap_file::LocationInFile;
 #[cfg(test)]
 use crate::serialization::serializers_test::{create_storage_serde_test, StorageSerdeTest};
 use crate::state::data::IndexedDeprecatedContractClass;
+use crate::state_tries::StateTriesRoots;
 use crate::version::Version;
 use crate::{MarkerKind, OffsetKind, TransactionMetadata};
 
@@ -325,6 +326,7 @@ auto_storage_serde! {
         Class = 4,
         CompiledClass = 5,
         BaseLayerBlock = 6,
+        StateTries = 7,
     }
     pub struct MessageToL1 {
         pub to_address: EthAddress,
@@ -414,6 +416,10 @@ auto_storage_serde! {
         V0_13_4 = 19,
     }
     pub struct StateDiffCommitment(pub PoseidonHash);
+    pub struct StateTriesRoots {
+        pub contracts_trie_root: StarkHash,
+        pub classes_trie_root: StarkHash,
+    }
     pub struct Tip(pub u64);
     pub struct TransactionCommitment(pub StarkHash);
     pub struct TypedParameter {
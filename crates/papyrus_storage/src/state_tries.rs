@@ -0,0 +1,151 @@
+//! Interface for handling the Patricia tries of the state.
+//!
+//! The tries are stored as facts: each node of a trie is stored under a key derived from its hash.
+//! Since nodes are addressed by their content, a node may be shared by the tries of several blocks,
+//! and facts are never overwritten with a different value. Along with the facts, the roots of the
+//! tries are stored for each block.
+//!
+//! Import [`StateTriesStorageReader`] and [`StateTriesStorageWriter`] to read and write the tries
+//! using a [`StorageTxn`].
+//! # Example
+//! ```
+//! use papyrus_storage::open_storage;
+//! use papyrus_storage::state_tries::{
+//!     StateTriesRoots,
+//!     StateTriesStorageReader,
+//!     StateTriesStorageWriter,
+//! };
+//! # use papyrus_storage::{db::DbConfig, StorageConfig};
+//! # use starknet_api::core::ChainId;
+//! use starknet_api::block::BlockNumber;
+//! use starknet_api::hash::StarkHash;
+//!
+//! # let dir_handle = tempfile::tempdir().unwrap();
+//! # let dir = dir_handle.path().to_path_buf();
+//! # let db_config = DbConfig {
+//! #     path_prefix: dir,
+//! #     chain_id: ChainId::Mainnet,
+//! #     enforce_file_exists: false,
+//! #     min_size: 1 << 20,    // 1MB
+//! #     max_size: 1 << 35,    // 32GB
+//! #     growth_step: 1 << 26, // 64MB
+//! # };
+//! # let storage_config = StorageConfig{db_config, ..Default::default()};
+//! let roots = StateTriesRoots {
+//!     contracts_trie_root: StarkHash::ONE,
+//!     classes_trie_root: StarkHash::ZERO,
+//! };
+//! let (reader, mut writer) = open_storage(storage_config)?;
+//! writer
+//!     .begin_rw_txn()?                                                    // Start a RW transaction.
+//!     .append_state_tries(BlockNumber(0), &roots, &[(vec![1], vec![2])])? // Append the tries.
+//!     .commit()?;                                                         // Commit the changes.
+//! let txn = reader.begin_ro_txn()?;
+//! assert_eq!(txn.get_state_tries_marker()?, BlockNumber(1));
+//! assert_eq!(txn.get_state_tries_roots(BlockNumber(0))?, Some(roots));
+//! assert_eq!(txn.get_state_tries_facts(&[vec![1], vec![3]])?, vec![Some(vec![2]), None]);
+//! # Ok::<(), papyrus_storage::StorageError>(())
+//! ```
+#[cfg(test)]
+#[path = "state_tries_test.rs"]
+mod state_tries_test;
+
+use starknet_api::block::BlockNumber;
+use starknet_api::hash::StarkHash;
+
+use crate::db::table_types::Table;
+use crate::db::{TransactionKind, RW};
+use crate::{MarkerKind, StorageError, StorageResult, StorageTxn};
+
+/// The roots of the Patricia tries of the state after a block.
+#[derive(Debug, Default, Clone, Copy, Eq, PartialEq)]
+pub struct StateTriesRoots {
+    /// The root of the trie of the contracts' states.
+    pub contracts_trie_root: StarkHash,
+    /// The root of the trie of the compiled class hashes.
+    pub classes_trie_root: StarkHash,
+}
+
+/// Interface for reading the Patricia tries of the state.
+pub trait StateTriesStorageReader {
+    /// The state tries marker is the first block number whose tries don't exist yet.
+    fn get_state_tries_marker(&self) -> StorageResult<BlockNumber>;
+
+    /// Returns the roots of the tries after the given block, if they exist.
+    fn get_state_tries_roots(
+        &self,
+        block_number: BlockNumber,
+    ) -> StorageResult<Option<StateTriesRoots>>;
+
+    /// Returns the values of the facts with the given keys, in the same order. The value of a
+    /// missing fact is `None`.
+    fn get_state_tries_facts(&self, keys: &[Vec<u8>]) -> StorageResult<Vec<Option<Vec<u8>>>>;
+}
+
+/// Interface for writing the Patricia tries of the state.
+pub trait StateTriesStorageWriter
+where
+    Self: Sized,
+{
+    /// Appends the tries of the given block: the facts it added, and the resulting roots.
+    // To enforce that no commit happen after a failure, we consume and return Self on success.
+    fn append_state_tries(
+        self,
+        block_number: BlockNumber,
+        roots: &StateTriesRoots,
+        new_facts: &[(Vec<u8>, Vec<u8>)],
+    ) -> StorageResult<Self>;
+}
+
+impl<Mode: TransactionKind> StateTriesStorageReader for StorageTxn<'_, Mode> {
+    fn get_state_tries_marker(&self) -> StorageResult<BlockNumber> {
+        let markers_table = self.open_table(&self.tables.markers)?;
+        Ok(markers_table.get(&self.txn, &MarkerKind::StateTries)?.unwrap_or_default())
+    }
+
+    fn get_state_tries_roots(
+        &self,
+        block_number: BlockNumber,
+    ) -> StorageResult<Option<StateTriesRoots>> {
+        let state_tries_roots_table = self.open_table(&self.tables.state_tries_roots)?;
+        Ok(state_tries_roots_table.get(&self.txn, &block_number)?)
+    }
+
+    fn get_state_tries_facts(&self, keys: &[Vec<u8>]) -> StorageResult<Vec<Option<Vec<u8>>>> {
+        let state_tries_facts_table = self.open_table(&self.tables.state_tries_facts)?;
+        let mut values = Vec::with_capacity(keys.len());
+        for key in keys {
+            values.push(state_tries_facts_table.get(&self.txn, key)?);
+        }
+        Ok(values)
+    }
+}
+
+impl StateTriesStorageWriter for StorageTxn<'_, RW> {
+    fn append_state_tries(
+        self,
+        block_number: BlockNumber,
+        roots: &StateTriesRoots,
+        new_facts: &[(Vec<u8>, Vec<u8>)],
+    ) -> StorageResult<Self> {
+        let markers_table = self.open_table(&self.tables.markers)?;
+        let state_tries_roots_table = self.open_table(&self.tables.state_tries_roots)?;
+        let state_tries_facts_table = self.open_table(&self.tables.state_tries_facts)?;
+
+        let state_tries_marker = self.get_state_tries_marker()?;
+        if state_tries_marker != block_number {
+            return Err(StorageError::MarkerMismatch {
+                expected: state_tries_marker,
+                found: block_number,
+            });
+        }
+
+        // A fact may already exist, if an identical node was added by an earlier block.
+        for (key, value) in new_facts {
+            state_tries_facts_table.upsert(&self.txn, key, value)?;
+        }
+        state_tries_roots_table.insert(&self.txn, &block_number, roots)?;
+        markers_table.upsert(&self.txn, &MarkerKind::StateTries, &block_number.unchecked_next())?;
+        Ok(self)
+    }
+}
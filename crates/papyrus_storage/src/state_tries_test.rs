@@ -0,0 +1,62 @@
+use assert_matches::assert_matches;
+use starknet_api::block::BlockNumber;
+use starknet_api::hash::StarkHash;
+
+use crate::state_tries::{StateTriesRoots, StateTriesStorageReader, StateTriesStorageWriter};
+use crate::test_utils::get_test_storage;
+use crate::StorageError;
+
+#[test]
+fn append_state_tries() {
+    let (reader, mut writer) = get_test_storage().0;
+    let first_roots =
+        StateTriesRoots { contracts_trie_root: StarkHash::ONE, classes_trie_root: StarkHash::ZERO };
+    let second_roots =
+        StateTriesRoots { contracts_trie_root: StarkHash::TWO, classes_trie_root: StarkHash::ONE };
+
+    writer
+        .begin_rw_txn()
+        .unwrap()
+        .append_state_tries(BlockNumber(0), &first_roots, &[(vec![1], vec![10])])
+        .unwrap()
+        .commit()
+        .unwrap();
+    // A fact that already exists is added again.
+    writer
+        .begin_rw_txn()
+        .unwrap()
+        .append_state_tries(
+            BlockNumber(1),
+            &second_roots,
+            &[(vec![1], vec![10]), (vec![2], vec![20])],
+        )
+        .unwrap()
+        .commit()
+        .unwrap();
+
+    let txn = reader.begin_ro_txn().unwrap();
+    assert_eq!(txn.get_state_tries_marker().unwrap(), BlockNumber(2));
+    assert_eq!(txn.get_state_tries_roots(BlockNumber(0)).unwrap(), Some(first_roots));
+    assert_eq!(txn.get_state_tries_roots(BlockNumber(1)).unwrap(), Some(second_roots));
+    assert_eq!(txn.get_state_tries_roots(BlockNumber(2)).unwrap(), None);
+    assert_eq!(
+        txn.get_state_tries_facts(&[vec![2], vec![3], vec![1]]).unwrap(),
+        vec![Some(vec![20]), None, Some(vec![10])]
+    );
+}
+
+#[test]
+fn append_state_tries_marker_mismatch() {
+    let (_, mut writer) = get_test_storage().0;
+
+    let result = writer.begin_rw_txn().unwrap().append_state_tries(
+        BlockNumber(1),
+        &StateTriesRoots::default(),
+        &[],
+    );
+
+    assert_matches!(
+        result,
+        Err(StorageError::MarkerMismatch { expected: BlockNumber(0), found: BlockNumber(1) })
+    );
+}
@@ -9,6 +9,7 @@ use starknet_api::core::{
     TransactionCommitment,
 };
 use starknet_api::data_availability::L1DataAvailabilityMode;
+use starknet_api::hash::StarkHash;
 use starknet_api::transaction::{
     EventIndexInTransactionOutput,
     TransactionHash,
@@ -20,6 +21,7 @@ use crate::compression_utils::IsCompressed;
 use crate::header::StorageBlockHeader;
 use crate::mmap_file::LocationInFile;
 use crate::state::data::IndexedDeprecatedContractClass;
+use crate::state_tries::StateTriesRoots;
 use crate::version::Version;
 use crate::{EventIndex, MarkerKind, OffsetKind, TransactionMetadata};
 
@@ -61,6 +63,7 @@ auto_impl_get_test_instance! {
         Class = 4,
         CompiledClass = 5,
         BaseLayerBlock = 6,
+        StateTries = 7,
     }
     pub enum OffsetKind {
         ThinStateDiff = 0,
@@ -68,6 +71,10 @@ auto_impl_get_test_instance! {
         Casm = 2,
         DeprecatedContractClass = 3,
     }
+    pub struct StateTriesRoots {
+        pub contracts_trie_root: StarkHash,
+        pub classes_trie_root: StarkHash,
+    }
     pub struct TransactionMetadata{
         pub tx_hash: TransactionHash,
         pub tx_location: LocationInFile,
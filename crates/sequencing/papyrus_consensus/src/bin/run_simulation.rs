@@ -14,6 +14,8 @@ use fs2::FileExt;
 use lazy_static::lazy_static;
 use nix::unistd::Pid;
 use papyrus_common::tcp::find_free_port;
+use papyrus_consensus::signature::public_key_from;
+use starknet_types_core::felt::Felt;
 use tokio::process::Command as TokioCommand;
 
 lazy_static! {
@@ -270,14 +272,19 @@ async fn build_node(data_dir: &str, logs_dir: &str, i: usize, papyrus_args: &Pap
         "RUST_LOG=papyrus_consensus=debug,papyrus=info target/release/run_consensus \
          --network.#is_none false --base_layer.node_url {} --storage.db_config.path_prefix {} \
          --consensus.#is_none false --consensus.validator_id 0x{} --consensus.num_validators {} \
-         --network.tcp_port {} --rpc.server_address 127.0.0.1:{} \
-         --monitoring_gateway.server_address 127.0.0.1:{} --collect_metrics true ",
+         --consensus.signing_key.#is_none false --consensus.signing_key {:#x} \
+         --consensus.validator_public_keys {} --network.tcp_port {} --network.quic_port {} \
+         --rpc.server_address 127.0.0.1:{} --monitoring_gateway.server_address 127.0.0.1:{} \
+         --collect_metrics true ",
         papyrus_args.base_layer_node_url,
         data_dir,
         i,
         papyrus_args.num_validators,
+        signing_key(i),
+        validator_public_keys(papyrus_args.num_validators),
         tcp_port,
         find_free_port(),
+        find_free_port(),
         monitoring_gateway_server_port
     );
 
@@ -325,6 +332,18 @@ async fn build_node(data_dir: &str, logs_dir: &str, i: usize, papyrus_args: &Pap
     Node::new(i, monitoring_gateway_server_port, cmd)
 }
 
+// The simulated validators sign with INSECURE keys, derived from their index.
+fn signing_key(i: usize) -> Felt {
+    Felt::from(i + 1)
+}
+
+fn validator_public_keys(num_validators: usize) -> String {
+    (0..num_validators)
+        .map(|i| format!("{:#x}", public_key_from(&signing_key(i)).0))
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
 async fn build_all_nodes(data_dir: &str, logs_dir: &str, papyrus_args: &PapyrusArgs) -> Vec<Node> {
     // Validators are started in a specific order to ensure proper network formation:
     // 1. The bootnode (validator 1) is started first for network peering.
@@ -2,33 +2,56 @@
 //! and its implementation of the `SerializeConfig` trait. The configuration includes parameters
 //! such as the validator ID, the network topic of the consensus, and the starting block height.
 
+#[cfg(test)]
+#[path = "config_test.rs"]
+mod config_test;
+
 use std::collections::BTreeMap;
+use std::path::PathBuf;
 use std::time::Duration;
 
 use papyrus_config::converters::{
     deserialize_float_seconds_to_duration,
     deserialize_seconds_to_duration,
 };
-use papyrus_config::dumping::{append_sub_config_name, ser_param, SerializeConfig};
+use papyrus_config::dumping::{
+    append_sub_config_name,
+    ser_optional_param,
+    ser_param,
+    SerializeConfig,
+};
 use papyrus_config::{ParamPath, ParamPrivacyInput, SerializedParam};
 use papyrus_network::NetworkConfig;
-use serde::{Deserialize, Serialize};
+use serde::de::Error;
+use serde::{Deserialize, Deserializer, Serialize};
 use starknet_api::block::BlockNumber;
-use starknet_api::core::ChainId;
+use starknet_api::core::{ChainId, ContractAddress};
+use starknet_api::crypto::utils::PublicKey;
+use starknet_types_core::felt::Felt;
+use validator::{Validate, ValidationError};
 
 use super::types::ValidatorId;
-use crate::types::DEFAULT_VALIDATOR_ID;
+use crate::signature::ConsensusSigner;
+use crate::types::{ConsensusError, DEFAULT_VALIDATOR_ID};
+use crate::validator_set::{ValidatorSet, VotingPower};
 
 const CONSENSUS_TCP_PORT: u16 = 10100;
 const CONSENSUS_QUIC_PORT: u16 = 10101;
 
 /// Configuration for consensus.
-#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq, Validate)]
+#[validate(schema(function = "validate_consensus_config"))]
 pub struct ConsensusConfig {
     /// The chain id of the Starknet chain.
     pub chain_id: ChainId,
     /// The validator ID of the node.
     pub validator_id: ValidatorId,
+    /// The Stark private key the node signs its consensus messages with. Required to run
+    /// consensus.
+    pub signing_key: Option<Felt>,
+    /// The path of the consensus write-ahead log, see [`crate::wal`]. If unset, a node which
+    /// restarts mid-height may equivocate.
+    pub wal_path: Option<PathBuf>,
     /// The network topic of the consensus.
     pub network_topic: String,
     /// The height to start the consensus from.
@@ -36,6 +59,17 @@ pub struct ConsensusConfig {
     /// The number of validators in the consensus.
     // Used for testing in an early milestones.
     pub num_validators: u64,
+    /// The voting power of each of the `num_validators` validators, ordered by validator ID. Empty
+    /// means all validators have equal voting power.
+    #[serde(deserialize_with = "deserialize_voting_powers")]
+    pub validator_voting_powers: Vec<VotingPower>,
+    /// The public key of each of the `num_validators` validators, ordered by validator ID. Empty
+    /// means the keys are unknown, so no messages from other validators are accepted.
+    #[serde(deserialize_with = "deserialize_public_keys")]
+    pub validator_public_keys: Vec<PublicKey>,
+    /// The address of the staking contract to read the validator set from. If unset, the
+    /// validator set is taken from this config.
+    pub staking_contract_address: Option<ContractAddress>,
     /// The delay (seconds) before starting consensus to give time for network peering.
     #[serde(deserialize_with = "deserialize_seconds_to_duration")]
     pub consensus_delay: Duration,
@@ -79,6 +113,20 @@ impl SerializeConfig for ConsensusConfig {
                 "The number of validators in the consensus.",
                 ParamPrivacyInput::Public,
             ),
+            ser_param(
+                "validator_voting_powers",
+                &serialize_voting_powers(&self.validator_voting_powers),
+                "Comma separated voting power of each validator, ordered by validator id. Empty \
+                 means all validators have equal voting power.",
+                ParamPrivacyInput::Public,
+            ),
+            ser_param(
+                "validator_public_keys",
+                &serialize_public_keys(&self.validator_public_keys),
+                "Comma separated public key of each validator, ordered by validator id. Empty \
+                 means the keys are unknown, so no messages from other validators are accepted.",
+                ParamPrivacyInput::Public,
+            ),
             ser_param(
                 "consensus_delay",
                 &self.consensus_delay.as_secs(),
@@ -86,6 +134,30 @@ impl SerializeConfig for ConsensusConfig {
                 ParamPrivacyInput::Public,
             ),
         ]);
+        config.extend(ser_optional_param(
+            &self.signing_key,
+            Felt::ZERO,
+            "signing_key",
+            "The Stark private key the node signs consensus messages with. Required to run \
+             consensus.",
+            ParamPrivacyInput::Private,
+        ));
+        config.extend(ser_optional_param(
+            &self.wal_path,
+            PathBuf::from("./data/consensus_wal"),
+            "wal_path",
+            "The path of the consensus write-ahead log, which lets a node restart mid-height \
+             without equivocating. If unset, no log is kept.",
+            ParamPrivacyInput::Public,
+        ));
+        config.extend(ser_optional_param(
+            &self.staking_contract_address,
+            ContractAddress::default(),
+            "staking_contract_address",
+            "The address of the staking contract to read the validator set from. If unset, the \
+             validator set is taken from this config.",
+            ParamPrivacyInput::Public,
+        ));
         config.extend(append_sub_config_name(self.timeouts.dump(), "timeouts"));
         config.extend(append_sub_config_name(self.network_config.dump(), "network_config"));
         config
@@ -102,9 +174,14 @@ impl Default for ConsensusConfig {
         Self {
             chain_id: ChainId::Other("0x0".to_string()),
             validator_id: ValidatorId::from(DEFAULT_VALIDATOR_ID),
+            signing_key: None,
+            wal_path: None,
             network_topic: "consensus".to_string(),
             start_height: BlockNumber::default(),
             num_validators: 1,
+            validator_voting_powers: Vec::new(),
+            validator_public_keys: Vec::new(),
+            staking_contract_address: None,
             consensus_delay: Duration::from_secs(5),
             timeouts: TimeoutsConfig::default(),
             network_config,
@@ -112,6 +189,95 @@ impl Default for ConsensusConfig {
     }
 }
 
+impl ConsensusConfig {
+    /// The validator set described by `num_validators`, `validator_voting_powers` and
+    /// `validator_public_keys`. Validator IDs are assigned sequentially, starting at
+    /// `DEFAULT_VALIDATOR_ID`.
+    ///
+    /// Assumes the config is valid, i.e. `validator_voting_powers` and `validator_public_keys` are
+    /// each empty or have an entry per validator.
+    pub fn validator_set(&self) -> ValidatorSet {
+        let ids: Vec<_> =
+            (0..self.num_validators).map(|i| ValidatorId::from(DEFAULT_VALIDATOR_ID + i)).collect();
+        let validator_set = if self.validator_voting_powers.is_empty() {
+            ValidatorSet::with_equal_voting_power(ids.iter().copied())
+        } else {
+            ValidatorSet::new(ids.iter().copied().zip(self.validator_voting_powers.iter().copied()))
+        };
+        validator_set
+            .with_public_keys(ids.into_iter().zip(self.validator_public_keys.iter().copied()))
+    }
+
+    /// The signer for this node's consensus messages. Fails if `signing_key` is unset.
+    pub fn signer(&self) -> Result<ConsensusSigner, ConsensusError> {
+        self.signing_key
+            .map(|signing_key| ConsensusSigner::new(signing_key, self.chain_id.clone()))
+            .ok_or(ConsensusError::MissingSigningKey)
+    }
+}
+
+fn validate_consensus_config(config: &ConsensusConfig) -> Result<(), ValidationError> {
+    let n_voting_powers = config.validator_voting_powers.len();
+    if n_voting_powers != 0
+        && u64::try_from(n_voting_powers).map_or(true, |n| n != config.num_validators)
+    {
+        return Err(ValidationError::new(
+            "validator_voting_powers must be empty or have an entry per validator",
+        ));
+    }
+    let n_public_keys = config.validator_public_keys.len();
+    if n_public_keys != 0
+        && u64::try_from(n_public_keys).map_or(true, |n| n != config.num_validators)
+    {
+        return Err(ValidationError::new(
+            "validator_public_keys must be empty or have an entry per validator",
+        ));
+    }
+    Ok(())
+}
+
+fn serialize_voting_powers(voting_powers: &[VotingPower]) -> String {
+    voting_powers.iter().map(VotingPower::to_string).collect::<Vec<_>>().join(",")
+}
+
+fn deserialize_voting_powers<'de, D>(de: D) -> Result<Vec<VotingPower>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let raw_str: String = Deserialize::deserialize(de)?;
+    if raw_str.is_empty() {
+        return Ok(Vec::new());
+    }
+    raw_str
+        .split(',')
+        .map(|voting_power| voting_power.trim().parse().map_err(D::Error::custom))
+        .collect()
+}
+
+fn serialize_public_keys(public_keys: &[PublicKey]) -> String {
+    public_keys
+        .iter()
+        .map(|public_key| format!("{:#x}", public_key.0))
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+fn deserialize_public_keys<'de, D>(de: D) -> Result<Vec<PublicKey>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let raw_str: String = Deserialize::deserialize(de)?;
+    if raw_str.is_empty() {
+        return Ok(Vec::new());
+    }
+    raw_str
+        .split(',')
+        .map(|public_key| {
+            Felt::from_hex(public_key.trim()).map(PublicKey).map_err(D::Error::custom)
+        })
+        .collect()
+}
+
 /// Configuration for consensus timeouts.
 #[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
 pub struct TimeoutsConfig {
@@ -0,0 +1,71 @@
+use starknet_api::crypto::utils::PublicKey;
+use starknet_types_core::felt::Felt;
+use validator::Validate;
+
+use crate::config::ConsensusConfig;
+use crate::signature::public_key_from;
+use crate::types::{ConsensusError, ValidatorId, DEFAULT_VALIDATOR_ID};
+
+#[test]
+fn validator_voting_powers_per_validator() {
+    let config = ConsensusConfig {
+        num_validators: 3,
+        validator_voting_powers: vec![1, 2, 3],
+        ..Default::default()
+    };
+    assert!(config.validate().is_ok());
+
+    let config = ConsensusConfig { validator_voting_powers: vec![], ..config };
+    assert!(config.validate().is_ok());
+}
+
+#[test]
+fn validator_voting_powers_length_mismatch() {
+    let config = ConsensusConfig {
+        num_validators: 3,
+        validator_voting_powers: vec![1, 2],
+        ..Default::default()
+    };
+    assert!(config.validate().is_err());
+}
+
+#[test]
+fn validator_public_keys_length_mismatch() {
+    let config = ConsensusConfig {
+        num_validators: 3,
+        validator_public_keys: vec![PublicKey(Felt::ONE), PublicKey(Felt::TWO)],
+        ..Default::default()
+    };
+    assert!(config.validate().is_err());
+}
+
+#[test]
+fn validator_set_has_configured_public_keys() {
+    let public_keys = vec![PublicKey(Felt::ONE), PublicKey(Felt::TWO)];
+    let config = ConsensusConfig {
+        num_validators: 2,
+        validator_public_keys: public_keys.clone(),
+        ..Default::default()
+    };
+    let validator_set = config.validator_set();
+    for (i, public_key) in (0..2).zip(public_keys) {
+        let id = ValidatorId::from(DEFAULT_VALIDATOR_ID + i);
+        assert_eq!(validator_set.public_key(&id), Some(&public_key));
+    }
+
+    let config = ConsensusConfig { validator_public_keys: vec![], ..config };
+    let validator_set = config.validator_set();
+    assert_eq!(validator_set.public_key(&ValidatorId::from(DEFAULT_VALIDATOR_ID)), None);
+}
+
+#[test]
+fn signer_requires_signing_key() {
+    let config = ConsensusConfig::default();
+    assert!(matches!(config.signer(), Err(ConsensusError::MissingSigningKey)));
+
+    let signing_key = Felt::from(12345_u64);
+    let config = ConsensusConfig { signing_key: Some(signing_key), ..config };
+    let signer = config.signer().unwrap();
+    assert_eq!(signer.public_key(), public_key_from(&signing_key));
+    assert_eq!(signer.chain_id(), &config.chain_id);
+}
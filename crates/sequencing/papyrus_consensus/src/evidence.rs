@@ -0,0 +1,116 @@
+//! Evidence of validators equivocating, i.e. signing two conflicting consensus messages.
+//!
+//! Equivocation is the only way for a minority of validators to break the safety of consensus, so
+//! each piece of evidence is kept for the height it was found in, reported to the context and
+//! gossiped to peers. This lets operators, and in the future slashing, act on the offender.
+
+#[cfg(test)]
+#[path = "evidence_test.rs"]
+mod evidence_test;
+
+use std::collections::hash_map::Entry;
+use std::collections::HashMap;
+
+use papyrus_protobuf::consensus::{ConflictingProposals, ConflictingVotes, Misbehavior, VoteType};
+use starknet_api::block::BlockNumber;
+use starknet_api::core::ChainId;
+
+use crate::signature::{verify_proposal_fin, verify_vote};
+use crate::types::{ConsensusError, Round, ValidatorId};
+use crate::validator_set::ValidatorSet;
+
+/// Checks that `misbehavior` proves that a validator in `validators` signed two conflicting
+/// messages at `height` on the chain `chain_id`.
+pub fn verify_misbehavior(
+    misbehavior: &Misbehavior,
+    height: BlockNumber,
+    validators: &ValidatorSet,
+    chain_id: &ChainId,
+) -> Result<(), ConsensusError> {
+    let offender = misbehavior.offender();
+    let invalid = |msg: &str| {
+        Err(ConsensusError::InvalidEvidence(offender, height, format!("{msg}: {misbehavior:?}")))
+    };
+    if misbehavior.height() != height.0 {
+        return invalid("wrong height");
+    }
+    let Some(public_key) = validators.public_key(&offender) else {
+        return invalid("the offender is not a validator");
+    };
+    match misbehavior {
+        Misbehavior::ConflictingVotes(ConflictingVotes { first, second }) => {
+            let is_same_vote_slot = first.vote_type == second.vote_type
+                && first.height == second.height
+                && first.round == second.round
+                && first.voter == second.voter;
+            if !is_same_vote_slot || first.block_hash == second.block_hash {
+                return invalid("the votes do not conflict");
+            }
+            if !verify_vote(first, chain_id, public_key)
+                || !verify_vote(second, chain_id, public_key)
+            {
+                return invalid("invalid vote signature");
+            }
+        }
+        Misbehavior::ConflictingProposals(ConflictingProposals {
+            height,
+            round,
+            proposer,
+            first,
+            second,
+        }) => {
+            if first.proposal_content_id == second.proposal_content_id {
+                return invalid("the proposals do not conflict");
+            }
+            let verify =
+                |fin| verify_proposal_fin(*height, *round, *proposer, fin, chain_id, public_key);
+            if !verify(first) || !verify(second) {
+                return invalid("invalid proposal fin signature");
+            }
+        }
+    }
+    Ok(())
+}
+
+// Identifies the message which was equivocated on. A single piece of evidence is kept for each,
+// regardless of how many conflicting messages the offender signed or the order they arrived in.
+#[derive(Debug, PartialEq, Eq, Hash)]
+enum EvidenceKey {
+    Votes(ValidatorId, Round, VoteType),
+    Proposals(ValidatorId, Round),
+}
+
+impl From<&Misbehavior> for EvidenceKey {
+    fn from(misbehavior: &Misbehavior) -> Self {
+        match misbehavior {
+            Misbehavior::ConflictingVotes(votes) => EvidenceKey::Votes(
+                votes.first.voter,
+                votes.first.round,
+                votes.first.vote_type.clone(),
+            ),
+            Misbehavior::ConflictingProposals(proposals) => {
+                EvidenceKey::Proposals(proposals.proposer, proposals.round)
+            }
+        }
+    }
+}
+
+/// The verified evidence of equivocation found during a single height.
+#[derive(Debug, Default)]
+pub(crate) struct EvidenceStore {
+    evidence: HashMap<EvidenceKey, Misbehavior>,
+}
+
+impl EvidenceStore {
+    /// Stores `misbehavior`, which must already be verified. Returns whether it is new, as opposed
+    /// to evidence of an equivocation which is already stored.
+    pub(crate) fn insert(&mut self, misbehavior: Misbehavior) -> bool {
+        match self.evidence.entry(EvidenceKey::from(&misbehavior)) {
+            Entry::Occupied(_) => false,
+            Entry::Vacant(entry) => {
+                entry.insert(misbehavior);
+                true
+            }
+        }
+    }
+}
@@ -0,0 +1,165 @@
+use lazy_static::lazy_static;
+use papyrus_protobuf::consensus::{
+    ConflictingProposals,
+    ConflictingVotes,
+    ConsensusMessage,
+    Misbehavior,
+};
+use starknet_api::block::{BlockHash, BlockNumber};
+use starknet_api::core::ChainId;
+use starknet_types_core::felt::Felt;
+use test_case::test_case;
+
+use crate::evidence::{verify_misbehavior, EvidenceStore};
+use crate::signature::ConsensusSigner;
+use crate::test_utils::{into_vote, precommit, prevote, proposal_fin, proposal_init};
+use crate::types::{ConsensusError, ValidatorId, DEFAULT_VALIDATOR_ID};
+use crate::validator_set::ValidatorSet;
+
+lazy_static! {
+    static ref VALIDATOR_ID_1: ValidatorId = DEFAULT_VALIDATOR_ID.into();
+    static ref VALIDATOR_ID_2: ValidatorId = (DEFAULT_VALIDATOR_ID + 1).into();
+    static ref NON_VALIDATOR_ID: ValidatorId = (DEFAULT_VALIDATOR_ID + 2).into();
+    static ref VALIDATORS: ValidatorSet =
+        ValidatorSet::with_equal_voting_power([*VALIDATOR_ID_1, *VALIDATOR_ID_2])
+            .with_testing_public_keys();
+}
+
+const HEIGHT: u64 = 1;
+
+fn conflicting_votes(first: ConsensusMessage, second: ConsensusMessage) -> Misbehavior {
+    Misbehavior::ConflictingVotes(ConflictingVotes {
+        first: into_vote(first),
+        second: into_vote(second),
+    })
+}
+
+// A prevote of VALIDATOR_ID_1 signed by VALIDATOR_ID_2.
+fn forged_prevote() -> ConsensusMessage {
+    let vote = into_vote(prevote(Some(Felt::TWO), HEIGHT, 0, *VALIDATOR_ID_1));
+    ConsensusMessage::Vote(ConsensusSigner::for_testing(*VALIDATOR_ID_2).sign_vote(vote))
+}
+
+fn conflicting_proposals(first_content_id: Felt, second_content_id: Felt) -> Misbehavior {
+    let init = proposal_init(HEIGHT, 1, *VALIDATOR_ID_1);
+    Misbehavior::ConflictingProposals(ConflictingProposals {
+        height: init.height,
+        round: init.round,
+        proposer: init.proposer,
+        first: proposal_fin(&init, BlockHash(first_content_id)),
+        second: proposal_fin(&init, BlockHash(second_content_id)),
+    })
+}
+
+// Conflicting proposal fins of VALIDATOR_ID_1, the second of which is signed by VALIDATOR_ID_2.
+fn forged_conflicting_proposals() -> Misbehavior {
+    let Misbehavior::ConflictingProposals(proposals) = conflicting_proposals(Felt::ONE, Felt::TWO)
+    else {
+        unreachable!();
+    };
+    let init = proposal_init(HEIGHT, 1, *VALIDATOR_ID_1);
+    let second = ConsensusSigner::for_testing(*VALIDATOR_ID_2)
+        .sign_proposal_fin(&init, proposals.second.clone());
+    Misbehavior::ConflictingProposals(ConflictingProposals { second, ..proposals })
+}
+
+#[test_case(
+    conflicting_votes(
+        prevote(Some(Felt::ONE), HEIGHT, 0, *VALIDATOR_ID_1),
+        prevote(Some(Felt::TWO), HEIGHT, 0, *VALIDATOR_ID_1),
+    );
+    "conflicting_prevotes"
+)]
+#[test_case(
+    conflicting_votes(
+        precommit(Some(Felt::ONE), HEIGHT, 0, *VALIDATOR_ID_1),
+        precommit(None, HEIGHT, 0, *VALIDATOR_ID_1),
+    );
+    "precommit_and_nil_precommit"
+)]
+#[test_case(conflicting_proposals(Felt::ONE, Felt::TWO); "different_proposal_contents")]
+fn valid_evidence_is_accepted(misbehavior: Misbehavior) {
+    assert_eq!(
+        verify_misbehavior(
+            &misbehavior,
+            BlockNumber(HEIGHT),
+            &VALIDATORS,
+            &ChainId::create_for_testing()
+        ),
+        Ok(())
+    );
+}
+
+#[test_case(
+    conflicting_votes(
+        prevote(Some(Felt::ONE), HEIGHT, 0, *VALIDATOR_ID_1),
+        prevote(Some(Felt::ONE), HEIGHT, 0, *VALIDATOR_ID_1),
+    );
+    "same_vote"
+)]
+#[test_case(
+    conflicting_votes(
+        prevote(Some(Felt::ONE), HEIGHT, 0, *VALIDATOR_ID_1),
+        precommit(Some(Felt::TWO), HEIGHT, 0, *VALIDATOR_ID_1),
+    );
+    "different_vote_types"
+)]
+#[test_case(
+    conflicting_votes(
+        prevote(Some(Felt::ONE), HEIGHT, 0, *VALIDATOR_ID_1),
+        prevote(Some(Felt::TWO), HEIGHT, 1, *VALIDATOR_ID_1),
+    );
+    "different_rounds"
+)]
+#[test_case(
+    conflicting_votes(
+        prevote(Some(Felt::ONE), HEIGHT, 0, *VALIDATOR_ID_1),
+        prevote(Some(Felt::TWO), HEIGHT, 0, *VALIDATOR_ID_2),
+    );
+    "different_voters"
+)]
+#[test_case(
+    conflicting_votes(
+        prevote(Some(Felt::ONE), HEIGHT + 1, 0, *VALIDATOR_ID_1),
+        prevote(Some(Felt::TWO), HEIGHT + 1, 0, *VALIDATOR_ID_1),
+    );
+    "wrong_height"
+)]
+#[test_case(
+    conflicting_votes(
+        prevote(Some(Felt::ONE), HEIGHT, 0, *NON_VALIDATOR_ID),
+        prevote(Some(Felt::TWO), HEIGHT, 0, *NON_VALIDATOR_ID),
+    );
+    "not_a_validator"
+)]
+#[test_case(
+    conflicting_votes(prevote(Some(Felt::ONE), HEIGHT, 0, *VALIDATOR_ID_1), forged_prevote());
+    "forged_vote"
+)]
+#[test_case(conflicting_proposals(Felt::ONE, Felt::ONE); "same_proposal")]
+#[test_case(forged_conflicting_proposals(); "forged_proposal_fin")]
+fn invalid_evidence_is_rejected(misbehavior: Misbehavior) {
+    let res = verify_misbehavior(
+        &misbehavior,
+        BlockNumber(HEIGHT),
+        &VALIDATORS,
+        &ChainId::create_for_testing(),
+    );
+    assert!(matches!(res, Err(ConsensusError::InvalidEvidence(..))), "{res:?}");
+}
+
+#[test]
+fn each_equivocation_is_stored_once() {
+    let first = prevote(Some(Felt::ONE), HEIGHT, 0, *VALIDATOR_ID_1);
+    let second = prevote(Some(Felt::TWO), HEIGHT, 0, *VALIDATOR_ID_1);
+    let mut store = EvidenceStore::default();
+
+    assert!(store.insert(conflicting_votes(first.clone(), second.clone())));
+    // The same equivocation, with the votes in the opposite order.
+    assert!(!store.insert(conflicting_votes(second, first)));
+    // Equivocating on precommits in the same round is a separate equivocation.
+    assert!(store.insert(conflicting_votes(
+        precommit(Some(Felt::ONE), HEIGHT, 0, *VALIDATOR_ID_1),
+        precommit(Some(Felt::TWO), HEIGHT, 0, *VALIDATOR_ID_1),
+    )));
+}
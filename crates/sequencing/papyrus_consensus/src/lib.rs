@@ -4,7 +4,9 @@
 //! A consensus implementation for a [`Starknet`](https://www.starknet.io/) node.
 
 pub mod config;
+pub mod evidence;
 pub mod manager;
+pub mod signature;
 #[allow(missing_docs)]
 pub mod simulation_network_receiver;
 #[allow(missing_docs)]
@@ -16,5 +18,7 @@ pub mod stream_handler;
 pub(crate) mod test_utils;
 #[allow(missing_docs)]
 pub mod types;
+pub mod validator_set;
+pub mod wal;
 
 pub use manager::run_consensus;
@@ -5,20 +5,22 @@
 mod manager_test;
 
 use std::collections::BTreeMap;
+use std::path::PathBuf;
 use std::time::Duration;
 
 use futures::channel::mpsc;
 use futures::stream::FuturesUnordered;
 use futures::{Stream, StreamExt};
 use papyrus_common::metrics::{PAPYRUS_CONSENSUS_HEIGHT, PAPYRUS_CONSENSUS_SYNC_COUNT};
-use papyrus_network::network_manager::BroadcastTopicClientTrait;
+use papyrus_network::network_manager::{BroadcastTopicClientTrait, MALICIOUS_REPORT};
 use papyrus_network_types::network_types::BroadcastedMessageMetadata;
 use papyrus_protobuf::consensus::{ConsensusMessage, ProposalInit};
 use papyrus_protobuf::converters::ProtobufConversionError;
 use starknet_api::block::BlockNumber;
-use tracing::{debug, info, instrument};
+use tracing::{debug, info, instrument, warn};
 
 use crate::config::TimeoutsConfig;
+use crate::signature::ConsensusSigner;
 use crate::single_height_consensus::{ShcReturn, SingleHeightConsensus};
 use crate::types::{
     BroadcastConsensusMessageChannel,
@@ -27,6 +29,7 @@ use crate::types::{
     Decision,
     ValidatorId,
 };
+use crate::wal::HeightWal;
 
 // TODO(dvir): add test for this.
 #[instrument(skip_all, level = "info")]
@@ -37,10 +40,15 @@ pub async fn run_consensus<ContextT, SyncReceiverT>(
     start_active_height: BlockNumber,
     start_observe_height: BlockNumber,
     validator_id: ValidatorId,
+    signer: ConsensusSigner,
+    wal_path: Option<PathBuf>,
     consensus_delay: Duration,
     timeouts: TimeoutsConfig,
     mut broadcast_channels: BroadcastConsensusMessageChannel,
-    mut inbound_proposal_receiver: mpsc::Receiver<mpsc::Receiver<ContextT::ProposalPart>>,
+    mut inbound_proposal_receiver: mpsc::Receiver<(
+        mpsc::Receiver<ContextT::ProposalPart>,
+        BroadcastedMessageMetadata,
+    )>,
     mut sync_receiver: SyncReceiverT,
 ) -> Result<(), ConsensusError>
 where
@@ -49,10 +57,12 @@ where
 {
     info!(
         "Running consensus, start_active_height={}, start_observe_height={}, validator_id={}, \
-         consensus_delay={}, timeouts={:?}",
+         public_key={:#x}, wal_path={:?}, consensus_delay={}, timeouts={:?}",
         start_active_height,
         start_observe_height,
         validator_id,
+        signer.public_key(),
+        wal_path,
         consensus_delay.as_secs(),
         timeouts
     );
@@ -61,7 +71,7 @@ where
     tokio::time::sleep(consensus_delay).await;
     assert!(start_observe_height <= start_active_height);
     let mut current_height = start_observe_height;
-    let mut manager = MultiHeightManager::new(validator_id, timeouts);
+    let mut manager = MultiHeightManager::new(validator_id, signer, wal_path, timeouts);
     #[allow(clippy::as_conversions)] // FIXME: use int metrics so `as f64` may be removed.
     loop {
         metrics::gauge!(PAPYRUS_CONSENSUS_HEIGHT, current_height.0 as f64);
@@ -95,19 +105,33 @@ where
 
 /// Runs Tendermint repeatedly across different heights. Handles issues which are not explicitly
 /// part of the single height consensus algorithm (e.g. messages from future heights).
-#[derive(Debug, Default)]
+#[derive(Debug)]
 struct MultiHeightManager<ContextT: ConsensusContext> {
     validator_id: ValidatorId,
-    cached_messages: BTreeMap<u64, Vec<ConsensusMessage>>,
-    cached_proposals: BTreeMap<u64, (ProposalInit, mpsc::Receiver<ContextT::ProposalPart>)>,
+    signer: ConsensusSigner,
+    wal_path: Option<PathBuf>,
+    // Messages from future heights, along with who sent them. They are propagated only once they
+    // are verified at their height.
+    cached_messages: BTreeMap<u64, Vec<(ConsensusMessage, BroadcastedMessageMetadata)>>,
+    cached_proposals: BTreeMap<
+        u64,
+        (ProposalInit, mpsc::Receiver<ContextT::ProposalPart>, BroadcastedMessageMetadata),
+    >,
     timeouts: TimeoutsConfig,
 }
 
 impl<ContextT: ConsensusContext> MultiHeightManager<ContextT> {
     /// Create a new consensus manager.
-    pub fn new(validator_id: ValidatorId, timeouts: TimeoutsConfig) -> Self {
+    pub fn new(
+        validator_id: ValidatorId,
+        signer: ConsensusSigner,
+        wal_path: Option<PathBuf>,
+        timeouts: TimeoutsConfig,
+    ) -> Self {
         Self {
             validator_id,
+            signer,
+            wal_path,
             cached_messages: BTreeMap::new(),
             cached_proposals: BTreeMap::new(),
             timeouts,
@@ -125,20 +149,33 @@ impl<ContextT: ConsensusContext> MultiHeightManager<ContextT> {
         height: BlockNumber,
         is_observer: bool,
         broadcast_channels: &mut BroadcastConsensusMessageChannel,
-        proposal_receiver: &mut mpsc::Receiver<mpsc::Receiver<ContextT::ProposalPart>>,
+        proposal_receiver: &mut mpsc::Receiver<(
+            mpsc::Receiver<ContextT::ProposalPart>,
+            BroadcastedMessageMetadata,
+        )>,
     ) -> Result<Decision, ConsensusError> {
-        let validators = context.validators(height).await;
+        let validators = context.validators(height).await?;
         info!("running consensus for height {height:?} with validator set {validators:?}");
         let mut shc = SingleHeightConsensus::new(
             height,
             is_observer,
             self.validator_id,
+            self.signer.clone(),
             validators,
             self.timeouts.clone(),
         );
+        // Replaying the WAL of a height we were running before a restart resumes it from the same
+        // round, without equivocating.
+        if let Some(wal_path) = &self.wal_path {
+            let (wal, entries) = HeightWal::open(wal_path, height)?;
+            if !entries.is_empty() {
+                info!("Resuming height {height} from {} WAL entries", entries.len());
+            }
+            shc = shc.with_wal(wal, entries);
+        }
         let mut shc_events = FuturesUnordered::new();
 
-        match self.start_height(context, height, &mut shc).await? {
+        match self.start_height(context, height, &mut shc, broadcast_channels).await? {
             ShcReturn::Decision(decision) => return Ok(decision),
             ShcReturn::Tasks(tasks) => {
                 for task in tasks {
@@ -154,7 +191,7 @@ impl<ContextT: ConsensusContext> MultiHeightManager<ContextT> {
                     self.handle_message(
                         context, height, &mut shc, message, broadcast_channels).await?
                 },
-                Some(mut content_receiver) = proposal_receiver.next() => {
+                Some((mut content_receiver, metadata)) = proposal_receiver.next() => {
                     // Get the first message to verify the init was sent.
                     // TODO(guyn): add a timeout and panic, since StreamHandler should only send once
                     // the first message (message_id=0) has arrived.
@@ -164,7 +201,16 @@ impl<ContextT: ConsensusContext> MultiHeightManager<ContextT> {
                         ));
                     };
                     let proposal_init: ProposalInit = first_part.try_into()?;
-                    self.handle_proposal(context, height, &mut shc, proposal_init, content_receiver).await?
+                    self.handle_proposal(
+                        context,
+                        height,
+                        &mut shc,
+                        proposal_init,
+                        content_receiver,
+                        metadata,
+                        broadcast_channels,
+                    )
+                    .await?
                 },
                 Some(shc_event) = shc_events.next() => {
                     shc.handle_event(context, shc_event).await?
@@ -187,21 +233,33 @@ impl<ContextT: ConsensusContext> MultiHeightManager<ContextT> {
         context: &mut ContextT,
         height: BlockNumber,
         shc: &mut SingleHeightConsensus,
+        broadcast_channels: &mut BroadcastConsensusMessageChannel,
     ) -> Result<ShcReturn, ConsensusError> {
         let mut tasks = match shc.start(context).await? {
             decision @ ShcReturn::Decision(_) => return Ok(decision),
             ShcReturn::Tasks(tasks) => tasks,
         };
 
-        if let Some((init, content_receiver)) = self.get_current_proposal(height) {
-            match shc.handle_proposal(context, init, content_receiver).await? {
-                decision @ ShcReturn::Decision(_) => return Ok(decision),
-                ShcReturn::Tasks(new_tasks) => tasks.extend(new_tasks),
+        if let Some((init, content_receiver, metadata)) = self.get_current_proposal(height) {
+            match shc.handle_proposal(context, init, content_receiver).await {
+                Ok(decision @ ShcReturn::Decision(_)) => return Ok(decision),
+                Ok(ShcReturn::Tasks(new_tasks)) => tasks.extend(new_tasks),
+                Err(err @ ConsensusError::InvalidSignature(..)) => {
+                    warn!("Reporting peer for an invalid cached proposal: {err}");
+                    let _ = broadcast_channels
+                        .broadcast_topic_client
+                        .report_peer(metadata, MALICIOUS_REPORT)
+                        .await;
+                }
+                Err(err) => return Err(err),
             }
         };
 
-        for msg in self.get_current_height_messages(height) {
-            match shc.handle_message(context, msg).await? {
+        // The signatures of these messages could only be verified once their height started, so
+        // they are propagated only now.
+        for (msg, metadata) in self.get_current_height_messages(height) {
+            let result = shc.handle_message(context, msg).await;
+            match propagate_or_report(result, metadata, broadcast_channels).await? {
                 decision @ ShcReturn::Decision(_) => return Ok(decision),
                 ShcReturn::Tasks(new_tasks) => tasks.extend(new_tasks),
             }
@@ -211,6 +269,7 @@ impl<ContextT: ConsensusContext> MultiHeightManager<ContextT> {
     }
 
     // Handle a new proposal receiver from the network.
+    #[allow(clippy::too_many_arguments)]
     async fn handle_proposal(
         &mut self,
         context: &mut ContextT,
@@ -218,17 +277,29 @@ impl<ContextT: ConsensusContext> MultiHeightManager<ContextT> {
         shc: &mut SingleHeightConsensus,
         proposal_init: ProposalInit,
         content_receiver: mpsc::Receiver<ContextT::ProposalPart>,
+        metadata: BroadcastedMessageMetadata,
+        broadcast_channels: &mut BroadcastConsensusMessageChannel,
     ) -> Result<ShcReturn, ConsensusError> {
         if proposal_init.height != height {
             debug!("Received a proposal for a different height. {:?}", proposal_init);
             if proposal_init.height > height {
                 // Note: this will overwrite an existing content_receiver for this height!
                 self.cached_proposals
-                    .insert(proposal_init.height.0, (proposal_init, content_receiver));
+                    .insert(proposal_init.height.0, (proposal_init, content_receiver, metadata));
             }
             return Ok(ShcReturn::Tasks(Vec::new()));
         }
-        shc.handle_proposal(context, proposal_init, content_receiver).await
+        match shc.handle_proposal(context, proposal_init, content_receiver).await {
+            Err(err @ ConsensusError::InvalidSignature(..)) => {
+                warn!("Reporting peer for an invalid proposal: {err}");
+                let _ = broadcast_channels
+                    .broadcast_topic_client
+                    .report_peer(metadata, MALICIOUS_REPORT)
+                    .await;
+                Ok(ShcReturn::Tasks(Vec::new()))
+            }
+            ret => ret,
+        }
     }
 
     // Handle a single consensus message.
@@ -243,19 +314,17 @@ impl<ContextT: ConsensusContext> MultiHeightManager<ContextT> {
         )>,
         broadcast_channels: &mut BroadcastConsensusMessageChannel,
     ) -> Result<ShcReturn, ConsensusError> {
-        let message = match message {
+        let (message, metadata) = match message {
             None => Err(ConsensusError::InternalNetworkError(
                 "NetworkReceiver should never be closed".to_string(),
             )),
-            Some((Ok(msg), metadata)) => {
-                // TODO(matan): Hold onto report_sender for use in later errors by SHC.
-                let _ =
-                    broadcast_channels.broadcast_topic_client.continue_propagation(&metadata).await;
-                Ok(msg)
-            }
+            Some((Ok(msg), metadata)) => Ok((msg, metadata)),
             Some((Err(e), metadata)) => {
                 // Failed to parse consensus message
-                let _ = broadcast_channels.broadcast_topic_client.report_peer(metadata).await;
+                let _ = broadcast_channels
+                    .broadcast_topic_client
+                    .report_peer(metadata, MALICIOUS_REPORT)
+                    .await;
                 Err(e.into())
             }
         }?;
@@ -267,12 +336,14 @@ impl<ContextT: ConsensusContext> MultiHeightManager<ContextT> {
         if message.height() != height.0 {
             debug!("Received a message for a different height. {:?}", message);
             if message.height() > height.0 {
-                self.cached_messages.entry(message.height()).or_default().push(message);
+                // Propagated once it is verified at its height.
+                self.cached_messages.entry(message.height()).or_default().push((message, metadata));
             }
             return Ok(ShcReturn::Tasks(Vec::new()));
         }
 
-        shc.handle_message(context, message).await
+        let result = shc.handle_message(context, message).await;
+        propagate_or_report(result, metadata, broadcast_channels).await
     }
 
     // Checks if a cached proposal already exists
@@ -282,7 +353,8 @@ impl<ContextT: ConsensusContext> MultiHeightManager<ContextT> {
     fn get_current_proposal(
         &mut self,
         height: BlockNumber,
-    ) -> Option<(ProposalInit, mpsc::Receiver<ContextT::ProposalPart>)> {
+    ) -> Option<(ProposalInit, mpsc::Receiver<ContextT::ProposalPart>, BroadcastedMessageMetadata)>
+    {
         loop {
             let entry = self.cached_proposals.first_entry()?;
             match entry.key().cmp(&height.0) {
@@ -299,7 +371,10 @@ impl<ContextT: ConsensusContext> MultiHeightManager<ContextT> {
     // - returns all of the current height messages.
     // - drops messages from earlier heights.
     // - retains future messages in the cache.
-    fn get_current_height_messages(&mut self, height: BlockNumber) -> Vec<ConsensusMessage> {
+    fn get_current_height_messages(
+        &mut self,
+        height: BlockNumber,
+    ) -> Vec<(ConsensusMessage, BroadcastedMessageMetadata)> {
         // Depends on `cached_messages` being sorted by height.
         loop {
             let Some(entry) = self.cached_messages.first_entry() else {
@@ -316,6 +391,31 @@ impl<ContextT: ConsensusContext> MultiHeightManager<ContextT> {
     }
 }
 
+// Reports the peer which sent a message that failed verification, and otherwise asks the network to
+// continue propagating the message. Invalid messages may still be spread, since the network
+// propagates messages once they're delivered and doesn't act on `continue_propagation` yet.
+async fn propagate_or_report(
+    result: Result<ShcReturn, ConsensusError>,
+    metadata: BroadcastedMessageMetadata,
+    broadcast_channels: &mut BroadcastConsensusMessageChannel,
+) -> Result<ShcReturn, ConsensusError> {
+    match result {
+        Err(err @ (ConsensusError::InvalidSignature(..) | ConsensusError::InvalidEvidence(..))) => {
+            warn!("Reporting peer for an invalid message: {err}");
+            let _ = broadcast_channels
+                .broadcast_topic_client
+                .report_peer(metadata, MALICIOUS_REPORT)
+                .await;
+            Ok(ShcReturn::Tasks(Vec::new()))
+        }
+        ret => {
+            // TODO(matan): Hold onto report_sender for use in later errors by SHC.
+            let _ = broadcast_channels.broadcast_topic_client.continue_propagation(&metadata).await;
+            ret
+        }
+    }
+}
+
 // Return only when a height is reached that is greater than or equal to the current height.
 async fn sync_height<SyncReceiverT>(
     height: BlockNumber,
@@ -3,7 +3,7 @@ use std::vec;
 
 use async_trait::async_trait;
 use futures::channel::{mpsc, oneshot};
-use futures::SinkExt;
+use futures::{SinkExt, StreamExt};
 use lazy_static::lazy_static;
 use mockall::mock;
 use mockall::predicate::eq;
@@ -12,9 +12,11 @@ use papyrus_network::network_manager::test_utils::{
     MockBroadcastedMessagesSender,
     TestSubscriberChannels,
 };
+use papyrus_network::network_manager::MALICIOUS_REPORT;
 use papyrus_network_types::network_types::BroadcastedMessageMetadata;
 use papyrus_protobuf::consensus::{
     ConsensusMessage,
+    Misbehavior,
     ProposalFin,
     ProposalInit,
     ProposalPart,
@@ -26,7 +28,8 @@ use starknet_types_core::felt::Felt;
 
 use super::{run_consensus, MultiHeightManager};
 use crate::config::TimeoutsConfig;
-use crate::test_utils::{precommit, prevote, proposal_init};
+use crate::signature::ConsensusSigner;
+use crate::test_utils::{precommit, prevote, proposal_fin, proposal_init};
 use crate::types::{
     ConsensusContext,
     ConsensusError,
@@ -35,6 +38,7 @@ use crate::types::{
     ValidatorId,
     DEFAULT_VALIDATOR_ID,
 };
+use crate::validator_set::ValidatorSet;
 
 lazy_static! {
     static ref PROPOSER_ID: ValidatorId = DEFAULT_VALIDATOR_ID.into();
@@ -74,7 +78,7 @@ mock! {
             init: ProposalInit,
         );
 
-        async fn validators(&self, height: BlockNumber) -> Vec<ValidatorId>;
+        async fn validators(&self, height: BlockNumber) -> Result<ValidatorSet, ConsensusError>;
 
         fn proposer(&self, height: BlockNumber, round: Round) -> ValidatorId;
 
@@ -86,6 +90,8 @@ mock! {
             precommits: Vec<Vote>,
         ) -> Result<(), ConsensusError>;
 
+        async fn report_misbehavior(&mut self, misbehavior: Misbehavior);
+
         async fn set_height_and_round(&mut self, height: BlockNumber, round: Round);
     }
 }
@@ -97,11 +103,15 @@ async fn send(sender: &mut MockBroadcastedMessagesSender<ConsensusMessage>, msg:
 }
 
 async fn send_proposal(
-    proposal_receiver_sender: &mut mpsc::Sender<mpsc::Receiver<ProposalPart>>,
+    proposal_receiver_sender: &mut mpsc::Sender<(
+        mpsc::Receiver<ProposalPart>,
+        BroadcastedMessageMetadata,
+    )>,
     content: Vec<ProposalPart>,
 ) {
     let (mut proposal_sender, proposal_receiver) = mpsc::channel(CHANNEL_SIZE);
-    proposal_receiver_sender.send(proposal_receiver).await.unwrap();
+    let metadata = BroadcastedMessageMetadata::get_test_instance(&mut get_rng());
+    proposal_receiver_sender.send((proposal_receiver, metadata)).await.unwrap();
     for item in content {
         proposal_sender.send(item).await.unwrap();
     }
@@ -110,13 +120,11 @@ async fn send_proposal(
 fn expect_validate_proposal(context: &mut MockTestContext, block_hash: Felt) {
     context
         .expect_validate_proposal()
-        .return_once(move |_, _, _, _, _| {
+        .return_once(move |height, round, proposer, _, _| {
             let (block_sender, block_receiver) = oneshot::channel();
+            let init = proposal_init(height.0, round, proposer);
             block_sender
-                .send((
-                    BlockHash(block_hash),
-                    ProposalFin { proposal_content_id: BlockHash(block_hash) },
-                ))
+                .send((BlockHash(block_hash), proposal_fin(&init, BlockHash(block_hash))))
                 .unwrap();
             block_receiver
         })
@@ -138,7 +146,10 @@ async fn manager_multiple_heights_unordered() {
         &mut proposal_receiver_sender,
         vec![
             ProposalPart::Init(proposal_init(2, 0, *PROPOSER_ID)),
-            ProposalPart::Fin(ProposalFin { proposal_content_id: BlockHash(Felt::TWO) }),
+            ProposalPart::Fin(proposal_fin(
+                &proposal_init(2, 0, *PROPOSER_ID),
+                BlockHash(Felt::TWO),
+            )),
         ],
     )
     .await;
@@ -149,7 +160,10 @@ async fn manager_multiple_heights_unordered() {
         &mut proposal_receiver_sender,
         vec![
             ProposalPart::Init(proposal_init(1, 0, *PROPOSER_ID)),
-            ProposalPart::Fin(ProposalFin { proposal_content_id: BlockHash(Felt::ONE) }),
+            ProposalPart::Fin(proposal_fin(
+                &proposal_init(1, 0, *PROPOSER_ID),
+                BlockHash(Felt::ONE),
+            )),
         ],
     )
     .await;
@@ -159,12 +173,20 @@ async fn manager_multiple_heights_unordered() {
     let mut context = MockTestContext::new();
     // Run the manager for height 1.
     expect_validate_proposal(&mut context, Felt::ONE);
-    context.expect_validators().returning(move |_| vec![*PROPOSER_ID, *VALIDATOR_ID]);
+    context.expect_validators().returning(move |_| {
+        Ok(ValidatorSet::with_equal_voting_power([*PROPOSER_ID, *VALIDATOR_ID])
+            .with_testing_public_keys())
+    });
     context.expect_proposer().returning(move |_, _| *PROPOSER_ID);
     context.expect_set_height_and_round().returning(move |_, _| ());
     context.expect_broadcast().returning(move |_| Ok(()));
 
-    let mut manager = MultiHeightManager::new(*VALIDATOR_ID, TIMEOUTS.clone());
+    let mut manager = MultiHeightManager::new(
+        *VALIDATOR_ID,
+        ConsensusSigner::for_testing(*VALIDATOR_ID),
+        None,
+        TIMEOUTS.clone(),
+    );
     let mut subscriber_channels = subscriber_channels.into();
     let decision = manager
         .run_height(
@@ -203,7 +225,10 @@ async fn run_consensus_sync() {
     let (mut proposal_receiver_sender, proposal_receiver_receiver) = mpsc::channel(CHANNEL_SIZE);
 
     expect_validate_proposal(&mut context, Felt::TWO);
-    context.expect_validators().returning(move |_| vec![*PROPOSER_ID, *VALIDATOR_ID]);
+    context.expect_validators().returning(move |_| {
+        Ok(ValidatorSet::with_equal_voting_power([*PROPOSER_ID, *VALIDATOR_ID])
+            .with_testing_public_keys())
+    });
     context.expect_proposer().returning(move |_, _| *PROPOSER_ID);
     context.expect_set_height_and_round().returning(move |_, _| ());
     context.expect_broadcast().returning(move |_| Ok(()));
@@ -234,6 +259,8 @@ async fn run_consensus_sync() {
             BlockNumber(1),
             BlockNumber(1),
             *VALIDATOR_ID,
+            ConsensusSigner::for_testing(*VALIDATOR_ID),
+            None,
             Duration::ZERO,
             TIMEOUTS.clone(),
             subscriber_channels.into(),
@@ -268,7 +295,10 @@ async fn run_consensus_sync_cancellation_safety() {
     let (mut proposal_receiver_sender, proposal_receiver_receiver) = mpsc::channel(CHANNEL_SIZE);
 
     expect_validate_proposal(&mut context, Felt::ONE);
-    context.expect_validators().returning(move |_| vec![*PROPOSER_ID, *VALIDATOR_ID]);
+    context.expect_validators().returning(move |_| {
+        Ok(ValidatorSet::with_equal_voting_power([*PROPOSER_ID, *VALIDATOR_ID])
+            .with_testing_public_keys())
+    });
     context.expect_proposer().returning(move |_, _| *PROPOSER_ID);
     context.expect_set_height_and_round().returning(move |_, _| ());
     context.expect_broadcast().with(eq(prevote(Some(Felt::ONE), 1, 0, *VALIDATOR_ID))).return_once(
@@ -295,6 +325,8 @@ async fn run_consensus_sync_cancellation_safety() {
             BlockNumber(1),
             BlockNumber(1),
             *VALIDATOR_ID,
+            ConsensusSigner::for_testing(*VALIDATOR_ID),
+            None,
             Duration::ZERO,
             TIMEOUTS.clone(),
             subscriber_channels.into(),
@@ -350,16 +382,23 @@ async fn test_timeouts() {
 
     let mut context = MockTestContext::new();
     context.expect_set_height_and_round().returning(move |_, _| ());
-    context.expect_validate_proposal().returning(move |_, _, _, _, _| {
+    context.expect_validate_proposal().returning(move |height, round, proposer, _, _| {
         let (block_sender, block_receiver) = oneshot::channel();
+        let init = proposal_init(height.0, round, proposer);
         block_sender
-            .send((BlockHash(Felt::ONE), ProposalFin { proposal_content_id: BlockHash(Felt::ONE) }))
+            .send((BlockHash(Felt::ONE), proposal_fin(&init, BlockHash(Felt::ONE))))
             .unwrap();
         block_receiver
     });
-    context
-        .expect_validators()
-        .returning(move |_| vec![*PROPOSER_ID, *VALIDATOR_ID, *VALIDATOR_ID_2, *VALIDATOR_ID_3]);
+    context.expect_validators().returning(move |_| {
+        Ok(ValidatorSet::with_equal_voting_power([
+            *PROPOSER_ID,
+            *VALIDATOR_ID,
+            *VALIDATOR_ID_2,
+            *VALIDATOR_ID_3,
+        ])
+        .with_testing_public_keys())
+    });
     context.expect_proposer().returning(move |_, _| *PROPOSER_ID);
 
     let (timeout_send, timeout_receive) = oneshot::channel();
@@ -374,7 +413,12 @@ async fn test_timeouts() {
         });
     context.expect_broadcast().returning(move |_| Ok(()));
 
-    let mut manager = MultiHeightManager::new(*VALIDATOR_ID, TIMEOUTS.clone());
+    let mut manager = MultiHeightManager::new(
+        *VALIDATOR_ID,
+        ConsensusSigner::for_testing(*VALIDATOR_ID),
+        None,
+        TIMEOUTS.clone(),
+    );
     let manager_handle = tokio::spawn(async move {
         let decision = manager
             .run_height(
@@ -406,3 +450,162 @@ async fn test_timeouts() {
 
     manager_handle.await.unwrap();
 }
+
+#[tokio::test]
+async fn invalid_vote_signature_is_reported() {
+    let TestSubscriberChannels { mut mock_network, subscriber_channels } =
+        mock_register_broadcast_topic().unwrap();
+    let (_proposal_receiver_sender, mut proposal_receiver_receiver) = mpsc::channel(CHANNEL_SIZE);
+
+    let mut context = MockTestContext::new();
+    context.expect_validators().returning(move |_| {
+        Ok(ValidatorSet::with_equal_voting_power([*PROPOSER_ID, *VALIDATOR_ID, *VALIDATOR_ID_2])
+            .with_testing_public_keys())
+    });
+    context.expect_proposer().returning(move |_, _| *PROPOSER_ID);
+    context.expect_set_height_and_round().returning(move |_, _| ());
+
+    let mut manager = MultiHeightManager::new(
+        *VALIDATOR_ID,
+        ConsensusSigner::for_testing(*VALIDATOR_ID),
+        None,
+        TIMEOUTS.clone(),
+    );
+    let manager_handle = tokio::spawn(async move {
+        manager
+            .run_height(
+                &mut context,
+                BlockNumber(1),
+                false,
+                &mut subscriber_channels.into(),
+                &mut proposal_receiver_receiver,
+            )
+            .await
+    });
+
+    // A vote claiming to be from VALIDATOR_ID_2, signed by VALIDATOR_ID_3.
+    let ConsensusMessage::Vote(forged_vote) = prevote(Some(Felt::ONE), 1, 0, *VALIDATOR_ID_3)
+    else {
+        unreachable!();
+    };
+    let forged_vote = Vote { voter: *VALIDATOR_ID_2, ..forged_vote };
+    let mut sender = mock_network.broadcasted_messages_sender;
+    send(&mut sender, ConsensusMessage::Vote(forged_vote)).await;
+    send(&mut sender, prevote(Some(Felt::ONE), 1, 0, *VALIDATOR_ID_2)).await;
+
+    // The forged vote is reported and only the valid vote is propagated.
+    let (_, reason) = mock_network.reported_messages_receiver.next().await.unwrap();
+    assert_eq!(reason, MALICIOUS_REPORT);
+    mock_network.continue_propagation_receiver.next().await.unwrap();
+    assert!(mock_network.continue_propagation_receiver.try_next().is_err());
+    assert!(!manager_handle.is_finished());
+    manager_handle.abort();
+}
+
+#[tokio::test]
+async fn future_height_messages_are_propagated_once_verified() {
+    let TestSubscriberChannels { mut mock_network, subscriber_channels } =
+        mock_register_broadcast_topic().unwrap();
+    let (_proposal_receiver_sender, mut proposal_receiver_receiver) = mpsc::channel(CHANNEL_SIZE);
+
+    let mut context = MockTestContext::new();
+    context.expect_validators().returning(move |_| {
+        Ok(ValidatorSet::with_equal_voting_power([*PROPOSER_ID, *VALIDATOR_ID, *VALIDATOR_ID_2])
+            .with_testing_public_keys())
+    });
+    context.expect_proposer().returning(move |_, _| *PROPOSER_ID);
+    context.expect_set_height_and_round().returning(move |_, _| ());
+    context.expect_broadcast().returning(move |_| Ok(()));
+
+    let mut manager = MultiHeightManager::new(
+        *VALIDATOR_ID,
+        ConsensusSigner::for_testing(*VALIDATOR_ID),
+        None,
+        TIMEOUTS.clone(),
+    );
+    let mut broadcast_channels = subscriber_channels.into();
+    let manager_handle = tokio::spawn(async move {
+        // Height 1 caches the height 2 messages, then times out without a decision.
+        tokio::time::timeout(
+            Duration::from_millis(100),
+            manager.run_height(
+                &mut context,
+                BlockNumber(1),
+                false,
+                &mut broadcast_channels,
+                &mut proposal_receiver_receiver,
+            ),
+        )
+        .await
+        .unwrap_err();
+        manager
+            .run_height(
+                &mut context,
+                BlockNumber(2),
+                false,
+                &mut broadcast_channels,
+                &mut proposal_receiver_receiver,
+            )
+            .await
+    });
+
+    // A vote claiming to be from VALIDATOR_ID_2, signed by VALIDATOR_ID_3.
+    let ConsensusMessage::Vote(forged_vote) = prevote(Some(Felt::ONE), 2, 0, *VALIDATOR_ID_3)
+    else {
+        unreachable!();
+    };
+    let forged_vote = Vote { voter: *VALIDATOR_ID_2, ..forged_vote };
+    let mut sender = mock_network.broadcasted_messages_sender;
+    send(&mut sender, ConsensusMessage::Vote(forged_vote)).await;
+    send(&mut sender, prevote(Some(Felt::ONE), 2, 0, *VALIDATOR_ID_2)).await;
+
+    // Once height 2 starts, the forged vote is reported and only the valid vote is propagated.
+    mock_network.reported_messages_receiver.next().await.unwrap();
+    mock_network.continue_propagation_receiver.next().await.unwrap();
+    assert!(mock_network.continue_propagation_receiver.try_next().is_err());
+    assert!(!manager_handle.is_finished());
+    manager_handle.abort();
+}
+
+#[tokio::test]
+async fn invalid_proposal_signature_is_reported() {
+    let TestSubscriberChannels { mut mock_network, subscriber_channels } =
+        mock_register_broadcast_topic().unwrap();
+    let (mut proposal_receiver_sender, mut proposal_receiver_receiver) =
+        mpsc::channel(CHANNEL_SIZE);
+
+    let mut context = MockTestContext::new();
+    context.expect_validators().returning(move |_| {
+        Ok(ValidatorSet::with_equal_voting_power([*PROPOSER_ID, *VALIDATOR_ID, *VALIDATOR_ID_2])
+            .with_testing_public_keys())
+    });
+    context.expect_proposer().returning(move |_, _| *PROPOSER_ID);
+    context.expect_set_height_and_round().returning(move |_, _| ());
+
+    let mut manager = MultiHeightManager::new(
+        *VALIDATOR_ID,
+        ConsensusSigner::for_testing(*VALIDATOR_ID),
+        None,
+        TIMEOUTS.clone(),
+    );
+    let manager_handle = tokio::spawn(async move {
+        manager
+            .run_height(
+                &mut context,
+                BlockNumber(1),
+                false,
+                &mut subscriber_channels.into(),
+                &mut proposal_receiver_receiver,
+            )
+            .await
+    });
+
+    // A proposal claiming to be from PROPOSER_ID, signed by VALIDATOR_ID_2.
+    let forged_init =
+        ProposalInit { proposer: *PROPOSER_ID, ..proposal_init(1, 0, *VALIDATOR_ID_2) };
+    send_proposal(&mut proposal_receiver_sender, vec![ProposalPart::Init(forged_init)]).await;
+
+    mock_network.reported_messages_receiver.next().await.unwrap();
+    assert!(!manager_handle.is_finished());
+    manager_handle.abort();
+}
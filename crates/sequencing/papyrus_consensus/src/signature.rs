@@ -0,0 +1,197 @@
+//! Signing and verification of consensus messages.
+//!
+//! Each signed message is hashed with Poseidon, prefixed by a tag which is unique to the message
+//! type and the chain, so a signature over one type of message can never be replayed as another,
+//! nor on another chain.
+
+#[cfg(test)]
+#[path = "signature_test.rs"]
+mod signature_test;
+
+use std::fmt::Debug;
+
+use lazy_static::lazy_static;
+use papyrus_protobuf::consensus::{ProposalFin, ProposalInit, Vote, VoteType};
+use starknet_api::block::BlockNumber;
+use starknet_api::core::ChainId;
+use starknet_api::crypto::utils::{verify_message_hash_signature, PublicKey, Signature};
+use starknet_types_core::felt::Felt;
+use starknet_types_core::hash::{Poseidon, StarkHash};
+
+use crate::types::{Round, ValidatorId};
+
+lazy_static! {
+    static ref VOTE_TAG: Felt = Felt::from_bytes_be_slice(b"CONSENSUS_VOTE");
+    static ref PROPOSAL_INIT_TAG: Felt = Felt::from_bytes_be_slice(b"CONSENSUS_PROPOSAL_INIT");
+    static ref PROPOSAL_FIN_TAG: Felt = Felt::from_bytes_be_slice(b"CONSENSUS_PROPOSAL_FIN");
+}
+
+/// Signs consensus messages on behalf of this node's validator.
+#[derive(Clone)]
+pub struct ConsensusSigner {
+    private_key: Felt,
+    public_key: PublicKey,
+    chain_id: ChainId,
+}
+
+// Never print the private key.
+impl Debug for ConsensusSigner {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ConsensusSigner")
+            .field("public_key", &self.public_key)
+            .field("chain_id", &self.chain_id)
+            .finish()
+    }
+}
+
+impl ConsensusSigner {
+    /// Creates a signer from a Stark private key, for the messages of the chain `chain_id`.
+    pub fn new(private_key: Felt, chain_id: ChainId) -> Self {
+        Self { private_key, public_key: public_key_from(&private_key), chain_id }
+    }
+
+    /// An INSECURE signer whose private key is the validator ID itself, see
+    /// `ValidatorSet::with_testing_public_keys`. Signs for the testing chain.
+    #[cfg(any(feature = "testing", test))]
+    pub fn for_testing(validator_id: ValidatorId) -> Self {
+        Self::new(Felt::from(validator_id), ChainId::create_for_testing())
+    }
+
+    /// The public key matching this signer's private key.
+    pub fn public_key(&self) -> PublicKey {
+        self.public_key
+    }
+
+    /// The chain this signer signs messages for.
+    pub fn chain_id(&self) -> &ChainId {
+        &self.chain_id
+    }
+
+    /// Signs a message hash.
+    pub fn sign(&self, message_hash: &Felt) -> Signature {
+        let k = starknet_crypto::rfc6979_generate_k(message_hash, &self.private_key, None);
+        // Fails only if the hash is at least 2^251, which a Poseidon hash is with negligible
+        // probability, or if `k` is degenerate, which rfc6979 avoids.
+        let signature = starknet_crypto::sign(&self.private_key, message_hash, &k)
+            .expect("Failed to sign a consensus message");
+        Signature { r: signature.r, s: signature.s }
+    }
+
+    /// Returns `vote` with its signature set.
+    pub fn sign_vote(&self, vote: Vote) -> Vote {
+        let signature = self.sign(&vote_hash(&self.chain_id, &vote));
+        Vote { signature, ..vote }
+    }
+
+    /// Returns `init` with its signature set.
+    pub fn sign_proposal_init(&self, init: ProposalInit) -> ProposalInit {
+        let signature = self.sign(&proposal_init_hash(&self.chain_id, &init));
+        ProposalInit { signature, ..init }
+    }
+
+    /// Returns `fin` with its signature set, binding it to the proposal started by `init`.
+    pub fn sign_proposal_fin(&self, init: &ProposalInit, fin: ProposalFin) -> ProposalFin {
+        let signature = self.sign(&proposal_fin_hash(
+            &self.chain_id,
+            init.height,
+            init.round,
+            init.proposer,
+            &fin,
+        ));
+        ProposalFin { signature, ..fin }
+    }
+}
+
+/// The public key matching a Stark private key.
+pub fn public_key_from(private_key: &Felt) -> PublicKey {
+    PublicKey(starknet_crypto::get_public_key(private_key))
+}
+
+/// Whether `vote` is signed by the holder of `public_key`, on the chain `chain_id`.
+pub fn verify_vote(vote: &Vote, chain_id: &ChainId, public_key: &PublicKey) -> bool {
+    verify(&vote_hash(chain_id, vote), &vote.signature, public_key)
+}
+
+/// Whether `init` is signed by the holder of `public_key`, on the chain `chain_id`.
+pub fn verify_proposal_init(
+    init: &ProposalInit,
+    chain_id: &ChainId,
+    public_key: &PublicKey,
+) -> bool {
+    verify(&proposal_init_hash(chain_id, init), &init.signature, public_key)
+}
+
+/// Whether `fin` is signed by the holder of `public_key`, for the proposal of `proposer` at
+/// `(height, round)` on the chain `chain_id`.
+pub fn verify_proposal_fin(
+    height: BlockNumber,
+    round: Round,
+    proposer: ValidatorId,
+    fin: &ProposalFin,
+    chain_id: &ChainId,
+    public_key: &PublicKey,
+) -> bool {
+    verify(&proposal_fin_hash(chain_id, height, round, proposer, fin), &fin.signature, public_key)
+}
+
+fn verify(message_hash: &Felt, signature: &Signature, public_key: &PublicKey) -> bool {
+    // An error means the signature or key is malformed, which is just as invalid.
+    verify_message_hash_signature(message_hash, signature, public_key).unwrap_or(false)
+}
+
+// Binds a message type's tag to the chain, so messages of one chain are never valid on another.
+fn chain_tag(tag: Felt, chain_id: &ChainId) -> Felt {
+    // Chain IDs may be longer than a felt, so they are hashed in chunks.
+    let chain_id: Vec<_> =
+        chain_id.to_string().as_bytes().chunks(31).map(Felt::from_bytes_be_slice).collect();
+    Poseidon::hash(&tag, &Poseidon::hash_array(&chain_id))
+}
+
+fn vote_hash(chain_id: &ChainId, vote: &Vote) -> Felt {
+    let vote_type = match vote.vote_type {
+        VoteType::Prevote => Felt::ZERO,
+        VoteType::Precommit => Felt::ONE,
+    };
+    Poseidon::hash_array(&[
+        chain_tag(*VOTE_TAG, chain_id),
+        vote_type,
+        Felt::from(vote.height),
+        Felt::from(vote.round),
+        optional_felt(vote.block_hash.map(|block_hash| block_hash.0)),
+        Felt::from(vote.voter),
+    ])
+}
+
+fn proposal_init_hash(chain_id: &ChainId, init: &ProposalInit) -> Felt {
+    Poseidon::hash_array(&[
+        chain_tag(*PROPOSAL_INIT_TAG, chain_id),
+        Felt::from(init.height.0),
+        Felt::from(init.round),
+        optional_felt(init.valid_round.map(Felt::from)),
+        Felt::from(init.proposer),
+    ])
+}
+
+fn proposal_fin_hash(
+    chain_id: &ChainId,
+    height: BlockNumber,
+    round: Round,
+    proposer: ValidatorId,
+    fin: &ProposalFin,
+) -> Felt {
+    Poseidon::hash_array(&[
+        chain_tag(*PROPOSAL_FIN_TAG, chain_id),
+        Felt::from(height.0),
+        Felt::from(round),
+        Felt::from(proposer),
+        fin.proposal_content_id.0,
+    ])
+}
+
+// Hashes `None` differently from any `Some` value.
+fn optional_felt(value: Option<Felt>) -> Felt {
+    match value {
+        Some(value) => Poseidon::hash(&Felt::ONE, &value),
+        None => Felt::ZERO,
+    }
+}
@@ -0,0 +1,123 @@
+use lazy_static::lazy_static;
+use papyrus_protobuf::consensus::{ProposalFin, ProposalInit, Vote, VoteType};
+use starknet_api::block::{BlockHash, BlockNumber};
+use starknet_api::core::ChainId;
+use starknet_api::crypto::utils::Signature;
+use starknet_types_core::felt::Felt;
+
+use crate::signature::{verify_proposal_fin, verify_proposal_init, verify_vote, ConsensusSigner};
+use crate::types::{ValidatorId, DEFAULT_VALIDATOR_ID};
+
+lazy_static! {
+    static ref VALIDATOR_ID: ValidatorId = DEFAULT_VALIDATOR_ID.into();
+    static ref OTHER_VALIDATOR_ID: ValidatorId = (DEFAULT_VALIDATOR_ID + 1).into();
+    static ref SIGNER: ConsensusSigner = ConsensusSigner::for_testing(*VALIDATOR_ID);
+    static ref CHAIN_ID: ChainId = SIGNER.chain_id().clone();
+    static ref PROPOSAL_INIT: ProposalInit = ProposalInit {
+        height: BlockNumber(1),
+        round: 2,
+        valid_round: None,
+        proposer: *VALIDATOR_ID,
+        signature: Signature::default(),
+    };
+}
+
+fn vote(block_hash: Option<BlockHash>) -> Vote {
+    Vote {
+        vote_type: VoteType::Prevote,
+        height: 1,
+        round: 2,
+        block_hash,
+        voter: *VALIDATOR_ID,
+        signature: Signature::default(),
+    }
+}
+
+fn proposal_fin() -> ProposalFin {
+    ProposalFin { proposal_content_id: BlockHash(Felt::ONE), signature: Signature::default() }
+}
+
+#[test]
+fn signed_vote_is_verified() {
+    let vote = SIGNER.sign_vote(vote(Some(BlockHash(Felt::ONE))));
+
+    assert!(verify_vote(&vote, &CHAIN_ID, &SIGNER.public_key()));
+}
+
+#[test]
+fn unsigned_vote_is_rejected() {
+    assert!(!verify_vote(&vote(None), &CHAIN_ID, &SIGNER.public_key()));
+}
+
+#[test]
+fn vote_signed_by_another_key_is_rejected() {
+    let vote = SIGNER.sign_vote(vote(None));
+    let other_public_key = ConsensusSigner::for_testing(*OTHER_VALIDATOR_ID).public_key();
+
+    assert!(!verify_vote(&vote, &CHAIN_ID, &other_public_key));
+}
+
+#[test]
+fn modified_vote_is_rejected() {
+    let signed = SIGNER.sign_vote(vote(None));
+
+    let nil_to_block = Vote { block_hash: Some(BlockHash(Felt::ZERO)), ..signed.clone() };
+    assert!(!verify_vote(&nil_to_block, &CHAIN_ID, &SIGNER.public_key()));
+    let prevote_to_precommit = Vote { vote_type: VoteType::Precommit, ..signed.clone() };
+    assert!(!verify_vote(&prevote_to_precommit, &CHAIN_ID, &SIGNER.public_key()));
+    let other_round = Vote { round: 3, ..signed };
+    assert!(!verify_vote(&other_round, &CHAIN_ID, &SIGNER.public_key()));
+}
+
+#[test]
+fn signed_proposal_init_is_verified() {
+    let init = SIGNER.sign_proposal_init(PROPOSAL_INIT.clone());
+    assert!(verify_proposal_init(&init, &CHAIN_ID, &SIGNER.public_key()));
+
+    let reproposal = ProposalInit { valid_round: Some(0), ..init };
+    assert!(!verify_proposal_init(&reproposal, &CHAIN_ID, &SIGNER.public_key()));
+}
+
+#[test]
+fn proposal_fin_is_bound_to_its_proposal() {
+    // Setup.
+    let fin = SIGNER.sign_proposal_fin(&PROPOSAL_INIT, proposal_fin());
+    let height = PROPOSAL_INIT.height;
+    let round = PROPOSAL_INIT.round;
+    let public_key = SIGNER.public_key();
+
+    // Assert.
+    assert!(verify_proposal_fin(height, round, *VALIDATOR_ID, &fin, &CHAIN_ID, &public_key));
+    assert!(!verify_proposal_fin(height, round + 1, *VALIDATOR_ID, &fin, &CHAIN_ID, &public_key));
+    assert!(!verify_proposal_fin(
+        height.unchecked_next(),
+        round,
+        *VALIDATOR_ID,
+        &fin,
+        &CHAIN_ID,
+        &public_key
+    ));
+    assert!(!verify_proposal_fin(height, round, *OTHER_VALIDATOR_ID, &fin, &CHAIN_ID, &public_key));
+}
+
+#[test]
+fn signatures_are_bound_to_the_chain() {
+    // Setup.
+    let other_chain_id = ChainId::Other("OTHER_CHAIN".to_string());
+    let vote = SIGNER.sign_vote(vote(None));
+    let init = SIGNER.sign_proposal_init(PROPOSAL_INIT.clone());
+    let fin = SIGNER.sign_proposal_fin(&PROPOSAL_INIT, proposal_fin());
+    let public_key = SIGNER.public_key();
+
+    // Assert.
+    assert!(!verify_vote(&vote, &other_chain_id, &public_key));
+    assert!(!verify_proposal_init(&init, &other_chain_id, &public_key));
+    assert!(!verify_proposal_fin(
+        init.height,
+        init.round,
+        init.proposer,
+        &fin,
+        &other_chain_id,
+        &public_key
+    ));
+}
@@ -11,10 +11,10 @@ use futures::{Stream, StreamExt};
 use lru::LruCache;
 use papyrus_network::network_manager::BroadcastTopicServer;
 use papyrus_network_types::network_types::BroadcastedMessageMetadata;
-use papyrus_protobuf::consensus::ConsensusMessage;
+use papyrus_protobuf::consensus::{ConsensusMessage, Misbehavior};
 use papyrus_protobuf::converters::ProtobufConversionError;
 use starknet_api::block::BlockHash;
-use starknet_api::core::{ContractAddress, PatriciaKey};
+use starknet_types_core::felt::Felt;
 use tracing::{debug, instrument};
 
 /// Receiver used to help run simulations of consensus. It has 2 goals in mind:
@@ -110,13 +110,18 @@ impl NetworkReceiver {
             return msg;
         }
         debug!("Invalidating message");
-        // TODO(matan): Allow for invalid votes based on signature.
         match msg {
             ConsensusMessage::Proposal(ref mut proposal) => {
                 proposal.block_hash = BlockHash(proposal.block_hash.0 + 1);
             }
             ConsensusMessage::Vote(ref mut vote) => {
-                vote.voter = ContractAddress(PatriciaKey::from(msg_hash));
+                vote.signature.r += Felt::ONE;
+            }
+            ConsensusMessage::Misbehavior(Misbehavior::ConflictingVotes(ref mut votes)) => {
+                votes.first.signature.r += Felt::ONE;
+            }
+            ConsensusMessage::Misbehavior(Misbehavior::ConflictingProposals(ref mut proposals)) => {
+                proposals.first.signature.r += Felt::ONE;
             }
         }
         msg
@@ -3,17 +3,31 @@
 mod single_height_consensus_test;
 
 use std::collections::hash_map::Entry;
-use std::collections::{HashMap, VecDeque};
+use std::collections::{BTreeMap, BTreeSet, HashMap, VecDeque};
 use std::time::Duration;
 
 #[cfg(test)]
 use enum_as_inner::EnumAsInner;
 use futures::channel::{mpsc, oneshot};
-use papyrus_protobuf::consensus::{ConsensusMessage, ProposalFin, ProposalInit, Vote, VoteType};
+use futures::StreamExt;
+use papyrus_common::metrics::PAPYRUS_CONSENSUS_EVIDENCE_COUNT;
+use papyrus_protobuf::consensus::{
+    ConflictingProposals,
+    ConflictingVotes,
+    ConsensusMessage,
+    Misbehavior,
+    ProposalFin,
+    ProposalInit,
+    Vote,
+    VoteType,
+};
 use starknet_api::block::BlockNumber;
+use starknet_api::crypto::utils::Signature;
 use tracing::{debug, info, instrument, trace, warn};
 
 use crate::config::TimeoutsConfig;
+use crate::evidence::{verify_misbehavior, EvidenceStore};
+use crate::signature::{verify_proposal_fin, verify_proposal_init, verify_vote, ConsensusSigner};
 use crate::state_machine::{StateMachine, StateMachineEvent};
 use crate::types::{
     ConsensusContext,
@@ -23,6 +37,8 @@ use crate::types::{
     Round,
     ValidatorId,
 };
+use crate::validator_set::{ValidatorSet, VotingPower};
+use crate::wal::{HeightWal, WalEntry, WalError};
 
 #[derive(Debug, PartialEq)]
 #[cfg_attr(test, derive(EnumAsInner))]
@@ -39,8 +55,10 @@ pub enum ShcEvent {
     Prevote(StateMachineEvent),
     Precommit(StateMachineEvent),
     BuildProposal(StateMachineEvent),
-    // TODO: Replace ProposalContentId with the unvalidated signature from the proposer.
+    // Carries the proposer's (not yet verified) fin.
     ValidateProposal(StateMachineEvent, Option<ProposalFin>),
+    // Carries the (not yet verified) fin of another proposal for a round which already has one.
+    DuplicateProposalFin(Round, Option<ProposalFin>),
 }
 
 #[derive(Debug)]
@@ -67,6 +85,9 @@ pub enum ShcTask {
     /// 3. Once validation is complete, the manager returns the built proposal to the SHC as an
     ///    event, which can be sent to the SM.
     ValidateProposal(ProposalInit, oneshot::Receiver<(ProposalContentId, ProposalFin)>),
+    /// Another proposal for a round which already has one is not validated, but its fin is read,
+    /// since a proposer signing fins for different content in the same round is equivocating.
+    ReadDuplicateProposalFin(Round, oneshot::Receiver<ProposalFin>),
 }
 
 impl PartialEq for ShcTask {
@@ -79,6 +100,10 @@ impl PartialEq for ShcTask {
             | (ShcTask::Precommit(d1, e1), ShcTask::Precommit(d2, e2)) => d1 == d2 && e1 == e2,
             (ShcTask::BuildProposal(r1, _), ShcTask::BuildProposal(r2, _)) => r1 == r2,
             (ShcTask::ValidateProposal(pi1, _), ShcTask::ValidateProposal(pi2, _)) => pi1 == pi2,
+            (
+                ShcTask::ReadDuplicateProposalFin(r1, _),
+                ShcTask::ReadDuplicateProposalFin(r2, _),
+            ) => r1 == r2,
             _ => false,
         }
     }
@@ -131,6 +156,9 @@ impl ShcTask {
                     received_proposal_id,
                 )
             }
+            ShcTask::ReadDuplicateProposalFin(round, fin_receiver) => {
+                ShcEvent::DuplicateProposalFin(round, fin_receiver.await.ok())
+            }
         }
     }
 }
@@ -141,15 +169,29 @@ impl ShcTask {
 /// out messages "directly" to the network, and returning a decision to the caller.
 pub(crate) struct SingleHeightConsensus {
     height: BlockNumber,
-    validators: Vec<ValidatorId>,
+    validators: ValidatorSet,
     id: ValidatorId,
+    signer: ConsensusSigner,
     timeouts: TimeoutsConfig,
     state_machine: StateMachine,
     proposals: HashMap<Round, Option<ProposalContentId>>,
+    // The first validly signed proposal fin received for each round, kept to catch a conflicting
+    // one.
+    proposal_fins: HashMap<Round, ProposalFin>,
     prevotes: HashMap<(Round, ValidatorId), Vote>,
     precommits: HashMap<(Round, ValidatorId), Vote>,
     last_prevote: Option<Vote>,
     last_precommit: Option<Vote>,
+    evidence: EvidenceStore,
+    wal: Option<HeightWal>,
+    // Proposals recorded in the WAL before a restart, which were not yet passed to the state
+    // machine. {round: (proposal_id, valid_round)}
+    replayed_proposals: BTreeMap<Round, (Option<ProposalContentId>, Option<Round>)>,
+    // Rounds in which we started building a proposal before a restart.
+    replayed_builds: BTreeSet<Round>,
+    // The state machine's locked and valid values last recorded in the WAL.
+    recorded_locked_value_round: Option<(ProposalContentId, Round)>,
+    recorded_valid_value_round: Option<(ProposalContentId, Round)>,
 }
 
 impl SingleHeightConsensus {
@@ -157,27 +199,90 @@ impl SingleHeightConsensus {
         height: BlockNumber,
         is_observer: bool,
         id: ValidatorId,
-        validators: Vec<ValidatorId>,
+        signer: ConsensusSigner,
+        validators: ValidatorSet,
         timeouts: TimeoutsConfig,
     ) -> Self {
-        // TODO(matan): Use actual weights, not just `len`.
-        let n_validators =
-            u32::try_from(validators.len()).expect("Should have way less than u32::MAX validators");
-        let state_machine = StateMachine::new(id, n_validators, is_observer);
+        let state_machine = StateMachine::new(
+            id,
+            validators.total_voting_power(),
+            validators.voting_power(&id),
+            is_observer,
+        );
         Self {
             height,
             validators,
             id,
+            signer,
             timeouts,
             state_machine,
             proposals: HashMap::new(),
+            proposal_fins: HashMap::new(),
             prevotes: HashMap::new(),
             precommits: HashMap::new(),
             last_prevote: None,
             last_precommit: None,
+            evidence: EvidenceStore::default(),
+            wal: None,
+            replayed_proposals: BTreeMap::new(),
+            replayed_builds: BTreeSet::new(),
+            recorded_locked_value_round: None,
+            recorded_valid_value_round: None,
         }
     }
 
+    /// Records this height in `wal`, after restoring the state it recorded before a restart, see
+    /// [`crate::wal`]. Must be called before `start`.
+    pub(crate) fn with_wal(mut self, wal: HeightWal, entries: Vec<WalEntry>) -> Self {
+        // Only our own votes, locks and proposals show that we reached a round; a proposal may be
+        // received for a round we never reached.
+        let mut round = 0;
+        for entry in entries {
+            match entry {
+                WalEntry::Proposal { round: proposal_round, proposal_id, valid_round } => {
+                    self.replayed_proposals.insert(proposal_round, (proposal_id, valid_round));
+                }
+                WalEntry::BuildingProposal { round: build_round } => {
+                    round = round.max(build_round);
+                    self.replayed_builds.insert(build_round);
+                }
+                WalEntry::Vote(vote) => {
+                    round = round.max(vote.round);
+                    // The state machine counts our own votes and mustn't cast conflicting ones.
+                    let (votes, last_vote) = match vote.vote_type {
+                        VoteType::Prevote => {
+                            self.state_machine.restore_prevote(vote.block_hash, vote.round);
+                            (&mut self.prevotes, &mut self.last_prevote)
+                        }
+                        VoteType::Precommit => {
+                            self.state_machine.restore_precommit(vote.block_hash, vote.round);
+                            (&mut self.precommits, &mut self.last_precommit)
+                        }
+                    };
+                    if last_vote.as_ref().map_or(true, |last| last.round <= vote.round) {
+                        *last_vote = Some(vote.clone());
+                    }
+                    votes.insert((vote.round, vote.voter), vote);
+                }
+                WalEntry::Lock { locked_value_round, valid_value_round } => {
+                    for (_, lock_round) in locked_value_round.iter().chain(valid_value_round.iter())
+                    {
+                        round = round.max(*lock_round);
+                    }
+                    self.recorded_locked_value_round = locked_value_round;
+                    self.recorded_valid_value_round = valid_value_round;
+                }
+            }
+        }
+        self.state_machine.restore(
+            round,
+            self.recorded_locked_value_round,
+            self.recorded_valid_value_round,
+        );
+        self.wal = Some(wal);
+        self
+    }
+
     #[instrument(skip_all, fields(height=self.height.0), level = "debug")]
     pub(crate) async fn start<ContextT: ConsensusContext>(
         &mut self,
@@ -185,13 +290,66 @@ impl SingleHeightConsensus {
     ) -> Result<ShcReturn, ConsensusError> {
         info!("Starting consensus with validators {:?}", self.validators);
         context.set_height_and_round(self.height, self.state_machine.round()).await;
+        // Keep rebroadcasting the votes cast before a restart, since peers may have missed them.
+        let restored_vote_tasks = self.last_vote_tasks();
         let leader_fn = |round: Round| -> ValidatorId { context.proposer(self.height, round) };
         let events = self.state_machine.start(&leader_fn);
-        let ret = self.handle_state_machine_events(context, events).await;
+        let ret = match self.handle_state_machine_events(context, events).await {
+            Ok(ShcReturn::Tasks(mut tasks)) => {
+                tasks.extend(restored_vote_tasks);
+                self.replay_proposals(context, tasks).await
+            }
+            ret => ret,
+        };
         context.set_height_and_round(self.height, self.state_machine.round()).await;
         ret
     }
 
+    fn last_vote_tasks(&self) -> Vec<ShcTask> {
+        let voting_power = self.validators.voting_power(&self.id);
+        let prevote_task = self.last_prevote.as_ref().map(|vote| {
+            ShcTask::Prevote(
+                self.timeouts.prevote_timeout,
+                StateMachineEvent::Prevote(vote.block_hash, vote.round, voting_power),
+            )
+        });
+        let precommit_task = self.last_precommit.as_ref().map(|vote| {
+            ShcTask::Precommit(
+                self.timeouts.precommit_timeout,
+                StateMachineEvent::Precommit(vote.block_hash, vote.round, voting_power),
+            )
+        });
+        prevote_task.into_iter().chain(precommit_task).collect()
+    }
+
+    // Passes the proposals recorded before a restart to the state machine, as they are not sent
+    // again by peers. Returns `tasks` along with those resulting from the proposals.
+    async fn replay_proposals<ContextT: ConsensusContext>(
+        &mut self,
+        context: &mut ContextT,
+        mut tasks: Vec<ShcTask>,
+    ) -> Result<ShcReturn, ConsensusError> {
+        let current_round = self.state_machine.round();
+        for (round, (proposal_id, valid_round)) in std::mem::take(&mut self.replayed_proposals) {
+            // Our own proposals for rounds we have yet to propose in are handed over once the
+            // state machine asks for them, see `handle_state_machine_get_proposal`.
+            if round >= current_round && context.proposer(self.height, round) == self.id {
+                self.replayed_proposals.insert(round, (proposal_id, valid_round));
+                continue;
+            }
+            if self.proposals.contains_key(&round) {
+                continue;
+            }
+            self.proposals.insert(round, proposal_id);
+            let sm_proposal = StateMachineEvent::Proposal(proposal_id, round, valid_round);
+            match self.process_inbound_proposal(context, sm_proposal).await? {
+                decision @ ShcReturn::Decision(_) => return Ok(decision),
+                ShcReturn::Tasks(new_tasks) => tasks.extend(new_tasks),
+            }
+        }
+        Ok(ShcReturn::Tasks(tasks))
+    }
+
     /// Process the proposal init and initiate block validation. See [`ShcTask::ValidateProposal`]
     /// for more details on the full proposal flow.
     #[instrument(
@@ -219,9 +377,22 @@ impl SingleHeightConsensus {
                 format!("invalid proposer: expected {:?}, got {:?}", proposer_id, init.proposer);
             return Err(ConsensusError::InvalidProposal(proposer_id, self.height, msg));
         }
+        let is_signed_by_proposer =
+            self.validators.public_key(&proposer_id).is_some_and(|public_key| {
+                verify_proposal_init(&init, self.signer.chain_id(), public_key)
+            });
+        if !is_signed_by_proposer {
+            let msg = format!("invalid proposal init signature: {init:?}");
+            return Err(ConsensusError::InvalidSignature(proposer_id, self.height, msg));
+        }
         let Entry::Vacant(proposal_entry) = self.proposals.entry(init.round) else {
-            warn!("Round {} already has a proposal, ignoring", init.round);
-            return Ok(ShcReturn::Tasks(Vec::new()));
+            debug!("Round {} already has a proposal, only reading its fin", init.round);
+            let fin_receiver =
+                read_proposal_fin(p2p_messages_receiver, self.timeouts.proposal_timeout);
+            return Ok(ShcReturn::Tasks(vec![ShcTask::ReadDuplicateProposalFin(
+                init.round,
+                fin_receiver,
+            )]));
         };
         // Since validating the proposal is non-blocking, we want to avoid validating the same round
         // twice in parallel. This could be caused by a network repeat or a malicious spam attack.
@@ -266,6 +437,15 @@ impl SingleHeightConsensus {
                 context.set_height_and_round(self.height, self.state_machine.round()).await;
                 ret
             }
+            ConsensusMessage::Misbehavior(misbehavior) => {
+                verify_misbehavior(
+                    &misbehavior,
+                    self.height,
+                    &self.validators,
+                    self.signer.chain_id(),
+                )?;
+                self.handle_misbehavior(context, misbehavior, false).await
+            }
         }
     }
 
@@ -284,7 +464,7 @@ impl SingleHeightConsensus {
                 let sm_events = self.state_machine.handle_event(event, &leader_fn);
                 self.handle_state_machine_events(context, sm_events).await
             }
-            ShcEvent::Prevote(StateMachineEvent::Prevote(proposal_id, round)) => {
+            ShcEvent::Prevote(StateMachineEvent::Prevote(proposal_id, round, voting_power)) => {
                 let Some(last_vote) = &self.last_prevote else {
                     return Err(ConsensusError::InvalidEvent("No prevote to send".to_string()));
                 };
@@ -294,10 +474,10 @@ impl SingleHeightConsensus {
                 context.broadcast(ConsensusMessage::Vote(last_vote.clone())).await?;
                 Ok(ShcReturn::Tasks(vec![ShcTask::Prevote(
                     self.timeouts.prevote_timeout,
-                    StateMachineEvent::Prevote(proposal_id, round),
+                    StateMachineEvent::Prevote(proposal_id, round, voting_power),
                 )]))
             }
-            ShcEvent::Precommit(StateMachineEvent::Precommit(proposal_id, round)) => {
+            ShcEvent::Precommit(StateMachineEvent::Precommit(proposal_id, round, voting_power)) => {
                 let Some(last_vote) = &self.last_precommit else {
                     return Err(ConsensusError::InvalidEvent("No precommit to send".to_string()));
                 };
@@ -307,36 +487,46 @@ impl SingleHeightConsensus {
                 context.broadcast(ConsensusMessage::Vote(last_vote.clone())).await?;
                 Ok(ShcReturn::Tasks(vec![ShcTask::Precommit(
                     self.timeouts.precommit_timeout,
-                    StateMachineEvent::Precommit(proposal_id, round),
+                    StateMachineEvent::Precommit(proposal_id, round, voting_power),
                 )]))
             }
             ShcEvent::ValidateProposal(
                 StateMachineEvent::Proposal(built_content_id, round, valid_round),
-                received_proposal_id,
+                received_fin,
             ) => {
-                // TODO(matan): Switch to signature validation.
-
-                let mut id = None;
-                if let (Some(built_content_id), Some(ProposalFin { proposal_content_id })) =
-                    (built_content_id, received_proposal_id.clone())
-                {
-                    if built_content_id == proposal_content_id {
-                        id = Some(built_content_id);
-                    }
-                }
-                if id.is_none() {
-                    warn!(
-                        "proposal_id built from content receiver does not match fin: {:#064x?} != \
-                         {:#064x?}",
-                        built_content_id, received_proposal_id
-                    );
+                let proposer = context.proposer(self.height, round);
+                let is_fin_signed = received_fin
+                    .as_ref()
+                    .is_some_and(|fin| self.is_fin_signed_by(proposer, round, fin));
+                if let (true, Some(fin)) = (is_fin_signed, &received_fin) {
+                    self.handle_signed_proposal_fin(context, round, proposer, fin.clone()).await?;
                 }
+                let id = match (built_content_id, received_fin) {
+                    (Some(_), Some(fin)) if !is_fin_signed => {
+                        warn!("Invalid proposal fin signature from {proposer:?}: {fin:?}");
+                        None
+                    }
+                    (Some(built_content_id), Some(fin))
+                        if built_content_id == fin.proposal_content_id =>
+                    {
+                        Some(built_content_id)
+                    }
+                    (built_content_id, received_fin) => {
+                        warn!(
+                            "proposal_id built from content receiver does not match fin: \
+                             {:#064x?} != {:#064x?}",
+                            built_content_id, received_fin
+                        );
+                        None
+                    }
+                };
                 // Retaining the entry for this round prevents us from receiving another proposal on
                 // this round. If the validations failed, which can be caused by a network issue, we
                 // may want to re-open ourselves to this round. The downside is that this may open
                 // us to a spam attack.
                 // TODO(Asmaa): consider revisiting this decision. Spam attacks may not be a problem
                 // given that serial proposing anyways forces us to use interrupts.
+                self.record(WalEntry::Proposal { round, proposal_id: id, valid_round })?;
                 self.proposals.insert(round, id);
                 self.process_inbound_proposal(
                     context,
@@ -344,7 +534,18 @@ impl SingleHeightConsensus {
                 )
                 .await
             }
+            ShcEvent::DuplicateProposalFin(round, Some(fin)) => {
+                let proposer = context.proposer(self.height, round);
+                if self.is_fin_signed_by(proposer, round, &fin) {
+                    self.handle_signed_proposal_fin(context, round, proposer, fin).await
+                } else {
+                    warn!("Invalid proposal fin signature from {proposer:?}: {fin:?}");
+                    Ok(ShcReturn::Tasks(Vec::new()))
+                }
+            }
+            ShcEvent::DuplicateProposalFin(_, None) => Ok(ShcReturn::Tasks(Vec::new())),
             ShcEvent::BuildProposal(StateMachineEvent::GetProposal(proposal_id, round)) => {
+                self.record(WalEntry::Proposal { round, proposal_id, valid_round: None })?;
                 let old = self.proposals.insert(round, proposal_id);
                 assert!(old.is_none(), "There should be no entry for this round.");
                 let leader_fn =
@@ -366,18 +567,29 @@ impl SingleHeightConsensus {
         context: &mut ContextT,
         vote: Vote,
     ) -> Result<ShcReturn, ConsensusError> {
-        if !self.validators.contains(&vote.voter) {
+        let voting_power = self.validators.voting_power(&vote.voter);
+        if voting_power == 0 {
             debug!("Ignoring vote from voter not in validators: vote={:?}", vote);
             return Ok(ShcReturn::Tasks(Vec::new()));
         }
+        let is_signed_by_voter = self
+            .validators
+            .public_key(&vote.voter)
+            .is_some_and(|public_key| verify_vote(&vote, self.signer.chain_id(), public_key));
+        if !is_signed_by_voter {
+            let msg = format!("invalid vote signature: {vote:?}");
+            return Err(ConsensusError::InvalidSignature(vote.voter, self.height, msg));
+        }
 
         let (votes, sm_vote) = match vote.vote_type {
-            VoteType::Prevote => {
-                (&mut self.prevotes, StateMachineEvent::Prevote(vote.block_hash, vote.round))
-            }
-            VoteType::Precommit => {
-                (&mut self.precommits, StateMachineEvent::Precommit(vote.block_hash, vote.round))
-            }
+            VoteType::Prevote => (
+                &mut self.prevotes,
+                StateMachineEvent::Prevote(vote.block_hash, vote.round, voting_power),
+            ),
+            VoteType::Precommit => (
+                &mut self.precommits,
+                StateMachineEvent::Precommit(vote.block_hash, vote.round, voting_power),
+            ),
         };
 
         match votes.entry((vote.round, vote.voter)) {
@@ -386,16 +598,15 @@ impl SingleHeightConsensus {
             }
             Entry::Occupied(entry) => {
                 let old = entry.get();
-                if old.block_hash != vote.block_hash {
-                    return Err(ConsensusError::Equivocation(
-                        self.height,
-                        ConsensusMessage::Vote(old.clone()),
-                        ConsensusMessage::Vote(vote),
-                    ));
-                } else {
+                if old.block_hash == vote.block_hash {
                     // Replay, ignore.
                     return Ok(ShcReturn::Tasks(Vec::new()));
                 }
+                let misbehavior = Misbehavior::ConflictingVotes(ConflictingVotes {
+                    first: old.clone(),
+                    second: vote,
+                });
+                return self.handle_misbehavior(context, misbehavior, true).await;
             }
         }
         let leader_fn = |round: Round| -> ValidatorId { context.proposer(self.height, round) };
@@ -403,6 +614,55 @@ impl SingleHeightConsensus {
         self.handle_state_machine_events(context, sm_events).await
     }
 
+    // Keeps the first fin signed by the proposer in each round, which is used to catch the proposer
+    // signing a fin for different content in the same round.
+    async fn handle_signed_proposal_fin<ContextT: ConsensusContext>(
+        &mut self,
+        context: &mut ContextT,
+        round: Round,
+        proposer: ValidatorId,
+        fin: ProposalFin,
+    ) -> Result<ShcReturn, ConsensusError> {
+        let first = match self.proposal_fins.entry(round) {
+            Entry::Occupied(entry) => entry.get().clone(),
+            Entry::Vacant(entry) => {
+                entry.insert(fin);
+                return Ok(ShcReturn::Tasks(Vec::new()));
+            }
+        };
+        if first.proposal_content_id == fin.proposal_content_id {
+            return Ok(ShcReturn::Tasks(Vec::new()));
+        }
+        let misbehavior = Misbehavior::ConflictingProposals(ConflictingProposals {
+            height: self.height,
+            round,
+            proposer,
+            first,
+            second: fin,
+        });
+        self.handle_misbehavior(context, misbehavior, true).await
+    }
+
+    // Stores verified evidence of an equivocation and reports it to the context, unless the same
+    // equivocation was already reported. Evidence caught by this node is gossiped, while evidence
+    // received from a peer is propagated by the manager.
+    async fn handle_misbehavior<ContextT: ConsensusContext>(
+        &mut self,
+        context: &mut ContextT,
+        misbehavior: Misbehavior,
+        is_caught_locally: bool,
+    ) -> Result<ShcReturn, ConsensusError> {
+        if !self.evidence.insert(misbehavior.clone()) {
+            return Ok(ShcReturn::Tasks(Vec::new()));
+        }
+        metrics::increment_counter!(PAPYRUS_CONSENSUS_EVIDENCE_COUNT);
+        context.report_misbehavior(misbehavior.clone()).await;
+        if is_caught_locally {
+            context.broadcast(ConsensusMessage::Misbehavior(misbehavior)).await?;
+        }
+        Ok(ShcReturn::Tasks(Vec::new()))
+    }
+
     // Handle events output by the state machine.
     #[instrument(skip_all)]
     async fn handle_state_machine_events<ContextT: ConsensusContext>(
@@ -410,13 +670,25 @@ impl SingleHeightConsensus {
         context: &mut ContextT,
         mut events: VecDeque<StateMachineEvent>,
     ) -> Result<ShcReturn, ConsensusError> {
+        // The events are the result of the state machine's current locks, so those must be
+        // recorded before any of the events is acted upon.
+        let locked_value_round = self.state_machine.locked_value_round();
+        let valid_value_round = self.state_machine.valid_value_round();
+        if self.wal.is_some()
+            && (locked_value_round != self.recorded_locked_value_round
+                || valid_value_round != self.recorded_valid_value_round)
+        {
+            self.record(WalEntry::Lock { locked_value_round, valid_value_round })?;
+            self.recorded_locked_value_round = locked_value_round;
+            self.recorded_valid_value_round = valid_value_round;
+        }
         let mut ret_val = Vec::new();
         while let Some(event) = events.pop_front() {
             trace!("Handling event: {:?}", event);
             match event {
                 StateMachineEvent::GetProposal(proposal_id, round) => {
                     ret_val.extend(
-                        self.handle_state_machine_get_proposal(context, proposal_id, round).await,
+                        self.handle_state_machine_get_proposal(context, proposal_id, round).await?,
                     );
                 }
                 StateMachineEvent::Proposal(proposal_id, round, valid_round) => {
@@ -426,7 +698,7 @@ impl SingleHeightConsensus {
                 StateMachineEvent::Decision(proposal_id, round) => {
                     return self.handle_state_machine_decision(proposal_id, round).await;
                 }
-                StateMachineEvent::Prevote(proposal_id, round) => {
+                StateMachineEvent::Prevote(proposal_id, round, _) => {
                     ret_val.extend(
                         self.handle_state_machine_vote(
                             context,
@@ -437,7 +709,7 @@ impl SingleHeightConsensus {
                         .await?,
                     );
                 }
-                StateMachineEvent::Precommit(proposal_id, round) => {
+                StateMachineEvent::Precommit(proposal_id, round, _) => {
                     ret_val.extend(
                         self.handle_state_machine_vote(
                             context,
@@ -470,7 +742,7 @@ impl SingleHeightConsensus {
         context: &mut ContextT,
         proposal_id: Option<ProposalContentId>,
         round: Round,
-    ) -> Vec<ShcTask> {
+    ) -> Result<Vec<ShcTask>, ConsensusError> {
         assert!(
             proposal_id.is_none(),
             "ProposalContentId must be None since the state machine is requesting a \
@@ -478,12 +750,34 @@ impl SingleHeightConsensus {
         );
         debug!("Proposer");
 
+        if let Some((proposal_id, _)) = self.replayed_proposals.remove(&round) {
+            // The proposal was built, and its content streamed, before restarting. Building another
+            // one would equivocate.
+            let (fin_sender, fin_receiver) = oneshot::channel();
+            if let Some(proposal_id) = proposal_id {
+                fin_sender.send(proposal_id).expect("The receiver is held below");
+            }
+            return Ok(vec![ShcTask::BuildProposal(round, fin_receiver)]);
+        }
+        if self.replayed_builds.remove(&round) {
+            // Building was interrupted by the restart, after some of the proposal may have been
+            // streamed. Building another one would equivocate, so the round gets no proposal.
+            warn!("Not building another proposal for round {round} after restarting");
+            let (_, fin_receiver) = oneshot::channel();
+            return Ok(vec![ShcTask::BuildProposal(round, fin_receiver)]);
+        }
+        self.record(WalEntry::BuildingProposal { round })?;
         // TODO: Figure out how to handle failed proposal building. I believe this should be handled
         // by applying timeoutPropose when we are the leader.
-        let init =
-            ProposalInit { height: self.height, round, proposer: self.id, valid_round: None };
+        let init = self.signer.sign_proposal_init(ProposalInit {
+            height: self.height,
+            round,
+            proposer: self.id,
+            valid_round: None,
+            signature: Signature::default(),
+        });
         let fin_receiver = context.build_proposal(init, self.timeouts.proposal_timeout).await;
-        vec![ShcTask::BuildProposal(round, fin_receiver)]
+        Ok(vec![ShcTask::BuildProposal(round, fin_receiver)])
     }
 
     #[instrument(skip(self, context), level = "debug")]
@@ -505,12 +799,13 @@ impl SingleHeightConsensus {
             .expect("proposals should have proposal for valid_round")
             .expect("proposal should not be None");
         assert_eq!(id, proposal_id, "proposal should match the stored proposal");
-        let init = ProposalInit {
+        let init = self.signer.sign_proposal_init(ProposalInit {
             height: self.height,
             round,
             proposer: self.id,
             valid_round: Some(valid_round),
-        };
+            signature: Signature::default(),
+        });
         context.repropose(id, init).await;
         let old = self.proposals.insert(round, Some(proposal_id));
         assert!(old.is_none(), "There should be no entry for this round.");
@@ -524,13 +819,34 @@ impl SingleHeightConsensus {
         round: Round,
         vote_type: VoteType,
     ) -> Result<Vec<ShcTask>, ConsensusError> {
+        let old_vote = match vote_type {
+            VoteType::Prevote => self.prevotes.get(&(round, self.id)),
+            VoteType::Precommit => self.precommits.get(&(round, self.id)),
+        };
+        if let Some(old) = old_vote {
+            // TODO(matan): Consider refactoring not to panic, rather log and return the error.
+            panic!(
+                "State machine should not send repeat votes: old={:?}, new={:?}",
+                old, proposal_id
+            );
+        }
+        let vote = self.signer.sign_vote(Vote {
+            vote_type: vote_type.clone(),
+            height: self.height.0,
+            round,
+            block_hash: proposal_id,
+            voter: self.id,
+            signature: Signature::default(),
+        });
+        self.record(WalEntry::Vote(vote.clone()))?;
+        let voting_power = self.validators.voting_power(&self.id);
         let (votes, last_vote, task) = match vote_type {
             VoteType::Prevote => (
                 &mut self.prevotes,
                 &mut self.last_prevote,
                 ShcTask::Prevote(
                     self.timeouts.prevote_timeout,
-                    StateMachineEvent::Prevote(proposal_id, round),
+                    StateMachineEvent::Prevote(proposal_id, round, voting_power),
                 ),
             ),
             VoteType::Precommit => (
@@ -538,21 +854,11 @@ impl SingleHeightConsensus {
                 &mut self.last_precommit,
                 ShcTask::Precommit(
                     self.timeouts.precommit_timeout,
-                    StateMachineEvent::Precommit(proposal_id, round),
+                    StateMachineEvent::Precommit(proposal_id, round, voting_power),
                 ),
             ),
         };
-        let vote = Vote {
-            vote_type,
-            height: self.height.0,
-            round,
-            block_hash: proposal_id,
-            voter: self.id,
-        };
-        if let Some(old) = votes.insert((round, self.id), vote.clone()) {
-            // TODO(matan): Consider refactoring not to panic, rather log and return the error.
-            panic!("State machine should not send repeat votes: old={:?}, new={:?}", old, vote);
-        }
+        votes.insert((round, self.id), vote.clone());
         context.broadcast(ConsensusMessage::Vote(vote.clone())).await?;
         if last_vote.as_ref().map_or(false, |last| round < last.round) {
             return Ok(Vec::new());
@@ -576,17 +882,59 @@ impl SingleHeightConsensus {
         let supporting_precommits: Vec<Vote> = self
             .validators
             .iter()
-            .filter_map(|v| {
+            .filter_map(|(v, _)| {
                 let vote = self.precommits.get(&(round, *v))?;
                 if vote.block_hash == Some(proposal_id) { Some(vote.clone()) } else { None }
             })
             .collect();
-        // TODO(matan): Check actual weights.
-        assert!(
-            supporting_precommits.len()
-                >= usize::try_from(self.state_machine.quorum_size())
-                    .expect("u32 should fit in usize")
-        );
+        let supporting_voting_power: VotingPower = supporting_precommits
+            .iter()
+            .map(|vote| self.validators.voting_power(&vote.voter))
+            .sum();
+        assert!(supporting_voting_power >= self.state_machine.quorum_size());
         Ok(ShcReturn::Decision(Decision { precommits: supporting_precommits, block }))
     }
+
+    // Records `entry` in the WAL, if there is one. Must be called before acting on `entry`.
+    fn record(&mut self, entry: WalEntry) -> Result<(), WalError> {
+        if let Some(wal) = &mut self.wal {
+            wal.append(entry)?;
+        }
+        Ok(())
+    }
+
+    fn is_fin_signed_by(&self, proposer: ValidatorId, round: Round, fin: &ProposalFin) -> bool {
+        self.validators.public_key(&proposer).is_some_and(|public_key| {
+            verify_proposal_fin(
+                self.height,
+                round,
+                proposer,
+                fin,
+                self.signer.chain_id(),
+                public_key,
+            )
+        })
+    }
+}
+
+// Reads the parts of a proposal until its fin, without validating them. Gives up once `timeout`
+// passes, so a stream which is never finished isn't held onto.
+fn read_proposal_fin<ProposalPartT>(
+    mut parts: mpsc::Receiver<ProposalPartT>,
+    timeout: Duration,
+) -> oneshot::Receiver<ProposalFin>
+where
+    ProposalPartT: TryInto<ProposalFin> + Send + 'static,
+{
+    let (fin_sender, fin_receiver) = oneshot::channel();
+    tokio::spawn(tokio::time::timeout(timeout, async move {
+        while let Some(part) = parts.next().await {
+            if let Ok(fin) = part.try_into() {
+                // Sending fails only if the height is over.
+                let _ = fin_sender.send(fin);
+                return;
+            }
+        }
+    }));
+    fin_receiver
 }
@@ -1,39 +1,62 @@
 use futures::channel::{mpsc, oneshot};
 use futures::SinkExt;
 use lazy_static::lazy_static;
-use papyrus_protobuf::consensus::{ConsensusMessage, ProposalFin, ProposalInit};
+use papyrus_protobuf::consensus::{
+    ConflictingProposals,
+    ConflictingVotes,
+    ConsensusMessage,
+    Misbehavior,
+    ProposalFin,
+    ProposalInit,
+    ProposalPart,
+    Vote,
+};
 use starknet_api::block::{BlockHash, BlockNumber};
+use starknet_api::crypto::utils::Signature;
 use starknet_types_core::felt::Felt;
+use tempfile::TempDir;
 use test_case::test_case;
 use tokio;
 
-use super::SingleHeightConsensus;
+use super::{read_proposal_fin, SingleHeightConsensus};
 use crate::config::TimeoutsConfig;
+use crate::signature::ConsensusSigner;
 use crate::single_height_consensus::{ShcEvent, ShcReturn, ShcTask};
 use crate::state_machine::StateMachineEvent;
-use crate::test_utils::{precommit, prevote, MockProposalPart, MockTestContext, TestBlock};
+use crate::test_utils::{
+    into_vote,
+    precommit,
+    prevote,
+    proposal_fin,
+    proposal_init,
+    MockProposalPart,
+    MockTestContext,
+    TestBlock,
+};
 use crate::types::{ConsensusError, ValidatorId, DEFAULT_VALIDATOR_ID};
+use crate::validator_set::ValidatorSet;
+use crate::wal::{HeightWal, WalEntry};
 
 lazy_static! {
     static ref PROPOSER_ID: ValidatorId = DEFAULT_VALIDATOR_ID.into();
     static ref VALIDATOR_ID_1: ValidatorId = (DEFAULT_VALIDATOR_ID + 1).into();
     static ref VALIDATOR_ID_2: ValidatorId = (DEFAULT_VALIDATOR_ID + 2).into();
     static ref VALIDATOR_ID_3: ValidatorId = (DEFAULT_VALIDATOR_ID + 3).into();
-    static ref VALIDATORS: Vec<ValidatorId> =
-        vec![*PROPOSER_ID, *VALIDATOR_ID_1, *VALIDATOR_ID_2, *VALIDATOR_ID_3];
+    static ref VALIDATORS: ValidatorSet = ValidatorSet::with_equal_voting_power([
+        *PROPOSER_ID,
+        *VALIDATOR_ID_1,
+        *VALIDATOR_ID_2,
+        *VALIDATOR_ID_3
+    ])
+    .with_testing_public_keys();
     static ref BLOCK: TestBlock = TestBlock { content: vec![1, 2, 3], id: BlockHash(Felt::ONE) };
-    static ref PROPOSAL_INIT: ProposalInit = ProposalInit {
-        height: BlockNumber(0),
-        round: 0,
-        proposer: *PROPOSER_ID,
-        valid_round: None
-    };
+    static ref PROPOSAL_INIT: ProposalInit = proposal_init(0, 0, *PROPOSER_ID);
     static ref TIMEOUTS: TimeoutsConfig = TimeoutsConfig::default();
     static ref VALIDATE_PROPOSAL_EVENT: ShcEvent = ShcEvent::ValidateProposal(
         StateMachineEvent::Proposal(Some(BLOCK.id), PROPOSAL_INIT.round, PROPOSAL_INIT.valid_round,),
-        Some(ProposalFin { proposal_content_id: BLOCK.id }),
+        Some(PROPOSAL_FIN.clone()),
     );
-    static ref PROPOSAL_FIN: ProposalFin = ProposalFin { proposal_content_id: BLOCK.id };
+    static ref PROPOSAL_FIN: ProposalFin = proposal_fin(&PROPOSAL_INIT, BLOCK.id);
 }
 
 const CHANNEL_SIZE: usize = 1;
@@ -41,14 +64,14 @@ const CHANNEL_SIZE: usize = 1;
 fn prevote_task(block_felt: Option<Felt>, round: u32) -> ShcTask {
     ShcTask::Prevote(
         TIMEOUTS.prevote_timeout,
-        StateMachineEvent::Prevote(block_felt.map(BlockHash), round),
+        StateMachineEvent::Prevote(block_felt.map(BlockHash), round, 1),
     )
 }
 
 fn precommit_task(block_felt: Option<Felt>, round: u32) -> ShcTask {
     ShcTask::Precommit(
         TIMEOUTS.precommit_timeout,
-        StateMachineEvent::Precommit(block_felt.map(BlockHash), round),
+        StateMachineEvent::Precommit(block_felt.map(BlockHash), round, 1),
     )
 }
 
@@ -82,7 +105,8 @@ async fn proposer() {
         BlockNumber(0),
         false,
         *PROPOSER_ID,
-        VALIDATORS.to_vec(),
+        ConsensusSigner::for_testing(*PROPOSER_ID),
+        VALIDATORS.clone(),
         TIMEOUTS.clone(),
     );
 
@@ -149,12 +173,10 @@ async fn proposer() {
         panic!("Expected decision");
     };
     assert_eq!(decision.block, BLOCK.id);
-    assert!(
-        decision
-            .precommits
-            .into_iter()
-            .all(|item| precommits.contains(&ConsensusMessage::Vote(item)))
-    );
+    assert!(decision
+        .precommits
+        .into_iter()
+        .all(|item| precommits.contains(&ConsensusMessage::Vote(item))));
 }
 
 #[test_case(false; "single_proposal")]
@@ -168,7 +190,8 @@ async fn validator(repeat_proposal: bool) {
         BlockNumber(0),
         false,
         *VALIDATOR_ID_1,
-        VALIDATORS.to_vec(),
+        ConsensusSigner::for_testing(*VALIDATOR_ID_1),
+        VALIDATORS.clone(),
         TIMEOUTS.clone(),
     );
 
@@ -193,9 +216,14 @@ async fn validator(repeat_proposal: bool) {
         Ok(ShcReturn::Tasks(vec![prevote_task(Some(BLOCK.id.0), 0)]))
     );
     if repeat_proposal {
-        // Send the same proposal again, which should be ignored (no expectations).
+        // Send the same proposal again, which isn't validated again (no expectations); only its
+        // fin is read.
         let shc_ret = handle_proposal(&mut shc, &mut context).await;
-        assert_eq!(shc_ret, ShcReturn::Tasks(Vec::new()));
+        let (_, fin_receiver) = oneshot::channel();
+        assert_eq!(
+            shc_ret,
+            ShcReturn::Tasks(vec![ShcTask::ReadDuplicateProposalFin(0, fin_receiver)])
+        );
     }
     assert_eq!(
         shc.handle_message(&mut context, prevote(Some(BLOCK.id.0), 0, 0, *PROPOSER_ID)).await,
@@ -230,12 +258,10 @@ async fn validator(repeat_proposal: bool) {
         panic!("Expected decision");
     };
     assert_eq!(decision.block, BLOCK.id);
-    assert!(
-        decision
-            .precommits
-            .into_iter()
-            .all(|item| precommits.contains(&ConsensusMessage::Vote(item)))
-    );
+    assert!(decision
+        .precommits
+        .into_iter()
+        .all(|item| precommits.contains(&ConsensusMessage::Vote(item))));
 }
 
 #[test_case(true; "repeat")]
@@ -248,11 +274,12 @@ async fn vote_twice(same_vote: bool) {
         BlockNumber(0),
         false,
         *VALIDATOR_ID_1,
-        VALIDATORS.to_vec(),
+        ConsensusSigner::for_testing(*VALIDATOR_ID_1),
+        VALIDATORS.clone(),
         TIMEOUTS.clone(),
     );
 
-    context.expect_proposer().times(1).returning(move |_, _| *PROPOSER_ID);
+    context.expect_proposer().times(2).returning(move |_, _| *PROPOSER_ID);
     context.expect_validate_proposal().times(1).returning(move |_, _, _, _, _| {
         let (block_sender, block_receiver) = oneshot::channel();
         block_sender.send((BLOCK.id, PROPOSAL_FIN.clone())).unwrap();
@@ -293,12 +320,28 @@ async fn vote_twice(same_vote: bool) {
 
     let second_vote =
         if same_vote { first_vote.clone() } else { precommit(Some(Felt::TWO), 0, 0, *PROPOSER_ID) };
-    let res = shc.handle_message(&mut context, second_vote.clone()).await;
-    if same_vote {
-        assert_eq!(res, Ok(ShcReturn::Tasks(Vec::new())));
-    } else {
-        assert!(matches!(res, Err(ConsensusError::Equivocation(_, _, _))));
+    if !same_vote {
+        // The equivocation is reported and gossiped, and the second vote is ignored.
+        let misbehavior = Misbehavior::ConflictingVotes(ConflictingVotes {
+            first: into_vote(first_vote.clone()),
+            second: into_vote(second_vote.clone()),
+        });
+        let expected_misbehavior = misbehavior.clone();
+        context
+            .expect_report_misbehavior()
+            .times(1)
+            .withf(move |misbehavior| misbehavior == &expected_misbehavior)
+            .return_const(());
+        context
+            .expect_broadcast()
+            .times(1)
+            .withf(move |msg: &ConsensusMessage| {
+                msg == &ConsensusMessage::Misbehavior(misbehavior.clone())
+            })
+            .returning(move |_| Ok(()));
     }
+    let res = shc.handle_message(&mut context, second_vote.clone()).await;
+    assert_eq!(res, Ok(ShcReturn::Tasks(Vec::new())));
 
     let ShcReturn::Decision(decision) = shc
         .handle_message(&mut context, precommit(Some(BLOCK.id.0), 0, 0, *VALIDATOR_ID_2))
@@ -318,7 +361,8 @@ async fn rebroadcast_votes() {
         BlockNumber(0),
         false,
         *PROPOSER_ID,
-        VALIDATORS.to_vec(),
+        ConsensusSigner::for_testing(*PROPOSER_ID),
+        VALIDATORS.clone(),
         TIMEOUTS.clone(),
     );
 
@@ -366,7 +410,7 @@ async fn rebroadcast_votes() {
     assert_eq!(
         shc.handle_event(
             &mut context,
-            ShcEvent::Precommit(StateMachineEvent::Precommit(Some(BLOCK.id), 0))
+            ShcEvent::Precommit(StateMachineEvent::Precommit(Some(BLOCK.id), 0, 1))
         )
         .await,
         Ok(ShcReturn::Tasks(vec![precommit_task(Some(BLOCK.id.0), 0),]))
@@ -381,7 +425,8 @@ async fn repropose() {
         BlockNumber(0),
         false,
         *PROPOSER_ID,
-        VALIDATORS.to_vec(),
+        ConsensusSigner::for_testing(*PROPOSER_ID),
+        VALIDATORS.clone(),
         TIMEOUTS.clone(),
     );
 
@@ -459,10 +504,391 @@ async fn repropose() {
         panic!("Expected decision");
     };
     assert_eq!(decision.block, BLOCK.id);
+    assert!(decision
+        .precommits
+        .into_iter()
+        .all(|item| precommits.contains(&ConsensusMessage::Vote(item))));
+}
+
+#[tokio::test]
+async fn vote_with_invalid_signature_is_rejected() {
+    let mut context = MockTestContext::new();
+    let mut shc = SingleHeightConsensus::new(
+        BlockNumber(0),
+        false,
+        *VALIDATOR_ID_1,
+        ConsensusSigner::for_testing(*VALIDATOR_ID_1),
+        VALIDATORS.clone(),
+        TIMEOUTS.clone(),
+    );
+    context.expect_set_height_and_round().returning(move |_, _| ());
+
+    // The signature is over a different block.
+    let ConsensusMessage::Vote(vote) = prevote(Some(BLOCK.id.0), 0, 0, *VALIDATOR_ID_2) else {
+        unreachable!();
+    };
+    let forged_vote = Vote { block_hash: Some(BlockHash(Felt::TWO)), ..vote.clone() };
+    let unsigned_vote = Vote { signature: Signature::default(), ..vote };
+
+    for vote in [forged_vote, unsigned_vote] {
+        let res = shc.handle_message(&mut context, ConsensusMessage::Vote(vote)).await;
+        assert!(
+            matches!(res, Err(ConsensusError::InvalidSignature(voter, _, _)) if voter == *VALIDATOR_ID_2)
+        );
+    }
+}
+
+#[tokio::test]
+async fn proposal_init_with_invalid_signature_is_rejected() {
+    let mut context = MockTestContext::new();
+    let mut shc = SingleHeightConsensus::new(
+        BlockNumber(0),
+        false,
+        *VALIDATOR_ID_1,
+        ConsensusSigner::for_testing(*VALIDATOR_ID_1),
+        VALIDATORS.clone(),
+        TIMEOUTS.clone(),
+    );
+    context.expect_proposer().returning(move |_, _| *PROPOSER_ID);
+    context.expect_validate_proposal().times(0);
+
+    // Signed by a validator which isn't the proposer.
+    let init =
+        ConsensusSigner::for_testing(*VALIDATOR_ID_2).sign_proposal_init(PROPOSAL_INIT.clone());
+    let (_, content_receiver) = mpsc::channel(CHANNEL_SIZE);
+    let res = shc.handle_proposal(&mut context, init, content_receiver).await;
+
+    assert!(
+        matches!(res, Err(ConsensusError::InvalidSignature(proposer, _, _)) if proposer == *PROPOSER_ID)
+    );
+}
+
+#[tokio::test]
+async fn proposal_fin_with_invalid_signature_is_not_accepted() {
+    let mut context = MockTestContext::new();
+    let mut shc = SingleHeightConsensus::new(
+        BlockNumber(0),
+        false,
+        *VALIDATOR_ID_1,
+        ConsensusSigner::for_testing(*VALIDATOR_ID_1),
+        VALIDATORS.clone(),
+        TIMEOUTS.clone(),
+    );
+    context.expect_proposer().returning(move |_, _| *PROPOSER_ID);
+    context.expect_validate_proposal().times(1).returning(move |_, _, _, _, _| {
+        let (block_sender, block_receiver) = oneshot::channel();
+        block_sender.send((BLOCK.id, PROPOSAL_FIN.clone())).unwrap();
+        block_receiver
+    });
+    context.expect_set_height_and_round().returning(move |_, _| ());
+    // The content matches, but since the fin isn't signed by the proposer we prevote nil.
+    context
+        .expect_broadcast()
+        .times(1)
+        .withf(move |msg: &ConsensusMessage| msg == &prevote(None, 0, 0, *VALIDATOR_ID_1))
+        .returning(move |_| Ok(()));
+    handle_proposal(&mut shc, &mut context).await;
+
+    let unsigned_fin = ProposalFin { signature: Signature::default(), ..PROPOSAL_FIN.clone() };
+    let event = ShcEvent::ValidateProposal(
+        StateMachineEvent::Proposal(Some(BLOCK.id), PROPOSAL_INIT.round, PROPOSAL_INIT.valid_round),
+        Some(unsigned_fin),
+    );
+    assert_eq!(
+        shc.handle_event(&mut context, event).await,
+        Ok(ShcReturn::Tasks(vec![prevote_task(None, 0)]))
+    );
+}
+
+#[tokio::test]
+async fn conflicting_proposal_fin_is_reported() {
+    // Setup.
+    let mut context = MockTestContext::new();
+    let mut shc = SingleHeightConsensus::new(
+        BlockNumber(0),
+        false,
+        *VALIDATOR_ID_1,
+        ConsensusSigner::for_testing(*VALIDATOR_ID_1),
+        VALIDATORS.clone(),
+        TIMEOUTS.clone(),
+    );
+    context.expect_proposer().returning(move |_, _| *PROPOSER_ID);
+    context.expect_validate_proposal().times(1).returning(move |_, _, _, _, _| {
+        let (_, block_receiver) = oneshot::channel();
+        block_receiver
+    });
+    context.expect_set_height_and_round().returning(move |_, _| ());
+    context
+        .expect_broadcast()
+        .times(1)
+        .withf(move |msg: &ConsensusMessage| {
+            msg == &prevote(Some(BLOCK.id.0), 0, 0, *VALIDATOR_ID_1)
+        })
+        .returning(move |_| Ok(()));
+    handle_proposal(&mut shc, &mut context).await;
+    // Another proposal for the same round isn't validated, only its fin is read.
+    let (_, fin_receiver) = oneshot::channel();
+    assert_eq!(
+        handle_proposal(&mut shc, &mut context).await,
+        ShcReturn::Tasks(vec![ShcTask::ReadDuplicateProposalFin(
+            PROPOSAL_INIT.round,
+            fin_receiver
+        )])
+    );
+    shc.handle_event(&mut context, VALIDATE_PROPOSAL_EVENT.clone()).await.unwrap();
+
+    let conflicting_fin = proposal_fin(&PROPOSAL_INIT, BlockHash(Felt::TWO));
+    let forged_fin = ProposalFin { signature: Signature::default(), ..conflicting_fin.clone() };
+    let misbehavior = Misbehavior::ConflictingProposals(ConflictingProposals {
+        height: PROPOSAL_INIT.height,
+        round: PROPOSAL_INIT.round,
+        proposer: *PROPOSER_ID,
+        first: PROPOSAL_FIN.clone(),
+        second: conflicting_fin.clone(),
+    });
+    let expected_misbehavior = misbehavior.clone();
+    // Reported and gossiped only once, even though the conflicting fin is received twice.
+    context
+        .expect_report_misbehavior()
+        .times(1)
+        .withf(move |misbehavior| misbehavior == &expected_misbehavior)
+        .return_const(());
+    context
+        .expect_broadcast()
+        .times(1)
+        .withf(move |msg: &ConsensusMessage| {
+            msg == &ConsensusMessage::Misbehavior(misbehavior.clone())
+        })
+        .returning(move |_| Ok(()));
+
+    for fin in [forged_fin, conflicting_fin.clone(), conflicting_fin] {
+        // Test.
+        let event = ShcEvent::DuplicateProposalFin(PROPOSAL_INIT.round, Some(fin));
+        let res = shc.handle_event(&mut context, event).await;
+
+        // Assert.
+        assert_eq!(res, Ok(ShcReturn::Tasks(Vec::new())));
+    }
+}
+
+#[tokio::test]
+async fn duplicate_proposal_fin_is_read() {
+    let (mut content_sender, content_receiver) = mpsc::channel(CHANNEL_SIZE);
+    let fin_receiver = read_proposal_fin(content_receiver, TIMEOUTS.proposal_timeout);
+
+    content_sender.send(ProposalPart::Init(PROPOSAL_INIT.clone())).await.unwrap();
+    content_sender.send(ProposalPart::Fin(PROPOSAL_FIN.clone())).await.unwrap();
+
+    assert_eq!(fin_receiver.await, Ok(PROPOSAL_FIN.clone()));
+}
+
+#[tokio::test]
+async fn gossiped_evidence_is_verified_and_reported() {
+    // Setup.
+    let mut context = MockTestContext::new();
+    let mut shc = SingleHeightConsensus::new(
+        BlockNumber(0),
+        false,
+        *VALIDATOR_ID_1,
+        ConsensusSigner::for_testing(*VALIDATOR_ID_1),
+        VALIDATORS.clone(),
+        TIMEOUTS.clone(),
+    );
+    let votes = ConflictingVotes {
+        first: into_vote(prevote(Some(BLOCK.id.0), 0, 0, *VALIDATOR_ID_2)),
+        second: into_vote(prevote(None, 0, 0, *VALIDATOR_ID_2)),
+    };
+    let forged_votes = ConflictingVotes {
+        second: Vote { signature: Signature::default(), ..votes.second.clone() },
+        ..votes.clone()
+    };
+    let misbehavior = Misbehavior::ConflictingVotes(votes);
+    let expected_misbehavior = misbehavior.clone();
+    // Evidence from peers is propagated by the manager, so it isn't broadcast again.
+    context.expect_broadcast().times(0);
+    context
+        .expect_report_misbehavior()
+        .times(1)
+        .withf(move |misbehavior| misbehavior == &expected_misbehavior)
+        .return_const(());
+
+    // Test.
+    let forged_res = shc
+        .handle_message(
+            &mut context,
+            ConsensusMessage::Misbehavior(Misbehavior::ConflictingVotes(forged_votes)),
+        )
+        .await;
+    let res =
+        shc.handle_message(&mut context, ConsensusMessage::Misbehavior(misbehavior.clone())).await;
+    let repeated_res =
+        shc.handle_message(&mut context, ConsensusMessage::Misbehavior(misbehavior)).await;
+
+    // Assert.
     assert!(
-        decision
-            .precommits
-            .into_iter()
-            .all(|item| precommits.contains(&ConsensusMessage::Vote(item)))
+        matches!(forged_res, Err(ConsensusError::InvalidEvidence(voter, _, _)) if voter == *VALIDATOR_ID_2)
+    );
+    assert_eq!(res, Ok(ShcReturn::Tasks(Vec::new())));
+    assert_eq!(repeated_res, Ok(ShcReturn::Tasks(Vec::new())));
+}
+
+#[tokio::test]
+async fn restarted_proposer_does_not_build_another_proposal() {
+    // Setup.
+    let wal_dir = TempDir::new().unwrap();
+    let wal_path = wal_dir.path().join("consensus.wal");
+    let new_shc = || {
+        let (wal, entries) = HeightWal::open(&wal_path, BlockNumber(0)).unwrap();
+        SingleHeightConsensus::new(
+            BlockNumber(0),
+            false,
+            *PROPOSER_ID,
+            ConsensusSigner::for_testing(*PROPOSER_ID),
+            VALIDATORS.clone(),
+            TIMEOUTS.clone(),
+        )
+        .with_wal(wal, entries)
+    };
+    let mut context = MockTestContext::new();
+    context.expect_proposer().returning(move |_, _| *PROPOSER_ID);
+    context.expect_set_height_and_round().returning(move |_, _| ());
+    context.expect_build_proposal().times(1).returning(move |_, _| {
+        let (block_sender, block_receiver) = oneshot::channel();
+        block_sender.send(BLOCK.id).unwrap();
+        block_receiver
+    });
+    // Only before restarting, afterwards the prevote is rebroadcast by its task.
+    context
+        .expect_broadcast()
+        .times(1)
+        .withf(move |msg: &ConsensusMessage| msg == &prevote(Some(BLOCK.id.0), 0, 0, *PROPOSER_ID))
+        .returning(move |_| Ok(()));
+    let mut shc = new_shc();
+    shc.start(&mut context).await.unwrap();
+    let build_proposal = ShcEvent::BuildProposal(StateMachineEvent::GetProposal(Some(BLOCK.id), 0));
+    shc.handle_event(&mut context, build_proposal).await.unwrap();
+    drop(shc);
+
+    // Test.
+    let mut shc = new_shc();
+    let tasks = shc.start(&mut context).await.unwrap().into_tasks().unwrap();
+
+    // Assert.
+    let (_, fin_receiver) = oneshot::channel();
+    assert_eq!(
+        tasks,
+        vec![ShcTask::BuildProposal(0, fin_receiver), prevote_task(Some(BLOCK.id.0), 0)]
     );
+    // The proposal built before restarting is handed back without building it again, and without
+    // prevoting again.
+    let event = tasks.into_iter().next().unwrap().run().await;
+    assert_eq!(shc.handle_event(&mut context, event).await, Ok(ShcReturn::Tasks(Vec::new())));
+}
+
+#[tokio::test]
+async fn restarted_proposer_does_not_rebuild_an_interrupted_proposal() {
+    // Setup.
+    let wal_dir = TempDir::new().unwrap();
+    let wal_path = wal_dir.path().join("consensus.wal");
+    // The proposer restarted while building its proposal.
+    let (mut wal, _) = HeightWal::open(&wal_path, BlockNumber(0)).unwrap();
+    wal.append(WalEntry::BuildingProposal { round: 0 }).unwrap();
+    drop(wal);
+
+    let mut context = MockTestContext::new();
+    context.expect_proposer().returning(move |_, _| *PROPOSER_ID);
+    context.expect_set_height_and_round().returning(move |_, _| ());
+    context.expect_build_proposal().times(0);
+    let (wal, entries) = HeightWal::open(&wal_path, BlockNumber(0)).unwrap();
+    let mut shc = SingleHeightConsensus::new(
+        BlockNumber(0),
+        false,
+        *PROPOSER_ID,
+        ConsensusSigner::for_testing(*PROPOSER_ID),
+        VALIDATORS.clone(),
+        TIMEOUTS.clone(),
+    )
+    .with_wal(wal, entries);
+
+    // Test.
+    let tasks = shc.start(&mut context).await.unwrap().into_tasks().unwrap();
+
+    // Assert.
+    let (_, fin_receiver) = oneshot::channel();
+    assert_eq!(tasks, vec![ShcTask::BuildProposal(0, fin_receiver)]);
+    // The round is left without a proposal.
+    let event = tasks.into_iter().next().unwrap().run().await;
+    assert!(matches!(event, ShcEvent::BuildProposal(StateMachineEvent::GetProposal(None, 0))));
+}
+
+#[tokio::test]
+async fn restarted_validator_does_not_equivocate() {
+    // Setup.
+    let wal_dir = TempDir::new().unwrap();
+    let wal_path = wal_dir.path().join("consensus.wal");
+    // Before restarting, the validator prevoted for the proposal, saw a prevote quorum for it and
+    // precommitted it.
+    let (mut wal, _) = HeightWal::open(&wal_path, BlockNumber(0)).unwrap();
+    wal.append(WalEntry::Proposal { round: 0, proposal_id: Some(BLOCK.id), valid_round: None })
+        .unwrap();
+    wal.append(WalEntry::Vote(into_vote(prevote(Some(BLOCK.id.0), 0, 0, *VALIDATOR_ID_1))))
+        .unwrap();
+    wal.append(WalEntry::Lock {
+        locked_value_round: Some((BLOCK.id, 0)),
+        valid_value_round: Some((BLOCK.id, 0)),
+    })
+    .unwrap();
+    wal.append(WalEntry::Vote(into_vote(precommit(Some(BLOCK.id.0), 0, 0, *VALIDATOR_ID_1))))
+        .unwrap();
+    drop(wal);
+
+    let mut context = MockTestContext::new();
+    context.expect_proposer().returning(move |_, _| *PROPOSER_ID);
+    context.expect_set_height_and_round().returning(move |_, _| ());
+    // The votes cast before restarting are only rebroadcast by their tasks.
+    context.expect_broadcast().times(0);
+    let (wal, entries) = HeightWal::open(&wal_path, BlockNumber(0)).unwrap();
+    let mut shc = SingleHeightConsensus::new(
+        BlockNumber(0),
+        false,
+        *VALIDATOR_ID_1,
+        ConsensusSigner::for_testing(*VALIDATOR_ID_1),
+        VALIDATORS.clone(),
+        TIMEOUTS.clone(),
+    )
+    .with_wal(wal, entries);
+    // The restored proposal doesn't lead to another prevote.
+    assert_eq!(
+        shc.start(&mut context).await,
+        Ok(ShcReturn::Tasks(vec![
+            ShcTask::TimeoutPropose(
+                TIMEOUTS.proposal_timeout,
+                StateMachineEvent::TimeoutPropose(0)
+            ),
+            prevote_task(Some(BLOCK.id.0), 0),
+            precommit_task(Some(BLOCK.id.0), 0),
+        ]))
+    );
+    for voter in [*VALIDATOR_ID_2, *VALIDATOR_ID_3] {
+        shc.handle_message(&mut context, prevote(None, 0, 0, voter)).await.unwrap();
+    }
+
+    // Test.
+    // Without a prevote quorum for the proposal, the state machine would precommit nil on timeout,
+    // but the precommit cast before restarting stands.
+    let timeout_prevote = ShcEvent::TimeoutPrevote(StateMachineEvent::TimeoutPrevote(0));
+    let mut ret = shc.handle_event(&mut context, timeout_prevote).await;
+    assert_eq!(ret, Ok(ShcReturn::Tasks(Vec::new())));
+    // The restored precommit counts towards a decision on the proposal.
+    for voter in [*VALIDATOR_ID_2, *VALIDATOR_ID_3] {
+        ret = shc.handle_message(&mut context, precommit(Some(BLOCK.id.0), 0, 0, voter)).await;
+    }
+
+    // Assert.
+    let Ok(ShcReturn::Decision(decision)) = ret else {
+        panic!("Expected decision");
+    };
+    assert_eq!(decision.block, BLOCK.id);
+    let own_precommit = into_vote(precommit(Some(BLOCK.id.0), 0, 0, *VALIDATOR_ID_1));
+    assert!(decision.precommits.contains(&own_precommit));
 }
@@ -12,6 +12,7 @@ use std::collections::{HashMap, HashSet, VecDeque};
 use tracing::trace;
 
 use crate::types::{ProposalContentId, Round, ValidatorId};
+use crate::validator_set::VotingPower;
 
 /// Events which the state machine sends/receives.
 #[derive(Debug, Clone, PartialEq)]
@@ -24,10 +25,12 @@ pub enum StateMachineEvent {
     /// Consensus message, can be both sent from and to the state machine.
     // (proposal_id, round, valid_round)
     Proposal(Option<ProposalContentId>, Round, Option<Round>),
-    /// Consensus message, can be both sent from and to the state machine.
-    Prevote(Option<ProposalContentId>, Round),
-    /// Consensus message, can be both sent from and to the state machine.
-    Precommit(Option<ProposalContentId>, Round),
+    /// Consensus message, can be both sent from and to the state machine. The voting power is
+    /// that of the voter.
+    Prevote(Option<ProposalContentId>, Round, VotingPower),
+    /// Consensus message, can be both sent from and to the state machine. The voting power is
+    /// that of the voter.
+    Precommit(Option<ProposalContentId>, Round, VotingPower),
     /// The state machine returns this event to the caller when a decision is reached. Not
     /// expected as an inbound message. We presume that the caller is able to recover the set of
     /// precommits which led to this decision from the information returned here.
@@ -55,14 +58,18 @@ pub struct StateMachine {
     id: ValidatorId,
     round: Round,
     step: Step,
-    quorum: u32,
-    round_skip_threshold: u32,
+    voting_power: VotingPower,
+    quorum: VotingPower,
+    round_skip_threshold: VotingPower,
     is_observer: bool,
     // {round: (proposal_id, valid_round)}
     proposals: HashMap<Round, (Option<ProposalContentId>, Option<Round>)>,
-    // {round: {proposal_id: vote_count}
-    prevotes: HashMap<Round, HashMap<Option<ProposalContentId>, u32>>,
-    precommits: HashMap<Round, HashMap<Option<ProposalContentId>, u32>>,
+    // {round: {proposal_id: voting_power}
+    prevotes: HashMap<Round, HashMap<Option<ProposalContentId>, VotingPower>>,
+    precommits: HashMap<Round, HashMap<Option<ProposalContentId>, VotingPower>>,
+    // Rounds in which this node already voted, including before a restart.
+    prevoted_rounds: HashSet<Round>,
+    precommitted_rounds: HashSet<Round>,
     // When true, the state machine will wait for a GetProposal event, buffering all other input
     // events in `events_queue`.
     awaiting_get_proposal: bool,
@@ -75,18 +82,28 @@ pub struct StateMachine {
 }
 
 impl StateMachine {
-    /// total_weight - the total voting weight of all validators for this height.
-    pub fn new(id: ValidatorId, total_weight: u32, is_observer: bool) -> Self {
+    /// total_voting_power - the voting power of all validators for this height.
+    /// voting_power - the voting power of this node, attached to the votes it sends.
+    pub fn new(
+        id: ValidatorId,
+        total_voting_power: VotingPower,
+        voting_power: VotingPower,
+        is_observer: bool,
+    ) -> Self {
         Self {
             id,
             round: 0,
             step: Step::Propose,
-            quorum: (2 * total_weight / 3) + 1,
-            round_skip_threshold: total_weight / 3 + 1,
+            voting_power,
+            quorum: VotingPower::try_from(u128::from(total_voting_power) * 2 / 3 + 1)
+                .expect("The quorum is at most the total voting power plus 1."),
+            round_skip_threshold: total_voting_power / 3 + 1,
             is_observer,
             proposals: HashMap::new(),
             prevotes: HashMap::new(),
             precommits: HashMap::new(),
+            prevoted_rounds: HashSet::new(),
+            precommitted_rounds: HashSet::new(),
             awaiting_get_proposal: false,
             events_queue: VecDeque::new(),
             locked_value_round: None,
@@ -101,18 +118,65 @@ impl StateMachine {
         self.round
     }
 
-    pub fn quorum_size(&self) -> u32 {
+    pub fn quorum_size(&self) -> VotingPower {
         self.quorum
     }
 
-    /// Starts the state machine, effectively calling `StartRound(0)` from the paper. This is
-    /// needed to trigger the first leader to propose.
+    pub fn locked_value_round(&self) -> Option<(ProposalContentId, Round)> {
+        self.locked_value_round
+    }
+
+    pub fn valid_value_round(&self) -> Option<(ProposalContentId, Round)> {
+        self.valid_value_round
+    }
+
+    /// Restores the round and the locked and valid values the node had before restarting, so that
+    /// `start` resumes from them. Must be called before `start`.
+    pub fn restore(
+        &mut self,
+        round: Round,
+        locked_value_round: Option<(ProposalContentId, Round)>,
+        valid_value_round: Option<(ProposalContentId, Round)>,
+    ) {
+        self.round = round;
+        self.locked_value_round = locked_value_round;
+        self.valid_value_round = valid_value_round;
+    }
+
+    /// Restores a prevote this node cast before restarting. It is counted like any other prevote,
+    /// and the state machine won't prevote again in its round. Must be called before `start`.
+    pub fn restore_prevote(&mut self, proposal_id: Option<ProposalContentId>, round: Round) {
+        assert!(self.prevoted_rounds.insert(round), "Restored two prevotes in round {round}");
+        *self.prevotes.entry(round).or_default().entry(proposal_id).or_insert(0) +=
+            self.voting_power;
+    }
+
+    /// Restores a precommit this node cast before restarting. It is counted like any other
+    /// precommit, and the state machine won't precommit again in its round. Must be called before
+    /// `start`.
+    pub fn restore_precommit(&mut self, proposal_id: Option<ProposalContentId>, round: Round) {
+        assert!(self.precommitted_rounds.insert(round), "Restored two precommits in round {round}");
+        *self.precommits.entry(round).or_default().entry(proposal_id).or_insert(0) +=
+            self.voting_power;
+    }
+
+    /// Starts the state machine, effectively calling `StartRound(0)` from the paper, or
+    /// `StartRound` of the restored round. This is needed to trigger the first leader to propose.
     /// See [`GetProposal`](StateMachineEvent::GetProposal)
     pub fn start<LeaderFn>(&mut self, leader_fn: &LeaderFn) -> VecDeque<StateMachineEvent>
     where
         LeaderFn: Fn(Round) -> ValidatorId,
     {
-        self.advance_to_round(0, leader_fn)
+        let mut output = self.advance_to_round(self.round, leader_fn);
+        // A restored proposer may repropose its valid value right away, which is handled like any
+        // other proposal sent by the state machine.
+        for event in &output {
+            if matches!(event, StateMachineEvent::Proposal(_, _, _)) {
+                self.events_queue.push_back(event.clone());
+            }
+        }
+        output.append(&mut self.handle_enqueued_events(leader_fn));
+        output
     }
 
     /// Process the incoming event.
@@ -166,9 +230,20 @@ impl StateMachine {
             let mut resultant_events = self.handle_event_internal(event, leader_fn);
             while let Some(e) = resultant_events.pop_front() {
                 match e {
+                    // A vote cast before restarting stands, so the step is left without another.
+                    StateMachineEvent::Prevote(_, round, _)
+                        if !self.prevoted_rounds.insert(round) =>
+                    {
+                        continue;
+                    }
+                    StateMachineEvent::Precommit(_, round, _)
+                        if !self.precommitted_rounds.insert(round) =>
+                    {
+                        continue;
+                    }
                     StateMachineEvent::Proposal(_, _, _)
-                    | StateMachineEvent::Prevote(_, _)
-                    | StateMachineEvent::Precommit(_, _) => {
+                    | StateMachineEvent::Prevote(_, _, _)
+                    | StateMachineEvent::Precommit(_, _, _) => {
                         if self.is_observer {
                             continue;
                         }
@@ -212,11 +287,11 @@ impl StateMachine {
             StateMachineEvent::Proposal(proposal_id, round, valid_round) => {
                 self.handle_proposal(proposal_id, round, valid_round, leader_fn)
             }
-            StateMachineEvent::Prevote(proposal_id, round) => {
-                self.handle_prevote(proposal_id, round, leader_fn)
+            StateMachineEvent::Prevote(proposal_id, round, voting_power) => {
+                self.handle_prevote(proposal_id, round, voting_power, leader_fn)
             }
-            StateMachineEvent::Precommit(proposal_id, round) => {
-                self.handle_precommit(proposal_id, round, leader_fn)
+            StateMachineEvent::Precommit(proposal_id, round, voting_power) => {
+                self.handle_precommit(proposal_id, round, voting_power, leader_fn)
             }
             StateMachineEvent::Decision(_, _) => {
                 unimplemented!(
@@ -263,7 +338,8 @@ impl StateMachine {
         if self.step != Step::Propose || round != self.round {
             return VecDeque::new();
         };
-        let mut output = VecDeque::from([StateMachineEvent::Prevote(None, round)]);
+        let mut output =
+            VecDeque::from([StateMachineEvent::Prevote(None, round, self.voting_power)]);
         output.append(&mut self.advance_to_step(Step::Prevote));
         output
     }
@@ -273,14 +349,14 @@ impl StateMachine {
         &mut self,
         proposal_id: Option<ProposalContentId>,
         round: u32,
+        voting_power: VotingPower,
         leader_fn: &LeaderFn,
     ) -> VecDeque<StateMachineEvent>
     where
         LeaderFn: Fn(Round) -> ValidatorId,
     {
-        let prevote_count = self.prevotes.entry(round).or_default().entry(proposal_id).or_insert(0);
-        // TODO(matan): Use variable weight.
-        *prevote_count += 1;
+        let prevote_power = self.prevotes.entry(round).or_default().entry(proposal_id).or_insert(0);
+        *prevote_power += voting_power;
         self.map_round_to_upons(round, leader_fn)
     }
 
@@ -288,7 +364,8 @@ impl StateMachine {
         if self.step != Step::Prevote || round != self.round {
             return VecDeque::new();
         };
-        let mut output = VecDeque::from([StateMachineEvent::Precommit(None, round)]);
+        let mut output =
+            VecDeque::from([StateMachineEvent::Precommit(None, round, self.voting_power)]);
         output.append(&mut self.advance_to_step(Step::Precommit));
         output
     }
@@ -298,15 +375,15 @@ impl StateMachine {
         &mut self,
         proposal_id: Option<ProposalContentId>,
         round: u32,
+        voting_power: VotingPower,
         leader_fn: &LeaderFn,
     ) -> VecDeque<StateMachineEvent>
     where
         LeaderFn: Fn(Round) -> ValidatorId,
     {
-        let precommit_count =
+        let precommit_power =
             self.precommits.entry(round).or_default().entry(proposal_id).or_insert(0);
-        // TODO(matan): Use variable weight.
-        *precommit_count += 1;
+        *precommit_power += voting_power;
         self.map_round_to_upons(round, leader_fn)
     }
 
@@ -411,9 +488,13 @@ impl StateMachine {
         let mut output = if proposal_id.is_some_and(|v| {
             self.locked_value_round.map_or(true, |(locked_value, _)| v == locked_value)
         }) {
-            VecDeque::from([StateMachineEvent::Prevote(*proposal_id, self.round)])
+            VecDeque::from([StateMachineEvent::Prevote(
+                *proposal_id,
+                self.round,
+                self.voting_power,
+            )])
         } else {
-            VecDeque::from([StateMachineEvent::Prevote(None, self.round)])
+            VecDeque::from([StateMachineEvent::Prevote(None, self.round, self.voting_power)])
         };
         output.append(&mut self.advance_to_step(Step::Prevote));
         output
@@ -441,9 +522,13 @@ impl StateMachine {
                 locked_round <= *valid_round || locked_value == v
             })
         }) {
-            VecDeque::from([StateMachineEvent::Prevote(*proposal_id, self.round)])
+            VecDeque::from([StateMachineEvent::Prevote(
+                *proposal_id,
+                self.round,
+                self.voting_power,
+            )])
         } else {
-            VecDeque::from([StateMachineEvent::Prevote(None, self.round)])
+            VecDeque::from([StateMachineEvent::Prevote(None, self.round, self.voting_power)])
         };
         output.append(&mut self.advance_to_step(Step::Prevote));
         output
@@ -484,8 +569,11 @@ impl StateMachine {
             return VecDeque::new();
         }
         self.locked_value_round = Some((*proposal_id, self.round));
-        let mut output =
-            VecDeque::from([StateMachineEvent::Precommit(Some(*proposal_id), self.round)]);
+        let mut output = VecDeque::from([StateMachineEvent::Precommit(
+            Some(*proposal_id),
+            self.round,
+            self.voting_power,
+        )]);
         output.append(&mut self.advance_to_step(Step::Precommit));
         output
     }
@@ -498,7 +586,8 @@ impl StateMachine {
         if !value_has_enough_votes(&self.prevotes, self.round, &None, self.quorum) {
             return VecDeque::new();
         }
-        let mut output = VecDeque::from([StateMachineEvent::Precommit(None, self.round)]);
+        let mut output =
+            VecDeque::from([StateMachineEvent::Precommit(None, self.round, self.voting_power)]);
         output.append(&mut self.advance_to_step(Step::Precommit));
         output
     }
@@ -547,18 +636,18 @@ impl StateMachine {
 }
 
 fn round_has_enough_votes(
-    votes: &HashMap<u32, HashMap<Option<ProposalContentId>, u32>>,
+    votes: &HashMap<u32, HashMap<Option<ProposalContentId>, VotingPower>>,
     round: u32,
-    threshold: u32,
+    threshold: VotingPower,
 ) -> bool {
     votes.get(&round).map_or(0, |v| v.values().sum()) >= threshold
 }
 
 fn value_has_enough_votes(
-    votes: &HashMap<u32, HashMap<Option<ProposalContentId>, u32>>,
+    votes: &HashMap<u32, HashMap<Option<ProposalContentId>, VotingPower>>,
     round: u32,
     value: &Option<ProposalContentId>,
-    threshold: u32,
+    threshold: VotingPower,
 ) -> bool {
     votes.get(&round).map_or(0, |v| *v.get(value).unwrap_or(&0)) >= threshold
 }
@@ -8,6 +8,7 @@ use test_case::test_case;
 use super::Round;
 use crate::state_machine::{StateMachine, StateMachineEvent};
 use crate::types::{ProposalContentId, ValidatorId, DEFAULT_VALIDATOR_ID};
+use crate::validator_set::VotingPower;
 
 lazy_static! {
     static ref PROPOSER_ID: ValidatorId = DEFAULT_VALIDATOR_ID.into();
@@ -24,9 +25,15 @@ struct TestWrapper<LeaderFn: Fn(Round) -> ValidatorId> {
 }
 
 impl<LeaderFn: Fn(Round) -> ValidatorId> TestWrapper<LeaderFn> {
-    pub fn new(id: ValidatorId, total_weight: u32, leader_fn: LeaderFn, is_observer: bool) -> Self {
+    /// Every node, including this one, has a voting power of 1 unless a vote says otherwise.
+    pub fn new(
+        id: ValidatorId,
+        total_voting_power: VotingPower,
+        leader_fn: LeaderFn,
+        is_observer: bool,
+    ) -> Self {
         Self {
-            state_machine: StateMachine::new(id, total_weight, is_observer),
+            state_machine: StateMachine::new(id, total_voting_power, 1, is_observer),
             leader_fn,
             events: VecDeque::new(),
         }
@@ -36,6 +43,19 @@ impl<LeaderFn: Fn(Round) -> ValidatorId> TestWrapper<LeaderFn> {
         self.events.pop_front()
     }
 
+    pub fn restore(
+        &mut self,
+        round: Round,
+        locked_value_round: Option<(ProposalContentId, Round)>,
+        valid_value_round: Option<(ProposalContentId, Round)>,
+    ) {
+        self.state_machine.restore(round, locked_value_round, valid_value_round)
+    }
+
+    pub fn restore_prevote(&mut self, proposal_id: Option<ProposalContentId>, round: Round) {
+        self.state_machine.restore_prevote(proposal_id, round)
+    }
+
     pub fn start(&mut self) {
         self.events.append(&mut self.state_machine.start(&self.leader_fn))
     }
@@ -49,11 +69,11 @@ impl<LeaderFn: Fn(Round) -> ValidatorId> TestWrapper<LeaderFn> {
     }
 
     pub fn send_prevote(&mut self, proposal_id: Option<ProposalContentId>, round: Round) {
-        self.send_event(StateMachineEvent::Prevote(proposal_id, round))
+        self.send_event(StateMachineEvent::Prevote(proposal_id, round, 1))
     }
 
     pub fn send_precommit(&mut self, proposal_id: Option<ProposalContentId>, round: Round) {
-        self.send_event(StateMachineEvent::Precommit(proposal_id, round))
+        self.send_event(StateMachineEvent::Precommit(proposal_id, round, 1))
     }
 
     pub fn send_timeout_propose(&mut self, round: Round) {
@@ -93,7 +113,7 @@ fn events_arrive_in_ideal_order(is_proposer: bool) {
         assert!(wrapper.next_event().is_none());
         wrapper.send_proposal(PROPOSAL_ID, ROUND);
     }
-    assert_eq!(wrapper.next_event().unwrap(), StateMachineEvent::Prevote(PROPOSAL_ID, ROUND));
+    assert_eq!(wrapper.next_event().unwrap(), StateMachineEvent::Prevote(PROPOSAL_ID, ROUND, 1));
     assert!(wrapper.next_event().is_none());
 
     wrapper.send_prevote(PROPOSAL_ID, ROUND);
@@ -102,7 +122,7 @@ fn events_arrive_in_ideal_order(is_proposer: bool) {
     wrapper.send_prevote(PROPOSAL_ID, ROUND);
     // The Node got a Prevote quorum.
     assert_eq!(wrapper.next_event().unwrap(), StateMachineEvent::TimeoutPrevote(ROUND));
-    assert_eq!(wrapper.next_event().unwrap(), StateMachineEvent::Precommit(PROPOSAL_ID, ROUND));
+    assert_eq!(wrapper.next_event().unwrap(), StateMachineEvent::Precommit(PROPOSAL_ID, ROUND, 1));
     assert!(wrapper.next_event().is_none());
 
     wrapper.send_precommit(PROPOSAL_ID, ROUND);
@@ -141,9 +161,9 @@ fn validator_receives_votes_first() {
 
     // Finally the proposal arrives.
     wrapper.send_proposal(PROPOSAL_ID, ROUND);
-    assert_eq!(wrapper.next_event().unwrap(), StateMachineEvent::Prevote(PROPOSAL_ID, ROUND));
+    assert_eq!(wrapper.next_event().unwrap(), StateMachineEvent::Prevote(PROPOSAL_ID, ROUND, 1));
     assert_eq!(wrapper.next_event().unwrap(), StateMachineEvent::TimeoutPrevote(ROUND));
-    assert_eq!(wrapper.next_event().unwrap(), StateMachineEvent::Precommit(PROPOSAL_ID, ROUND));
+    assert_eq!(wrapper.next_event().unwrap(), StateMachineEvent::Precommit(PROPOSAL_ID, ROUND, 1));
     assert_eq!(
         wrapper.next_event().unwrap(),
         StateMachineEvent::Decision(PROPOSAL_ID.unwrap(), ROUND)
@@ -171,9 +191,9 @@ fn buffer_events_during_get_proposal(vote: Option<ProposalContentId>) {
         wrapper.next_event().unwrap(),
         StateMachineEvent::Proposal(PROPOSAL_ID, ROUND, None)
     );
-    assert_eq!(wrapper.next_event().unwrap(), StateMachineEvent::Prevote(PROPOSAL_ID, ROUND));
+    assert_eq!(wrapper.next_event().unwrap(), StateMachineEvent::Prevote(PROPOSAL_ID, ROUND, 1));
     assert_eq!(wrapper.next_event().unwrap(), StateMachineEvent::TimeoutPrevote(ROUND));
-    assert_eq!(wrapper.next_event().unwrap(), StateMachineEvent::Precommit(vote, ROUND));
+    assert_eq!(wrapper.next_event().unwrap(), StateMachineEvent::Precommit(vote, ROUND, 1));
     assert!(wrapper.next_event().is_none());
 }
 
@@ -194,9 +214,9 @@ fn only_send_precommit_with_prevote_quorum_and_proposal() {
 
     // Finally the proposal arrives.
     wrapper.send_proposal(PROPOSAL_ID, ROUND);
-    assert_eq!(wrapper.next_event().unwrap(), StateMachineEvent::Prevote(PROPOSAL_ID, ROUND));
+    assert_eq!(wrapper.next_event().unwrap(), StateMachineEvent::Prevote(PROPOSAL_ID, ROUND, 1));
     assert_eq!(wrapper.next_event().unwrap(), StateMachineEvent::TimeoutPrevote(ROUND));
-    assert_eq!(wrapper.next_event().unwrap(), StateMachineEvent::Precommit(PROPOSAL_ID, ROUND));
+    assert_eq!(wrapper.next_event().unwrap(), StateMachineEvent::Precommit(PROPOSAL_ID, ROUND, 1));
     assert!(wrapper.next_event().is_none());
 }
 
@@ -219,9 +239,9 @@ fn only_decide_with_prcommit_quorum_and_proposal() {
 
     // Finally the proposal arrives.
     wrapper.send_proposal(PROPOSAL_ID, ROUND);
-    assert_eq!(wrapper.next_event().unwrap(), StateMachineEvent::Prevote(PROPOSAL_ID, ROUND));
+    assert_eq!(wrapper.next_event().unwrap(), StateMachineEvent::Prevote(PROPOSAL_ID, ROUND, 1));
     assert_eq!(wrapper.next_event().unwrap(), StateMachineEvent::TimeoutPrevote(ROUND));
-    assert_eq!(wrapper.next_event().unwrap(), StateMachineEvent::Precommit(PROPOSAL_ID, ROUND));
+    assert_eq!(wrapper.next_event().unwrap(), StateMachineEvent::Precommit(PROPOSAL_ID, ROUND, 1));
     assert_eq!(wrapper.next_event().unwrap(), StateMachineEvent::TimeoutPrecommit(ROUND));
     assert_eq!(
         wrapper.next_event().unwrap(),
@@ -240,7 +260,7 @@ fn advance_to_the_next_round() {
     assert!(wrapper.next_event().is_none());
 
     wrapper.send_proposal(PROPOSAL_ID, ROUND);
-    assert_eq!(wrapper.next_event().unwrap(), StateMachineEvent::Prevote(PROPOSAL_ID, ROUND));
+    assert_eq!(wrapper.next_event().unwrap(), StateMachineEvent::Prevote(PROPOSAL_ID, ROUND, 1));
     wrapper.send_precommit(None, ROUND);
     wrapper.send_precommit(None, ROUND);
     assert!(wrapper.next_event().is_none());
@@ -253,7 +273,10 @@ fn advance_to_the_next_round() {
     wrapper.send_timeout_precommit(ROUND);
     // The Node sends Prevote after advancing to the next round.
     assert_eq!(wrapper.next_event().unwrap(), StateMachineEvent::TimeoutPropose(ROUND + 1));
-    assert_eq!(wrapper.next_event().unwrap(), StateMachineEvent::Prevote(PROPOSAL_ID, ROUND + 1));
+    assert_eq!(
+        wrapper.next_event().unwrap(),
+        StateMachineEvent::Prevote(PROPOSAL_ID, ROUND + 1, 1)
+    );
 }
 
 #[test]
@@ -277,7 +300,10 @@ fn prevote_when_receiving_proposal_in_current_round() {
     assert!(wrapper.next_event().is_none());
     // The node should prevote when receiving a proposal for the current round.
     wrapper.send_proposal(PROPOSAL_ID, ROUND + 1);
-    assert_eq!(wrapper.next_event().unwrap(), StateMachineEvent::Prevote(PROPOSAL_ID, ROUND + 1));
+    assert_eq!(
+        wrapper.next_event().unwrap(),
+        StateMachineEvent::Prevote(PROPOSAL_ID, ROUND + 1, 1)
+    );
 }
 
 #[test_case(true ; "send_proposal")]
@@ -292,17 +318,20 @@ fn mixed_quorum(send_prposal: bool) {
 
     if send_prposal {
         wrapper.send_proposal(PROPOSAL_ID, ROUND);
-        assert_eq!(wrapper.next_event().unwrap(), StateMachineEvent::Prevote(PROPOSAL_ID, ROUND));
+        assert_eq!(
+            wrapper.next_event().unwrap(),
+            StateMachineEvent::Prevote(PROPOSAL_ID, ROUND, 1)
+        );
     } else {
         wrapper.send_timeout_propose(ROUND);
-        assert_eq!(wrapper.next_event().unwrap(), StateMachineEvent::Prevote(None, ROUND));
+        assert_eq!(wrapper.next_event().unwrap(), StateMachineEvent::Prevote(None, ROUND, 1));
     }
     wrapper.send_prevote(PROPOSAL_ID, ROUND);
     wrapper.send_prevote(None, ROUND);
     // The Node got a Prevote quorum.
     assert_eq!(wrapper.next_event().unwrap(), StateMachineEvent::TimeoutPrevote(ROUND));
     wrapper.send_timeout_prevote(ROUND);
-    assert_eq!(wrapper.next_event().unwrap(), StateMachineEvent::Precommit(None, ROUND));
+    assert_eq!(wrapper.next_event().unwrap(), StateMachineEvent::Precommit(None, ROUND, 1));
     wrapper.send_precommit(PROPOSAL_ID, ROUND);
     wrapper.send_precommit(PROPOSAL_ID, ROUND);
     // The Node got a Precommit quorum.
@@ -369,12 +398,12 @@ fn return_proposal_if_locked_value_is_set() {
         wrapper.next_event().unwrap(),
         StateMachineEvent::Proposal(PROPOSAL_ID, ROUND, None)
     );
-    assert_eq!(wrapper.next_event().unwrap(), StateMachineEvent::Prevote(PROPOSAL_ID, ROUND));
+    assert_eq!(wrapper.next_event().unwrap(), StateMachineEvent::Prevote(PROPOSAL_ID, ROUND, 1));
     // locked_value is set after receiving a Prevote quorum.
     wrapper.send_prevote(PROPOSAL_ID, ROUND);
     wrapper.send_prevote(PROPOSAL_ID, ROUND);
     assert_eq!(wrapper.next_event().unwrap(), StateMachineEvent::TimeoutPrevote(ROUND));
-    assert_eq!(wrapper.next_event().unwrap(), StateMachineEvent::Precommit(PROPOSAL_ID, ROUND));
+    assert_eq!(wrapper.next_event().unwrap(), StateMachineEvent::Precommit(PROPOSAL_ID, ROUND, 1));
 
     wrapper.send_precommit(None, ROUND);
     wrapper.send_precommit(None, ROUND);
@@ -387,7 +416,10 @@ fn return_proposal_if_locked_value_is_set() {
         wrapper.next_event().unwrap(),
         StateMachineEvent::Proposal(PROPOSAL_ID, ROUND + 1, Some(ROUND))
     );
-    assert_eq!(wrapper.next_event().unwrap(), StateMachineEvent::Prevote(PROPOSAL_ID, ROUND + 1));
+    assert_eq!(
+        wrapper.next_event().unwrap(),
+        StateMachineEvent::Prevote(PROPOSAL_ID, ROUND + 1, 1)
+    );
 }
 
 #[test]
@@ -415,3 +447,103 @@ fn observer_node_reaches_decision() {
     );
     assert!(wrapper.next_event().is_none());
 }
+
+#[test]
+fn quorum_is_reached_by_voting_power() {
+    // Setup. A quorum of a total voting power of 10 is 7.
+    let mut wrapper = TestWrapper::new(*VALIDATOR_ID, 10, |_: Round| *PROPOSER_ID, false);
+    wrapper.start();
+    assert_eq!(wrapper.next_event().unwrap(), StateMachineEvent::TimeoutPropose(ROUND));
+    wrapper.send_proposal(PROPOSAL_ID, ROUND);
+    assert_eq!(wrapper.next_event().unwrap(), StateMachineEvent::Prevote(PROPOSAL_ID, ROUND, 1));
+    assert!(wrapper.next_event().is_none());
+
+    // Test.
+    wrapper.send_event(StateMachineEvent::Prevote(PROPOSAL_ID, ROUND, 5));
+    assert!(wrapper.next_event().is_none());
+    wrapper.send_prevote(PROPOSAL_ID, ROUND);
+    assert_eq!(wrapper.next_event().unwrap(), StateMachineEvent::TimeoutPrevote(ROUND));
+    assert_eq!(wrapper.next_event().unwrap(), StateMachineEvent::Precommit(PROPOSAL_ID, ROUND, 1));
+    assert!(wrapper.next_event().is_none());
+
+    // A single heavy validator completes the precommit quorum.
+    wrapper.send_event(StateMachineEvent::Precommit(PROPOSAL_ID, ROUND, 6));
+
+    // Assert.
+    assert_eq!(wrapper.next_event().unwrap(), StateMachineEvent::TimeoutPrecommit(ROUND));
+    assert_eq!(
+        wrapper.next_event().unwrap(),
+        StateMachineEvent::Decision(PROPOSAL_ID.unwrap(), ROUND)
+    );
+    assert!(wrapper.next_event().is_none());
+}
+
+#[test]
+fn round_skip_is_reached_by_voting_power() {
+    // Setup. Skipping to a future round requires more than a third of a total of 10.
+    let mut wrapper = TestWrapper::new(*VALIDATOR_ID, 10, |_: Round| *PROPOSER_ID, false);
+    wrapper.start();
+    assert_eq!(wrapper.next_event().unwrap(), StateMachineEvent::TimeoutPropose(ROUND));
+
+    // Test.
+    wrapper.send_event(StateMachineEvent::Prevote(None, ROUND + 1, 3));
+    assert!(wrapper.next_event().is_none());
+    wrapper.send_prevote(None, ROUND + 1);
+
+    // Assert.
+    assert_eq!(wrapper.next_event().unwrap(), StateMachineEvent::TimeoutPropose(ROUND + 1));
+    assert!(wrapper.next_event().is_none());
+}
+
+#[test]
+fn restored_lock_is_respected() {
+    let mut wrapper = TestWrapper::new(*VALIDATOR_ID, 4, |_: Round| *PROPOSER_ID, false);
+    let locked_value_round = Some((PROPOSAL_ID.unwrap(), ROUND));
+    wrapper.restore(ROUND + 1, locked_value_round, locked_value_round);
+
+    wrapper.start();
+    assert_eq!(wrapper.next_event().unwrap(), StateMachineEvent::TimeoutPropose(ROUND + 1));
+    assert!(wrapper.next_event().is_none());
+
+    // A new proposal for a different value is rejected, since we are locked on PROPOSAL_ID.
+    let other_proposal_id = Some(BlockHash(Felt::TWO));
+    wrapper.send_proposal(other_proposal_id, ROUND + 1);
+    assert_eq!(wrapper.next_event().unwrap(), StateMachineEvent::Prevote(None, ROUND + 1, 1));
+    assert!(wrapper.next_event().is_none());
+}
+
+#[test]
+fn restored_proposer_reproposes_valid_value() {
+    let mut wrapper = TestWrapper::new(*PROPOSER_ID, 4, |_: Round| *PROPOSER_ID, false);
+    let valid_value_round = Some((PROPOSAL_ID.unwrap(), ROUND));
+    wrapper.restore(ROUND + 1, valid_value_round, valid_value_round);
+
+    wrapper.start();
+    assert_eq!(
+        wrapper.next_event().unwrap(),
+        StateMachineEvent::Proposal(PROPOSAL_ID, ROUND + 1, Some(ROUND))
+    );
+}
+
+#[test]
+fn restored_prevote_is_not_cast_again() {
+    let mut wrapper = TestWrapper::new(*VALIDATOR_ID, 4, |_: Round| *PROPOSER_ID, false);
+    // Before restarting, the validator prevoted nil since the proposal hadn't arrived in time.
+    wrapper.restore_prevote(None, ROUND);
+
+    wrapper.start();
+    assert_eq!(wrapper.next_event().unwrap(), StateMachineEvent::TimeoutPropose(ROUND));
+    assert!(wrapper.next_event().is_none());
+
+    // The proposal arrives, but the validator already prevoted in this round.
+    wrapper.send_proposal(PROPOSAL_ID, ROUND);
+    assert!(wrapper.next_event().is_none());
+
+    // The restored prevote counts towards the nil prevote quorum.
+    wrapper.send_prevote(None, ROUND);
+    assert!(wrapper.next_event().is_none());
+    wrapper.send_prevote(None, ROUND);
+    assert_eq!(wrapper.next_event().unwrap(), StateMachineEvent::TimeoutPrevote(ROUND));
+    assert_eq!(wrapper.next_event().unwrap(), StateMachineEvent::Precommit(None, ROUND, 1));
+    assert!(wrapper.next_event().is_none());
+}
@@ -35,13 +35,10 @@ mod tests {
     }
 
     // Check if two vectors are the same, regardless of ordering
-    fn do_vecs_match_unordered<T: PartialEq + Ord + Clone>(a: &Vec<T>, b: &Vec<T>) -> bool
-    where
-        T: std::hash::Hash + Eq,
-    {
-        let mut a = a.clone();
+    fn do_vecs_match_unordered<T: Ord + Clone>(a: &[T], b: &[T]) -> bool {
+        let mut a = a.to_vec();
         a.sort();
-        let mut b = b.clone();
+        let mut b = b.to_vec();
         b.sort();
         a == b
     }
@@ -58,7 +55,7 @@ mod tests {
     fn setup_test() -> (
         StreamHandler<ConsensusMessage>,
         MockBroadcastedMessagesSender<StreamMessage<ConsensusMessage>>,
-        mpsc::Receiver<mpsc::Receiver<ConsensusMessage>>,
+        mpsc::Receiver<(mpsc::Receiver<ConsensusMessage>, BroadcastedMessageMetadata)>,
         BroadcastedMessageMetadata,
         mpsc::Sender<(StreamId, mpsc::Receiver<ConsensusMessage>)>,
         futures::stream::Map<
@@ -98,8 +95,10 @@ mod tests {
         // The inbound_channel_sender is given to StreamHandler so it can output new channels for
         // each stream. The inbound_channel_receiver is given to the "mock consensus" that
         // gets new channels and inbounds to them.
-        let (inbound_channel_sender, inbound_channel_receiver) =
-            mpsc::channel::<mpsc::Receiver<ConsensusMessage>>(CHANNEL_SIZE);
+        let (inbound_channel_sender, inbound_channel_receiver) = mpsc::channel::<(
+            mpsc::Receiver<ConsensusMessage>,
+            BroadcastedMessageMetadata,
+        )>(CHANNEL_SIZE);
 
         // TODO(guyn): We should also give the broadcast_topic_client to the StreamHandler
         // This will allow reporting to the network things like bad peers.
@@ -139,7 +138,8 @@ mod tests {
 
         join_handle.await.expect("Task should succeed");
 
-        let mut receiver = inbound_channel_receiver.next().await.unwrap();
+        let (mut receiver, stream_metadata) = inbound_channel_receiver.next().await.unwrap();
+        assert_eq!(stream_metadata, metadata);
         for _ in 0..9 {
             // message number 9 is Fin, so it will not be sent!
             let _ = receiver.next().await.unwrap();
@@ -174,7 +174,7 @@ mod tests {
         let mut stream_handler = join_handle.await.expect("Task should succeed");
 
         // Get the receiver for the stream.
-        let mut receiver = inbound_channel_receiver.next().await.unwrap();
+        let (mut receiver, _) = inbound_channel_receiver.next().await.unwrap();
         // Check that the channel is empty (no messages were sent yet).
         assert!(receiver.try_next().is_err());
 
@@ -294,19 +294,19 @@ mod tests {
         ));
 
         // Get the receiver for the first stream.
-        let mut receiver1 = inbound_channel_receiver.next().await.unwrap();
+        let (mut receiver1, _) = inbound_channel_receiver.next().await.unwrap();
 
         // Check that the channel is empty (no messages were sent yet).
         assert!(receiver1.try_next().is_err());
 
         // Get the receiver for the second stream.
-        let mut receiver2 = inbound_channel_receiver.next().await.unwrap();
+        let (mut receiver2, _) = inbound_channel_receiver.next().await.unwrap();
 
         // Check that the channel is empty (no messages were sent yet).
         assert!(receiver2.try_next().is_err());
 
         // Get the receiver for the third stream.
-        let mut receiver3 = inbound_channel_receiver.next().await.unwrap();
+        let (mut receiver3, _) = inbound_channel_receiver.next().await.unwrap();
 
         // Check that the channel is empty (no messages were sent yet).
         assert!(receiver3.try_next().is_err());
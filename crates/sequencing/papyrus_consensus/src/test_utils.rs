@@ -3,11 +3,20 @@ use std::time::Duration;
 use async_trait::async_trait;
 use futures::channel::{mpsc, oneshot};
 use mockall::mock;
-use papyrus_protobuf::consensus::{ConsensusMessage, ProposalFin, ProposalInit, Vote, VoteType};
+use papyrus_protobuf::consensus::{
+    ConsensusMessage,
+    Misbehavior,
+    ProposalFin,
+    ProposalInit,
+    Vote,
+    VoteType,
+};
 use papyrus_protobuf::converters::ProtobufConversionError;
 use starknet_api::block::{BlockHash, BlockNumber};
+use starknet_api::crypto::utils::Signature;
 use starknet_types_core::felt::Felt;
 
+use crate::signature::ConsensusSigner;
 use crate::types::{
     ConsensusContext,
     ConsensusError,
@@ -16,6 +25,7 @@ use crate::types::{
     ValidatorId,
     DEFAULT_VALIDATOR_ID,
 };
+use crate::validator_set::ValidatorSet;
 
 /// Define a consensus block which can be used to enable auto mocking Context.
 #[derive(Debug, PartialEq, Clone)]
@@ -36,10 +46,17 @@ impl From<ProposalInit> for MockProposalPart {
 impl TryFrom<MockProposalPart> for ProposalInit {
     type Error = ProtobufConversionError;
     fn try_from(part: MockProposalPart) -> Result<Self, Self::Error> {
-        Ok(ProposalInit {
-            height: BlockNumber(part.0),
-            proposer: DEFAULT_VALIDATOR_ID.into(),
-            ..Default::default()
+        Ok(proposal_init(part.0, 0, DEFAULT_VALIDATOR_ID.into()))
+    }
+}
+
+impl TryFrom<MockProposalPart> for ProposalFin {
+    type Error = ProtobufConversionError;
+    fn try_from(part: MockProposalPart) -> Result<Self, Self::Error> {
+        Err(ProtobufConversionError::WrongEnumVariant {
+            type_description: "MockProposalPart",
+            expected: "Fin",
+            value_as_str: format!("{part:?}"),
         })
     }
 }
@@ -87,7 +104,7 @@ mock! {
             init: ProposalInit,
         );
 
-        async fn validators(&self, height: BlockNumber) -> Vec<ValidatorId>;
+        async fn validators(&self, height: BlockNumber) -> Result<ValidatorSet, ConsensusError>;
 
         fn proposer(&self, height: BlockNumber, round: Round) -> ValidatorId;
 
@@ -99,6 +116,8 @@ mock! {
             precommits: Vec<Vote>,
         ) -> Result<(), ConsensusError>;
 
+        async fn report_misbehavior(&mut self, misbehavior: Misbehavior);
+
         async fn set_height_and_round(&mut self, height: BlockNumber, round: Round);
     }
 }
@@ -110,7 +129,15 @@ pub fn prevote(
     voter: ValidatorId,
 ) -> ConsensusMessage {
     let block_hash = block_felt.map(BlockHash);
-    ConsensusMessage::Vote(Vote { vote_type: VoteType::Prevote, height, round, block_hash, voter })
+    let vote = Vote {
+        vote_type: VoteType::Prevote,
+        height,
+        round,
+        block_hash,
+        voter,
+        signature: Signature::default(),
+    };
+    ConsensusMessage::Vote(ConsensusSigner::for_testing(voter).sign_vote(vote))
 }
 
 pub fn precommit(
@@ -120,14 +147,36 @@ pub fn precommit(
     voter: ValidatorId,
 ) -> ConsensusMessage {
     let block_hash = block_felt.map(BlockHash);
-    ConsensusMessage::Vote(Vote {
+    let vote = Vote {
         vote_type: VoteType::Precommit,
         height,
         round,
         block_hash,
         voter,
-    })
+        signature: Signature::default(),
+    };
+    ConsensusMessage::Vote(ConsensusSigner::for_testing(voter).sign_vote(vote))
+}
+
+pub fn into_vote(message: ConsensusMessage) -> Vote {
+    match message {
+        ConsensusMessage::Vote(vote) => vote,
+        _ => panic!("Expected a vote, got {message:?}"),
+    }
 }
+
 pub fn proposal_init(height: u64, round: u32, proposer: ValidatorId) -> ProposalInit {
-    ProposalInit { height: BlockNumber(height), round, proposer, valid_round: None }
+    let init = ProposalInit {
+        height: BlockNumber(height),
+        round,
+        proposer,
+        valid_round: None,
+        signature: Signature::default(),
+    };
+    ConsensusSigner::for_testing(proposer).sign_proposal_init(init)
+}
+
+pub fn proposal_fin(init: &ProposalInit, proposal_content_id: BlockHash) -> ProposalFin {
+    let fin = ProposalFin { proposal_content_id, signature: Signature::default() };
+    ConsensusSigner::for_testing(init.proposer).sign_proposal_fin(init, fin)
 }
@@ -9,11 +9,14 @@ use papyrus_network::network_manager::{
     GenericReceiver,
 };
 use papyrus_network_types::network_types::BroadcastedMessageMetadata;
-use papyrus_protobuf::consensus::{ConsensusMessage, ProposalFin, ProposalInit, Vote};
+use papyrus_protobuf::consensus::{ConsensusMessage, Misbehavior, ProposalFin, ProposalInit, Vote};
 use papyrus_protobuf::converters::ProtobufConversionError;
 use starknet_api::block::{BlockHash, BlockNumber};
 use starknet_api::core::ContractAddress;
 
+use crate::validator_set::{ValidatorSet, ValidatorSetError};
+use crate::wal::WalError;
+
 /// Used to identify the node by consensus.
 /// 1. This ID is derived from the id registered with Starknet's L2 staking contract.
 /// 2. We must be able to derive the public key associated with this ID for the sake of validating
@@ -35,10 +38,12 @@ pub trait ConsensusContext {
     type ProposalPart: TryFrom<Vec<u8>, Error = ProtobufConversionError>
         + Into<Vec<u8>>
         + TryInto<ProposalInit, Error = ProtobufConversionError>
+        + TryInto<ProposalFin, Error = ProtobufConversionError>
         + From<ProposalInit>
         + Clone
         + Send
-        + Debug;
+        + Debug
+        + 'static;
 
     // TODO(matan): The oneshot for receiving the build block could be generalized to just be some
     // future which returns a block.
@@ -93,11 +98,9 @@ pub trait ConsensusContext {
     async fn repropose(&mut self, id: ProposalContentId, init: ProposalInit);
 
     /// Get the set of validators for a given height. These are the nodes that can propose and vote
-    /// on blocks.
-    // TODO(matan): We expect this to change in the future to BTreeMap. Why?
-    // 1. Map - The nodes will have associated information (e.g. voting weight).
-    // 2. BTreeMap - We want a stable ordering of the nodes for deterministic leader selection.
-    async fn validators(&self, height: BlockNumber) -> Vec<ValidatorId>;
+    /// on blocks, each weighted by its voting power. Waits until the set of the height is
+    /// available.
+    async fn validators(&self, height: BlockNumber) -> Result<ValidatorSet, ConsensusError>;
 
     /// Calculates the ID of the Proposer based on the inputs.
     fn proposer(&self, height: BlockNumber, round: Round) -> ValidatorId;
@@ -114,6 +117,11 @@ pub trait ConsensusContext {
         precommits: Vec<Vote>,
     ) -> Result<(), ConsensusError>;
 
+    /// Report evidence that a validator signed two conflicting messages at the current height.
+    /// Called once for each equivocation, whether it was caught by this node or by a peer. The
+    /// evidence is verified and already gossiped by consensus.
+    async fn report_misbehavior(&mut self, misbehavior: Misbehavior);
+
     /// Update the context with the current height and round.
     /// Must be called at the beginning of each height.
     async fn set_height_and_round(&mut self, height: BlockNumber, round: Round);
@@ -166,12 +174,31 @@ pub enum ConsensusError {
     InvalidProposal(ValidatorId, BlockNumber, String),
     #[error(transparent)]
     SendError(#[from] mpsc::SendError),
-    #[error("Conflicting messages for block {0}. Old: {1:?}, New: {2:?}")]
-    Equivocation(BlockNumber, ConsensusMessage, ConsensusMessage),
+    /// The message is missing a valid signature from the validator it claims to come from.
+    #[error("Invalid signature from {0:?} at height {1}: {2}")]
+    InvalidSignature(ValidatorId, BlockNumber, String),
+    /// Evidence received from a peer which does not prove that the validator equivocated.
+    #[error("Invalid evidence against {0:?} at height {1}: {2}")]
+    InvalidEvidence(ValidatorId, BlockNumber, String),
     // Indicates an error in communication between consensus and the node's networking component.
     // As opposed to an error between this node and peer nodes.
     #[error("{0}")]
     InternalNetworkError(String),
     #[error("{0}")]
     SyncError(String),
+    #[error(transparent)]
+    ValidatorSetError(#[from] ValidatorSetError),
+    /// The node has no key to sign its consensus messages with.
+    #[error("A signing key is required to run consensus.")]
+    MissingSigningKey,
+    /// Failed to read or write the WAL. Holds the error's string, since `WalError` does not
+    /// derive `PartialEq`.
+    #[error("WAL error: {0}")]
+    WalError(String),
+}
+
+impl From<WalError> for ConsensusError {
+    fn from(error: WalError) -> Self {
+        ConsensusError::WalError(error.to_string())
+    }
 }
@@ -0,0 +1,235 @@
+//! The set of validators participating in consensus at a given height, along with the voting
+//! power and public key of each validator.
+
+#[cfg(test)]
+#[path = "validator_set_test.rs"]
+mod validator_set_test;
+
+use std::cmp::max;
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+use starknet_api::block::BlockNumber;
+use starknet_api::crypto::utils::PublicKey;
+use tracing::debug;
+
+#[cfg(any(feature = "testing", test))]
+use crate::signature::ConsensusSigner;
+use crate::types::{Round, ValidatorId};
+
+/// The weight of a validator's vote.
+pub type VotingPower = u64;
+
+/// The voting powers of a validator set are scaled down so that their total is about this value,
+/// which keeps the arithmetic on them from overflowing.
+pub const MAX_TOTAL_VOTING_POWER: VotingPower = 1 << 32;
+
+/// The validators for a single height, their voting power and the public keys their messages are
+/// signed with.
+///
+/// Validators are kept ordered by their ID, so iteration (and therefore proposer selection) is
+/// deterministic across nodes. Validators with zero voting power are not part of the set.
+///
+/// If the total voting power exceeds [`MAX_TOTAL_VOTING_POWER`], the voting powers are scaled
+/// down proportionally, keeping every validator's voting power at least 1. The total may then
+/// exceed the maximum by at most the number of validators.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ValidatorSet {
+    voting_powers: BTreeMap<ValidatorId, VotingPower>,
+    public_keys: BTreeMap<ValidatorId, PublicKey>,
+}
+
+impl ValidatorSet {
+    /// Creates a set from `(validator, voting_power)` pairs. Validators with zero voting power are
+    /// dropped, and a repeated validator keeps the last voting power given for it.
+    ///
+    /// The set has no public keys, see [`ValidatorSet::with_public_keys`].
+    pub fn new(validators: impl IntoIterator<Item = (ValidatorId, VotingPower)>) -> Self {
+        Self::from_stakes(
+            validators.into_iter().map(|(id, voting_power)| (id, u128::from(voting_power))),
+        )
+    }
+
+    /// Creates a set from `(validator, stake)` pairs, where the voting power of each validator is
+    /// proportional to its stake. Stakes may be given in any unit, e.g. the smallest unit of the
+    /// staked token.
+    pub fn from_stakes(validators: impl IntoIterator<Item = (ValidatorId, u128)>) -> Self {
+        let stakes: BTreeMap<ValidatorId, u128> =
+            validators.into_iter().filter(|(_, stake)| *stake > 0).collect();
+
+        // Drop the low bits of large stakes, so that the stakes sum up without overflowing, and
+        // each of them can be multiplied by the maximal total voting power.
+        let max_stake = stakes.values().copied().max().unwrap_or_default();
+        let shift = (u128::BITS - max_stake.leading_zeros()).saturating_sub(u64::BITS);
+        let stakes: Vec<_> =
+            stakes.into_iter().map(|(id, stake)| (id, max(stake >> shift, 1))).collect();
+        let total_stake: u128 = stakes.iter().map(|(_, stake)| stake).sum();
+
+        let max_total_voting_power = u128::from(MAX_TOTAL_VOTING_POWER);
+        let voting_powers = stakes
+            .into_iter()
+            .map(|(id, stake)| {
+                let voting_power = if total_stake <= max_total_voting_power {
+                    stake
+                } else {
+                    max(stake * max_total_voting_power / total_stake, 1)
+                };
+                let voting_power = VotingPower::try_from(voting_power)
+                    .expect("Voting power is at most the maximal total voting power.");
+                (id, voting_power)
+            })
+            .collect();
+        Self { voting_powers, public_keys: BTreeMap::new() }
+    }
+
+    /// Creates a set where every validator has a voting power of 1.
+    pub fn with_equal_voting_power(validators: impl IntoIterator<Item = ValidatorId>) -> Self {
+        Self::new(validators.into_iter().map(|id| (id, 1)))
+    }
+
+    /// Sets the public keys of the validators. Keys of IDs which are not in the set are ignored.
+    pub fn with_public_keys(
+        mut self,
+        public_keys: impl IntoIterator<Item = (ValidatorId, PublicKey)>,
+    ) -> Self {
+        self.public_keys =
+            public_keys.into_iter().filter(|(id, _)| self.voting_powers.contains_key(id)).collect();
+        self
+    }
+
+    /// Sets the public key of each validator to the INSECURE key derived from its ID by
+    /// [`ConsensusSigner::for_testing`].
+    #[cfg(any(feature = "testing", test))]
+    pub fn with_testing_public_keys(self) -> Self {
+        let public_keys: Vec<_> = self
+            .voting_powers
+            .keys()
+            .map(|id| (*id, ConsensusSigner::for_testing(*id).public_key()))
+            .collect();
+        self.with_public_keys(public_keys)
+    }
+
+    /// Whether `id` is a validator for this height.
+    pub fn contains(&self, id: &ValidatorId) -> bool {
+        self.voting_powers.contains_key(id)
+    }
+
+    /// The voting power of `id`, or 0 if it is not a validator.
+    pub fn voting_power(&self, id: &ValidatorId) -> VotingPower {
+        self.voting_powers.get(id).copied().unwrap_or_default()
+    }
+
+    /// The public key `id` signs its messages with, if it is a validator with a known key.
+    pub fn public_key(&self, id: &ValidatorId) -> Option<&PublicKey> {
+        self.public_keys.get(id)
+    }
+
+    /// The sum of the voting power of all validators.
+    pub fn total_voting_power(&self) -> VotingPower {
+        self.voting_powers.values().sum()
+    }
+
+    /// The number of validators.
+    pub fn len(&self) -> usize {
+        self.voting_powers.len()
+    }
+
+    /// Whether there are no validators.
+    pub fn is_empty(&self) -> bool {
+        self.voting_powers.is_empty()
+    }
+
+    /// Iterates over the validators in ascending ID order.
+    pub fn iter(&self) -> impl Iterator<Item = (&ValidatorId, &VotingPower)> {
+        self.voting_powers.iter()
+    }
+
+    /// The validator which proposes at `(height, round)`.
+    ///
+    /// Each validator owns a range of positions proportional to its voting power, and the slot
+    /// `height + round` is mapped to a position by a stride coprime with the total voting power.
+    /// Every `total_voting_power` consecutive slots therefore visit each position exactly once, so
+    /// each validator proposes a share of the slots proportional to its voting power, and the
+    /// stride spreads its turns over the schedule instead of making them consecutive.
+    ///
+    /// Panics if the set is empty.
+    pub fn proposer(&self, height: BlockNumber, round: Round) -> ValidatorId {
+        assert!(!self.is_empty(), "Cannot select a proposer from an empty validator set.");
+        let total = u128::from(self.total_voting_power());
+        let slot = (u128::from(height.0) + u128::from(round)) % total;
+        let position = slot * proposer_stride(total) % total;
+
+        let mut range_end = 0;
+        for (id, voting_power) in &self.voting_powers {
+            range_end += u128::from(*voting_power);
+            if position < range_end {
+                return *id;
+            }
+        }
+        unreachable!("The position is less than the total voting power.");
+    }
+}
+
+impl FromIterator<(ValidatorId, VotingPower)> for ValidatorSet {
+    fn from_iter<T: IntoIterator<Item = (ValidatorId, VotingPower)>>(iter: T) -> Self {
+        Self::new(iter)
+    }
+}
+
+/// A failure to get the validator set of a height.
+#[derive(Debug, PartialEq, thiserror::Error)]
+pub enum ValidatorSetError {
+    /// The state the validator set is read from doesn't reach the height yet.
+    #[error("The state for height {0} is not synced yet.")]
+    NotSynced(BlockNumber),
+    /// The validator set could not be read, e.g. due to a storage error or an invalid value.
+    #[error("Failed to read the validator set for height {0}: {1}")]
+    ReadFailed(BlockNumber, String),
+}
+
+/// Supplies the validator set for each height.
+pub trait ValidatorSetProvider: Send + Sync {
+    /// The validators which propose and vote on the block at `height`.
+    fn validator_set(&self, height: BlockNumber) -> Result<ValidatorSet, ValidatorSetError>;
+}
+
+/// A fixed validator set, used for every height.
+impl ValidatorSetProvider for ValidatorSet {
+    fn validator_set(&self, _height: BlockNumber) -> Result<ValidatorSet, ValidatorSetError> {
+        Ok(self.clone())
+    }
+}
+
+const SLEEP_BETWEEN_CHECK_FOR_STATE: Duration = Duration::from_secs(1);
+
+/// Returns the validator set of `height`, waiting until the state it is read from is synced.
+pub async fn wait_for_validator_set(
+    provider: &dyn ValidatorSetProvider,
+    height: BlockNumber,
+) -> Result<ValidatorSet, ValidatorSetError> {
+    loop {
+        match provider.validator_set(height) {
+            Err(ValidatorSetError::NotSynced(_)) => {
+                debug!("Waiting for the state of height {height} to read its validator set");
+                tokio::time::sleep(SLEEP_BETWEEN_CHECK_FOR_STATE).await;
+            }
+            result => return result,
+        }
+    }
+}
+
+// A stride of about `total / golden ratio`, which spreads the positions of successive slots evenly.
+fn proposer_stride(total: u128) -> u128 {
+    let mut stride = max(total * 618_034 / 1_000_000, 1);
+    while gcd(stride, total) != 1 {
+        stride += 1;
+    }
+    stride
+}
+
+fn gcd(mut a: u128, mut b: u128) -> u128 {
+    while b != 0 {
+        (a, b) = (b, a % b);
+    }
+    a
+}
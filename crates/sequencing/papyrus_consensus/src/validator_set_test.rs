@@ -0,0 +1,175 @@
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use lazy_static::lazy_static;
+use starknet_api::block::BlockNumber;
+
+use crate::signature::ConsensusSigner;
+use crate::types::{ValidatorId, DEFAULT_VALIDATOR_ID};
+use crate::validator_set::{
+    wait_for_validator_set,
+    ValidatorSet,
+    ValidatorSetError,
+    ValidatorSetProvider,
+    MAX_TOTAL_VOTING_POWER,
+};
+
+lazy_static! {
+    static ref VALIDATOR_ID_1: ValidatorId = DEFAULT_VALIDATOR_ID.into();
+    static ref VALIDATOR_ID_2: ValidatorId = (DEFAULT_VALIDATOR_ID + 1).into();
+    static ref VALIDATOR_ID_3: ValidatorId = (DEFAULT_VALIDATOR_ID + 2).into();
+}
+
+fn proposers(validator_set: &ValidatorSet, n_slots: u64) -> Vec<ValidatorId> {
+    (0..n_slots).map(|height| validator_set.proposer(BlockNumber(height), 0)).collect()
+}
+
+#[test]
+fn zero_voting_power_is_not_a_validator() {
+    let validator_set = ValidatorSet::new([(*VALIDATOR_ID_1, 2), (*VALIDATOR_ID_2, 0)]);
+
+    assert_eq!(validator_set.len(), 1);
+    assert!(validator_set.contains(&VALIDATOR_ID_1));
+    assert!(!validator_set.contains(&VALIDATOR_ID_2));
+    assert_eq!(validator_set.voting_power(&VALIDATOR_ID_2), 0);
+    assert_eq!(validator_set.total_voting_power(), 2);
+}
+
+#[test]
+fn equal_voting_power_rotates_in_id_order() {
+    // Setup.
+    let validator_set =
+        ValidatorSet::with_equal_voting_power([*VALIDATOR_ID_3, *VALIDATOR_ID_1, *VALIDATOR_ID_2]);
+
+    // Test.
+    let proposers = proposers(&validator_set, 6);
+
+    // Assert.
+    let expected = [*VALIDATOR_ID_1, *VALIDATOR_ID_2, *VALIDATOR_ID_3];
+    assert_eq!(proposers, expected.repeat(2));
+}
+
+#[test]
+fn weighted_proposers_are_interleaved() {
+    // Setup.
+    let validator_set =
+        ValidatorSet::new([(*VALIDATOR_ID_1, 5), (*VALIDATOR_ID_2, 1), (*VALIDATOR_ID_3, 1)]);
+
+    // Test.
+    let proposers = proposers(&validator_set, 7);
+
+    // Assert.
+    let (a, b, c) = (*VALIDATOR_ID_1, *VALIDATOR_ID_2, *VALIDATOR_ID_3);
+    assert_eq!(proposers, vec![a, a, a, b, a, c, a]);
+}
+
+#[test]
+fn large_stakes_are_normalized() {
+    // Setup.
+    let stake = 1 << 100;
+    let validator_set = ValidatorSet::from_stakes([
+        (*VALIDATOR_ID_1, 2 * stake),
+        (*VALIDATOR_ID_2, stake),
+        (*VALIDATOR_ID_3, stake),
+    ]);
+
+    // Assert.
+    let total_voting_power = validator_set.total_voting_power();
+    assert!(total_voting_power <= MAX_TOTAL_VOTING_POWER + 3);
+    assert_eq!(validator_set.voting_power(&VALIDATOR_ID_1), total_voting_power / 2);
+    assert_eq!(validator_set.voting_power(&VALIDATOR_ID_2), total_voting_power / 4);
+    // Selecting a proposer doesn't depend on the size of the schedule.
+    validator_set.proposer(BlockNumber(u64::MAX), u32::MAX);
+}
+
+#[test]
+fn tiny_stakes_keep_voting_power() {
+    let validator_set =
+        ValidatorSet::from_stakes([(*VALIDATOR_ID_1, u128::MAX), (*VALIDATOR_ID_2, 1)]);
+
+    assert_eq!(validator_set.voting_power(&VALIDATOR_ID_2), 1);
+}
+
+#[test]
+fn proposer_share_matches_voting_power() {
+    // Setup.
+    let validator_set =
+        ValidatorSet::new([(*VALIDATOR_ID_1, 30), (*VALIDATOR_ID_2, 20), (*VALIDATOR_ID_3, 10)]);
+
+    // Test.
+    let mut counts = HashMap::new();
+    for proposer in proposers(&validator_set, 60) {
+        *counts.entry(proposer).or_insert(0) += 1;
+    }
+
+    // Assert.
+    assert_eq!(counts[&*VALIDATOR_ID_1], 30);
+    assert_eq!(counts[&*VALIDATOR_ID_2], 20);
+    assert_eq!(counts[&*VALIDATOR_ID_3], 10);
+}
+
+#[test]
+fn round_advances_the_proposer() {
+    let validator_set = ValidatorSet::with_equal_voting_power([*VALIDATOR_ID_1, *VALIDATOR_ID_2]);
+
+    assert_eq!(validator_set.proposer(BlockNumber(0), 0), *VALIDATOR_ID_1);
+    assert_eq!(validator_set.proposer(BlockNumber(0), 1), *VALIDATOR_ID_2);
+    assert_eq!(validator_set.proposer(BlockNumber(1), 1), *VALIDATOR_ID_1);
+}
+
+#[test]
+fn static_provider_returns_the_same_set() {
+    let validator_set = ValidatorSet::new([(*VALIDATOR_ID_1, 3), (*VALIDATOR_ID_2, 1)]);
+
+    assert_eq!(validator_set.validator_set(BlockNumber(0)), Ok(validator_set.clone()));
+    assert_eq!(validator_set.validator_set(BlockNumber(10)), Ok(validator_set.clone()));
+}
+
+#[test]
+fn public_keys_are_kept_only_for_validators() {
+    let public_key_1 = ConsensusSigner::for_testing(*VALIDATOR_ID_1).public_key();
+    let public_key_2 = ConsensusSigner::for_testing(*VALIDATOR_ID_2).public_key();
+
+    let validator_set = ValidatorSet::with_equal_voting_power([*VALIDATOR_ID_1])
+        .with_public_keys([(*VALIDATOR_ID_1, public_key_1), (*VALIDATOR_ID_2, public_key_2)]);
+
+    assert_eq!(validator_set.public_key(&VALIDATOR_ID_1), Some(&public_key_1));
+    assert_eq!(validator_set.public_key(&VALIDATOR_ID_2), None);
+}
+
+#[test]
+fn testing_public_keys_match_testing_signers() {
+    let validator_set = ValidatorSet::with_equal_voting_power([*VALIDATOR_ID_1, *VALIDATOR_ID_2])
+        .with_testing_public_keys();
+
+    for id in [*VALIDATOR_ID_1, *VALIDATOR_ID_2] {
+        let expected = ConsensusSigner::for_testing(id).public_key();
+        assert_eq!(validator_set.public_key(&id), Some(&expected));
+    }
+    assert_eq!(validator_set.public_key(&VALIDATOR_ID_3), None);
+}
+
+// Reports the state as not synced on the first read.
+struct SyncedOnSecondRead {
+    synced: AtomicBool,
+    validator_set: ValidatorSet,
+}
+
+impl ValidatorSetProvider for SyncedOnSecondRead {
+    fn validator_set(&self, height: BlockNumber) -> Result<ValidatorSet, ValidatorSetError> {
+        if self.synced.swap(true, Ordering::SeqCst) {
+            Ok(self.validator_set.clone())
+        } else {
+            Err(ValidatorSetError::NotSynced(height))
+        }
+    }
+}
+
+#[tokio::test]
+async fn wait_for_validator_set_until_synced() {
+    let validator_set = ValidatorSet::with_equal_voting_power([*VALIDATOR_ID_1]);
+    let provider =
+        SyncedOnSecondRead { synced: AtomicBool::new(false), validator_set: validator_set.clone() };
+
+    assert_eq!(wait_for_validator_set(&provider, BlockNumber(0)).await, Ok(validator_set));
+}
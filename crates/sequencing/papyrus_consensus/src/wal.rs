@@ -0,0 +1,140 @@
+//! Write-ahead log (WAL) which allows a validator to restart mid-height without equivocating.
+//!
+//! Everything the node commits to during a height (its votes, the proposals it started building or
+//! passed to the state machine and the values it locked on) is appended to the WAL and synced to
+//! disk before it is acted upon. When the height is run again after a restart, these entries are
+//! replayed so the node resumes the same round with the same locks, and repeats its old votes
+//! instead of casting new ones.
+//!
+//! The WAL is a file of JSON lines, each holding an entry and the height it belongs to.
+
+#[cfg(test)]
+#[path = "wal_test.rs"]
+mod wal_test;
+
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Write};
+use std::path::Path;
+
+use papyrus_protobuf::consensus::Vote;
+use serde::{Deserialize, Serialize};
+use starknet_api::block::BlockNumber;
+use tracing::warn;
+
+use crate::types::{ProposalContentId, Round};
+
+/// Errors from reading or writing the WAL.
+#[derive(Debug, thiserror::Error)]
+pub enum WalError {
+    /// Failed to access the WAL file.
+    #[error(transparent)]
+    Io(#[from] io::Error),
+    /// Failed to serialize a record, or to parse a complete record read from the WAL.
+    #[error("Invalid WAL record: {0}")]
+    InvalidRecord(#[from] serde_json::Error),
+}
+
+/// A single fact recorded in the WAL.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub(crate) enum WalEntry {
+    /// A proposal passed to the state machine, either built by this node or received and
+    /// validated. `None` marks a proposal which failed validation.
+    Proposal { round: Round, proposal_id: Option<ProposalContentId>, valid_round: Option<Round> },
+    /// This node started building its proposal for `round`. If the node restarts before the
+    /// proposal is recorded, it doesn't build another one, since peers may have received parts of
+    /// the first.
+    BuildingProposal { round: Round },
+    /// A vote cast by this node.
+    Vote(Vote),
+    /// The state machine's locked and valid values, recorded whenever either changes.
+    Lock {
+        locked_value_round: Option<(ProposalContentId, Round)>,
+        valid_value_round: Option<(ProposalContentId, Round)>,
+    },
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct WalRecord {
+    height: BlockNumber,
+    entry: WalEntry,
+}
+
+/// The WAL, opened for appending the entries of a single height.
+#[derive(Debug)]
+pub(crate) struct HeightWal {
+    height: BlockNumber,
+    file: File,
+}
+
+impl HeightWal {
+    /// Opens the WAL at `path` for `height`, creating it if needed, and returns the entries already
+    /// recorded for `height`.
+    ///
+    /// Entries of earlier heights are no longer needed and are dropped. Entries of later heights
+    /// are kept, since we may still reach those heights and must not forget our votes there.
+    pub(crate) fn open(
+        path: &Path,
+        height: BlockNumber,
+    ) -> Result<(Self, Vec<WalEntry>), WalError> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let (records, is_torn) = read_records(path)?;
+        let num_records = records.len();
+        let records: Vec<_> =
+            records.into_iter().filter(|record| record.height >= height).collect();
+        if is_torn || records.len() < num_records {
+            rewrite_records(path, &records)?;
+        }
+
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+        let entries = records
+            .into_iter()
+            .filter(|record| record.height == height)
+            .map(|record| record.entry)
+            .collect();
+        Ok((Self { height, file }, entries))
+    }
+
+    /// Appends `entry` and syncs it to disk. Only once this returns may the node act on `entry`.
+    pub(crate) fn append(&mut self, entry: WalEntry) -> Result<(), WalError> {
+        let mut line = serde_json::to_vec(&WalRecord { height: self.height, entry })?;
+        line.push(b'\n');
+        self.file.write_all(&line)?;
+        self.file.sync_data()?;
+        Ok(())
+    }
+}
+
+// Returns the records in the WAL, and whether its last record was torn.
+fn read_records(path: &Path) -> Result<(Vec<WalRecord>, bool), WalError> {
+    let contents = match fs::read(path) {
+        Ok(contents) => contents,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok((Vec::new(), false)),
+        Err(err) => return Err(err.into()),
+    };
+    // Each record is synced before the next one is written, so a crash can only tear the last
+    // record, which then lacks its terminating newline. Such a record was never acted upon.
+    let complete_len = contents.iter().rposition(|byte| *byte == b'\n').map_or(0, |i| i + 1);
+    let is_torn = complete_len < contents.len();
+    if is_torn {
+        warn!("Dropping a torn record at the end of the WAL {path:?}");
+    }
+    let records = serde_json::Deserializer::from_slice(&contents[..complete_len])
+        .into_iter::<WalRecord>()
+        .collect::<Result<_, _>>()?;
+    Ok((records, is_torn))
+}
+
+// Atomically replaces the WAL's contents with `records`.
+fn rewrite_records(path: &Path, records: &[WalRecord]) -> Result<(), WalError> {
+    let tmp_path = path.with_extension("tmp");
+    let mut file = File::create(&tmp_path)?;
+    for record in records {
+        serde_json::to_writer(&mut file, record)?;
+        file.write_all(b"\n")?;
+    }
+    file.sync_data()?;
+    fs::rename(tmp_path, path)?;
+    Ok(())
+}
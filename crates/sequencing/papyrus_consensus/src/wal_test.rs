@@ -0,0 +1,85 @@
+use std::fs::OpenOptions;
+use std::io::Write;
+
+use papyrus_protobuf::consensus::{Vote, VoteType};
+use starknet_api::block::{BlockHash, BlockNumber};
+use starknet_types_core::felt::Felt;
+use tempfile::TempDir;
+
+use crate::types::DEFAULT_VALIDATOR_ID;
+use crate::wal::{HeightWal, WalEntry};
+
+const WAL_FILE: &str = "consensus.wal";
+
+fn vote(height: u64) -> WalEntry {
+    WalEntry::Vote(Vote {
+        vote_type: VoteType::Prevote,
+        height,
+        round: 1,
+        block_hash: Some(BlockHash(Felt::ONE)),
+        voter: DEFAULT_VALIDATOR_ID.into(),
+        ..Default::default()
+    })
+}
+
+fn lock() -> WalEntry {
+    WalEntry::Lock {
+        locked_value_round: Some((BlockHash(Felt::ONE), 1)),
+        valid_value_round: Some((BlockHash(Felt::ONE), 1)),
+    }
+}
+
+#[test]
+fn entries_are_replayed_for_the_same_height() {
+    // Setup.
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join(WAL_FILE);
+    let (mut wal, entries) = HeightWal::open(&path, BlockNumber(1)).unwrap();
+    assert!(entries.is_empty());
+    let proposal = WalEntry::Proposal { round: 0, proposal_id: None, valid_round: None };
+    wal.append(proposal.clone()).unwrap();
+    wal.append(vote(1)).unwrap();
+    wal.append(lock()).unwrap();
+    drop(wal);
+
+    // Test.
+    let (_, entries) = HeightWal::open(&path, BlockNumber(1)).unwrap();
+
+    // Assert.
+    assert_eq!(entries, vec![proposal, vote(1), lock()]);
+}
+
+#[test]
+fn earlier_heights_are_dropped_and_later_heights_kept() {
+    // Setup.
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join(WAL_FILE);
+    HeightWal::open(&path, BlockNumber(1)).unwrap().0.append(vote(1)).unwrap();
+    HeightWal::open(&path, BlockNumber(3)).unwrap().0.append(vote(3)).unwrap();
+
+    // Test.
+    let (_, entries_at_2) = HeightWal::open(&path, BlockNumber(2)).unwrap();
+
+    // Assert.
+    assert!(entries_at_2.is_empty());
+    assert!(HeightWal::open(&path, BlockNumber(1)).unwrap().1.is_empty());
+    assert_eq!(HeightWal::open(&path, BlockNumber(3)).unwrap().1, vec![vote(3)]);
+}
+
+#[test]
+fn torn_record_is_dropped() {
+    // Setup.
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join(WAL_FILE);
+    HeightWal::open(&path, BlockNumber(1)).unwrap().0.append(vote(1)).unwrap();
+    // Simulate a crash in the middle of writing a record.
+    OpenOptions::new().append(true).open(&path).unwrap().write_all(b"{\"height\":1,\"en").unwrap();
+
+    // Test.
+    let (mut wal, entries) = HeightWal::open(&path, BlockNumber(1)).unwrap();
+    wal.append(lock()).unwrap();
+
+    // Assert.
+    assert_eq!(entries, vec![vote(1)]);
+    assert_eq!(HeightWal::open(&path, BlockNumber(1)).unwrap().1, vec![vote(1), lock()]);
+}
@@ -7,3 +7,4 @@
 pub mod papyrus_consensus_context;
 #[allow(missing_docs)]
 pub mod sequencer_consensus_context;
+pub mod staking_validator_set_provider;
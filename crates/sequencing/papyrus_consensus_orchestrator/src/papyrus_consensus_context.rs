@@ -15,17 +15,23 @@ use std::time::Duration;
 use async_trait::async_trait;
 use futures::channel::{mpsc, oneshot};
 use futures::{SinkExt, StreamExt};
+use papyrus_consensus::signature::ConsensusSigner;
 use papyrus_consensus::types::{
     ConsensusContext,
     ConsensusError,
     ProposalContentId,
     Round,
     ValidatorId,
-    DEFAULT_VALIDATOR_ID,
+};
+use papyrus_consensus::validator_set::{
+    wait_for_validator_set,
+    ValidatorSet,
+    ValidatorSetProvider,
 };
 use papyrus_network::network_manager::{BroadcastTopicClient, BroadcastTopicClientTrait};
 use papyrus_protobuf::consensus::{
     ConsensusMessage,
+    Misbehavior,
     Proposal,
     ProposalFin,
     ProposalInit,
@@ -37,6 +43,7 @@ use papyrus_storage::body::BodyStorageReader;
 use papyrus_storage::header::HeaderStorageReader;
 use papyrus_storage::{StorageError, StorageReader};
 use starknet_api::block::BlockNumber;
+use starknet_api::crypto::utils::Signature;
 use starknet_api::transaction::Transaction;
 use tracing::{debug, debug_span, info, warn, Instrument};
 
@@ -50,8 +57,10 @@ pub struct PapyrusConsensusContext {
     storage_reader: StorageReader,
     network_broadcast_client: BroadcastTopicClient<ConsensusMessage>,
     network_proposal_sender: mpsc::Sender<(u64, mpsc::Receiver<ProposalPart>)>,
-    validators: Vec<ValidatorId>,
+    validator_set_provider: Arc<dyn ValidatorSetProvider>,
     sync_broadcast_sender: Option<BroadcastTopicClient<Vote>>,
+    // Signs the fin of the proposals we build.
+    signer: ConsensusSigner,
     // Proposal building/validating returns immediately, leaving the actual processing to a spawned
     // task. The spawned task processes the proposal asynchronously and updates the
     // valid_proposals map upon completion, ensuring consistency across tasks.
@@ -63,18 +72,17 @@ impl PapyrusConsensusContext {
         storage_reader: StorageReader,
         network_broadcast_client: BroadcastTopicClient<ConsensusMessage>,
         network_proposal_sender: mpsc::Sender<(u64, mpsc::Receiver<ProposalPart>)>,
-        num_validators: u64,
+        validator_set_provider: Arc<dyn ValidatorSetProvider>,
         sync_broadcast_sender: Option<BroadcastTopicClient<Vote>>,
+        signer: ConsensusSigner,
     ) -> Self {
         Self {
             storage_reader,
             network_broadcast_client,
             network_proposal_sender,
-            // TODO(Matan): Set the actual validator IDs (contract addresses).
-            validators: (0..num_validators)
-                .map(|i| ValidatorId::from(DEFAULT_VALIDATOR_ID + i))
-                .collect(),
+            validator_set_provider,
             sync_broadcast_sender,
+            signer,
             valid_proposals: Arc::new(Mutex::new(BTreeMap::new())),
         }
     }
@@ -94,6 +102,7 @@ impl ConsensusContext for PapyrusConsensusContext {
 
         let storage_reader = self.storage_reader.clone();
         let valid_proposals = Arc::clone(&self.valid_proposals);
+        let signer = self.signer.clone();
         tokio::spawn(
             async move {
                 // TODO(dvir): consider fix this for the case of reverts. If between the check that
@@ -142,10 +151,14 @@ impl ConsensusContext for PapyrusConsensusContext {
                     }))
                     .await
                     .expect("Failed to send transactions");
-                proposal_sender
-                    .send(ProposalPart::Fin(ProposalFin { proposal_content_id: block_hash }))
-                    .await
-                    .expect("Failed to send fin");
+                let fin = signer.sign_proposal_fin(
+                    &proposal_init,
+                    ProposalFin {
+                        proposal_content_id: block_hash,
+                        signature: Signature::default(),
+                    },
+                );
+                proposal_sender.send(ProposalPart::Fin(fin)).await.expect("Failed to send fin");
                 {
                     let mut proposals = valid_proposals
                         .lock()
@@ -194,7 +207,7 @@ impl ConsensusContext for PapyrusConsensusContext {
 
                 // First gather all the non-fin transactions.
                 let mut content_transactions: Vec<Transaction> = Vec::new();
-                let received_block_hash = loop {
+                let received_fin = loop {
                     match content.next().await {
                         Some(ProposalPart::Transactions(batch)) => {
                             for tx in batch.transactions {
@@ -202,7 +215,7 @@ impl ConsensusContext for PapyrusConsensusContext {
                             }
                         }
                         Some(ProposalPart::Fin(fin)) => {
-                            break fin.proposal_content_id;
+                            break fin;
                         }
                         msg => panic!("Unexpected message: {msg:?}"),
                     }
@@ -238,11 +251,9 @@ impl ConsensusContext for PapyrusConsensusContext {
                 // Done after inserting the proposal into the map to avoid race conditions between
                 // insertion and calls to `repropose`.
                 // This can happen as a result of sync interrupting `run_height`.
-                fin_sender
-                    .send((block_hash, ProposalFin { proposal_content_id: received_block_hash }))
-                    .unwrap_or_else(|_| {
-                        warn!("Failed to send block to consensus. height={height}");
-                    })
+                fin_sender.send((block_hash, received_fin)).unwrap_or_else(|_| {
+                    warn!("Failed to send block to consensus. height={height}");
+                })
             }
             .instrument(debug_span!("consensus_validate_proposal")),
         );
@@ -275,12 +286,20 @@ impl ConsensusContext for PapyrusConsensusContext {
             .expect("Failed to send proposal");
     }
 
-    async fn validators(&self, _height: BlockNumber) -> Vec<ValidatorId> {
-        self.validators.clone()
+    async fn validators(&self, height: BlockNumber) -> Result<ValidatorSet, ConsensusError> {
+        Ok(wait_for_validator_set(self.validator_set_provider.as_ref(), height).await?)
     }
 
-    fn proposer(&self, _height: BlockNumber, _round: Round) -> ValidatorId {
-        *self.validators.first().expect("there should be at least one validator")
+    // Papyrus reruns consensus on blocks which already exist, so the proposer is kept fixed (the
+    // lowest validator ID) instead of rotating.
+    fn proposer(&self, height: BlockNumber, _round: Round) -> ValidatorId {
+        let validator_set = self
+            .validator_set_provider
+            .validator_set(height)
+            .expect("The validator set should be available once the height started");
+        let (proposer, _) =
+            validator_set.iter().next().expect("there should be at least one validator");
+        *proposer
     }
 
     async fn broadcast(&mut self, message: ConsensusMessage) -> Result<(), ConsensusError> {
@@ -308,6 +327,16 @@ impl ConsensusContext for PapyrusConsensusContext {
         Ok(())
     }
 
+    async fn report_misbehavior(&mut self, misbehavior: Misbehavior) {
+        // Until slashing exists, the evidence is left for the operator in the log.
+        warn!(
+            "Validator {:?} equivocated at height {}, round {}: {misbehavior:?}",
+            misbehavior.offender(),
+            misbehavior.height(),
+            misbehavior.round()
+        );
+    }
+
     async fn set_height_and_round(&mut self, _height: BlockNumber, _round: Round) {
         // No-op
     }
@@ -1,9 +1,12 @@
+use std::sync::Arc;
 use std::time::Duration;
 
 use futures::channel::{mpsc, oneshot};
 use futures::StreamExt;
+use papyrus_consensus::signature::ConsensusSigner;
 use papyrus_consensus::stream_handler::StreamHandler;
 use papyrus_consensus::types::{ConsensusContext, ValidatorId, DEFAULT_VALIDATOR_ID};
+use papyrus_consensus::validator_set::ValidatorSet;
 use papyrus_network::network_manager::test_utils::{
     mock_register_broadcast_topic,
     BroadcastNetworkMock,
@@ -24,6 +27,7 @@ use papyrus_storage::header::HeaderStorageWriter;
 use papyrus_storage::test_utils::get_test_storage;
 use papyrus_test_utils::get_test_block;
 use starknet_api::block::{Block, BlockHash};
+use starknet_api::crypto::utils::Signature;
 
 use crate::papyrus_consensus_context::PapyrusConsensusContext;
 
@@ -41,6 +45,7 @@ async fn build_proposal() {
         round: 0,
         proposer: ValidatorId::from(DEFAULT_VALIDATOR_ID),
         valid_round: None,
+        signature: Signature::default(),
     };
     // TODO(Asmaa): Test proposal content.
     let fin_receiver = papyrus_context.build_proposal(proposal_init, Duration::MAX).await;
@@ -62,7 +67,10 @@ async fn validate_proposal_success() {
         });
         validate_sender.try_send(tx_part).unwrap();
     }
-    let fin_part = ProposalPart::Fin(ProposalFin { proposal_content_id: block.header.block_hash });
+    let fin_part = ProposalPart::Fin(ProposalFin {
+        proposal_content_id: block.header.block_hash,
+        signature: Signature::default(),
+    });
     validate_sender.try_send(fin_part).unwrap();
     validate_sender.close_channel();
 
@@ -154,8 +162,11 @@ fn test_setup() -> (
         storage_reader.clone(),
         network_channels.subscriber_channels.broadcast_topic_client,
         outbound_internal_sender,
-        4,
+        Arc::new(ValidatorSet::with_equal_voting_power(
+            (0..4).map(|i| ValidatorId::from(DEFAULT_VALIDATOR_ID + i)),
+        )),
         Some(sync_channels.subscriber_channels.broadcast_topic_client),
+        ConsensusSigner::for_testing(ValidatorId::from(DEFAULT_VALIDATOR_ID)),
     );
     (block, papyrus_context, network_channels.mock_network, sync_channels.mock_network)
 }
@@ -12,17 +12,23 @@ use std::time::Duration;
 use async_trait::async_trait;
 use futures::channel::{mpsc, oneshot};
 use futures::{SinkExt, StreamExt};
+use papyrus_consensus::signature::ConsensusSigner;
 use papyrus_consensus::types::{
     ConsensusContext,
     ConsensusError,
     ProposalContentId,
     Round,
     ValidatorId,
-    DEFAULT_VALIDATOR_ID,
+};
+use papyrus_consensus::validator_set::{
+    wait_for_validator_set,
+    ValidatorSet,
+    ValidatorSetProvider,
 };
 use papyrus_network::network_manager::{BroadcastTopicClient, BroadcastTopicClientTrait};
 use papyrus_protobuf::consensus::{
     ConsensusMessage,
+    Misbehavior,
     ProposalFin,
     ProposalInit,
     ProposalPart,
@@ -39,8 +45,10 @@ use starknet_api::block::{
     GasPrices,
     NonzeroGasPrice,
 };
+use starknet_api::crypto::utils::Signature;
 use starknet_api::executable_transaction::Transaction as ExecutableTransaction;
 use starknet_batcher_types::batcher_types::{
+    AbortProposalInput,
     DecisionReachedInput,
     GetProposalContent,
     GetProposalContentInput,
@@ -82,7 +90,7 @@ const CHANNEL_SIZE: usize = 100;
 
 pub struct SequencerConsensusContext {
     batcher: Arc<dyn BatcherClient>,
-    validators: Vec<ValidatorId>,
+    validator_set_provider: Arc<dyn ValidatorSetProvider>,
     // Proposal building/validating returns immediately, leaving the actual processing to a spawned
     // task. The spawned task processes the proposal asynchronously and updates the
     // valid_proposals map upon completion, ensuring consistency across tasks.
@@ -97,13 +105,15 @@ pub struct SequencerConsensusContext {
     // Building proposals are not tracked as active, as consensus can't move on to the next
     // height/round until building is done. Context only works on proposals for the
     // current round.
-    active_proposal: Option<(Arc<Notify>, JoinHandle<()>)>,
+    active_proposal: Option<(ProposalId, Arc<Notify>, JoinHandle<()>)>,
     // Stores proposals for future rounds until the round is reached.
     queued_proposals:
         BTreeMap<Round, (ValidationParams, oneshot::Sender<(ProposalContentId, ProposalFin)>)>,
     outbound_proposal_sender: mpsc::Sender<(u64, mpsc::Receiver<ProposalPart>)>,
     // Used to broadcast votes to other consensus nodes.
     vote_broadcast_client: BroadcastTopicClient<ConsensusMessage>,
+    // Signs the fin of the proposals we build.
+    signer: ConsensusSigner,
 }
 
 impl SequencerConsensusContext {
@@ -111,16 +121,15 @@ impl SequencerConsensusContext {
         batcher: Arc<dyn BatcherClient>,
         outbound_proposal_sender: mpsc::Sender<(u64, mpsc::Receiver<ProposalPart>)>,
         vote_broadcast_client: BroadcastTopicClient<ConsensusMessage>,
-        num_validators: u64,
+        validator_set_provider: Arc<dyn ValidatorSetProvider>,
+        signer: ConsensusSigner,
     ) -> Self {
         Self {
             batcher,
             outbound_proposal_sender,
             vote_broadcast_client,
-            // TODO(Matan): Set the actual validator IDs (contract addresses).
-            validators: (0..num_validators)
-                .map(|i| ValidatorId::from(DEFAULT_VALIDATOR_ID + i))
-                .collect(),
+            validator_set_provider,
+            signer,
             valid_proposals: Arc::new(Mutex::new(HeightToIdToContent::new())),
             proposal_id: 0,
             current_height: None,
@@ -150,6 +159,7 @@ impl ConsensusContext for SequencerConsensusContext {
 
         let batcher = Arc::clone(&self.batcher);
         let valid_proposals = Arc::clone(&self.valid_proposals);
+        let signer = self.signer.clone();
 
         let proposal_id = ProposalId(self.proposal_id);
         self.proposal_id += 1;
@@ -198,12 +208,13 @@ impl ConsensusContext for SequencerConsensusContext {
         tokio::spawn(
             async move {
                 stream_build_proposal(
-                    proposal_init.height,
+                    proposal_init,
                     proposal_id,
                     batcher,
                     valid_proposals,
                     proposal_sender,
                     fin_sender,
+                    signer,
                 )
                 .await;
             }
@@ -260,17 +271,15 @@ impl ConsensusContext for SequencerConsensusContext {
         // TODO(guyn): Stream the TXs to the network.
     }
 
-    async fn validators(&self, _height: BlockNumber) -> Vec<ValidatorId> {
-        self.validators.clone()
+    async fn validators(&self, height: BlockNumber) -> Result<ValidatorSet, ConsensusError> {
+        Ok(wait_for_validator_set(self.validator_set_provider.as_ref(), height).await?)
     }
 
     fn proposer(&self, height: BlockNumber, round: Round) -> ValidatorId {
-        let height: usize = height.0.try_into().expect("Cannot convert to usize");
-        let round: usize = round.try_into().expect("Cannot convert to usize");
-        *self
-            .validators
-            .get((height + round) % self.validators.len())
-            .expect("There should be at least one validator")
+        self.validator_set_provider
+            .validator_set(height)
+            .expect("The validator set should be available once the height started")
+            .proposer(height, round)
     }
 
     async fn broadcast(&mut self, message: ConsensusMessage) -> Result<(), ConsensusError> {
@@ -303,14 +312,24 @@ impl ConsensusContext for SequencerConsensusContext {
         Ok(())
     }
 
+    async fn report_misbehavior(&mut self, misbehavior: Misbehavior) {
+        warn!(
+            "Validator {:?} equivocated at height {}, round {}: {misbehavior:?}",
+            misbehavior.offender(),
+            misbehavior.height(),
+            misbehavior.round()
+        );
+    }
+
     async fn set_height_and_round(&mut self, height: BlockNumber, round: Round) {
         if self.current_height.map(|h| height > h).unwrap_or(true) {
             self.current_height = Some(height);
             assert_eq!(round, 0);
             self.current_round = round;
+            // No need to abort the proposal in the batcher, starting the new height drops all the
+            // proposals of the previous one.
             self.interrupt_active_proposal();
             self.queued_proposals.clear();
-            self.active_proposal = None;
             // The Batcher must be told when we begin to work on a new height. The implicit model is
             // that consensus works on a given height until it is done (either a decision is reached
             // or sync causes us to move on) and then moves on to a different height, never to
@@ -326,7 +345,10 @@ impl ConsensusContext for SequencerConsensusContext {
             return;
         }
         assert!(round > self.current_round);
-        self.interrupt_active_proposal();
+        if let Some(proposal_id) = self.interrupt_active_proposal() {
+            // Abort the stale proposal so the batcher can work on the one of the new round.
+            abort_proposal(self.batcher.as_ref(), proposal_id).await;
+        }
         self.current_round = round;
         let mut to_process = None;
         while let Some(entry) = self.queued_proposals.first_entry() {
@@ -396,7 +418,7 @@ impl SequencerConsensusContext {
                 let validate_fut = stream_validate_proposal(
                     height,
                     proposal_id,
-                    batcher,
+                    Arc::clone(&batcher),
                     valid_proposals,
                     content_receiver,
                     fin_sender,
@@ -406,19 +428,21 @@ impl SequencerConsensusContext {
                     result = tokio::time::timeout(timeout, validate_fut) =>{
                         if let Err(e) = result {
                             error!("Validation timed out. {e:?}");
+                            abort_proposal(batcher.as_ref(), proposal_id).await;
                         }
                     }
                 }
             }
             .instrument(debug_span!("consensus_validate_proposal")),
         );
-        self.active_proposal = Some((notify, handle));
+        self.active_proposal = Some((proposal_id, notify, handle));
     }
 
-    fn interrupt_active_proposal(&self) {
-        if let Some((notify, _)) = &self.active_proposal {
-            notify.notify_one();
-        }
+    // Stops validating the active proposal, returning its ID if there was one.
+    fn interrupt_active_proposal(&mut self) -> Option<ProposalId> {
+        let (proposal_id, notify, _) = self.active_proposal.take()?;
+        notify.notify_one();
+        Some(proposal_id)
     }
 }
 
@@ -427,15 +451,17 @@ impl SequencerConsensusContext {
 // 2. Forward these to the stream handler to be streamed out to the network.
 // 3. Once finished, receive the commitment from the batcher.
 // 4. Store the proposal for re-proposal.
-// 5. Send the commitment to the stream handler (to send fin).
+// 5. Send the signed commitment to the stream handler (to send fin).
 async fn stream_build_proposal(
-    height: BlockNumber,
+    proposal_init: ProposalInit,
     proposal_id: ProposalId,
     batcher: Arc<dyn BatcherClient>,
     valid_proposals: Arc<Mutex<HeightToIdToContent>>,
     mut proposal_sender: mpsc::Sender<ProposalPart>,
     fin_sender: oneshot::Sender<ProposalContentId>,
+    signer: ConsensusSigner,
 ) {
+    let height = proposal_init.height;
     let mut content = Vec::new();
     loop {
         let response =
@@ -479,8 +505,12 @@ async fn stream_build_proposal(
                     height
                 );
                 debug!("Broadcasting proposal fin: {proposal_content_id:?}");
+                let fin = signer.sign_proposal_fin(
+                    &proposal_init,
+                    ProposalFin { proposal_content_id, signature: Signature::default() },
+                );
                 proposal_sender
-                    .send(ProposalPart::Fin(ProposalFin { proposal_content_id }))
+                    .send(ProposalPart::Fin(fin))
                     .await
                     .expect("Failed to broadcast proposal fin");
                 // Update valid_proposals before sending fin to avoid a race condition
@@ -515,10 +545,10 @@ async fn stream_validate_proposal(
     fin_sender: oneshot::Sender<(ProposalContentId, ProposalFin)>,
 ) {
     let mut content = Vec::new();
-    let network_block_id = loop {
+    let network_fin = loop {
         let Some(prop_part) = content_receiver.next().await else {
-            // TODO(Asmaa): Tell the batcher to abort.
             warn!("Failed to receive proposal content: {proposal_id:?}");
+            abort_proposal(batcher.as_ref(), proposal_id).await;
             return;
         };
         match prop_part {
@@ -545,9 +575,9 @@ async fn stream_validate_proposal(
                     status => panic!("Unexpected status: for {proposal_id:?}, {status:?}"),
                 }
             }
-            ProposalPart::Fin(ProposalFin { proposal_content_id: id }) => {
+            ProposalPart::Fin(fin) => {
                 // Output this along with the ID from batcher, to compare them.
-                break id;
+                break fin;
             }
             _ => panic!("Invalid proposal part: {:?}", prop_part),
         }
@@ -570,21 +600,24 @@ async fn stream_validate_proposal(
         "Finished validating proposal {:?}: network_block_id: {:?}, batcher_block_id = {:?}, \
          num_txs = {:?}, height = {:?}",
         proposal_id,
-        network_block_id,
+        network_fin.proposal_content_id,
         batcher_block_id,
         content.len(),
         height
     );
     // Update valid_proposals before sending fin to avoid a race condition
     // with `get_proposal` being called before `valid_proposals` is updated.
-    // TODO(Matan): Consider validating the ProposalFin signature here.
+    // The fin's signature is verified by consensus.
     let mut valid_proposals = valid_proposals.lock().unwrap();
     valid_proposals.entry(height).or_default().insert(batcher_block_id, (content, proposal_id));
-    if fin_sender
-        .send((batcher_block_id, ProposalFin { proposal_content_id: network_block_id }))
-        .is_err()
-    {
+    if fin_sender.send((batcher_block_id, network_fin)).is_err() {
         // Consensus may exit early (e.g. sync).
         warn!("Failed to send proposal content ids");
     }
 }
+
+async fn abort_proposal(batcher: &dyn BatcherClient, proposal_id: ProposalId) {
+    if let Err(e) = batcher.abort_proposal(AbortProposalInput { proposal_id }).await {
+        warn!("Failed to abort proposal {proposal_id:?}: {e:?}");
+    }
+}
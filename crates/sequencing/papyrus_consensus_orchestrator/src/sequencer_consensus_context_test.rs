@@ -5,8 +5,10 @@ use std::vec;
 use futures::channel::mpsc;
 use futures::{FutureExt, SinkExt};
 use lazy_static::lazy_static;
+use papyrus_consensus::signature::ConsensusSigner;
 use papyrus_consensus::stream_handler::StreamHandler;
 use papyrus_consensus::types::{ConsensusContext, ValidatorId, DEFAULT_VALIDATOR_ID};
+use papyrus_consensus::validator_set::ValidatorSet;
 use papyrus_network::network_manager::test_utils::{
     mock_register_broadcast_topic,
     BroadcastNetworkMock,
@@ -23,6 +25,7 @@ use papyrus_protobuf::consensus::{
 };
 use starknet_api::block::{BlockHash, BlockNumber};
 use starknet_api::core::StateDiffCommitment;
+use starknet_api::crypto::utils::Signature;
 use starknet_api::executable_transaction::Transaction as ExecutableTransaction;
 use starknet_api::hash::PoseidonHash;
 use starknet_api::test_utils::invoke::{executable_invoke_tx, invoke_tx, InvokeTxArgs};
@@ -90,7 +93,10 @@ fn setup(batcher: MockBatcherClient) -> (SequencerConsensusContext, NetworkDepen
         Arc::new(batcher),
         outbound_proposal_stream_sender,
         votes_topic_client,
-        NUM_VALIDATORS,
+        Arc::new(ValidatorSet::with_equal_voting_power(
+            (0..NUM_VALIDATORS).map(|i| ValidatorId::from(DEFAULT_VALIDATOR_ID + i)),
+        )),
+        ConsensusSigner::for_testing(ValidatorId::from(DEFAULT_VALIDATOR_ID)),
     );
 
     let network_dependencies = NetworkDependencies {
@@ -192,6 +198,7 @@ async fn validate_proposal_success() {
     content_sender
         .send(ProposalPart::Fin(ProposalFin {
             proposal_content_id: BlockHash(STATE_DIFF_COMMITMENT.0.0),
+            signature: Signature::default(),
         }))
         .await
         .unwrap();
@@ -250,6 +257,7 @@ async fn repropose() {
     content_sender
         .send(ProposalPart::Fin(ProposalFin {
             proposal_content_id: BlockHash(STATE_DIFF_COMMITMENT.0.0),
+            signature: Signature::default(),
         }))
         .await
         .unwrap();
@@ -322,6 +330,7 @@ async fn proposals_from_different_rounds() {
     });
     let prop_part_fin = ProposalPart::Fin(ProposalFin {
         proposal_content_id: BlockHash(STATE_DIFF_COMMITMENT.0.0),
+        signature: Signature::default(),
     });
 
     // The proposal from the past round is ignored.
@@ -390,6 +399,12 @@ async fn interrupt_active_proposal() {
         .times(1)
         .withf(|input| input.proposal_id == ProposalId(1))
         .returning(|_| Ok(()));
+    // The proposal of the first round is aborted once the context moves to the next round.
+    batcher
+        .expect_abort_proposal()
+        .times(1)
+        .withf(|input| input.proposal_id == ProposalId(0))
+        .returning(|_| Ok(()));
     batcher
         .expect_send_proposal_content()
         .withf(|input| {
@@ -442,6 +457,7 @@ async fn interrupt_active_proposal() {
     content_sender_1
         .send(ProposalPart::Fin(ProposalFin {
             proposal_content_id: BlockHash(STATE_DIFF_COMMITMENT.0.0),
+            signature: Signature::default(),
         }))
         .await
         .unwrap();
@@ -0,0 +1,118 @@
+//! Reads the validator set for each height from the L2 staking contract's storage.
+#[cfg(test)]
+#[path = "staking_validator_set_provider_test.rs"]
+mod staking_validator_set_provider_test;
+
+use papyrus_consensus::types::ValidatorId;
+use papyrus_consensus::validator_set::{ValidatorSet, ValidatorSetError, ValidatorSetProvider};
+use papyrus_storage::state::StateStorageReader;
+use papyrus_storage::{StorageError, StorageReader};
+use starknet_api::abi::abi_utils::get_storage_var_address;
+use starknet_api::block::BlockNumber;
+use starknet_api::core::ContractAddress;
+use starknet_api::crypto::utils::PublicKey;
+use starknet_api::state::StateNumber;
+use starknet_types_core::felt::Felt;
+use tracing::warn;
+
+/// Loads the validator set of a height from the staking contract, as of the state at the start of
+/// that height.
+///
+/// The contract is expected to expose the following storage variables:
+/// - `validators_len`: the number of validators.
+/// - `validators(i)`: the address of the i-th validator, for `i` in `0..validators_len`.
+/// - `voting_power(address)`: the stake of the validator at `address`, in any unit. Voting powers
+///   are normalized from the stakes, see [`ValidatorSet::from_stakes`].
+/// - `public_key(address)`: the Stark public key the validator at `address` signs consensus
+///   messages with. Zero if unset, in which case none of the validator's messages are accepted.
+///
+/// If the contract holds no validators, the `fallback` set is used instead. If the state for the
+/// height is not synced yet, [`ValidatorSetError::NotSynced`] is returned.
+pub struct StakingValidatorSetProvider {
+    storage_reader: StorageReader,
+    staking_contract_address: ContractAddress,
+    fallback: ValidatorSet,
+}
+
+// Bounds the number of storage reads for a height, in case `validators_len` holds garbage.
+const MAX_VALIDATORS: u64 = 1000;
+
+#[derive(Debug, thiserror::Error)]
+enum StakingReadError {
+    #[error(transparent)]
+    Storage(#[from] StorageError),
+    #[error("State for height {height} is not synced yet. State marker: {state_marker}.")]
+    StateNotSynced { height: BlockNumber, state_marker: BlockNumber },
+    #[error("Invalid value in storage variable {name}: {value}.")]
+    InvalidValue { name: &'static str, value: Felt },
+}
+
+impl StakingValidatorSetProvider {
+    /// Creates a provider which reads the contract at `staking_contract_address`.
+    pub fn new(
+        storage_reader: StorageReader,
+        staking_contract_address: ContractAddress,
+        fallback: ValidatorSet,
+    ) -> Self {
+        Self { storage_reader, staking_contract_address, fallback }
+    }
+
+    fn read_validator_set(&self, height: BlockNumber) -> Result<ValidatorSet, StakingReadError> {
+        let txn = self.storage_reader.begin_ro_txn()?;
+        let state_marker = txn.get_state_marker()?;
+        if state_marker < height {
+            return Err(StakingReadError::StateNotSynced { height, state_marker });
+        }
+        let state_reader = txn.get_state_reader()?;
+        let state_number = StateNumber::right_before_block(height);
+        let read = |name: &'static str, args: &[Felt]| -> Result<Felt, StakingReadError> {
+            let key = get_storage_var_address(name, args);
+            Ok(state_reader.get_storage_at(state_number, &self.staking_contract_address, &key)?)
+        };
+
+        let validators_len = read("validators_len", &[])?;
+        let validators_len =
+            u64::try_from(validators_len).ok().filter(|len| *len <= MAX_VALIDATORS).ok_or(
+                StakingReadError::InvalidValue { name: "validators_len", value: validators_len },
+            )?;
+        let mut validators = Vec::new();
+        let mut public_keys = Vec::new();
+        for i in 0..validators_len {
+            let address = read("validators", &[Felt::from(i)])?;
+            let id = ValidatorId::try_from(address).map_err(|_| {
+                StakingReadError::InvalidValue { name: "validators", value: address }
+            })?;
+            let stake = read("voting_power", &[address])?;
+            let stake = u128::try_from(stake).map_err(|_| StakingReadError::InvalidValue {
+                name: "voting_power",
+                value: stake,
+            })?;
+            validators.push((id, stake));
+            let public_key = read("public_key", &[address])?;
+            if public_key != Felt::ZERO {
+                public_keys.push((id, PublicKey(public_key)));
+            }
+        }
+        Ok(ValidatorSet::from_stakes(validators).with_public_keys(public_keys))
+    }
+}
+
+impl ValidatorSetProvider for StakingValidatorSetProvider {
+    fn validator_set(&self, height: BlockNumber) -> Result<ValidatorSet, ValidatorSetError> {
+        match self.read_validator_set(height) {
+            Ok(validator_set) if !validator_set.is_empty() => Ok(validator_set),
+            Ok(_) => {
+                warn!(
+                    "Staking contract {:?} has no validators at height {height}, using the \
+                     fallback validator set.",
+                    self.staking_contract_address
+                );
+                Ok(self.fallback.clone())
+            }
+            Err(StakingReadError::StateNotSynced { .. }) => {
+                Err(ValidatorSetError::NotSynced(height))
+            }
+            Err(err) => Err(ValidatorSetError::ReadFailed(height, err.to_string())),
+        }
+    }
+}
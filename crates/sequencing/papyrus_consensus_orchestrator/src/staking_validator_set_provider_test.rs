@@ -0,0 +1,188 @@
+use papyrus_consensus::types::{ValidatorId, DEFAULT_VALIDATOR_ID};
+use papyrus_consensus::validator_set::{
+    ValidatorSet,
+    ValidatorSetError,
+    ValidatorSetProvider,
+    MAX_TOTAL_VOTING_POWER,
+};
+use papyrus_storage::state::StateStorageWriter;
+use papyrus_storage::test_utils::get_test_storage;
+use starknet_api::abi::abi_utils::get_storage_var_address;
+use starknet_api::block::BlockNumber;
+use starknet_api::core::ContractAddress;
+use starknet_api::crypto::utils::PublicKey;
+use starknet_api::state::ThinStateDiff;
+use starknet_types_core::felt::Felt;
+
+use crate::staking_validator_set_provider::StakingValidatorSetProvider;
+
+const STAKING_CONTRACT_ADDRESS: u64 = 0x5;
+
+fn fallback() -> ValidatorSet {
+    ValidatorSet::with_equal_voting_power([ValidatorId::from(DEFAULT_VALIDATOR_ID)])
+}
+
+// Writes the validators, their voting power and their public key (the address plus 1) to the
+// staking contract's storage at block 0.
+fn staking_state_diff(validators: &[(u64, u128)]) -> ThinStateDiff {
+    let mut storage = vec![(
+        get_storage_var_address("validators_len", &[]),
+        Felt::from(u64::try_from(validators.len()).unwrap()),
+    )];
+    for (i, (address, voting_power)) in validators.iter().enumerate() {
+        storage.push((
+            get_storage_var_address("validators", &[Felt::from(u64::try_from(i).unwrap())]),
+            Felt::from(*address),
+        ));
+        storage.push((
+            get_storage_var_address("voting_power", &[Felt::from(*address)]),
+            Felt::from(*voting_power),
+        ));
+        storage.push((
+            get_storage_var_address("public_key", &[Felt::from(*address)]),
+            Felt::from(*address + 1),
+        ));
+    }
+    ThinStateDiff {
+        storage_diffs: [(
+            ContractAddress::from(STAKING_CONTRACT_ADDRESS),
+            storage.into_iter().collect(),
+        )]
+        .into_iter()
+        .collect(),
+        ..Default::default()
+    }
+}
+
+#[test]
+fn reads_validator_set_from_staking_contract() {
+    // Setup.
+    let ((storage_reader, mut storage_writer), _temp_dir) = get_test_storage();
+    storage_writer
+        .begin_rw_txn()
+        .unwrap()
+        .append_state_diff(BlockNumber(0), staking_state_diff(&[(0x100, 3), (0x101, 1)]))
+        .unwrap()
+        .commit()
+        .unwrap();
+    let provider = StakingValidatorSetProvider::new(
+        storage_reader,
+        ContractAddress::from(STAKING_CONTRACT_ADDRESS),
+        fallback(),
+    );
+
+    // Test.
+    let validator_set = provider.validator_set(BlockNumber(1)).unwrap();
+
+    // Assert.
+    let expected =
+        ValidatorSet::new([(ValidatorId::from(0x100_u64), 3), (ValidatorId::from(0x101_u64), 1)])
+            .with_public_keys([
+                (ValidatorId::from(0x100_u64), PublicKey(Felt::from(0x101_u64))),
+                (ValidatorId::from(0x101_u64), PublicKey(Felt::from(0x102_u64))),
+            ]);
+    assert_eq!(validator_set, expected);
+}
+
+#[test]
+fn normalizes_stakes_to_voting_power() {
+    // Setup.
+    let ((storage_reader, mut storage_writer), _temp_dir) = get_test_storage();
+    // Stakes in the smallest unit of a token with 18 decimals.
+    let token = 10_u128.pow(18);
+    storage_writer
+        .begin_rw_txn()
+        .unwrap()
+        .append_state_diff(
+            BlockNumber(0),
+            staking_state_diff(&[(0x100, 3_000_000_000 * token), (0x101, 1_000_000_000 * token)]),
+        )
+        .unwrap()
+        .commit()
+        .unwrap();
+    let provider = StakingValidatorSetProvider::new(
+        storage_reader,
+        ContractAddress::from(STAKING_CONTRACT_ADDRESS),
+        fallback(),
+    );
+
+    // Test.
+    let validator_set = provider.validator_set(BlockNumber(1)).unwrap();
+
+    // Assert.
+    let voting_power_1 = validator_set.voting_power(&ValidatorId::from(0x100_u64));
+    let voting_power_2 = validator_set.voting_power(&ValidatorId::from(0x101_u64));
+    assert!(validator_set.total_voting_power() <= MAX_TOTAL_VOTING_POWER + 2);
+    assert!(voting_power_1.abs_diff(3 * voting_power_2) <= 3);
+}
+
+#[test]
+fn state_not_synced() {
+    // Setup.
+    let ((storage_reader, mut storage_writer), _temp_dir) = get_test_storage();
+    storage_writer
+        .begin_rw_txn()
+        .unwrap()
+        .append_state_diff(BlockNumber(0), staking_state_diff(&[(0x100, 3)]))
+        .unwrap()
+        .commit()
+        .unwrap();
+    let provider = StakingValidatorSetProvider::new(
+        storage_reader,
+        ContractAddress::from(STAKING_CONTRACT_ADDRESS),
+        fallback(),
+    );
+
+    // Test.
+    let validator_set = provider.validator_set(BlockNumber(2));
+
+    // Assert.
+    assert_eq!(validator_set, Err(ValidatorSetError::NotSynced(BlockNumber(2))));
+}
+
+#[test]
+fn falls_back_when_contract_has_no_validators() {
+    // Setup.
+    let ((storage_reader, _), _temp_dir) = get_test_storage();
+    let provider = StakingValidatorSetProvider::new(
+        storage_reader,
+        ContractAddress::from(STAKING_CONTRACT_ADDRESS),
+        fallback(),
+    );
+
+    // Test.
+    let validator_set = provider.validator_set(BlockNumber(0));
+
+    // Assert.
+    assert_eq!(validator_set, Ok(fallback()));
+}
+
+#[test]
+fn rejects_too_many_validators() {
+    // Setup.
+    let ((storage_reader, mut storage_writer), _temp_dir) = get_test_storage();
+    let mut state_diff = staking_state_diff(&[]);
+    state_diff
+        .storage_diffs
+        .get_mut(&ContractAddress::from(STAKING_CONTRACT_ADDRESS))
+        .unwrap()
+        .insert(get_storage_var_address("validators_len", &[]), Felt::from(u64::MAX));
+    storage_writer
+        .begin_rw_txn()
+        .unwrap()
+        .append_state_diff(BlockNumber(0), state_diff)
+        .unwrap()
+        .commit()
+        .unwrap();
+    let provider = StakingValidatorSetProvider::new(
+        storage_reader,
+        ContractAddress::from(STAKING_CONTRACT_ADDRESS),
+        fallback(),
+    );
+
+    // Test.
+    let validator_set = provider.validator_set(BlockNumber(1));
+
+    // Assert.
+    assert!(matches!(validator_set, Err(ValidatorSetError::ReadFailed(BlockNumber(1), _))));
+}
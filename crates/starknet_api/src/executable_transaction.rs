@@ -8,6 +8,7 @@ use crate::rpc_transaction::{
     RpcDeployAccountTransactionV3,
     RpcInvokeTransaction,
     RpcInvokeTransactionV3,
+    RpcTransaction,
 };
 use crate::transaction::fields::{
     AccountDeploymentData,
@@ -90,6 +91,20 @@ impl AccountTransaction {
             AccountTransaction::Invoke(tx_data) => tx_data.tx_hash,
         }
     }
+
+    /// Returns the transaction in the form it's submitted in. Returns `None` for declare
+    /// transactions, which don't hold their Sierra class.
+    pub fn into_rpc_transaction(self) -> Option<RpcTransaction> {
+        match self {
+            AccountTransaction::Invoke(tx) => {
+                Some(RpcTransaction::Invoke(RpcInvokeTransaction::V3(tx.into())))
+            }
+            AccountTransaction::DeployAccount(tx) => {
+                Some(RpcTransaction::DeployAccount(RpcDeployAccountTransaction::V3(tx.into())))
+            }
+            AccountTransaction::Declare(_) => None,
+        }
+    }
 }
 
 // TODO: add a converter for Declare transactions as well.
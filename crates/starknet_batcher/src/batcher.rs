@@ -1,3 +1,4 @@
+use std::cmp::{max, min};
 use std::collections::HashMap;
 use std::sync::Arc;
 
@@ -6,31 +7,59 @@ use blockifier::state::global_cache::GlobalContractCache;
 use chrono::Utc;
 #[cfg(test)]
 use mockall::automock;
+use papyrus_storage::body::{BodyStorageReader, BodyStorageWriter};
+use papyrus_storage::compiled_class::CasmStorageWriter;
+use papyrus_storage::header::{HeaderStorageReader, HeaderStorageWriter};
 use papyrus_storage::state::{StateStorageReader, StateStorageWriter};
-use starknet_api::block::{BlockHashAndNumber, BlockNumber};
+use papyrus_storage::state_tries::{
+    StateTriesRoots,
+    StateTriesStorageReader,
+    StateTriesStorageWriter,
+};
+use starknet_api::block::{
+    BlockBody,
+    BlockHash,
+    BlockHashAndNumber,
+    BlockHeader,
+    BlockNumber,
+    GasPrice,
+};
 use starknet_api::executable_transaction::Transaction;
 use starknet_api::state::ThinStateDiff;
+use starknet_api::transaction::{TransactionHash, TransactionOutput};
 use starknet_batcher_types::batcher_types::{
+    AbortProposalInput,
     BatcherResult,
     DecisionReachedInput,
     GetHeightResponse,
+    GetPendingBlockUpdatesInput,
+    GetPendingBlockUpdatesResponse,
     GetProposalContent,
     GetProposalContentInput,
     GetProposalContentResponse,
+    GetProposalStatusInput,
+    GetProposalStatusResponse,
+    PendingBlockSubscriptionId,
+    PendingBlockUpdate,
     ProposalId,
+    ProposalState,
     ProposalStatus,
     ProposeBlockInput,
     SendProposalContent,
     SendProposalContentInput,
     SendProposalContentResponse,
     StartHeightInput,
+    SubscribePendingBlockResponse,
     ValidateBlockInput,
 };
 use starknet_batcher_types::errors::BatcherError;
+use starknet_l1_provider_types::{L1ProviderClientResult, SharedL1ProviderClient};
 use starknet_mempool_types::communication::SharedMempoolClient;
 use starknet_mempool_types::mempool_types::CommitBlockArgs;
 use starknet_sequencer_infra::component_definitions::ComponentStarter;
-use tracing::{debug, error, info, instrument, trace};
+use starknet_state_sync_types::communication::SharedStateSyncClient;
+use starknet_state_sync_types::state_sync_types::SyncBlock;
+use tracing::{debug, error, info, instrument, trace, warn};
 
 use crate::block_builder::{
     BlockBuilderError,
@@ -40,6 +69,15 @@ use crate::block_builder::{
     BlockMetadata,
 };
 use crate::config::BatcherConfig;
+use crate::decided_block::DecidedBlock;
+use crate::fee_market::{
+    calculate_next_base_gas_price,
+    L2GasUsage,
+    GAS_TARGET,
+    MAX_BLOCK_SIZE,
+    MIN_GAS_PRICE,
+};
+use crate::pending_block::{PendingBlockBroadcaster, PendingBlockSubscription};
 use crate::proposal_manager::{
     GenerateProposalError,
     InternalProposalStatus,
@@ -48,48 +86,108 @@ use crate::proposal_manager::{
     ProposalManagerTrait,
     ProposalOutput,
 };
-use crate::transaction_provider::{
-    DummyL1ProviderClient,
-    ProposeTransactionProvider,
-    ValidateTransactionProvider,
+use crate::proposal_metadata::{
+    FileProposalMetadataStorage,
+    ProposalMetadata,
+    ProposalMetadataStorageTrait,
 };
+use crate::state_committer::{StateCommitment, StateCommitter};
+use crate::transaction_ordering::create_transaction_ordering_policy;
+use crate::transaction_provider::{ProposeTransactionProvider, ValidateTransactionProvider};
 
 type OutputStreamReceiver = tokio::sync::mpsc::UnboundedReceiver<Transaction>;
 type InputStreamSender = tokio::sync::mpsc::Sender<Transaction>;
 
+const PROPOSAL_METADATA_DIR_NAME: &str = "batcher_proposals";
+
+// The time given to re-execute a restored proposal once a decision is reached on it.
+const PROPOSAL_REEXECUTION_TIMEOUT: tokio::time::Duration = tokio::time::Duration::from_secs(60);
+
+#[derive(Clone, Copy, Debug)]
+enum L1ProviderBlockKind {
+    Propose,
+    Validate,
+}
+
 pub struct Batcher {
     pub config: BatcherConfig,
     pub storage_reader: Arc<dyn BatcherStorageReaderTrait>,
     pub storage_writer: Box<dyn BatcherStorageWriterTrait>,
     pub mempool_client: SharedMempoolClient,
+    pub l1_provider_client: SharedL1ProviderClient,
+    pub state_sync_client: SharedStateSyncClient,
 
     active_height: Option<BlockNumber>,
+    // Whether the L1 provider is in the middle of a proposal or a validation, which must be ended
+    // by a commit before another one starts.
+    l1_provider_in_block: bool,
+    // The base L2 gas price of the next block, derived from the gas usage of the committed ones.
+    // On startup, it is restored from the last stored block.
+    l2_gas_price: u64,
+    state_committer: StateCommitter,
     proposal_manager: Box<dyn ProposalManagerTrait>,
 
     block_builder_factory: Box<dyn BlockBuilderFactoryTrait>,
     propose_tx_streams: HashMap<ProposalId, OutputStreamReceiver>,
     validate_tx_streams: HashMap<ProposalId, InputStreamSender>,
+    // The metadata of each proposal of the active height. The block info is needed to build the
+    // block header once a proposal is decided, and finished proposals are persisted so they can be
+    // executed again if the batcher restarts before a decision is reached.
+    proposals_metadata: HashMap<ProposalId, ProposalMetadata>,
+    proposal_metadata_storage: Box<dyn ProposalMetadataStorageTrait>,
+    // Proposals of the active height that finished before the batcher restarted.
+    restored_proposals: HashMap<ProposalId, ProposalMetadata>,
+    // The block builder of the active proposal publishes the block being built through this
+    // channel. The pending block is cleared once a block of its height is committed.
+    pending_block_broadcaster: PendingBlockBroadcaster,
+    pending_block_subscriptions: HashMap<PendingBlockSubscriptionId, PendingBlockSubscription>,
+    next_pending_block_subscription_id: PendingBlockSubscriptionId,
 }
 
 impl Batcher {
+    #[allow(clippy::too_many_arguments)]
     pub(crate) fn new(
         config: BatcherConfig,
         storage_reader: Arc<dyn BatcherStorageReaderTrait>,
         storage_writer: Box<dyn BatcherStorageWriterTrait>,
         mempool_client: SharedMempoolClient,
+        l1_provider_client: SharedL1ProviderClient,
+        state_sync_client: SharedStateSyncClient,
         block_builder_factory: Box<dyn BlockBuilderFactoryTrait>,
         proposal_manager: Box<dyn ProposalManagerTrait>,
+        proposal_metadata_storage: Box<dyn ProposalMetadataStorageTrait>,
+        pending_block_broadcaster: PendingBlockBroadcaster,
     ) -> Self {
+        // The base price of the next block follows from the last stored block's price and usage.
+        let l2_gas_price = storage_reader
+            .last_l2_gas_usage()
+            .expect("Failed to read the L2 gas usage from the batcher's storage")
+            .map_or(MIN_GAS_PRICE, |L2GasUsage { price, gas_used }| {
+                next_l2_gas_price(price, gas_used)
+            });
+        let state_committer = StateCommitter::load(storage_reader.as_ref())
+            .expect("Failed to read the state tries from the batcher's storage");
         Self {
             config: config.clone(),
             storage_reader,
             storage_writer,
             mempool_client,
+            l1_provider_client,
+            state_sync_client,
             active_height: None,
+            l1_provider_in_block: false,
+            l2_gas_price,
+            state_committer,
             block_builder_factory,
             proposal_manager,
             propose_tx_streams: HashMap::new(),
             validate_tx_streams: HashMap::new(),
+            proposals_metadata: HashMap::new(),
+            proposal_metadata_storage,
+            restored_proposals: HashMap::new(),
+            pending_block_broadcaster,
+            pending_block_subscriptions: HashMap::new(),
+            next_pending_block_subscription_id: PendingBlockSubscriptionId::default(),
         }
     }
 
@@ -101,23 +199,37 @@ impl Batcher {
 
         let storage_height =
             self.storage_reader.height().map_err(|_| BatcherError::InternalError)?;
-        if storage_height < input.height {
-            return Err(BatcherError::StorageNotSynced {
-                storage_height,
-                requested_height: input.height,
-            });
-        }
         if storage_height > input.height {
             return Err(BatcherError::HeightAlreadyPassed {
                 storage_height,
                 requested_height: input.height,
             });
         }
+        self.rebuild_state_committer(storage_height).await?;
+        if storage_height < input.height {
+            self.sync_storage(storage_height, input.height).await?;
+        }
 
         // Clear all the proposals from the previous height.
         self.proposal_manager.reset().await;
         self.propose_tx_streams.clear();
         self.validate_tx_streams.clear();
+        self.proposals_metadata.clear();
+        self.pending_block_broadcaster.publish(PendingBlockUpdate::Cleared);
+
+        // The batcher might have restarted in the middle of this height.
+        self.restored_proposals =
+            self.proposal_metadata_storage.load(input.height).await.unwrap_or_else(|err| {
+                error!("Failed to load the stored proposals of height {}: {}", input.height, err);
+                HashMap::new()
+            });
+        if !self.restored_proposals.is_empty() {
+            info!(
+                "Restored {} proposals of height {}.",
+                self.restored_proposals.len(),
+                input.height
+            );
+        }
 
         info!("Starting to work on height {}.", input.height);
         self.active_height = Some(input.height);
@@ -137,11 +249,15 @@ impl Batcher {
             propose_block_input.retrospective_block_hash,
         )?;
 
+        self.start_l1_provider_block(L1ProviderBlockKind::Propose).await?;
         let tx_provider = ProposeTransactionProvider::new(
             self.mempool_client.clone(),
-            // TODO: use a real L1 provider client.
-            Arc::new(DummyL1ProviderClient),
+            self.l1_provider_client.clone(),
             self.config.max_l1_handler_txs_per_block_proposal,
+            create_transaction_ordering_policy(
+                &self.config.transaction_ordering_config,
+                self.config.block_builder_config.bouncer_config.block_max_capacity.sierra_gas,
+            ),
         );
 
         // A channel to receive the transactions included in the proposed block.
@@ -151,7 +267,7 @@ impl Batcher {
             .block_builder_factory
             .create_block_builder(
                 BlockMetadata {
-                    block_info: propose_block_input.block_info,
+                    block_info: propose_block_input.block_info.clone(),
                     retrospective_block_hash: propose_block_input.retrospective_block_hash,
                 },
                 BlockBuilderExecutionParams {
@@ -168,6 +284,14 @@ impl Batcher {
             .await?;
 
         self.propose_tx_streams.insert(propose_block_input.proposal_id, output_tx_receiver);
+        self.proposals_metadata.insert(
+            propose_block_input.proposal_id,
+            ProposalMetadata {
+                block_info: propose_block_input.block_info,
+                retrospective_block_hash: propose_block_input.retrospective_block_hash,
+                txs: Vec::new(),
+            },
+        );
         Ok(())
     }
 
@@ -183,21 +307,21 @@ impl Batcher {
             validate_block_input.retrospective_block_hash,
         )?;
 
+        self.start_l1_provider_block(L1ProviderBlockKind::Validate).await?;
         // A channel to send the transactions to include in the block being validated.
         let (input_tx_sender, input_tx_receiver) =
             tokio::sync::mpsc::channel(self.config.input_stream_content_buffer_size);
 
         let tx_provider = ValidateTransactionProvider {
             tx_receiver: input_tx_receiver,
-            // TODO: use a real L1 provider client.
-            l1_provider_client: Arc::new(DummyL1ProviderClient),
+            l1_provider_client: self.l1_provider_client.clone(),
         };
 
         let (block_builder, abort_signal_sender) = self
             .block_builder_factory
             .create_block_builder(
                 BlockMetadata {
-                    block_info: validate_block_input.block_info,
+                    block_info: validate_block_input.block_info.clone(),
                     retrospective_block_hash: validate_block_input.retrospective_block_hash,
                 },
                 BlockBuilderExecutionParams {
@@ -214,6 +338,14 @@ impl Batcher {
             .await?;
 
         self.validate_tx_streams.insert(validate_block_input.proposal_id, input_tx_sender);
+        self.proposals_metadata.insert(
+            validate_block_input.proposal_id,
+            ProposalMetadata {
+                block_info: validate_block_input.block_info,
+                retrospective_block_hash: validate_block_input.retrospective_block_hash,
+                txs: Vec::new(),
+            },
+        );
         Ok(())
     }
 
@@ -236,12 +368,56 @@ impl Batcher {
                 self.close_tx_channel_and_get_commitment(proposal_id).await
             }
             SendProposalContent::Abort => {
-                self.proposal_manager.abort_proposal(proposal_id).await;
+                self.abort_proposal(AbortProposalInput { proposal_id }).await?;
                 Ok(SendProposalContentResponse { response: ProposalStatus::Aborted })
             }
         }
     }
 
+    /// Stops the proposal if it is still being executed. Its task is awaited by the proposal
+    /// manager before the next proposal starts, so a stale proposal never runs alongside it.
+    #[instrument(skip(self), err)]
+    pub async fn abort_proposal(&mut self, input: AbortProposalInput) -> BatcherResult<()> {
+        let proposal_id = input.proposal_id;
+        match self.proposal_manager.get_proposal_status(proposal_id).await {
+            InternalProposalStatus::Processing => {
+                info!("Aborting proposal {}.", proposal_id);
+                self.proposal_manager.abort_proposal(proposal_id).await;
+                // Close the streams of the proposal, so its task isn't left waiting on them.
+                self.propose_tx_streams.remove(&proposal_id);
+                self.validate_tx_streams.remove(&proposal_id);
+                Ok(())
+            }
+            // The proposal is already done, there is nothing to stop.
+            InternalProposalStatus::Finished | InternalProposalStatus::Failed => Ok(()),
+            InternalProposalStatus::NotFound => Err(BatcherError::ProposalNotFound { proposal_id }),
+        }
+    }
+
+    #[instrument(skip(self), err)]
+    pub async fn get_proposal_status(
+        &mut self,
+        input: GetProposalStatusInput,
+    ) -> BatcherResult<GetProposalStatusResponse> {
+        let proposal_id = input.proposal_id;
+        let state = match self.proposal_manager.get_proposal_status(proposal_id).await {
+            InternalProposalStatus::Processing => ProposalState::Building,
+            InternalProposalStatus::NotFound => {
+                return Err(BatcherError::ProposalNotFound { proposal_id });
+            }
+            // The proposal is done, so this doesn't block.
+            InternalProposalStatus::Finished | InternalProposalStatus::Failed => {
+                match self.proposal_manager.await_proposal_commitment(proposal_id).await {
+                    Some(Ok(proposal_commitment)) => ProposalState::Finished(proposal_commitment),
+                    Some(Err(ProposalError::Aborted)) => ProposalState::Aborted,
+                    Some(Err(err)) => ProposalState::Failed { reason: err.to_string() },
+                    None => return Err(BatcherError::ProposalNotFound { proposal_id }),
+                }
+            }
+        };
+        Ok(GetProposalStatusResponse { state })
+    }
+
     async fn send_txs_and_get_status(
         &mut self,
         proposal_id: ProposalId,
@@ -253,6 +429,9 @@ impl Batcher {
                     .validate_tx_streams
                     .get(&proposal_id)
                     .expect("Expecting tx_provider_sender to exist during batching.");
+                if let Some(metadata) = self.proposals_metadata.get_mut(&proposal_id) {
+                    metadata.txs.extend_from_slice(&txs);
+                }
                 for tx in txs {
                     tx_provider_sender.send(tx).await.map_err(|err| {
                         error!("Failed to send transaction to the tx provider: {}", err);
@@ -281,7 +460,10 @@ impl Batcher {
         self.close_input_transaction_stream(proposal_id)?;
 
         let response = match self.proposal_manager.await_proposal_commitment(proposal_id).await {
-            Some(Ok(proposal_commitment)) => ProposalStatus::Finished(proposal_commitment),
+            Some(Ok(proposal_commitment)) => {
+                self.store_proposal_metadata(proposal_id).await;
+                ProposalStatus::Finished(proposal_commitment)
+            }
             Some(Err(ProposalError::BlockBuilderError(err))) => match err.as_ref() {
                 BlockBuilderError::FailOnError(_) => ProposalStatus::InvalidProposal,
                 _ => return Err(BatcherError::InternalError),
@@ -330,6 +512,9 @@ impl Batcher {
 
         if n_executed_txs != 0 {
             debug!("Streaming {} txs", n_executed_txs);
+            if let Some(metadata) = self.proposals_metadata.get_mut(&proposal_id) {
+                metadata.txs.extend_from_slice(&txs);
+            }
             return Ok(GetProposalContentResponse { content: GetProposalContent::Txs(txs) });
         }
 
@@ -342,6 +527,7 @@ impl Batcher {
             .await_proposal_commitment(proposal_id)
             .await
             .ok_or(BatcherError::ProposalNotFound { proposal_id })??;
+        self.store_proposal_metadata(proposal_id).await;
         Ok(GetProposalContentResponse {
             content: GetProposalContent::Finished(proposal_commitment),
         })
@@ -350,77 +536,545 @@ impl Batcher {
     #[instrument(skip(self), err)]
     pub async fn decision_reached(&mut self, input: DecisionReachedInput) -> BatcherResult<()> {
         let proposal_id = input.proposal_id;
-        let proposal_output = self
-            .proposal_manager
-            .take_proposal_result(proposal_id)
-            .await
-            .ok_or(BatcherError::ExecutedProposalNotFound { proposal_id })??;
-        let ProposalOutput { state_diff, nonces: address_to_nonce, tx_hashes, .. } =
-            proposal_output;
-        // TODO: Keep the height from start_height or get it from the input.
-        let height = self.storage_reader.height().map_err(|err| {
-            error!("Failed to get height from storage: {}", err);
-            BatcherError::InternalError
-        })?;
+        let (proposal_output, block_info) =
+            match self.proposal_manager.take_proposal_result(proposal_id).await {
+                Some(proposal_result) => {
+                    let proposal_output = proposal_result?;
+                    let metadata = self
+                        .proposals_metadata
+                        .remove(&proposal_id)
+                        .ok_or(BatcherError::ExecutedProposalNotFound { proposal_id })?;
+                    (proposal_output, metadata.block_info)
+                }
+                None => {
+                    let metadata = self
+                        .restored_proposals
+                        .remove(&proposal_id)
+                        .ok_or(BatcherError::ExecutedProposalNotFound { proposal_id })?;
+                    let proposal_output = self.reexecute_proposal(proposal_id, &metadata).await?;
+                    (proposal_output, metadata.block_info)
+                }
+            };
+        let ProposalOutput {
+            state_diff,
+            nonces: address_to_nonce,
+            tx_hashes,
+            rejected_tx_hashes,
+            l2_gas_used,
+            content,
+            ..
+        } = proposal_output;
+        let height = block_info.block_number;
         info!(
             "Committing proposal {} at height {} and notifying mempool of the block.",
             proposal_id, height
         );
         trace!("Transactions: {:#?}, State diff: {:#?}.", tx_hashes, state_diff);
-        self.storage_writer.commit_proposal(height, state_diff).map_err(|err| {
+        let l1_handler_tx_hashes =
+            l1_handler_tx_hashes(&content.body.transactions, &content.body.transaction_hashes);
+
+        let parent_hash = self.parent_block_hash(height)?;
+        let state_commitment = self.prepare_state_commitment(&state_diff).await?;
+        let block = DecidedBlock::new(
+            &block_info,
+            parent_hash,
+            state_commitment.global_root,
+            state_diff,
+            content,
+        )
+        .map_err(|err| {
+            error!("Failed to compute the block hash: {}", err);
+            BatcherError::InternalError
+        })?;
+        debug!("Block hash of height {}: {}.", height, block.header.block_hash);
+        self.storage_writer.commit_proposal(height, block, &state_commitment).map_err(|err| {
             error!("Failed to commit proposal to storage: {}", err);
             BatcherError::InternalError
         })?;
-        if let Err(mempool_err) =
-            self.mempool_client.commit_block(CommitBlockArgs { address_to_nonce, tx_hashes }).await
+        self.state_committer.apply(&state_commitment);
+        self.pending_block_broadcaster.publish(PendingBlockUpdate::Cleared);
+        self.l2_gas_price =
+            calculate_next_base_gas_price(self.l2_gas_price, l2_gas_used.0, GAS_TARGET);
+        let next_l2_gas_price = GasPrice(self.l2_gas_price.into());
+        if let Err(mempool_err) = self
+            .mempool_client
+            .commit_block(CommitBlockArgs {
+                address_to_nonce,
+                tx_hashes,
+                rejected_tx_hashes,
+                next_l2_gas_price,
+            })
+            .await
         {
             error!("Failed to commit block to mempool: {}", mempool_err);
             // TODO: Should we rollback the state diff and return an error?
         }
+        if let Err(err) = self.commit_l1_provider_block(l1_handler_tx_hashes).await {
+            error!("Failed to commit block to the L1 provider: {}", err);
+        }
+        Ok(())
+    }
+
+    /// Subscribes to the updates of the block being proposed, which are published after each
+    /// executed chunk of transactions.
+    pub fn subscribe_pending_block(&mut self) -> BatcherResult<SubscribePendingBlockResponse> {
+        let subscription_id = self.next_pending_block_subscription_id;
+        self.next_pending_block_subscription_id.0 += 1;
+        self.pending_block_subscriptions
+            .insert(subscription_id, self.pending_block_broadcaster.subscribe());
+        Ok(SubscribePendingBlockResponse { subscription_id })
+    }
+
+    pub fn get_pending_block_updates(
+        &mut self,
+        input: GetPendingBlockUpdatesInput,
+    ) -> BatcherResult<GetPendingBlockUpdatesResponse> {
+        let subscription_id = input.subscription_id;
+        let subscription = self
+            .pending_block_subscriptions
+            .get_mut(&subscription_id)
+            .ok_or(BatcherError::PendingBlockSubscriptionNotFound { subscription_id })?;
+        let Some(updates) = subscription.next_updates() else {
+            self.pending_block_subscriptions.remove(&subscription_id);
+            return Err(BatcherError::PendingBlockSubscriptionLagged { subscription_id });
+        };
+        Ok(GetPendingBlockUpdatesResponse { updates })
+    }
+
+    async fn prepare_state_commitment(
+        &self,
+        state_diff: &ThinStateDiff,
+    ) -> BatcherResult<StateCommitment> {
+        let storage_reader = self.storage_reader.as_ref();
+        self.state_committer.prepare(storage_reader, state_diff).await.map_err(|err| {
+            error!("Failed to compute the state root: {}", err);
+            BatcherError::InternalError
+        })
+    }
+
+    // Persists the metadata of a finished proposal, so it can be executed again if the batcher
+    // restarts before a decision is reached.
+    async fn store_proposal_metadata(&mut self, proposal_id: ProposalId) {
+        let (Some(height), Some(metadata)) =
+            (self.active_height, self.proposals_metadata.get(&proposal_id))
+        else {
+            return;
+        };
+        if let Err(err) = self.proposal_metadata_storage.store(height, proposal_id, metadata).await
+        {
+            error!("Failed to store the metadata of proposal {}: {}", proposal_id, err);
+        }
+    }
+
+    // Moves the L1 provider into a proposal or a validation. One that is already in progress, e.g.,
+    // of an earlier round of the height, is abandoned first, returning its transactions to the
+    // provider.
+    async fn start_l1_provider_block(&mut self, kind: L1ProviderBlockKind) -> BatcherResult<()> {
+        if self.l1_provider_in_block {
+            self.commit_l1_provider_block(Vec::new()).await.map_err(|err| {
+                error!("Failed to abandon the L1 provider's previous block: {}", err);
+                BatcherError::InternalError
+            })?;
+        }
+        let result = match kind {
+            L1ProviderBlockKind::Propose => self.l1_provider_client.proposal_start().await,
+            L1ProviderBlockKind::Validate => self.l1_provider_client.validation_start().await,
+        };
+        result.map_err(|err| {
+            error!("Failed to start the L1 provider's {:?} block: {}", kind, err);
+            BatcherError::InternalError
+        })?;
+        self.l1_provider_in_block = true;
         Ok(())
     }
+
+    // Ends the L1 provider's proposal or validation, marking the given L1 handler transactions as
+    // included on L2.
+    async fn commit_l1_provider_block(
+        &mut self,
+        committed_txs: Vec<TransactionHash>,
+    ) -> L1ProviderClientResult<()> {
+        self.l1_provider_client.commit_block(committed_txs).await?;
+        self.l1_provider_in_block = false;
+        Ok(())
+    }
+
+    // Executes a proposal that finished before the batcher restarted, using its stored metadata.
+    async fn reexecute_proposal(
+        &mut self,
+        proposal_id: ProposalId,
+        metadata: &ProposalMetadata,
+    ) -> BatcherResult<ProposalOutput> {
+        info!("Re-executing proposal {} from its stored metadata.", proposal_id);
+        self.start_l1_provider_block(L1ProviderBlockKind::Validate).await?;
+        // The whole content is known, so the stream is filled and closed upfront.
+        let (input_tx_sender, input_tx_receiver) =
+            tokio::sync::mpsc::channel(metadata.txs.len().max(1));
+        for tx in metadata.txs.iter().cloned() {
+            input_tx_sender.try_send(tx).expect("The channel should fit all the transactions.");
+        }
+        drop(input_tx_sender);
+
+        let tx_provider = ValidateTransactionProvider {
+            tx_receiver: input_tx_receiver,
+            l1_provider_client: self.l1_provider_client.clone(),
+        };
+        let (block_builder, abort_signal_sender) = self
+            .block_builder_factory
+            .create_block_builder(
+                BlockMetadata {
+                    block_info: metadata.block_info.clone(),
+                    retrospective_block_hash: metadata.retrospective_block_hash,
+                },
+                BlockBuilderExecutionParams {
+                    deadline: tokio::time::Instant::now() + PROPOSAL_REEXECUTION_TIMEOUT,
+                    fail_on_err: true,
+                },
+                Box::new(tx_provider),
+                None,
+            )
+            .map_err(|_| BatcherError::InternalError)?;
+        self.proposal_manager
+            .spawn_proposal(proposal_id, block_builder, abort_signal_sender)
+            .await?;
+        self.proposal_manager.await_active_proposal().await;
+
+        self.proposal_manager
+            .take_proposal_result(proposal_id)
+            .await
+            .ok_or(BatcherError::ExecutedProposalNotFound { proposal_id })?
+            .map_err(BatcherError::from)
+    }
+
+    // Brings the state tries up to the stored blocks by committing their state diffs, when starting
+    // from a storage whose blocks were stored without their tries.
+    async fn rebuild_state_committer(&mut self, storage_height: BlockNumber) -> BatcherResult<()> {
+        let committer_height = self.state_committer.next_height();
+        if committer_height == storage_height {
+            return Ok(());
+        }
+
+        info!(
+            "Rebuilding the state tries from the state diffs of heights {} up to {}.",
+            committer_height, storage_height
+        );
+        for height in committer_height.iter_up_to(storage_height) {
+            let state_diff = self
+                .storage_reader
+                .state_diff(height)
+                .map_err(|err| {
+                    error!("Failed to get the state diff of height {}: {}", height, err);
+                    BatcherError::InternalError
+                })?
+                .ok_or_else(|| {
+                    error!("Missing the state diff of height {}.", height);
+                    BatcherError::InternalError
+                })?;
+            let state_commitment = self.prepare_state_commitment(&state_diff).await?;
+            self.storage_writer.commit_state_tries(height, &state_commitment).map_err(|err| {
+                error!("Failed to commit the state tries of height {} to storage: {}", height, err);
+                BatcherError::InternalError
+            })?;
+            self.state_committer.apply(&state_commitment);
+        }
+        Ok(())
+    }
+
+    // Catches up with the state sync, committing the blocks that the storage is missing.
+    async fn sync_storage(
+        &mut self,
+        storage_height: BlockNumber,
+        requested_height: BlockNumber,
+    ) -> BatcherResult<()> {
+        info!(
+            "Storage is at height {}, syncing up to height {}.",
+            storage_height, requested_height
+        );
+        for height in storage_height.iter_up_to(requested_height) {
+            let sync_block = match self.state_sync_client.get_block(height).await {
+                Ok(Some(sync_block)) => sync_block,
+                Ok(None) => {
+                    warn!("Block {} is not available in the state sync yet.", height);
+                    return Err(BatcherError::StorageNotSynced {
+                        storage_height: height,
+                        requested_height,
+                    });
+                }
+                Err(err) => {
+                    error!("Failed to get block {} from the state sync: {}", height, err);
+                    return Err(BatcherError::StorageNotSynced {
+                        storage_height: height,
+                        requested_height,
+                    });
+                }
+            };
+            self.commit_sync_block(height, sync_block).await?;
+        }
+        Ok(())
+    }
+
+    async fn commit_sync_block(
+        &mut self,
+        height: BlockNumber,
+        sync_block: SyncBlock,
+    ) -> BatcherResult<()> {
+        let SyncBlock {
+            block_header,
+            state_diff,
+            transactions,
+            transaction_outputs,
+            transaction_hashes,
+        } = sync_block;
+        debug!("Committing synced block {}.", height);
+        // The base price of the next block follows from the synced block's price and usage, as if
+        // the block was decided here. Prices are kept in a u64, so a header with a larger price
+        // is rejected.
+        let synced_l2_gas_price =
+            block_header.block_header_without_hash.l2_gas_price.price_in_fri.0;
+        let synced_l2_gas_price = u64::try_from(synced_l2_gas_price).map_err(|_| {
+            error!(
+                "L2 gas price {} of synced block {} exceeds the maximal price.",
+                synced_l2_gas_price, height
+            );
+            BatcherError::InternalError
+        })?;
+        // Keep the tries up to date for the blocks decided after the synced ones.
+        let state_commitment = self.prepare_state_commitment(&state_diff).await?;
+        let address_to_nonce =
+            state_diff.nonces.iter().map(|(address, nonce)| (*address, *nonce)).collect();
+        let l2_gas_used = l2_gas_consumed(&transaction_outputs);
+        let l1_handler_tx_hashes = l1_handler_tx_hashes(&transactions, &transaction_hashes);
+        let block_body = BlockBody {
+            transactions,
+            transaction_outputs,
+            transaction_hashes: transaction_hashes.clone(),
+        };
+        self.storage_writer
+            .commit_sync_block(height, block_header, block_body, state_diff, &state_commitment)
+            .map_err(|err| {
+                error!("Failed to commit synced block to storage: {}", err);
+                BatcherError::InternalError
+            })?;
+        self.state_committer.apply(&state_commitment);
+        self.pending_block_broadcaster.publish(PendingBlockUpdate::Cleared);
+        self.l2_gas_price = next_l2_gas_price(synced_l2_gas_price, l2_gas_used);
+        let next_l2_gas_price = GasPrice(self.l2_gas_price.into());
+        if let Err(mempool_err) = self
+            .mempool_client
+            .commit_block(CommitBlockArgs {
+                address_to_nonce,
+                tx_hashes: transaction_hashes.into_iter().collect(),
+                rejected_tx_hashes: HashMap::new(),
+                next_l2_gas_price,
+            })
+            .await
+        {
+            error!("Failed to commit synced block to mempool: {}", mempool_err);
+        }
+        // The synced block's L1 handler transactions must not be proposed again.
+        if let Err(err) = self.commit_l1_provider_block(l1_handler_tx_hashes).await {
+            error!("Failed to commit synced block to the L1 provider: {}", err);
+        }
+        Ok(())
+    }
+
+    fn parent_block_hash(&self, height: BlockNumber) -> BatcherResult<BlockHash> {
+        let Some(parent_height) = height.prev() else {
+            // The parent hash of the genesis block.
+            return Ok(BlockHash::default());
+        };
+        self.storage_reader
+            .block_hash(parent_height)
+            .map_err(|err| {
+                error!("Failed to get the block hash of height {}: {}", parent_height, err);
+                BatcherError::InternalError
+            })?
+            .ok_or_else(|| {
+                error!("Missing the header of height {}.", parent_height);
+                BatcherError::InternalError
+            })
+    }
 }
 
-pub fn create_batcher(config: BatcherConfig, mempool_client: SharedMempoolClient) -> Batcher {
+pub fn create_batcher(
+    config: BatcherConfig,
+    mempool_client: SharedMempoolClient,
+    l1_provider_client: SharedL1ProviderClient,
+    state_sync_client: SharedStateSyncClient,
+) -> Batcher {
     let (storage_reader, storage_writer) = papyrus_storage::open_storage(config.storage.clone())
         .expect("Failed to open batcher's storage");
 
+    let pending_block_broadcaster = PendingBlockBroadcaster::default();
     let block_builder_factory = Box::new(BlockBuilderFactory {
         block_builder_config: config.block_builder_config.clone(),
         storage_reader: storage_reader.clone(),
         global_class_hash_to_class: GlobalContractCache::new(config.global_contract_cache_size),
+        pending_block_broadcaster: pending_block_broadcaster.clone(),
     });
     let storage_reader = Arc::new(storage_reader);
     let storage_writer = Box::new(storage_writer);
     let proposal_manager = Box::new(ProposalManager::new());
+    // Kept next to the storage, so it survives restarts along with it.
+    let proposal_metadata_storage = Box::new(FileProposalMetadataStorage {
+        dir: config.storage.db_config.path_prefix.join(PROPOSAL_METADATA_DIR_NAME),
+    });
     Batcher::new(
         config,
         storage_reader,
         storage_writer,
         mempool_client,
+        l1_provider_client,
+        state_sync_client,
         block_builder_factory,
         proposal_manager,
+        proposal_metadata_storage,
+        pending_block_broadcaster,
     )
 }
 
+/// The base price of the block following a block of the given L2 gas price and usage, for blocks
+/// that were not decided by this batcher.
+fn next_l2_gas_price(price: u64, l2_gas_used: u64) -> u64 {
+    calculate_next_base_gas_price(
+        max(price, MIN_GAS_PRICE),
+        min(l2_gas_used, MAX_BLOCK_SIZE),
+        GAS_TARGET,
+    )
+}
+
+fn l2_gas_consumed(transaction_outputs: &[TransactionOutput]) -> u64 {
+    transaction_outputs
+        .iter()
+        .map(|output| output.execution_resources().gas_consumed.l2_gas.0)
+        .fold(0, u64::saturating_add)
+}
+
+fn l1_handler_tx_hashes(
+    transactions: &[starknet_api::transaction::Transaction],
+    transaction_hashes: &[TransactionHash],
+) -> Vec<TransactionHash> {
+    transactions
+        .iter()
+        .zip(transaction_hashes)
+        .filter(|(tx, _)| matches!(tx, starknet_api::transaction::Transaction::L1Handler(_)))
+        .map(|(_, tx_hash)| *tx_hash)
+        .collect()
+}
+
 #[cfg_attr(test, automock)]
 pub trait BatcherStorageReaderTrait: Send + Sync {
     /// Returns the next height that the batcher should work on.
     fn height(&self) -> papyrus_storage::StorageResult<BlockNumber>;
+
+    /// Returns the hash of the block at the given height, if its header is stored.
+    fn block_hash(&self, height: BlockNumber) -> papyrus_storage::StorageResult<Option<BlockHash>>;
+
+    /// Returns the L2 gas price (in fri) and the L2 gas usage of the last stored block, if any.
+    fn last_l2_gas_usage(&self) -> papyrus_storage::StorageResult<Option<L2GasUsage>>;
+
+    /// Returns the state diff of the block at the given height, if it is stored.
+    fn state_diff(
+        &self,
+        height: BlockNumber,
+    ) -> papyrus_storage::StorageResult<Option<ThinStateDiff>>;
+
+    /// Returns the height of the first block whose state tries are not stored.
+    fn state_tries_height(&self) -> papyrus_storage::StorageResult<BlockNumber>;
+
+    /// Returns the roots of the state tries after the block at the given height, if they are
+    /// stored.
+    fn state_tries_roots(
+        &self,
+        height: BlockNumber,
+    ) -> papyrus_storage::StorageResult<Option<StateTriesRoots>>;
+
+    /// Returns the values of the facts of the state tries with the given keys, in the same order.
+    fn state_tries_facts(
+        &self,
+        keys: &[Vec<u8>],
+    ) -> papyrus_storage::StorageResult<Vec<Option<Vec<u8>>>>;
 }
 
 impl BatcherStorageReaderTrait for papyrus_storage::StorageReader {
     fn height(&self) -> papyrus_storage::StorageResult<BlockNumber> {
         self.begin_ro_txn()?.get_state_marker()
     }
+
+    fn block_hash(&self, height: BlockNumber) -> papyrus_storage::StorageResult<Option<BlockHash>> {
+        Ok(self.begin_ro_txn()?.get_block_header(height)?.map(|header| header.block_hash))
+    }
+
+    fn last_l2_gas_usage(&self) -> papyrus_storage::StorageResult<Option<L2GasUsage>> {
+        let txn = self.begin_ro_txn()?;
+        let Some(last_height) = txn.get_body_marker()?.prev() else {
+            return Ok(None);
+        };
+        let Some(header) = txn.get_block_header(last_height)? else {
+            return Ok(None);
+        };
+        let Some(transaction_outputs) = txn.get_block_transaction_outputs(last_height)? else {
+            return Ok(None);
+        };
+        let price = header.block_header_without_hash.l2_gas_price.price_in_fri.0;
+        Ok(Some(L2GasUsage {
+            price: u64::try_from(price).unwrap_or(u64::MAX),
+            gas_used: l2_gas_consumed(&transaction_outputs),
+        }))
+    }
+
+    fn state_diff(
+        &self,
+        height: BlockNumber,
+    ) -> papyrus_storage::StorageResult<Option<ThinStateDiff>> {
+        self.begin_ro_txn()?.get_state_diff(height)
+    }
+
+    fn state_tries_height(&self) -> papyrus_storage::StorageResult<BlockNumber> {
+        self.begin_ro_txn()?.get_state_tries_marker()
+    }
+
+    fn state_tries_roots(
+        &self,
+        height: BlockNumber,
+    ) -> papyrus_storage::StorageResult<Option<StateTriesRoots>> {
+        self.begin_ro_txn()?.get_state_tries_roots(height)
+    }
+
+    fn state_tries_facts(
+        &self,
+        keys: &[Vec<u8>],
+    ) -> papyrus_storage::StorageResult<Vec<Option<Vec<u8>>>> {
+        self.begin_ro_txn()?.get_state_tries_facts(keys)
+    }
 }
 
 #[cfg_attr(test, automock)]
 pub trait BatcherStorageWriterTrait: Send + Sync {
+    /// Commits a decided block along with its state tries.
     fn commit_proposal(
         &mut self,
         height: BlockNumber,
+        block: DecidedBlock,
+        state_commitment: &StateCommitment,
+    ) -> papyrus_storage::StorageResult<()>;
+
+    /// Commits a block that was synced from other nodes instead of being decided by the batcher,
+    /// along with its state tries.
+    fn commit_sync_block(
+        &mut self,
+        height: BlockNumber,
+        block_header: BlockHeader,
+        block_body: BlockBody,
         state_diff: ThinStateDiff,
+        state_commitment: &StateCommitment,
+    ) -> papyrus_storage::StorageResult<()>;
+
+    /// Commits the state tries of a block that is already stored.
+    fn commit_state_tries(
+        &mut self,
+        height: BlockNumber,
+        state_commitment: &StateCommitment,
     ) -> papyrus_storage::StorageResult<()>;
 }
 
@@ -428,10 +1082,48 @@ impl BatcherStorageWriterTrait for papyrus_storage::StorageWriter {
     fn commit_proposal(
         &mut self,
         height: BlockNumber,
+        block: DecidedBlock,
+        state_commitment: &StateCommitment,
+    ) -> papyrus_storage::StorageResult<()> {
+        let DecidedBlock { header, body, state_diff, casms } = block;
+        // TODO: Write the Sierra classes, once they are passed along with the declare
+        // transactions.
+        let mut txn = self
+            .begin_rw_txn()?
+            .append_header(height, &header)?
+            .append_body(height, body)?
+            .append_state_diff(height, state_diff)?
+            .append_state_tries(height, &state_commitment.roots, &state_commitment.new_facts)?;
+        for (class_hash, casm) in &casms {
+            txn = txn.append_casm(class_hash, casm)?;
+        }
+        txn.commit()
+    }
+
+    fn commit_sync_block(
+        &mut self,
+        height: BlockNumber,
+        block_header: BlockHeader,
+        block_body: BlockBody,
         state_diff: ThinStateDiff,
+        state_commitment: &StateCommitment,
+    ) -> papyrus_storage::StorageResult<()> {
+        self.begin_rw_txn()?
+            .append_header(height, &block_header)?
+            .append_body(height, block_body)?
+            .append_state_diff(height, state_diff)?
+            .append_state_tries(height, &state_commitment.roots, &state_commitment.new_facts)?
+            .commit()
+    }
+
+    fn commit_state_tries(
+        &mut self,
+        height: BlockNumber,
+        state_commitment: &StateCommitment,
     ) -> papyrus_storage::StorageResult<()> {
-        // TODO: write casms.
-        self.begin_rw_txn()?.append_state_diff(height, state_diff)?.commit()
+        self.begin_rw_txn()?
+            .append_state_tries(height, &state_commitment.roots, &state_commitment.new_facts)?
+            .commit()
     }
 }
 
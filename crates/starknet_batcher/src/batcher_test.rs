@@ -5,27 +5,43 @@ use assert_matches::assert_matches;
 use async_trait::async_trait;
 use blockifier::abi::constants;
 use blockifier::test_utils::struct_impls::BlockInfoExt;
+use blockifier::transaction::objects::TransactionExecutionInfo;
 use chrono::Utc;
 use futures::future::BoxFuture;
 use futures::FutureExt;
-use mockall::automock;
+use indexmap::IndexMap;
 use mockall::predicate::{always, eq};
+use mockall::{automock, Sequence};
+use papyrus_storage::body::BodyStorageReader;
+use papyrus_storage::state_tries::{StateTriesRoots, StateTriesStorageReader};
+use papyrus_storage::test_utils::get_test_storage_by_scope;
+use papyrus_storage::{StorageReader, StorageScope, StorageWriter};
 use rstest::rstest;
-use starknet_api::block::{BlockInfo, BlockNumber};
+use starknet_api::block::{BlockBody, BlockHash, BlockHeader, BlockInfo, BlockNumber, GasPrice};
 use starknet_api::core::{ContractAddress, Nonce, StateDiffCommitment};
 use starknet_api::executable_transaction::Transaction;
+use starknet_api::execution_resources::{ExecutionResources, GasAmount, GasVector};
 use starknet_api::hash::PoseidonHash;
 use starknet_api::state::ThinStateDiff;
-use starknet_api::transaction::TransactionHash;
+use starknet_api::transaction::{InvokeTransactionOutput, InvokeTransactionV1, TransactionHash};
 use starknet_api::{contract_address, felt, nonce, tx_hash};
 use starknet_batcher_types::batcher_types::{
+    AbortProposalInput,
     DecisionReachedInput,
     GetHeightResponse,
+    GetPendingBlockUpdatesInput,
+    GetPendingBlockUpdatesResponse,
     GetProposalContent,
     GetProposalContentInput,
     GetProposalContentResponse,
+    GetProposalStatusInput,
+    GetProposalStatusResponse,
+    PendingBlockSubscriptionId,
+    PendingBlockUpdate,
+    PendingTxs,
     ProposalCommitment,
     ProposalId,
+    ProposalState,
     ProposalStatus,
     ProposeBlockInput,
     SendProposalContent,
@@ -35,8 +51,11 @@ use starknet_batcher_types::batcher_types::{
     ValidateBlockInput,
 };
 use starknet_batcher_types::errors::BatcherError;
+use starknet_l1_provider_types::MockL1ProviderClient;
 use starknet_mempool_types::communication::MockMempoolClient;
 use starknet_mempool_types::mempool_types::CommitBlockArgs;
+use starknet_state_sync_types::communication::MockStateSyncClient;
+use starknet_state_sync_types::state_sync_types::SyncBlock;
 
 use crate::batcher::{Batcher, MockBatcherStorageReaderTrait, MockBatcherStorageWriterTrait};
 use crate::block_builder::{
@@ -48,6 +67,9 @@ use crate::block_builder::{
     MockBlockBuilderTrait,
 };
 use crate::config::BatcherConfig;
+use crate::decided_block::BlockContent;
+use crate::fee_market::{L2GasUsage, GAS_TARGET};
+use crate::pending_block::PendingBlockBroadcaster;
 use crate::proposal_manager::{
     GenerateProposalError,
     InternalProposalStatus,
@@ -56,6 +78,7 @@ use crate::proposal_manager::{
     ProposalOutput,
     ProposalResult,
 };
+use crate::proposal_metadata::{MockProposalMetadataStorageTrait, ProposalMetadata};
 use crate::test_utils::test_txs;
 use crate::transaction_provider::NextTxs;
 
@@ -100,32 +123,83 @@ struct MockDependencies {
     storage_reader: MockBatcherStorageReaderTrait,
     storage_writer: MockBatcherStorageWriterTrait,
     mempool_client: MockMempoolClient,
+    l1_provider_client: MockL1ProviderClient,
+    state_sync_client: MockStateSyncClient,
     proposal_manager: MockProposalManagerTraitWrapper,
     block_builder_factory: MockBlockBuilderFactoryTrait,
+    proposal_metadata_storage: MockProposalMetadataStorageTrait,
+    pending_block_broadcaster: PendingBlockBroadcaster,
 }
 
 impl Default for MockDependencies {
     fn default() -> Self {
         let mut storage_reader = MockBatcherStorageReaderTrait::new();
         storage_reader.expect_height().returning(|| Ok(INITIAL_HEIGHT));
+        storage_reader.expect_last_l2_gas_usage().returning(|| Ok(None));
+        expect_stored_state_tries(&mut storage_reader, INITIAL_HEIGHT);
+        let mut state_sync_client = MockStateSyncClient::new();
+        state_sync_client.expect_get_block().returning(|_| Ok(None));
+        let mut proposal_metadata_storage = MockProposalMetadataStorageTrait::new();
+        proposal_metadata_storage.expect_load().returning(|_| Ok(HashMap::new()));
+        proposal_metadata_storage.expect_store().returning(|_, _, _| Ok(()));
+        let mut l1_provider_client = MockL1ProviderClient::new();
+        l1_provider_client.expect_proposal_start().returning(|| Ok(()));
+        l1_provider_client.expect_validation_start().returning(|| Ok(()));
+        l1_provider_client.expect_commit_block().returning(|_| Ok(()));
         Self {
             storage_reader,
             storage_writer: MockBatcherStorageWriterTrait::new(),
             mempool_client: MockMempoolClient::new(),
+            l1_provider_client,
+            state_sync_client,
             proposal_manager: MockProposalManagerTraitWrapper::new(),
             block_builder_factory: MockBlockBuilderFactoryTrait::new(),
+            proposal_metadata_storage,
+            pending_block_broadcaster: PendingBlockBroadcaster::default(),
         }
     }
 }
 
+// The state tries of the blocks below the given height are stored.
+fn expect_stored_state_tries(
+    storage_reader: &mut MockBatcherStorageReaderTrait,
+    height: BlockNumber,
+) {
+    storage_reader.expect_state_tries_height().returning(move || Ok(height));
+    storage_reader.expect_state_tries_roots().returning(|_| Ok(Some(StateTriesRoots::default())));
+}
+
 fn create_batcher(mock_dependencies: MockDependencies) -> Batcher {
     Batcher::new(
         BatcherConfig { outstream_content_buffer_size: STREAMING_CHUNK_SIZE, ..Default::default() },
         Arc::new(mock_dependencies.storage_reader),
         Box::new(mock_dependencies.storage_writer),
         Arc::new(mock_dependencies.mempool_client),
+        Arc::new(mock_dependencies.l1_provider_client),
+        Arc::new(mock_dependencies.state_sync_client),
+        Box::new(mock_dependencies.block_builder_factory),
+        Box::new(mock_dependencies.proposal_manager),
+        Box::new(mock_dependencies.proposal_metadata_storage),
+        mock_dependencies.pending_block_broadcaster,
+    )
+}
+
+// Creates a batcher that commits the decided blocks to the given storage instead of a mock.
+fn create_batcher_with_storage(
+    mock_dependencies: MockDependencies,
+    (storage_reader, storage_writer): (StorageReader, StorageWriter),
+) -> Batcher {
+    Batcher::new(
+        BatcherConfig { outstream_content_buffer_size: STREAMING_CHUNK_SIZE, ..Default::default() },
+        Arc::new(storage_reader),
+        Box::new(storage_writer),
+        Arc::new(mock_dependencies.mempool_client),
+        Arc::new(mock_dependencies.l1_provider_client),
+        Arc::new(mock_dependencies.state_sync_client),
         Box::new(mock_dependencies.block_builder_factory),
         Box::new(mock_dependencies.proposal_manager),
+        Box::new(mock_dependencies.proposal_metadata_storage),
+        mock_dependencies.pending_block_broadcaster,
     )
 }
 
@@ -204,6 +278,105 @@ async fn start_height_success() {
     assert_eq!(batcher.start_height(StartHeightInput { height: INITIAL_HEIGHT }).await, Ok(()));
 }
 
+#[rstest]
+#[tokio::test]
+async fn start_height_rebuilds_state_committer() {
+    let mut storage_reader = MockBatcherStorageReaderTrait::new();
+    storage_reader.expect_height().returning(|| Ok(INITIAL_HEIGHT));
+    storage_reader.expect_last_l2_gas_usage().returning(|| Ok(None));
+    // The blocks were stored without their tries.
+    expect_stored_state_tries(&mut storage_reader, BlockNumber(0));
+    // The state diff of each stored block is committed once, and its tries are stored.
+    let mut storage_writer = MockBatcherStorageWriterTrait::new();
+    for height in BlockNumber(0).iter_up_to(INITIAL_HEIGHT) {
+        storage_reader
+            .expect_state_diff()
+            .times(1)
+            .with(eq(height))
+            .returning(|_| Ok(Some(ThinStateDiff::default())));
+        storage_writer
+            .expect_commit_state_tries()
+            .times(1)
+            .withf(move |tries_height, _| *tries_height == height)
+            .returning(|_, _| Ok(()));
+    }
+    let mut proposal_manager = MockProposalManagerTraitWrapper::new();
+    proposal_manager.expect_wrap_reset().times(1).return_once(|| async {}.boxed());
+
+    let mut batcher = create_batcher(MockDependencies {
+        storage_reader,
+        storage_writer,
+        proposal_manager,
+        ..Default::default()
+    });
+    batcher.start_height(StartHeightInput { height: INITIAL_HEIGHT }).await.unwrap();
+}
+
+#[rstest]
+#[tokio::test]
+async fn start_height_missing_stored_state_diff() {
+    let mut storage_reader = MockBatcherStorageReaderTrait::new();
+    storage_reader.expect_height().returning(|| Ok(INITIAL_HEIGHT));
+    storage_reader.expect_last_l2_gas_usage().returning(|| Ok(None));
+    expect_stored_state_tries(&mut storage_reader, BlockNumber(0));
+    storage_reader.expect_state_diff().returning(|_| Ok(None));
+
+    let mut batcher = create_batcher(MockDependencies { storage_reader, ..Default::default() });
+    assert_eq!(
+        batcher.start_height(StartHeightInput { height: INITIAL_HEIGHT }).await,
+        Err(BatcherError::InternalError)
+    );
+}
+
+#[rstest]
+#[tokio::test]
+async fn pending_block_subscription() {
+    // Setup.
+    let mut proposal_manager = MockProposalManagerTraitWrapper::new();
+    proposal_manager.expect_wrap_reset().times(1).return_once(|| async {}.boxed());
+    let pending_block_broadcaster = PendingBlockBroadcaster::default();
+    let mut batcher = create_batcher(MockDependencies {
+        proposal_manager,
+        pending_block_broadcaster: pending_block_broadcaster.clone(),
+        ..Default::default()
+    });
+    let new_block = PendingBlockUpdate::NewBlock(initial_block_info());
+    let txs = PendingBlockUpdate::Txs(PendingTxs::default());
+    pending_block_broadcaster.publish(new_block.clone());
+
+    // Test.
+    let subscription_id = batcher.subscribe_pending_block().unwrap().subscription_id;
+    let mut get_updates = || {
+        batcher
+            .get_pending_block_updates(GetPendingBlockUpdatesInput { subscription_id })
+            .unwrap()
+            .updates
+    };
+    // The subscriber catches up on the pending block it subscribed in the middle of.
+    pending_block_broadcaster.publish(txs.clone());
+    assert_eq!(get_updates(), [new_block, txs]);
+    assert_eq!(get_updates(), []);
+
+    // Starting a new height clears the pending block.
+    batcher.start_height(StartHeightInput { height: INITIAL_HEIGHT }).await.unwrap();
+    assert_eq!(
+        batcher.get_pending_block_updates(GetPendingBlockUpdatesInput { subscription_id }),
+        Ok(GetPendingBlockUpdatesResponse { updates: vec![PendingBlockUpdate::Cleared] })
+    );
+}
+
+#[rstest]
+#[tokio::test]
+async fn pending_block_subscription_not_found() {
+    let mut batcher = create_batcher(MockDependencies::default());
+    let subscription_id = PendingBlockSubscriptionId(0);
+
+    assert_eq!(
+        batcher.get_pending_block_updates(GetPendingBlockUpdatesInput { subscription_id }),
+        Err(BatcherError::PendingBlockSubscriptionNotFound { subscription_id })
+    );
+}
+
 #[rstest]
 #[case::height_already_passed(
     INITIAL_HEIGHT.prev().unwrap(),
@@ -241,6 +414,294 @@ async fn duplicate_start_height() {
     assert_eq!(batcher.start_height(initial_height).await, Err(BatcherError::HeightInProgress));
 }
 
+#[rstest]
+#[tokio::test]
+async fn start_height_syncs_missing_blocks() {
+    // Setup.
+    let requested_height = INITIAL_HEIGHT.unchecked_next().unchecked_next();
+    let mut mock_dependencies =
+        MockDependencies { state_sync_client: MockStateSyncClient::new(), ..Default::default() };
+    mock_dependencies.state_sync_client.expect_get_block().times(2).returning(|height| {
+        let mut block_header = BlockHeader::default();
+        block_header.block_header_without_hash.l2_gas_price.price_in_fri = GasPrice(200000);
+        // A full block.
+        let transaction_output =
+            starknet_api::transaction::TransactionOutput::Invoke(InvokeTransactionOutput {
+                execution_resources: ExecutionResources {
+                    gas_consumed: GasVector {
+                        l2_gas: GasAmount(GAS_TARGET * 2),
+                        ..Default::default()
+                    },
+                    ..Default::default()
+                },
+                ..Default::default()
+            });
+        Ok(Some(SyncBlock {
+            block_header,
+            state_diff: ThinStateDiff::default(),
+            transactions: vec![starknet_api::transaction::Transaction::Invoke(
+                InvokeTransactionV1::default().into(),
+            )],
+            transaction_outputs: vec![transaction_output],
+            transaction_hashes: vec![tx_hash!(height.0)],
+        }))
+    });
+    // The synced transactions are stored.
+    mock_dependencies
+        .storage_writer
+        .expect_commit_sync_block()
+        .times(1)
+        .withf(|height, _, block_body, _, _| {
+            *height == INITIAL_HEIGHT && block_body.transactions.len() == 1
+        })
+        .returning(|_, _, _, _, _| Ok(()));
+    mock_dependencies
+        .storage_writer
+        .expect_commit_sync_block()
+        .times(1)
+        .withf(|height, _, block_body, _, _| {
+            *height == INITIAL_HEIGHT.unchecked_next() && block_body.transactions.len() == 1
+        })
+        .returning(|_, _, _, _, _| Ok(()));
+    // The mempool is notified of the transactions of the synced blocks, and of the L2 gas price
+    // following each of them: the synced price, increased by the maximal change rate of 1/48.
+    mock_dependencies
+        .mempool_client
+        .expect_commit_block()
+        .times(2)
+        .withf(|args| {
+            args.tx_hashes.len() == 1 && args.next_l2_gas_price == GasPrice(200000 + 4166)
+        })
+        .returning(|_| Ok(()));
+    mock_dependencies
+        .proposal_manager
+        .expect_wrap_reset()
+        .times(1)
+        .return_once(|| async {}.boxed());
+
+    let mut batcher = create_batcher(mock_dependencies);
+
+    // Test.
+    assert_eq!(batcher.start_height(StartHeightInput { height: requested_height }).await, Ok(()));
+}
+
+#[rstest]
+#[tokio::test]
+async fn start_height_stores_synced_block_body() {
+    // Setup.
+    let ((storage_reader, storage_writer), _temp_dir) =
+        get_test_storage_by_scope(StorageScope::FullArchive);
+    let block_body = BlockBody {
+        transactions: vec![starknet_api::transaction::Transaction::Invoke(
+            InvokeTransactionV1::default().into(),
+        )],
+        transaction_outputs: vec![starknet_api::transaction::TransactionOutput::Invoke(
+            InvokeTransactionOutput::default(),
+        )],
+        transaction_hashes: vec![tx_hash!(1)],
+    };
+    let mut mock_dependencies =
+        MockDependencies { state_sync_client: MockStateSyncClient::new(), ..Default::default() };
+    let synced_body = block_body.clone();
+    mock_dependencies.state_sync_client.expect_get_block().times(1).return_once(move |_| {
+        Ok(Some(SyncBlock {
+            block_header: BlockHeader::default(),
+            state_diff: ThinStateDiff::default(),
+            transactions: synced_body.transactions,
+            transaction_outputs: synced_body.transaction_outputs,
+            transaction_hashes: synced_body.transaction_hashes,
+        }))
+    });
+    mock_dependencies.mempool_client.expect_commit_block().times(1).returning(|_| Ok(()));
+    mock_dependencies
+        .proposal_manager
+        .expect_wrap_reset()
+        .times(1)
+        .return_once(|| async {}.boxed());
+
+    let mut batcher =
+        create_batcher_with_storage(mock_dependencies, (storage_reader.clone(), storage_writer));
+
+    // Test.
+    batcher.start_height(StartHeightInput { height: BlockNumber(1) }).await.unwrap();
+
+    // The synced transactions and their receipts can be served from the storage.
+    let txn = storage_reader.begin_ro_txn().unwrap();
+    let height = BlockNumber(0);
+    assert_eq!(txn.get_block_transactions(height).unwrap(), Some(block_body.transactions));
+    assert_eq!(
+        txn.get_block_transaction_outputs(height).unwrap(),
+        Some(block_body.transaction_outputs)
+    );
+    assert_eq!(
+        txn.get_block_transaction_hashes(height).unwrap(),
+        Some(block_body.transaction_hashes)
+    );
+}
+
+#[rstest]
+#[tokio::test]
+async fn start_height_commits_synced_l1_handler_txs_to_l1_provider() {
+    // Setup.
+    let mut mock_dependencies =
+        MockDependencies { state_sync_client: MockStateSyncClient::new(), ..Default::default() };
+    mock_dependencies.state_sync_client.expect_get_block().times(1).returning(|_| {
+        Ok(Some(SyncBlock {
+            block_header: BlockHeader::default(),
+            state_diff: ThinStateDiff::default(),
+            transactions: vec![
+                starknet_api::transaction::Transaction::L1Handler(Default::default()),
+                starknet_api::transaction::Transaction::Invoke(
+                    InvokeTransactionV1::default().into(),
+                ),
+            ],
+            transaction_outputs: vec![],
+            transaction_hashes: vec![tx_hash!(1), tx_hash!(2)],
+        }))
+    });
+    mock_dependencies
+        .storage_writer
+        .expect_commit_sync_block()
+        .times(1)
+        .returning(|_, _, _, _, _| Ok(()));
+    mock_dependencies.mempool_client.expect_commit_block().times(1).returning(|_| Ok(()));
+    // The synced L1 handler transactions are no longer served by the L1 provider.
+    let mut l1_provider_client = MockL1ProviderClient::new();
+    l1_provider_client
+        .expect_commit_block()
+        .times(1)
+        .with(eq(vec![tx_hash!(1)]))
+        .returning(|_| Ok(()));
+    mock_dependencies.l1_provider_client = l1_provider_client;
+    mock_dependencies
+        .proposal_manager
+        .expect_wrap_reset()
+        .times(1)
+        .return_once(|| async {}.boxed());
+
+    let mut batcher = create_batcher(mock_dependencies);
+
+    // Test.
+    assert_eq!(
+        batcher.start_height(StartHeightInput { height: INITIAL_HEIGHT.unchecked_next() }).await,
+        Ok(())
+    );
+}
+
+#[rstest]
+#[tokio::test]
+async fn start_height_rejects_synced_block_with_overflowing_l2_gas_price() {
+    let mut mock_dependencies =
+        MockDependencies { state_sync_client: MockStateSyncClient::new(), ..Default::default() };
+    mock_dependencies.state_sync_client.expect_get_block().times(1).returning(|_| {
+        let mut block_header = BlockHeader::default();
+        block_header.block_header_without_hash.l2_gas_price.price_in_fri =
+            GasPrice(u128::from(u64::MAX) + 1);
+        Ok(Some(SyncBlock {
+            block_header,
+            state_diff: ThinStateDiff::default(),
+            transactions: vec![],
+            transaction_outputs: vec![],
+            transaction_hashes: vec![],
+        }))
+    });
+    // The synced block isn't stored.
+    mock_dependencies.storage_writer.expect_commit_sync_block().never();
+
+    let mut batcher = create_batcher(mock_dependencies);
+
+    assert_eq!(
+        batcher.start_height(StartHeightInput { height: INITIAL_HEIGHT.unchecked_next() }).await,
+        Err(BatcherError::InternalError)
+    );
+}
+
+#[rstest]
+#[tokio::test]
+async fn decision_reached_on_proposal_restored_after_restart() {
+    // Setup.
+    let parent_hash = BlockHash(felt!("0x123"));
+    let restored_proposal = ProposalMetadata {
+        block_info: initial_block_info(),
+        retrospective_block_hash: None,
+        txs: test_txs(0..2),
+    };
+    let mut mock_dependencies = MockDependencies {
+        block_builder_factory: mock_create_builder_for_validate_block(),
+        ..Default::default()
+    };
+
+    mock_dependencies.proposal_metadata_storage = MockProposalMetadataStorageTrait::new();
+    mock_dependencies
+        .proposal_metadata_storage
+        .expect_load()
+        .times(1)
+        .with(eq(INITIAL_HEIGHT))
+        .return_once(move |_| Ok(HashMap::from([(PROPOSAL_ID, restored_proposal)])));
+
+    mock_dependencies
+        .proposal_manager
+        .expect_wrap_reset()
+        .times(1)
+        .return_once(|| async {}.boxed());
+    // The batcher doesn't know the proposal after the restart, so it executes it again.
+    mock_dependencies
+        .proposal_manager
+        .expect_wrap_take_proposal_result()
+        .times(1)
+        .with(eq(PROPOSAL_ID))
+        .return_once(|_| async move { None }.boxed());
+    mock_dependencies
+        .proposal_manager
+        .expect_wrap_spawn_proposal()
+        .times(1)
+        .with(eq(PROPOSAL_ID), always(), always())
+        .return_once(|_, _, _| { async move { Ok(()) } }.boxed());
+    mock_dependencies
+        .proposal_manager
+        .expect_wrap_await_active_proposal()
+        .times(1)
+        .return_once(|| async move { true }.boxed());
+    mock_dependencies
+        .proposal_manager
+        .expect_wrap_take_proposal_result()
+        .times(1)
+        .with(eq(PROPOSAL_ID))
+        .return_once(|_| {
+            async move {
+                Some(Ok(ProposalOutput {
+                    state_diff: ThinStateDiff::default(),
+                    commitment: ProposalCommitment::default(),
+                    tx_hashes: test_tx_hashes(),
+                    rejected_tx_hashes: HashMap::new(),
+                    nonces: test_contract_nonces(),
+                    l2_gas_used: GasAmount::default(),
+                    content: BlockContent::default(),
+                }))
+            }
+            .boxed()
+        });
+
+    mock_dependencies.mempool_client.expect_commit_block().times(1).returning(|_| Ok(()));
+    mock_dependencies
+        .storage_reader
+        .expect_block_hash()
+        .with(eq(INITIAL_HEIGHT.prev().unwrap()))
+        .returning(move |_| Ok(Some(parent_hash)));
+    mock_dependencies
+        .storage_writer
+        .expect_commit_proposal()
+        .times(1)
+        .withf(|height, _, _| *height == INITIAL_HEIGHT)
+        .returning(|_, _, _| Ok(()));
+
+    let mut batcher = create_batcher(mock_dependencies);
+    batcher.start_height(StartHeightInput { height: INITIAL_HEIGHT }).await.unwrap();
+
+    // Test.
+    batcher.decision_reached(DecisionReachedInput { proposal_id: PROPOSAL_ID }).await.unwrap();
+}
+
 #[rstest]
 #[tokio::test]
 async fn no_active_height() {
@@ -445,11 +906,60 @@ async fn propose_block_full_flow() {
     assert_matches!(exhausted, Err(BatcherError::ProposalNotFound { .. }));
 }
 
+#[rstest]
+#[tokio::test]
+async fn l1_provider_block_of_earlier_round_abandoned() {
+    let mut l1_provider_client = MockL1ProviderClient::new();
+    let mut seq = Sequence::new();
+    l1_provider_client
+        .expect_validation_start()
+        .times(1)
+        .in_sequence(&mut seq)
+        .returning(|| Ok(()));
+    // The validated transactions are returned to the provider before the next round starts.
+    l1_provider_client
+        .expect_commit_block()
+        .times(1)
+        .with(eq(vec![]))
+        .in_sequence(&mut seq)
+        .returning(|_| Ok(()));
+    l1_provider_client.expect_proposal_start().times(1).in_sequence(&mut seq).returning(|| Ok(()));
+
+    let mut block_builder_factory = MockBlockBuilderFactoryTrait::new();
+    block_builder_factory.expect_create_block_builder().times(2).returning(|_, _, _, _| {
+        Ok((Box::new(MockBlockBuilderTrait::new()), abort_signal_sender()))
+    });
+    let mut proposal_manager = MockProposalManagerTraitWrapper::new();
+    proposal_manager.expect_wrap_reset().times(1).return_once(|| async {}.boxed());
+    proposal_manager
+        .expect_wrap_spawn_proposal()
+        .times(2)
+        .returning(|_, _, _| { async move { Ok(()) } }.boxed());
+
+    let mut batcher = create_batcher(MockDependencies {
+        l1_provider_client,
+        block_builder_factory,
+        proposal_manager,
+        ..Default::default()
+    });
+    batcher.start_height(StartHeightInput { height: INITIAL_HEIGHT }).await.unwrap();
+    batcher.validate_block(validate_block_input()).await.unwrap();
+    batcher
+        .propose_block(ProposeBlockInput {
+            proposal_id: ProposalId(PROPOSAL_ID.0 + 1),
+            ..propose_block_input()
+        })
+        .await
+        .unwrap();
+}
+
 #[rstest]
 #[tokio::test]
 async fn get_height() {
     let mut storage_reader = MockBatcherStorageReaderTrait::new();
     storage_reader.expect_height().returning(|| Ok(INITIAL_HEIGHT));
+    storage_reader.expect_last_l2_gas_usage().returning(|| Ok(None));
+    expect_stored_state_tries(&mut storage_reader, INITIAL_HEIGHT);
 
     let mut batcher = create_batcher(MockDependencies { storage_reader, ..Default::default() });
 
@@ -467,6 +977,11 @@ async fn propose_block_without_retrospective_block_hash() {
     storage_reader
         .expect_height()
         .returning(|| Ok(BlockNumber(constants::STORED_BLOCK_HASH_BUFFER)));
+    storage_reader.expect_last_l2_gas_usage().returning(|| Ok(None));
+    expect_stored_state_tries(
+        &mut storage_reader,
+        BlockNumber(constants::STORED_BLOCK_HASH_BUFFER),
+    );
 
     let mut batcher =
         create_batcher(MockDependencies { proposal_manager, storage_reader, ..Default::default() });
@@ -495,8 +1010,199 @@ async fn get_content_from_unknown_proposal() {
 
 #[rstest]
 #[tokio::test]
-async fn decision_reached() {
-    let mut mock_dependencies = MockDependencies::default();
+async fn abort_proposal_in_progress() {
+    // Setup.
+    let block_builder_factory = mock_create_builder_for_propose_block(vec![]);
+    let mut proposal_manager = MockProposalManagerTraitWrapper::new();
+    proposal_manager.expect_wrap_reset().times(1).return_once(|| async {}.boxed());
+    proposal_manager
+        .expect_wrap_spawn_proposal()
+        .times(1)
+        .return_once(|_, _, _| { async move { Ok(()) } }.boxed());
+    proposal_manager
+        .expect_wrap_get_proposal_status()
+        .times(1)
+        .with(eq(PROPOSAL_ID))
+        .return_once(|_| async move { InternalProposalStatus::Processing }.boxed());
+    proposal_manager
+        .expect_wrap_abort_proposal()
+        .times(1)
+        .with(eq(PROPOSAL_ID))
+        .return_once(|_| async {}.boxed());
+
+    let mut batcher = create_batcher(MockDependencies {
+        proposal_manager,
+        block_builder_factory,
+        ..Default::default()
+    });
+    batcher.start_height(StartHeightInput { height: INITIAL_HEIGHT }).await.unwrap();
+    batcher.propose_block(propose_block_input()).await.unwrap();
+
+    // Test.
+    batcher.abort_proposal(AbortProposalInput { proposal_id: PROPOSAL_ID }).await.unwrap();
+
+    // The content of an aborted proposal is no longer streamed.
+    let result =
+        batcher.get_proposal_content(GetProposalContentInput { proposal_id: PROPOSAL_ID }).await;
+    assert_eq!(result, Err(BatcherError::ProposalNotFound { proposal_id: PROPOSAL_ID }));
+}
+
+#[rstest]
+#[case::finished(InternalProposalStatus::Finished)]
+#[case::failed(InternalProposalStatus::Failed)]
+#[tokio::test]
+async fn abort_done_proposal(#[case] status: InternalProposalStatus) {
+    let mut proposal_manager = MockProposalManagerTraitWrapper::new();
+    proposal_manager
+        .expect_wrap_get_proposal_status()
+        .times(1)
+        .with(eq(PROPOSAL_ID))
+        .return_once(move |_| async move { status }.boxed());
+    proposal_manager.expect_wrap_abort_proposal().never();
+
+    let mut batcher = create_batcher(MockDependencies { proposal_manager, ..Default::default() });
+    let result = batcher.abort_proposal(AbortProposalInput { proposal_id: PROPOSAL_ID }).await;
+    assert_eq!(result, Ok(()));
+}
+
+#[rstest]
+#[tokio::test]
+async fn abort_unknown_proposal() {
+    let mut proposal_manager = MockProposalManagerTraitWrapper::new();
+    proposal_manager
+        .expect_wrap_get_proposal_status()
+        .times(1)
+        .with(eq(PROPOSAL_ID))
+        .return_once(|_| async move { InternalProposalStatus::NotFound }.boxed());
+    proposal_manager.expect_wrap_abort_proposal().never();
+
+    let mut batcher = create_batcher(MockDependencies { proposal_manager, ..Default::default() });
+    let result = batcher.abort_proposal(AbortProposalInput { proposal_id: PROPOSAL_ID }).await;
+    assert_eq!(result, Err(BatcherError::ProposalNotFound { proposal_id: PROPOSAL_ID }));
+}
+
+#[rstest]
+#[case::finished(
+    InternalProposalStatus::Finished,
+    Ok(proposal_commitment()),
+    ProposalState::Finished(proposal_commitment())
+)]
+#[case::aborted(
+    InternalProposalStatus::Failed,
+    Err(ProposalError::Aborted),
+    ProposalState::Aborted
+)]
+#[case::failed(
+    InternalProposalStatus::Failed,
+    Err(ProposalError::BlockBuilderError(Arc::new(BlockBuilderError::FailOnError(
+        FailOnErrorCause::BlockFull
+    )))),
+    ProposalState::Failed { reason: "Block is full".to_string() }
+)]
+#[tokio::test]
+async fn get_status_of_done_proposal(
+    #[case] status: InternalProposalStatus,
+    #[case] result: ProposalResult<ProposalCommitment>,
+    #[case] expected_state: ProposalState,
+) {
+    let mut proposal_manager = MockProposalManagerTraitWrapper::new();
+    proposal_manager
+        .expect_wrap_get_proposal_status()
+        .times(1)
+        .with(eq(PROPOSAL_ID))
+        .return_once(move |_| async move { status }.boxed());
+    proposal_manager
+        .expect_wrap_await_proposal_commitment()
+        .times(1)
+        .with(eq(PROPOSAL_ID))
+        .return_once(move |_| async move { Some(result) }.boxed());
+
+    let mut batcher = create_batcher(MockDependencies { proposal_manager, ..Default::default() });
+    let response =
+        batcher.get_proposal_status(GetProposalStatusInput { proposal_id: PROPOSAL_ID }).await;
+    assert_eq!(response, Ok(GetProposalStatusResponse { state: expected_state }));
+}
+
+#[rstest]
+#[tokio::test]
+async fn get_status_of_proposal_in_progress() {
+    let mut proposal_manager = MockProposalManagerTraitWrapper::new();
+    proposal_manager
+        .expect_wrap_get_proposal_status()
+        .times(1)
+        .with(eq(PROPOSAL_ID))
+        .return_once(|_| async move { InternalProposalStatus::Processing }.boxed());
+    proposal_manager.expect_wrap_await_proposal_commitment().never();
+
+    let mut batcher = create_batcher(MockDependencies { proposal_manager, ..Default::default() });
+    let response =
+        batcher.get_proposal_status(GetProposalStatusInput { proposal_id: PROPOSAL_ID }).await;
+    assert_eq!(response, Ok(GetProposalStatusResponse { state: ProposalState::Building }));
+}
+
+#[rstest]
+// The minimal price, increased by the maximal change rate of 1/48.
+#[case::no_stored_block(None, 100000 + 2083)]
+// The price of the last stored block is kept for a block at the gas target, and then increased by
+// the maximal change rate of 1/48.
+#[case::stored_block_at_target(
+    Some(L2GasUsage { price: 200000, gas_used: GAS_TARGET }),
+    200000 + 4166
+)]
+// The price of the last stored block is increased twice by the maximal change rate of 1/48: once
+// for the stored full block, and once for the decided one.
+#[case::stored_full_block(
+    Some(L2GasUsage { price: 200000, gas_used: GAS_TARGET * 2 }),
+    200000 + 4166 + 4253
+)]
+#[tokio::test]
+async fn decision_reached(
+    #[case] stored_l2_gas_usage: Option<L2GasUsage>,
+    #[case] expected_next_l2_gas_price: u64,
+) {
+    let parent_hash = BlockHash(felt!("0x123"));
+    let mut storage_reader = MockBatcherStorageReaderTrait::new();
+    storage_reader.expect_height().returning(|| Ok(INITIAL_HEIGHT));
+    storage_reader.expect_last_l2_gas_usage().returning(move || Ok(stored_l2_gas_usage));
+    expect_stored_state_tries(&mut storage_reader, INITIAL_HEIGHT);
+    let mut l1_provider_client = MockL1ProviderClient::new();
+    l1_provider_client.expect_proposal_start().times(1).returning(|| Ok(()));
+    // Only the L1 handler transactions are committed to the L1 provider.
+    l1_provider_client
+        .expect_commit_block()
+        .times(1)
+        .with(eq(vec![tx_hash!(1)]))
+        .returning(|_| Ok(()));
+    let mut mock_dependencies = MockDependencies {
+        storage_reader,
+        l1_provider_client,
+        block_builder_factory: mock_create_builder_for_propose_block(vec![]),
+        ..Default::default()
+    };
+    let content = BlockContent {
+        body: BlockBody {
+            transactions: vec![
+                starknet_api::transaction::Transaction::L1Handler(Default::default()),
+                starknet_api::transaction::Transaction::Invoke(
+                    InvokeTransactionV1::default().into(),
+                ),
+            ],
+            transaction_outputs: Vec::new(),
+            transaction_hashes: vec![tx_hash!(1), tx_hash!(2)],
+        },
+        ..Default::default()
+    };
+
+    mock_dependencies
+        .proposal_manager
+        .expect_wrap_reset()
+        .times(1)
+        .return_once(|| async {}.boxed());
+    mock_dependencies
+        .proposal_manager
+        .expect_wrap_spawn_proposal()
+        .times(1)
+        .return_once(|_, _, _| { async move { Ok(()) } }.boxed());
 
     mock_dependencies
         .proposal_manager
@@ -509,7 +1215,11 @@ async fn decision_reached() {
                     state_diff: ThinStateDiff::default(),
                     commitment: ProposalCommitment::default(),
                     tx_hashes: test_tx_hashes(),
+                    rejected_tx_hashes: test_rejected_tx_hashes(),
                     nonces: test_contract_nonces(),
+                    // A full block.
+                    l2_gas_used: GasAmount(GAS_TARGET * 2),
+                    content,
                 }))
             }
             .boxed()
@@ -521,20 +1231,112 @@ async fn decision_reached() {
         .with(eq(CommitBlockArgs {
             address_to_nonce: test_contract_nonces(),
             tx_hashes: test_tx_hashes(),
+            rejected_tx_hashes: test_rejected_tx_hashes(),
+            next_l2_gas_price: GasPrice(expected_next_l2_gas_price.into()),
         }))
         .returning(|_| Ok(()));
 
+    mock_dependencies
+        .storage_reader
+        .expect_block_hash()
+        .with(eq(INITIAL_HEIGHT.prev().unwrap()))
+        .returning(move |_| Ok(Some(parent_hash)));
+
     mock_dependencies
         .storage_writer
         .expect_commit_proposal()
-        .with(eq(INITIAL_HEIGHT), eq(ThinStateDiff::default()))
-        .returning(|_, _| Ok(()));
+        .withf(move |height, block, _| {
+            *height == INITIAL_HEIGHT
+                && block.header.block_header_without_hash.block_number == INITIAL_HEIGHT
+                && block.header.block_header_without_hash.parent_hash == parent_hash
+                && block.state_diff == ThinStateDiff::default()
+        })
+        .returning(|_, _, _| Ok(()));
 
     let mut batcher = create_batcher(mock_dependencies);
 
+    batcher.start_height(StartHeightInput { height: INITIAL_HEIGHT }).await.unwrap();
+    batcher.propose_block(propose_block_input()).await.unwrap();
     batcher.decision_reached(DecisionReachedInput { proposal_id: PROPOSAL_ID }).await.unwrap();
 }
 
+#[rstest]
+#[tokio::test]
+async fn decision_reached_stores_block_body() {
+    // Setup.
+    let ((storage_reader, storage_writer), _temp_dir) =
+        get_test_storage_by_scope(StorageScope::FullArchive);
+    let txs = test_txs(0..2);
+    let execution_infos: IndexMap<_, _> =
+        txs.iter().map(|tx| (tx.tx_hash(), TransactionExecutionInfo::default())).collect();
+    let content = BlockContent::new(txs, &execution_infos).unwrap();
+    let expected_body = content.body.clone();
+
+    let mut mock_dependencies = MockDependencies {
+        block_builder_factory: mock_create_builder_for_propose_block(vec![]),
+        ..Default::default()
+    };
+    mock_dependencies
+        .proposal_manager
+        .expect_wrap_reset()
+        .times(1)
+        .return_once(|| async {}.boxed());
+    mock_dependencies
+        .proposal_manager
+        .expect_wrap_spawn_proposal()
+        .times(1)
+        .return_once(|_, _, _| { async move { Ok(()) } }.boxed());
+    mock_dependencies
+        .proposal_manager
+        .expect_wrap_take_proposal_result()
+        .times(1)
+        .with(eq(PROPOSAL_ID))
+        .return_once(move |_| {
+            async move {
+                Some(Ok(ProposalOutput {
+                    state_diff: ThinStateDiff::default(),
+                    commitment: ProposalCommitment::default(),
+                    tx_hashes: test_tx_hashes(),
+                    rejected_tx_hashes: HashMap::new(),
+                    nonces: HashMap::new(),
+                    l2_gas_used: GasAmount::default(),
+                    content,
+                }))
+            }
+            .boxed()
+        });
+    mock_dependencies.mempool_client.expect_commit_block().times(1).returning(|_| Ok(()));
+
+    let mut batcher =
+        create_batcher_with_storage(mock_dependencies, (storage_reader.clone(), storage_writer));
+    let height = BlockNumber(0);
+    batcher.start_height(StartHeightInput { height }).await.unwrap();
+    batcher
+        .propose_block(ProposeBlockInput {
+            block_info: BlockInfo { block_number: height, ..BlockInfo::create_for_testing() },
+            ..propose_block_input()
+        })
+        .await
+        .unwrap();
+
+    // Test.
+    batcher.decision_reached(DecisionReachedInput { proposal_id: PROPOSAL_ID }).await.unwrap();
+
+    // The transactions and their receipts can be served from the storage.
+    let txn = storage_reader.begin_ro_txn().unwrap();
+    assert_eq!(txn.get_block_transactions(height).unwrap(), Some(expected_body.transactions));
+    assert_eq!(
+        txn.get_block_transaction_outputs(height).unwrap(),
+        Some(expected_body.transaction_outputs)
+    );
+    assert_eq!(
+        txn.get_block_transaction_hashes(height).unwrap(),
+        Some(expected_body.transaction_hashes)
+    );
+    // The state tries are stored along with the block.
+    assert_eq!(txn.get_state_tries_marker().unwrap(), height.unchecked_next());
+}
+
 #[rstest]
 #[tokio::test]
 async fn decision_reached_no_executed_proposal() {
@@ -648,6 +1450,10 @@ fn test_tx_hashes() -> HashSet<TransactionHash> {
     (0..5u8).map(|i| tx_hash!(i + 12)).collect()
 }
 
+fn test_rejected_tx_hashes() -> HashMap<TransactionHash, String> {
+    HashMap::from([(tx_hash!(20), String::from("Transaction validation failed."))])
+}
+
 fn test_contract_nonces() -> HashMap<ContractAddress, Nonce> {
     HashMap::from_iter((0..3u8).map(|i| (contract_address!(i + 33), nonce!(i + 9))))
 }
@@ -1,4 +1,6 @@
 use std::collections::BTreeMap;
+use std::sync::Arc;
+use std::time::Duration;
 
 use async_trait::async_trait;
 use blockifier::blockifier::config::TransactionExecutorConfig;
@@ -14,23 +16,28 @@ use blockifier::execution::contract_class::RunnableCompiledClass;
 use blockifier::state::cached_state::CommitmentStateDiff;
 use blockifier::state::errors::StateError;
 use blockifier::state::global_cache::GlobalContractCache;
+use blockifier::transaction::errors::TransactionExecutionError;
 use blockifier::transaction::objects::TransactionExecutionInfo;
 use blockifier::transaction::transaction_execution::Transaction as BlockifierTransaction;
 use blockifier::versioned_constants::{VersionedConstants, VersionedConstantsOverrides};
 use indexmap::IndexMap;
 #[cfg(test)]
 use mockall::automock;
+use papyrus_config::converters::deserialize_milliseconds_to_duration;
 use papyrus_config::dumping::{append_sub_config_name, ser_param, SerializeConfig};
 use papyrus_config::{ParamPath, ParamPrivacyInput, SerializedParam};
 use papyrus_state_reader::papyrus_state::PapyrusReader;
 use papyrus_storage::StorageReader;
 use serde::{Deserialize, Serialize};
 use starknet_api::block::{BlockHashAndNumber, BlockInfo};
+use starknet_api::core::{ClassHash, ContractAddress};
 use starknet_api::executable_transaction::Transaction;
 use starknet_api::transaction::TransactionHash;
 use thiserror::Error;
 use tracing::{debug, error, info, trace};
 
+use crate::decided_block::MissingExecutionInfoError;
+use crate::pending_block::{PendingBlockBroadcaster, PendingBlockPublisher};
 use crate::transaction_executor::TransactionExecutorTrait;
 use crate::transaction_provider::{NextTxs, TransactionProvider, TransactionProviderError};
 
@@ -48,6 +55,8 @@ pub enum BlockBuilderError {
     FailOnError(FailOnErrorCause),
     #[error("The block builder was aborted.")]
     Aborted,
+    #[error(transparent)]
+    MissingExecutionInfo(#[from] MissingExecutionInfoError),
 }
 
 pub type BlockBuilderResult<T> = Result<T, BlockBuilderError>;
@@ -62,15 +71,35 @@ pub enum FailOnErrorCause {
     TransactionFailed(BlockifierTransactionExecutorError),
 }
 
-#[cfg_attr(test, derive(Clone))]
-#[derive(Debug, PartialEq)]
+#[cfg_attr(test, derive(Clone, PartialEq))]
+#[derive(Debug)]
 pub struct BlockExecutionArtifacts {
     pub execution_infos: IndexMap<TransactionHash, TransactionExecutionInfo>,
+    // The successfully executed transactions, in execution order.
+    pub executed_txs: Vec<Transaction>,
+    // The transactions that failed execution and were left out of the block, in execution order.
+    pub rejected_txs: Vec<RejectedTransaction>,
     pub commitment_state_diff: CommitmentStateDiff,
+    // The class hashes before the block of the contracts whose class hash the block sets.
+    pub pre_block_class_hashes: IndexMap<ContractAddress, ClassHash>,
     pub visited_segments_mapping: VisitedSegmentsMapping,
     pub bouncer_weights: BouncerWeights,
 }
 
+#[derive(Clone, Debug)]
+pub struct RejectedTransaction {
+    pub tx: Transaction,
+    pub error: Arc<TransactionExecutionError>,
+}
+
+// Execution errors can't be compared, so tests compare their messages instead.
+#[cfg(test)]
+impl PartialEq for RejectedTransaction {
+    fn eq(&self, other: &Self) -> bool {
+        self.tx == other.tx && self.error.to_string() == other.error.to_string()
+    }
+}
+
 /// The BlockBuilderTrait is responsible for building a new block from transactions provided by the
 /// tx_provider. The block building will stop at time deadline.
 /// The transactions that were added to the block will be streamed to the output_content_sender.
@@ -90,6 +119,7 @@ pub struct BlockBuilder {
     executor: Box<dyn TransactionExecutorTrait>,
     tx_provider: Box<dyn TransactionProvider>,
     output_content_sender: Option<tokio::sync::mpsc::UnboundedSender<Transaction>>,
+    pending_block_publisher: Option<PendingBlockPublisher>,
     abort_signal_receiver: tokio::sync::oneshot::Receiver<()>,
 
     // Parameters to configure the block builder behavior.
@@ -102,6 +132,7 @@ impl BlockBuilder {
         executor: Box<dyn TransactionExecutorTrait>,
         tx_provider: Box<dyn TransactionProvider>,
         output_content_sender: Option<tokio::sync::mpsc::UnboundedSender<Transaction>>,
+        pending_block_publisher: Option<PendingBlockPublisher>,
         abort_signal_receiver: tokio::sync::oneshot::Receiver<()>,
         tx_chunk_size: usize,
         execution_params: BlockBuilderExecutionParams,
@@ -110,6 +141,7 @@ impl BlockBuilder {
             executor,
             tx_provider,
             output_content_sender,
+            pending_block_publisher,
             abort_signal_receiver,
             tx_chunk_size,
             execution_params,
@@ -120,8 +152,24 @@ impl BlockBuilder {
 #[async_trait]
 impl BlockBuilderTrait for BlockBuilder {
     async fn build_block(&mut self) -> BlockBuilderResult<BlockExecutionArtifacts> {
+        if let Some(pending_block_publisher) = &self.pending_block_publisher {
+            pending_block_publisher.start();
+        }
+        let result = self.execute_txs().await;
+        if let (Err(_), Some(pending_block_publisher)) = (&result, &self.pending_block_publisher) {
+            pending_block_publisher.clear();
+        }
+        result
+    }
+}
+
+impl BlockBuilder {
+    async fn execute_txs(&mut self) -> BlockBuilderResult<BlockExecutionArtifacts> {
         let mut block_is_full = false;
         let mut execution_infos = IndexMap::new();
+        let mut executed_txs = Vec::new();
+        let mut rejected_txs = Vec::new();
+        let mut n_published_txs = 0;
         // TODO(yael 6/10/2024): delete the timeout condition once the executor has a timeout
         while !block_is_full {
             if tokio::time::Instant::now() >= self.execution_params.deadline {
@@ -159,20 +207,83 @@ impl BlockBuilderTrait for BlockBuilder {
                 next_tx_chunk,
                 results,
                 &mut execution_infos,
+                &mut executed_txs,
+                &mut rejected_txs,
                 &self.output_content_sender,
                 self.execution_params.fail_on_err,
             )
             .await?;
+            if self
+                .pending_block_publisher
+                .as_ref()
+                .is_some_and(|publisher| publisher.is_update_due())
+            {
+                self.publish_pending_txs(&executed_txs, &execution_infos, &mut n_published_txs)?;
+            }
         }
+        // The transactions executed since the last update are published before the block closes.
+        self.publish_pending_txs(&executed_txs, &execution_infos, &mut n_published_txs)?;
         let (commitment_state_diff, visited_segments_mapping, bouncer_weights) =
             self.executor.close_block()?;
+        let pre_block_class_hashes =
+            get_pre_block_class_hashes(&*self.executor, &commitment_state_diff)?;
         Ok(BlockExecutionArtifacts {
             execution_infos,
+            executed_txs,
+            rejected_txs,
             commitment_state_diff,
+            pre_block_class_hashes,
             visited_segments_mapping,
             bouncer_weights,
         })
     }
+
+    // Publishes the transactions executed since the last update of the pending block, along with
+    // the state changes since then.
+    fn publish_pending_txs(
+        &mut self,
+        executed_txs: &[Transaction],
+        execution_infos: &IndexMap<TransactionHash, TransactionExecutionInfo>,
+        n_published_txs: &mut usize,
+    ) -> BlockBuilderResult<()> {
+        let Some(pending_block_publisher) = &mut self.pending_block_publisher else {
+            return Ok(());
+        };
+        let unpublished_txs = &executed_txs[*n_published_txs..];
+        if unpublished_txs.is_empty() {
+            return Ok(());
+        }
+        let state_diff = self.executor.get_state_diff()?;
+        let pre_block_values = self
+            .executor
+            .get_pre_block_values(&pending_block_publisher.reverted_entries(&state_diff))?;
+        let pre_block_class_hashes = get_pre_block_class_hashes(&*self.executor, &state_diff)?;
+        pending_block_publisher.add_txs(
+            unpublished_txs,
+            execution_infos,
+            state_diff,
+            pre_block_values,
+            pre_block_class_hashes,
+        )?;
+        *n_published_txs = executed_txs.len();
+        Ok(())
+    }
+}
+
+// Returns the class hashes before the block of the contracts whose class hash the given state diff
+// sets, which tell the replaced classes apart from the deployed contracts.
+fn get_pre_block_class_hashes(
+    executor: &dyn TransactionExecutorTrait,
+    state_diff: &CommitmentStateDiff,
+) -> BlockBuilderResult<IndexMap<ContractAddress, ClassHash>> {
+    if state_diff.address_to_class_hash.is_empty() {
+        return Ok(IndexMap::new());
+    }
+    let entries = CommitmentStateDiff {
+        address_to_class_hash: state_diff.address_to_class_hash.clone(),
+        ..Default::default()
+    };
+    Ok(executor.get_pre_block_values(&entries)?.address_to_class_hash)
 }
 
 /// Returns true if the block is full and should be closed, false otherwise.
@@ -180,6 +291,8 @@ async fn collect_execution_results_and_stream_txs(
     tx_chunk: Vec<Transaction>,
     results: Vec<TransactionExecutorResult<TransactionExecutionInfo>>,
     execution_infos: &mut IndexMap<TransactionHash, TransactionExecutionInfo>,
+    executed_txs: &mut Vec<Transaction>,
+    rejected_txs: &mut Vec<RejectedTransaction>,
     output_content_sender: &Option<tokio::sync::mpsc::UnboundedSender<Transaction>>,
     fail_on_err: bool,
 ) -> BlockBuilderResult<bool> {
@@ -188,8 +301,9 @@ async fn collect_execution_results_and_stream_txs(
             Ok(tx_execution_info) => {
                 execution_infos.insert(input_tx.tx_hash(), tx_execution_info);
                 if let Some(output_content_sender) = output_content_sender {
-                    output_content_sender.send(input_tx)?;
+                    output_content_sender.send(input_tx.clone())?;
                 }
+                executed_txs.push(input_tx);
             }
             // TODO(yael 18/9/2024): add timeout error handling here once this
             // feature is added.
@@ -207,6 +321,10 @@ async fn collect_execution_results_and_stream_txs(
                         FailOnErrorCause::TransactionFailed(err),
                     ));
                 }
+                // State errors aren't caused by the transaction itself, so it isn't rejected.
+                if let BlockifierTransactionExecutorError::TransactionExecutionError(error) = err {
+                    rejected_txs.push(RejectedTransaction { tx: input_tx, error: Arc::new(error) });
+                }
             }
         }
     }
@@ -239,6 +357,8 @@ pub struct BlockBuilderConfig {
     pub execute_config: TransactionExecutorConfig,
     pub bouncer_config: BouncerConfig,
     pub tx_chunk_size: usize,
+    #[serde(deserialize_with = "deserialize_milliseconds_to_duration")]
+    pub pending_block_publish_interval: Duration,
     pub versioned_constants_overrides: VersionedConstantsOverrides,
 }
 
@@ -250,6 +370,7 @@ impl Default for BlockBuilderConfig {
             execute_config: TransactionExecutorConfig::default(),
             bouncer_config: BouncerConfig::default(),
             tx_chunk_size: 100,
+            pending_block_publish_interval: Duration::from_millis(1000),
             versioned_constants_overrides: VersionedConstantsOverrides::default(),
         }
     }
@@ -260,12 +381,21 @@ impl SerializeConfig for BlockBuilderConfig {
         let mut dump = append_sub_config_name(self.chain_info.dump(), "chain_info");
         dump.append(&mut append_sub_config_name(self.execute_config.dump(), "execute_config"));
         dump.append(&mut append_sub_config_name(self.bouncer_config.dump(), "bouncer_config"));
-        dump.append(&mut BTreeMap::from([ser_param(
-            "tx_chunk_size",
-            &self.tx_chunk_size,
-            "The size of the transaction chunk.",
-            ParamPrivacyInput::Public,
-        )]));
+        dump.append(&mut BTreeMap::from([
+            ser_param(
+                "tx_chunk_size",
+                &self.tx_chunk_size,
+                "The size of the transaction chunk.",
+                ParamPrivacyInput::Public,
+            ),
+            ser_param(
+                "pending_block_publish_interval",
+                &self.pending_block_publish_interval.as_millis(),
+                "The minimal time in milliseconds between updates of the pending block of a \
+                 proposal.",
+                ParamPrivacyInput::Public,
+            ),
+        ]));
         dump.append(&mut append_sub_config_name(
             self.versioned_constants_overrides.dump(),
             "versioned_constants_overrides",
@@ -278,6 +408,7 @@ pub struct BlockBuilderFactory {
     pub block_builder_config: BlockBuilderConfig,
     pub storage_reader: StorageReader,
     pub global_class_hash_to_class: GlobalContractCache<RunnableCompiledClass>,
+    pub pending_block_broadcaster: PendingBlockBroadcaster,
 }
 
 impl BlockBuilderFactory {
@@ -322,12 +453,21 @@ impl BlockBuilderFactoryTrait for BlockBuilderFactory {
         tx_provider: Box<dyn TransactionProvider>,
         output_content_sender: Option<tokio::sync::mpsc::UnboundedSender<Transaction>>,
     ) -> BlockBuilderResult<(Box<dyn BlockBuilderTrait>, AbortSignalSender)> {
+        // Only the blocks this node proposes are published as pending.
+        let pending_block_publisher = output_content_sender.as_ref().map(|_| {
+            PendingBlockPublisher::new(
+                self.pending_block_broadcaster.clone(),
+                block_metadata.block_info.clone(),
+                self.block_builder_config.pending_block_publish_interval,
+            )
+        });
         let executor = self.preprocess_and_create_transaction_executor(block_metadata)?;
         let (abort_signal_sender, abort_signal_receiver) = tokio::sync::oneshot::channel();
         let block_builder = Box::new(BlockBuilder::new(
             Box::new(executor),
             tx_provider,
             output_content_sender,
+            pending_block_publisher,
             abort_signal_receiver,
             self.block_builder_config.tx_chunk_size,
             execution_params,
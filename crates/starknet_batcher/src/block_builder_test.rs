@@ -1,18 +1,27 @@
+use std::sync::Arc;
+use std::time::Duration;
+
 use assert_matches::assert_matches;
 use blockifier::blockifier::transaction_executor::TransactionExecutorError;
 use blockifier::bouncer::BouncerWeights;
 use blockifier::fee::fee_checks::FeeCheckError;
+use blockifier::state::cached_state::CommitmentStateDiff;
 use blockifier::state::errors::StateError;
+use blockifier::test_utils::struct_impls::BlockInfoExt;
+use blockifier::transaction::errors::TransactionExecutionError;
 use blockifier::transaction::objects::{RevertError, TransactionExecutionInfo};
 use blockifier::transaction::transaction_execution::Transaction as BlockifierTransaction;
 use indexmap::{indexmap, IndexMap};
 use mockall::predicate::eq;
 use mockall::Sequence;
 use rstest::rstest;
+use starknet_api::block::BlockInfo;
+use starknet_api::core::ClassHash;
 use starknet_api::executable_transaction::Transaction;
 use starknet_api::transaction::fields::Fee;
 use starknet_api::transaction::TransactionHash;
-use starknet_api::tx_hash;
+use starknet_api::{contract_address, felt, nonce};
+use starknet_batcher_types::batcher_types::PendingBlockUpdate;
 use tokio::sync::mpsc::{UnboundedReceiver, UnboundedSender};
 
 use crate::block_builder::{
@@ -23,7 +32,10 @@ use crate::block_builder::{
     BlockBuilderTrait,
     BlockExecutionArtifacts,
     FailOnErrorCause,
+    RejectedTransaction,
 };
+use crate::decided_block::thin_state_diff;
+use crate::pending_block::{PendingBlockBroadcaster, PendingBlockPublisher};
 use crate::test_utils::test_txs;
 use crate::transaction_executor::MockTransactionExecutorTrait;
 use crate::transaction_provider::{MockTransactionProvider, NextTxs};
@@ -44,12 +56,13 @@ fn output_channel() -> (UnboundedSender<Transaction>, UnboundedReceiver<Transact
     tokio::sync::mpsc::unbounded_channel()
 }
 
-fn block_execution_artifacts(
-    execution_infos: IndexMap<TransactionHash, TransactionExecutionInfo>,
-) -> BlockExecutionArtifacts {
+fn block_execution_artifacts(executed_txs: Vec<Transaction>) -> BlockExecutionArtifacts {
     BlockExecutionArtifacts {
-        execution_infos,
+        execution_infos: executed_txs.iter().map(|tx| (tx.tx_hash(), execution_info())).collect(),
+        executed_txs,
+        rejected_txs: Vec::new(),
         commitment_state_diff: Default::default(),
+        pre_block_class_hashes: Default::default(),
         visited_segments_mapping: Default::default(),
         bouncer_weights: BouncerWeights { l1_gas: 100, ..BouncerWeights::empty() },
     }
@@ -233,24 +246,80 @@ fn stream_done_test_expectations() -> TestExpectations {
     }
 }
 
-fn transaction_failed_test_expectations() -> TestExpectations {
+fn replaced_class_test_expectations() -> TestExpectations {
+    let input_txs = test_txs(0..1);
+    let mut mock_transaction_executor = MockTransactionExecutorTrait::new();
+    mock_transaction_executor
+        .expect_add_txs_to_block()
+        .times(1)
+        .return_once(|_| vec![Ok(execution_info())]);
+
+    // The block replaces the class of a deployed contract.
+    let address = contract_address!(1_u8);
+    let mut expected_block_artifacts = block_execution_artifacts(input_txs.clone());
+    expected_block_artifacts.commitment_state_diff.address_to_class_hash =
+        indexmap! { address => ClassHash(felt!(2_u8)) };
+    expected_block_artifacts.pre_block_class_hashes =
+        indexmap! { address => ClassHash(felt!(1_u8)) };
+    let expected_block_artifacts_copy = expected_block_artifacts.clone();
+    mock_transaction_executor.expect_close_block().times(1).return_once(move || {
+        Ok((
+            expected_block_artifacts_copy.commitment_state_diff,
+            expected_block_artifacts_copy.visited_segments_mapping,
+            expected_block_artifacts_copy.bouncer_weights,
+        ))
+    });
+    let pre_block_class_hashes = expected_block_artifacts.pre_block_class_hashes.clone();
+    mock_transaction_executor
+        .expect_get_pre_block_values()
+        .times(1)
+        .withf(move |entries| entries.address_to_class_hash.keys().eq([&address]))
+        .return_once(move |_| {
+            Ok(CommitmentStateDiff {
+                address_to_class_hash: pre_block_class_hashes,
+                ..Default::default()
+            })
+        });
+
+    let mock_tx_provider = mock_tx_provider_limitless_calls(1, vec![input_txs.clone()]);
+
+    TestExpectations {
+        mock_transaction_executor,
+        mock_tx_provider,
+        expected_block_artifacts,
+        expected_txs_output: input_txs,
+    }
+}
+
+fn state_error() -> TransactionExecutorError {
+    TransactionExecutorError::StateError(StateError::OutOfRangeContractAddress)
+}
+
+fn transaction_execution_error() -> TransactionExecutorError {
+    TransactionExecutorError::TransactionExecutionError(
+        TransactionExecutionError::DeclareTransactionError { class_hash: ClassHash::default() },
+    )
+}
+
+fn transaction_failed_test_expectations(
+    execution_error: fn() -> TransactionExecutorError,
+) -> TestExpectations {
     let input_txs = test_txs(0..3);
 
     let mut expected_txs_output = input_txs.clone();
     expected_txs_output.remove(1);
 
     let mut mock_transaction_executor = MockTransactionExecutorTrait::new();
-    let execution_error =
-        TransactionExecutorError::StateError(StateError::OutOfRangeContractAddress);
     mock_transaction_executor.expect_add_txs_to_block().times(1).return_once(move |_| {
-        vec![Ok(execution_info()), Err(execution_error), Ok(execution_info())]
+        vec![Ok(execution_info()), Err(execution_error()), Ok(execution_info())]
     });
 
-    let execution_infos_mapping = indexmap![
-        tx_hash!(0)=> execution_info(),
-        tx_hash!(2)=> execution_info(),
-    ];
-    let expected_block_artifacts = block_execution_artifacts(execution_infos_mapping);
+    let mut expected_block_artifacts = block_execution_artifacts(expected_txs_output.clone());
+    // State errors aren't caused by the transaction itself, so only execution errors reject it.
+    if let TransactionExecutorError::TransactionExecutionError(error) = execution_error() {
+        expected_block_artifacts.rejected_txs =
+            vec![RejectedTransaction { tx: input_txs[1].clone(), error: Arc::new(error) }];
+    }
     let expected_block_artifacts_copy = expected_block_artifacts.clone();
     mock_transaction_executor.expect_close_block().times(1).return_once(move || {
         Ok((
@@ -273,10 +342,7 @@ fn transaction_failed_test_expectations() -> TestExpectations {
 // Fill the executor outputs with some non-default values to make sure the block_builder uses
 // them.
 fn block_builder_expected_output(execution_info_len: usize) -> BlockExecutionArtifacts {
-    let execution_info_len_u8 = u8::try_from(execution_info_len).unwrap();
-    let execution_infos_mapping =
-        (0..execution_info_len_u8).map(|i| (tx_hash!(i), execution_info())).collect();
-    block_execution_artifacts(execution_infos_mapping)
+    block_execution_artifacts(test_txs(0..execution_info_len))
 }
 
 fn set_close_block_expectations(
@@ -386,6 +452,7 @@ async fn run_build_block(
         Box::new(mock_transaction_executor),
         Box::new(tx_provider),
         output_sender,
+        None,
         abort_receiver,
         TX_CHUNK_SIZE,
         BlockBuilderExecutionParams { deadline, fail_on_err },
@@ -401,7 +468,9 @@ async fn run_build_block(
 #[case::block_full(block_full_test_expectations())]
 #[case::deadline_reached_after_first_chunk(test_expectations_with_delay())]
 #[case::stream_done(stream_done_test_expectations())]
-#[case::transaction_failed(transaction_failed_test_expectations())]
+#[case::transaction_failed(transaction_failed_test_expectations(state_error))]
+#[case::transaction_rejected(transaction_failed_test_expectations(transaction_execution_error))]
+#[case::replaced_class(replaced_class_test_expectations())]
 #[tokio::test]
 async fn test_build_block(#[case] test_expectations: TestExpectations) {
     let (output_tx_sender, output_tx_receiver) = output_channel();
@@ -544,3 +613,191 @@ async fn test_build_block_abort_immediately() {
         Err(BlockBuilderError::Aborted)
     );
 }
+
+fn pending_block_publisher(
+    pending_block_broadcaster: &PendingBlockBroadcaster,
+    publish_interval: Duration,
+) -> PendingBlockPublisher {
+    PendingBlockPublisher::new(
+        pending_block_broadcaster.clone(),
+        BlockInfo::create_for_testing(),
+        publish_interval,
+    )
+}
+
+fn build_block_with_publisher(
+    mock_transaction_executor: MockTransactionExecutorTrait,
+    tx_provider: MockTransactionProvider,
+    pending_block_publisher: PendingBlockPublisher,
+    abort_receiver: tokio::sync::oneshot::Receiver<()>,
+) -> BlockBuilder {
+    let deadline = tokio::time::Instant::now()
+        + tokio::time::Duration::from_secs(BLOCK_GENERATION_DEADLINE_SECS);
+    BlockBuilder::new(
+        Box::new(mock_transaction_executor),
+        Box::new(tx_provider),
+        None,
+        Some(pending_block_publisher),
+        abort_receiver,
+        TX_CHUNK_SIZE,
+        BlockBuilderExecutionParams { deadline, fail_on_err: false },
+    )
+}
+
+#[tokio::test]
+async fn test_build_block_publishes_pending_block() {
+    // Setup.
+    let TestExpectations {
+        mut mock_transaction_executor,
+        mock_tx_provider,
+        expected_txs_output,
+        ..
+    } = two_chunks_test_expectations();
+    // The first chunk sets the nonce of account 1, and the second one that of account 2.
+    let nonces_after_first_chunk = [(contract_address!(1_u8), nonce!(1_u8))];
+    let nonces_after_second_chunk =
+        [(contract_address!(1_u8), nonce!(1_u8)), (contract_address!(2_u8), nonce!(1_u8))];
+    let mut state_diffs = [nonces_after_first_chunk.to_vec(), nonces_after_second_chunk.to_vec()]
+        .map(|nonces| CommitmentStateDiff {
+            address_to_nonce: nonces.into_iter().collect(),
+            ..Default::default()
+        })
+        .into_iter();
+    mock_transaction_executor
+        .expect_get_state_diff()
+        .times(2)
+        .returning(move || Ok(state_diffs.next().unwrap()));
+    // No published entry is reverted.
+    mock_transaction_executor
+        .expect_get_pre_block_values()
+        .times(2)
+        .withf(|entries| *entries == CommitmentStateDiff::default())
+        .returning(|_| Ok(CommitmentStateDiff::default()));
+
+    let pending_block_broadcaster = PendingBlockBroadcaster::default();
+    let mut subscription = pending_block_broadcaster.subscribe();
+    let (_abort_sender, abort_receiver) = tokio::sync::oneshot::channel();
+    let mut block_builder = build_block_with_publisher(
+        mock_transaction_executor,
+        mock_tx_provider,
+        pending_block_publisher(&pending_block_broadcaster, Duration::ZERO),
+        abort_receiver,
+    );
+
+    // Test.
+    block_builder.build_block().await.unwrap();
+
+    // Each update holds only the transactions and state changes of its chunk.
+    let updates = subscription.next_updates().unwrap();
+    let [
+        PendingBlockUpdate::NewBlock(block_info),
+        PendingBlockUpdate::Txs(first_chunk),
+        PendingBlockUpdate::Txs(second_chunk),
+    ] = updates.as_slice()
+    else {
+        panic!("Unexpected pending block updates: {updates:?}");
+    };
+    assert_eq!(*block_info, BlockInfo::create_for_testing());
+    for (chunk, expected_txs, expected_nonces) in [
+        (first_chunk, &expected_txs_output[..TX_CHUNK_SIZE], nonces_after_first_chunk.to_vec()),
+        (
+            second_chunk,
+            &expected_txs_output[TX_CHUNK_SIZE..],
+            vec![(contract_address!(2_u8), nonce!(1_u8))],
+        ),
+    ] {
+        assert_eq!(
+            chunk.body.transaction_hashes,
+            expected_txs.iter().map(Transaction::tx_hash).collect::<Vec<_>>()
+        );
+        assert_eq!(chunk.body.transaction_outputs.len(), expected_txs.len());
+        assert_eq!(
+            chunk.state_diff,
+            thin_state_diff(
+                CommitmentStateDiff {
+                    address_to_nonce: expected_nonces.into_iter().collect(),
+                    ..Default::default()
+                },
+                &IndexMap::new()
+            )
+        );
+    }
+}
+
+#[tokio::test]
+async fn test_build_block_throttles_pending_block_updates() {
+    // Setup.
+    let TestExpectations {
+        mut mock_transaction_executor,
+        mock_tx_provider,
+        expected_txs_output,
+        ..
+    } = two_chunks_test_expectations();
+    let state_diff = CommitmentStateDiff {
+        address_to_nonce: indexmap! {
+            contract_address!(1_u8) => nonce!(1_u8),
+            contract_address!(2_u8) => nonce!(1_u8),
+        },
+        ..Default::default()
+    };
+    let returned_state_diff = state_diff.clone();
+    mock_transaction_executor
+        .expect_get_state_diff()
+        .times(1)
+        .return_once(move || Ok(returned_state_diff));
+    mock_transaction_executor
+        .expect_get_pre_block_values()
+        .times(1)
+        .returning(|_| Ok(CommitmentStateDiff::default()));
+
+    let pending_block_broadcaster = PendingBlockBroadcaster::default();
+    let mut subscription = pending_block_broadcaster.subscribe();
+    let (_abort_sender, abort_receiver) = tokio::sync::oneshot::channel();
+    let mut block_builder = build_block_with_publisher(
+        mock_transaction_executor,
+        mock_tx_provider,
+        pending_block_publisher(&pending_block_broadcaster, Duration::from_secs(3600)),
+        abort_receiver,
+    );
+
+    // Test.
+    block_builder.build_block().await.unwrap();
+
+    // No update is due while the block is built, so both chunks are published once it's done.
+    let updates = subscription.next_updates().unwrap();
+    let [PendingBlockUpdate::NewBlock(_), PendingBlockUpdate::Txs(txs)] = updates.as_slice() else {
+        panic!("Unexpected pending block updates: {updates:?}");
+    };
+    assert_eq!(
+        txs.body.transaction_hashes,
+        expected_txs_output.iter().map(Transaction::tx_hash).collect::<Vec<_>>()
+    );
+    assert_eq!(txs.state_diff, thin_state_diff(state_diff, &IndexMap::new()));
+}
+
+#[tokio::test]
+async fn test_build_block_aborted_clears_pending_block() {
+    // Setup.
+    let mut mock_tx_provider = MockTransactionProvider::new();
+    mock_tx_provider.expect_get_txs().times(0);
+    let pending_block_broadcaster = PendingBlockBroadcaster::default();
+    let mut subscription = pending_block_broadcaster.subscribe();
+    let (abort_sender, abort_receiver) = tokio::sync::oneshot::channel();
+    abort_sender.send(()).unwrap();
+    let mut block_builder = build_block_with_publisher(
+        MockTransactionExecutorTrait::new(),
+        mock_tx_provider,
+        pending_block_publisher(&pending_block_broadcaster, Duration::ZERO),
+        abort_receiver,
+    );
+
+    // Test.
+    assert_matches!(block_builder.build_block().await, Err(BlockBuilderError::Aborted));
+    assert_eq!(
+        subscription.next_updates().unwrap(),
+        [
+            PendingBlockUpdate::NewBlock(BlockInfo::create_for_testing()),
+            PendingBlockUpdate::Cleared
+        ]
+    );
+}
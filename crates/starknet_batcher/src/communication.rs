@@ -21,6 +21,18 @@ impl ComponentRequestHandler<BatcherRequest, BatcherResponse> for Batcher {
             BatcherRequest::GetProposalContent(input) => {
                 BatcherResponse::GetProposalContent(self.get_proposal_content(input).await)
             }
+            BatcherRequest::AbortProposal(input) => {
+                BatcherResponse::AbortProposal(self.abort_proposal(input).await)
+            }
+            BatcherRequest::GetProposalStatus(input) => {
+                BatcherResponse::GetProposalStatus(self.get_proposal_status(input).await)
+            }
+            BatcherRequest::SubscribePendingBlock => {
+                BatcherResponse::SubscribePendingBlock(self.subscribe_pending_block())
+            }
+            BatcherRequest::GetPendingBlockUpdates(input) => {
+                BatcherResponse::GetPendingBlockUpdates(self.get_pending_block_updates(input))
+            }
             BatcherRequest::StartHeight(input) => {
                 BatcherResponse::StartHeight(self.start_height(input).await)
             }
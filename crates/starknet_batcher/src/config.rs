@@ -6,6 +6,7 @@ use serde::{Deserialize, Serialize};
 use validator::{Validate, ValidationError};
 
 use crate::block_builder::BlockBuilderConfig;
+use crate::transaction_ordering::TransactionOrderingConfig;
 
 /// The batcher related configuration.
 #[derive(Clone, Debug, Serialize, Deserialize, Validate, PartialEq)]
@@ -17,6 +18,8 @@ pub struct BatcherConfig {
     pub block_builder_config: BlockBuilderConfig,
     pub global_contract_cache_size: usize,
     pub max_l1_handler_txs_per_block_proposal: usize,
+    #[validate]
+    pub transaction_ordering_config: TransactionOrderingConfig,
 }
 
 impl SerializeConfig for BatcherConfig {
@@ -55,6 +58,10 @@ impl SerializeConfig for BatcherConfig {
             self.block_builder_config.dump(),
             "block_builder_config",
         ));
+        dump.append(&mut append_sub_config_name(
+            self.transaction_ordering_config.dump(),
+            "transaction_ordering_config",
+        ));
         dump
     }
 }
@@ -69,7 +76,7 @@ impl Default for BatcherConfig {
                     enforce_file_exists: true,
                     ..Default::default()
                 },
-                scope: papyrus_storage::StorageScope::StateOnly,
+                scope: papyrus_storage::StorageScope::FullArchive,
                 ..Default::default()
             },
             // TODO: set a more reasonable default value.
@@ -78,6 +85,7 @@ impl Default for BatcherConfig {
             block_builder_config: BlockBuilderConfig::default(),
             global_contract_cache_size: 400,
             max_l1_handler_txs_per_block_proposal: 3,
+            transaction_ordering_config: TransactionOrderingConfig::default(),
         }
     }
 }
@@ -0,0 +1,357 @@
+use std::collections::HashMap;
+
+use blockifier::execution::call_info::CallInfo;
+use blockifier::fee::receipt::TransactionReceipt;
+use blockifier::state::cached_state::CommitmentStateDiff;
+use blockifier::transaction::objects::TransactionExecutionInfo;
+use cairo_lang_starknet_classes::casm_contract_class::CasmContractClass;
+use cairo_vm::types::builtin_name::BuiltinName;
+use indexmap::IndexMap;
+use starknet_api::block::{
+    BlockBody,
+    BlockHash,
+    BlockHeader,
+    BlockHeaderWithoutHash,
+    BlockInfo,
+    GasPricePerToken,
+    StarknetVersion,
+};
+use starknet_api::block_hash::block_hash_calculator::{
+    calculate_block_commitments,
+    calculate_block_hash,
+    TransactionHashingData,
+    TransactionOutputForHash,
+};
+use starknet_api::contract_class::ContractClass;
+use starknet_api::core::{ClassHash, ContractAddress, GlobalRoot, SequencerContractAddress};
+use starknet_api::data_availability::L1DataAvailabilityMode;
+use starknet_api::executable_transaction::{AccountTransaction, Transaction};
+use starknet_api::execution_resources::{Builtin, ExecutionResources};
+use starknet_api::state::ThinStateDiff;
+use starknet_api::transaction::fields::TransactionSignature;
+use starknet_api::transaction::{
+    DeclareTransactionOutput,
+    DeployAccountTransactionOutput,
+    Event,
+    InvokeTransactionOutput,
+    L1HandlerTransactionOutput,
+    MessageToL1,
+    RevertedTransactionExecutionStatus,
+    TransactionExecutionStatus,
+    TransactionHash,
+    TransactionOutput,
+};
+use starknet_api::StarknetApiResult;
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+#[error("Missing the execution info of transaction {0}.")]
+pub struct MissingExecutionInfoError(pub TransactionHash);
+
+/// The parts of a block that are derived from its executed transactions alone, regardless of its
+/// position in the chain.
+#[derive(Debug, Default, PartialEq)]
+pub struct BlockContent {
+    pub body: BlockBody,
+    pub transactions_data: Vec<TransactionHashingData>,
+    pub casms: IndexMap<ClassHash, CasmContractClass>,
+}
+
+impl BlockContent {
+    /// Fails if `execution_infos` is missing the entry of any of the transactions.
+    pub fn new(
+        txs: Vec<Transaction>,
+        execution_infos: &IndexMap<TransactionHash, TransactionExecutionInfo>,
+    ) -> Result<Self, MissingExecutionInfoError> {
+        let mut content = Self::default();
+        for tx in txs {
+            let tx_hash = tx.tx_hash();
+            let execution_info =
+                execution_infos.get(&tx_hash).ok_or(MissingExecutionInfoError(tx_hash))?;
+            let output_for_hash = transaction_output_for_hash(execution_info);
+            let transaction_output = transaction_output(
+                &tx,
+                output_for_hash.clone(),
+                execution_resources(&execution_info.receipt),
+            );
+            content.transactions_data.push(TransactionHashingData {
+                transaction_signature: transaction_signature(&tx),
+                transaction_output: output_for_hash,
+                transaction_hash: tx_hash,
+            });
+            if let Transaction::Account(AccountTransaction::Declare(declare_tx)) = &tx {
+                if let ContractClass::V1(casm) = &declare_tx.class_info.contract_class {
+                    content.casms.insert(declare_tx.class_hash(), casm.clone());
+                }
+            }
+            content.body.transactions.push(tx.into());
+            content.body.transaction_outputs.push(transaction_output);
+            content.body.transaction_hashes.push(tx_hash);
+        }
+        Ok(content)
+    }
+}
+
+/// A block that consensus decided on, ready to be appended to the storage.
+#[derive(Debug, PartialEq)]
+pub struct DecidedBlock {
+    pub header: BlockHeader,
+    pub body: BlockBody,
+    pub state_diff: ThinStateDiff,
+    pub casms: IndexMap<ClassHash, CasmContractClass>,
+}
+
+impl DecidedBlock {
+    /// Computes the block commitments and the block hash, and assembles the block.
+    pub fn new(
+        block_info: &BlockInfo,
+        parent_hash: BlockHash,
+        state_root: GlobalRoot,
+        state_diff: ThinStateDiff,
+        content: BlockContent,
+    ) -> StarknetApiResult<Self> {
+        let l1_da_mode = if block_info.use_kzg_da {
+            L1DataAvailabilityMode::Blob
+        } else {
+            L1DataAvailabilityMode::Calldata
+        };
+        let starknet_version = StarknetVersion::LATEST;
+        let commitments = calculate_block_commitments(
+            &content.transactions_data,
+            &state_diff,
+            l1_da_mode,
+            &starknet_version,
+        );
+
+        let gas_prices = &block_info.gas_prices;
+        let block_header_without_hash = BlockHeaderWithoutHash {
+            parent_hash,
+            block_number: block_info.block_number,
+            l1_gas_price: GasPricePerToken {
+                price_in_fri: gas_prices.strk_gas_prices.l1_gas_price.get(),
+                price_in_wei: gas_prices.eth_gas_prices.l1_gas_price.get(),
+            },
+            l1_data_gas_price: GasPricePerToken {
+                price_in_fri: gas_prices.strk_gas_prices.l1_data_gas_price.get(),
+                price_in_wei: gas_prices.eth_gas_prices.l1_data_gas_price.get(),
+            },
+            l2_gas_price: GasPricePerToken {
+                price_in_fri: gas_prices.strk_gas_prices.l2_gas_price.get(),
+                price_in_wei: gas_prices.eth_gas_prices.l2_gas_price.get(),
+            },
+            state_root,
+            sequencer: SequencerContractAddress(block_info.sequencer_address),
+            timestamp: block_info.block_timestamp,
+            l1_da_mode,
+            starknet_version,
+        };
+        let block_hash =
+            calculate_block_hash(block_header_without_hash.clone(), commitments.clone())?;
+
+        let header = BlockHeader {
+            block_hash,
+            block_header_without_hash,
+            state_diff_commitment: Some(commitments.state_diff_commitment),
+            state_diff_length: Some(state_diff.len()),
+            transaction_commitment: Some(commitments.transaction_commitment),
+            event_commitment: Some(commitments.event_commitment),
+            n_transactions: content.transactions_data.len(),
+            n_events: content
+                .transactions_data
+                .iter()
+                .map(|data| data.transaction_output.events.len())
+                .sum(),
+            receipt_commitment: Some(commitments.receipt_commitment),
+        };
+        Ok(Self { header, body: content.body, state_diff, casms: content.casms })
+    }
+}
+
+/// Converts the state diff of executed transactions to the form in which it is stored.
+/// `pre_block_class_hashes` holds the class hashes before the block of the contracts whose class
+/// hash is set; those that were already deployed had their class replaced.
+pub fn thin_state_diff(
+    commitment_state_diff: CommitmentStateDiff,
+    pre_block_class_hashes: &IndexMap<ContractAddress, ClassHash>,
+) -> ThinStateDiff {
+    let (replaced_classes, deployed_contracts): (IndexMap<_, _>, IndexMap<_, _>) =
+        commitment_state_diff.address_to_class_hash.into_iter().partition(|(address, _)| {
+            pre_block_class_hashes
+                .get(address)
+                .is_some_and(|class_hash| *class_hash != ClassHash::default())
+        });
+    ThinStateDiff {
+        deployed_contracts,
+        storage_diffs: commitment_state_diff.storage_updates,
+        declared_classes: commitment_state_diff.class_hash_to_compiled_class_hash,
+        nonces: commitment_state_diff.address_to_nonce,
+        // TODO: Remove this when the structure of storage diffs changes.
+        deprecated_declared_classes: Vec::new(),
+        replaced_classes,
+    }
+}
+
+fn transaction_signature(tx: &Transaction) -> TransactionSignature {
+    match tx {
+        Transaction::Account(account_tx) => account_tx.signature(),
+        Transaction::L1Handler(_) => TransactionSignature::default(),
+    }
+}
+
+fn transaction_output_for_hash(
+    execution_info: &TransactionExecutionInfo,
+) -> TransactionOutputForHash {
+    let execution_status = match &execution_info.revert_error {
+        Some(revert_error) => {
+            TransactionExecutionStatus::Reverted(RevertedTransactionExecutionStatus {
+                revert_reason: revert_error.to_string(),
+            })
+        }
+        None => TransactionExecutionStatus::Succeeded,
+    };
+    TransactionOutputForHash {
+        actual_fee: execution_info.receipt.fee,
+        events: events(execution_info),
+        execution_status,
+        gas_consumed: execution_info.receipt.gas,
+        messages_sent: messages_sent(execution_info),
+    }
+}
+
+fn transaction_output(
+    tx: &Transaction,
+    output_for_hash: TransactionOutputForHash,
+    execution_resources: ExecutionResources,
+) -> TransactionOutput {
+    let TransactionOutputForHash { actual_fee, events, execution_status, messages_sent, .. } =
+        output_for_hash;
+    match tx {
+        Transaction::Account(AccountTransaction::Declare(_)) => {
+            TransactionOutput::Declare(DeclareTransactionOutput {
+                actual_fee,
+                messages_sent,
+                events,
+                execution_status,
+                execution_resources,
+            })
+        }
+        Transaction::Account(AccountTransaction::DeployAccount(deploy_account_tx)) => {
+            TransactionOutput::DeployAccount(DeployAccountTransactionOutput {
+                actual_fee,
+                messages_sent,
+                events,
+                contract_address: deploy_account_tx.contract_address,
+                execution_status,
+                execution_resources,
+            })
+        }
+        Transaction::Account(AccountTransaction::Invoke(_)) => {
+            TransactionOutput::Invoke(InvokeTransactionOutput {
+                actual_fee,
+                messages_sent,
+                events,
+                execution_status,
+                execution_resources,
+            })
+        }
+        Transaction::L1Handler(_) => TransactionOutput::L1Handler(L1HandlerTransactionOutput {
+            actual_fee,
+            messages_sent,
+            events,
+            execution_status,
+            execution_resources,
+        }),
+    }
+}
+
+// The order of events and messages is shared by all the calls of a top-level call (validate,
+// execute or fee transfer), so they are sorted per top-level call.
+fn events(execution_info: &TransactionExecutionInfo) -> Vec<Event> {
+    execution_info
+        .non_optional_call_infos()
+        .flat_map(|top_level_call_info| {
+            let mut ordered_events: Vec<_> = top_level_call_info
+                .iter()
+                .flat_map(|call_info| {
+                    call_info.execution.events.iter().map(|ordered_event| {
+                        (
+                            ordered_event.order,
+                            Event {
+                                from_address: call_info.call.storage_address,
+                                content: ordered_event.event.clone(),
+                            },
+                        )
+                    })
+                })
+                .collect();
+            ordered_events.sort_by_key(|(order, _)| *order);
+            ordered_events.into_iter().map(|(_, event)| event)
+        })
+        .collect()
+}
+
+fn messages_sent(execution_info: &TransactionExecutionInfo) -> Vec<MessageToL1> {
+    execution_info
+        .non_optional_call_infos()
+        .flat_map(|top_level_call_info| {
+            let mut ordered_messages: Vec<_> = top_level_call_info
+                .iter()
+                .flat_map(|call_info: &CallInfo| {
+                    call_info.execution.l2_to_l1_messages.iter().map(|ordered_message| {
+                        (
+                            ordered_message.order,
+                            MessageToL1 {
+                                from_address: call_info.call.storage_address,
+                                to_address: ordered_message.message.to_address,
+                                payload: ordered_message.message.payload.clone(),
+                            },
+                        )
+                    })
+                })
+                .collect();
+            ordered_messages.sort_by_key(|(order, _)| *order);
+            ordered_messages.into_iter().map(|(_, message)| message)
+        })
+        .collect()
+}
+
+fn execution_resources(receipt: &TransactionReceipt) -> ExecutionResources {
+    let computation = &receipt.resources.computation;
+    let vm_resources = &computation.vm_resources;
+    let builtin_instance_counter: HashMap<Builtin, u64> = vm_resources
+        .builtin_instance_counter
+        .iter()
+        .filter(|(_, count)| **count > 0)
+        .filter_map(|(builtin_name, count)| {
+            Some((builtin(builtin_name)?, u64::try_from(*count).expect("Builtin count overflow.")))
+        })
+        .collect();
+
+    ExecutionResources {
+        steps: u64::try_from(vm_resources.n_steps + computation.n_reverted_steps)
+            .expect("Steps count overflow."),
+        builtin_instance_counter,
+        memory_holes: u64::try_from(vm_resources.n_memory_holes)
+            .expect("Memory holes count overflow."),
+        da_gas_consumed: receipt.da_gas,
+        gas_consumed: receipt.gas,
+    }
+}
+
+fn builtin(builtin_name: &BuiltinName) -> Option<Builtin> {
+    match builtin_name {
+        BuiltinName::range_check => Some(Builtin::RangeCheck),
+        BuiltinName::pedersen => Some(Builtin::Pedersen),
+        BuiltinName::poseidon => Some(Builtin::Poseidon),
+        BuiltinName::ec_op => Some(Builtin::EcOp),
+        BuiltinName::ecdsa => Some(Builtin::Ecdsa),
+        BuiltinName::bitwise => Some(Builtin::Bitwise),
+        BuiltinName::keccak => Some(Builtin::Keccak),
+        BuiltinName::segment_arena => Some(Builtin::SegmentArena),
+        BuiltinName::add_mod => Some(Builtin::AddMod),
+        BuiltinName::mul_mod => Some(Builtin::MulMod),
+        BuiltinName::range_check96 => Some(Builtin::RangeCheck96),
+        // The output builtin isn't reported.
+        BuiltinName::output => None,
+    }
+}
@@ -0,0 +1,159 @@
+use assert_matches::assert_matches;
+use blockifier::execution::call_info::{CallExecution, CallInfo, OrderedEvent};
+use blockifier::execution::entry_point::CallEntryPoint;
+use blockifier::state::cached_state::CommitmentStateDiff;
+use blockifier::test_utils::struct_impls::BlockInfoExt;
+use blockifier::transaction::objects::TransactionExecutionInfo;
+use indexmap::indexmap;
+use starknet_api::block::{BlockHash, BlockInfo, BlockNumber};
+use starknet_api::core::{ClassHash, ContractAddress, GlobalRoot};
+use starknet_api::executable_transaction::Transaction;
+use starknet_api::state::ThinStateDiff;
+use starknet_api::test_utils::invoke::{executable_invoke_tx, InvokeTxArgs};
+use starknet_api::transaction::fields::{Fee, TransactionSignature};
+use starknet_api::transaction::{Event, EventContent, EventKey, TransactionExecutionStatus};
+use starknet_api::{class_hash, contract_address, felt, tx_hash};
+
+use crate::decided_block::{
+    thin_state_diff,
+    BlockContent,
+    DecidedBlock,
+    MissingExecutionInfoError,
+};
+
+fn event(key: u8) -> EventContent {
+    EventContent { keys: vec![EventKey(felt!(key))], ..Default::default() }
+}
+
+fn call_info(
+    storage_address: ContractAddress,
+    events: Vec<OrderedEvent>,
+    inner_calls: Vec<CallInfo>,
+) -> CallInfo {
+    CallInfo {
+        call: CallEntryPoint { storage_address, ..Default::default() },
+        execution: CallExecution { events, ..Default::default() },
+        inner_calls,
+        ..Default::default()
+    }
+}
+
+fn invoke_tx() -> Transaction {
+    Transaction::Account(executable_invoke_tx(InvokeTxArgs {
+        tx_hash: tx_hash!(1),
+        signature: TransactionSignature(vec![felt!("0x5")]),
+        ..Default::default()
+    }))
+}
+
+// The inner call emits the first event of the execution, after which the outer call emits another.
+fn execution_info() -> TransactionExecutionInfo {
+    let inner_call = call_info(
+        contract_address!("0x2"),
+        vec![OrderedEvent { order: 0, event: event(0) }],
+        vec![],
+    );
+    let execute_call_info = call_info(
+        contract_address!("0x1"),
+        vec![OrderedEvent { order: 1, event: event(1) }],
+        vec![inner_call],
+    );
+    let mut execution_info = TransactionExecutionInfo {
+        execute_call_info: Some(execute_call_info),
+        ..Default::default()
+    };
+    execution_info.receipt.fee = Fee(7);
+    execution_info
+}
+
+#[test]
+fn block_content_from_execution_infos() {
+    let content =
+        BlockContent::new(vec![invoke_tx()], &indexmap! { tx_hash!(1) => execution_info() })
+            .unwrap();
+
+    assert_eq!(content.body.transactions, vec![invoke_tx().into()]);
+    assert_eq!(content.body.transaction_hashes, vec![tx_hash!(1)]);
+    assert_eq!(content.body.transaction_outputs.len(), 1);
+
+    let [transaction_data] = content.transactions_data.as_slice() else {
+        panic!("Expected a single transaction, got {:?}.", content.transactions_data);
+    };
+    assert_eq!(transaction_data.transaction_signature, TransactionSignature(vec![felt!("0x5")]));
+    assert_eq!(transaction_data.transaction_output.actual_fee, Fee(7));
+    assert_eq!(
+        transaction_data.transaction_output.execution_status,
+        TransactionExecutionStatus::Succeeded
+    );
+    // Events are ordered by their emission order, not by the call tree.
+    assert_eq!(
+        transaction_data.transaction_output.events,
+        vec![
+            Event { from_address: contract_address!("0x2"), content: event(0) },
+            Event { from_address: contract_address!("0x1"), content: event(1) },
+        ]
+    );
+    assert_eq!(
+        content.body.transaction_outputs[0].events(),
+        transaction_data.transaction_output.events
+    );
+}
+
+#[test]
+fn block_content_missing_execution_info() {
+    let result = BlockContent::new(vec![invoke_tx()], &indexmap! {});
+
+    assert_matches!(result, Err(MissingExecutionInfoError(tx_hash)) if tx_hash == tx_hash!(1));
+}
+
+#[test]
+fn decided_block_header() {
+    let block_info = BlockInfo { block_number: BlockNumber(3), ..BlockInfo::create_for_testing() };
+    let parent_hash = BlockHash(felt!("0x123"));
+    let state_root = GlobalRoot(felt!("0x456"));
+    let content =
+        BlockContent::new(vec![invoke_tx()], &indexmap! { tx_hash!(1) => execution_info() })
+            .unwrap();
+
+    let block =
+        DecidedBlock::new(&block_info, parent_hash, state_root, ThinStateDiff::default(), content)
+            .unwrap();
+
+    let header = &block.header;
+    assert_eq!(header.block_header_without_hash.parent_hash, parent_hash);
+    assert_eq!(header.block_header_without_hash.block_number, BlockNumber(3));
+    assert_eq!(header.block_header_without_hash.state_root, state_root);
+    assert_eq!(header.block_header_without_hash.timestamp, block_info.block_timestamp);
+    assert_eq!(header.n_transactions, 1);
+    assert_eq!(header.n_events, 2);
+    assert_eq!(header.state_diff_length, Some(0));
+
+    assert_ne!(header.block_hash, BlockHash::default());
+}
+
+#[test]
+fn thin_state_diff_separates_replaced_classes() {
+    let commitment_state_diff = CommitmentStateDiff {
+        address_to_class_hash: indexmap! {
+            contract_address!("0x1") => class_hash!("0x11"),
+            contract_address!("0x2") => class_hash!("0x12"),
+        },
+        ..Default::default()
+    };
+    // Only the first contract was deployed before the block.
+    let pre_block_class_hashes = indexmap! {
+        contract_address!("0x1") => class_hash!("0x10"),
+        contract_address!("0x2") => ClassHash::default(),
+    };
+
+    let state_diff = thin_state_diff(commitment_state_diff, &pre_block_class_hashes);
+
+    assert_eq!(
+        state_diff.replaced_classes,
+        indexmap! { contract_address!("0x1") => class_hash!("0x11") }
+    );
+    assert_eq!(
+        state_diff.deployed_contracts,
+        indexmap! { contract_address!("0x2") => class_hash!("0x12") }
+    );
+}
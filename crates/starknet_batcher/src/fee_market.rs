@@ -8,9 +8,17 @@ pub mod fee_market_test;
 // and serves as a sensitivity parameter that limits the maximum rate of change of the gas price
 // between consecutive blocks.
 const GAS_PRICE_MAX_CHANGE_DENOMINATOR: u128 = 48;
-const MIN_GAS_PRICE: u64 = 100000; // In fri.
+pub(crate) const MIN_GAS_PRICE: u64 = 100000; // In fri.
 // TODO(Mohammad): Check the exact value for maximum block size in StarkNet.
-const MAX_BLOCK_SIZE: u64 = 4000000000; // In gas units. It's equivalent to 40M gas steps, with 100 gas units per step.
+pub(crate) const MAX_BLOCK_SIZE: u64 = 4000000000; // In gas units. It's equivalent to 40M gas steps, with 100 gas units per step.
+pub(crate) const GAS_TARGET: u64 = MAX_BLOCK_SIZE / 2;
+
+/// The L2 gas price (in fri) of a block, along with the L2 gas consumed by its transactions.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct L2GasUsage {
+    pub price: u64,
+    pub gas_used: u64,
+}
 
 /// Calculate the base gas price for the next block according to EIP-1559.
 ///
@@ -62,8 +70,11 @@ pub fn calculate_next_base_gas_price(price: u64, gas_used: u64, gas_target: u64)
     let price_change = u64::try_from(price_change_u128)
         .expect("Result fits u64 after division of a bounded gas delta");
 
-    let adjusted_price =
-        if gas_used > gas_target { price + price_change } else { price - price_change };
+    let adjusted_price = if gas_used > gas_target {
+        price.saturating_add(price_change)
+    } else {
+        price - price_change
+    };
 
     assert!(
         gas_used > gas_target && adjusted_price >= price
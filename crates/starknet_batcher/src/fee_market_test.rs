@@ -60,3 +60,9 @@ fn test_gas_price_with_extreme_values() {
     let gas_used = MAX_BLOCK_SIZE;
     calculate_next_base_gas_price(u64::try_from(price_u128).unwrap(), gas_used, gas_target); // Should not panic.
 }
+
+#[test]
+fn test_gas_price_increase_saturates() {
+    let gas_target = MAX_BLOCK_SIZE / 2;
+    assert_eq!(calculate_next_base_gas_price(u64::MAX, MAX_BLOCK_SIZE, gas_target), u64::MAX);
+}
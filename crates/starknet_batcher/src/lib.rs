@@ -6,13 +6,28 @@ pub mod block_builder;
 mod block_builder_test;
 pub mod communication;
 pub mod config;
+mod decided_block;
+#[cfg(test)]
+mod decided_block_test;
 pub mod fee_market;
+pub mod pending_block;
+#[cfg(test)]
+mod pending_block_test;
 mod proposal_manager;
 #[cfg(test)]
 mod proposal_manager_test;
+mod proposal_metadata;
+#[cfg(test)]
+mod proposal_metadata_test;
+mod state_committer;
+#[cfg(test)]
+mod state_committer_test;
 #[cfg(test)]
 mod test_utils;
 mod transaction_executor;
+pub mod transaction_ordering;
+#[cfg(test)]
+mod transaction_ordering_test;
 mod transaction_provider;
 #[cfg(test)]
 mod transaction_provider_test;
@@ -0,0 +1,251 @@
+use std::hash::Hash;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use blockifier::state::cached_state::CommitmentStateDiff;
+use blockifier::transaction::objects::TransactionExecutionInfo;
+use indexmap::IndexMap;
+use starknet_api::block::BlockInfo;
+use starknet_api::core::{ClassHash, ContractAddress};
+use starknet_api::executable_transaction::Transaction;
+use starknet_api::transaction::TransactionHash;
+use starknet_batcher_types::batcher_types::{PendingBlockUpdate, PendingTxs};
+use tokio::sync::broadcast;
+use tokio::sync::broadcast::error::TryRecvError;
+use tokio::time::Instant;
+
+use crate::decided_block::{thin_state_diff, BlockContent, MissingExecutionInfoError};
+
+// The number of updates a subscriber may fall behind before its subscription is dropped.
+pub(crate) const PENDING_BLOCK_UPDATES_CAPACITY: usize = 1000;
+
+/// Broadcasts the updates of the block being proposed to the pending block subscribers. The updates
+/// of the current pending block are kept, so that new subscribers can catch up on it.
+#[derive(Clone)]
+pub struct PendingBlockBroadcaster(Arc<Mutex<PendingBlockChannel>>);
+
+struct PendingBlockChannel {
+    sender: broadcast::Sender<PendingBlockUpdate>,
+    current_block_updates: Vec<PendingBlockUpdate>,
+}
+
+impl Default for PendingBlockBroadcaster {
+    fn default() -> Self {
+        let (sender, _) = broadcast::channel(PENDING_BLOCK_UPDATES_CAPACITY);
+        Self(Arc::new(Mutex::new(PendingBlockChannel {
+            sender,
+            current_block_updates: Vec::new(),
+        })))
+    }
+}
+
+impl PendingBlockBroadcaster {
+    pub fn publish(&self, update: PendingBlockUpdate) {
+        let mut channel = self.0.lock().expect("Pending block channel lock is poisoned.");
+        match &update {
+            PendingBlockUpdate::NewBlock(_) => channel.current_block_updates = vec![update.clone()],
+            PendingBlockUpdate::Txs(_) => channel.current_block_updates.push(update.clone()),
+            PendingBlockUpdate::Cleared => {
+                if channel.current_block_updates.is_empty() {
+                    // There is no pending block to clear.
+                    return;
+                }
+                channel.current_block_updates.clear();
+            }
+        }
+        // Sending fails only if there are no subscribers.
+        channel.sender.send(update).ok();
+    }
+
+    pub fn subscribe(&self) -> PendingBlockSubscription {
+        let channel = self.0.lock().expect("Pending block channel lock is poisoned.");
+        PendingBlockSubscription {
+            missed_updates: channel.current_block_updates.clone(),
+            receiver: channel.sender.subscribe(),
+        }
+    }
+}
+
+pub struct PendingBlockSubscription {
+    // The updates of the pending block published before the subscription.
+    missed_updates: Vec<PendingBlockUpdate>,
+    receiver: broadcast::Receiver<PendingBlockUpdate>,
+}
+
+impl PendingBlockSubscription {
+    /// Returns the updates published since the previous call, or `None` if the subscription fell
+    /// behind and some of them were lost.
+    pub fn next_updates(&mut self) -> Option<Vec<PendingBlockUpdate>> {
+        let mut updates = std::mem::take(&mut self.missed_updates);
+        loop {
+            match self.receiver.try_recv() {
+                Ok(update) => updates.push(update),
+                Err(TryRecvError::Empty) | Err(TryRecvError::Closed) => return Some(updates),
+                Err(TryRecvError::Lagged(_)) => return None,
+            }
+        }
+    }
+}
+
+/// Publishes the block being built by a block builder, sending only what changed in each update.
+/// Updates are sent at most once per publish interval, since each requires the state diff of the
+/// whole block so far.
+pub struct PendingBlockPublisher {
+    broadcaster: PendingBlockBroadcaster,
+    block_info: BlockInfo,
+    publish_interval: Duration,
+    next_update_time: Instant,
+    // The state diff of the block as of the last update.
+    published_state_diff: CommitmentStateDiff,
+}
+
+impl PendingBlockPublisher {
+    pub fn new(
+        broadcaster: PendingBlockBroadcaster,
+        block_info: BlockInfo,
+        publish_interval: Duration,
+    ) -> Self {
+        Self {
+            broadcaster,
+            block_info,
+            publish_interval,
+            next_update_time: Instant::now() + publish_interval,
+            published_state_diff: CommitmentStateDiff::default(),
+        }
+    }
+
+    // Replaces the previously published pending block, which may belong to another proposal.
+    pub(crate) fn start(&self) {
+        self.broadcaster.publish(PendingBlockUpdate::NewBlock(self.block_info.clone()));
+    }
+
+    // Fails if `execution_infos` is missing the entry of any of the given transactions. Assumes that
+    // `state_diff` is the state diff of the block so far, that `pre_block_values` holds the
+    // values before the block of its reverted entries, and that `pre_block_class_hashes` holds
+    // the class hashes before the block of the contracts whose class hash it sets.
+    pub(crate) fn add_txs(
+        &mut self,
+        txs: &[Transaction],
+        execution_infos: &IndexMap<TransactionHash, TransactionExecutionInfo>,
+        state_diff: CommitmentStateDiff,
+        pre_block_values: CommitmentStateDiff,
+        mut pre_block_class_hashes: IndexMap<ContractAddress, ClassHash>,
+    ) -> Result<(), MissingExecutionInfoError> {
+        let BlockContent { body, .. } = BlockContent::new(txs.to_vec(), execution_infos)?;
+        // The reverted contracts are set back to their class hashes before the block.
+        pre_block_class_hashes.extend(pre_block_values.address_to_class_hash.clone());
+        let state_diff = thin_state_diff(
+            self.take_state_changes(state_diff, pre_block_values),
+            &pre_block_class_hashes,
+        );
+        self.broadcaster.publish(PendingBlockUpdate::Txs(PendingTxs { body, state_diff }));
+        self.next_update_time = Instant::now() + self.publish_interval;
+        Ok(())
+    }
+
+    pub(crate) fn is_update_due(&self) -> bool {
+        Instant::now() >= self.next_update_time
+    }
+
+    pub(crate) fn clear(&self) {
+        self.broadcaster.publish(PendingBlockUpdate::Cleared);
+    }
+
+    // Returns the published entries that are no longer part of the given state diff of the block,
+    // since they were set back to their value before the block.
+    pub(crate) fn reverted_entries(&self, state_diff: &CommitmentStateDiff) -> CommitmentStateDiff {
+        let published = &self.published_state_diff;
+        CommitmentStateDiff {
+            address_to_class_hash: missing_entries(
+                &published.address_to_class_hash,
+                &state_diff.address_to_class_hash,
+            ),
+            address_to_nonce: missing_entries(
+                &published.address_to_nonce,
+                &state_diff.address_to_nonce,
+            ),
+            storage_updates: published
+                .storage_updates
+                .iter()
+                .filter_map(|(address, published_storage)| {
+                    let reverted_storage = match state_diff.storage_updates.get(address) {
+                        Some(storage) => missing_entries(published_storage, storage),
+                        None => published_storage.clone(),
+                    };
+                    (!reverted_storage.is_empty()).then_some((*address, reverted_storage))
+                })
+                .collect(),
+            class_hash_to_compiled_class_hash: missing_entries(
+                &published.class_hash_to_compiled_class_hash,
+                &state_diff.class_hash_to_compiled_class_hash,
+            ),
+        }
+    }
+
+    // Returns the entries of the given state diff that were not published yet, along with the
+    // reverted entries set to their values before the block, and marks them as published.
+    fn take_state_changes(
+        &mut self,
+        state_diff: CommitmentStateDiff,
+        pre_block_values: CommitmentStateDiff,
+    ) -> CommitmentStateDiff {
+        let published = std::mem::replace(&mut self.published_state_diff, state_diff);
+        let current = &self.published_state_diff;
+        let mut changes = CommitmentStateDiff {
+            address_to_class_hash: changed_entries(
+                &current.address_to_class_hash,
+                &published.address_to_class_hash,
+            ),
+            address_to_nonce: changed_entries(
+                &current.address_to_nonce,
+                &published.address_to_nonce,
+            ),
+            storage_updates: current
+                .storage_updates
+                .iter()
+                .filter_map(|(address, storage)| {
+                    let changed_storage = match published.storage_updates.get(address) {
+                        Some(published_storage) => changed_entries(storage, published_storage),
+                        None => storage.clone(),
+                    };
+                    (!changed_storage.is_empty()).then_some((*address, changed_storage))
+                })
+                .collect(),
+            class_hash_to_compiled_class_hash: changed_entries(
+                &current.class_hash_to_compiled_class_hash,
+                &published.class_hash_to_compiled_class_hash,
+            ),
+        };
+        changes.address_to_class_hash.extend(pre_block_values.address_to_class_hash);
+        changes.address_to_nonce.extend(pre_block_values.address_to_nonce);
+        for (address, storage) in pre_block_values.storage_updates {
+            changes.storage_updates.entry(address).or_default().extend(storage);
+        }
+        changes
+            .class_hash_to_compiled_class_hash
+            .extend(pre_block_values.class_hash_to_compiled_class_hash);
+        changes
+    }
+}
+
+fn changed_entries<K: Copy + Eq + Hash, V: Copy + PartialEq>(
+    current: &IndexMap<K, V>,
+    published: &IndexMap<K, V>,
+) -> IndexMap<K, V> {
+    current
+        .iter()
+        .filter(|(key, value)| published.get(*key) != Some(*value))
+        .map(|(key, value)| (*key, *value))
+        .collect()
+}
+
+fn missing_entries<K: Copy + Eq + Hash, V: Copy>(
+    published: &IndexMap<K, V>,
+    current: &IndexMap<K, V>,
+) -> IndexMap<K, V> {
+    published
+        .iter()
+        .filter(|(key, _)| !current.contains_key(*key))
+        .map(|(key, value)| (*key, *value))
+        .collect()
+}
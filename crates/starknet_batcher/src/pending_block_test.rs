@@ -0,0 +1,118 @@
+use std::time::Duration;
+
+use blockifier::state::cached_state::CommitmentStateDiff;
+use blockifier::test_utils::struct_impls::BlockInfoExt;
+use indexmap::{indexmap, IndexMap};
+use starknet_api::block::BlockInfo;
+use starknet_api::{contract_address, felt, storage_key};
+use starknet_batcher_types::batcher_types::{PendingBlockUpdate, PendingTxs};
+
+use crate::decided_block::thin_state_diff;
+use crate::pending_block::{
+    PendingBlockBroadcaster,
+    PendingBlockPublisher,
+    PENDING_BLOCK_UPDATES_CAPACITY,
+};
+
+fn new_block() -> PendingBlockUpdate {
+    PendingBlockUpdate::NewBlock(BlockInfo::create_for_testing())
+}
+
+fn txs() -> PendingBlockUpdate {
+    PendingBlockUpdate::Txs(PendingTxs::default())
+}
+
+#[test]
+fn subscription_catches_up_on_current_block() {
+    let broadcaster = PendingBlockBroadcaster::default();
+    broadcaster.publish(new_block());
+    broadcaster.publish(txs());
+    broadcaster.publish(PendingBlockUpdate::Cleared);
+    broadcaster.publish(new_block());
+    broadcaster.publish(txs());
+
+    // Only the updates of the current pending block are replayed.
+    let mut subscription = broadcaster.subscribe();
+    assert_eq!(subscription.next_updates().unwrap(), [new_block(), txs()]);
+
+    broadcaster.publish(txs());
+    assert_eq!(subscription.next_updates().unwrap(), [txs()]);
+    assert_eq!(subscription.next_updates().unwrap(), []);
+}
+
+#[test]
+fn clear_without_pending_block_is_not_published() {
+    let broadcaster = PendingBlockBroadcaster::default();
+    let mut subscription = broadcaster.subscribe();
+
+    broadcaster.publish(PendingBlockUpdate::Cleared);
+    assert_eq!(subscription.next_updates().unwrap(), []);
+}
+
+#[test]
+fn lagging_subscription() {
+    let broadcaster = PendingBlockBroadcaster::default();
+    let mut subscription = broadcaster.subscribe();
+    broadcaster.publish(new_block());
+
+    // The channel rounds its capacity up to a power of two.
+    for _ in 0..2 * PENDING_BLOCK_UPDATES_CAPACITY {
+        broadcaster.publish(txs());
+    }
+    assert_eq!(subscription.next_updates(), None);
+}
+
+#[test]
+fn reverted_state_change_is_published() {
+    let broadcaster = PendingBlockBroadcaster::default();
+    let mut publisher = PendingBlockPublisher::new(
+        broadcaster.clone(),
+        BlockInfo::create_for_testing(),
+        Duration::ZERO,
+    );
+    let mut subscription = broadcaster.subscribe();
+    let storage_diff = |value| CommitmentStateDiff {
+        storage_updates: indexmap! {
+            contract_address!(1_u8) => indexmap! { storage_key!(1_u8) => value },
+        },
+        ..Default::default()
+    };
+
+    publisher.add_txs(
+        &[],
+        &IndexMap::new(),
+        storage_diff(felt!(2_u8)),
+        Default::default(),
+        IndexMap::new(),
+    )
+    .unwrap();
+    // The entry is set back to its value before the block, so it drops out of the state diff.
+    let state_diff = CommitmentStateDiff::default();
+    let reverted_entries = publisher.reverted_entries(&state_diff);
+    assert_eq!(reverted_entries, storage_diff(felt!(2_u8)));
+    publisher.add_txs(
+        &[],
+        &IndexMap::new(),
+        state_diff,
+        storage_diff(felt!(1_u8)),
+        IndexMap::new(),
+    )
+    .unwrap();
+
+    let state_diffs: Vec<_> = subscription
+        .next_updates()
+        .unwrap()
+        .into_iter()
+        .map(|update| match update {
+            PendingBlockUpdate::Txs(PendingTxs { state_diff, .. }) => state_diff,
+            _ => panic!("Unexpected pending block update: {update:?}"),
+        })
+        .collect();
+    assert_eq!(
+        state_diffs,
+        [
+            thin_state_diff(storage_diff(felt!(2_u8)), &IndexMap::new()),
+            thin_state_diff(storage_diff(felt!(1_u8)), &IndexMap::new())
+        ]
+    );
+}
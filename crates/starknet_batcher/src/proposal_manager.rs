@@ -2,9 +2,9 @@ use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
 
 use async_trait::async_trait;
-use indexmap::IndexMap;
 use starknet_api::block_hash::state_diff_hash::calculate_state_diff_hash;
 use starknet_api::core::{ContractAddress, Nonce};
+use starknet_api::execution_resources::GasAmount;
 use starknet_api::state::ThinStateDiff;
 use starknet_api::transaction::TransactionHash;
 use starknet_batcher_types::batcher_types::{ProposalCommitment, ProposalId};
@@ -13,6 +13,7 @@ use tokio::sync::Mutex;
 use tracing::{debug, error, info, instrument, Instrument};
 
 use crate::block_builder::{BlockBuilderError, BlockBuilderTrait, BlockExecutionArtifacts};
+use crate::decided_block::{thin_state_diff, BlockContent};
 
 #[derive(Debug, Error)]
 pub enum GenerateProposalError {
@@ -103,6 +104,9 @@ pub(crate) struct ProposalManager {
     /// or validated).
     active_proposal: Arc<Mutex<Option<ProposalId>>>,
     active_proposal_task: Option<ProposalTask>,
+    /// The task of the last aborted proposal, which might still be shutting down. It is awaited
+    /// before starting the next proposal, so that proposals never execute concurrently.
+    aborted_proposal_task: Option<tokio::task::JoinHandle<()>>,
 
     executed_proposals: Arc<Mutex<HashMap<ProposalId, ProposalResult<ProposalOutput>>>>,
 }
@@ -114,7 +118,11 @@ pub struct ProposalOutput {
     pub state_diff: ThinStateDiff,
     pub commitment: ProposalCommitment,
     pub tx_hashes: HashSet<TransactionHash>,
+    // The transactions that failed execution, with the reasons they were rejected.
+    pub rejected_tx_hashes: HashMap<TransactionHash, String>,
     pub nonces: HashMap<ContractAddress, Nonce>,
+    pub l2_gas_used: GasAmount,
+    pub content: BlockContent,
 }
 
 #[async_trait]
@@ -128,6 +136,7 @@ impl ProposalManagerTrait for ProposalManager {
         mut block_builder: Box<dyn BlockBuilderTrait>,
         abort_signal_sender: tokio::sync::oneshot::Sender<()>,
     ) -> Result<(), GenerateProposalError> {
+        self.await_aborted_proposal().await;
         self.set_active_proposal(proposal_id).await?;
 
         info!("Starting generation of a new proposal with id {}.", proposal_id);
@@ -140,7 +149,7 @@ impl ProposalManagerTrait for ProposalManager {
                 let result = block_builder
                     .build_block()
                     .await
-                    .map(ProposalOutput::from)
+                    .and_then(ProposalOutput::try_from)
                     .map_err(|e| ProposalError::BlockBuilderError(Arc::new(e)));
 
                 // The proposal is done, clear the active proposal.
@@ -218,7 +227,7 @@ impl ProposalManagerTrait for ProposalManager {
     }
 
     // Aborts the proposal with the given ID, if active.
-    // Should be used in validate flow, if the consensus decides to abort the proposal.
+    // Used when consensus moves on from the proposal, e.g. when the round it belongs to ends.
     async fn abort_proposal(&mut self, proposal_id: ProposalId) {
         if *self.active_proposal.lock().await == Some(proposal_id) {
             self.abort_active_proposal().await;
@@ -237,6 +246,7 @@ impl ProposalManager {
         Self {
             active_proposal: Arc::new(Mutex::new(None)),
             active_proposal_task: None,
+            aborted_proposal_task: None,
             executed_proposals: Arc::new(Mutex::new(HashMap::new())),
         }
     }
@@ -266,41 +276,56 @@ impl ProposalManager {
     }
 
     // Ends the current active proposal.
-    // This call is non-blocking.
+    // This call is non-blocking, the proposal task is awaited before the next proposal starts.
     async fn abort_active_proposal(&mut self) {
         self.active_proposal.lock().await.take();
         if let Some(proposal_task) = self.active_proposal_task.take() {
             proposal_task.abort_signal_sender.send(()).ok();
+            self.aborted_proposal_task = Some(proposal_task.join_handle);
+        }
+    }
+
+    async fn await_aborted_proposal(&mut self) {
+        if let Some(join_handle) = self.aborted_proposal_task.take() {
+            debug!("Waiting for the aborted proposal to shut down.");
+            join_handle.await.ok();
         }
     }
 }
 
-impl From<BlockExecutionArtifacts> for ProposalOutput {
-    fn from(artifacts: BlockExecutionArtifacts) -> Self {
-        let commitment_state_diff = artifacts.commitment_state_diff;
+impl TryFrom<BlockExecutionArtifacts> for ProposalOutput {
+    type Error = BlockBuilderError;
+
+    fn try_from(artifacts: BlockExecutionArtifacts) -> Result<Self, Self::Error> {
         let nonces = HashMap::from_iter(
-            commitment_state_diff
+            artifacts
+                .commitment_state_diff
                 .address_to_nonce
                 .iter()
                 .map(|(address, nonce)| (*address, *nonce)),
         );
-
-        // TODO: Get these from the transactions.
-        let deployed_contracts = IndexMap::new();
-        let declared_classes = IndexMap::new();
-        let state_diff = ThinStateDiff {
-            deployed_contracts,
-            storage_diffs: commitment_state_diff.storage_updates,
-            declared_classes,
-            nonces: commitment_state_diff.address_to_nonce,
-            // TODO: Remove this when the structure of storage diffs changes.
-            deprecated_declared_classes: Vec::new(),
-            replaced_classes: IndexMap::new(),
-        };
+        let state_diff =
+            thin_state_diff(artifacts.commitment_state_diff, &artifacts.pre_block_class_hashes);
         let commitment =
             ProposalCommitment { state_diff_commitment: calculate_state_diff_hash(&state_diff) };
         let tx_hashes = HashSet::from_iter(artifacts.execution_infos.keys().copied());
-
-        Self { state_diff, commitment, tx_hashes, nonces }
+        let rejected_tx_hashes = artifacts
+            .rejected_txs
+            .iter()
+            .map(|rejected_tx| (rejected_tx.tx.tx_hash(), rejected_tx.error.to_string()))
+            .collect();
+
+        let l2_gas_used = artifacts.bouncer_weights.sierra_gas;
+        let content = BlockContent::new(artifacts.executed_txs, &artifacts.execution_infos)?;
+
+        Ok(Self {
+            state_diff,
+            commitment,
+            tx_hashes,
+            rejected_tx_hashes,
+            nonces,
+            l2_gas_used,
+            content,
+        })
     }
 }
@@ -1,9 +1,17 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
 use assert_matches::assert_matches;
 use rstest::{fixture, rstest};
 use starknet_api::executable_transaction::Transaction;
 use starknet_batcher_types::batcher_types::ProposalId;
 
-use crate::block_builder::{BlockBuilderTrait, BlockExecutionArtifacts, MockBlockBuilderTrait};
+use crate::block_builder::{
+    BlockBuilderError,
+    BlockBuilderTrait,
+    BlockExecutionArtifacts,
+    MockBlockBuilderTrait,
+};
 use crate::proposal_manager::{
     GenerateProposalError,
     ProposalError,
@@ -115,7 +123,7 @@ async fn take_proposal_result_no_active_proposal(mut proposal_manager: ProposalM
     spawn_proposal(&mut proposal_manager, ProposalId(0), mock_build_block()).await;
 
     let expected_proposal_output =
-        ProposalOutput::from(BlockExecutionArtifacts::create_for_testing());
+        ProposalOutput::try_from(BlockExecutionArtifacts::create_for_testing()).unwrap();
     assert_eq!(
         proposal_manager.take_proposal_result(ProposalId(0)).await.unwrap().unwrap(),
         expected_proposal_output
@@ -141,6 +149,40 @@ async fn abort_active_proposal(mut proposal_manager: ProposalManager) {
     assert!(!proposal_manager.await_active_proposal().await);
 }
 
+#[rstest]
+#[tokio::test]
+async fn next_proposal_waits_for_aborted_proposal(mut proposal_manager: ProposalManager) {
+    // Setup.
+    let aborted_proposal_done = Arc::new(AtomicBool::new(false));
+    let aborted_proposal_done_clone = aborted_proposal_done.clone();
+    let mut aborted_block_builder = MockBlockBuilderTrait::new();
+    aborted_block_builder.expect_build_block().times(1).return_once(move || {
+        aborted_proposal_done_clone.store(true, Ordering::SeqCst);
+        Err(BlockBuilderError::Aborted)
+    });
+    spawn_proposal_non_blocking(
+        &mut proposal_manager,
+        ProposalId(0),
+        Box::new(aborted_block_builder),
+    )
+    .await
+    .unwrap();
+
+    // Test.
+    proposal_manager.abort_proposal(ProposalId(0)).await;
+    spawn_proposal_non_blocking(&mut proposal_manager, ProposalId(1), mock_build_block())
+        .await
+        .unwrap();
+    assert!(aborted_proposal_done.load(Ordering::SeqCst));
+
+    assert!(proposal_manager.await_active_proposal().await);
+    assert_matches!(
+        proposal_manager.take_proposal_result(ProposalId(0)).await,
+        Some(Err(ProposalError::Aborted))
+    );
+    proposal_manager.take_proposal_result(ProposalId(1)).await.unwrap().unwrap();
+}
+
 #[rstest]
 #[tokio::test]
 async fn reset(mut proposal_manager: ProposalManager) {
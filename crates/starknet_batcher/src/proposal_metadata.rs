@@ -0,0 +1,130 @@
+use std::collections::HashMap;
+use std::fs;
+use std::io::{self, ErrorKind};
+use std::path::{Path, PathBuf};
+
+use async_trait::async_trait;
+#[cfg(test)]
+use mockall::automock;
+use serde::{Deserialize, Serialize};
+use starknet_api::block::{BlockHashAndNumber, BlockInfo, BlockNumber};
+use starknet_api::executable_transaction::Transaction;
+use starknet_batcher_types::batcher_types::ProposalId;
+use starknet_sequencer_infra::file_utils::{
+    read_json_file,
+    remove_file,
+    write_json_file_atomically,
+};
+
+/// Everything needed to execute a proposal again, e.g. after the batcher restarted.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ProposalMetadata {
+    pub block_info: BlockInfo,
+    pub retrospective_block_hash: Option<BlockHashAndNumber>,
+    // The transactions of the proposal, in execution order.
+    pub txs: Vec<Transaction>,
+}
+
+/// Persists the metadata of the finished proposals of the active height.
+#[cfg_attr(test, automock)]
+#[async_trait]
+pub trait ProposalMetadataStorageTrait: Send + Sync {
+    /// Stores the metadata of a proposal of the given height, discarding the proposals of any
+    /// other height.
+    async fn store(
+        &mut self,
+        height: BlockNumber,
+        proposal_id: ProposalId,
+        metadata: &ProposalMetadata,
+    ) -> io::Result<()>;
+
+    /// Returns the stored proposals of the given height.
+    async fn load(&self, height: BlockNumber) -> io::Result<HashMap<ProposalId, ProposalMetadata>>;
+}
+
+/// Keeps each proposal in a JSON file of its own, named after its height and ID, so that storing a
+/// proposal doesn't rewrite the others. The files are accessed on a blocking thread.
+pub struct FileProposalMetadataStorage {
+    pub dir: PathBuf,
+}
+
+#[async_trait]
+impl ProposalMetadataStorageTrait for FileProposalMetadataStorage {
+    async fn store(
+        &mut self,
+        height: BlockNumber,
+        proposal_id: ProposalId,
+        metadata: &ProposalMetadata,
+    ) -> io::Result<()> {
+        let dir = self.dir.clone();
+        let metadata = metadata.clone();
+        tokio::task::spawn_blocking(move || store_proposal(&dir, height, proposal_id, &metadata))
+            .await
+            .map_err(io::Error::other)?
+    }
+
+    async fn load(&self, height: BlockNumber) -> io::Result<HashMap<ProposalId, ProposalMetadata>> {
+        let dir = self.dir.clone();
+        tokio::task::spawn_blocking(move || load_proposals(&dir, height))
+            .await
+            .map_err(io::Error::other)?
+    }
+}
+
+fn store_proposal(
+    dir: &Path,
+    height: BlockNumber,
+    proposal_id: ProposalId,
+    metadata: &ProposalMetadata,
+) -> io::Result<()> {
+    fs::create_dir_all(dir)?;
+    for (stored_height, _, path) in stored_proposals(dir)? {
+        if stored_height != height {
+            remove_file(&path)?;
+        }
+    }
+    write_json_file_atomically(&dir.join(format!("{}_{}.json", height.0, proposal_id.0)), metadata)
+}
+
+fn load_proposals(
+    dir: &Path,
+    height: BlockNumber,
+) -> io::Result<HashMap<ProposalId, ProposalMetadata>> {
+    let mut proposals = HashMap::new();
+    for (stored_height, proposal_id, path) in stored_proposals(dir)? {
+        if stored_height != height {
+            continue;
+        }
+        if let Some(metadata) = read_json_file(&path)? {
+            proposals.insert(proposal_id, metadata);
+        }
+    }
+    Ok(proposals)
+}
+
+// Returns the height, ID and file of each stored proposal.
+fn stored_proposals(dir: &Path) -> io::Result<Vec<(BlockNumber, ProposalId, PathBuf)>> {
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(err) if err.kind() == ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(err) => return Err(err),
+    };
+    let mut proposals = Vec::new();
+    for entry in entries {
+        let path = entry?.path();
+        if let Some((height, proposal_id)) = parse_proposal_file_name(&path) {
+            proposals.push((height, proposal_id, path));
+        }
+    }
+    Ok(proposals)
+}
+
+// Returns `None` for files which don't hold a proposal, e.g., the temporary file of an interrupted
+// write.
+fn parse_proposal_file_name(path: &Path) -> Option<(BlockNumber, ProposalId)> {
+    if path.extension()? != "json" {
+        return None;
+    }
+    let (height, proposal_id) = path.file_stem()?.to_str()?.split_once('_')?;
+    Some((BlockNumber(height.parse().ok()?), ProposalId(proposal_id.parse().ok()?)))
+}
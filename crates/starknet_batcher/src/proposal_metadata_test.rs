@@ -0,0 +1,67 @@
+use std::collections::HashMap;
+
+use blockifier::test_utils::struct_impls::BlockInfoExt;
+use starknet_api::block::{BlockInfo, BlockNumber};
+use starknet_batcher_types::batcher_types::ProposalId;
+
+use crate::proposal_metadata::{
+    FileProposalMetadataStorage,
+    ProposalMetadata,
+    ProposalMetadataStorageTrait,
+};
+use crate::test_utils::test_txs;
+
+const HEIGHT: BlockNumber = BlockNumber(3);
+
+fn metadata(n_txs: usize) -> ProposalMetadata {
+    ProposalMetadata {
+        block_info: BlockInfo { block_number: HEIGHT, ..BlockInfo::create_for_testing() },
+        retrospective_block_hash: None,
+        txs: test_txs(0..n_txs),
+    }
+}
+
+#[tokio::test]
+async fn load_without_stored_proposals() {
+    let temp_dir = tempfile::tempdir().await.unwrap();
+    let storage = FileProposalMetadataStorage { dir: temp_dir.path().join("proposals") };
+
+    assert_eq!(storage.load(HEIGHT).await.unwrap(), HashMap::new());
+}
+
+#[tokio::test]
+async fn store_and_load() {
+    // Setup.
+    let temp_dir = tempfile::tempdir().await.unwrap();
+    let mut storage = FileProposalMetadataStorage { dir: temp_dir.path().join("proposals") };
+
+    // Test.
+    storage.store(HEIGHT, ProposalId(0), &metadata(1)).await.unwrap();
+    storage.store(HEIGHT, ProposalId(1), &metadata(2)).await.unwrap();
+    // Storing a proposal again replaces its metadata.
+    storage.store(HEIGHT, ProposalId(0), &metadata(3)).await.unwrap();
+
+    assert_eq!(
+        storage.load(HEIGHT).await.unwrap(),
+        HashMap::from([(ProposalId(0), metadata(3)), (ProposalId(1), metadata(2))])
+    );
+    assert_eq!(storage.load(HEIGHT.unchecked_next()).await.unwrap(), HashMap::new());
+}
+
+#[tokio::test]
+async fn storing_a_new_height_discards_the_previous_one() {
+    // Setup.
+    let temp_dir = tempfile::tempdir().await.unwrap();
+    let mut storage = FileProposalMetadataStorage { dir: temp_dir.path().join("proposals") };
+    storage.store(HEIGHT, ProposalId(0), &metadata(1)).await.unwrap();
+
+    // Test.
+    let next_height = HEIGHT.unchecked_next();
+    storage.store(next_height, ProposalId(1), &metadata(2)).await.unwrap();
+
+    assert_eq!(storage.load(HEIGHT).await.unwrap(), HashMap::new());
+    assert_eq!(
+        storage.load(next_height).await.unwrap(),
+        HashMap::from([(ProposalId(1), metadata(2))])
+    );
+}
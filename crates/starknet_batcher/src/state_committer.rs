@@ -0,0 +1,219 @@
+use std::collections::HashMap;
+use std::slice;
+use std::sync::LazyLock;
+
+use papyrus_storage::state_tries::StateTriesRoots;
+use papyrus_storage::{StorageError, StorageResult};
+use starknet_api::block::BlockNumber;
+use starknet_api::core::{ascii_as_felt, GlobalRoot};
+use starknet_api::hash::StarkHash;
+use starknet_api::state::ThinStateDiff;
+use starknet_committer::block_committer::commit::commit_state_diff;
+use starknet_committer::block_committer::errors::BlockCommitmentError;
+use starknet_committer::block_committer::input::{
+    ConfigImpl,
+    ContractAddress,
+    StarknetStorageKey,
+    StarknetStorageValue,
+    StateDiff,
+};
+use starknet_committer::patricia_merkle_tree::types::{ClassHash, CompiledClassHash, Nonce};
+use starknet_patricia::felt::Felt;
+use starknet_patricia::hash::hash_trait::HashOutput;
+use starknet_patricia::storage::map_storage::MapStorage;
+use starknet_patricia::storage::storage_trait::{Storage, StorageKey, StorageValue};
+use starknet_types_core::hash::{Poseidon, StarkHash as _};
+use tracing::error;
+use tracing::level_filters::LevelFilter;
+
+use crate::batcher::BatcherStorageReaderTrait;
+
+static STARKNET_STATE_V0: LazyLock<StarkHash> = LazyLock::new(|| {
+    ascii_as_felt("STARKNET_STATE_V0").expect("ascii_as_felt failed for 'STARKNET_STATE_V0'")
+});
+
+/// Maintains the roots of the Patricia tries of the Starknet state and computes the global state
+/// root of each committed block.
+/// The facts of the tries are read from the batcher's storage, where the new facts of each block
+/// are stored along with the block itself.
+#[derive(Debug, Default)]
+pub struct StateCommitter {
+    roots: StateTriesRoots,
+    // The height of the next block to commit.
+    next_height: BlockNumber,
+}
+
+/// The tries after applying the state diff of a block, to be stored along with the block.
+#[derive(Debug)]
+pub struct StateCommitment {
+    pub global_root: GlobalRoot,
+    pub roots: StateTriesRoots,
+    pub new_facts: Vec<(Vec<u8>, Vec<u8>)>,
+}
+
+impl StateCommitter {
+    /// Continues from the tries of the last block whose tries are stored.
+    pub fn load(storage_reader: &dyn BatcherStorageReaderTrait) -> StorageResult<Self> {
+        let next_height = storage_reader.state_tries_height()?;
+        let Some(last_height) = next_height.prev() else {
+            return Ok(Self::default());
+        };
+        let roots = storage_reader.state_tries_roots(last_height)?.ok_or_else(|| {
+            StorageError::DBInconsistency {
+                msg: format!("Missing the roots of the state tries of height {last_height}."),
+            }
+        })?;
+        Ok(Self { roots, next_height })
+    }
+
+    pub fn next_height(&self) -> BlockNumber {
+        self.next_height
+    }
+
+    /// Computes the tries after applying the state diff of the next block. Once they are stored
+    /// along with the block, `apply` moves the committer to the next block.
+    pub async fn prepare(
+        &self,
+        storage_reader: &dyn BatcherStorageReaderTrait,
+        state_diff: &ThinStateDiff,
+    ) -> Result<StateCommitment, BlockCommitmentError> {
+        // Only the modified paths are read from the facts, and only the new nodes are written.
+        let mut facts = Facts { storage_reader, new_facts: MapStorage::default() };
+        let filled_forest = commit_state_diff(
+            &facts,
+            &committer_state_diff(state_diff),
+            HashOutput(Felt(self.roots.contracts_trie_root)),
+            HashOutput(Felt(self.roots.classes_trie_root)),
+            &ConfigImpl::new(false, LevelFilter::INFO),
+        )
+        .await?;
+        filled_forest.write_to_storage(&mut facts);
+        let contracts_trie_root_hash = filled_forest.get_contract_root_hash();
+        let classes_trie_root_hash = filled_forest.get_compiled_class_root_hash();
+
+        Ok(StateCommitment {
+            global_root: global_root(contracts_trie_root_hash, classes_trie_root_hash),
+            roots: StateTriesRoots {
+                contracts_trie_root: contracts_trie_root_hash.0.into(),
+                classes_trie_root: classes_trie_root_hash.0.into(),
+            },
+            new_facts: facts
+                .new_facts
+                .storage
+                .into_iter()
+                .map(|(StorageKey(key), StorageValue(value))| (key, value))
+                .collect(),
+        })
+    }
+
+    /// Moves to the next block, once the tries computed by `prepare` are stored.
+    pub fn apply(&mut self, state_commitment: &StateCommitment) {
+        self.roots = state_commitment.roots;
+        self.next_height = self.next_height.unchecked_next();
+    }
+}
+
+// The facts of the tries: the stored ones, along with the ones added by the block being committed.
+struct Facts<'a> {
+    storage_reader: &'a dyn BatcherStorageReaderTrait,
+    new_facts: MapStorage,
+}
+
+impl Storage for Facts<'_> {
+    fn get(&self, key: &StorageKey) -> Option<StorageValue> {
+        self.mget(slice::from_ref(key)).pop().flatten()
+    }
+
+    fn set(&mut self, key: StorageKey, value: StorageValue) -> Option<StorageValue> {
+        self.new_facts.set(key, value)
+    }
+
+    // A fact that fails to be read is missing, which fails the commitment.
+    fn mget(&self, keys: &[StorageKey]) -> Vec<Option<StorageValue>> {
+        let stored_keys: Vec<_> = keys
+            .iter()
+            .filter(|key| !self.new_facts.storage.contains_key(*key))
+            .map(|StorageKey(key)| key.clone())
+            .collect();
+        let stored_values =
+            self.storage_reader.state_tries_facts(&stored_keys).unwrap_or_else(|err| {
+                error!("Failed to read the facts of the state tries: {}", err);
+                vec![None; stored_keys.len()]
+            });
+        let mut stored_values = stored_values.into_iter();
+        keys.iter()
+            .map(|key| match self.new_facts.get(key) {
+                Some(value) => Some(value),
+                None => stored_values.next().flatten().map(StorageValue),
+            })
+            .collect()
+    }
+
+    fn mset(&mut self, key_to_value: HashMap<StorageKey, StorageValue>) {
+        self.new_facts.mset(key_to_value)
+    }
+
+    // Stored facts are never deleted, as they may be shared by the tries of several blocks.
+    fn delete(&mut self, key: &StorageKey) -> Option<StorageValue> {
+        self.new_facts.delete(key)
+    }
+}
+
+/// The global root commits to both tries, except before any class was declared, where it is the
+/// root of the contracts trie alone.
+fn global_root(
+    contracts_trie_root_hash: HashOutput,
+    classes_trie_root_hash: HashOutput,
+) -> GlobalRoot {
+    if classes_trie_root_hash == HashOutput::default() {
+        return GlobalRoot(contracts_trie_root_hash.0.into());
+    }
+    GlobalRoot(Poseidon::hash_array(&[
+        *STARKNET_STATE_V0,
+        contracts_trie_root_hash.0.into(),
+        classes_trie_root_hash.0.into(),
+    ]))
+}
+
+fn committer_state_diff(state_diff: &ThinStateDiff) -> StateDiff {
+    StateDiff {
+        address_to_class_hash: state_diff
+            .deployed_contracts
+            .iter()
+            .chain(state_diff.replaced_classes.iter())
+            .map(|(address, class_hash)| {
+                (ContractAddress(Felt(*address.0.key())), ClassHash(Felt(class_hash.0)))
+            })
+            .collect(),
+        address_to_nonce: state_diff
+            .nonces
+            .iter()
+            .map(|(address, nonce)| (ContractAddress(Felt(*address.0.key())), Nonce(Felt(nonce.0))))
+            .collect(),
+        class_hash_to_compiled_class_hash: state_diff
+            .declared_classes
+            .iter()
+            .map(|(class_hash, compiled_class_hash)| {
+                (ClassHash(Felt(class_hash.0)), CompiledClassHash(Felt(compiled_class_hash.0)))
+            })
+            .collect(),
+        storage_updates: state_diff
+            .storage_diffs
+            .iter()
+            .map(|(address, storage_diff)| {
+                (
+                    ContractAddress(Felt(*address.0.key())),
+                    storage_diff
+                        .iter()
+                        .map(|(key, value)| {
+                            (
+                                StarknetStorageKey(Felt(*key.0.key())),
+                                StarknetStorageValue(Felt(*value)),
+                            )
+                        })
+                        .collect(),
+                )
+            })
+            .collect(),
+    }
+}
@@ -0,0 +1,112 @@
+use indexmap::indexmap;
+use papyrus_storage::test_utils::get_test_storage;
+use papyrus_storage::{StorageReader, StorageWriter};
+use starknet_api::block::BlockNumber;
+use starknet_api::core::{CompiledClassHash, GlobalRoot, Nonce};
+use starknet_api::state::ThinStateDiff;
+use starknet_api::{class_hash, contract_address, felt, storage_key};
+
+use crate::batcher::BatcherStorageWriterTrait;
+use crate::state_committer::StateCommitter;
+
+fn deploy_diff() -> ThinStateDiff {
+    ThinStateDiff {
+        deployed_contracts: indexmap! { contract_address!("0x10") => class_hash!("0x20") },
+        nonces: indexmap! { contract_address!("0x10") => Nonce(felt!("0x1")) },
+        ..Default::default()
+    }
+}
+
+fn storage_and_declare_diff() -> ThinStateDiff {
+    ThinStateDiff {
+        storage_diffs: indexmap! {
+            contract_address!("0x10") => indexmap! { storage_key!("0x30") => felt!("0x40") },
+        },
+        declared_classes: indexmap! { class_hash!("0x20") => CompiledClassHash(felt!("0x50")) },
+        ..Default::default()
+    }
+}
+
+// Commits the state diff as the next block, storing its tries.
+async fn commit(
+    state_committer: &mut StateCommitter,
+    (storage_reader, storage_writer): &mut (StorageReader, StorageWriter),
+    state_diff: &ThinStateDiff,
+) -> GlobalRoot {
+    let state_commitment = state_committer.prepare(&*storage_reader, state_diff).await.unwrap();
+    storage_writer.commit_state_tries(state_committer.next_height(), &state_commitment).unwrap();
+    state_committer.apply(&state_commitment);
+    state_commitment.global_root
+}
+
+#[tokio::test]
+async fn empty_state_diff() {
+    let (mut storage, _temp_dir) = get_test_storage();
+    let mut state_committer = StateCommitter::default();
+
+    assert_eq!(
+        commit(&mut state_committer, &mut storage, &ThinStateDiff::default()).await,
+        GlobalRoot::default()
+    );
+}
+
+#[tokio::test]
+async fn root_depends_only_on_the_resulting_state() {
+    // Setup.
+    let mut merged_diff = deploy_diff();
+    merged_diff.storage_diffs = storage_and_declare_diff().storage_diffs;
+    merged_diff.declared_classes = storage_and_declare_diff().declared_classes;
+
+    let (mut storage, _temp_dir) = get_test_storage();
+    let (mut merged_storage, _merged_temp_dir) = get_test_storage();
+    let mut state_committer = StateCommitter::default();
+    let mut merged_state_committer = StateCommitter::default();
+
+    // Test.
+    let first_root = commit(&mut state_committer, &mut storage, &deploy_diff()).await;
+    assert_ne!(first_root, GlobalRoot::default());
+
+    let second_root = commit(&mut state_committer, &mut storage, &storage_and_declare_diff()).await;
+    assert_ne!(second_root, first_root);
+    assert_eq!(state_committer.next_height(), BlockNumber(2));
+
+    assert_eq!(
+        commit(&mut merged_state_committer, &mut merged_storage, &merged_diff).await,
+        second_root
+    );
+}
+
+#[tokio::test]
+async fn loaded_state_committer_continues_from_the_stored_tries() {
+    // Setup.
+    let (mut storage, _temp_dir) = get_test_storage();
+    let mut state_committer = StateCommitter::default();
+    commit(&mut state_committer, &mut storage, &deploy_diff()).await;
+
+    // Test.
+    let mut loaded_state_committer = StateCommitter::load(&storage.0).unwrap();
+
+    // Assert.
+    assert_eq!(loaded_state_committer.next_height(), BlockNumber(1));
+    let expected_root =
+        state_committer.prepare(&storage.0, &storage_and_declare_diff()).await.unwrap().global_root;
+    assert_eq!(
+        commit(&mut loaded_state_committer, &mut storage, &storage_and_declare_diff()).await,
+        expected_root
+    );
+}
+
+#[tokio::test]
+async fn facts_are_read_from_the_storage() {
+    // Setup: the tries of the first block are not stored.
+    let ((storage_reader, _), _temp_dir) = get_test_storage();
+    let mut state_committer = StateCommitter::default();
+    let state_commitment = state_committer.prepare(&storage_reader, &deploy_diff()).await.unwrap();
+    state_committer.apply(&state_commitment);
+
+    // Test.
+    let result = state_committer.prepare(&storage_reader, &storage_and_declare_diff()).await;
+
+    // Assert: the second block modifies the contract deployed in the first one.
+    assert!(result.is_err());
+}
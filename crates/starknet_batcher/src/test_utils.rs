@@ -25,7 +25,10 @@ impl BlockExecutionArtifacts {
     pub fn create_for_testing() -> Self {
         Self {
             execution_infos: IndexMap::default(),
+            executed_txs: Vec::new(),
+            rejected_txs: Vec::new(),
             commitment_state_diff: CommitmentStateDiff::default(),
+            pre_block_class_hashes: IndexMap::default(),
             visited_segments_mapping: VisitedSegmentsMapping::default(),
             bouncer_weights: BouncerWeights::empty(),
         }
@@ -5,7 +5,7 @@ use blockifier::blockifier::transaction_executor::{
 };
 use blockifier::bouncer::BouncerWeights;
 use blockifier::state::cached_state::CommitmentStateDiff;
-use blockifier::state::state_api::StateReader;
+use blockifier::state::state_api::{StateReader, StateResult};
 use blockifier::transaction::objects::TransactionExecutionInfo;
 use blockifier::transaction::transaction_execution::Transaction as BlockifierTransaction;
 #[cfg(test)]
@@ -17,6 +17,11 @@ pub trait TransactionExecutorTrait: Send {
         &mut self,
         txs: &[BlockifierTransaction],
     ) -> Vec<TransactionExecutorResult<TransactionExecutionInfo>>;
+    fn get_state_diff(&mut self) -> TransactionExecutorResult<CommitmentStateDiff>;
+    fn get_pre_block_values(
+        &self,
+        entries: &CommitmentStateDiff,
+    ) -> TransactionExecutorResult<CommitmentStateDiff>;
     fn close_block(
         &mut self,
     ) -> TransactionExecutorResult<(CommitmentStateDiff, VisitedSegmentsMapping, BouncerWeights)>;
@@ -30,6 +35,55 @@ impl<S: StateReader + Send + Sync> TransactionExecutorTrait for TransactionExecu
     ) -> Vec<TransactionExecutorResult<TransactionExecutionInfo>> {
         self.execute_txs(txs)
     }
+    /// Returns the state diff of the transactions executed so far, leaving the block open.
+    fn get_state_diff(&mut self) -> TransactionExecutorResult<CommitmentStateDiff> {
+        Ok(self
+            .block_state
+            .as_mut()
+            .expect("The block state should be set between executions.")
+            .to_state_diff()?
+            .state_maps
+            .into())
+    }
+    /// Returns the values of the given entries before the block.
+    fn get_pre_block_values(
+        &self,
+        entries: &CommitmentStateDiff,
+    ) -> TransactionExecutorResult<CommitmentStateDiff> {
+        let state = &self
+            .block_state
+            .as_ref()
+            .expect("The block state should be set between executions.")
+            .state;
+        Ok(CommitmentStateDiff {
+            address_to_class_hash: entries
+                .address_to_class_hash
+                .keys()
+                .map(|address| Ok((*address, state.get_class_hash_at(*address)?)))
+                .collect::<StateResult<_>>()?,
+            address_to_nonce: entries
+                .address_to_nonce
+                .keys()
+                .map(|address| Ok((*address, state.get_nonce_at(*address)?)))
+                .collect::<StateResult<_>>()?,
+            storage_updates: entries
+                .storage_updates
+                .iter()
+                .map(|(address, storage)| {
+                    let storage = storage
+                        .keys()
+                        .map(|key| Ok((*key, state.get_storage_at(*address, *key)?)))
+                        .collect::<StateResult<_>>()?;
+                    Ok((*address, storage))
+                })
+                .collect::<StateResult<_>>()?,
+            class_hash_to_compiled_class_hash: entries
+                .class_hash_to_compiled_class_hash
+                .keys()
+                .map(|class_hash| Ok((*class_hash, state.get_compiled_class_hash(*class_hash)?)))
+                .collect::<StateResult<_>>()?,
+        })
+    }
     /// Finalizes the block creation and returns the commitment state diff, visited
     /// segments mapping and bouncer.
     fn close_block(
@@ -0,0 +1,310 @@
+use std::cmp::min;
+use std::collections::{BTreeMap, HashMap, VecDeque};
+
+use async_trait::async_trait;
+use indexmap::IndexMap;
+use papyrus_config::dumping::{ser_param, SerializeConfig};
+use papyrus_config::{ParamPath, ParamPrivacyInput, SerializedParam};
+use serde::{Deserialize, Serialize};
+use starknet_api::core::ContractAddress;
+use starknet_api::executable_transaction::AccountTransaction;
+use starknet_api::execution_resources::GasAmount;
+use starknet_mempool_types::communication::{MempoolClientResult, SharedMempoolClient};
+use validator::Validate;
+
+// The number of transactions the round-robin policy keeps at hand to interleave between accounts,
+// as a multiple of the number of transactions requested.
+const ROUND_ROBIN_LOOKAHEAD_FACTOR: usize = 2;
+// The maximal number of transactions the sender share cap holds back, as a multiple of the number
+// of transactions requested; once reached, no more transactions are fetched in the same call.
+const SENDER_SHARE_CAP_LOOKAHEAD_FACTOR: usize = 2;
+
+/// The order in which the proposer takes transactions from the mempool.
+#[derive(Clone, Copy, Debug, Default, Serialize, Deserialize, PartialEq)]
+pub enum TransactionOrderingPolicyType {
+    /// Highest tip first, as prioritized by the mempool.
+    #[default]
+    FeePriority,
+    /// Oldest submission first.
+    Fifo,
+    /// One transaction of each account in turn, so that accounts with many transactions can't
+    /// crowd out the others.
+    RoundRobin,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, Validate, PartialEq)]
+pub struct TransactionOrderingConfig {
+    pub policy: TransactionOrderingPolicyType,
+    #[validate(range(min = 1, max = 100))]
+    pub max_sender_share_percent: u8,
+}
+
+impl Default for TransactionOrderingConfig {
+    fn default() -> Self {
+        Self { policy: TransactionOrderingPolicyType::default(), max_sender_share_percent: 100 }
+    }
+}
+
+impl SerializeConfig for TransactionOrderingConfig {
+    fn dump(&self) -> BTreeMap<ParamPath, SerializedParam> {
+        BTreeMap::from([
+            ser_param(
+                "policy",
+                &self.policy,
+                "The order in which transactions are taken from the mempool into a proposal: \
+                 FeePriority, Fifo or RoundRobin.",
+                ParamPrivacyInput::Public,
+            ),
+            ser_param(
+                "max_sender_share_percent",
+                &self.max_sender_share_percent,
+                "The maximum percentage of the block's L2 gas capacity that the mempool \
+                 transactions of a single sender may use while other senders have transactions to \
+                 offer. Transactions are weighed by their L2 gas bound.",
+                ParamPrivacyInput::Public,
+            ),
+        ])
+    }
+}
+
+/// Decides which of the mempool transactions are proposed next, and in which order.
+/// The transactions of each account must be kept in nonce order.
+#[async_trait]
+pub trait TransactionOrderingPolicy: Send {
+    /// Returns up to `n_txs` transactions to append to the proposal.
+    async fn get_txs(
+        &mut self,
+        mempool_client: &SharedMempoolClient,
+        n_txs: usize,
+    ) -> MempoolClientResult<Vec<AccountTransaction>>;
+}
+
+pub fn create_transaction_ordering_policy(
+    config: &TransactionOrderingConfig,
+    block_max_l2_gas: GasAmount,
+) -> Box<dyn TransactionOrderingPolicy> {
+    let policy: Box<dyn TransactionOrderingPolicy> = match config.policy {
+        TransactionOrderingPolicyType::FeePriority => Box::new(FeePriorityPolicy),
+        TransactionOrderingPolicyType::Fifo => Box::new(FifoPolicy),
+        TransactionOrderingPolicyType::RoundRobin => Box::new(RoundRobinPolicy::default()),
+    };
+    if config.max_sender_share_percent >= 100 {
+        return policy;
+    }
+    Box::new(SenderShareCap::new(policy, config.max_sender_share_percent, block_max_l2_gas))
+}
+
+pub struct FeePriorityPolicy;
+
+#[async_trait]
+impl TransactionOrderingPolicy for FeePriorityPolicy {
+    async fn get_txs(
+        &mut self,
+        mempool_client: &SharedMempoolClient,
+        n_txs: usize,
+    ) -> MempoolClientResult<Vec<AccountTransaction>> {
+        mempool_client.get_txs(n_txs).await
+    }
+}
+
+pub struct FifoPolicy;
+
+#[async_trait]
+impl TransactionOrderingPolicy for FifoPolicy {
+    async fn get_txs(
+        &mut self,
+        mempool_client: &SharedMempoolClient,
+        n_txs: usize,
+    ) -> MempoolClientResult<Vec<AccountTransaction>> {
+        mempool_client.get_oldest_txs(n_txs).await
+    }
+}
+
+/// Takes a single transaction of each account in turn, starting with the account whose turn came
+/// least recently. Transactions fetched but not yet proposed are kept for the following calls.
+#[derive(Default)]
+pub struct RoundRobinPolicy {
+    // The fetched transactions of each account, ordered by the accounts' turns.
+    pending_txs: PendingTransactions,
+}
+
+#[async_trait]
+impl TransactionOrderingPolicy for RoundRobinPolicy {
+    async fn get_txs(
+        &mut self,
+        mempool_client: &SharedMempoolClient,
+        n_txs: usize,
+    ) -> MempoolClientResult<Vec<AccountTransaction>> {
+        let n_txs_to_fetch =
+            (n_txs * ROUND_ROBIN_LOOKAHEAD_FACTOR).saturating_sub(self.pending_txs.len());
+        if n_txs_to_fetch > 0 {
+            self.pending_txs.extend(mempool_client.get_txs(n_txs_to_fetch).await?);
+        }
+
+        let mut txs = Vec::with_capacity(n_txs);
+        while txs.len() < n_txs && !self.pending_txs.is_empty() {
+            txs.push(self.pending_txs.pop_account_tx(0));
+        }
+        Ok(txs)
+    }
+}
+
+/// Limits the share of the block's L2 gas capacity that the transactions of a single sender may
+/// use, on top of another policy. Since transactions are taken before they're executed, each is
+/// weighed by its L2 gas bound. Transactions of senders that reached their share are held back for
+/// later calls, and are proposed regardless of the cap only when no other sender has transactions
+/// to offer, so that the block doesn't go empty. The number of held back transactions is bounded,
+/// so that a sender flooding the mempool can't make the proposer drain it.
+pub struct SenderShareCap {
+    policy: Box<dyn TransactionOrderingPolicy>,
+    max_l2_gas_per_sender: GasAmount,
+    l2_gas_per_sender: HashMap<ContractAddress, GasAmount>,
+    held_back_txs: PendingTransactions,
+}
+
+impl SenderShareCap {
+    pub fn new(
+        policy: Box<dyn TransactionOrderingPolicy>,
+        max_sender_share_percent: u8,
+        block_max_l2_gas: GasAmount,
+    ) -> Self {
+        let max_l2_gas_per_sender =
+            u128::from(block_max_l2_gas.0) * u128::from(min(max_sender_share_percent, 100)) / 100;
+        Self {
+            policy,
+            max_l2_gas_per_sender: GasAmount(
+                max_l2_gas_per_sender.try_into().expect("A share of a u64 fits in a u64."),
+            ),
+            l2_gas_per_sender: HashMap::new(),
+            held_back_txs: PendingTransactions::default(),
+        }
+    }
+
+    fn is_within_share(&self, tx: &AccountTransaction) -> bool {
+        let sender_l2_gas =
+            self.l2_gas_per_sender.get(&tx.contract_address()).copied().unwrap_or_default();
+        sender_l2_gas
+            .checked_add(l2_gas_bound(tx))
+            .is_some_and(|l2_gas| l2_gas <= self.max_l2_gas_per_sender)
+    }
+
+    fn take(&mut self, tx: AccountTransaction, txs: &mut Vec<AccountTransaction>) {
+        let sender_l2_gas = self.l2_gas_per_sender.entry(tx.contract_address()).or_default();
+        *sender_l2_gas = sender_l2_gas.checked_add(l2_gas_bound(&tx)).unwrap_or(GasAmount::MAX);
+        txs.push(tx);
+    }
+
+    fn take_held_back_txs(
+        &mut self,
+        txs: &mut Vec<AccountTransaction>,
+        n_txs: usize,
+        enforce_share: bool,
+    ) {
+        while txs.len() < n_txs {
+            let Some(index) =
+                self.held_back_txs.position(|tx| !enforce_share || self.is_within_share(tx))
+            else {
+                break;
+            };
+            let tx = self.held_back_txs.pop_account_tx(index);
+            self.take(tx, txs);
+        }
+    }
+}
+
+#[async_trait]
+impl TransactionOrderingPolicy for SenderShareCap {
+    async fn get_txs(
+        &mut self,
+        mempool_client: &SharedMempoolClient,
+        n_txs: usize,
+    ) -> MempoolClientResult<Vec<AccountTransaction>> {
+        let max_n_held_back_txs = n_txs * SENDER_SHARE_CAP_LOOKAHEAD_FACTOR;
+        let mut txs = Vec::with_capacity(n_txs);
+        loop {
+            self.take_held_back_txs(&mut txs, n_txs, true);
+            let n_txs_to_fetch = min(
+                n_txs - txs.len(),
+                max_n_held_back_txs.saturating_sub(self.held_back_txs.len()),
+            );
+            if n_txs_to_fetch == 0 {
+                break;
+            }
+
+            let fetched_txs = self.policy.get_txs(mempool_client, n_txs_to_fetch).await?;
+            if fetched_txs.is_empty() {
+                break;
+            }
+            for tx in fetched_txs {
+                let sender = tx.contract_address();
+                // Once a transaction of the sender is held back, so are its following ones, to
+                // keep them in nonce order.
+                if txs.len() < n_txs
+                    && !self.held_back_txs.contains_account(sender)
+                    && self.is_within_share(&tx)
+                {
+                    self.take(tx, &mut txs);
+                } else {
+                    self.held_back_txs.push(tx);
+                }
+            }
+        }
+
+        if txs.is_empty() {
+            self.take_held_back_txs(&mut txs, n_txs, false);
+        }
+        Ok(txs)
+    }
+}
+
+fn l2_gas_bound(tx: &AccountTransaction) -> GasAmount {
+    tx.resource_bounds().get_l2_bounds().max_amount
+}
+
+/// Transactions grouped by account, in nonce order. Accounts are ordered by their turn, starting
+/// with the account whose transaction was taken least recently.
+#[derive(Default)]
+struct PendingTransactions(IndexMap<ContractAddress, VecDeque<AccountTransaction>>);
+
+impl PendingTransactions {
+    fn len(&self) -> usize {
+        self.0.values().map(VecDeque::len).sum()
+    }
+
+    fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    fn contains_account(&self, address: ContractAddress) -> bool {
+        self.0.contains_key(&address)
+    }
+
+    fn push(&mut self, tx: AccountTransaction) {
+        self.0.entry(tx.contract_address()).or_default().push_back(tx);
+    }
+
+    fn extend(&mut self, txs: impl IntoIterator<Item = AccountTransaction>) {
+        for tx in txs {
+            self.push(tx);
+        }
+    }
+
+    /// Returns the turn of the first account whose next transaction satisfies the given predicate.
+    fn position(&self, predicate: impl Fn(&AccountTransaction) -> bool) -> Option<usize> {
+        self.0.values().position(|account_txs| {
+            predicate(account_txs.front().expect("Pending accounts have transactions."))
+        })
+    }
+
+    /// Takes the next transaction of the account with the given turn, and moves the account to the
+    /// end of the turn order.
+    fn pop_account_tx(&mut self, index: usize) -> AccountTransaction {
+        let (address, mut account_txs) =
+            self.0.shift_remove_index(index).expect("Account turn out of range.");
+        let tx = account_txs.pop_front().expect("Pending accounts have transactions.");
+        if !account_txs.is_empty() {
+            self.0.insert(address, account_txs);
+        }
+        tx
+    }
+}
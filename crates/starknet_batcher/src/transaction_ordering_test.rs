@@ -0,0 +1,154 @@
+use std::sync::Arc;
+
+use mockall::predicate::eq;
+use rstest::rstest;
+use starknet_api::executable_transaction::AccountTransaction;
+use starknet_api::execution_resources::GasAmount;
+use starknet_api::test_utils::invoke::executable_invoke_tx;
+use starknet_api::transaction::fields::{AllResourceBounds, ResourceBounds, ValidResourceBounds};
+use starknet_api::{contract_address, invoke_tx_args, nonce, tx_hash};
+use starknet_mempool_types::communication::{MockMempoolClient, SharedMempoolClient};
+
+use crate::transaction_ordering::{
+    create_transaction_ordering_policy,
+    TransactionOrderingConfig,
+    TransactionOrderingPolicy,
+    TransactionOrderingPolicyType,
+};
+
+const TX_L2_GAS: GasAmount = GasAmount(100);
+const BLOCK_MAX_L2_GAS: GasAmount = GasAmount(400);
+
+fn tx(address: u8, nonce: u8) -> AccountTransaction {
+    tx_with_l2_gas(address, nonce, TX_L2_GAS)
+}
+
+fn tx_with_l2_gas(address: u8, nonce: u8, l2_gas: GasAmount) -> AccountTransaction {
+    executable_invoke_tx(invoke_tx_args!(
+        sender_address: contract_address!(address),
+        nonce: nonce!(nonce),
+        tx_hash: tx_hash!(u64::from(address) * 100 + u64::from(nonce)),
+        resource_bounds: ValidResourceBounds::AllResources(AllResourceBounds {
+            l2_gas: ResourceBounds { max_amount: l2_gas, ..Default::default() },
+            ..Default::default()
+        }),
+    ))
+}
+
+// Three transactions of account 0, followed by a single transaction of account 1.
+fn mempool_txs() -> Vec<AccountTransaction> {
+    vec![tx(0, 0), tx(0, 1), tx(0, 2), tx(1, 0)]
+}
+
+// The first request is answered by the given transactions, and any later request finds the mempool
+// empty.
+fn mempool_client_with_txs(txs: Vec<AccountTransaction>) -> SharedMempoolClient {
+    let mut mempool_client = MockMempoolClient::new();
+    mempool_client.expect_get_txs().times(1).return_once(|_| Ok(txs));
+    mempool_client.expect_get_txs().returning(|_| Ok(vec![]));
+    Arc::new(mempool_client)
+}
+
+fn ordering_policy(
+    policy: TransactionOrderingPolicyType,
+    max_sender_share_percent: u8,
+) -> Box<dyn TransactionOrderingPolicy> {
+    create_transaction_ordering_policy(
+        &TransactionOrderingConfig { policy, max_sender_share_percent },
+        BLOCK_MAX_L2_GAS,
+    )
+}
+
+#[tokio::test]
+async fn fee_priority_keeps_mempool_order() {
+    let mempool_client = mempool_client_with_txs(mempool_txs());
+    let mut policy = ordering_policy(TransactionOrderingPolicyType::FeePriority, 100);
+
+    let txs = policy.get_txs(&mempool_client, 4).await.unwrap();
+    assert_eq!(txs, mempool_txs());
+}
+
+#[tokio::test]
+async fn fifo_takes_oldest_txs() {
+    let mut mempool_client = MockMempoolClient::new();
+    mempool_client.expect_get_txs().times(0);
+    mempool_client.expect_get_oldest_txs().with(eq(4)).times(1).returning(|_| Ok(mempool_txs()));
+    let mempool_client: SharedMempoolClient = Arc::new(mempool_client);
+    let mut policy = ordering_policy(TransactionOrderingPolicyType::Fifo, 100);
+
+    let txs = policy.get_txs(&mempool_client, 4).await.unwrap();
+    assert_eq!(txs, mempool_txs());
+}
+
+#[tokio::test]
+async fn round_robin_interleaves_accounts() {
+    // Setup.
+    let mempool_client = mempool_client_with_txs(mempool_txs());
+    let mut policy = ordering_policy(TransactionOrderingPolicyType::RoundRobin, 100);
+
+    // Test.
+    let txs = policy.get_txs(&mempool_client, 2).await.unwrap();
+    assert_eq!(txs, [tx(0, 0), tx(1, 0)]);
+
+    // The transactions left over from the first fetch are served first.
+    let txs = policy.get_txs(&mempool_client, 2).await.unwrap();
+    assert_eq!(txs, [tx(0, 1), tx(0, 2)]);
+}
+
+#[rstest]
+#[case::fee_priority(TransactionOrderingPolicyType::FeePriority, [tx(0, 0), tx(0, 1), tx(1, 0)])]
+#[case::round_robin(TransactionOrderingPolicyType::RoundRobin, [tx(0, 0), tx(1, 0), tx(0, 1)])]
+#[tokio::test]
+async fn sender_share_is_capped(
+    #[case] policy_type: TransactionOrderingPolicyType,
+    #[case] expected_txs: [AccountTransaction; 3],
+) {
+    // Setup.
+    let mempool_client = mempool_client_with_txs(mempool_txs());
+    let mut policy = ordering_policy(policy_type, 50);
+
+    // Test.
+    // Account 0 may use at most half of the block's L2 gas, as long as account 1 has transactions.
+    let txs = policy.get_txs(&mempool_client, 4).await.unwrap();
+    assert_eq!(txs, expected_txs);
+
+    // No other sender has transactions left, so account 0 may exceed its share.
+    let txs = policy.get_txs(&mempool_client, 4).await.unwrap();
+    assert_eq!(txs, [tx(0, 2)]);
+}
+
+#[tokio::test]
+async fn sender_share_is_measured_in_l2_gas() {
+    // Setup.
+    let heavy_tx = tx_with_l2_gas(0, 0, BLOCK_MAX_L2_GAS);
+    let mempool_client = mempool_client_with_txs(vec![heavy_tx.clone(), tx(1, 0), tx(1, 1)]);
+    let mut policy = ordering_policy(TransactionOrderingPolicyType::FeePriority, 50);
+
+    // Test.
+    // A single transaction of account 0 exceeds its share, while two of account 1 don't.
+    let txs = policy.get_txs(&mempool_client, 4).await.unwrap();
+    assert_eq!(txs, [tx(1, 0), tx(1, 1)]);
+
+    let txs = policy.get_txs(&mempool_client, 4).await.unwrap();
+    assert_eq!(txs, [heavy_tx]);
+}
+
+#[tokio::test]
+async fn sender_share_cap_bounds_held_back_txs() {
+    // Setup: a mempool flooded by account 0.
+    let mut mempool_client = MockMempoolClient::new();
+    let mut next_nonce = 0;
+    mempool_client.expect_get_txs().times(4).returning(move |n_txs| {
+        let txs = (next_nonce..next_nonce + n_txs).map(|nonce| tx(0, nonce.try_into().unwrap()));
+        next_nonce += n_txs;
+        Ok(txs.collect())
+    });
+    let mempool_client: SharedMempoolClient = Arc::new(mempool_client);
+    // Account 0 may propose a single transaction.
+    let mut policy = ordering_policy(TransactionOrderingPolicyType::FeePriority, 25);
+
+    // Test.
+    // Fetching stops once twice the requested number of transactions are held back.
+    let txs = policy.get_txs(&mempool_client, 2).await.unwrap();
+    assert_eq!(txs, [tx(0, 0)]);
+}
@@ -1,15 +1,17 @@
 use std::cmp::min;
-use std::sync::Arc;
 use std::vec;
 
 use async_trait::async_trait;
 #[cfg(test)]
 use mockall::automock;
-use starknet_api::executable_transaction::{L1HandlerTransaction, Transaction};
+use starknet_api::executable_transaction::Transaction;
 use starknet_api::transaction::TransactionHash;
+use starknet_l1_provider_types::errors::L1ProviderClientError;
+use starknet_l1_provider_types::{SharedL1ProviderClient, ValidationStatus};
 use starknet_mempool_types::communication::{MempoolClientError, SharedMempoolClient};
 use thiserror::Error;
-use tracing::warn;
+
+use crate::transaction_ordering::TransactionOrderingPolicy;
 
 type TransactionProviderResult<T> = Result<T, TransactionProviderError>;
 
@@ -17,8 +19,16 @@ type TransactionProviderResult<T> = Result<T, TransactionProviderError>;
 pub enum TransactionProviderError {
     #[error(transparent)]
     MempoolError(#[from] MempoolClientError),
-    #[error("L1Handler transaction validation failed for tx with hash {0}.")]
-    L1HandlerTransactionValidationFailed(TransactionHash),
+    #[error(transparent)]
+    L1ProviderError(#[from] L1ProviderClientError),
+    #[error(
+        "L1Handler transaction validation failed for tx with hash {tx_hash}, status: \
+         {validation_status:?}."
+    )]
+    L1HandlerTransactionValidationFailed {
+        tx_hash: TransactionHash,
+        validation_status: ValidationStatus,
+    },
 }
 
 #[derive(Debug, PartialEq)]
@@ -33,11 +43,11 @@ pub trait TransactionProvider: Send {
     async fn get_txs(&mut self, n_txs: usize) -> TransactionProviderResult<NextTxs>;
 }
 
-#[derive(Clone)]
 pub struct ProposeTransactionProvider {
     pub mempool_client: SharedMempoolClient,
     pub l1_provider_client: SharedL1ProviderClient,
     pub max_l1_handler_txs_per_block: usize,
+    ordering_policy: Box<dyn TransactionOrderingPolicy>,
     phase: TxProviderPhase,
     n_l1handler_txs_so_far: usize,
 }
@@ -54,18 +64,29 @@ impl ProposeTransactionProvider {
         mempool_client: SharedMempoolClient,
         l1_provider_client: SharedL1ProviderClient,
         max_l1_handler_txs_per_block: usize,
+        ordering_policy: Box<dyn TransactionOrderingPolicy>,
     ) -> Self {
         Self {
             mempool_client,
             l1_provider_client,
             max_l1_handler_txs_per_block,
+            ordering_policy,
             phase: TxProviderPhase::L1,
             n_l1handler_txs_so_far: 0,
         }
     }
 
-    fn get_l1_handler_txs(&mut self, n_txs: usize) -> Vec<Transaction> {
-        self.l1_provider_client.get_txs(n_txs).into_iter().map(Transaction::L1Handler).collect()
+    async fn get_l1_handler_txs(
+        &mut self,
+        n_txs: usize,
+    ) -> TransactionProviderResult<Vec<Transaction>> {
+        Ok(self
+            .l1_provider_client
+            .get_txs(n_txs)
+            .await?
+            .into_iter()
+            .map(Transaction::L1Handler)
+            .collect())
     }
 
     async fn get_mempool_txs(
@@ -73,8 +94,8 @@ impl ProposeTransactionProvider {
         n_txs: usize,
     ) -> TransactionProviderResult<Vec<Transaction>> {
         Ok(self
-            .mempool_client
-            .get_txs(n_txs)
+            .ordering_policy
+            .get_txs(&self.mempool_client, n_txs)
             .await?
             .into_iter()
             .map(Transaction::Account)
@@ -90,7 +111,7 @@ impl TransactionProvider for ProposeTransactionProvider {
         if self.phase == TxProviderPhase::L1 {
             let n_l1handler_txs_to_get =
                 min(self.max_l1_handler_txs_per_block - self.n_l1handler_txs_so_far, n_txs);
-            let mut l1handler_txs = self.get_l1_handler_txs(n_l1handler_txs_to_get);
+            let mut l1handler_txs = self.get_l1_handler_txs(n_l1handler_txs_to_get).await?;
             self.n_l1handler_txs_so_far += l1handler_txs.len();
 
             // Determine whether we need to switch to mempool phase.
@@ -131,38 +152,15 @@ impl TransactionProvider for ValidateTransactionProvider {
         }
         for tx in &buffer {
             if let Transaction::L1Handler(tx) = tx {
-                if !self.l1_provider_client.validate(tx) {
-                    return Err(TransactionProviderError::L1HandlerTransactionValidationFailed(
-                        tx.tx_hash,
-                    ));
+                let validation_status = self.l1_provider_client.validate(tx.tx_hash).await?;
+                if validation_status != ValidationStatus::Validated {
+                    return Err(TransactionProviderError::L1HandlerTransactionValidationFailed {
+                        tx_hash: tx.tx_hash,
+                        validation_status,
+                    });
                 }
             }
         }
         Ok(NextTxs::Txs(buffer))
     }
 }
-
-// TODO: Remove L1Provider code when the communication module of l1_provider is added.
-#[cfg_attr(test, automock)]
-#[async_trait]
-pub trait L1ProviderClient: Send + Sync {
-    fn get_txs(&self, n_txs: usize) -> Vec<L1HandlerTransaction>;
-    fn validate(&self, tx: &L1HandlerTransaction) -> bool;
-}
-
-pub type SharedL1ProviderClient = Arc<dyn L1ProviderClient>;
-
-pub struct DummyL1ProviderClient;
-
-#[async_trait]
-impl L1ProviderClient for DummyL1ProviderClient {
-    fn get_txs(&self, _n_txs: usize) -> Vec<L1HandlerTransaction> {
-        warn!("Dummy L1 provider client is used, no L1 transactions are provided.");
-        vec![]
-    }
-
-    fn validate(&self, _tx: &L1HandlerTransaction) -> bool {
-        warn!("Dummy L1 provider client is used, tx is not really validated.");
-        true
-    }
-}
@@ -6,10 +6,11 @@ use rstest::{fixture, rstest};
 use starknet_api::executable_transaction::{L1HandlerTransaction, Transaction};
 use starknet_api::test_utils::invoke::{executable_invoke_tx, InvokeTxArgs};
 use starknet_api::tx_hash;
+use starknet_l1_provider_types::{MockL1ProviderClient, ValidationStatus};
 use starknet_mempool_types::communication::MockMempoolClient;
 
+use crate::transaction_ordering::FeePriorityPolicy;
 use crate::transaction_provider::{
-    MockL1ProviderClient,
     NextTxs,
     ProposeTransactionProvider,
     TransactionProvider,
@@ -33,7 +34,7 @@ impl MockDependencies {
         self.l1_provider_client
             .expect_get_txs()
             .with(eq(n_to_request))
-            .returning(move |_| vec![L1HandlerTransaction::default(); n_to_return]);
+            .returning(move |_| Ok(vec![L1HandlerTransaction::default(); n_to_return]));
     }
 
     fn expect_get_mempool_txs(&mut self, n_to_request: usize) {
@@ -42,11 +43,11 @@ impl MockDependencies {
         });
     }
 
-    fn expect_validate_l1handler(&mut self, tx: L1HandlerTransaction, result: bool) {
+    fn expect_validate_l1handler(&mut self, tx: L1HandlerTransaction, result: ValidationStatus) {
         self.l1_provider_client
             .expect_validate()
-            .withf(move |tx_arg| tx_arg == &tx)
-            .returning(move |_| result);
+            .with(eq(tx.tx_hash))
+            .returning(move |_| Ok(result));
     }
 
     async fn simulate_input_txs(&mut self, txs: Vec<Transaction>) {
@@ -60,6 +61,7 @@ impl MockDependencies {
             Arc::new(self.mempool_client),
             Arc::new(self.l1_provider_client),
             MAX_L1_HANDLER_TXS_PER_BLOCK,
+            Box::new(FeePriorityPolicy),
         )
     }
 
@@ -163,7 +165,7 @@ async fn no_more_l1_handler(mut mock_dependencies: MockDependencies) {
 #[tokio::test]
 async fn validate_flow(mut mock_dependencies: MockDependencies) {
     let test_tx = test_l1handler_tx();
-    mock_dependencies.expect_validate_l1handler(test_tx.clone(), true);
+    mock_dependencies.expect_validate_l1handler(test_tx.clone(), ValidationStatus::Validated);
     mock_dependencies
         .simulate_input_txs(vec![
             Transaction::L1Handler(test_tx),
@@ -180,10 +182,15 @@ async fn validate_flow(mut mock_dependencies: MockDependencies) {
 }
 
 #[rstest]
+#[case::already_included_on_l2(ValidationStatus::AlreadyIncludedOnL2)]
+#[case::consumed_on_l1_or_unknown(ValidationStatus::ConsumedOnL1OrUnknown)]
 #[tokio::test]
-async fn validate_fails(mut mock_dependencies: MockDependencies) {
+async fn validate_fails(
+    mut mock_dependencies: MockDependencies,
+    #[case] expected_validation_status: ValidationStatus,
+) {
     let test_tx = test_l1handler_tx();
-    mock_dependencies.expect_validate_l1handler(test_tx.clone(), false);
+    mock_dependencies.expect_validate_l1handler(test_tx.clone(), expected_validation_status);
     mock_dependencies
         .simulate_input_txs(vec![
             Transaction::L1Handler(test_tx),
@@ -195,6 +202,9 @@ async fn validate_fails(mut mock_dependencies: MockDependencies) {
     let result = validate_tx_provider.get_txs(MAX_TXS_PER_FETCH).await;
     assert_matches!(
         result,
-        Err(TransactionProviderError::L1HandlerTransactionValidationFailed(_tx_hash))
+        Err(TransactionProviderError::L1HandlerTransactionValidationFailed {
+            validation_status,
+            ..
+        }) if validation_status == expected_validation_status
     );
 }
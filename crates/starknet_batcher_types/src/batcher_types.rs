@@ -2,9 +2,10 @@ use std::fmt::Debug;
 
 use chrono::prelude::*;
 use serde::{Deserialize, Serialize};
-use starknet_api::block::{BlockHashAndNumber, BlockInfo, BlockNumber};
+use starknet_api::block::{BlockBody, BlockHashAndNumber, BlockInfo, BlockNumber};
 use starknet_api::core::StateDiffCommitment;
 use starknet_api::executable_transaction::Transaction;
+use starknet_api::state::ThinStateDiff;
 
 use crate::errors::BatcherError;
 
@@ -100,6 +101,82 @@ pub enum ProposalStatus {
     InvalidProposal,
 }
 
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct AbortProposalInput {
+    pub proposal_id: ProposalId,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct GetProposalStatusInput {
+    pub proposal_id: ProposalId,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct GetProposalStatusResponse {
+    pub state: ProposalState,
+}
+
+/// The lifecycle state of a single proposal of the active height.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum ProposalState {
+    Building,
+    Finished(ProposalCommitment),
+    Aborted,
+    Failed { reason: String },
+}
+
+#[derive(
+    Copy,
+    Clone,
+    Debug,
+    Serialize,
+    Deserialize,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Default,
+    derive_more::Display,
+    Hash,
+)]
+pub struct PendingBlockSubscriptionId(pub u64);
+
+/// An update of the block the batcher is currently proposing. A subscriber rebuilds the pending
+/// block by applying the updates in order.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum PendingBlockUpdate {
+    /// A new pending block, replacing the previous one.
+    NewBlock(BlockInfo),
+    /// Transactions executed in the pending block since the previous update.
+    Txs(PendingTxs),
+    /// The pending block was decided or abandoned.
+    Cleared,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct PendingTxs {
+    // The executed transactions with their hashes and outputs (receipts and events), in execution
+    // order.
+    pub body: BlockBody,
+    // The state changes since the previous update.
+    pub state_diff: ThinStateDiff,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct SubscribePendingBlockResponse {
+    pub subscription_id: PendingBlockSubscriptionId,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct GetPendingBlockUpdatesInput {
+    pub subscription_id: PendingBlockSubscriptionId,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct GetPendingBlockUpdatesResponse {
+    pub updates: Vec<PendingBlockUpdate>,
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct StartHeightInput {
     pub height: BlockNumber,
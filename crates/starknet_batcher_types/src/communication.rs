@@ -18,15 +18,21 @@ use starknet_state_sync_types::state_sync_types::SyncBlock;
 use thiserror::Error;
 
 use crate::batcher_types::{
+    AbortProposalInput,
     BatcherResult,
     DecisionReachedInput,
     GetHeightResponse,
+    GetPendingBlockUpdatesInput,
+    GetPendingBlockUpdatesResponse,
     GetProposalContentInput,
     GetProposalContentResponse,
+    GetProposalStatusInput,
+    GetProposalStatusResponse,
     ProposeBlockInput,
     SendProposalContentInput,
     SendProposalContentResponse,
     StartHeightInput,
+    SubscribePendingBlockResponse,
     ValidateBlockInput,
 };
 use crate::errors::BatcherError;
@@ -65,6 +71,25 @@ pub trait BatcherClient: Send + Sync {
         &self,
         input: SendProposalContentInput,
     ) -> BatcherClientResult<SendProposalContentResponse>;
+    /// Stops the execution of a proposal that is still being built or validated. The batcher
+    /// finishes shutting it down before starting the next proposal. Aborting a proposal that is
+    /// already done has no effect.
+    async fn abort_proposal(&self, input: AbortProposalInput) -> BatcherClientResult<()>;
+    /// Gets the current state of a proposal of the active height.
+    async fn get_proposal_status(
+        &self,
+        input: GetProposalStatusInput,
+    ) -> BatcherClientResult<GetProposalStatusResponse>;
+    /// Subscribes to the updates of the block the batcher is proposing. The updates made so far to
+    /// the current pending block, if any, are the first ones of the subscription.
+    async fn subscribe_pending_block(&self) -> BatcherClientResult<SubscribePendingBlockResponse>;
+    /// Gets the next available updates from a pending block subscription, which may be none. A
+    /// subscription that falls too far behind is dropped, and the subscriber has to subscribe
+    /// again.
+    async fn get_pending_block_updates(
+        &self,
+        input: GetPendingBlockUpdatesInput,
+    ) -> BatcherClientResult<GetPendingBlockUpdatesResponse>;
     /// Starts the process of a new height.
     /// From this point onwards, the batcher will accept requests only for proposals associated
     /// with this height.
@@ -76,18 +101,24 @@ pub trait BatcherClient: Send + Sync {
     async fn add_sync_block(&self, sync_block: SyncBlock) -> BatcherClientResult<()>;
 }
 
+#[allow(clippy::large_enum_variant)]
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub enum BatcherRequest {
     ProposeBlock(ProposeBlockInput),
     GetProposalContent(GetProposalContentInput),
     ValidateBlock(ValidateBlockInput),
     SendProposalContent(SendProposalContentInput),
+    AbortProposal(AbortProposalInput),
+    GetProposalStatus(GetProposalStatusInput),
+    SubscribePendingBlock,
+    GetPendingBlockUpdates(GetPendingBlockUpdatesInput),
     StartHeight(StartHeightInput),
     GetCurrentHeight,
     DecisionReached(DecisionReachedInput),
     AddSyncBlock(SyncBlock),
 }
 
+#[allow(clippy::large_enum_variant)]
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub enum BatcherResponse {
     ProposeBlock(BatcherResult<()>),
@@ -95,6 +126,10 @@ pub enum BatcherResponse {
     GetProposalContent(BatcherResult<GetProposalContentResponse>),
     ValidateBlock(BatcherResult<()>),
     SendProposalContent(BatcherResult<SendProposalContentResponse>),
+    AbortProposal(BatcherResult<()>),
+    GetProposalStatus(BatcherResult<GetProposalStatusResponse>),
+    SubscribePendingBlock(BatcherResult<SubscribePendingBlockResponse>),
+    GetPendingBlockUpdates(BatcherResult<GetPendingBlockUpdatesResponse>),
     StartHeight(BatcherResult<()>),
     DecisionReached(BatcherResult<()>),
     AddSyncBlock(BatcherResult<()>),
@@ -153,6 +188,51 @@ where
         )
     }
 
+    async fn abort_proposal(&self, input: AbortProposalInput) -> BatcherClientResult<()> {
+        let request = BatcherRequest::AbortProposal(input);
+        let response = self.send(request).await;
+        handle_response_variants!(BatcherResponse, AbortProposal, BatcherClientError, BatcherError)
+    }
+
+    async fn get_proposal_status(
+        &self,
+        input: GetProposalStatusInput,
+    ) -> BatcherClientResult<GetProposalStatusResponse> {
+        let request = BatcherRequest::GetProposalStatus(input);
+        let response = self.send(request).await;
+        handle_response_variants!(
+            BatcherResponse,
+            GetProposalStatus,
+            BatcherClientError,
+            BatcherError
+        )
+    }
+
+    async fn subscribe_pending_block(&self) -> BatcherClientResult<SubscribePendingBlockResponse> {
+        let request = BatcherRequest::SubscribePendingBlock;
+        let response = self.send(request).await;
+        handle_response_variants!(
+            BatcherResponse,
+            SubscribePendingBlock,
+            BatcherClientError,
+            BatcherError
+        )
+    }
+
+    async fn get_pending_block_updates(
+        &self,
+        input: GetPendingBlockUpdatesInput,
+    ) -> BatcherClientResult<GetPendingBlockUpdatesResponse> {
+        let request = BatcherRequest::GetPendingBlockUpdates(input);
+        let response = self.send(request).await;
+        handle_response_variants!(
+            BatcherResponse,
+            GetPendingBlockUpdates,
+            BatcherClientError,
+            BatcherError
+        )
+    }
+
     async fn start_height(&self, input: StartHeightInput) -> BatcherClientResult<()> {
         let request = BatcherRequest::StartHeight(input);
         let response = self.send(request).await;
@@ -3,7 +3,7 @@ use serde::{Deserialize, Serialize};
 use starknet_api::block::BlockNumber;
 use thiserror::Error;
 
-use crate::batcher_types::ProposalId;
+use crate::batcher_types::{PendingBlockSubscriptionId, ProposalId};
 
 #[derive(Clone, Debug, Error, PartialEq, Eq, Serialize, Deserialize)]
 pub enum BatcherError {
@@ -39,6 +39,13 @@ pub enum BatcherError {
          transactions."
     )]
     ProposalAlreadyFinished { proposal_id: ProposalId },
+    #[error(
+        "Pending block subscription {subscription_id} fell behind the pending block updates and \
+         was dropped."
+    )]
+    PendingBlockSubscriptionLagged { subscription_id: PendingBlockSubscriptionId },
+    #[error("Pending block subscription {subscription_id} not found.")]
+    PendingBlockSubscriptionNotFound { subscription_id: PendingBlockSubscriptionId },
     #[error("Proposal failed.")]
     ProposalFailed,
     #[error("Proposal aborted.")]
@@ -1,7 +1,9 @@
 use std::collections::HashMap;
 
+use starknet_patricia::hash::hash_trait::HashOutput;
 use starknet_patricia::patricia_merkle_tree::types::{NodeIndex, SortedLeafIndices};
 use starknet_patricia::storage::map_storage::MapStorage;
+use starknet_patricia::storage::storage_trait::Storage;
 use tracing::{info, warn};
 
 use crate::block_committer::errors::BlockCommitmentError;
@@ -16,8 +18,28 @@ use crate::patricia_merkle_tree::types::{ClassHash, Nonce};
 type BlockCommitmentResult<T> = Result<T, BlockCommitmentError>;
 
 pub async fn commit_block(input: Input<ConfigImpl>) -> BlockCommitmentResult<FilledForest> {
+    commit_state_diff(
+        &MapStorage::from(input.storage),
+        &input.state_diff,
+        input.contracts_trie_root_hash,
+        input.classes_trie_root_hash,
+        &input.config,
+    )
+    .await
+}
+
+/// Commits the state diff on top of the tries with the given roots, reading their facts from
+/// `storage`. Unlike [commit_block], the storage is borrowed, so the caller can keep it and write
+/// the filled forest to it.
+pub async fn commit_state_diff(
+    storage: &impl Storage,
+    state_diff: &StateDiff,
+    contracts_trie_root_hash: HashOutput,
+    classes_trie_root_hash: HashOutput,
+    config: &ConfigImpl,
+) -> BlockCommitmentResult<FilledForest> {
     let (mut storage_tries_indices, mut contracts_trie_indices, mut classes_trie_indices) =
-        get_all_modified_indices(&input.state_diff);
+        get_all_modified_indices(state_diff);
     let forest_sorted_indices = ForestSortedIndices {
         storage_tries_sorted_indices: storage_tries_indices
             .iter_mut()
@@ -26,34 +48,34 @@ pub async fn commit_block(input: Input<ConfigImpl>) -> BlockCommitmentResult<Fil
         contracts_trie_sorted_indices: SortedLeafIndices::new(&mut contracts_trie_indices),
         classes_trie_sorted_indices: SortedLeafIndices::new(&mut classes_trie_indices),
     };
-    let actual_storage_updates = input.state_diff.actual_storage_updates();
-    let actual_classes_updates = input.state_diff.actual_classes_updates();
+    let actual_storage_updates = state_diff.actual_storage_updates();
+    let actual_classes_updates = state_diff.actual_classes_updates();
     let (mut original_forest, original_contracts_trie_leaves) = OriginalSkeletonForest::create(
-        MapStorage::from(input.storage),
-        input.contracts_trie_root_hash,
-        input.classes_trie_root_hash,
+        storage,
+        contracts_trie_root_hash,
+        classes_trie_root_hash,
         &actual_storage_updates,
         &actual_classes_updates,
         &forest_sorted_indices,
-        &input.config,
+        config,
     )?;
     info!("Original skeleton forest created successfully.");
 
-    if input.config.warn_on_trivial_modifications() {
+    if config.warn_on_trivial_modifications() {
         check_trivial_nonce_and_class_hash_updates(
             &original_contracts_trie_leaves,
-            &input.state_diff.address_to_class_hash,
-            &input.state_diff.address_to_nonce,
+            &state_diff.address_to_class_hash,
+            &state_diff.address_to_nonce,
         );
     }
 
     let updated_forest = UpdatedSkeletonForest::create(
         &mut original_forest,
-        &input.state_diff.skeleton_classes_updates(),
-        &input.state_diff.skeleton_storage_updates(),
+        &state_diff.skeleton_classes_updates(),
+        &state_diff.skeleton_storage_updates(),
         &original_contracts_trie_leaves,
-        &input.state_diff.address_to_class_hash,
-        &input.state_diff.address_to_nonce,
+        &state_diff.address_to_class_hash,
+        &state_diff.address_to_nonce,
     )?;
     info!("Updated skeleton forest created successfully.");
 
@@ -62,8 +84,8 @@ pub async fn commit_block(input: Input<ConfigImpl>) -> BlockCommitmentResult<Fil
         actual_storage_updates,
         actual_classes_updates,
         &original_contracts_trie_leaves,
-        &input.state_diff.address_to_class_hash,
-        &input.state_diff.address_to_nonce,
+        &state_diff.address_to_class_hash,
+        &state_diff.address_to_nonce,
     )
     .await?;
     info!("Filled forest created successfully.");
@@ -10,6 +10,7 @@ use validator::Validate;
 /// TODO(Matan): Remove ConsensusManagerConfig if it's only field remains ConsensusConfig.
 #[derive(Clone, Default, Debug, Serialize, Deserialize, Validate, PartialEq)]
 pub struct ConsensusManagerConfig {
+    #[validate]
     pub consensus_config: ConsensusConfig,
 }
 
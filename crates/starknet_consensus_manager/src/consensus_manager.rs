@@ -12,7 +12,7 @@ use starknet_batcher_types::communication::SharedBatcherClient;
 use starknet_sequencer_infra::component_definitions::ComponentStarter;
 use starknet_sequencer_infra::errors::ComponentError;
 use starknet_state_sync_types::communication::SharedStateSyncClient;
-use tracing::{error, info};
+use tracing::{error, info, warn};
 
 use crate::config::ConsensusManagerConfig;
 
@@ -38,6 +38,7 @@ impl ConsensusManager {
     }
 
     pub async fn run(&self) -> Result<(), ConsensusError> {
+        let signer = self.config.consensus_config.signer()?;
         let mut network_manager =
             NetworkManager::new(self.config.consensus_config.network_config.clone(), None);
 
@@ -63,11 +64,19 @@ impl ConsensusManager {
         let (outbound_internal_sender, inbound_internal_receiver, mut stream_handler_task_handle) =
             StreamHandler::get_channels(inbound_network_receiver, outbound_network_sender);
 
+        if self.config.consensus_config.staking_contract_address.is_some() {
+            // The sequencer has no state reader to query the staking contract with yet.
+            warn!(
+                "Reading the validator set from a staking contract is not supported by the \
+                 sequencer, using the configured validator set."
+            );
+        }
         let context = SequencerConsensusContext::new(
             Arc::clone(&self.batcher_client),
             outbound_internal_sender,
             votes_broadcast_channels.broadcast_topic_client.clone(),
-            self.config.consensus_config.num_validators,
+            Arc::new(self.config.consensus_config.validator_set()),
+            signer.clone(),
         );
 
         let mut network_handle = tokio::task::spawn(network_manager.run());
@@ -77,6 +86,8 @@ impl ConsensusManager {
             // TODO(Asmaa): replace with the correct value.
             self.config.consensus_config.start_height,
             self.config.consensus_config.validator_id,
+            signer,
+            self.config.consensus_config.wal_path.clone(),
             self.config.consensus_config.consensus_delay,
             self.config.consensus_config.timeouts.clone(),
             votes_broadcast_channels.into(),
@@ -64,6 +64,12 @@ impl Gateway {
         tx: RpcTransaction,
         p2p_message_metadata: Option<BroadcastedMessageMetadata>,
     ) -> GatewayResult<TransactionHash> {
+        // The mempool doesn't hold the Sierra class of declare transactions, so it gets the whole
+        // transaction for propagating it to peers.
+        let rpc_declare_tx = match &tx {
+            RpcTransaction::Declare(declare_tx) => Some(declare_tx.clone()),
+            _ => None,
+        };
         let blocking_task = ProcessTxBlockingTask::new(self, tx);
         // Run the blocking task in the current span.
         let curr_span = Span::current();
@@ -77,7 +83,8 @@ impl Gateway {
 
         let tx_hash = add_tx_args.tx.tx_hash();
 
-        let add_tx_args = AddTransactionArgsWrapper { args: add_tx_args, p2p_message_metadata };
+        let add_tx_args =
+            AddTransactionArgsWrapper { args: add_tx_args, p2p_message_metadata, rpc_declare_tx };
         self.mempool_client.add_tx(add_tx_args).await.map_err(|e| {
             error!("Failed to send tx to mempool: {}", e);
             GatewaySpecError::UnexpectedError { data: "Internal server error".to_owned() }
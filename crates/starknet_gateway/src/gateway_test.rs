@@ -110,6 +110,7 @@ async fn test_add_tx(mut mock_dependencies: MockDependencies) {
     mock_dependencies.expect_add_tx(AddTransactionArgsWrapper {
         args: add_tx_args,
         p2p_message_metadata: p2p_message_metadata.clone(),
+        rpc_declare_tx: None,
     });
 
     let gateway = mock_dependencies.gateway();
@@ -55,7 +55,7 @@ pub(crate) fn dump_config_file_changes(
 ) -> PathBuf {
     // Dump config changes file for the sequencer node.
     // TODO(Tsabary): auto dump the entirety of RequiredParams fields.
-    let json_data = config_fields_to_json!(
+    let mut json_data = config_fields_to_json!(
         required_params.chain_id,
         required_params.eth_fee_token_address,
         required_params.strk_fee_token_address,
@@ -66,9 +66,13 @@ pub(crate) fn dump_config_file_changes(
         config.http_server_config.ip,
         config.http_server_config.port,
         config.consensus_manager_config.consensus_config.start_height,
+        config.consensus_manager_config.consensus_config.signing_key,
         config.state_sync_config.storage_config.db_config.path_prefix,
         config.state_sync_config.network_config.tcp_port,
+        config.state_sync_config.network_config.quic_port,
     );
+    // An optional param is only set along with its "#is_none" flag.
+    json_data["consensus_manager_config.consensus_config.signing_key.#is_none"] = json!(false);
     let node_config_path = dump_json_data(json_data, NODE_CONFIG_CHANGES_FILE_PATH, dir);
     assert!(node_config_path.exists(), "File does not exist: {:?}", node_config_path);
 
@@ -135,6 +139,7 @@ pub async fn get_http_only_component_config(gateway_socket: SocketAddr) -> Compo
         monitoring_endpoint: Default::default(),
         batcher: get_disabled_component_config(),
         consensus_manager: ActiveComponentExecutionConfig::disabled(),
+        l1_provider: get_disabled_component_config(),
         mempool: get_disabled_component_config(),
         mempool_p2p: get_disabled_component_config(),
         state_sync: get_disabled_component_config(),
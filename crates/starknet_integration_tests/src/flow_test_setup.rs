@@ -10,6 +10,7 @@ use starknet_consensus_manager::config::ConsensusManagerConfig;
 use starknet_gateway_types::errors::GatewaySpecError;
 use starknet_http_server::config::HttpServerConfig;
 use starknet_http_server::test_utils::HttpTestClient;
+use starknet_sequencer_infra::shutdown::ShutdownController;
 use starknet_sequencer_node::config::node_config::SequencerNodeConfig;
 use starknet_sequencer_node::servers::run_component_servers;
 use starknet_sequencer_node::utils::create_node_modules;
@@ -133,7 +134,7 @@ impl SequencerSetup {
 
         debug!("Sequencer config: {:#?}", config);
 
-        let (_clients, servers) = create_node_modules(&config);
+        let (_clients, servers) = create_node_modules(&config, &mut ShutdownController::new());
 
         let HttpServerConfig { ip, port } = config.http_server_config;
         let add_tx_http_client = HttpTestClient::new(SocketAddr::from((ip, port)));
@@ -68,7 +68,7 @@ impl StorageTestSetup {
         create_test_state(&mut rpc_storage_writer, chain_info, test_defined_accounts.clone());
         let ((_, mut batcher_storage_writer), batcher_storage_config, batcher_storage_file_handle) =
             TestStorageBuilder::default()
-                .scope(StorageScope::StateOnly)
+                .scope(StorageScope::FullArchive)
                 .chain_id(chain_info.chain_id.clone())
                 .build();
         create_test_state(&mut batcher_storage_writer, chain_info, test_defined_accounts.clone());
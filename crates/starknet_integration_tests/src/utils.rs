@@ -7,6 +7,7 @@ use blockifier::test_utils::contracts::FeatureContract;
 use blockifier::test_utils::{CairoVersion, RunnableCairo1};
 use mempool_test_utils::starknet_api_test_utils::{AccountId, MultiAccountTransactionGenerator};
 use papyrus_consensus::config::ConsensusConfig;
+use papyrus_consensus::signature::ConsensusSigner;
 use papyrus_consensus::types::{ValidatorId, DEFAULT_VALIDATOR_ID};
 use papyrus_network::network_manager::test_utils::create_network_configs_connected_to_broadcast_channels;
 use papyrus_network::network_manager::BroadcastTopicChannels;
@@ -37,6 +38,7 @@ use starknet_types_core::felt::Felt;
 
 // TODO(Tsabary): Get rid of this constant once we have a better way to set the port for testing.
 const STATE_SYNC_NETWORK_CONFIG_TCP_PORT_FOR_TESTING: u16 = 12345;
+const STATE_SYNC_NETWORK_CONFIG_QUIC_PORT_FOR_TESTING: u16 = 12346;
 
 pub fn create_chain_info() -> ChainInfo {
     let mut chain_info = ChainInfo::create_for_testing();
@@ -65,7 +67,7 @@ pub async fn create_config(
     let mempool_p2p_config =
         create_mempool_p2p_config(sequencer_index, chain_info.chain_id.clone());
     let monitoring_endpoint_config = create_monitoring_endpoint_config(sequencer_index);
-    let state_sync_config = create_state_sync_config(state_sync_storage_config);
+    let state_sync_config = create_state_sync_config(state_sync_storage_config, sequencer_index);
 
     (
         SequencerNodeConfig {
@@ -106,16 +108,25 @@ pub fn create_consensus_manager_configs_and_channels(
     timeouts.prevote_timeout *= 3;
     timeouts.proposal_timeout *= 3;
 
+    // The validators sign with the INSECURE testing keys of their IDs, see `set_validator_id`.
+    let validator_public_keys: Vec<_> = (0..u64::try_from(n_managers).unwrap())
+        .map(|i| {
+            ConsensusSigner::for_testing(ValidatorId::from(DEFAULT_VALIDATOR_ID + i)).public_key()
+        })
+        .collect();
+
     let consensus_manager_configs = network_configs
         .into_iter()
         // TODO(Matan): Get config from default config file.
         .map(|network_config| ConsensusManagerConfig {
             consensus_config: ConsensusConfig {
+                chain_id: papyrus_storage::test_utils::CHAIN_ID_FOR_TESTS.clone(),
                 start_height: BlockNumber(1),
 		// TODO(Matan, Dan): Set the right amount
                 consensus_delay: Duration::from_secs(5),
                 network_config,
                 num_validators: u64::try_from(n_managers).unwrap(),
+                validator_public_keys: validator_public_keys.clone(),
                 timeouts: timeouts.clone(),
                 ..Default::default()
             },
@@ -263,11 +274,13 @@ pub fn run_integration_test() -> bool {
 }
 
 fn set_validator_id(consensus_manager_config: &mut ConsensusManagerConfig, sequencer_index: usize) {
-    consensus_manager_config.consensus_config.validator_id = ValidatorId::try_from(
-        Felt::from(consensus_manager_config.consensus_config.validator_id)
-            + Felt::from(sequencer_index),
+    let consensus_config = &mut consensus_manager_config.consensus_config;
+    consensus_config.validator_id = ValidatorId::try_from(
+        Felt::from(consensus_config.validator_id) + Felt::from(sequencer_index),
     )
     .unwrap();
+    // The INSECURE testing key of the validator, see `ConsensusSigner::for_testing`.
+    consensus_config.signing_key = Some(Felt::from(consensus_config.validator_id));
 }
 
 fn create_mempool_p2p_config(sequencer_index: usize, chain_id: ChainId) -> MempoolP2pConfig {
@@ -285,11 +298,17 @@ fn create_monitoring_endpoint_config(sequencer_index: usize) -> MonitoringEndpoi
     config.port += u16::try_from(sequencer_index).unwrap();
     config
 }
-pub fn create_state_sync_config(state_sync_storage_config: StorageConfig) -> StateSyncConfig {
+pub fn create_state_sync_config(
+    state_sync_storage_config: StorageConfig,
+    sequencer_index: usize,
+) -> StateSyncConfig {
+    // As with the mempool p2p network, the ports of each sequencer on the machine must differ.
+    let port_offset = u16::try_from(sequencer_index).unwrap();
     StateSyncConfig {
         storage_config: state_sync_storage_config,
         network_config: NetworkConfig {
-            tcp_port: STATE_SYNC_NETWORK_CONFIG_TCP_PORT_FOR_TESTING,
+            tcp_port: STATE_SYNC_NETWORK_CONFIG_TCP_PORT_FOR_TESTING + port_offset,
+            quic_port: STATE_SYNC_NETWORK_CONFIG_QUIC_PORT_FOR_TESTING + port_offset,
             ..Default::default()
         },
         ..Default::default()
@@ -2,6 +2,7 @@ use std::collections::HashSet;
 
 use futures::StreamExt;
 use mempool_test_utils::starknet_api_test_utils::MultiAccountTransactionGenerator;
+use papyrus_consensus::signature::ConsensusSigner;
 use papyrus_consensus::types::ValidatorId;
 use papyrus_network::network_manager::BroadcastTopicChannels;
 use papyrus_protobuf::consensus::{
@@ -15,6 +16,7 @@ use papyrus_storage::test_utils::CHAIN_ID_FOR_TESTS;
 use pretty_assertions::assert_eq;
 use rstest::{fixture, rstest};
 use starknet_api::block::{BlockHash, BlockNumber};
+use starknet_api::crypto::utils::Signature;
 use starknet_api::transaction::TransactionHash;
 use starknet_integration_tests::flow_test_setup::FlowTestSetup;
 use starknet_integration_tests::utils::{
@@ -96,13 +98,22 @@ async fn listen_to_broadcasted_messages(
     let broadcasted_messages_receiver =
         &mut consensus_proposals_channels.broadcasted_messages_receiver;
     // TODO (Dan, Guy): retrieve / calculate the expected proposal init and fin.
-    let expected_proposal_init = ProposalInit {
+    // The nodes are configured to sign with the testing key of their ID.
+    let signer = ConsensusSigner::new(Felt::from(expected_proposer_id), chain_id.clone());
+    let expected_proposal_init = signer.sign_proposal_init(ProposalInit {
         height: expected_height,
         round: 0,
         valid_round: None,
         proposer: expected_proposer_id,
-    };
-    let expected_proposal_fin = ProposalFin { proposal_content_id: BlockHash(expected_content_id) };
+        signature: Signature::default(),
+    });
+    let expected_proposal_fin = signer.sign_proposal_fin(
+        &expected_proposal_init,
+        ProposalFin {
+            proposal_content_id: BlockHash(expected_content_id),
+            signature: Signature::default(),
+        },
+    );
 
     let StreamMessage {
         stream_id: first_stream_id,
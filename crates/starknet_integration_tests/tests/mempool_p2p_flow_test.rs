@@ -1,21 +1,36 @@
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 use std::future::ready;
 use std::net::SocketAddr;
 
 use futures::StreamExt;
 use mempool_test_utils::starknet_api_test_utils::MultiAccountTransactionGenerator;
 use papyrus_network::gossipsub_impl::Topic;
-use papyrus_network::network_manager::test_utils::create_network_configs_connected_to_broadcast_channels;
-use papyrus_network::network_manager::{BroadcastTopicChannels, BroadcastTopicClientTrait};
-use papyrus_protobuf::mempool::RpcTransactionWrapper;
+use papyrus_network::network_manager::test_utils::create_connected_network_configs;
+use papyrus_network::network_manager::{
+    BroadcastTopicChannels,
+    BroadcastTopicClientTrait,
+    NetworkManager,
+    SqmrServerReceiver,
+};
+use papyrus_protobuf::mempool::{
+    MempoolTransactionsQuery,
+    NewTransactionHashes,
+    RpcTransactionWrapper,
+};
+use papyrus_protobuf::sync::DataOrFin;
 use rstest::{fixture, rstest};
-use starknet_api::executable_transaction::AccountTransaction;
+use starknet_api::core::ChainId;
+use starknet_api::executable_transaction::{
+    AccountTransaction,
+    DeployAccountTransaction,
+    InvokeTransaction,
+};
 use starknet_api::rpc_transaction::{
     RpcDeployAccountTransaction,
     RpcInvokeTransaction,
     RpcTransaction,
 };
-use starknet_api::transaction::TransactionHash;
+use starknet_api::transaction::{TransactionHash, TransactionHasher};
 use starknet_http_server::config::HttpServerConfig;
 use starknet_http_server::test_utils::HttpTestClient;
 use starknet_integration_tests::state_reader::{spawn_test_rpc_state_reader, StorageTestSetup};
@@ -30,7 +45,12 @@ use starknet_integration_tests::utils::{
     test_rpc_state_reader_config,
 };
 use starknet_mempool_p2p::config::MempoolP2pConfig;
-use starknet_mempool_p2p::MEMPOOL_TOPIC;
+use starknet_mempool_p2p::{
+    MEMPOOL_DECLARE_TRANSACTIONS_PROTOCOL,
+    MEMPOOL_TOPIC,
+    MEMPOOL_TRANSACTIONS_PROTOCOL,
+};
+use starknet_sequencer_infra::shutdown::ShutdownController;
 use starknet_sequencer_node::config::component_config::ComponentConfig;
 use starknet_sequencer_node::config::component_execution_config::{
     ActiveComponentExecutionConfig,
@@ -43,6 +63,18 @@ use starknet_sequencer_node::utils::create_node_modules;
 use starknet_task_executor::tokio_executor::TokioExecutor;
 use tokio::runtime::Handle;
 
+const BUFFER_SIZE: usize = 1000;
+
+type MempoolTransactionsServer =
+    SqmrServerReceiver<MempoolTransactionsQuery, DataOrFin<RpcTransactionWrapper>>;
+
+// The channels of the other peer, which announces and serves transactions.
+struct PeerChannels {
+    broadcast_channels: BroadcastTopicChannels<NewTransactionHashes>,
+    transactions_server: MempoolTransactionsServer,
+    declare_transactions_server: MempoolTransactionsServer,
+}
+
 #[fixture]
 fn tx_generator() -> MultiAccountTransactionGenerator {
     create_integration_test_tx_generator()
@@ -51,7 +83,7 @@ fn tx_generator() -> MultiAccountTransactionGenerator {
 // TODO: remove code duplication with FlowTestSetup
 async fn setup(
     tx_generator: &MultiAccountTransactionGenerator,
-) -> (SequencerNodeConfig, BroadcastTopicChannels<RpcTransactionWrapper>) {
+) -> (SequencerNodeConfig, PeerChannels) {
     let accounts = tx_generator.accounts();
     let chain_info = create_chain_info();
     let storage_for_test = StorageTestSetup::new(accounts, &chain_info);
@@ -76,16 +108,28 @@ async fn setup(
 
     let batcher_config =
         create_batcher_config(storage_for_test.batcher_storage_config, chain_info.clone());
+    let chain_id = chain_info.chain_id.clone();
     let gateway_config = create_gateway_config(chain_info).await;
     let http_server_config = create_http_server_config().await;
     let rpc_state_reader_config = test_rpc_state_reader_config(rpc_server_addr);
-    let state_sync_config = create_state_sync_config(storage_for_test.state_sync_storage_config);
-    let (mut network_configs, broadcast_channels) =
-        create_network_configs_connected_to_broadcast_channels::<RpcTransactionWrapper>(
-            1,
-            Topic::new(MEMPOOL_TOPIC),
-        );
-    let network_config = network_configs.pop().unwrap();
+    let state_sync_config = create_state_sync_config(storage_for_test.state_sync_storage_config, 0);
+
+    // Create the network of the other peer, which announces and serves transactions.
+    let mut network_configs = create_connected_network_configs(2);
+    let mut network_config = network_configs.pop().unwrap();
+    network_config.chain_id = chain_id;
+    let mut peer_network_manager = NetworkManager::new(network_configs.pop().unwrap(), None);
+    let broadcast_channels = peer_network_manager
+        .register_broadcast_topic(Topic::new(MEMPOOL_TOPIC), BUFFER_SIZE)
+        .unwrap();
+    let transactions_server = peer_network_manager
+        .register_sqmr_protocol_server(MEMPOOL_TRANSACTIONS_PROTOCOL.to_string(), BUFFER_SIZE);
+    let declare_transactions_server = peer_network_manager.register_sqmr_protocol_server(
+        MEMPOOL_DECLARE_TRANSACTIONS_PROTOCOL.to_string(),
+        BUFFER_SIZE,
+    );
+    tokio::task::spawn(peer_network_manager.run());
+
     let mempool_p2p_config = MempoolP2pConfig { network_config, ..Default::default() };
     let config = SequencerNodeConfig {
         components,
@@ -97,7 +141,7 @@ async fn setup(
         state_sync_config,
         ..SequencerNodeConfig::default()
     };
-    (config, broadcast_channels)
+    (config, PeerChannels { broadcast_channels, transactions_server, declare_transactions_server })
 }
 
 #[rstest]
@@ -105,8 +149,8 @@ async fn setup(
 async fn test_mempool_sends_tx_to_other_peer(mut tx_generator: MultiAccountTransactionGenerator) {
     let handle = Handle::current();
     let task_executor = TokioExecutor::new(handle);
-    let (config, mut broadcast_channels) = setup(&tx_generator).await;
-    let (_clients, servers) = create_node_modules(&config);
+    let (config, mut peer_channels) = setup(&tx_generator).await;
+    let (_clients, servers) = create_node_modules(&config, &mut ShutdownController::new());
 
     let HttpServerConfig { ip, port } = config.http_server_config;
     let add_tx_http_client = HttpTestClient::new(SocketAddr::from((ip, port)));
@@ -120,23 +164,58 @@ async fn test_mempool_sends_tx_to_other_peer(mut tx_generator: MultiAccountTrans
     // flakiness.
     tokio::time::sleep(std::time::Duration::from_millis(1000)).await;
 
-    let mut expected_txs = HashSet::new();
-
     // Create and send transactions.
-    let _tx_hashes = run_integration_test_scenario(&mut tx_generator, &mut |tx: RpcTransaction| {
-        expected_txs.insert(tx.clone()); // push the sent tx to the expected_txs list
+    let tx_hashes = run_integration_test_scenario(&mut tx_generator, &mut |tx: RpcTransaction| {
         add_tx_http_client.assert_add_tx_success(tx)
     })
     .await;
 
-    while !expected_txs.is_empty() {
-        let tx =
-            broadcast_channels.broadcasted_messages_receiver.next().await.unwrap().0.unwrap().0;
-        assert!(expected_txs.contains(&tx));
-        expected_txs.remove(&tx);
+    // Only the hashes of the transactions are announced to the other peer.
+    let mut expected_tx_hashes: HashSet<_> = tx_hashes.into_iter().collect();
+    while !expected_tx_hashes.is_empty() {
+        let (message, _) =
+            peer_channels.broadcast_channels.broadcasted_messages_receiver.next().await.unwrap();
+        let NewTransactionHashes(announced_tx_hashes) = message.unwrap();
+        for tx_hash in announced_tx_hashes {
+            assert!(expected_tx_hashes.remove(&tx_hash));
+        }
     }
 }
 
+fn calculate_tx_hash(tx: &RpcTransaction, chain_id: &ChainId) -> TransactionHash {
+    match tx {
+        RpcTransaction::Invoke(tx) => {
+            InvokeTransaction::from_rpc_tx(tx.clone(), chain_id).unwrap().tx_hash()
+        }
+        RpcTransaction::DeployAccount(tx) => {
+            DeployAccountTransaction::from_rpc_tx(tx.clone(), chain_id).unwrap().tx_hash()
+        }
+        RpcTransaction::Declare(tx) => {
+            let tx: starknet_api::transaction::DeclareTransaction = tx.clone().into();
+            tx.calculate_transaction_hash(chain_id, &tx.version()).unwrap()
+        }
+    }
+}
+
+// Serves the transactions requested in the next query received by `transactions_server` among
+// `txs`.
+async fn serve_next_query(
+    transactions_server: &mut MempoolTransactionsServer,
+    txs: &HashMap<TransactionHash, RpcTransaction>,
+) {
+    let mut query_manager = transactions_server.next().await.unwrap();
+    let MempoolTransactionsQuery(requested_tx_hashes) = query_manager.query().clone().unwrap();
+    for tx_hash in requested_tx_hashes {
+        if let Some(tx) = txs.get(&tx_hash) {
+            let response = DataOrFin(Some(RpcTransactionWrapper(tx.clone())));
+            query_manager.send_response(response).await.unwrap();
+        }
+    }
+    query_manager.send_response(DataOrFin(None)).await.unwrap();
+    // The responses are flushed once the query manager is dropped.
+    drop(query_manager);
+}
+
 #[rstest]
 #[tokio::test]
 async fn test_mempool_receives_tx_from_other_peer(
@@ -147,8 +226,8 @@ async fn test_mempool_receives_tx_from_other_peer(
 
     let handle = Handle::current();
     let task_executor = TokioExecutor::new(handle);
-    let (config, mut broadcast_channels) = setup(&tx_generator).await;
-    let (clients, servers) = create_node_modules(&config);
+    let (config, mut peer_channels) = setup(&tx_generator).await;
+    let (clients, servers) = create_node_modules(&config, &mut ShutdownController::new());
     let mempool_client = clients.get_mempool_shared_client().unwrap();
     // Build and run the sequencer node.
     let sequencer_node_future = run_component_servers(servers);
@@ -158,41 +237,55 @@ async fn test_mempool_receives_tx_from_other_peer(
     // flakiness.
     tokio::time::sleep(std::time::Duration::from_millis(1000)).await;
 
-    let mut expected_txs = HashSet::new();
+    // The announced transactions, split by the protocol they're served over.
+    let mut expected_txs = HashMap::new();
+    let mut declare_txs = HashMap::new();
 
-    let _tx_hashes = run_integration_test_scenario(&mut tx_generator, &mut |tx: RpcTransaction| {
-        expected_txs.insert(tx.clone());
-        ready(TransactionHash::default()) // using the default value because we don't use the hash anyways.
+    let chain_id = create_chain_info().chain_id;
+    let tx_hashes = run_integration_test_scenario(&mut tx_generator, &mut |tx: RpcTransaction| {
+        let tx_hash = calculate_tx_hash(&tx, &chain_id);
+        match tx {
+            RpcTransaction::Declare(_) => declare_txs.insert(tx_hash, tx),
+            _ => expected_txs.insert(tx_hash, tx),
+        };
+        ready(tx_hash)
     })
     .await;
-    for tx in &expected_txs {
-        broadcast_channels
-            .broadcast_topic_client
-            .broadcast_message(RpcTransactionWrapper(tx.clone()))
-            .await
-            .unwrap();
+    peer_channels
+        .broadcast_channels
+        .broadcast_topic_client
+        .broadcast_message(NewTransactionHashes(tx_hashes))
+        .await
+        .unwrap();
+
+    // Serve the transactions requested by the mempool following the announcement. The ones that
+    // aren't served from the mempool are then requested as declare transactions.
+    serve_next_query(&mut peer_channels.transactions_server, &expected_txs).await;
+    if !declare_txs.is_empty() {
+        serve_next_query(&mut peer_channels.declare_transactions_server, &declare_txs).await;
     }
 
+    let n_expected_txs = expected_txs.len() + declare_txs.len();
     let mut received_txs: Vec<AccountTransaction> = vec![];
     // Polling for as many rounds as needed up to the set constant
     for _ in 0..(TXS_RETRIVAL_TIMEOUT / RECEIVED_TX_POLL_INTERVAL) {
-        if received_txs.len() == expected_txs.len() {
+        if received_txs.len() == n_expected_txs {
             break;
         }
         received_txs.append(
             // Querying for more txs than we sent verifies there are no extra txs
-            &mut mempool_client.get_txs(expected_txs.len() - received_txs.len() + 1).await.unwrap(),
+            &mut mempool_client.get_txs(n_expected_txs - received_txs.len() + 1).await.unwrap(),
         );
         tokio::time::sleep(std::time::Duration::from_millis(RECEIVED_TX_POLL_INTERVAL)).await;
     }
-    assert_eq!(received_txs.len(), expected_txs.len());
+    assert_eq!(received_txs.len(), n_expected_txs);
 
     for tx in received_txs {
         // TODO: change mempool to store RpcTransaction
         let converted_tx: RpcTransaction = match tx {
-            AccountTransaction::Declare(_declare_tx) => {
-                panic!("No implementation for converting DeclareTransaction to an RpcTransaction")
-            }
+            // The mempool doesn't hold the Sierra class of a declare transaction, so it can't be
+            // compared to the sent one. Received declare transactions are verified by count.
+            AccountTransaction::Declare(_) => continue,
             AccountTransaction::DeployAccount(deploy_account_transaction) => {
                 RpcTransaction::DeployAccount(RpcDeployAccountTransaction::V3(
                     deploy_account_transaction.clone().into(),
@@ -202,6 +295,6 @@ async fn test_mempool_receives_tx_from_other_peer(
                 RpcTransaction::Invoke(RpcInvokeTransaction::V3(invoke_transaction.clone().into()))
             }
         };
-        assert!(expected_txs.contains(&converted_tx));
+        assert!(expected_txs.values().any(|tx| tx == &converted_tx));
     }
 }
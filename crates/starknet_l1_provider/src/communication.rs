@@ -23,9 +23,21 @@ impl ComponentRequestHandler<L1ProviderRequest, L1ProviderResponse> for L1Provid
     #[instrument(skip(self))]
     async fn handle_request(&mut self, request: L1ProviderRequest) -> L1ProviderResponse {
         match request {
+            L1ProviderRequest::CommitBlock(committed_txs) => {
+                L1ProviderResponse::CommitBlock(self.commit_block(&committed_txs))
+            }
             L1ProviderRequest::GetTransactions(n_txs) => {
                 L1ProviderResponse::GetTransactions(self.get_txs(n_txs))
             }
+            L1ProviderRequest::ProposalStart => {
+                L1ProviderResponse::ProposalStart(self.proposal_start())
+            }
+            L1ProviderRequest::Validate(tx_hash) => {
+                L1ProviderResponse::Validate(self.validate(tx_hash))
+            }
+            L1ProviderRequest::ValidationStart => {
+                L1ProviderResponse::ValidationStart(self.validation_start())
+            }
         }
     }
 }
@@ -1,14 +1,25 @@
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+
 use assert_matches::assert_matches;
+use papyrus_base_layer::ethereum_base_layer_contract::EthereumBaseLayerError;
+use papyrus_base_layer::{L1Event, L1ToL2Message};
 use pretty_assertions::assert_eq;
+use starknet_api::core::{ChainId, Nonce};
 use starknet_api::test_utils::l1_handler::executable_l1_handler_tx;
+use starknet_api::transaction::fields::Fee;
 use starknet_api::transaction::TransactionHash;
 use starknet_api::{l1_handler_tx_args, tx_hash};
 use starknet_l1_provider_types::errors::L1ProviderError;
 use starknet_l1_provider_types::ValidationStatus;
+use starknet_sequencer_infra::component_definitions::ComponentStarter;
+use tokio::sync::mpsc::unbounded_channel;
 
+use crate::l1_scraper::{L1ScraperError, MockL1MessagesSource, ScrapedEvent};
+use crate::persistence::{L2IncludedTx, L2IncludedTxs};
 use crate::test_utils::L1ProviderContentBuilder;
-use crate::L1Provider;
 use crate::ProviderState::{Pending, Propose, Uninitialized, Validate};
+use crate::{create_l1_provider, L1Provider, L1ProviderConfig};
 
 macro_rules! tx {
     (tx_hash: $tx_hash:expr) => {{
@@ -127,3 +138,258 @@ fn validation_start_errors() {
         L1ProviderError::unexpected_transition(Validate, Propose)
     );
 }
+
+#[test]
+fn commit_block_happy_flow() {
+    // Setup.
+    let txs = [tx!(tx_hash: 1), tx!(tx_hash: 2), tx!(tx_hash: 3)];
+    let mut l1_provider = L1ProviderContentBuilder::new()
+        .with_txs(txs.clone())
+        .with_state(Propose)
+        .build_into_l1_provider();
+
+    // Test.
+    assert_eq!(l1_provider.get_txs(2).unwrap(), txs[..2]);
+    l1_provider.commit_block(&[tx_hash!(1)]).unwrap();
+    assert_eq!(l1_provider.state, Pending);
+
+    l1_provider.validation_start().unwrap();
+    assert_eq!(l1_provider.validate(tx_hash!(1)).unwrap(), ValidationStatus::AlreadyIncludedOnL2);
+    assert_eq!(l1_provider.validate(tx_hash!(2)).unwrap(), ValidationStatus::Validated);
+    l1_provider.commit_block(&[]).unwrap();
+
+    // Proposed transactions that weren't committed are proposed again.
+    l1_provider.proposal_start().unwrap();
+    assert_eq!(l1_provider.get_txs(3).unwrap(), txs[1..]);
+}
+
+#[test]
+fn commit_synced_block() {
+    // Setup.
+    let txs = [tx!(tx_hash: 1), tx!(tx_hash: 2), tx!(tx_hash: 3)];
+    let mut l1_provider = L1ProviderContentBuilder::new()
+        .with_txs(txs.clone())
+        .with_state(Pending)
+        .build_into_l1_provider();
+
+    // Test.
+    // A block committed without proposing or validating it, e.g., caught up from the state sync.
+    l1_provider.commit_block(&[tx_hash!(2)]).unwrap();
+    assert_eq!(l1_provider.state, Pending);
+
+    // Its transactions are no longer proposed.
+    l1_provider.proposal_start().unwrap();
+    assert_eq!(l1_provider.get_txs(3).unwrap(), [txs[0].clone(), txs[2].clone()]);
+}
+
+#[test]
+fn commit_block_uninitialized_errors() {
+    let mut uninitialized_l1_provider = L1Provider::default();
+
+    assert_eq!(
+        uninitialized_l1_provider.commit_block(&[]).unwrap_err(),
+        L1ProviderError::unexpected_transition(Uninitialized, Pending)
+    );
+}
+
+#[test]
+fn scraped_events_applied_between_blocks() {
+    // Setup.
+    let mut l1_provider = L1ProviderContentBuilder::new()
+        .with_txs([tx!(tx_hash: 1)])
+        .with_on_l2_awaiting_l1_consumption([tx_hash!(2), tx_hash!(3)])
+        .with_state(Pending)
+        .build_into_l1_provider();
+    let (sender, receiver) = unbounded_channel();
+    l1_provider.scraped_events = Some(receiver);
+
+    sender.send(ScrapedEvent::NewL1HandlerTransaction(tx!(tx_hash: 2))).unwrap();
+    sender.send(ScrapedEvent::NewL1HandlerTransaction(tx!(tx_hash: 4))).unwrap();
+    sender.send(ScrapedEvent::ConsumedOnL1(tx_hash!(1))).unwrap();
+    sender.send(ScrapedEvent::ConsumedOnL1(tx_hash!(3))).unwrap();
+
+    // Test.
+    l1_provider.proposal_start().unwrap();
+    assert_eq!(l1_provider.get_txs(1).unwrap(), [tx!(tx_hash: 4)]);
+
+    // Events scraped during a proposal wait for it to end.
+    sender.send(ScrapedEvent::NewL1HandlerTransaction(tx!(tx_hash: 5))).unwrap();
+    assert_eq!(l1_provider.get_txs(1).unwrap(), []);
+    l1_provider.commit_block(&[tx_hash!(4)]).unwrap();
+
+    l1_provider.validation_start().unwrap();
+    assert_eq!(l1_provider.validate(tx_hash!(2)).unwrap(), ValidationStatus::AlreadyIncludedOnL2);
+    assert_eq!(l1_provider.validate(tx_hash!(3)).unwrap(), ValidationStatus::ConsumedOnL1OrUnknown);
+    assert_eq!(l1_provider.validate(tx_hash!(4)).unwrap(), ValidationStatus::AlreadyIncludedOnL2);
+    assert_eq!(l1_provider.validate(tx_hash!(5)).unwrap(), ValidationStatus::Validated);
+}
+
+#[tokio::test]
+async fn reset_starts_scraping() {
+    // Setup.
+    let config = L1ProviderConfig { chain_id: ChainId::Mainnet, ..Default::default() };
+    let message = L1ToL2Message::default();
+    let expected_tx = message.to_l1_handler_tx(&config.chain_id, Fee(1)).unwrap();
+
+    let mut source = MockL1MessagesSource::new();
+    source.expect_latest_l1_block_number().returning(|_| Ok(Some(1000)));
+    source.expect_events().times(1).returning(move |_| {
+        Ok(vec![L1Event::LogMessageToL2 { message: message.clone(), fee: Fee(1) }])
+    });
+    let mut l1_provider = L1Provider::new(config, Arc::new(source)).unwrap();
+    assert_eq!(l1_provider.state, Uninitialized);
+
+    // Test.
+    l1_provider.reset().await.unwrap();
+    assert_eq!(l1_provider.state, Pending);
+
+    // Let the scraping task run its first poll.
+    tokio::task::yield_now().await;
+    l1_provider.proposal_start().unwrap();
+    assert_eq!(l1_provider.get_txs(2).unwrap(), [expected_tx]);
+}
+
+#[tokio::test]
+async fn reset_restores_l2_included_txs() {
+    // Setup.
+    let l2_included_txs_dir = tempfile::tempdir().unwrap();
+    let l2_included_txs_path = l2_included_txs_dir.path().join("l2_included_txs.json");
+    let config = L1ProviderConfig {
+        chain_id: ChainId::Mainnet,
+        l2_included_txs_path: Some(l2_included_txs_path.clone()),
+        ..Default::default()
+    };
+    let included_message = L1ToL2Message { nonce: Nonce(0_u8.into()), ..Default::default() };
+    let included_tx = included_message.to_l1_handler_tx(&config.chain_id, Fee(1)).unwrap();
+    let new_message = L1ToL2Message { nonce: Nonce(1_u8.into()), ..Default::default() };
+    let new_tx = new_message.to_l1_handler_tx(&config.chain_id, Fee(1)).unwrap();
+
+    // A previous run included the first transaction on L2.
+    let mut previous_l1_provider =
+        L1ProviderContentBuilder::new().with_state(Pending).build_into_l1_provider();
+    previous_l1_provider.config = config.clone();
+    previous_l1_provider.commit_block(&[included_tx.tx_hash]).unwrap();
+
+    let mut source = MockL1MessagesSource::new();
+    source.expect_latest_l1_block_number().returning(|_| Ok(Some(1000)));
+    source.expect_events().times(1).returning(move |_| {
+        Ok(vec![
+            L1Event::LogMessageToL2 { message: included_message.clone(), fee: Fee(1) },
+            L1Event::LogMessageToL2 { message: new_message.clone(), fee: Fee(1) },
+        ])
+    });
+    let mut l1_provider = L1Provider::new(config, Arc::new(source)).unwrap();
+
+    // Test.
+    l1_provider.reset().await.unwrap();
+
+    // Assert: the rescraped transaction that is already on L2 isn't offered again.
+    tokio::task::yield_now().await;
+    l1_provider.proposal_start().unwrap();
+    assert_eq!(l1_provider.get_txs(2).unwrap(), [new_tx]);
+    l1_provider.commit_block(&[]).unwrap();
+    l1_provider.validation_start().unwrap();
+    assert_eq!(
+        l1_provider.validate(included_tx.tx_hash).unwrap(),
+        ValidationStatus::AlreadyIncludedOnL2
+    );
+}
+
+#[tokio::test]
+async fn l2_included_txs_are_pruned() {
+    // Setup.
+    let l2_included_txs_dir = tempfile::tempdir().unwrap();
+    let l2_included_txs_path = l2_included_txs_dir.path().join("l2_included_txs.json");
+    let config = L1ProviderConfig {
+        l2_included_txs_path: Some(l2_included_txs_path.clone()),
+        ..Default::default()
+    };
+    // The first transaction was included before the range rescraped on startup.
+    let now = SystemTime::now();
+    let old_inclusion = now - config.startup_rewind_time - Duration::from_secs(60 * 60);
+    L2IncludedTxs {
+        txs: vec![
+            L2IncludedTx { tx_hash: tx_hash!(1), included_at: old_inclusion },
+            L2IncludedTx { tx_hash: tx_hash!(2), included_at: now },
+            L2IncludedTx { tx_hash: tx_hash!(3), included_at: now },
+        ],
+    }
+    .store(&l2_included_txs_path)
+    .unwrap();
+
+    let mut source = MockL1MessagesSource::new();
+    source.expect_latest_l1_block_number().returning(|_| Ok(Some(1000)));
+    source.expect_events().returning(|_| Ok(vec![]));
+    let mut l1_provider = L1Provider::new(config, Arc::new(source)).unwrap();
+    l1_provider.reset().await.unwrap();
+
+    let (sender, receiver) = unbounded_channel();
+    l1_provider.scraped_events = Some(receiver);
+    sender.send(ScrapedEvent::ConsumedOnL1(tx_hash!(2))).unwrap();
+
+    // Test.
+    l1_provider.commit_block(&[]).unwrap();
+
+    // Assert: only the transaction that is neither consumed nor too old is kept.
+    let l2_included_txs = L2IncludedTxs::load(&l2_included_txs_path).unwrap().unwrap();
+    assert_eq!(l2_included_txs.txs, [L2IncludedTx { tx_hash: tx_hash!(3), included_at: now }]);
+}
+
+#[tokio::test]
+async fn start_scrapes_the_configured_source() {
+    // Setup.
+    let config = L1ProviderConfig { chain_id: ChainId::Mainnet, ..Default::default() };
+    let message = L1ToL2Message::default();
+    let expected_tx = message.to_l1_handler_tx(&config.chain_id, Fee(1)).unwrap();
+
+    let mut source = MockL1MessagesSource::new();
+    source.expect_latest_l1_block_number().returning(|_| Ok(Some(1000)));
+    source.expect_events().times(1).returning(move |_| {
+        Ok(vec![L1Event::LogMessageToL2 { message: message.clone(), fee: Fee(1) }])
+    });
+    let mut l1_provider = create_l1_provider(config, Some(Arc::new(source)));
+    assert_eq!(l1_provider.state, Uninitialized);
+
+    // Test.
+    l1_provider.start().await.unwrap();
+    assert_eq!(l1_provider.state, Pending);
+
+    // Let the scraping task run its first poll.
+    tokio::task::yield_now().await;
+    l1_provider.proposal_start().unwrap();
+    assert_eq!(l1_provider.get_txs(2).unwrap(), [expected_tx]);
+}
+
+#[tokio::test(start_paused = true)]
+async fn start_retries_while_l1_is_unreachable() {
+    // Setup.
+    let mut source = MockL1MessagesSource::new();
+    source.expect_latest_l1_block_number().times(2).returning(|_| {
+        Err(L1ScraperError::BaseLayer(EthereumBaseLayerError::OutOfRange {
+            event: "LogMessageToL2",
+            value: "0x1".to_string(),
+        }))
+    });
+    source.expect_latest_l1_block_number().returning(|_| Ok(Some(1000)));
+    source.expect_events().returning(|_| Ok(vec![]));
+    let mut l1_provider = create_l1_provider(L1ProviderConfig::default(), Some(Arc::new(source)));
+
+    // Test.
+    let start_time = tokio::time::Instant::now();
+    l1_provider.start().await.unwrap();
+
+    // Assert: the provider waited for one second, then two, before L1 was reachable.
+    assert_eq!(start_time.elapsed(), Duration::from_secs(3));
+    assert_eq!(l1_provider.state, Pending);
+}
+
+#[tokio::test]
+async fn start_without_source() {
+    let mut l1_provider = create_l1_provider(L1ProviderConfig::default(), None);
+
+    l1_provider.start().await.unwrap();
+    assert_eq!(l1_provider.state, Pending);
+
+    l1_provider.proposal_start().unwrap();
+    assert_eq!(l1_provider.get_txs(1).unwrap(), []);
+}
@@ -0,0 +1,201 @@
+use std::cmp::min;
+use std::fmt::Debug;
+use std::ops::RangeInclusive;
+use std::sync::Arc;
+use std::time::Duration;
+
+use async_trait::async_trait;
+#[cfg(any(feature = "testing", test))]
+use mockall::automock;
+use papyrus_base_layer::ethereum_base_layer_contract::EthereumBaseLayerError;
+use papyrus_base_layer::{BaseLayerContract, L1BlockNumber, L1Event};
+use starknet_api::executable_transaction::L1HandlerTransaction;
+use starknet_api::transaction::fields::Fee;
+use starknet_api::transaction::TransactionHash;
+use starknet_api::StarknetApiError;
+use thiserror::Error;
+use tokio::sync::mpsc::UnboundedSender;
+use tracing::{debug, error, warn};
+
+use crate::L1ProviderConfig;
+
+#[cfg(test)]
+#[path = "l1_scraper_tests.rs"]
+pub mod l1_scraper_tests;
+
+/// Ethereum's slot time, used to translate the startup rewind time into a number of L1 blocks.
+pub const L1_BLOCK_TIME: Duration = Duration::from_secs(12);
+
+pub type L1ScraperResult<T> = Result<T, L1ScraperError>;
+
+#[derive(Error, Debug)]
+pub enum L1ScraperError {
+    #[error(transparent)]
+    BaseLayer(#[from] EthereumBaseLayerError),
+}
+
+impl L1ScraperError {
+    /// Whether the same request may succeed when retried, as opposed to errors in the scraped data
+    /// itself, which would fail again.
+    pub fn is_transient(&self) -> bool {
+        matches!(
+            self,
+            Self::BaseLayer(
+                EthereumBaseLayerError::RpcError(_) | EthereumBaseLayerError::Contract(_)
+            )
+        )
+    }
+}
+
+/// The base layer as seen by the scraper.
+#[cfg_attr(any(feature = "testing", test), automock)]
+#[async_trait]
+pub trait L1MessagesSource: Debug + Send + Sync {
+    /// Returns the latest L1 block number, `finality` blocks back from the tip, or `None` if the
+    /// chain is not long enough.
+    async fn latest_l1_block_number(&self, finality: u64)
+    -> L1ScraperResult<Option<L1BlockNumber>>;
+
+    /// Returns the messaging events emitted in the given L1 block range, in order.
+    async fn events(
+        &self,
+        block_range: RangeInclusive<L1BlockNumber>,
+    ) -> L1ScraperResult<Vec<L1Event>>;
+}
+
+#[async_trait]
+impl<BaseLayer> L1MessagesSource for BaseLayer
+where
+    BaseLayer: BaseLayerContract<Error = EthereumBaseLayerError> + Debug + Send + Sync,
+{
+    async fn latest_l1_block_number(
+        &self,
+        finality: u64,
+    ) -> L1ScraperResult<Option<L1BlockNumber>> {
+        let latest_l1_block = self.latest_l1_block(finality).await?;
+        Ok(latest_l1_block.map(|block| block.number))
+    }
+
+    async fn events(
+        &self,
+        block_range: RangeInclusive<L1BlockNumber>,
+    ) -> L1ScraperResult<Vec<L1Event>> {
+        Ok(BaseLayerContract::events(self, block_range).await?)
+    }
+}
+
+/// An update to the provider's L1 buffers, derived from a core contract event.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum ScrapedEvent {
+    NewL1HandlerTransaction(L1HandlerTransaction),
+    ConsumedOnL1(TransactionHash),
+}
+
+/// Collects messaging events from L1, starting from a fixed L1 block and moving forward.
+#[derive(Debug)]
+pub struct L1Scraper {
+    source: Arc<dyn L1MessagesSource>,
+    config: L1ProviderConfig,
+    next_block_to_scrape: L1BlockNumber,
+}
+
+impl L1Scraper {
+    /// Creates a scraper whose first scrape starts `config.startup_rewind_time` back from the
+    /// latest L1 block, so that recent messages are not missed after a (re)start.
+    pub async fn new(
+        source: Arc<dyn L1MessagesSource>,
+        config: L1ProviderConfig,
+    ) -> L1ScraperResult<Self> {
+        let latest_l1_block_number =
+            source.latest_l1_block_number(config.l1_finality).await?.unwrap_or_default();
+        let rewind_in_blocks = config.startup_rewind_time.as_secs() / L1_BLOCK_TIME.as_secs();
+        let next_block_to_scrape = latest_l1_block_number.saturating_sub(rewind_in_blocks);
+
+        Ok(Self { source, config, next_block_to_scrape })
+    }
+
+    pub fn next_block_to_scrape(&self) -> L1BlockNumber {
+        self.next_block_to_scrape
+    }
+
+    /// Returns the events emitted since the previous call, up to the latest final L1 block, and up
+    /// to `config.max_blocks_per_poll` blocks; the following blocks are left to the next calls.
+    /// Malformed events are skipped; an error is returned only if the range should be retried.
+    pub async fn fetch_events(&mut self) -> L1ScraperResult<Vec<ScrapedEvent>> {
+        let Some(latest_l1_block_number) =
+            self.source.latest_l1_block_number(self.config.l1_finality).await?
+        else {
+            return Ok(vec![]);
+        };
+        if latest_l1_block_number < self.next_block_to_scrape {
+            return Ok(vec![]);
+        }
+
+        let last_block_to_scrape = min(
+            latest_l1_block_number,
+            self.next_block_to_scrape
+                .saturating_add(self.config.max_blocks_per_poll.saturating_sub(1)),
+        );
+        let block_range = self.next_block_to_scrape..=last_block_to_scrape;
+        let events = match self.source.events(block_range.clone()).await {
+            Ok(events) => events,
+            Err(err) if err.is_transient() => return Err(err),
+            // Scraping the same range again would fail the same way, so it is skipped.
+            Err(err) => {
+                error!("Skipping L1 blocks {block_range:?}, failed to parse their events: {err}");
+                self.next_block_to_scrape = last_block_to_scrape + 1;
+                return Ok(vec![]);
+            }
+        };
+        let scraped_events: Vec<_> = events
+            .iter()
+            .filter_map(|event| match self.scraped_event(event) {
+                Ok(scraped_event) => Some(scraped_event),
+                Err(err) => {
+                    warn!("Skipping L1 event {event:?}, failed to convert it: {err}");
+                    None
+                }
+            })
+            .collect();
+        debug!("Scraped {} events from L1 blocks {block_range:?}.", scraped_events.len());
+
+        self.next_block_to_scrape = last_block_to_scrape + 1;
+        Ok(scraped_events)
+    }
+
+    /// Polls L1 every `config.polling_interval` and forwards the scraped events to the provider.
+    /// Returns once the provider drops its receiver.
+    pub async fn run(mut self, sender: UnboundedSender<ScrapedEvent>) {
+        while !sender.is_closed() {
+            match self.fetch_events().await {
+                Ok(events) => {
+                    for event in events {
+                        if sender.send(event).is_err() {
+                            return;
+                        }
+                    }
+                }
+                // Only transient errors are returned, so the same range is retried on the next
+                // poll.
+                Err(err) => error!("Failed to scrape L1 events: {err}"),
+            }
+
+            tokio::time::sleep(self.config.polling_interval).await;
+        }
+    }
+
+    fn scraped_event(&self, event: &L1Event) -> Result<ScrapedEvent, StarknetApiError> {
+        let chain_id = &self.config.chain_id;
+        Ok(match event {
+            L1Event::LogMessageToL2 { message, fee } => {
+                ScrapedEvent::NewL1HandlerTransaction(message.to_l1_handler_tx(chain_id, *fee)?)
+            }
+            L1Event::ConsumedMessageToL2(message) => {
+                // The fee isn't part of the transaction hash.
+                ScrapedEvent::ConsumedOnL1(
+                    message.to_l1_handler_tx(chain_id, Fee::default())?.tx_hash,
+                )
+            }
+        })
+    }
+}
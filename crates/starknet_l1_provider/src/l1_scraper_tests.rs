@@ -0,0 +1,183 @@
+use std::sync::Arc;
+
+use mockall::predicate::eq;
+use papyrus_base_layer::ethereum_base_layer_contract::EthereumBaseLayerError;
+use papyrus_base_layer::{L1Event, L1ToL2Message};
+use pretty_assertions::assert_eq;
+use starknet_api::core::{ChainId, EthAddress, Nonce};
+use starknet_api::transaction::fields::Fee;
+use starknet_api::{contract_address, felt};
+
+use crate::l1_scraper::{
+    L1Scraper,
+    L1ScraperError,
+    MockL1MessagesSource,
+    ScrapedEvent,
+    L1_BLOCK_TIME,
+};
+use crate::L1ProviderConfig;
+
+const LATEST_L1_BLOCK_NUMBER: u64 = 1000;
+
+fn message(nonce: u8) -> L1ToL2Message {
+    L1ToL2Message {
+        from_address: EthAddress::try_from(felt!("0x1")).unwrap(),
+        to_address: contract_address!("0x2"),
+        payload: vec![felt!("0x3")],
+        nonce: Nonce(felt!(nonce)),
+        ..Default::default()
+    }
+}
+
+fn config() -> L1ProviderConfig {
+    L1ProviderConfig { chain_id: ChainId::Mainnet, ..Default::default() }
+}
+
+async fn scraper(source: MockL1MessagesSource) -> L1Scraper {
+    L1Scraper::new(Arc::new(source), config()).await.unwrap()
+}
+
+#[tokio::test]
+async fn new_scraper_rewinds_startup_rewind_time() {
+    // Setup.
+    let mut source = MockL1MessagesSource::new();
+    source
+        .expect_latest_l1_block_number()
+        .with(eq(config().l1_finality))
+        .returning(|_| Ok(Some(LATEST_L1_BLOCK_NUMBER)));
+
+    // Test.
+    let scraper = scraper(source).await;
+
+    let rewind_in_blocks = config().startup_rewind_time.as_secs() / L1_BLOCK_TIME.as_secs();
+    assert_eq!(scraper.next_block_to_scrape(), LATEST_L1_BLOCK_NUMBER - rewind_in_blocks);
+}
+
+#[tokio::test]
+async fn new_scraper_on_short_chain_starts_from_genesis() {
+    // Setup.
+    let mut source = MockL1MessagesSource::new();
+    source.expect_latest_l1_block_number().returning(|_| Ok(Some(10)));
+
+    // Test.
+    assert_eq!(scraper(source).await.next_block_to_scrape(), 0);
+}
+
+#[tokio::test]
+async fn fetch_events_happy_flow() {
+    // Setup.
+    let rewind_in_blocks = config().startup_rewind_time.as_secs() / L1_BLOCK_TIME.as_secs();
+    let first_block = LATEST_L1_BLOCK_NUMBER - rewind_in_blocks;
+    let fee = Fee(100);
+
+    let mut source = MockL1MessagesSource::new();
+    // Once for the scraper creation, then once per fetch.
+    let mut latest_l1_block_numbers =
+        [LATEST_L1_BLOCK_NUMBER, LATEST_L1_BLOCK_NUMBER, LATEST_L1_BLOCK_NUMBER + 10].into_iter();
+    source
+        .expect_latest_l1_block_number()
+        .times(3)
+        .returning(move |_| Ok(latest_l1_block_numbers.next()));
+    source.expect_events().with(eq(first_block..=LATEST_L1_BLOCK_NUMBER)).returning(move |_| {
+        Ok(vec![
+            L1Event::LogMessageToL2 { message: message(0), fee },
+            L1Event::ConsumedMessageToL2(message(1)),
+        ])
+    });
+    source
+        .expect_events()
+        .with(eq(LATEST_L1_BLOCK_NUMBER + 1..=LATEST_L1_BLOCK_NUMBER + 10))
+        .returning(|_| Ok(vec![]));
+    let mut scraper = scraper(source).await;
+
+    // Test.
+    let expected_tx = message(0).to_l1_handler_tx(&config().chain_id, fee).unwrap();
+    let consumed_tx_hash =
+        message(1).to_l1_handler_tx(&config().chain_id, Fee::default()).unwrap().tx_hash;
+    assert_eq!(
+        scraper.fetch_events().await.unwrap(),
+        [
+            ScrapedEvent::NewL1HandlerTransaction(expected_tx),
+            ScrapedEvent::ConsumedOnL1(consumed_tx_hash)
+        ]
+    );
+    assert_eq!(scraper.next_block_to_scrape(), LATEST_L1_BLOCK_NUMBER + 1);
+
+    // The next fetch continues from where the previous one stopped.
+    assert_eq!(scraper.fetch_events().await.unwrap(), []);
+    assert_eq!(scraper.next_block_to_scrape(), LATEST_L1_BLOCK_NUMBER + 11);
+}
+
+#[tokio::test]
+async fn fetch_events_scrapes_at_most_max_blocks_per_poll() {
+    // Setup.
+    let config = L1ProviderConfig { max_blocks_per_poll: 100, ..config() };
+    let rewind_in_blocks = config.startup_rewind_time.as_secs() / L1_BLOCK_TIME.as_secs();
+    let first_block = LATEST_L1_BLOCK_NUMBER - rewind_in_blocks;
+
+    let mut source = MockL1MessagesSource::new();
+    source.expect_latest_l1_block_number().returning(|_| Ok(Some(LATEST_L1_BLOCK_NUMBER)));
+    source
+        .expect_events()
+        .with(eq(first_block..=first_block + 99))
+        .times(1)
+        .returning(|_| Ok(vec![]));
+    source
+        .expect_events()
+        .with(eq(first_block + 100..=first_block + 199))
+        .times(1)
+        .returning(|_| Ok(vec![]));
+    let mut scraper = L1Scraper::new(Arc::new(source), config).await.unwrap();
+
+    // Test.
+    scraper.fetch_events().await.unwrap();
+    assert_eq!(scraper.next_block_to_scrape(), first_block + 100);
+
+    // The next fetch continues with the following chunk.
+    scraper.fetch_events().await.unwrap();
+    assert_eq!(scraper.next_block_to_scrape(), first_block + 200);
+}
+
+#[tokio::test]
+async fn fetch_events_without_new_blocks() {
+    // Setup.
+    let mut source = MockL1MessagesSource::new();
+    source.expect_latest_l1_block_number().returning(|_| Ok(Some(LATEST_L1_BLOCK_NUMBER)));
+    source.expect_events().times(1).returning(|_| Ok(vec![]));
+    let mut scraper = scraper(source).await;
+
+    // Test.
+    scraper.fetch_events().await.unwrap();
+    assert_eq!(scraper.fetch_events().await.unwrap(), []);
+    assert_eq!(scraper.next_block_to_scrape(), LATEST_L1_BLOCK_NUMBER + 1);
+}
+
+#[tokio::test]
+async fn fetch_events_skips_range_with_malformed_event() {
+    // Setup.
+    let mut source = MockL1MessagesSource::new();
+    source.expect_latest_l1_block_number().returning(|_| Ok(Some(LATEST_L1_BLOCK_NUMBER)));
+    source.expect_events().times(1).returning(|_| {
+        Err(L1ScraperError::BaseLayer(EthereumBaseLayerError::OutOfRange {
+            event: "LogMessageToL2",
+            value: "0x1".to_string(),
+        }))
+    });
+    let mut scraper = scraper(source).await;
+
+    // Test.
+    assert_eq!(scraper.fetch_events().await.unwrap(), []);
+    assert_eq!(scraper.next_block_to_scrape(), LATEST_L1_BLOCK_NUMBER + 1);
+}
+
+#[test]
+fn message_to_l1_handler_tx() {
+    let tx = message(7).to_l1_handler_tx(&config().chain_id, Fee(100)).unwrap();
+
+    // The sender's address prepends the payload.
+    assert_eq!(*tx.tx.calldata.0, [felt!("0x1"), felt!("0x3")]);
+    assert_eq!(tx.payload_size(), 1);
+    assert_eq!(tx.tx.nonce, Nonce(felt!(7_u8)));
+    assert_eq!(tx.tx.contract_address, contract_address!("0x2"));
+    assert_eq!(tx.paid_fee_on_l1, Fee(100));
+}
@@ -1,22 +1,44 @@
 pub mod communication;
-pub mod errors;
+pub mod l1_scraper;
+pub mod persistence;
 
 #[cfg(test)]
 pub mod test_utils;
 
+use std::cmp::min;
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+
+use async_trait::async_trait;
 use indexmap::{IndexMap, IndexSet};
+use papyrus_config::converters::deserialize_seconds_to_duration;
+use papyrus_config::dumping::{ser_optional_param, ser_param, SerializeConfig};
+use papyrus_config::{ParamPath, ParamPrivacyInput, SerializedParam};
 use serde::{Deserialize, Serialize};
+use starknet_api::core::ChainId;
 use starknet_api::executable_transaction::L1HandlerTransaction;
 use starknet_api::transaction::TransactionHash;
 use starknet_l1_provider_types::errors::L1ProviderError;
 use starknet_l1_provider_types::{L1ProviderResult, ValidationStatus};
 use starknet_sequencer_infra::component_definitions::ComponentStarter;
+use starknet_sequencer_infra::errors::ComponentError;
+use tokio::sync::mpsc::{unbounded_channel, UnboundedReceiver};
+use tokio::task::JoinHandle;
+use tracing::{debug, error, info, warn};
 use validator::Validate;
 
+use crate::l1_scraper::{L1MessagesSource, L1Scraper, ScrapedEvent, L1_BLOCK_TIME};
+use crate::persistence::{L2IncludedTx, L2IncludedTxs};
+
 #[cfg(test)]
 #[path = "l1_provider_tests.rs"]
 pub mod l1_provider_tests;
 
+const START_RETRY_INITIAL_BACKOFF: Duration = Duration::from_secs(1);
+const START_RETRY_MAX_BACKOFF: Duration = Duration::from_secs(60);
+
 // TODO: optimistic proposer support, will add later to keep things simple, but the design here
 // is compatible with it.
 #[derive(Debug, Default)]
@@ -25,11 +47,20 @@ pub struct L1Provider {
     // TODO(Gilad): consider transitioning to a generic phantom state once the infra is stabilized
     // and we see how well it handles consuming the L1Provider when moving between states.
     state: ProviderState,
+    config: L1ProviderConfig,
+    l1_messages_source: Option<Arc<dyn L1MessagesSource>>,
+    // Events collected by the scraping task, applied to the buffers between blocks.
+    scraped_events: Option<UnboundedReceiver<ScrapedEvent>>,
+    scraping_task: Option<JoinHandle<()>>,
 }
 
 impl L1Provider {
-    pub fn new(_config: L1ProviderConfig) -> L1ProviderResult<Self> {
-        todo!("Init crawler in uninitialized_state from config, to initialize call `reset`.");
+    /// Creates an uninitialized provider; it starts scraping `l1_messages_source` on `reset`.
+    pub fn new(
+        config: L1ProviderConfig,
+        l1_messages_source: Arc<dyn L1MessagesSource>,
+    ) -> L1ProviderResult<Self> {
+        Ok(Self { config, l1_messages_source: Some(l1_messages_source), ..Default::default() })
     }
 
     /// Retrieves up to `n_txs` transactions that have yet to be proposed or accepted on L2.
@@ -55,22 +86,28 @@ impl L1Provider {
 
     // TODO: when deciding on consensus, if possible, have commit_block also tell the node if it's
     // about to [optimistically-]propose or validate the next block.
-    pub fn commit_block(&mut self, _commited_txs: &[TransactionHash]) {
-        todo!(
-            "Purges txs from internal buffers, if was proposer clear staging buffer, 
-            reset state to Pending until we get proposing/validating notice from consensus."
-        )
+    /// Marks the committed transactions as included on L2, returns the uncommitted proposed ones
+    /// to the pool, and waits in `Pending` for the next proposal or validation.
+    pub fn commit_block(&mut self, committed_txs: &[TransactionHash]) -> L1ProviderResult<()> {
+        self.state = self.state.transition_to_pending()?;
+        self.tx_manager.commit_txs(committed_txs, SystemTime::now());
+        self.apply_scraped_events();
+        self.prune_l2_included_txs();
+        self.persist_l2_included_txs();
+        Ok(())
     }
 
     // TODO: pending formal consensus API, guessing the API here to keep things moving.
     // TODO: consider adding block number, it isn't strictly necessary, but will help debugging.
     pub fn validation_start(&mut self) -> L1ProviderResult<()> {
         self.state = self.state.transition_to_validate()?;
+        self.apply_scraped_events();
         Ok(())
     }
 
     pub fn proposal_start(&mut self) -> L1ProviderResult<()> {
         self.state = self.state.transition_to_propose()?;
+        self.apply_scraped_events();
         Ok(())
     }
 
@@ -80,32 +117,137 @@ impl L1Provider {
         self.reset().await
     }
 
-    // TODO: this will likely change during integration with infra team.
-    pub async fn start(&self) {
-        todo!(
-            "Create a process that wakes up every config.poll_interval seconds and updates
-        internal L1 and L2 buffers according to collected L1 events and recent blocks created on
-        L2."
-        )
+    /// Clears the L1 buffers and restarts scraping from `config.startup_rewind_time` ago, then
+    /// waits in `Pending` for the next proposal or validation.
+    pub async fn reset(&mut self) -> L1ProviderResult<()> {
+        let l1_messages_source = self
+            .l1_messages_source
+            .clone()
+            .expect("L1 provider cannot be reset without an L1 messages source.");
+
+        if let Some(scraping_task) = self.scraping_task.take() {
+            scraping_task.abort();
+        }
+        // Transactions included on L2 are unaffected by L1 reorgs, so they are kept. On startup,
+        // they are restored before scraping, so that rescraped ones aren't offered again.
+        self.tx_manager.clear_l1_buffers();
+        self.restore_l2_included_txs()?;
+
+        let scraper = L1Scraper::new(l1_messages_source, self.config.clone())
+            .await
+            .map_err(|err| L1ProviderError::BaseLayer(err.to_string()))?;
+        info!("Scraping L1 events starting from L1 block {}.", scraper.next_block_to_scrape());
+        let (sender, receiver) = unbounded_channel();
+        self.scraped_events = Some(receiver);
+        self.scraping_task = Some(tokio::spawn(scraper.run(sender)));
+
+        self.state = ProviderState::Pending;
+        Ok(())
     }
 
-    pub async fn reset(&mut self) -> L1ProviderResult<()> {
-        todo!(
-            "resets internal buffers and rewinds the internal crawler _pointer_ back for ~1 \
-             hour,so that the main loop will start collecting from that time gracefully. May hit \
-             base layer errors when finding the latest block on l1 to 'subtract' 1 hour from. \
-             Then, transition to Pending."
+    fn restore_l2_included_txs(&mut self) -> L1ProviderResult<()> {
+        let Some(path) = self.config.l2_included_txs_path.as_ref() else {
+            return Ok(());
+        };
+        let l2_included_txs = L2IncludedTxs::load(path)
+            .map_err(|err| L1ProviderError::Storage(err.to_string()))?
+            .unwrap_or_default();
+        for tx in &l2_included_txs.txs {
+            self.tx_manager.mark_tx_included_on_l2(&tx.tx_hash, tx.included_at);
+        }
+        self.prune_l2_included_txs();
+        info!(
+            "Restored {} L1 handler transactions included on L2.",
+            self.tx_manager.on_l2_awaiting_l1_consumption.len()
         );
+        Ok(())
+    }
+
+    // A transaction is included on L2 after its message is logged on L1, so once its inclusion is
+    // older than the range rescraped on reset, it can't be rescraped and is no longer tracked, even
+    // if its consumption on L1 was missed.
+    fn prune_l2_included_txs(&mut self) {
+        let rescraped_time = self.config.startup_rewind_time
+            + Duration::from_secs(self.config.l1_finality * L1_BLOCK_TIME.as_secs());
+        let Some(oldest_rescraped_inclusion) = SystemTime::now().checked_sub(rescraped_time) else {
+            return;
+        };
+        self.tx_manager
+            .on_l2_awaiting_l1_consumption
+            .retain(|_, included_at| *included_at >= oldest_rescraped_inclusion);
+    }
+
+    fn persist_l2_included_txs(&self) {
+        let Some(path) = self.config.l2_included_txs_path.as_ref() else {
+            return;
+        };
+        let l2_included_txs = L2IncludedTxs {
+            txs: self
+                .tx_manager
+                .on_l2_awaiting_l1_consumption
+                .iter()
+                .map(|(&tx_hash, &included_at)| L2IncludedTx { tx_hash, included_at })
+                .collect(),
+        };
+        if let Err(err) = l2_included_txs.store(path) {
+            error!("Failed to persist the L1 handler transactions included on L2: {err}");
+        }
+    }
+
+    // Only called outside of proposals and validations, so that the buffers don't change under a
+    // block that is being built.
+    fn apply_scraped_events(&mut self) {
+        let Some(scraped_events) = self.scraped_events.as_mut() else {
+            return;
+        };
+
+        while let Ok(event) = scraped_events.try_recv() {
+            match event {
+                ScrapedEvent::NewL1HandlerTransaction(tx) => {
+                    self.tx_manager.add_unconsumed_l1_not_in_l2_block_tx(tx)
+                }
+                ScrapedEvent::ConsumedOnL1(tx_hash) => {
+                    self.tx_manager.mark_tx_consumed_on_l1(&tx_hash)
+                }
+            }
+        }
     }
 }
 
-impl ComponentStarter for L1Provider {}
+#[async_trait]
+impl ComponentStarter for L1Provider {
+    async fn start(&mut self) -> Result<(), ComponentError> {
+        if self.l1_messages_source.is_none() {
+            info!("No L1 messages source configured, L1 events will not be scraped.");
+            return Ok(());
+        }
+
+        // L1 may be unreachable for a while, e.g., while the node and its L1 endpoint start
+        // together, so scraping is retried rather than failing the node.
+        let mut backoff = START_RETRY_INITIAL_BACKOFF;
+        loop {
+            match self.reset().await {
+                Ok(()) => return Ok(()),
+                Err(err @ L1ProviderError::BaseLayer(_)) => {
+                    warn!("Failed to start scraping L1, retrying in {backoff:?}: {err}");
+                    tokio::time::sleep(backoff).await;
+                    backoff = min(backoff * 2, START_RETRY_MAX_BACKOFF);
+                }
+                Err(err) => {
+                    error!("Failed to start the L1 provider: {err}");
+                    return Err(ComponentError::InternalComponentError);
+                }
+            }
+        }
+    }
+}
 
 #[derive(Debug, Default)]
 struct TransactionManager {
     txs: IndexMap<TransactionHash, L1HandlerTransaction>,
     proposed_txs: IndexSet<TransactionHash>,
-    on_l2_awaiting_l1_consumption: IndexSet<TransactionHash>,
+    // Mapped to the time they were included at.
+    on_l2_awaiting_l1_consumption: IndexMap<TransactionHash, SystemTime>,
 }
 
 impl TransactionManager {
@@ -125,22 +267,44 @@ impl TransactionManager {
     pub fn tx_status(&self, tx_hash: TransactionHash) -> ValidationStatus {
         if self.txs.contains_key(&tx_hash) {
             ValidationStatus::Validated
-        } else if self.on_l2_awaiting_l1_consumption.contains(&tx_hash) {
+        } else if self.on_l2_awaiting_l1_consumption.contains_key(&tx_hash) {
             ValidationStatus::AlreadyIncludedOnL2
         } else {
             ValidationStatus::ConsumedOnL1OrUnknown
         }
     }
 
-    pub fn _add_unconsumed_l1_not_in_l2_block_tx(&mut self, _tx: L1HandlerTransaction) {
-        todo!(
-            "Check if tx is in L2, if it isn't on L2 add it to the txs buffer, otherwise print
-             debug and do nothing."
-        )
+    pub fn add_unconsumed_l1_not_in_l2_block_tx(&mut self, tx: L1HandlerTransaction) {
+        if self.on_l2_awaiting_l1_consumption.contains_key(&tx.tx_hash) {
+            debug!("Skipping L1 handler transaction {} which is already on L2.", tx.tx_hash);
+            return;
+        }
+
+        // Rescraped transactions keep their place in the queue.
+        self.txs.entry(tx.tx_hash).or_insert(tx);
+    }
+
+    pub fn mark_tx_included_on_l2(&mut self, tx_hash: &TransactionHash, included_at: SystemTime) {
+        self.txs.shift_remove(tx_hash);
+        self.on_l2_awaiting_l1_consumption.entry(*tx_hash).or_insert(included_at);
+    }
+
+    pub fn mark_tx_consumed_on_l1(&mut self, tx_hash: &TransactionHash) {
+        self.txs.shift_remove(tx_hash);
+        self.on_l2_awaiting_l1_consumption.shift_remove(tx_hash);
+    }
+
+    /// Proposed transactions that weren't committed become available for proposal again.
+    pub fn commit_txs(&mut self, committed_txs: &[TransactionHash], committed_at: SystemTime) {
+        self.proposed_txs.clear();
+        for tx_hash in committed_txs {
+            self.mark_tx_included_on_l2(tx_hash, committed_at);
+        }
     }
 
-    pub fn _mark_tx_included_on_l2(&mut self, _tx_hash: &TransactionHash) {
-        todo!("Adds the tx hash to l2 buffer; remove tx from the txs storage if it's there.")
+    pub fn clear_l1_buffers(&mut self) {
+        self.txs.clear();
+        self.proposed_txs.clear();
     }
 }
 
@@ -169,8 +333,17 @@ impl ProviderState {
         }
     }
 
-    fn _transition_to_pending(self) -> L1ProviderResult<Self> {
-        todo!()
+    // Pending to Pending is allowed, for blocks committed without the node proposing or
+    // validating them.
+    fn transition_to_pending(self) -> L1ProviderResult<Self> {
+        match self {
+            ProviderState::Pending | ProviderState::Propose | ProviderState::Validate => {
+                Ok(ProviderState::Pending)
+            }
+            ProviderState::Uninitialized => {
+                Err(L1ProviderError::unexpected_transition(self, ProviderState::Pending))
+            }
+        }
     }
 
     pub fn as_str(&self) -> &str {
@@ -189,9 +362,97 @@ impl std::fmt::Display for ProviderState {
     }
 }
 
-#[derive(Clone, Debug, Default, Serialize, Deserialize, Validate, PartialEq)]
-pub struct L1ProviderConfig;
+#[derive(Clone, Debug, Serialize, Deserialize, Validate, PartialEq)]
+pub struct L1ProviderConfig {
+    pub chain_id: ChainId,
+    pub l1_finality: u64,
+    #[validate(range(min = 1))]
+    pub max_blocks_per_poll: u64,
+    #[serde(deserialize_with = "deserialize_seconds_to_duration")]
+    pub polling_interval: Duration,
+    #[serde(deserialize_with = "deserialize_seconds_to_duration")]
+    pub startup_rewind_time: Duration,
+    // File the L1 handler transactions included on L2 and not yet consumed on L1 are persisted to
+    // after each block, and restored from on startup; persistence is disabled if unset.
+    // Transactions included before the range rescraped on startup are dropped.
+    pub l2_included_txs_path: Option<PathBuf>,
+}
 
-pub fn create_l1_provider(_config: L1ProviderConfig) -> L1Provider {
-    L1Provider { state: ProviderState::Propose, ..Default::default() }
+impl Default for L1ProviderConfig {
+    fn default() -> Self {
+        Self {
+            chain_id: ChainId::Other("0x0".to_string()),
+            l1_finality: 10,
+            max_blocks_per_poll: 1000,
+            polling_interval: Duration::from_secs(5),
+            startup_rewind_time: Duration::from_secs(60 * 60),
+            l2_included_txs_path: None,
+        }
+    }
+}
+
+impl SerializeConfig for L1ProviderConfig {
+    fn dump(&self) -> BTreeMap<ParamPath, SerializedParam> {
+        let mut dump = BTreeMap::from_iter([
+            ser_param(
+                "chain_id",
+                &self.chain_id,
+                "The chain ID of the Starknet chain, used to compute L1 handler transaction \
+                 hashes.",
+                ParamPrivacyInput::Public,
+            ),
+            ser_param(
+                "l1_finality",
+                &self.l1_finality,
+                "Number of L1 blocks back from the tip that are considered final.",
+                ParamPrivacyInput::Public,
+            ),
+            ser_param(
+                "max_blocks_per_poll",
+                &self.max_blocks_per_poll,
+                "Maximal number of L1 blocks scraped in a single poll; scraping a longer range, \
+                 e.g., after a long downtime, is spread over several polls.",
+                ParamPrivacyInput::Public,
+            ),
+            ser_param(
+                "polling_interval",
+                &self.polling_interval.as_secs(),
+                "Interval (seconds) between polls of L1 for new messaging events.",
+                ParamPrivacyInput::Public,
+            ),
+            ser_param(
+                "startup_rewind_time",
+                &self.startup_rewind_time.as_secs(),
+                "Time (seconds) back from the latest L1 block to start scraping from on startup \
+                 and after reorgs.",
+                ParamPrivacyInput::Public,
+            ),
+        ]);
+        dump.extend(ser_optional_param(
+            &self.l2_included_txs_path,
+            PathBuf::from("./l1_provider_l2_included_txs.json"),
+            "l2_included_txs_path",
+            "File the L1 handler transactions included on L2 and not yet consumed on L1 are \
+             persisted to, and restored from on startup, so that rescraping L1 does not offer \
+             them for inclusion again.",
+            ParamPrivacyInput::Public,
+        ));
+        dump
+    }
+}
+
+/// Creates a provider that starts scraping `l1_messages_source` once started. Without a source,
+/// the provider waits in `Pending` right away, and never has L1 handler transactions to offer.
+pub fn create_l1_provider(
+    config: L1ProviderConfig,
+    l1_messages_source: Option<Arc<dyn L1MessagesSource>>,
+) -> L1Provider {
+    match l1_messages_source {
+        Some(l1_messages_source) => L1Provider {
+            config,
+            l1_messages_source: Some(l1_messages_source),
+            ..Default::default()
+        },
+        None => L1Provider { config, state: ProviderState::Pending, ..Default::default() },
+    }
 }
@@ -0,0 +1,34 @@
+use std::io;
+use std::path::Path;
+use std::time::SystemTime;
+
+use serde::{Deserialize, Serialize};
+use starknet_api::transaction::TransactionHash;
+use starknet_sequencer_infra::file_utils::{read_json_file, write_json_file_atomically};
+
+/// The L1 handler transactions included on L2 and not yet consumed on L1. They are kept across
+/// restarts, since rescraping L1 on startup would otherwise offer them for inclusion again.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct L2IncludedTxs {
+    pub txs: Vec<L2IncludedTx>,
+}
+
+/// An L1 handler transaction included on L2, along with the time it was included at.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct L2IncludedTx {
+    pub tx_hash: TransactionHash,
+    pub included_at: SystemTime,
+}
+
+impl L2IncludedTxs {
+    /// Reads the transactions from the given file; returns `None` if the file does not exist.
+    pub fn load(path: &Path) -> io::Result<Option<Self>> {
+        read_json_file(path)
+    }
+
+    /// Writes the transactions to the given file, such that a crash mid-write does not corrupt
+    /// it.
+    pub fn store(&self, path: &Path) -> io::Result<()> {
+        write_json_file_atomically(path, self)
+    }
+}
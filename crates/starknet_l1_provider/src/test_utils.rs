@@ -1,4 +1,6 @@
-use indexmap::{IndexMap, IndexSet};
+use std::time::SystemTime;
+
+use indexmap::IndexMap;
 use starknet_api::executable_transaction::L1HandlerTransaction;
 use starknet_api::transaction::TransactionHash;
 
@@ -20,6 +22,7 @@ impl From<L1ProviderContent> for L1Provider {
                 .map(|tm_content| tm_content.complete_to_tx_manager())
                 .unwrap_or_default(),
             state: content.state.unwrap_or_default(),
+            ..Default::default()
         }
     }
 }
@@ -71,7 +74,7 @@ impl L1ProviderContentBuilder {
 #[derive(Debug, Default)]
 struct TransactionManagerContent {
     txs: Option<IndexMap<TransactionHash, L1HandlerTransaction>>,
-    on_l2_awaiting_l1_consumption: Option<IndexSet<TransactionHash>>,
+    on_l2_awaiting_l1_consumption: Option<IndexMap<TransactionHash, SystemTime>>,
 }
 
 impl TransactionManagerContent {
@@ -87,7 +90,7 @@ impl TransactionManagerContent {
 #[derive(Debug, Default)]
 struct TransactionManagerContentBuilder {
     txs: Option<IndexMap<TransactionHash, L1HandlerTransaction>>,
-    on_l2_awaiting_l1_consumption: Option<IndexSet<TransactionHash>>,
+    on_l2_awaiting_l1_consumption: Option<IndexMap<TransactionHash, SystemTime>>,
 }
 
 impl TransactionManagerContentBuilder {
@@ -100,7 +103,9 @@ impl TransactionManagerContentBuilder {
         mut self,
         tx_hashes: impl IntoIterator<Item = TransactionHash>,
     ) -> Self {
-        self.on_l2_awaiting_l1_consumption = Some(tx_hashes.into_iter().collect());
+        let included_at = SystemTime::now();
+        self.on_l2_awaiting_l1_consumption =
+            Some(tx_hashes.into_iter().map(|tx_hash| (tx_hash, included_at)).collect());
         self
     }
 
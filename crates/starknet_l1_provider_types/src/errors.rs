@@ -6,12 +6,16 @@ use thiserror::Error;
 
 #[derive(Clone, Debug, Error, PartialEq, Eq, Serialize, Deserialize)]
 pub enum L1ProviderError {
+    #[error("Base layer error: {0}")]
+    BaseLayer(String),
     #[error(
         "`get_txs` called while in `Pending` state, likely due to a crash; restart block proposal"
     )]
     GetTransactionsInPendingState,
     #[error("`get_txs` while in validate state")]
     GetTransactionConsensusBug,
+    #[error("Storage error: {0}")]
+    Storage(String),
     #[error("Cannot transition from {from} to {to}")]
     UnexpectedProviderStateTransition { from: String, to: String },
     #[error(
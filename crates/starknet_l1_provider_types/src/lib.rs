@@ -19,7 +19,7 @@ pub type L1ProviderResult<T> = Result<T, L1ProviderError>;
 pub type L1ProviderClientResult<T> = Result<T, L1ProviderClientError>;
 pub type SharedL1ProviderClient = Arc<dyn L1ProviderClient>;
 
-#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub enum ValidationStatus {
     Validated,
     AlreadyIncludedOnL2,
@@ -28,12 +28,20 @@ pub enum ValidationStatus {
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub enum L1ProviderRequest {
+    CommitBlock(Vec<TransactionHash>),
     GetTransactions(usize),
+    ProposalStart,
+    Validate(TransactionHash),
+    ValidationStart,
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub enum L1ProviderResponse {
+    CommitBlock(L1ProviderResult<()>),
     GetTransactions(L1ProviderResult<Vec<L1HandlerTransaction>>),
+    ProposalStart(L1ProviderResult<()>),
+    Validate(L1ProviderResult<ValidationStatus>),
+    ValidationStart(L1ProviderResult<()>),
 }
 
 /// Serves as the provider's shared interface. Requires `Send + Sync` to allow transferring and
@@ -42,8 +50,14 @@ pub enum L1ProviderResponse {
 #[async_trait]
 pub trait L1ProviderClient: Send + Sync {
     async fn get_txs(&self, n_txs: usize) -> L1ProviderClientResult<Vec<L1HandlerTransaction>>;
-    async fn validate(&self, _tx_hash: TransactionHash)
-    -> L1ProviderClientResult<ValidationStatus>;
+    async fn validate(&self, tx_hash: TransactionHash) -> L1ProviderClientResult<ValidationStatus>;
+    /// Notifies the provider that the node is about to propose a block.
+    async fn proposal_start(&self) -> L1ProviderClientResult<()>;
+    /// Notifies the provider that the node is about to validate a block.
+    async fn validation_start(&self) -> L1ProviderClientResult<()>;
+    /// Notifies the provider of the L1 handler transactions included in a decided block.
+    async fn commit_block(&self, committed_txs: Vec<TransactionHash>)
+    -> L1ProviderClientResult<()>;
 }
 
 #[async_trait]
@@ -62,10 +76,54 @@ where
             L1ProviderError
         )
     }
-    async fn validate(
+
+    #[instrument(skip(self))]
+    async fn validate(&self, tx_hash: TransactionHash) -> L1ProviderClientResult<ValidationStatus> {
+        let request = L1ProviderRequest::Validate(tx_hash);
+        let response = self.send(request).await;
+        handle_response_variants!(
+            L1ProviderResponse,
+            Validate,
+            L1ProviderClientError,
+            L1ProviderError
+        )
+    }
+    #[instrument(skip(self))]
+    async fn proposal_start(&self) -> L1ProviderClientResult<()> {
+        let request = L1ProviderRequest::ProposalStart;
+        let response = self.send(request).await;
+        handle_response_variants!(
+            L1ProviderResponse,
+            ProposalStart,
+            L1ProviderClientError,
+            L1ProviderError
+        )
+    }
+
+    #[instrument(skip(self))]
+    async fn validation_start(&self) -> L1ProviderClientResult<()> {
+        let request = L1ProviderRequest::ValidationStart;
+        let response = self.send(request).await;
+        handle_response_variants!(
+            L1ProviderResponse,
+            ValidationStart,
+            L1ProviderClientError,
+            L1ProviderError
+        )
+    }
+
+    #[instrument(skip(self))]
+    async fn commit_block(
         &self,
-        _tx_hash: TransactionHash,
-    ) -> L1ProviderClientResult<ValidationStatus> {
-        todo!();
+        committed_txs: Vec<TransactionHash>,
+    ) -> L1ProviderClientResult<()> {
+        let request = L1ProviderRequest::CommitBlock(committed_txs);
+        let response = self.send(request).await;
+        handle_response_variants!(
+            L1ProviderResponse,
+            CommitBlock,
+            L1ProviderClientError,
+            L1ProviderError
+        )
     }
 }
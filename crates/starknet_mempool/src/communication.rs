@@ -1,11 +1,10 @@
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
 use async_trait::async_trait;
-use papyrus_network_types::network_types::BroadcastedMessageMetadata;
 use starknet_api::executable_transaction::AccountTransaction;
-use starknet_api::rpc_transaction::{
-    RpcDeployAccountTransaction,
-    RpcInvokeTransaction,
-    RpcTransaction,
-};
+use starknet_gateway_types::communication::SharedGatewayClient;
+use starknet_gateway_types::gateway_types::GatewayInput;
 use starknet_mempool_p2p_types::communication::SharedMempoolP2pPropagatorClient;
 use starknet_mempool_types::communication::{
     AddTransactionArgsWrapper,
@@ -13,90 +12,160 @@ use starknet_mempool_types::communication::{
     MempoolResponse,
 };
 use starknet_mempool_types::errors::MempoolError;
-use starknet_mempool_types::mempool_types::{CommitBlockArgs, MempoolResult};
+use starknet_mempool_types::mempool_types::{CommitBlockArgs, MempoolResult, RewindBlockArgs};
 use starknet_sequencer_infra::component_definitions::{ComponentRequestHandler, ComponentStarter};
-use starknet_sequencer_infra::component_server::{LocalComponentServer, RemoteComponentServer};
+use starknet_sequencer_infra::component_server::{
+    LocalActiveComponentServer,
+    RemoteComponentServer,
+};
+use starknet_sequencer_infra::errors::ComponentError;
+use starknet_sequencer_infra::shutdown::ShutdownSubscriber;
+use tokio::sync::Mutex;
 
+use crate::config::MempoolConfig;
 use crate::mempool::Mempool;
+use crate::persistence::MempoolSnapshot;
 
 pub type LocalMempoolServer =
-    LocalComponentServer<MempoolCommunicationWrapper, MempoolRequest, MempoolResponse>;
+    LocalActiveComponentServer<MempoolCommunicationWrapper, MempoolRequest, MempoolResponse>;
 pub type RemoteMempoolServer = RemoteComponentServer<MempoolRequest, MempoolResponse>;
 
 pub fn create_mempool(
+    config: MempoolConfig,
     mempool_p2p_propagator_client: SharedMempoolP2pPropagatorClient,
+    gateway_client: SharedGatewayClient,
+    shutdown: ShutdownSubscriber,
 ) -> MempoolCommunicationWrapper {
-    MempoolCommunicationWrapper::new(Mempool::default(), mempool_p2p_propagator_client)
+    MempoolCommunicationWrapper::new(
+        Mempool::new(config),
+        mempool_p2p_propagator_client,
+        gateway_client,
+        shutdown,
+    )
 }
 
 /// Wraps the mempool to enable inbound async communication from other components.
+#[derive(Clone)]
 pub struct MempoolCommunicationWrapper {
-    mempool: Mempool,
+    // Shared with the task that periodically maintains the mempool, see `start`.
+    mempool: Arc<Mutex<Mempool>>,
     mempool_p2p_propagator_client: SharedMempoolP2pPropagatorClient,
+    // Used to resubmit the transactions of a snapshot on startup.
+    gateway_client: SharedGatewayClient,
+    // The mempool is persisted once the node shuts down.
+    shutdown: ShutdownSubscriber,
 }
 
 impl MempoolCommunicationWrapper {
     pub fn new(
         mempool: Mempool,
         mempool_p2p_propagator_client: SharedMempoolP2pPropagatorClient,
+        gateway_client: SharedGatewayClient,
+        shutdown: ShutdownSubscriber,
     ) -> Self {
-        MempoolCommunicationWrapper { mempool, mempool_p2p_propagator_client }
+        MempoolCommunicationWrapper {
+            mempool: Arc::new(Mutex::new(mempool)),
+            mempool_p2p_propagator_client,
+            gateway_client,
+            shutdown,
+        }
     }
 
-    async fn send_tx_to_p2p(
-        &self,
-        message_metadata: Option<BroadcastedMessageMetadata>,
-        tx: AccountTransaction,
-    ) -> MempoolResult<()> {
-        match message_metadata {
-            Some(message_metadata) => self
-                .mempool_p2p_propagator_client
-                .continue_propagation(message_metadata)
-                .await
-                .map_err(|_| MempoolError::P2pPropagatorClientError { tx_hash: tx.tx_hash() }),
-            None => {
-                let tx_hash = tx.tx_hash();
-                match tx {
-                    AccountTransaction::Invoke(invoke_tx) => self
-                        .mempool_p2p_propagator_client
-                        .add_transaction(RpcTransaction::Invoke(RpcInvokeTransaction::V3(
-                            invoke_tx.into(),
-                        )))
-                        .await
-                        .map_err(|_| MempoolError::P2pPropagatorClientError { tx_hash })?,
-                    AccountTransaction::DeployAccount(deploy_account_tx) => self
-                        .mempool_p2p_propagator_client
-                        .add_transaction(RpcTransaction::DeployAccount(
-                            RpcDeployAccountTransaction::V3(deploy_account_tx.into()),
-                        ))
-                        .await
-                        .map_err(|_| MempoolError::P2pPropagatorClientError { tx_hash })?,
-                    AccountTransaction::Declare(_) => {}
-                }
-                Ok(())
+    async fn send_tx_to_p2p(&self, args_wrapper: AddTransactionArgsWrapper) -> MempoolResult<()> {
+        let tx_hash = args_wrapper.args.tx.tx_hash();
+        let result = match (args_wrapper.p2p_message_metadata, args_wrapper.rpc_declare_tx) {
+            (Some(message_metadata), _) => {
+                self.mempool_p2p_propagator_client.continue_propagation(message_metadata).await
             }
-        }
+            // The mempool doesn't hold the Sierra class of a declare transaction, so the propagator
+            // keeps the transaction in order to serve it to peers.
+            (None, Some(rpc_declare_tx)) => {
+                self.mempool_p2p_propagator_client
+                    .add_declare_transaction(tx_hash, rpc_declare_tx)
+                    .await
+            }
+            // Only the hash is announced. Peers request the transaction itself from the mempool.
+            (None, None) => self.mempool_p2p_propagator_client.add_transaction(tx_hash).await,
+        };
+        result.map_err(|_| MempoolError::P2pPropagatorClientError { tx_hash })
     }
 
     pub(crate) async fn add_tx(
         &mut self,
         args_wrapper: AddTransactionArgsWrapper,
     ) -> MempoolResult<()> {
-        self.mempool.add_tx(args_wrapper.args.clone())?;
+        self.mempool.lock().await.add_tx(args_wrapper.args.clone())?;
         // TODO: Verify that only transactions that were added to the mempool are sent.
-        // TODO: handle declare correctly and remove this match.
-        match args_wrapper.args.tx {
-            AccountTransaction::Declare(_) => Ok(()),
-            _ => self.send_tx_to_p2p(args_wrapper.p2p_message_metadata, args_wrapper.args.tx).await,
-        }
+        self.send_tx_to_p2p(args_wrapper).await
     }
 
-    fn commit_block(&mut self, args: CommitBlockArgs) -> MempoolResult<()> {
-        self.mempool.commit_block(args)
+    async fn commit_block(&mut self, args: CommitBlockArgs) -> MempoolResult<()> {
+        self.mempool.lock().await.commit_block(args)
     }
 
-    fn get_txs(&mut self, n_txs: usize) -> MempoolResult<Vec<AccountTransaction>> {
-        self.mempool.get_txs(n_txs)
+    async fn get_txs(&mut self, n_txs: usize) -> MempoolResult<Vec<AccountTransaction>> {
+        self.mempool.lock().await.get_txs(n_txs)
+    }
+
+    async fn get_oldest_txs(&mut self, n_txs: usize) -> MempoolResult<Vec<AccountTransaction>> {
+        self.mempool.lock().await.get_oldest_txs(n_txs)
+    }
+
+    async fn rewind_block(&mut self, args: RewindBlockArgs) -> MempoolResult<()> {
+        self.mempool.lock().await.rewind_block(args)
+    }
+
+    // The content is captured under the lock, but written to disk without holding it.
+    async fn persist_snapshot(&self, snapshot_path: PathBuf) {
+        let snapshot = self.mempool.lock().await.snapshot();
+        let path = snapshot_path.clone();
+        let result = tokio::task::spawn_blocking(move || snapshot.store(&path))
+            .await
+            .unwrap_or_else(|err| Err(std::io::Error::other(err)));
+        if let Err(err) = result {
+            tracing::warn!(
+                "Failed to persist mempool snapshot to {}: {err}.",
+                snapshot_path.display()
+            );
+        }
+    }
+
+    // Resubmits the transactions of the snapshot through the gateway, so that only those still
+    // valid against the current state are restored. Declare transactions are dropped, since the
+    // mempool doesn't hold the Sierra class required to resubmit them.
+    pub(crate) async fn restore_snapshot(&self, snapshot_path: &Path) {
+        let snapshot = match MempoolSnapshot::load(snapshot_path) {
+            Ok(Some(snapshot)) => snapshot,
+            Ok(None) => return,
+            Err(err) => {
+                tracing::warn!(
+                    "Failed to load mempool snapshot from {}: {err}.",
+                    snapshot_path.display()
+                );
+                return;
+            }
+        };
+
+        let n_txs = snapshot.txs.len();
+        let txs = self.mempool.lock().await.prepare_restore(snapshot);
+        let mut n_restored_txs = 0;
+        // The mempool isn't locked while resubmitting, since the gateway adds the transactions to
+        // it.
+        for tx in txs {
+            let tx_hash = tx.tx_hash();
+            let Some(rpc_tx) = tx.into_rpc_transaction() else {
+                tracing::debug!("Dropped declare transaction {tx_hash} from snapshot.");
+                continue;
+            };
+            match self.gateway_client.add_tx(GatewayInput { rpc_tx, message_metadata: None }).await
+            {
+                Ok(_) => n_restored_txs += 1,
+                Err(err) => tracing::debug!("Dropped transaction {tx_hash} from snapshot: {err}."),
+            }
+        }
+        self.mempool.lock().await.finish_restore();
+
+        tracing::info!("Restored {n_restored_txs} out of {n_txs} transactions from snapshot.");
     }
 }
 
@@ -108,13 +177,73 @@ impl ComponentRequestHandler<MempoolRequest, MempoolResponse> for MempoolCommuni
                 MempoolResponse::AddTransaction(self.add_tx(args).await)
             }
             MempoolRequest::CommitBlock(args) => {
-                MempoolResponse::CommitBlock(self.commit_block(args))
+                MempoolResponse::CommitBlock(self.commit_block(args).await)
             }
             MempoolRequest::GetTransactions(n_txs) => {
-                MempoolResponse::GetTransactions(self.get_txs(n_txs))
+                MempoolResponse::GetTransactions(self.get_txs(n_txs).await)
+            }
+            MempoolRequest::GetOldestTransactions(n_txs) => {
+                MempoolResponse::GetOldestTransactions(self.get_oldest_txs(n_txs).await)
+            }
+            MempoolRequest::RewindBlock(args) => {
+                MempoolResponse::RewindBlock(self.rewind_block(args).await)
+            }
+            MempoolRequest::GetTransactionStatus(tx_hash) => MempoolResponse::GetTransactionStatus(
+                self.mempool.lock().await.get_transaction_status(tx_hash),
+            ),
+            MempoolRequest::GetAccountTransactions(address) => {
+                MempoolResponse::GetAccountTransactions(
+                    self.mempool.lock().await.get_account_transactions(address),
+                )
+            }
+            MempoolRequest::GetPoolStats => {
+                MempoolResponse::GetPoolStats(self.mempool.lock().await.get_pool_stats())
+            }
+            MempoolRequest::GetTransactionsByHash(tx_hashes) => {
+                MempoolResponse::GetTransactionsByHash(
+                    self.mempool.lock().await.get_txs_by_hash(&tx_hashes),
+                )
             }
         }
     }
 }
 
-impl ComponentStarter for MempoolCommunicationWrapper {}
+#[async_trait]
+impl ComponentStarter for MempoolCommunicationWrapper {
+    // Runs alongside the request handling for as long as the node is up.
+    async fn start(&mut self) -> Result<(), ComponentError> {
+        let config = self.mempool.lock().await.config().clone();
+        if let Some(snapshot_path) = &config.snapshot_path {
+            self.restore_snapshot(snapshot_path).await;
+        }
+
+        let mut expiry_sweep = tokio::time::interval(config.expiry_sweep_interval);
+        let mut snapshot = tokio::time::interval(config.snapshot_interval);
+        let mut shutdown = self.shutdown.clone();
+        // The snapshot taken on shutdown is the last one, while requests are still served until
+        // the node exits.
+        let mut is_shutting_down = false;
+        loop {
+            tokio::select! {
+                _ = expiry_sweep.tick() => {
+                    // Transactions also expire lazily when the mempool is accessed; the sweep
+                    // keeps an idle mempool bounded as well.
+                    self.mempool.lock().await.remove_expired_txs();
+                }
+                _ = snapshot.tick(), if config.snapshot_path.is_some() && !is_shutting_down => {
+                    let snapshot_path =
+                        config.snapshot_path.clone().expect("Snapshot path should be set.");
+                    self.persist_snapshot(snapshot_path).await;
+                }
+                _ = shutdown.requested(), if !is_shutting_down => {
+                    is_shutting_down = true;
+                    if let Some(snapshot_path) = config.snapshot_path.clone() {
+                        self.persist_snapshot(snapshot_path).await;
+                        tracing::info!("Persisted mempool snapshot on shutdown.");
+                    }
+                    shutdown.done();
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,148 @@
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use papyrus_config::converters::deserialize_seconds_to_duration;
+use papyrus_config::dumping::{ser_optional_param, ser_param, SerializeConfig};
+use papyrus_config::{ParamPath, ParamPrivacyInput, SerializedParam};
+use serde::{Deserialize, Serialize};
+use validator::{Validate, ValidationError};
+
+#[derive(Clone, Debug, Serialize, Deserialize, Validate, PartialEq)]
+pub struct MempoolConfig {
+    pub enable_fee_escalation: bool,
+    // TODO: consider adding validations; should be bounded?
+    // Percentage increase for tip and max gas price to enable transaction replacement.
+    pub fee_escalation_percentage: u8, // E.g., 10 for a 10% increase.
+    // Maximal number of transactions held in the mempool; once reached, an incoming transaction
+    // is accepted only by evicting a transaction of lower priority.
+    pub max_n_txs_in_pool: usize,
+    // Maximal number of transactions held in the mempool per account.
+    pub max_n_txs_per_account: usize,
+    // Time a transaction may be held in the mempool before it is dropped.
+    #[serde(deserialize_with = "deserialize_seconds_to_duration")]
+    pub transaction_ttl: Duration,
+    // Interval between sweeps that drop the expired transactions.
+    #[validate(custom = "validate_positive_duration")]
+    #[serde(deserialize_with = "deserialize_seconds_to_duration")]
+    pub expiry_sweep_interval: Duration,
+    // Maximal number of suspended transactions, i.e., transactions held back due to a nonce gap.
+    pub max_n_suspended_txs: usize,
+    // Maximal number of suspended transactions per account.
+    pub max_n_suspended_txs_per_account: usize,
+    // Number of evicted transactions remembered for status queries.
+    pub max_n_evicted_tx_records: usize,
+    // File the mempool content is persisted to periodically and on shutdown, and restored from on
+    // startup; persistence is disabled if unset.
+    pub snapshot_path: Option<PathBuf>,
+    // Interval between snapshots of the mempool content.
+    #[validate(custom = "validate_positive_duration")]
+    #[serde(deserialize_with = "deserialize_seconds_to_duration")]
+    pub snapshot_interval: Duration,
+}
+
+fn validate_positive_duration(duration: &Duration) -> Result<(), ValidationError> {
+    if duration.is_zero() {
+        return Err(ValidationError::new("The duration must be positive"));
+    }
+    Ok(())
+}
+
+impl Default for MempoolConfig {
+    fn default() -> Self {
+        MempoolConfig {
+            enable_fee_escalation: true,
+            fee_escalation_percentage: 10,
+            max_n_txs_in_pool: 100_000,
+            max_n_txs_per_account: 64,
+            transaction_ttl: Duration::from_secs(60 * 60),
+            expiry_sweep_interval: Duration::from_secs(60),
+            max_n_suspended_txs: 10_000,
+            max_n_suspended_txs_per_account: 16,
+            max_n_evicted_tx_records: 10_000,
+            snapshot_path: None,
+            snapshot_interval: Duration::from_secs(60),
+        }
+    }
+}
+
+impl SerializeConfig for MempoolConfig {
+    fn dump(&self) -> BTreeMap<ParamPath, SerializedParam> {
+        let mut dump = BTreeMap::from_iter([
+            ser_param(
+                "enable_fee_escalation",
+                &self.enable_fee_escalation,
+                "If true, transactions can be replaced by transactions of the same account and \
+                 nonce with higher fees.",
+                ParamPrivacyInput::Public,
+            ),
+            ser_param(
+                "fee_escalation_percentage",
+                &self.fee_escalation_percentage,
+                "Percentage increase of both the tip and the max L2 gas price required to replace \
+                 a transaction.",
+                ParamPrivacyInput::Public,
+            ),
+            ser_param(
+                "max_n_txs_in_pool",
+                &self.max_n_txs_in_pool,
+                "Maximal number of transactions held in the mempool. Once reached, transactions \
+                 of lower priority are evicted to make room for incoming ones.",
+                ParamPrivacyInput::Public,
+            ),
+            ser_param(
+                "max_n_txs_per_account",
+                &self.max_n_txs_per_account,
+                "Maximal number of transactions held in the mempool per account.",
+                ParamPrivacyInput::Public,
+            ),
+            ser_param(
+                "transaction_ttl",
+                &self.transaction_ttl.as_secs(),
+                "Time (seconds) a transaction may be held in the mempool before it is dropped.",
+                ParamPrivacyInput::Public,
+            ),
+            ser_param(
+                "expiry_sweep_interval",
+                &self.expiry_sweep_interval.as_secs(),
+                "Interval (seconds) between sweeps that drop the transactions held in the mempool \
+                 for longer than the transaction TTL. Must be positive.",
+                ParamPrivacyInput::Public,
+            ),
+            ser_param(
+                "max_n_suspended_txs",
+                &self.max_n_suspended_txs,
+                "Maximal number of transactions held back due to a nonce gap.",
+                ParamPrivacyInput::Public,
+            ),
+            ser_param(
+                "max_n_suspended_txs_per_account",
+                &self.max_n_suspended_txs_per_account,
+                "Maximal number of transactions held back due to a nonce gap per account.",
+                ParamPrivacyInput::Public,
+            ),
+            ser_param(
+                "max_n_evicted_tx_records",
+                &self.max_n_evicted_tx_records,
+                "Number of evicted transactions remembered for status queries.",
+                ParamPrivacyInput::Public,
+            ),
+            ser_param(
+                "snapshot_interval",
+                &self.snapshot_interval.as_secs(),
+                "Interval (seconds) between snapshots of the mempool content, if a snapshot path \
+                 is set. Must be positive.",
+                ParamPrivacyInput::Public,
+            ),
+        ]);
+        dump.extend(ser_optional_param(
+            &self.snapshot_path,
+            PathBuf::from("./mempool_snapshot.json"),
+            "snapshot_path",
+            "File the mempool content is persisted to periodically and on shutdown, and restored \
+             from on startup.",
+            ParamPrivacyInput::Public,
+        ));
+        dump
+    }
+}
@@ -0,0 +1,42 @@
+use std::collections::{HashMap, VecDeque};
+
+use starknet_api::transaction::TransactionHash;
+use starknet_mempool_types::mempool_types::EvictionReason;
+
+/// Remembers the most recently evicted transactions, so that their status can be queried after
+/// they left the mempool. Once the capacity is reached, the oldest records are dropped.
+#[derive(Debug, Default)]
+pub struct EvictedTransactions {
+    capacity: usize,
+    // Transaction hashes in the order they were evicted, oldest first.
+    eviction_order: VecDeque<TransactionHash>,
+    hash_to_reason: HashMap<TransactionHash, EvictionReason>,
+}
+
+impl EvictedTransactions {
+    pub fn new(capacity: usize) -> Self {
+        Self { capacity, ..Default::default() }
+    }
+
+    pub fn insert(&mut self, tx_hash: TransactionHash, reason: EvictionReason) {
+        if self.capacity == 0 {
+            return;
+        }
+
+        if self.hash_to_reason.insert(tx_hash, reason).is_some() {
+            // Already recorded; keep its original position.
+            return;
+        }
+        self.eviction_order.push_back(tx_hash);
+
+        if self.eviction_order.len() > self.capacity {
+            let oldest_tx_hash =
+                self.eviction_order.pop_front().expect("Capacity is positive, queue is non-empty.");
+            self.hash_to_reason.remove(&oldest_tx_hash);
+        }
+    }
+
+    pub fn get(&self, tx_hash: TransactionHash) -> Option<EvictionReason> {
+        self.hash_to_reason.get(&tx_hash).cloned()
+    }
+}
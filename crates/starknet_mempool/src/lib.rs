@@ -1,5 +1,9 @@
 pub mod communication;
+pub mod config;
+pub(crate) mod evicted_transactions;
 pub mod mempool;
+pub mod metrics;
+pub mod persistence;
 pub(crate) mod suspended_transaction_pool;
 pub(crate) mod transaction_pool;
 pub(crate) mod transaction_queue;
@@ -1,4 +1,5 @@
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
+use std::time::Instant;
 
 use starknet_api::block::GasPrice;
 use starknet_api::core::{ContractAddress, Nonce};
@@ -8,33 +9,30 @@ use starknet_api::transaction::TransactionHash;
 use starknet_mempool_types::errors::MempoolError;
 use starknet_mempool_types::mempool_types::{
     AccountState,
+    AccountTransactions,
     AddTransactionArgs,
     CommitBlockArgs,
+    EvictionReason,
     MempoolResult,
+    MempoolStats,
+    RewindBlockArgs,
+    TransactionInfo,
+    TransactionStatus,
 };
 
+use crate::config::MempoolConfig;
+use crate::evicted_transactions::EvictedTransactions;
+use crate::metrics::MEMPOOL_EXPIRED_TXS;
+use crate::persistence::{MempoolSnapshot, SnapshotTransaction};
+use crate::suspended_transaction_pool::SuspendedTransactionPool;
 use crate::transaction_pool::TransactionPool;
 use crate::transaction_queue::TransactionQueue;
-use crate::utils::try_increment_nonce;
+use crate::utils::{get_instant_now, try_increment_nonce};
 
 #[cfg(test)]
 #[path = "mempool_test.rs"]
 pub mod mempool_test;
 
-#[derive(Debug)]
-pub struct MempoolConfig {
-    enable_fee_escalation: bool,
-    // TODO: consider adding validations; should be bounded?
-    // Percentage increase for tip and max gas price to enable transaction replacement.
-    fee_escalation_percentage: u8, // E.g., 10 for a 10% increase.
-}
-
-impl Default for MempoolConfig {
-    fn default() -> Self {
-        MempoolConfig { enable_fee_escalation: true, fee_escalation_percentage: 10 }
-    }
-}
-
 type AddressToNonce = HashMap<ContractAddress, Nonce>;
 
 /// Represents the state tracked by the mempool.
@@ -107,6 +105,30 @@ impl MempoolState {
         addresses_to_rewind
     }
 
+    /// Rolls back the committed nonces of the given accounts to their values prior to a reverted
+    /// block. Staged nonces are discarded, since the block in progress was built on top of the
+    /// reverted one. Returns all affected addresses.
+    fn rewind(&mut self, address_to_nonce: AddressToNonce) -> Vec<ContractAddress> {
+        let mut affected_addresses: Vec<_> =
+            self.staged.drain().map(|(address, _)| address).collect();
+
+        for (address, nonce) in address_to_nonce {
+            self.tentative.remove(&address);
+            self.committed.insert(address, nonce);
+            affected_addresses.push(address);
+        }
+
+        affected_addresses
+    }
+
+    /// Returns whether the given transaction was already handed over for sequencing in the block
+    /// currently being created.
+    fn is_staged(&self, tx_reference: &TransactionReference) -> bool {
+        self.staged
+            .get(&tx_reference.address)
+            .is_some_and(|&next_nonce| tx_reference.nonce < next_nonce)
+    }
+
     fn validate_incoming_tx(&self, tx_reference: TransactionReference) -> MempoolResult<()> {
         let TransactionReference { address, nonce: tx_nonce, .. } = tx_reference;
         if self.get(address).is_some_and(|existing_nonce| tx_nonce < existing_nonce) {
@@ -120,8 +142,7 @@ impl MempoolState {
         // FIXME: Remove after first POC.
         // If commit_block wants to decrease the stored account nonce this can mean one of two
         // things:
-        // 1. this is a reorg, which should be handled by a dedicated TBD mechanism and not inside
-        //    commit_block
+        // 1. this is a reorg, which should be handled by `rewind_block` and not inside commit_block
         // 2. the stored nonce originated from add_tx, so should be treated as tentative due to
         //    possible races with the gateway; these types of nonces should be tagged somehow so
         //    that commit_block can override them. Regardless, in the first POC this cannot happen
@@ -132,7 +153,7 @@ impl MempoolState {
     }
 }
 
-#[derive(Debug, Default)]
+#[derive(Debug)]
 pub struct Mempool {
     config: MempoolConfig,
     // TODO: add docstring explaining visibility and coupling of the fields.
@@ -140,10 +161,39 @@ pub struct Mempool {
     tx_pool: TransactionPool,
     // Transactions eligible for sequencing.
     tx_queue: TransactionQueue,
+    // Transactions held back due to a nonce gap; a subset of the transaction pool.
+    suspended_tx_pool: SuspendedTransactionPool,
+    // Transactions recently removed from the mempool without being committed.
+    evicted_txs: EvictedTransactions,
     state: MempoolState,
+    // The submission times of the snapshot transactions being restored, by which they keep their
+    // age once resubmitted.
+    restored_tx_submission_times: HashMap<TransactionHash, Instant>,
+}
+
+impl Default for Mempool {
+    fn default() -> Self {
+        Self::new(MempoolConfig::default())
+    }
 }
 
 impl Mempool {
+    pub fn new(config: MempoolConfig) -> Self {
+        Mempool {
+            evicted_txs: EvictedTransactions::new(config.max_n_evicted_tx_records),
+            config,
+            tx_pool: TransactionPool::default(),
+            tx_queue: TransactionQueue::default(),
+            suspended_tx_pool: SuspendedTransactionPool::default(),
+            state: MempoolState::default(),
+            restored_tx_submission_times: HashMap::new(),
+        }
+    }
+
+    pub fn config(&self) -> &MempoolConfig {
+        &self.config
+    }
+
     /// Returns an iterator of the current eligible transactions for sequencing, ordered by their
     /// priority.
     pub fn iter(&self) -> impl Iterator<Item = &TransactionReference> {
@@ -156,6 +206,8 @@ impl Mempool {
     // TODO: Consider renaming to `pop_txs` to be more consistent with the standard library.
     #[tracing::instrument(skip(self), err)]
     pub fn get_txs(&mut self, n_txs: usize) -> MempoolResult<Vec<AccountTransaction>> {
+        self.remove_expired_txs();
+
         let mut eligible_tx_references: Vec<TransactionReference> = Vec::with_capacity(n_txs);
         let mut n_remaining_txs = n_txs;
 
@@ -166,6 +218,57 @@ impl Mempool {
             eligible_tx_references.extend(chunk);
         }
 
+        self.stage_txs(n_txs, eligible_tx_references)
+    }
+
+    /// Retrieves up to `n_txs` eligible transactions, in the order they were submitted to the
+    /// mempool, regardless of their priority.
+    /// Transactions are guaranteed to be unique across calls until the block in-progress is
+    /// created.
+    #[tracing::instrument(skip(self), err)]
+    pub fn get_oldest_txs(&mut self, n_txs: usize) -> MempoolResult<Vec<AccountTransaction>> {
+        self.remove_expired_txs();
+
+        // Only the queued transaction of each account is eligible; the account's following
+        // transactions depend on it. The eligible transactions are indexed by submission time once,
+        // and the index is kept up to date as the accounts' following transactions become eligible.
+        let mut ready_txs_by_submission_time: BTreeMap<_, TransactionReference> = self
+            .tx_queue
+            .iter_over_ready_txs()
+            .map(|tx_reference| (self.submission_order_key(tx_reference), *tx_reference))
+            .collect();
+
+        let mut eligible_tx_references: Vec<TransactionReference> = Vec::with_capacity(n_txs);
+        while eligible_tx_references.len() < n_txs {
+            let Some((_, oldest_tx_reference)) = ready_txs_by_submission_time.pop_first() else {
+                break;
+            };
+            self.tx_queue.remove(oldest_tx_reference.address);
+            self.enqueue_next_eligible_txs(&[oldest_tx_reference])?;
+            if let Some(next_tx_reference) = self.tx_queue.get_ready_tx(oldest_tx_reference.address)
+            {
+                ready_txs_by_submission_time
+                    .insert(self.submission_order_key(&next_tx_reference), next_tx_reference);
+            }
+            eligible_tx_references.push(oldest_tx_reference);
+        }
+
+        self.stage_txs(n_txs, eligible_tx_references)
+    }
+
+    fn submission_order_key(
+        &self,
+        tx_reference: &TransactionReference,
+    ) -> (Option<Instant>, TransactionHash) {
+        (self.tx_pool.get_submission_time(tx_reference.tx_hash), tx_reference.tx_hash)
+    }
+
+    // Stages the given transactions for the block in-progress, and returns them.
+    fn stage_txs(
+        &mut self,
+        n_txs: usize,
+        eligible_tx_references: Vec<TransactionReference>,
+    ) -> MempoolResult<Vec<AccountTransaction>> {
         // Update the mempool state with the given transactions' nonces.
         for tx_reference in &eligible_tx_references {
             self.state.stage(tx_reference)?;
@@ -200,29 +303,54 @@ impl Mempool {
         err
     )]
     pub fn add_tx(&mut self, args: AddTransactionArgs) -> MempoolResult<()> {
+        self.remove_expired_txs();
+
         let AddTransactionArgs { tx, account_state } = args;
+        let submission_time =
+            self.restored_tx_submission_times.remove(&tx.tx_hash()).unwrap_or_else(get_instant_now);
         let tx_reference = TransactionReference::new(&tx);
         self.validate_incoming_tx(tx_reference)?;
-
-        self.handle_fee_escalation(&tx)?;
-        self.tx_pool.insert(tx)?;
+        let replaced_tx_reference = self.validate_fee_escalation(&tx_reference)?;
 
         // Align to account nonce, only if it is at least the one stored.
         let AccountState { address, nonce: incoming_account_nonce } = account_state;
         let stored_account_nonce = self.state.get_or_insert(address, incoming_account_nonce);
+        let is_nonce_gapped = self.is_nonce_gapped(&tx_reference, stored_account_nonce);
+
+        // All checks pass before the mempool is modified, so that a rejected transaction leaves
+        // it intact. A replacement takes the place of the replaced transaction, and requires no
+        // additional capacity.
+        match replaced_tx_reference {
+            Some(replaced_tx_reference) => self.replace_tx(replaced_tx_reference),
+            None => {
+                if is_nonce_gapped {
+                    self.validate_suspended_capacity(&tx_reference)?;
+                }
+                self.handle_capacity(&tx_reference)?;
+            }
+        }
+        self.tx_pool.insert_submitted_at(tx, submission_time)?;
+
+        if is_nonce_gapped {
+            self.suspended_tx_pool.insert(tx_reference);
+            return Ok(());
+        }
+
         if tx_reference.nonce == stored_account_nonce {
             self.tx_queue.remove(address);
             self.tx_queue.insert(tx_reference);
         }
 
-        Ok(())
+        // The incoming transaction may close a nonce gap.
+        self.resume_suspended_txs(address, try_increment_nonce(tx_reference.nonce)?)
     }
 
     /// Update the mempool's internal state according to the committed block (resolves nonce gaps,
     /// updates account balances).
     #[tracing::instrument(skip(self, args), err)]
     pub fn commit_block(&mut self, args: CommitBlockArgs) -> MempoolResult<()> {
-        let CommitBlockArgs { address_to_nonce, tx_hashes } = args;
+        let CommitBlockArgs { address_to_nonce, tx_hashes, rejected_tx_hashes, next_l2_gas_price } =
+            args;
         tracing::debug!("Committing block with {} transactions to mempool.", tx_hashes.len());
 
         // Align mempool data to committed nonces.
@@ -240,8 +368,10 @@ impl Mempool {
 
             // Remove from pool.
             self.tx_pool.remove_up_to_nonce(address, next_nonce);
+            self.suspended_tx_pool.remove_up_to_nonce(address, next_nonce);
 
             // Maybe close nonce gap.
+            self.resume_suspended_txs(address, next_nonce)?;
             if self.tx_queue.get_nonce(address).is_none() {
                 if let Some(tx_reference) =
                     self.tx_pool.get_by_address_and_nonce(address, next_nonce)
@@ -268,18 +398,181 @@ impl Mempool {
 
         // Hard-delete: finally, remove committed transactions from the mempool.
         for tx_hash in tx_hashes {
-            let Ok(_tx) = self.tx_pool.remove(tx_hash) else {
+            let Ok(tx) = self.tx_pool.remove(tx_hash) else {
                 continue; // Transaction hash unknown to mempool, from a different node.
             };
+            self.suspended_tx_pool.remove(tx.contract_address(), tx.nonce());
 
             // TODO(clean_accounts): remove address with no transactions left after a block cycle /
             // TTL.
         }
         tracing::debug!("Removed committed transactions known to mempool.");
 
+        // Evict the transactions that failed execution, so they aren't proposed again. Rejected
+        // transactions are no longer staged, as the block was already committed to the state.
+        for (tx_hash, reason) in rejected_tx_hashes {
+            let Ok(tx_reference) =
+                self.tx_pool.get_by_tx_hash(tx_hash).map(TransactionReference::new)
+            else {
+                continue; // Transaction hash unknown to mempool, from a different node.
+            };
+            self.remove_tx(tx_reference);
+            self.evicted_txs.insert(tx_hash, EvictionReason::Rejected { reason });
+        }
+
+        self.update_gas_price_threshold(next_l2_gas_price);
+
+        Ok(())
+    }
+
+    /// Captures the content of the mempool, such that it can be rebuilt using `restore`.
+    pub fn snapshot(&self) -> MempoolSnapshot {
+        let mut tx_references: Vec<_> = self.tx_pool.iter_tx_references().collect();
+        tx_references.sort_by_key(|tx_reference| (tx_reference.address, tx_reference.nonce));
+        let now = get_instant_now();
+        let txs = tx_references
+            .into_iter()
+            .map(|tx_reference| {
+                let tx = self
+                    .tx_pool
+                    .get_by_tx_hash(tx_reference.tx_hash)
+                    .expect("Transaction hash from pool must exist.")
+                    .clone();
+                let submission_time = self
+                    .tx_pool
+                    .get_submission_time(tx_reference.tx_hash)
+                    .expect("Transaction hash from pool must have a submission time.");
+                SnapshotTransaction { tx, age: now.saturating_duration_since(submission_time) }
+            })
+            .collect();
+
+        MempoolSnapshot { txs }
+    }
+
+    /// Prepares the restoration of the given snapshot, whose transactions are resubmitted through
+    /// the gateway so that they're validated against the current state. Returns the transactions
+    /// that haven't expired; once resubmitted, they keep the age they had when the snapshot was
+    /// taken, so they expire as if never restarted.
+    pub fn prepare_restore(&mut self, snapshot: MempoolSnapshot) -> Vec<AccountTransaction> {
+        let now = get_instant_now();
+        let mut txs = Vec::with_capacity(snapshot.txs.len());
+        for SnapshotTransaction { tx, age } in snapshot.txs {
+            if age >= self.config.transaction_ttl {
+                tracing::debug!("Dropped transaction {} from snapshot: expired.", tx.tx_hash());
+                continue;
+            }
+            // The clock may not reach back as far as the age right after boot, in which case the
+            // age is forgotten.
+            let submission_time = now.checked_sub(age).unwrap_or(now);
+            self.restored_tx_submission_times.insert(tx.tx_hash(), submission_time);
+            txs.push(tx);
+        }
+        txs
+    }
+
+    /// Ends the restoration of a snapshot; its transactions that are submitted later on are
+    /// considered new.
+    pub fn finish_restore(&mut self) {
+        self.restored_tx_submission_times.clear();
+    }
+
+    /// Reverts a previously committed block: its transactions are re-inserted into the mempool, and
+    /// the account nonces are rolled back to their values prior to the block.
+    #[tracing::instrument(skip(self, args), err)]
+    pub fn rewind_block(&mut self, args: RewindBlockArgs) -> MempoolResult<()> {
+        let RewindBlockArgs { address_to_nonce, txs } = args;
+        tracing::debug!("Rewinding block with {} transactions in mempool.", txs.len());
+
+        // Reverted transactions were already accepted once, so capacity limits do not apply.
+        let mut affected_addresses = Vec::with_capacity(txs.len());
+        for tx in txs {
+            let tx_reference = TransactionReference::new(&tx);
+            affected_addresses.push(tx_reference.address);
+            if self.tx_pool.get_by_tx_hash(tx_reference.tx_hash).is_ok() {
+                continue;
+            }
+
+            // A transaction with the same nonce may only have been received after the reverted
+            // one was committed; the reverted transaction takes precedence.
+            if let Some(existing_tx_reference) =
+                self.tx_pool.get_by_address_and_nonce(tx_reference.address, tx_reference.nonce)
+            {
+                self.tx_pool
+                    .remove(existing_tx_reference.tx_hash)
+                    .expect("Transaction hash from pool must exist.");
+                self.evicted_txs.insert(existing_tx_reference.tx_hash, EvictionReason::Replaced);
+            }
+            self.tx_pool.insert(tx)?;
+        }
+
+        affected_addresses.extend(self.state.rewind(address_to_nonce));
+        affected_addresses.sort();
+        affected_addresses.dedup();
+        for address in affected_addresses {
+            self.align_account_to_nonce(address)?;
+        }
+
+        tracing::debug!("Aligned mempool to rewound nonces.");
+
+        Ok(())
+    }
+
+    /// Rebuilds the queued and suspended transactions of the given account from scratch,
+    /// according to its current nonce.
+    fn align_account_to_nonce(&mut self, address: ContractAddress) -> MempoolResult<()> {
+        self.tx_queue.remove(address);
+        self.suspended_tx_pool.remove_account(address);
+
+        let Some(account_nonce) = self.state.get(address) else {
+            return Ok(());
+        };
+
+        let mut next_ready_nonce = account_nonce;
+        let account_txs: Vec<TransactionReference> =
+            self.tx_pool.account_txs_sorted_by_nonce(address).copied().collect();
+        for tx_reference in account_txs {
+            if tx_reference.nonce < account_nonce {
+                continue;
+            }
+
+            if tx_reference.nonce == next_ready_nonce {
+                next_ready_nonce = try_increment_nonce(next_ready_nonce)?;
+            } else {
+                self.suspended_tx_pool.insert(tx_reference);
+            }
+        }
+
+        if let Some(tx_reference) = self.tx_pool.get_by_address_and_nonce(address, account_nonce) {
+            self.tx_queue.insert(tx_reference);
+        }
+
         Ok(())
     }
 
+    /// Removes all transactions that were held in the mempool for longer than the configured TTL.
+    /// Transactions already passed on for sequencing are kept until the block is committed.
+    pub fn remove_expired_txs(&mut self) {
+        let Some(expiry_time) = get_instant_now().checked_sub(self.config.transaction_ttl) else {
+            return;
+        };
+
+        let mut n_expired_txs: u64 = 0;
+        for tx_reference in self.tx_pool.get_submitted_before(expiry_time) {
+            if self.state.is_staged(&tx_reference) {
+                continue;
+            }
+
+            self.remove_tx(tx_reference);
+            self.evicted_txs.insert(tx_reference.tx_hash, EvictionReason::Expired);
+            n_expired_txs += 1;
+        }
+
+        if n_expired_txs > 0 {
+            tracing::debug!("Removed {n_expired_txs} expired transactions from the mempool.");
+            metrics::counter!(MEMPOOL_EXPIRED_TXS, n_expired_txs);
+        }
+    }
+
     fn validate_incoming_tx(&self, tx_reference: TransactionReference) -> MempoolResult<()> {
         self.state.validate_incoming_tx(tx_reference)
     }
@@ -288,6 +581,71 @@ impl Mempool {
         self.state.validate_commitment(address, next_nonce);
     }
 
+    /// Returns the status of the given transaction, if it is held in the mempool or was recently
+    /// evicted from it.
+    pub fn get_transaction_status(
+        &self,
+        tx_hash: TransactionHash,
+    ) -> MempoolResult<TransactionStatus> {
+        match self.tx_pool.get_by_tx_hash(tx_hash) {
+            Ok(tx) => Ok(self.status(&TransactionReference::new(tx))),
+            Err(err) => self.evicted_txs.get(tx_hash).map(TransactionStatus::Evicted).ok_or(err),
+        }
+    }
+
+    /// Returns the account nonce known to the mempool and the account's transactions held in it.
+    pub fn get_account_transactions(
+        &self,
+        address: ContractAddress,
+    ) -> MempoolResult<AccountTransactions> {
+        let txs = self
+            .tx_pool
+            .account_txs_sorted_by_nonce(address)
+            .map(|tx_reference| TransactionInfo {
+                tx_hash: tx_reference.tx_hash,
+                nonce: tx_reference.nonce,
+                status: self.status(tx_reference),
+            })
+            .collect();
+
+        Ok(AccountTransactions { address, nonce: self.state.get(address), txs })
+    }
+
+    /// Returns the transactions with the given hashes that are held in the mempool, in the order of
+    /// the given hashes. Unknown hashes are skipped.
+    pub fn get_txs_by_hash(
+        &self,
+        tx_hashes: &[TransactionHash],
+    ) -> MempoolResult<Vec<AccountTransaction>> {
+        Ok(tx_hashes
+            .iter()
+            .filter_map(|&tx_hash| self.tx_pool.get_by_tx_hash(tx_hash).ok())
+            .cloned()
+            .collect())
+    }
+
+    pub fn get_pool_stats(&self) -> MempoolResult<MempoolStats> {
+        Ok(MempoolStats {
+            n_txs: self.tx_pool.n_txs(),
+            n_queued_txs: self.tx_queue.n_ready_txs(),
+            n_suspended_txs: self.suspended_tx_pool.n_txs(),
+            n_accounts: self.tx_pool.n_accounts(),
+            gas_price_threshold: self.tx_queue.gas_price_threshold(),
+        })
+    }
+
+    fn status(&self, tx_reference: &TransactionReference) -> TransactionStatus {
+        if self.state.is_staged(tx_reference) {
+            TransactionStatus::Staged
+        } else if self.suspended_tx_pool.contains(tx_reference.address, tx_reference.nonce) {
+            TransactionStatus::Suspended
+        } else if self.tx_queue.is_ready(tx_reference) {
+            TransactionStatus::Queued
+        } else {
+            TransactionStatus::Pending
+        }
+    }
+
     // TODO(Mohammad): Rename this method once consensus API is added.
     pub fn update_gas_price_threshold(&mut self, threshold: GasPrice) {
         self.tx_queue.update_gas_price_threshold(threshold);
@@ -307,26 +665,26 @@ impl Mempool {
         Ok(())
     }
 
-    #[tracing::instrument(level = "debug", skip(self, incoming_tx), err)]
-    fn handle_fee_escalation(&mut self, incoming_tx: &AccountTransaction) -> MempoolResult<()> {
-        let incoming_tx_reference = TransactionReference::new(incoming_tx);
-        let TransactionReference { address, nonce, .. } = incoming_tx_reference;
-
-        if !self.config.enable_fee_escalation {
-            if self.tx_pool.get_by_address_and_nonce(address, nonce).is_some() {
-                return Err(MempoolError::DuplicateNonce { address, nonce });
-            };
-
-            return Ok(());
-        }
+    /// Returns the transaction of the same account and nonce that the incoming transaction
+    /// replaces, if any. Fails if there is such a transaction, and it may not be replaced.
+    #[tracing::instrument(level = "debug", skip(self), err)]
+    fn validate_fee_escalation(
+        &self,
+        incoming_tx_reference: &TransactionReference,
+    ) -> MempoolResult<Option<TransactionReference>> {
+        let TransactionReference { address, nonce, .. } = *incoming_tx_reference;
 
         let Some(existing_tx_reference) = self.tx_pool.get_by_address_and_nonce(address, nonce)
         else {
             // Replacement irrelevant: no existing transaction with the same nonce for address.
-            return Ok(());
+            return Ok(None);
         };
 
-        if !self.should_replace_tx(&existing_tx_reference, &incoming_tx_reference) {
+        if !self.config.enable_fee_escalation {
+            return Err(MempoolError::DuplicateNonce { address, nonce });
+        }
+
+        if !self.should_replace_tx(&existing_tx_reference, incoming_tx_reference) {
             tracing::debug!(
                 "{existing_tx_reference} was not replaced by {incoming_tx_reference} due to
                 insufficient fee escalation."
@@ -336,11 +694,138 @@ impl Mempool {
         }
 
         tracing::debug!("{existing_tx_reference} will be replaced by {incoming_tx_reference}.");
+        Ok(Some(existing_tx_reference))
+    }
 
+    fn replace_tx(&mut self, existing_tx_reference: TransactionReference) {
+        let TransactionReference { address, nonce, tx_hash, .. } = existing_tx_reference;
         self.tx_queue.remove(address);
-        self.tx_pool
-            .remove(existing_tx_reference.tx_hash)
-            .expect("Transaction hash from pool must exist.");
+        self.tx_pool.remove(tx_hash).expect("Transaction hash from pool must exist.");
+        self.suspended_tx_pool.remove(address, nonce);
+        self.evicted_txs.insert(tx_hash, EvictionReason::Replaced);
+    }
+
+    /// Makes room for the incoming transaction if the account or the pool are at full capacity, by
+    /// evicting a transaction of lower priority. Only the highest-nonce transaction of an account
+    /// is evicted, so that no nonce gaps are created.
+    #[tracing::instrument(level = "debug", skip(self), err)]
+    fn handle_capacity(
+        &mut self,
+        incoming_tx_reference: &TransactionReference,
+    ) -> MempoolResult<()> {
+        let TransactionReference { address, nonce, tx_hash, .. } = *incoming_tx_reference;
+
+        let account_eviction_candidate =
+            if self.tx_pool.n_account_txs(address) >= self.config.max_n_txs_per_account {
+                // Prefer lower nonces of the account, as they are the first to become eligible.
+                let last_account_tx = self
+                    .tx_pool
+                    .last_account_tx(address)
+                    .filter(|last_account_tx| last_account_tx.nonce > nonce)
+                    .ok_or(MempoolError::AccountCapacityExceeded { address })?;
+                Some(last_account_tx)
+            } else {
+                None
+            };
+
+        // Evicting a transaction of the account makes room in the pool as well.
+        let n_txs_after_eviction =
+            self.tx_pool.n_txs() - usize::from(account_eviction_candidate.is_some());
+        let pool_eviction_candidate = if n_txs_after_eviction >= self.config.max_n_txs_in_pool {
+            let Some(&eviction_candidate) = self.tx_pool.iter_eviction_candidates().find(|tx| {
+                // Evicting a transaction of the same account would leave the incoming one gapped.
+                tx.address != address && !self.state.is_staged(tx)
+            }) else {
+                return Err(MempoolError::MempoolFull { tx_hash });
+            };
+
+            let priority = |tx: &TransactionReference| (tx.tip, tx.max_l2_gas_price);
+            if priority(&eviction_candidate) >= priority(incoming_tx_reference) {
+                return Err(MempoolError::MempoolFull { tx_hash });
+            }
+            Some(eviction_candidate)
+        } else {
+            None
+        };
+
+        // Nothing is evicted unless the incoming transaction is admitted.
+        for tx_reference in account_eviction_candidate.into_iter().chain(pool_eviction_candidate) {
+            self.evict(tx_reference);
+        }
+
+        Ok(())
+    }
+
+    fn evict(&mut self, tx_reference: TransactionReference) {
+        tracing::debug!("{tx_reference} will be evicted from the mempool.");
+        self.remove_tx(tx_reference);
+        self.evicted_txs.insert(tx_reference.tx_hash, EvictionReason::Capacity);
+    }
+
+    /// Removes a transaction from the mempool. Transactions of the same account with higher nonces
+    /// can no longer be reached from the account nonce, and are therefore suspended.
+    fn remove_tx(&mut self, tx_reference: TransactionReference) {
+        let TransactionReference { address, nonce, tx_hash, .. } = tx_reference;
+
+        if self.tx_queue.get_nonce(address) == Some(nonce) {
+            self.tx_queue.remove(address);
+        }
+        self.tx_pool.remove(tx_hash).expect("Transaction hash from pool must exist.");
+        self.suspended_tx_pool.remove(address, nonce);
+
+        let txs_to_suspend: Vec<TransactionReference> = self
+            .tx_pool
+            .account_txs_sorted_by_nonce(address)
+            .filter(|tx| tx.nonce > nonce && !self.suspended_tx_pool.contains(address, tx.nonce))
+            .copied()
+            .collect();
+        for tx in txs_to_suspend {
+            self.suspended_tx_pool.insert(tx);
+        }
+    }
+
+    /// A transaction is nonce-gapped if it cannot be reached from the account nonce through
+    /// transactions with consecutive nonces.
+    fn is_nonce_gapped(&self, tx_reference: &TransactionReference, account_nonce: Nonce) -> bool {
+        let TransactionReference { address, nonce, .. } = *tx_reference;
+        if nonce <= account_nonce {
+            return false;
+        }
+
+        let previous_nonce = nonce.try_decrement().expect("Nonce is above account nonce.");
+        self.tx_pool.get_by_address_and_nonce(address, previous_nonce).is_none()
+            || self.suspended_tx_pool.contains(address, previous_nonce)
+    }
+
+    fn validate_suspended_capacity(
+        &self,
+        tx_reference: &TransactionReference,
+    ) -> MempoolResult<()> {
+        let TransactionReference { address, tx_hash, .. } = *tx_reference;
+
+        if self.suspended_tx_pool.n_account_txs(address)
+            >= self.config.max_n_suspended_txs_per_account
+        {
+            return Err(MempoolError::AccountCapacityExceeded { address });
+        }
+        if self.suspended_tx_pool.n_txs() >= self.config.max_n_suspended_txs {
+            return Err(MempoolError::MempoolFull { tx_hash });
+        }
+
+        Ok(())
+    }
+
+    /// Resumes the suspended transactions of the given account that are reachable through
+    /// consecutive nonces, starting from the given nonce.
+    fn resume_suspended_txs(
+        &mut self,
+        address: ContractAddress,
+        nonce: Nonce,
+    ) -> MempoolResult<()> {
+        let mut nonce = nonce;
+        while self.suspended_tx_pool.remove(address, nonce) {
+            nonce = try_increment_nonce(nonce)?;
+        }
 
         Ok(())
     }
@@ -1,26 +1,59 @@
+use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
 use std::sync::Arc;
+use std::time::Duration;
 
-use mockall::predicate;
+use assert_matches::assert_matches;
+use mockall::{predicate, Sequence};
 use papyrus_network_types::network_types::BroadcastedMessageMetadata;
 use papyrus_test_utils::{get_rng, GetTestInstance};
 use pretty_assertions::assert_eq;
 use rstest::{fixture, rstest};
 use starknet_api::block::GasPrice;
+use starknet_api::contract_class::{ClassInfo, ContractClass, SierraVersion};
+use starknet_api::deprecated_contract_class::ContractClass as DeprecatedContractClass;
 use starknet_api::executable_transaction::AccountTransaction;
-use starknet_api::rpc_transaction::{
-    RpcDeployAccountTransaction,
-    RpcInvokeTransaction,
-    RpcTransaction,
-};
-use starknet_api::{contract_address, nonce};
+use starknet_api::rpc_transaction::{RpcInvokeTransaction, RpcTransaction};
+use starknet_api::state::SierraContractClass;
+use starknet_api::test_utils::declare::{executable_declare_tx, rpc_declare_tx};
+use starknet_api::transaction::TransactionVersion;
+use starknet_api::{contract_address, declare_tx_args, nonce, tx_hash};
+use starknet_gateway_types::communication::MockGatewayClient;
+use starknet_gateway_types::gateway_types::GatewayInput;
 use starknet_mempool_p2p_types::communication::MockMempoolP2pPropagatorClient;
-use starknet_mempool_types::communication::AddTransactionArgsWrapper;
+use starknet_mempool_types::communication::{
+    AddTransactionArgsWrapper,
+    MempoolRequest,
+    MempoolResponse,
+};
 use starknet_mempool_types::errors::MempoolError;
-use starknet_mempool_types::mempool_types::AddTransactionArgs;
+use starknet_mempool_types::mempool_types::{
+    AccountState,
+    AccountTransactions,
+    AddTransactionArgs,
+    CommitBlockArgs,
+    EvictionReason,
+    MempoolStats,
+    TransactionInfo,
+    TransactionStatus,
+};
+use starknet_sequencer_infra::component_definitions::{ComponentRequestHandler, ComponentStarter};
+use starknet_sequencer_infra::shutdown::ShutdownController;
+use validator::Validate;
 
 use crate::communication::MempoolCommunicationWrapper;
-use crate::mempool::{Mempool, MempoolConfig, TransactionReference};
-use crate::test_utils::{add_tx, add_tx_expect_error, commit_block, get_txs_and_assert_expected};
+use crate::config::MempoolConfig;
+use crate::evicted_transactions::EvictedTransactions;
+use crate::mempool::{Mempool, TransactionReference};
+use crate::persistence::MempoolSnapshot;
+use crate::suspended_transaction_pool::SuspendedTransactionPool;
+use crate::test_utils::{
+    add_tx,
+    add_tx_expect_error,
+    commit_block,
+    get_txs_and_assert_expected,
+    rewind_block,
+};
 use crate::transaction_pool::TransactionPool;
 use crate::transaction_queue::transaction_queue_test_utils::{
     TransactionQueueContent,
@@ -37,6 +70,7 @@ struct MempoolContent {
     config: MempoolConfig,
     tx_pool: Option<TransactionPool>,
     tx_queue_content: Option<TransactionQueueContent>,
+    suspended_tx_pool: Option<SuspendedTransactionPool>,
 }
 
 impl MempoolContent {
@@ -49,18 +83,25 @@ impl MempoolContent {
         if let Some(tx_queue_content) = &self.tx_queue_content {
             tx_queue_content.assert_eq(&mempool.tx_queue);
         }
+
+        if let Some(suspended_tx_pool) = &self.suspended_tx_pool {
+            assert_eq!(&mempool.suspended_tx_pool, suspended_tx_pool);
+        }
     }
 }
 
 impl From<MempoolContent> for Mempool {
     fn from(mempool_content: MempoolContent) -> Mempool {
-        let MempoolContent { tx_pool, tx_queue_content, config } = mempool_content;
+        let MempoolContent { tx_pool, tx_queue_content, suspended_tx_pool, config } =
+            mempool_content;
         Mempool {
+            evicted_txs: EvictedTransactions::new(config.max_n_evicted_tx_records),
             config,
             tx_pool: tx_pool.unwrap_or_default(),
             tx_queue: tx_queue_content
                 .map(|content| content.complete_to_tx_queue())
                 .unwrap_or_default(),
+            suspended_tx_pool: suspended_tx_pool.unwrap_or_default(),
             // TODO: Add implementation when needed.
             state: Default::default(),
         }
@@ -72,6 +113,7 @@ struct MempoolContentBuilder {
     config: MempoolConfig,
     tx_pool: Option<TransactionPool>,
     tx_queue_content_builder: TransactionQueueContentBuilder,
+    suspended_tx_pool: Option<SuspendedTransactionPool>,
 }
 
 impl MempoolContentBuilder {
@@ -80,6 +122,7 @@ impl MempoolContentBuilder {
             config: MempoolConfig { enable_fee_escalation: false, ..Default::default() },
             tx_pool: None,
             tx_queue_content_builder: Default::default(),
+            suspended_tx_pool: None,
         }
     }
 
@@ -91,6 +134,14 @@ impl MempoolContentBuilder {
         self
     }
 
+    fn with_suspended_pool<S>(mut self, suspended_txs: S) -> Self
+    where
+        S: IntoIterator<Item = TransactionReference>,
+    {
+        self.suspended_tx_pool = Some(suspended_txs.into_iter().collect());
+        self
+    }
+
     fn with_priority_queue<Q>(mut self, queue_txs: Q) -> Self
     where
         Q: IntoIterator<Item = TransactionReference>,
@@ -114,7 +165,33 @@ impl MempoolContentBuilder {
     }
 
     fn with_fee_escalation_percentage(mut self, fee_escalation_percentage: u8) -> Self {
-        self.config = MempoolConfig { enable_fee_escalation: true, fee_escalation_percentage };
+        self.config =
+            MempoolConfig { enable_fee_escalation: true, fee_escalation_percentage, ..self.config };
+        self
+    }
+
+    fn with_transaction_ttl(mut self, transaction_ttl: Duration) -> Self {
+        self.config = MempoolConfig { transaction_ttl, ..self.config };
+        self
+    }
+
+    fn with_suspended_capacity(
+        mut self,
+        max_n_suspended_txs: usize,
+        max_n_suspended_txs_per_account: usize,
+    ) -> Self {
+        self.config =
+            MempoolConfig { max_n_suspended_txs, max_n_suspended_txs_per_account, ..self.config };
+        self
+    }
+
+    fn with_snapshot_path(mut self, snapshot_path: PathBuf) -> Self {
+        self.config = MempoolConfig { snapshot_path: Some(snapshot_path), ..self.config };
+        self
+    }
+
+    fn with_capacity(mut self, max_n_txs_in_pool: usize, max_n_txs_per_account: usize) -> Self {
+        self.config = MempoolConfig { max_n_txs_in_pool, max_n_txs_per_account, ..self.config };
         self
     }
 
@@ -123,6 +200,7 @@ impl MempoolContentBuilder {
             config: self.config,
             tx_pool: self.tx_pool,
             tx_queue_content: self.tx_queue_content_builder.build(),
+            suspended_tx_pool: self.suspended_tx_pool,
         }
     }
 
@@ -141,6 +219,16 @@ impl FromIterator<AccountTransaction> for TransactionPool {
     }
 }
 
+impl FromIterator<TransactionReference> for SuspendedTransactionPool {
+    fn from_iter<T: IntoIterator<Item = TransactionReference>>(txs: T) -> Self {
+        let mut suspended_pool = Self::default();
+        for tx in txs {
+            suspended_pool.insert(tx);
+        }
+        suspended_pool
+    }
+}
+
 #[track_caller]
 fn add_tx_and_verify_replacement(
     mut mempool: Mempool,
@@ -327,6 +415,33 @@ fn test_get_txs_with_nonce_gap() {
     expected_mempool_content.assert_eq(&mempool);
 }
 
+#[rstest]
+#[tokio::test(start_paused = true)]
+async fn test_get_oldest_txs_returns_by_submission_order(mut mempool: Mempool) {
+    // Setup.
+    let input_address_0_nonce_1 =
+        add_tx_input!(tx_hash: 1, address: "0x0", tx_nonce: 1, account_nonce: 0, tip: 30);
+    let input_address_1_nonce_0 =
+        add_tx_input!(tx_hash: 2, address: "0x1", tx_nonce: 0, account_nonce: 0, tip: 10);
+    let input_address_0_nonce_0 =
+        add_tx_input!(tx_hash: 3, address: "0x0", tx_nonce: 0, account_nonce: 0, tip: 30);
+    for input in [&input_address_0_nonce_1, &input_address_1_nonce_0, &input_address_0_nonce_0] {
+        add_tx(&mut mempool, input);
+        tokio::time::advance(Duration::from_secs(1)).await;
+    }
+
+    // Test.
+    let fetched_txs = mempool.get_oldest_txs(3).unwrap();
+
+    // Assert: the oldest transaction is returned only after the one preceding it in nonce order.
+    assert_eq!(
+        fetched_txs,
+        [input_address_1_nonce_0.tx, input_address_0_nonce_0.tx, input_address_0_nonce_1.tx]
+    );
+    let expected_mempool_content = MempoolContentBuilder::new().with_priority_queue([]).build();
+    expected_mempool_content.assert_eq(&mempool);
+}
+
 // `add_tx` tests.
 
 #[rstest]
@@ -521,6 +636,74 @@ fn test_add_tx_does_not_decrease_account_nonce(mut mempool: Mempool) {
     assert_eq!(mempool.state.get(contract_address!("0x0")), Some(nonce!(2)));
 }
 
+#[rstest]
+fn test_add_tx_suspends_nonce_gapped_txs(mut mempool: Mempool) {
+    // Setup.
+    let input_nonce_0 = add_tx_input!(tx_hash: 1, tx_nonce: 0, account_nonce: 0);
+    let input_nonce_2 = add_tx_input!(tx_hash: 2, tx_nonce: 2, account_nonce: 0);
+    let input_nonce_3 = add_tx_input!(tx_hash: 3, tx_nonce: 3, account_nonce: 0);
+
+    // Test.
+    for input in [&input_nonce_0, &input_nonce_2, &input_nonce_3] {
+        add_tx(&mut mempool, input);
+    }
+
+    // Assert: transactions following the gap are suspended.
+    let expected_suspended_txs =
+        [&input_nonce_2.tx, &input_nonce_3.tx].map(TransactionReference::new);
+    let expected_mempool_content = MempoolContentBuilder::new()
+        .with_priority_queue([TransactionReference::new(&input_nonce_0.tx)])
+        .with_suspended_pool(expected_suspended_txs)
+        .build();
+    expected_mempool_content.assert_eq(&mempool);
+
+    // Test: close the gap.
+    let input_nonce_1 = add_tx_input!(tx_hash: 4, tx_nonce: 1, account_nonce: 0);
+    add_tx(&mut mempool, &input_nonce_1);
+
+    // Assert: all transactions are resumed.
+    let expected_mempool_content = MempoolContentBuilder::new()
+        .with_priority_queue([TransactionReference::new(&input_nonce_0.tx)])
+        .with_suspended_pool([])
+        .build();
+    expected_mempool_content.assert_eq(&mempool);
+    get_txs_and_assert_expected(
+        &mut mempool,
+        4,
+        &[input_nonce_0.tx, input_nonce_1.tx, input_nonce_2.tx, input_nonce_3.tx],
+    );
+}
+
+#[rstest]
+fn test_add_tx_suspended_capacity() {
+    // Setup.
+    let mut mempool =
+        MempoolContentBuilder::new().with_suspended_capacity(2, 1).build_into_mempool();
+    add_tx(&mut mempool, &add_tx_input!(tx_hash: 1, address: "0x0", tx_nonce: 1, account_nonce: 0));
+
+    // Test and assert: the account limit is enforced.
+    let input_same_account =
+        add_tx_input!(tx_hash: 2, address: "0x0", tx_nonce: 3, account_nonce: 0);
+    add_tx_expect_error(
+        &mut mempool,
+        &input_same_account,
+        MempoolError::AccountCapacityExceeded { address: contract_address!("0x0") },
+    );
+
+    // Test and assert: the global limit is enforced.
+    add_tx(&mut mempool, &add_tx_input!(tx_hash: 3, address: "0x1", tx_nonce: 1, account_nonce: 0));
+    let input_other_account =
+        add_tx_input!(tx_hash: 4, address: "0x2", tx_nonce: 1, account_nonce: 0);
+    add_tx_expect_error(
+        &mut mempool,
+        &input_other_account,
+        MempoolError::MempoolFull { tx_hash: input_other_account.tx.tx_hash() },
+    );
+
+    // Test and assert: transactions that are not nonce-gapped are not affected.
+    add_tx(&mut mempool, &add_tx_input!(tx_hash: 5, address: "0x2", tx_nonce: 0, account_nonce: 0));
+}
+
 // `commit_block` tests.
 
 #[rstest]
@@ -561,6 +744,72 @@ fn test_commit_block_includes_all_proposed_txs() {
     expected_mempool_content.assert_eq(&mempool);
 }
 
+#[rstest]
+fn test_commit_block_resumes_suspended_txs(mut mempool: Mempool) {
+    // Setup.
+    let input_nonce_2 = add_tx_input!(tx_hash: 1, address: "0x0", tx_nonce: 2, account_nonce: 0);
+    let input_nonce_3 = add_tx_input!(tx_hash: 2, address: "0x0", tx_nonce: 3, account_nonce: 0);
+    let input_nonce_5 = add_tx_input!(tx_hash: 3, address: "0x0", tx_nonce: 5, account_nonce: 0);
+    for input in [&input_nonce_2, &input_nonce_3, &input_nonce_5] {
+        add_tx(&mut mempool, input);
+    }
+
+    // Test: nonces 0 and 1 were included in a block by a different node.
+    commit_block(&mut mempool, [("0x0", 2)], []);
+
+    // Assert: transactions up to the next gap are resumed.
+    let expected_mempool_content = MempoolContentBuilder::new()
+        .with_priority_queue([TransactionReference::new(&input_nonce_2.tx)])
+        .with_suspended_pool([TransactionReference::new(&input_nonce_5.tx)])
+        .build();
+    expected_mempool_content.assert_eq(&mempool);
+}
+
+// `rewind_block` tests.
+
+#[rstest]
+fn test_rewind_block_reinserts_txs_and_rebuilds_queue(mut mempool: Mempool) {
+    // Setup: nonces 0 and 1 were committed, while nonce 3 arrived afterwards.
+    let tx_nonce_0 = tx!(tx_hash: 1, address: "0x0", tx_nonce: 0);
+    let tx_nonce_1 = tx!(tx_hash: 2, address: "0x0", tx_nonce: 1);
+    commit_block(&mut mempool, [("0x0", 2)], []);
+    let input_nonce_3 = add_tx_input!(tx_hash: 3, address: "0x0", tx_nonce: 3, account_nonce: 2);
+    add_tx(&mut mempool, &input_nonce_3);
+
+    // Test.
+    rewind_block(&mut mempool, [("0x0", 0)], [tx_nonce_0.clone(), tx_nonce_1.clone()]);
+
+    // Assert: the account is queued from its rolled back nonce, and the gap is kept.
+    let expected_mempool_content = MempoolContentBuilder::new()
+        .with_pool([tx_nonce_0.clone(), tx_nonce_1, input_nonce_3.tx.clone()])
+        .with_priority_queue([TransactionReference::new(&tx_nonce_0)])
+        .with_suspended_pool([TransactionReference::new(&input_nonce_3.tx)])
+        .build();
+    expected_mempool_content.assert_eq(&mempool);
+}
+
+#[rstest]
+fn test_rewind_block_replaces_tx_with_same_nonce(mut mempool: Mempool) {
+    // Setup.
+    let reverted_tx = tx!(tx_hash: 1, address: "0x0", tx_nonce: 0);
+    let input_same_nonce = add_tx_input!(tx_hash: 2, address: "0x0", tx_nonce: 0, account_nonce: 0);
+    add_tx(&mut mempool, &input_same_nonce);
+
+    // Test.
+    rewind_block(&mut mempool, [("0x0", 0)], [reverted_tx.clone()]);
+
+    // Assert: the reverted transaction takes precedence.
+    let expected_mempool_content = MempoolContentBuilder::new()
+        .with_pool([reverted_tx.clone()])
+        .with_priority_queue([TransactionReference::new(&reverted_tx)])
+        .build();
+    expected_mempool_content.assert_eq(&mempool);
+    assert_eq!(
+        mempool.get_transaction_status(input_same_nonce.tx.tx_hash()),
+        Ok(TransactionStatus::Evicted(EvictionReason::Replaced))
+    );
+}
+
 // Fee escalation tests.
 
 #[rstest]
@@ -695,6 +944,660 @@ fn test_fee_escalation_invalid_replacement_overflow_gracefully_handled() {
     add_txs_and_verify_no_replacement(mempool, existing_tx, [invalid_replacement_input]);
 }
 
+// Capacity tests.
+
+#[rstest]
+fn test_add_tx_evicts_lowest_priority_tx_when_pool_is_full() {
+    // Setup.
+    let tx_tip_10 = tx!(tx_hash: 1, address: "0x0", tip: 10);
+    let tx_tip_20 = tx!(tx_hash: 2, address: "0x1", tip: 20);
+    let mut mempool = MempoolContentBuilder::new()
+        .with_pool([tx_tip_10.clone(), tx_tip_20.clone()])
+        .with_priority_queue([&tx_tip_10, &tx_tip_20].map(TransactionReference::new))
+        .with_capacity(2, 10)
+        .build_into_mempool();
+
+    // Test.
+    let input_tip_30 = add_tx_input!(tx_hash: 3, address: "0x2", tip: 30);
+    add_tx(&mut mempool, &input_tip_30);
+
+    // Assert: the transaction with the lowest tip was evicted from both pool and queue.
+    let expected_queue_txs = [&input_tip_30.tx, &tx_tip_20].map(TransactionReference::new);
+    let expected_mempool_content = MempoolContentBuilder::new()
+        .with_pool([tx_tip_20, input_tip_30.tx])
+        .with_priority_queue(expected_queue_txs)
+        .build();
+    expected_mempool_content.assert_eq(&mempool);
+}
+
+#[rstest]
+fn test_add_tx_evicts_highest_nonce_of_account_when_pool_is_full() {
+    // Setup.
+    let tx_nonce_0 = tx!(tx_hash: 1, address: "0x0", tx_nonce: 0, tip: 10);
+    let tx_nonce_1 = tx!(tx_hash: 2, address: "0x0", tx_nonce: 1, tip: 20);
+    let mut mempool = MempoolContentBuilder::new()
+        .with_pool([tx_nonce_0.clone(), tx_nonce_1])
+        .with_priority_queue([TransactionReference::new(&tx_nonce_0)])
+        .with_capacity(2, 10)
+        .build_into_mempool();
+
+    // Test.
+    let input = add_tx_input!(tx_hash: 3, address: "0x1", tip: 30);
+    add_tx(&mut mempool, &input);
+
+    // Assert: although it has a lower tip, the first transaction of the account was kept.
+    let expected_queue_txs = [&input.tx, &tx_nonce_0].map(TransactionReference::new);
+    let expected_mempool_content = MempoolContentBuilder::new()
+        .with_pool([tx_nonce_0, input.tx])
+        .with_priority_queue(expected_queue_txs)
+        .build();
+    expected_mempool_content.assert_eq(&mempool);
+}
+
+#[rstest]
+#[case::lower_tip(add_tx_input!(tx_hash: 2, address: "0x1", tip: 9, max_l2_gas_price: 100))]
+#[case::equal_priority(add_tx_input!(tx_hash: 2, address: "0x1", tip: 10, max_l2_gas_price: 100))]
+fn test_add_tx_rejected_when_pool_is_full(#[case] input: AddTransactionArgs) {
+    // Setup.
+    let existing_tx = tx!(tx_hash: 1, tip: 10, max_l2_gas_price: 100);
+    let mut mempool = MempoolContentBuilder::new()
+        .with_pool([existing_tx.clone()])
+        .with_capacity(1, 10)
+        .build_into_mempool();
+
+    // Test and assert: the existing transaction remains.
+    add_tx_expect_error(
+        &mut mempool,
+        &input,
+        MempoolError::MempoolFull { tx_hash: input.tx.tx_hash() },
+    );
+    let expected_mempool_content = MempoolContentBuilder::new().with_pool([existing_tx]).build();
+    expected_mempool_content.assert_eq(&mempool);
+}
+
+#[rstest]
+fn test_add_tx_rejected_when_pool_is_full_evicts_nothing() {
+    // Setup: the pool exceeds its capacity, as after a rewound block, and the account is full.
+    let tx_nonce_1 = tx!(tx_hash: 1, address: "0x0", tx_nonce: 1, tip: 10);
+    let tx_tip_50 = tx!(tx_hash: 2, address: "0x1", tip: 50);
+    let other_tx_tip_50 = tx!(tx_hash: 3, address: "0x2", tip: 50);
+    let pool_txs = [tx_nonce_1, tx_tip_50, other_tx_tip_50];
+    let mut mempool = MempoolContentBuilder::new()
+        .with_pool(pool_txs.clone())
+        .with_capacity(2, 1)
+        .build_into_mempool();
+
+    // Test: evicting the higher nonce of the account doesn't make enough room.
+    let input = add_tx_input!(tx_hash: 4, address: "0x0", tx_nonce: 0, account_nonce: 0, tip: 20);
+    add_tx_expect_error(
+        &mut mempool,
+        &input,
+        MempoolError::MempoolFull { tx_hash: input.tx.tx_hash() },
+    );
+
+    // Assert: the higher nonce of the account was kept.
+    let expected_mempool_content = MempoolContentBuilder::new().with_pool(pool_txs).build();
+    expected_mempool_content.assert_eq(&mempool);
+}
+
+#[rstest]
+fn test_add_tx_does_not_evict_txs_passed_to_batcher() {
+    // Setup.
+    let mut mempool = MempoolContentBuilder::new().with_capacity(1, 10).build_into_mempool();
+    let input_tip_10 = add_tx_input!(tx_hash: 1, address: "0x0", tip: 10);
+    add_tx(&mut mempool, &input_tip_10);
+    get_txs_and_assert_expected(&mut mempool, 1, &[input_tip_10.tx.clone()]);
+
+    // Test and assert.
+    let input_tip_20 = add_tx_input!(tx_hash: 2, address: "0x1", tip: 20);
+    add_tx_expect_error(
+        &mut mempool,
+        &input_tip_20,
+        MempoolError::MempoolFull { tx_hash: input_tip_20.tx.tx_hash() },
+    );
+    let expected_mempool_content =
+        MempoolContentBuilder::new().with_pool([input_tip_10.tx]).build();
+    expected_mempool_content.assert_eq(&mempool);
+}
+
+#[rstest]
+fn test_add_tx_account_capacity() {
+    // Setup.
+    let tx_nonce_0 = tx!(tx_hash: 1, address: "0x0", tx_nonce: 0);
+    let tx_nonce_2 = tx!(tx_hash: 2, address: "0x0", tx_nonce: 2);
+    let mut mempool = MempoolContentBuilder::new()
+        .with_pool([tx_nonce_0.clone(), tx_nonce_2.clone()])
+        .with_priority_queue([TransactionReference::new(&tx_nonce_0)])
+        .with_capacity(10, 2)
+        .build_into_mempool();
+
+    // Test and assert: a higher nonce is rejected.
+    let input_nonce_3 = add_tx_input!(tx_hash: 3, address: "0x0", tx_nonce: 3, account_nonce: 0);
+    add_tx_expect_error(
+        &mut mempool,
+        &input_nonce_3,
+        MempoolError::AccountCapacityExceeded { address: contract_address!("0x0") },
+    );
+
+    // Test and assert: a lower nonce evicts the highest nonce of the account.
+    let input_nonce_1 = add_tx_input!(tx_hash: 4, address: "0x0", tx_nonce: 1, account_nonce: 0);
+    add_tx(&mut mempool, &input_nonce_1);
+    let expected_mempool_content = MempoolContentBuilder::new()
+        .with_pool([tx_nonce_0.clone(), input_nonce_1.tx])
+        .with_priority_queue([TransactionReference::new(&tx_nonce_0)])
+        .build();
+    expected_mempool_content.assert_eq(&mempool);
+}
+
+// Expiry tests.
+
+#[rstest]
+#[tokio::test(start_paused = true)]
+async fn test_expired_txs_removed_from_pool_and_queue() {
+    // Setup.
+    let mut mempool = MempoolContentBuilder::new()
+        .with_transaction_ttl(Duration::from_secs(60))
+        .build_into_mempool();
+    let input_address_0_nonce_0 =
+        add_tx_input!(tx_hash: 1, address: "0x0", tx_nonce: 0, account_nonce: 0);
+    let input_address_0_nonce_1 =
+        add_tx_input!(tx_hash: 2, address: "0x0", tx_nonce: 1, account_nonce: 0);
+    add_tx(&mut mempool, &input_address_0_nonce_0);
+    add_tx(&mut mempool, &input_address_0_nonce_1);
+
+    tokio::time::advance(Duration::from_secs(30)).await;
+    let input_address_1_nonce_0 =
+        add_tx_input!(tx_hash: 3, address: "0x1", tx_nonce: 0, account_nonce: 0);
+    add_tx(&mut mempool, &input_address_1_nonce_0);
+
+    // Test.
+    tokio::time::advance(Duration::from_secs(31)).await;
+    mempool.remove_expired_txs();
+
+    // Assert: only the transaction that has not expired remains.
+    let expected_mempool_content = MempoolContentBuilder::new()
+        .with_pool([input_address_1_nonce_0.tx.clone()])
+        .with_priority_queue([TransactionReference::new(&input_address_1_nonce_0.tx)])
+        .with_suspended_pool([])
+        .build();
+    expected_mempool_content.assert_eq(&mempool);
+}
+
+#[rstest]
+#[tokio::test(start_paused = true)]
+async fn test_expired_tx_suspends_following_txs() {
+    // Setup.
+    let mut mempool = MempoolContentBuilder::new()
+        .with_transaction_ttl(Duration::from_secs(60))
+        .build_into_mempool();
+    let input_nonce_0 = add_tx_input!(tx_hash: 1, tx_nonce: 0, account_nonce: 0);
+    add_tx(&mut mempool, &input_nonce_0);
+
+    tokio::time::advance(Duration::from_secs(30)).await;
+    let input_nonce_1 = add_tx_input!(tx_hash: 2, tx_nonce: 1, account_nonce: 0);
+    add_tx(&mut mempool, &input_nonce_1);
+
+    // Test.
+    tokio::time::advance(Duration::from_secs(31)).await;
+    mempool.remove_expired_txs();
+
+    // Assert: the remaining transaction is nonce-gapped.
+    let expected_mempool_content = MempoolContentBuilder::new()
+        .with_pool([input_nonce_1.tx.clone()])
+        .with_priority_queue([])
+        .with_suspended_pool([TransactionReference::new(&input_nonce_1.tx)])
+        .build();
+    expected_mempool_content.assert_eq(&mempool);
+}
+
+#[rstest]
+#[tokio::test(start_paused = true)]
+async fn test_expired_txs_passed_to_batcher_kept_until_commit() {
+    // Setup.
+    let mut mempool = MempoolContentBuilder::new()
+        .with_transaction_ttl(Duration::from_secs(60))
+        .build_into_mempool();
+    let input = add_tx_input!(tx_hash: 1, address: "0x0", tx_nonce: 0, account_nonce: 0);
+    add_tx(&mut mempool, &input);
+    get_txs_and_assert_expected(&mut mempool, 1, &[input.tx.clone()]);
+
+    // Test.
+    tokio::time::advance(Duration::from_secs(61)).await;
+    mempool.remove_expired_txs();
+
+    // Assert: the transaction remains while being sequenced, and is dropped once it is not
+    // included in the committed block.
+    let expected_mempool_content = MempoolContentBuilder::new().with_pool([input.tx]).build();
+    expected_mempool_content.assert_eq(&mempool);
+
+    commit_block(&mut mempool, [], []);
+    get_txs_and_assert_expected(&mut mempool, 1, &[]);
+    let expected_mempool_content =
+        MempoolContentBuilder::new().with_pool([]).with_priority_queue([]).build();
+    expected_mempool_content.assert_eq(&mempool);
+}
+
+#[rstest]
+#[tokio::test(start_paused = true)]
+async fn test_expired_txs_swept_periodically() {
+    // Setup.
+    let mut mempool = MempoolContentBuilder::new()
+        .with_transaction_ttl(Duration::from_secs(60))
+        .build_into_mempool();
+    let input = add_tx_input!(tx_hash: 1, address: "0x0", tx_nonce: 0, account_nonce: 0);
+    add_tx(&mut mempool, &input);
+    let mut mempool_wrapper = MempoolCommunicationWrapper::new(
+        mempool,
+        Arc::new(MockMempoolP2pPropagatorClient::new()),
+        Arc::new(MockGatewayClient::new()),
+        ShutdownController::new().subscribe(),
+    );
+    let mut sweeping_wrapper = mempool_wrapper.clone();
+    tokio::spawn(async move { sweeping_wrapper.start().await });
+
+    // Test: no request reaches the mempool while the transaction expires.
+    tokio::time::sleep(Duration::from_secs(121)).await;
+
+    // Assert.
+    let response = mempool_wrapper
+        .handle_request(MempoolRequest::GetTransactionStatus(input.tx.tx_hash()))
+        .await;
+    assert_matches!(
+        response,
+        MempoolResponse::GetTransactionStatus(Ok(TransactionStatus::Evicted(
+            EvictionReason::Expired
+        )))
+    );
+}
+
+// Query tests.
+
+#[rstest]
+fn test_get_transaction_status() {
+    // Setup.
+    let mut mempool =
+        MempoolContentBuilder::new().with_gas_price_threshold(100).build_into_mempool();
+    let input_nonce_0 = add_tx_input!(tx_hash: 1, address: "0x0", tx_nonce: 0, account_nonce: 0);
+    let input_nonce_1 = add_tx_input!(tx_hash: 2, address: "0x0", tx_nonce: 1, account_nonce: 0);
+    let input_nonce_3 = add_tx_input!(tx_hash: 3, address: "0x0", tx_nonce: 3, account_nonce: 0);
+    let input_low_gas_price = add_tx_input!(tx_hash: 4, address: "0x1", tx_nonce: 0, account_nonce: 0, tip: 0, max_l2_gas_price: 50);
+    for input in [&input_nonce_0, &input_nonce_1, &input_nonce_3, &input_low_gas_price] {
+        add_tx(&mut mempool, input);
+    }
+    get_txs_and_assert_expected(&mut mempool, 1, &[input_nonce_0.tx.clone()]);
+
+    // Test and assert.
+    let expected_statuses = [
+        (&input_nonce_0, TransactionStatus::Staged),
+        (&input_nonce_1, TransactionStatus::Queued),
+        (&input_nonce_3, TransactionStatus::Suspended),
+        (&input_low_gas_price, TransactionStatus::Pending),
+    ];
+    for (input, expected_status) in expected_statuses {
+        assert_eq!(mempool.get_transaction_status(input.tx.tx_hash()), Ok(expected_status));
+    }
+
+    let unknown_tx_hash = tx!(tx_hash: 5, address: "0x2", tx_nonce: 0).tx_hash();
+    assert_eq!(
+        mempool.get_transaction_status(unknown_tx_hash),
+        Err(MempoolError::TransactionNotFound { tx_hash: unknown_tx_hash })
+    );
+}
+
+#[rstest]
+fn test_get_transaction_status_of_evicted_txs() {
+    // Setup.
+    let mut mempool = MempoolContentBuilder::new()
+        .with_fee_escalation_percentage(10)
+        .with_capacity(1, 10)
+        .build_into_mempool();
+    let input_tip_10 = add_tx_input!(tx_hash: 1, address: "0x0", tip: 10, max_l2_gas_price: 100);
+    let input_replacement =
+        add_tx_input!(tx_hash: 2, address: "0x0", tip: 20, max_l2_gas_price: 200);
+    let input_tip_30 = add_tx_input!(tx_hash: 3, address: "0x1", tip: 30, max_l2_gas_price: 300);
+
+    // Test.
+    add_tx(&mut mempool, &input_tip_10);
+    add_tx(&mut mempool, &input_replacement);
+    add_tx(&mut mempool, &input_tip_30);
+
+    // Assert.
+    assert_eq!(
+        mempool.get_transaction_status(input_tip_10.tx.tx_hash()),
+        Ok(TransactionStatus::Evicted(EvictionReason::Replaced))
+    );
+    assert_eq!(
+        mempool.get_transaction_status(input_replacement.tx.tx_hash()),
+        Ok(TransactionStatus::Evicted(EvictionReason::Capacity))
+    );
+    assert_eq!(
+        mempool.get_transaction_status(input_tip_30.tx.tx_hash()),
+        Ok(TransactionStatus::Queued)
+    );
+}
+
+#[rstest]
+#[tokio::test(start_paused = true)]
+async fn test_get_transaction_status_of_expired_tx() {
+    // Setup.
+    let mut mempool = MempoolContentBuilder::new()
+        .with_transaction_ttl(Duration::from_secs(60))
+        .build_into_mempool();
+    let input = add_tx_input!(tx_hash: 1, address: "0x0", tx_nonce: 0, account_nonce: 0);
+    add_tx(&mut mempool, &input);
+
+    // Test.
+    tokio::time::advance(Duration::from_secs(61)).await;
+    mempool.remove_expired_txs();
+
+    // Assert.
+    assert_eq!(
+        mempool.get_transaction_status(input.tx.tx_hash()),
+        Ok(TransactionStatus::Evicted(EvictionReason::Expired))
+    );
+}
+
+#[rstest]
+fn test_get_transaction_status_of_rejected_tx(mut mempool: Mempool) {
+    // Setup.
+    let input_nonce_0 = add_tx_input!(tx_hash: 1, address: "0x0", tx_nonce: 0, account_nonce: 0);
+    let input_nonce_1 = add_tx_input!(tx_hash: 2, address: "0x0", tx_nonce: 1, account_nonce: 0);
+    for input in [&input_nonce_0, &input_nonce_1] {
+        add_tx(&mut mempool, input);
+    }
+    get_txs_and_assert_expected(&mut mempool, 1, &[input_nonce_0.tx.clone()]);
+
+    // Test: the staged transaction failed execution, so the block doesn't advance the account.
+    let reason = String::from("Transaction validation failed.");
+    let args = CommitBlockArgs {
+        address_to_nonce: HashMap::new(),
+        tx_hashes: HashSet::new(),
+        rejected_tx_hashes: HashMap::from([(input_nonce_0.tx.tx_hash(), reason.clone())]),
+        next_l2_gas_price: GasPrice::default(),
+    };
+    assert_eq!(mempool.commit_block(args), Ok(()));
+
+    // Assert: the following transaction of the account is now nonce-gapped.
+    assert_eq!(
+        mempool.get_transaction_status(input_nonce_0.tx.tx_hash()),
+        Ok(TransactionStatus::Evicted(EvictionReason::Rejected { reason }))
+    );
+    assert_eq!(
+        mempool.get_transaction_status(input_nonce_1.tx.tx_hash()),
+        Ok(TransactionStatus::Suspended)
+    );
+}
+
+#[rstest]
+fn test_get_account_transactions(mut mempool: Mempool) {
+    // Setup.
+    let input_nonce_1 = add_tx_input!(tx_hash: 1, address: "0x0", tx_nonce: 1, account_nonce: 1);
+    let input_nonce_3 = add_tx_input!(tx_hash: 2, address: "0x0", tx_nonce: 3, account_nonce: 1);
+    let input_other_account =
+        add_tx_input!(tx_hash: 3, address: "0x1", tx_nonce: 0, account_nonce: 0);
+    for input in [&input_nonce_3, &input_nonce_1, &input_other_account] {
+        add_tx(&mut mempool, input);
+    }
+
+    // Test.
+    let address = contract_address!("0x0");
+    let account_txs = mempool.get_account_transactions(address).unwrap();
+
+    // Assert: transactions are ordered by nonce.
+    let expected_account_txs = AccountTransactions {
+        address,
+        nonce: Some(nonce!(1)),
+        txs: vec![
+            TransactionInfo {
+                tx_hash: input_nonce_1.tx.tx_hash(),
+                nonce: nonce!(1),
+                status: TransactionStatus::Queued,
+            },
+            TransactionInfo {
+                tx_hash: input_nonce_3.tx.tx_hash(),
+                nonce: nonce!(3),
+                status: TransactionStatus::Suspended,
+            },
+        ],
+    };
+    assert_eq!(account_txs, expected_account_txs);
+
+    let unknown_address = contract_address!("0x2");
+    assert_eq!(
+        mempool.get_account_transactions(unknown_address),
+        Ok(AccountTransactions { address: unknown_address, nonce: None, txs: vec![] })
+    );
+}
+
+#[rstest]
+fn test_get_txs_by_hash(mut mempool: Mempool) {
+    // Setup.
+    let input_address_0 = add_tx_input!(tx_hash: 1, address: "0x0", tx_nonce: 0, account_nonce: 0);
+    let input_address_1 = add_tx_input!(tx_hash: 2, address: "0x1", tx_nonce: 0, account_nonce: 0);
+    for input in [&input_address_0, &input_address_1] {
+        add_tx(&mut mempool, input);
+    }
+    let unknown_tx_hash = tx!(tx_hash: 3, address: "0x2", tx_nonce: 0).tx_hash();
+
+    // Test.
+    let txs = mempool
+        .get_txs_by_hash(&[
+            input_address_1.tx.tx_hash(),
+            unknown_tx_hash,
+            input_address_0.tx.tx_hash(),
+        ])
+        .unwrap();
+
+    // Assert: unknown hashes are skipped, and the order of the given hashes is kept.
+    assert_eq!(txs, vec![input_address_1.tx, input_address_0.tx]);
+}
+
+#[rstest]
+fn test_get_pool_stats() {
+    // Setup.
+    let mut mempool =
+        MempoolContentBuilder::new().with_gas_price_threshold(100).build_into_mempool();
+    let input_nonce_0 = add_tx_input!(tx_hash: 1, address: "0x0", tx_nonce: 0, account_nonce: 0);
+    let input_nonce_2 = add_tx_input!(tx_hash: 2, address: "0x0", tx_nonce: 2, account_nonce: 0);
+    let input_low_gas_price = add_tx_input!(tx_hash: 3, address: "0x1", tx_nonce: 0, account_nonce: 0, tip: 0, max_l2_gas_price: 50);
+    for input in [&input_nonce_0, &input_nonce_2, &input_low_gas_price] {
+        add_tx(&mut mempool, input);
+    }
+
+    // Test and assert.
+    let expected_stats = MempoolStats {
+        n_txs: 3,
+        n_queued_txs: 1,
+        n_suspended_txs: 1,
+        n_accounts: 2,
+        gas_price_threshold: GasPrice(100),
+    };
+    assert_eq!(mempool.get_pool_stats(), Ok(expected_stats));
+}
+
+// Persistence tests.
+
+#[rstest]
+#[tokio::test]
+async fn test_snapshot_resubmitted_through_gateway(mut mempool: Mempool) {
+    // Setup.
+    let input_nonce_0 = add_tx_input!(tx_hash: 1, address: "0x0", tx_nonce: 0, account_nonce: 0);
+    let input_nonce_1 = add_tx_input!(tx_hash: 2, address: "0x0", tx_nonce: 1, account_nonce: 0);
+    let input_address_1 = add_tx_input!(tx_hash: 3, address: "0x1", tx_nonce: 0, account_nonce: 0);
+    for input in [&input_nonce_0, &input_nonce_1, &input_address_1] {
+        add_tx(&mut mempool, input);
+    }
+    // Staged transactions are restored as well, since their block was not committed.
+    get_txs_and_assert_expected(&mut mempool, 1, &[input_nonce_0.tx.clone()]);
+    let snapshot_dir = tempfile::tempdir().unwrap();
+    let snapshot_path = snapshot_dir.path().join("mempool_snapshot.json");
+    mempool.snapshot().store(&snapshot_path).unwrap();
+
+    // The transactions are resubmitted in account and nonce order, for the gateway to validate
+    // them against the current state.
+    let mut mock_gateway_client = MockGatewayClient::new();
+    let mut seq = Sequence::new();
+    for input in [&input_nonce_0, &input_nonce_1, &input_address_1] {
+        let AccountTransaction::Invoke(invoke_tx) = input.tx.clone() else {
+            panic!("Expected an invoke transaction");
+        };
+        let tx_hash = invoke_tx.tx_hash;
+        let gateway_input = GatewayInput {
+            rpc_tx: RpcTransaction::Invoke(RpcInvokeTransaction::V3(invoke_tx.into())),
+            message_metadata: None,
+        };
+        mock_gateway_client
+            .expect_add_tx()
+            .with(predicate::eq(gateway_input))
+            .times(1)
+            .in_sequence(&mut seq)
+            .return_once(move |_| Ok(tx_hash));
+    }
+    let mempool_wrapper = MempoolCommunicationWrapper::new(
+        Mempool::default(),
+        Arc::new(MockMempoolP2pPropagatorClient::new()),
+        Arc::new(mock_gateway_client),
+        ShutdownController::new().subscribe(),
+    );
+
+    // Test.
+    mempool_wrapper.restore_snapshot(&snapshot_path).await;
+}
+
+#[rstest]
+#[tokio::test(start_paused = true)]
+async fn test_restored_txs_keep_their_age() {
+    // Setup.
+    let mut mempool = MempoolContentBuilder::new()
+        .with_transaction_ttl(Duration::from_secs(60))
+        .build_into_mempool();
+    let input_old = add_tx_input!(tx_hash: 1, address: "0x0", tx_nonce: 0, account_nonce: 0);
+    add_tx(&mut mempool, &input_old);
+    tokio::time::advance(Duration::from_secs(30)).await;
+    let input_new = add_tx_input!(tx_hash: 2, address: "0x1", tx_nonce: 0, account_nonce: 0);
+    add_tx(&mut mempool, &input_new);
+    let snapshot = mempool.snapshot();
+
+    // Test: once restored, a transaction expires a TTL after its original submission.
+    tokio::time::advance(Duration::from_secs(10)).await;
+    let mut restored_mempool = MempoolContentBuilder::new()
+        .with_transaction_ttl(Duration::from_secs(60))
+        .build_into_mempool();
+    let restored_txs = restored_mempool.prepare_restore(snapshot);
+    assert_eq!(restored_txs, [input_old.tx.clone(), input_new.tx.clone()]);
+    // Resubmitted by the gateway.
+    for input in [&input_old, &input_new] {
+        add_tx(&mut restored_mempool, input);
+    }
+    restored_mempool.finish_restore();
+    tokio::time::advance(Duration::from_secs(31)).await;
+    restored_mempool.remove_expired_txs();
+
+    // Assert.
+    let expected_mempool_content = MempoolContentBuilder::new()
+        .with_pool([input_new.tx.clone()])
+        .with_priority_queue([TransactionReference::new(&input_new.tx)])
+        .build();
+    expected_mempool_content.assert_eq(&restored_mempool);
+}
+
+#[rstest]
+#[tokio::test(start_paused = true)]
+async fn test_mempool_persisted_periodically() {
+    // Setup.
+    let snapshot_dir = tempfile::tempdir().unwrap();
+    let snapshot_path = snapshot_dir.path().join("mempool_snapshot.json");
+    let mut mempool =
+        MempoolContentBuilder::new().with_snapshot_path(snapshot_path.clone()).build_into_mempool();
+    let input_nonce_0 = add_tx_input!(tx_hash: 1, address: "0x0", tx_nonce: 0, account_nonce: 0);
+    let input_nonce_1 = add_tx_input!(tx_hash: 2, address: "0x0", tx_nonce: 1, account_nonce: 0);
+    add_tx(&mut mempool, &input_nonce_0);
+    add_tx(&mut mempool, &input_nonce_1);
+    get_txs_and_assert_expected(&mut mempool, 1, &[input_nonce_0.tx.clone()]);
+    commit_block(&mut mempool, [("0x0", 1)], [1]);
+
+    let mut mock_mempool_p2p_propagator_client = MockMempoolP2pPropagatorClient::new();
+    mock_mempool_p2p_propagator_client.expect_add_transaction().returning(|_| Ok(()));
+    let mut mempool_wrapper = MempoolCommunicationWrapper::new(
+        mempool,
+        Arc::new(mock_mempool_p2p_propagator_client),
+        Arc::new(MockGatewayClient::new()),
+        ShutdownController::new().subscribe(),
+    );
+    let mut maintaining_wrapper = mempool_wrapper.clone();
+    tokio::spawn(async move { maintaining_wrapper.start().await });
+
+    // Test: a transaction added after the mempool started is persisted by a later snapshot.
+    let input_address_1 = add_tx_input!(tx_hash: 3, address: "0x1", tx_nonce: 0, account_nonce: 0);
+    mempool_wrapper
+        .add_tx(AddTransactionArgsWrapper {
+            args: input_address_1.clone(),
+            p2p_message_metadata: None,
+            rpc_declare_tx: None,
+        })
+        .await
+        .unwrap();
+    tokio::time::timeout(Duration::from_secs(2 * 60), async {
+        while MempoolSnapshot::load(&snapshot_path)
+            .unwrap()
+            .map_or(0, |snapshot| snapshot.txs.len())
+            < 2
+        {
+            tokio::time::sleep(Duration::from_secs(1)).await;
+        }
+    })
+    .await
+    .expect("The mempool should be persisted periodically.");
+
+    // Assert: the committed transaction is not persisted.
+    let snapshot = MempoolSnapshot::load(&snapshot_path).unwrap().unwrap();
+    let persisted_txs: Vec<_> =
+        snapshot.txs.into_iter().map(|snapshot_tx| snapshot_tx.tx).collect();
+    assert_eq!(persisted_txs, [input_nonce_1.tx, input_address_1.tx]);
+}
+
+#[rstest]
+#[tokio::test]
+async fn test_mempool_persisted_on_shutdown() {
+    // Setup.
+    let snapshot_dir = tempfile::tempdir().unwrap();
+    let snapshot_path = snapshot_dir.path().join("mempool_snapshot.json");
+    let mempool =
+        MempoolContentBuilder::new().with_snapshot_path(snapshot_path.clone()).build_into_mempool();
+    let mut mock_mempool_p2p_propagator_client = MockMempoolP2pPropagatorClient::new();
+    mock_mempool_p2p_propagator_client.expect_add_transaction().returning(|_| Ok(()));
+    let mut shutdown_controller = ShutdownController::new();
+    let mut mempool_wrapper = MempoolCommunicationWrapper::new(
+        mempool,
+        Arc::new(mock_mempool_p2p_propagator_client),
+        Arc::new(MockGatewayClient::new()),
+        shutdown_controller.subscribe(),
+    );
+    let mut maintaining_wrapper = mempool_wrapper.clone();
+    tokio::spawn(async move { maintaining_wrapper.start().await });
+    let input = add_tx_input!(tx_hash: 1, address: "0x0", tx_nonce: 0, account_nonce: 0);
+    mempool_wrapper
+        .add_tx(AddTransactionArgsWrapper {
+            args: input.clone(),
+            p2p_message_metadata: None,
+            rpc_declare_tx: None,
+        })
+        .await
+        .unwrap();
+
+    // Test.
+    let shutdown_timeout = Duration::from_secs(60);
+    let shutdown_start = tokio::time::Instant::now();
+    shutdown_controller.shutdown(shutdown_timeout).await;
+
+    // Assert: the mempool reported it's done, after persisting its content.
+    assert!(shutdown_start.elapsed() < shutdown_timeout);
+    let snapshot = MempoolSnapshot::load(&snapshot_path).unwrap().unwrap();
+    let persisted_txs: Vec<_> =
+        snapshot.txs.into_iter().map(|snapshot_tx| snapshot_tx.tx).collect();
+    assert_eq!(persisted_txs, [input.tx]);
+}
+
 // `update_gas_price_threshold` tests.
 
 #[rstest]
@@ -749,40 +1652,103 @@ fn test_update_gas_price_threshold_decreases_threshold() {
     expected_mempool_content.assert_eq(&mempool);
 }
 
+#[rstest]
+fn test_commit_block_updates_gas_price_threshold(mut mempool: Mempool) {
+    // Setup.
+    let input_low_gas = add_tx_input!(tx_hash: 1, address: "0x0", tx_nonce: 0, account_nonce: 0, tip: 0, max_l2_gas_price: 89);
+    let input_high_gas = add_tx_input!(tx_hash: 2, address: "0x1", tx_nonce: 0, account_nonce: 0, tip: 0, max_l2_gas_price: 90);
+    add_tx(&mut mempool, &input_low_gas);
+    add_tx(&mut mempool, &input_high_gas);
+
+    // Test.
+    let args = CommitBlockArgs {
+        address_to_nonce: HashMap::new(),
+        tx_hashes: HashSet::new(),
+        rejected_tx_hashes: HashMap::new(),
+        next_l2_gas_price: GasPrice(90),
+    };
+    assert_eq!(mempool.commit_block(args), Ok(()));
+
+    // Assert: the under-priced transaction is held back.
+    let expected_mempool_content = MempoolContentBuilder::new()
+        .with_pending_queue([TransactionReference::new(&input_low_gas.tx)])
+        .with_priority_queue([TransactionReference::new(&input_high_gas.tx)])
+        .build();
+    expected_mempool_content.assert_eq(&mempool);
+}
+
 #[rstest]
 #[tokio::test]
 async fn test_new_tx_sent_to_p2p(mempool: Mempool) {
     // add_tx_input! creates an Invoke Transaction
     let tx_args = add_tx_input!(tx_hash: 1, address: "0x0", tx_nonce: 2, account_nonce: 2);
-    let propagateor_args =
-        AddTransactionArgsWrapper { args: tx_args.clone(), p2p_message_metadata: None };
-    // TODO: use regular conversion once we have a compiler component
-    let rpc_tx = match tx_args.tx {
-        AccountTransaction::Declare(_declare_tx) => {
-            panic!("No implementation for converting DeclareTransaction to an RpcTransaction")
-        }
-        AccountTransaction::DeployAccount(deploy_account_transaction) => {
-            RpcTransaction::DeployAccount(RpcDeployAccountTransaction::V3(
-                deploy_account_transaction.clone().into(),
-            ))
-        }
-        AccountTransaction::Invoke(invoke_transaction) => {
-            RpcTransaction::Invoke(RpcInvokeTransaction::V3(invoke_transaction.clone().into()))
-        }
+    let propagateor_args = AddTransactionArgsWrapper {
+        args: tx_args.clone(),
+        p2p_message_metadata: None,
+        rpc_declare_tx: None,
     };
-
     let mut mock_mempool_p2p_propagator_client = MockMempoolP2pPropagatorClient::new();
     mock_mempool_p2p_propagator_client
         .expect_add_transaction()
         .times(1)
-        .with(predicate::eq(rpc_tx))
+        .with(predicate::eq(tx_args.tx.tx_hash()))
         .returning(|_| Ok(()));
-    let mut mempool_wrapper =
-        MempoolCommunicationWrapper::new(mempool, Arc::new(mock_mempool_p2p_propagator_client));
+    let mut mempool_wrapper = MempoolCommunicationWrapper::new(
+        mempool,
+        Arc::new(mock_mempool_p2p_propagator_client),
+        Arc::new(MockGatewayClient::new()),
+        ShutdownController::new().subscribe(),
+    );
 
     mempool_wrapper.add_tx(propagateor_args).await.unwrap();
 }
 
+#[rstest]
+#[tokio::test]
+async fn test_new_declare_tx_sent_whole_to_p2p(mempool: Mempool) {
+    let class_info = ClassInfo {
+        contract_class: ContractClass::V0(DeprecatedContractClass::default()),
+        sierra_program_length: 0,
+        abi_length: 0,
+        sierra_version: SierraVersion::DEPRECATED,
+    };
+    let tx = executable_declare_tx(
+        declare_tx_args!(
+            tx_hash: tx_hash!(1),
+            sender_address: contract_address!("0x0"),
+            version: TransactionVersion::THREE,
+        ),
+        class_info,
+    );
+    let RpcTransaction::Declare(rpc_declare_tx) =
+        rpc_declare_tx(declare_tx_args!(), SierraContractClass::default())
+    else {
+        panic!("Expected a declare transaction");
+    };
+    let args = AddTransactionArgsWrapper {
+        args: AddTransactionArgs {
+            tx,
+            account_state: AccountState { address: contract_address!("0x0"), nonce: nonce!(0) },
+        },
+        p2p_message_metadata: None,
+        rpc_declare_tx: Some(rpc_declare_tx.clone()),
+    };
+    let mut mock_mempool_p2p_propagator_client = MockMempoolP2pPropagatorClient::new();
+    mock_mempool_p2p_propagator_client
+        .expect_add_declare_transaction()
+        .times(1)
+        .with(predicate::eq(tx_hash!(1)), predicate::eq(rpc_declare_tx))
+        .returning(|_, _| Ok(()));
+    let mut mempool_wrapper = MempoolCommunicationWrapper::new(
+        mempool,
+        Arc::new(mock_mempool_p2p_propagator_client),
+        Arc::new(MockGatewayClient::new()),
+        ShutdownController::new().subscribe(),
+    );
+
+    mempool_wrapper.add_tx(args).await.unwrap();
+}
+
 #[rstest]
 #[tokio::test]
 async fn test_propagated_tx_sent_to_p2p(mempool: Mempool) {
@@ -792,6 +1758,7 @@ async fn test_propagated_tx_sent_to_p2p(mempool: Mempool) {
     let propagated_args = AddTransactionArgsWrapper {
         args: tx_args.clone(),
         p2p_message_metadata: Some(expected_message_metadata.clone()),
+        rpc_declare_tx: None,
     };
 
     let mut mock_mempool_p2p_propagator_client = MockMempoolP2pPropagatorClient::new();
@@ -801,8 +1768,25 @@ async fn test_propagated_tx_sent_to_p2p(mempool: Mempool) {
         .with(predicate::eq(expected_message_metadata.clone()))
         .returning(|_| Ok(()));
 
-    let mut mempool_wrapper =
-        MempoolCommunicationWrapper::new(mempool, Arc::new(mock_mempool_p2p_propagator_client));
+    let mut mempool_wrapper = MempoolCommunicationWrapper::new(
+        mempool,
+        Arc::new(mock_mempool_p2p_propagator_client),
+        Arc::new(MockGatewayClient::new()),
+        ShutdownController::new().subscribe(),
+    );
 
     mempool_wrapper.add_tx(propagated_args).await.unwrap();
 }
+
+#[test]
+fn zero_expiry_sweep_interval_is_invalid() {
+    let config = MempoolConfig { expiry_sweep_interval: Duration::ZERO, ..Default::default() };
+    assert!(config.validate().is_err());
+    assert!(MempoolConfig::default().validate().is_ok());
+}
+
+#[test]
+fn zero_snapshot_interval_is_invalid() {
+    let config = MempoolConfig { snapshot_interval: Duration::ZERO, ..Default::default() };
+    assert!(config.validate().is_err());
+}
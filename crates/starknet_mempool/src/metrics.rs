@@ -0,0 +1,2 @@
+/// The number of transactions removed from the mempool after exceeding their time-to-live.
+pub const MEMPOOL_EXPIRED_TXS: &str = "mempool_expired_txs";
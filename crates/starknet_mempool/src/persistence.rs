@@ -0,0 +1,35 @@
+use std::io;
+use std::path::Path;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use starknet_api::executable_transaction::AccountTransaction;
+use starknet_sequencer_infra::file_utils::{read_json_file, write_json_file_atomically};
+
+/// The content of the mempool required to rebuild it after a restart: the transactions it holds,
+/// sorted by account and nonce. Account nonces aren't kept, since restored transactions are
+/// validated against the state at the time of the restart.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct MempoolSnapshot {
+    pub txs: Vec<SnapshotTransaction>,
+}
+
+/// A transaction held by the mempool, along with the time it was held for when the snapshot was
+/// taken; restoring it with its age keeps its TTL from being reset on every restart.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct SnapshotTransaction {
+    pub tx: AccountTransaction,
+    pub age: Duration,
+}
+
+impl MempoolSnapshot {
+    /// Reads a snapshot from the given file; returns `None` if the file does not exist.
+    pub fn load(path: &Path) -> io::Result<Option<Self>> {
+        read_json_file(path)
+    }
+
+    /// Writes the snapshot to the given file, such that a crash mid-write does not corrupt it.
+    pub fn store(&self, path: &Path) -> io::Result<()> {
+        write_json_file_atomically(path, self)
+    }
+}
@@ -1,30 +1,80 @@
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 
 use starknet_api::core::{ContractAddress, Nonce};
 
 use crate::mempool::TransactionReference;
 
-type _AddressNonceToTransaction = HashMap<(ContractAddress, Nonce), TransactionReference>;
+type AddressToSuspendedTransactions =
+    HashMap<ContractAddress, BTreeMap<Nonce, TransactionReference>>;
 
-#[derive(Debug)]
-pub struct _SuspendedTransactionPool {
-    suspended_tx_pool: _AddressNonceToTransaction,
+/// Holds references to the transactions that cannot be sequenced yet due to a nonce gap, i.e.,
+/// transactions not reachable from their account nonce through a sequence of consecutive nonces.
+/// The transactions themselves remain in the transaction pool.
+#[derive(Debug, Default, Eq, PartialEq)]
+pub struct SuspendedTransactionPool {
+    suspended_tx_pool: AddressToSuspendedTransactions,
+    n_txs: usize,
 }
 
-impl _SuspendedTransactionPool {
-    pub fn _contains(&self, address: ContractAddress, nonce: Nonce) -> bool {
-        self.suspended_tx_pool.contains_key(&(address, nonce))
+impl SuspendedTransactionPool {
+    pub fn contains(&self, address: ContractAddress, nonce: Nonce) -> bool {
+        self.suspended_tx_pool
+            .get(&address)
+            .is_some_and(|account_txs| account_txs.contains_key(&nonce))
     }
 
-    pub fn _insert(&mut self, tx: TransactionReference) {
+    pub fn insert(&mut self, tx: TransactionReference) {
         assert_eq!(
-            self.suspended_tx_pool.insert((tx.address, tx.nonce), tx),
+            self.suspended_tx_pool.entry(tx.address).or_default().insert(tx.nonce, tx),
             None,
             "Keys should be unique; duplicates are checked prior."
         );
+        self.n_txs += 1;
     }
 
-    pub fn _remove(&mut self, tx: &TransactionReference) -> bool {
-        self.suspended_tx_pool.remove(&(tx.address, tx.nonce)).is_some()
+    pub fn remove(&mut self, address: ContractAddress, nonce: Nonce) -> bool {
+        let Some(account_txs) = self.suspended_tx_pool.get_mut(&address) else {
+            return false;
+        };
+
+        let removed = account_txs.remove(&nonce).is_some();
+        if account_txs.is_empty() {
+            self.suspended_tx_pool.remove(&address);
+        }
+        if removed {
+            self.n_txs -= 1;
+        }
+
+        removed
+    }
+
+    /// Removes all suspended transactions of the given account with a nonce lower than the given
+    /// one.
+    pub fn remove_up_to_nonce(&mut self, address: ContractAddress, nonce: Nonce) {
+        let Some(account_txs) = self.suspended_tx_pool.get_mut(&address) else {
+            return;
+        };
+
+        let txs_with_higher_or_equal_nonce = account_txs.split_off(&nonce);
+        let txs_with_lower_nonce = std::mem::replace(account_txs, txs_with_higher_or_equal_nonce);
+        if account_txs.is_empty() {
+            self.suspended_tx_pool.remove(&address);
+        }
+
+        self.n_txs -= txs_with_lower_nonce.len();
+    }
+
+    pub fn remove_account(&mut self, address: ContractAddress) {
+        if let Some(account_txs) = self.suspended_tx_pool.remove(&address) {
+            self.n_txs -= account_txs.len();
+        }
+    }
+
+    pub fn n_txs(&self) -> usize {
+        self.n_txs
+    }
+
+    pub fn n_account_txs(&self, address: ContractAddress) -> usize {
+        self.suspended_tx_pool.get(&address).map_or(0, |account_txs| account_txs.len())
     }
 }
@@ -1,10 +1,11 @@
 use std::collections::{HashMap, HashSet};
 
 use pretty_assertions::assert_eq;
+use starknet_api::block::GasPrice;
 use starknet_api::executable_transaction::AccountTransaction;
 use starknet_api::{contract_address, nonce, tx_hash};
 use starknet_mempool_types::errors::MempoolError;
-use starknet_mempool_types::mempool_types::{AddTransactionArgs, CommitBlockArgs};
+use starknet_mempool_types::mempool_types::{AddTransactionArgs, CommitBlockArgs, RewindBlockArgs};
 
 use crate::mempool::Mempool;
 
@@ -239,11 +240,30 @@ pub fn commit_block(
         nonces.into_iter().map(|(address, nonce)| (contract_address!(address), nonce!(nonce))),
     );
     let tx_hashes = HashSet::from_iter(tx_hashes.into_iter().map(|tx_hash| tx_hash!(tx_hash)));
-    let args = CommitBlockArgs { address_to_nonce: nonces, tx_hashes };
+    let args = CommitBlockArgs {
+        address_to_nonce: nonces,
+        tx_hashes,
+        rejected_tx_hashes: HashMap::new(),
+        next_l2_gas_price: GasPrice::default(),
+    };
 
     assert_eq!(mempool.commit_block(args), Ok(()));
 }
 
+#[track_caller]
+pub fn rewind_block(
+    mempool: &mut Mempool,
+    nonces: impl IntoIterator<Item = (&'static str, u8)>,
+    txs: impl IntoIterator<Item = AccountTransaction>,
+) {
+    let nonces = HashMap::from_iter(
+        nonces.into_iter().map(|(address, nonce)| (contract_address!(address), nonce!(nonce))),
+    );
+    let args = RewindBlockArgs { address_to_nonce: nonces, txs: txs.into_iter().collect() };
+
+    assert_eq!(mempool.rewind_block(args), Ok(()));
+}
+
 #[track_caller]
 pub fn get_txs_and_assert_expected(
     mempool: &mut Mempool,
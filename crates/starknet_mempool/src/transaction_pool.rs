@@ -1,4 +1,6 @@
-use std::collections::{hash_map, BTreeMap, HashMap};
+use std::cmp::Ordering;
+use std::collections::{hash_map, BTreeMap, BTreeSet, HashMap};
+use std::time::Instant;
 
 use starknet_api::core::{ContractAddress, Nonce};
 use starknet_api::executable_transaction::AccountTransaction;
@@ -7,7 +9,7 @@ use starknet_mempool_types::errors::MempoolError;
 use starknet_mempool_types::mempool_types::{AccountState, MempoolResult};
 
 use crate::mempool::TransactionReference;
-use crate::utils::try_increment_nonce;
+use crate::utils::{get_instant_now, try_increment_nonce};
 
 type HashToTransaction = HashMap<TransactionHash, AccountTransaction>;
 
@@ -21,12 +23,24 @@ pub struct TransactionPool {
     tx_pool: HashToTransaction,
     // Transactions organized by account address, sorted by ascending nonce values.
     txs_by_account: AccountTransactionIndex,
+    // Transactions organized by submission time, oldest first.
+    txs_by_submission_time: TimedTransactionIndex,
+    // The highest-nonce transaction of each account, sorted by eviction priority.
+    eviction_candidates: BTreeSet<EvictionCandidate>,
     // Tracks the capacity of the pool.
     capacity: PoolCapacity,
 }
 
 impl TransactionPool {
     pub fn insert(&mut self, tx: AccountTransaction) -> MempoolResult<()> {
+        self.insert_submitted_at(tx, get_instant_now())
+    }
+
+    pub fn insert_submitted_at(
+        &mut self,
+        tx: AccountTransaction,
+        submission_time: Instant,
+    ) -> MempoolResult<()> {
         let tx_reference = TransactionReference::new(&tx);
         let tx_hash = tx_reference.tx_hash;
 
@@ -38,6 +52,7 @@ impl TransactionPool {
         }
 
         // Insert to account mapping.
+        let last_account_tx = self.txs_by_account.last(tx_reference.address);
         let unexpected_existing_tx = self.txs_by_account.insert(tx_reference);
         if unexpected_existing_tx.is_some() {
             panic!(
@@ -46,7 +61,9 @@ impl TransactionPool {
                 mapping",
             )
         };
+        self.update_eviction_candidate(tx_reference.address, last_account_tx);
 
+        self.txs_by_submission_time.insert(tx_hash, submission_time);
         self.capacity.add();
 
         Ok(())
@@ -58,20 +75,26 @@ impl TransactionPool {
             self.tx_pool.remove(&tx_hash).ok_or(MempoolError::TransactionNotFound { tx_hash })?;
 
         // Remove from account mapping.
-        self.txs_by_account.remove(TransactionReference::new(&tx)).unwrap_or_else(|| {
+        let tx_reference = TransactionReference::new(&tx);
+        let last_account_tx = self.txs_by_account.last(tx_reference.address);
+        self.txs_by_account.remove(tx_reference).unwrap_or_else(|| {
             panic!(
                 "Transaction pool consistency error: transaction with hash {tx_hash} appears in
                 main mapping, but does not appear in the account mapping"
             )
         });
+        self.update_eviction_candidate(tx_reference.address, last_account_tx);
 
+        self.txs_by_submission_time.remove(tx_hash);
         self.capacity.remove();
 
         Ok(tx)
     }
 
     pub fn remove_up_to_nonce(&mut self, address: ContractAddress, nonce: Nonce) {
+        let last_account_tx = self.txs_by_account.last(address);
         let removed_txs = self.txs_by_account.remove_up_to_nonce(address, nonce);
+        self.update_eviction_candidate(address, last_account_tx);
 
         for TransactionReference { tx_hash, .. } in removed_txs {
             self.tx_pool.remove(&tx_hash).unwrap_or_else(|| {
@@ -81,6 +104,7 @@ impl TransactionPool {
                 );
             });
 
+            self.txs_by_submission_time.remove(tx_hash);
             self.capacity.remove();
         }
     }
@@ -116,6 +140,69 @@ impl TransactionPool {
     pub fn _contains_account(&self, address: ContractAddress) -> bool {
         self.txs_by_account._contains(address)
     }
+
+    pub fn n_txs(&self) -> usize {
+        self.capacity.n_txs
+    }
+
+    pub fn n_account_txs(&self, address: ContractAddress) -> usize {
+        self.txs_by_account.n_account_txs(address)
+    }
+
+    pub fn n_accounts(&self) -> usize {
+        self.txs_by_account.0.len()
+    }
+
+    pub fn iter_tx_references(&self) -> impl Iterator<Item = TransactionReference> + '_ {
+        self.txs_by_account.0.values().flat_map(|account_txs| account_txs.values().copied())
+    }
+
+    pub fn last_account_tx(&self, address: ContractAddress) -> Option<TransactionReference> {
+        self.txs_by_account.last(address)
+    }
+
+    pub fn get_submission_time(&self, tx_hash: TransactionHash) -> Option<Instant> {
+        self.txs_by_submission_time.hash_to_submission_time.get(&tx_hash).copied()
+    }
+
+    /// Returns the transactions submitted before the given time, oldest first.
+    pub fn get_submitted_before(&self, submission_time: Instant) -> Vec<TransactionReference> {
+        self.txs_by_submission_time
+            .iter_submitted_before(submission_time)
+            .map(|tx_hash| {
+                let tx = self
+                    .tx_pool
+                    .get(&tx_hash)
+                    .expect("Transaction hash from submission time index must appear in pool.");
+                TransactionReference::new(tx)
+            })
+            .collect()
+    }
+
+    /// Returns an iterator over the highest-nonce transaction of each account, ordered from the
+    /// first to be evicted (lowest tip, then lowest max L2 gas price) to the last.
+    pub fn iter_eviction_candidates(&self) -> impl Iterator<Item = &TransactionReference> {
+        self.eviction_candidates.iter().map(|candidate| &candidate.0)
+    }
+
+    /// Re-aligns the eviction candidate of the given account after its transactions changed.
+    fn update_eviction_candidate(
+        &mut self,
+        address: ContractAddress,
+        previous_last_tx: Option<TransactionReference>,
+    ) {
+        let current_last_tx = self.txs_by_account.last(address);
+        if previous_last_tx == current_last_tx {
+            return;
+        }
+
+        if let Some(previous_last_tx) = previous_last_tx {
+            self.eviction_candidates.remove(&previous_last_tx.into());
+        }
+        if let Some(current_last_tx) = current_last_tx {
+            self.eviction_candidates.insert(current_last_tx.into());
+        }
+    }
 }
 
 #[derive(Debug, Default, Eq, PartialEq)]
@@ -144,6 +231,15 @@ impl AccountTransactionIndex {
         self.0.get(&address)?.get(&nonce).copied()
     }
 
+    /// Returns the transaction with the highest nonce of the given account, if any.
+    fn last(&self, address: ContractAddress) -> Option<TransactionReference> {
+        self.0.get(&address)?.last_key_value().map(|(_, tx)| *tx)
+    }
+
+    fn n_account_txs(&self, address: ContractAddress) -> usize {
+        self.0.get(&address).map_or(0, |account_txs| account_txs.len())
+    }
+
     fn account_txs_sorted_by_nonce(
         &self,
         address: ContractAddress,
@@ -177,6 +273,53 @@ impl AccountTransactionIndex {
     }
 }
 
+#[derive(Debug, Default)]
+struct TimedTransactionIndex {
+    hash_to_submission_time: HashMap<TransactionHash, Instant>,
+    submission_time_to_hash: BTreeSet<(Instant, TransactionHash)>,
+}
+
+impl TimedTransactionIndex {
+    fn insert(&mut self, tx_hash: TransactionHash, submission_time: Instant) {
+        assert_eq!(
+            self.hash_to_submission_time.insert(tx_hash, submission_time),
+            None,
+            "Keys should be unique; duplicates are checked prior."
+        );
+        self.submission_time_to_hash.insert((submission_time, tx_hash));
+    }
+
+    fn remove(&mut self, tx_hash: TransactionHash) {
+        if let Some(submission_time) = self.hash_to_submission_time.remove(&tx_hash) {
+            self.submission_time_to_hash.remove(&(submission_time, tx_hash));
+        }
+    }
+
+    fn iter_submitted_before(
+        &self,
+        submission_time: Instant,
+    ) -> impl Iterator<Item = TransactionHash> + '_ {
+        self.submission_time_to_hash
+            .iter()
+            .take_while(move |(tx_submission_time, _)| *tx_submission_time < submission_time)
+            .map(|(_, tx_hash)| *tx_hash)
+    }
+}
+
+/// Submission times are not compared, since they depend on when the transactions were inserted;
+/// only the set of indexed transactions is.
+impl PartialEq for TimedTransactionIndex {
+    fn eq(&self, other: &Self) -> bool {
+        self.hash_to_submission_time.len() == other.hash_to_submission_time.len()
+            && self
+                .hash_to_submission_time
+                .keys()
+                .all(|tx_hash| other.hash_to_submission_time.contains_key(tx_hash))
+    }
+}
+
+impl Eq for TimedTransactionIndex {}
+
 #[derive(Debug, Default, Eq, PartialEq)]
 pub struct PoolCapacity {
     n_txs: usize,
@@ -193,3 +336,31 @@ impl PoolCapacity {
             self.n_txs.checked_sub(1).expect("Underflow: Cannot subtract from an empty pool.");
     }
 }
+
+/// Encapsulates a transaction reference to assess its eviction order: transactions with a lower tip
+/// are evicted first, ties are broken by the maximal L2 gas price and then by hash.
+#[derive(Clone, Copy, Debug, derive_more::Deref, derive_more::From)]
+struct EvictionCandidate(pub TransactionReference);
+
+impl PartialEq for EvictionCandidate {
+    fn eq(&self, other: &EvictionCandidate) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for EvictionCandidate {}
+
+impl Ord for EvictionCandidate {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.tip
+            .cmp(&other.tip)
+            .then_with(|| self.max_l2_gas_price.cmp(&other.max_l2_gas_price))
+            .then_with(|| self.tx_hash.cmp(&other.tx_hash))
+    }
+}
+
+impl PartialOrd for EvictionCandidate {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
@@ -66,6 +66,11 @@ impl TransactionQueue {
         self.priority_queue.iter().rev().map(|tx| &tx.0)
     }
 
+    /// Returns the queued transaction of the given account, if it is eligible for sequencing.
+    pub fn get_ready_tx(&self, address: ContractAddress) -> Option<TransactionReference> {
+        self.address_to_tx.get(&address).filter(|tx_reference| self.is_ready(tx_reference)).copied()
+    }
+
     pub fn get_nonce(&self, address: ContractAddress) -> Option<Nonce> {
         self.address_to_tx.get(&address).map(|tx| tx.nonce)
     }
@@ -85,6 +90,19 @@ impl TransactionQueue {
         !self.priority_queue.is_empty()
     }
 
+    /// Returns whether the given transaction is queued and eligible for sequencing.
+    pub fn is_ready(&self, tx_reference: &TransactionReference) -> bool {
+        self.priority_queue.contains(&(*tx_reference).into())
+    }
+
+    pub fn n_ready_txs(&self) -> usize {
+        self.priority_queue.len()
+    }
+
+    pub fn gas_price_threshold(&self) -> GasPrice {
+        self.gas_price_threshold
+    }
+
     pub fn update_gas_price_threshold(&mut self, threshold: GasPrice) {
         match threshold.cmp(&self.gas_price_threshold) {
             Ordering::Less => self.promote_txs_to_priority(threshold),
@@ -1,3 +1,5 @@
+use std::time::Instant;
+
 use starknet_api::core::Nonce;
 use starknet_mempool_types::communication::MempoolResult;
 use starknet_mempool_types::errors::MempoolError;
@@ -5,3 +7,14 @@ use starknet_mempool_types::errors::MempoolError;
 pub fn try_increment_nonce(nonce: Nonce) -> MempoolResult<Nonce> {
     nonce.try_increment().map_err(|_| MempoolError::NonceTooLarge(nonce))
 }
+
+#[cfg(not(test))]
+pub fn get_instant_now() -> Instant {
+    Instant::now()
+}
+
+// In tests we simulate time passing using tokio, so we need to use tokio's Instant instead of std.
+#[cfg(test)]
+pub fn get_instant_now() -> Instant {
+    tokio::time::Instant::now().into_std()
+}
@@ -8,6 +8,7 @@ use starknet_mempool::test_utils::{
     add_tx_expect_error,
     commit_block,
     get_txs_and_assert_expected,
+    rewind_block,
 };
 use starknet_mempool_types::errors::MempoolError;
 
@@ -313,3 +314,34 @@ fn test_update_gas_price_threshold(mut mempool: Mempool) {
     mempool.update_gas_price_threshold(GasPrice(10));
     get_txs_and_assert_expected(&mut mempool, 2, &[input_gas_price_20.tx]);
 }
+
+#[rstest]
+fn test_rewind_block_after_commit(mut mempool: Mempool) {
+    // Setup.
+    let input_nonce_0 = add_tx_input!(tx_hash: 1, address: "0x0", tx_nonce: 0, account_nonce: 0);
+    let input_nonce_1 = add_tx_input!(tx_hash: 2, address: "0x0", tx_nonce: 1, account_nonce: 0);
+    let input_nonce_2 = add_tx_input!(tx_hash: 3, address: "0x0", tx_nonce: 2, account_nonce: 0);
+    for input in [&input_nonce_0, &input_nonce_1, &input_nonce_2] {
+        add_tx(&mut mempool, input);
+    }
+    get_txs_and_assert_expected(
+        &mut mempool,
+        2,
+        &[input_nonce_0.tx.clone(), input_nonce_1.tx.clone()],
+    );
+    commit_block(&mut mempool, [("0x0", 2)], [1, 2]);
+
+    // Test: the block is reverted.
+    rewind_block(&mut mempool, [("0x0", 0)], [input_nonce_0.tx.clone(), input_nonce_1.tx.clone()]);
+
+    // Assert: reverted transactions are returned again, followed by the pending one.
+    get_txs_and_assert_expected(
+        &mut mempool,
+        3,
+        &[input_nonce_0.tx, input_nonce_1.tx.clone(), input_nonce_2.tx.clone()],
+    );
+
+    // Test and assert: a lower nonce can be committed after the rewind.
+    commit_block(&mut mempool, [("0x0", 1)], [1]);
+    get_txs_and_assert_expected(&mut mempool, 2, &[input_nonce_1.tx, input_nonce_2.tx]);
+}
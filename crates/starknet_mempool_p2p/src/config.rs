@@ -11,23 +11,58 @@ pub struct MempoolP2pConfig {
     #[validate]
     pub network_config: NetworkConfig,
     pub network_buffer_size: usize,
+    // TODO: validate that transaction_hashes_cache_size is a positive integer.
+    pub transaction_hashes_cache_size: usize,
+    // TODO: validate that max_transactions_per_query is a positive integer.
+    pub max_transactions_per_query: usize,
+    // TODO: validate that declare_transactions_store_size is a positive integer.
+    pub declare_transactions_store_size: usize,
 }
 
 impl Default for MempoolP2pConfig {
     fn default() -> Self {
-        Self { network_config: NetworkConfig::default(), network_buffer_size: 10000 }
+        Self {
+            network_config: NetworkConfig::default(),
+            network_buffer_size: 10000,
+            transaction_hashes_cache_size: 100000,
+            max_transactions_per_query: 1000,
+            declare_transactions_store_size: 1000,
+        }
     }
 }
 
 impl SerializeConfig for MempoolP2pConfig {
     fn dump(&self) -> BTreeMap<ParamPath, SerializedParam> {
         vec![
-            BTreeMap::from_iter([ser_param(
-                "network_buffer_size",
-                &self.network_buffer_size,
-                "Network buffer size.",
-                ParamPrivacyInput::Public,
-            )]),
+            BTreeMap::from_iter([
+                ser_param(
+                    "network_buffer_size",
+                    &self.network_buffer_size,
+                    "Network buffer size.",
+                    ParamPrivacyInput::Public,
+                ),
+                ser_param(
+                    "transaction_hashes_cache_size",
+                    &self.transaction_hashes_cache_size,
+                    "The number of recently announced transaction hashes to remember in order not \
+                     to request them again. Must be a positive integer.",
+                    ParamPrivacyInput::Public,
+                ),
+                ser_param(
+                    "max_transactions_per_query",
+                    &self.max_transactions_per_query,
+                    "The maximal number of transactions to request or serve in a single query. \
+                     Peers that request more are reported. Must be a positive integer.",
+                    ParamPrivacyInput::Public,
+                ),
+                ser_param(
+                    "declare_transactions_store_size",
+                    &self.declare_transactions_store_size,
+                    "The number of recently added declare transactions to keep in order to serve \
+                     them to peers. Must be a positive integer.",
+                    ParamPrivacyInput::Public,
+                ),
+            ]),
             append_sub_config_name(self.network_config.dump(), "network_config"),
         ]
         .into_iter()
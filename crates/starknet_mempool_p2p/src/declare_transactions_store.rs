@@ -0,0 +1,31 @@
+use std::num::NonZeroUsize;
+use std::sync::{Arc, Mutex};
+
+use lru::LruCache;
+use starknet_api::rpc_transaction::RpcDeclareTransaction;
+use starknet_api::transaction::TransactionHash;
+
+/// The declare transactions recently added by the node's users, kept in order to serve them to the
+/// peers they're announced to. The mempool can't serve them, since it doesn't hold their Sierra
+/// class. Shared between the propagator, which adds them, and the runner, which serves them.
+#[derive(Clone)]
+pub struct DeclareTransactionsStore(Arc<Mutex<LruCache<TransactionHash, RpcDeclareTransaction>>>);
+
+impl DeclareTransactionsStore {
+    pub fn new(capacity: NonZeroUsize) -> Self {
+        Self(Arc::new(Mutex::new(LruCache::new(capacity))))
+    }
+
+    pub fn insert(&self, tx_hash: TransactionHash, declare_tx: RpcDeclareTransaction) {
+        self.0
+            .lock()
+            .expect("Declare transactions store lock is poisoned.")
+            .put(tx_hash, declare_tx);
+    }
+
+    /// Returns the stored transactions among the given ones, in order.
+    pub fn get(&self, tx_hashes: &[TransactionHash]) -> Vec<RpcDeclareTransaction> {
+        let mut store = self.0.lock().expect("Declare transactions store lock is poisoned.");
+        tx_hashes.iter().filter_map(|tx_hash| store.get(tx_hash).cloned()).collect()
+    }
+}
@@ -1,21 +1,38 @@
 pub mod config;
+pub mod declare_transactions_store;
 pub mod propagator;
 pub mod runner;
 
+use std::num::NonZeroUsize;
+
 use papyrus_network::gossipsub_impl::Topic;
 use papyrus_network::network_manager::{BroadcastTopicChannels, NetworkManager};
+use papyrus_protobuf::mempool::{MempoolTransactionsQuery, RpcTransactionWrapper};
+use papyrus_protobuf::sync::DataOrFin;
 use starknet_gateway_types::communication::SharedGatewayClient;
+use starknet_mempool_types::communication::SharedMempoolClient;
 
 use crate::config::MempoolP2pConfig;
+use crate::declare_transactions_store::DeclareTransactionsStore;
 use crate::propagator::MempoolP2pPropagator;
 use crate::runner::MempoolP2pRunner;
 
-pub const MEMPOOL_TOPIC: &str = "starknet_mempool_transaction_propagation/0.1.0";
+/// The topic on which the hashes of new transactions are announced.
+pub const MEMPOOL_TOPIC: &str = "starknet_mempool_transaction_propagation/0.2.0";
+/// The protocol on which peers request the announced transactions they don't know.
+pub const MEMPOOL_TRANSACTIONS_PROTOCOL: &str = "/starknet/mempool_transactions/0.1.0";
+/// The protocol on which peers request the announced transactions that weren't served over
+/// [`MEMPOOL_TRANSACTIONS_PROTOCOL`], which may be declare transactions. The mempool can't serve
+/// those, so they're served from a [`DeclareTransactionsStore`].
+pub const MEMPOOL_DECLARE_TRANSACTIONS_PROTOCOL: &str =
+    "/starknet/mempool_declare_transactions/0.1.0";
 
 pub fn create_p2p_propagator_and_runner(
     mempool_p2p_config: MempoolP2pConfig,
     gateway_client: SharedGatewayClient,
+    mempool_client: SharedMempoolClient,
 ) -> (MempoolP2pPropagator, MempoolP2pRunner) {
+    let chain_id = mempool_p2p_config.network_config.chain_id.clone();
     let mut network_manager = NetworkManager::new(
         mempool_p2p_config.network_config,
         // TODO: Consider filling this once the sequencer node has a name.
@@ -28,12 +45,50 @@ pub fn create_p2p_propagator_and_runner(
                 mempool_p2p_config.network_buffer_size,
             )
             .expect("Failed to register broadcast topic");
-    let mempool_p2p_propagator = MempoolP2pPropagator::new(broadcast_topic_client.clone());
+    let transactions_client = network_manager
+        .register_sqmr_protocol_client::<MempoolTransactionsQuery, DataOrFin<RpcTransactionWrapper>>(
+            MEMPOOL_TRANSACTIONS_PROTOCOL.to_string(),
+            mempool_p2p_config.network_buffer_size,
+        );
+    let transactions_server = network_manager
+        .register_sqmr_protocol_server::<MempoolTransactionsQuery, DataOrFin<RpcTransactionWrapper>>(
+            MEMPOOL_TRANSACTIONS_PROTOCOL.to_string(),
+            mempool_p2p_config.network_buffer_size,
+        );
+    let declare_transactions_client = network_manager
+        .register_sqmr_protocol_client::<MempoolTransactionsQuery, DataOrFin<RpcTransactionWrapper>>(
+            MEMPOOL_DECLARE_TRANSACTIONS_PROTOCOL.to_string(),
+            mempool_p2p_config.network_buffer_size,
+        );
+    let declare_transactions_server = network_manager
+        .register_sqmr_protocol_server::<MempoolTransactionsQuery, DataOrFin<RpcTransactionWrapper>>(
+            MEMPOOL_DECLARE_TRANSACTIONS_PROTOCOL.to_string(),
+            mempool_p2p_config.network_buffer_size,
+        );
+    let declare_transactions_store = DeclareTransactionsStore::new(
+        NonZeroUsize::new(mempool_p2p_config.declare_transactions_store_size)
+            .expect("declare_transactions_store_size should be a positive integer."),
+    );
+    let mempool_p2p_propagator = MempoolP2pPropagator::new(
+        broadcast_topic_client.clone(),
+        declare_transactions_store.clone(),
+    );
     let mempool_p2p_runner = MempoolP2pRunner::new(
         Some(network_manager),
         broadcasted_messages_receiver,
         broadcast_topic_client,
+        transactions_client,
+        transactions_server,
+        declare_transactions_client,
+        declare_transactions_server,
+        declare_transactions_store,
         gateway_client,
+        mempool_client,
+        chain_id,
+        NonZeroUsize::new(mempool_p2p_config.transaction_hashes_cache_size)
+            .expect("transaction_hashes_cache_size should be a positive integer."),
+        NonZeroUsize::new(mempool_p2p_config.max_transactions_per_query)
+            .expect("max_transactions_per_query should be a positive integer."),
     );
     (mempool_p2p_propagator, mempool_p2p_runner)
 }
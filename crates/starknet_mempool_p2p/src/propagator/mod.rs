@@ -3,7 +3,7 @@ mod test;
 
 use async_trait::async_trait;
 use papyrus_network::network_manager::{BroadcastTopicClient, BroadcastTopicClientTrait};
-use papyrus_protobuf::mempool::RpcTransactionWrapper;
+use papyrus_protobuf::mempool::NewTransactionHashes;
 use starknet_mempool_p2p_types::communication::{
     MempoolP2pPropagatorRequest,
     MempoolP2pPropagatorResponse,
@@ -12,13 +12,19 @@ use starknet_mempool_p2p_types::errors::MempoolP2pPropagatorError;
 use starknet_sequencer_infra::component_definitions::{ComponentRequestHandler, ComponentStarter};
 use starknet_sequencer_infra::component_server::{LocalComponentServer, RemoteComponentServer};
 
+use crate::declare_transactions_store::DeclareTransactionsStore;
+
 pub struct MempoolP2pPropagator {
-    broadcast_topic_client: BroadcastTopicClient<RpcTransactionWrapper>,
+    broadcast_topic_client: BroadcastTopicClient<NewTransactionHashes>,
+    declare_transactions_store: DeclareTransactionsStore,
 }
 
 impl MempoolP2pPropagator {
-    pub fn new(broadcast_topic_client: BroadcastTopicClient<RpcTransactionWrapper>) -> Self {
-        Self { broadcast_topic_client }
+    pub fn new(
+        broadcast_topic_client: BroadcastTopicClient<NewTransactionHashes>,
+        declare_transactions_store: DeclareTransactionsStore,
+    ) -> Self {
+        Self { broadcast_topic_client, declare_transactions_store }
     }
 }
 
@@ -31,14 +37,26 @@ impl ComponentRequestHandler<MempoolP2pPropagatorRequest, MempoolP2pPropagatorRe
         request: MempoolP2pPropagatorRequest,
     ) -> MempoolP2pPropagatorResponse {
         match request {
-            MempoolP2pPropagatorRequest::AddTransaction(transaction) => {
+            MempoolP2pPropagatorRequest::AddTransaction(tx_hash) => {
+                // TODO: Batch the hashes of transactions added in a short period of time into a
+                // single announcement.
                 let result = self
                     .broadcast_topic_client
-                    .broadcast_message(RpcTransactionWrapper(transaction))
+                    .broadcast_message(NewTransactionHashes(vec![tx_hash]))
                     .await
                     .map_err(|_| MempoolP2pPropagatorError::NetworkSendError);
                 MempoolP2pPropagatorResponse::AddTransaction(result)
             }
+            MempoolP2pPropagatorRequest::AddDeclareTransaction(tx_hash, declare_tx) => {
+                // Stored before the announcement, so that it can be served to whoever requests it.
+                self.declare_transactions_store.insert(tx_hash, declare_tx);
+                let result = self
+                    .broadcast_topic_client
+                    .broadcast_message(NewTransactionHashes(vec![tx_hash]))
+                    .await
+                    .map_err(|_| MempoolP2pPropagatorError::NetworkSendError);
+                MempoolP2pPropagatorResponse::AddDeclareTransaction(result)
+            }
             MempoolP2pPropagatorRequest::ContinuePropagation(propagation_manager) => {
                 let result = self
                     .broadcast_topic_client
@@ -1,3 +1,5 @@
+use std::num::NonZeroUsize;
+
 use futures::stream::StreamExt;
 use papyrus_network::network_manager::test_utils::{
     mock_register_broadcast_topic,
@@ -6,17 +8,26 @@ use papyrus_network::network_manager::test_utils::{
 };
 use papyrus_network::network_manager::BroadcastTopicChannels;
 use papyrus_network_types::network_types::BroadcastedMessageMetadata;
-use papyrus_protobuf::mempool::RpcTransactionWrapper;
+use papyrus_protobuf::mempool::NewTransactionHashes;
 use papyrus_test_utils::{get_rng, GetTestInstance};
+use starknet_api::declare_tx_args;
 use starknet_api::rpc_transaction::RpcTransaction;
+use starknet_api::state::SierraContractClass;
+use starknet_api::test_utils::declare::rpc_declare_tx;
+use starknet_api::transaction::TransactionHash;
 use starknet_mempool_p2p_types::communication::MempoolP2pPropagatorRequest;
 use starknet_sequencer_infra::component_definitions::ComponentRequestHandler;
 use tokio::time::timeout;
 
 use super::MempoolP2pPropagator;
+use crate::declare_transactions_store::DeclareTransactionsStore;
 
 const TIMEOUT: std::time::Duration = std::time::Duration::from_secs(1);
 
+fn declare_transactions_store() -> DeclareTransactionsStore {
+    DeclareTransactionsStore::new(NonZeroUsize::new(1).unwrap())
+}
+
 #[tokio::test]
 async fn process_handle_add_tx() {
     let TestSubscriberChannels { mock_network, subscriber_channels } =
@@ -24,13 +35,42 @@ async fn process_handle_add_tx() {
     let BroadcastTopicChannels { broadcasted_messages_receiver: _, broadcast_topic_client } =
         subscriber_channels;
     let BroadcastNetworkMock { mut messages_to_broadcast_receiver, .. } = mock_network;
-    let rpc_transaction = RpcTransaction::get_test_instance(&mut get_rng());
-    let mut mempool_p2p_propagator = MempoolP2pPropagator::new(broadcast_topic_client);
+    let tx_hash = TransactionHash::get_test_instance(&mut get_rng());
+    let mut mempool_p2p_propagator =
+        MempoolP2pPropagator::new(broadcast_topic_client, declare_transactions_store());
     mempool_p2p_propagator
-        .handle_request(MempoolP2pPropagatorRequest::AddTransaction(rpc_transaction.clone()))
+        .handle_request(MempoolP2pPropagatorRequest::AddTransaction(tx_hash))
+        .await;
+    let message = timeout(TIMEOUT, messages_to_broadcast_receiver.next()).await.unwrap().unwrap();
+    assert_eq!(message, NewTransactionHashes(vec![tx_hash]));
+}
+
+#[tokio::test]
+async fn process_handle_add_declare_tx() {
+    let TestSubscriberChannels { mock_network, subscriber_channels } =
+        mock_register_broadcast_topic().expect("Failed to create mock network");
+    let BroadcastTopicChannels { broadcasted_messages_receiver: _, broadcast_topic_client } =
+        subscriber_channels;
+    let BroadcastNetworkMock { mut messages_to_broadcast_receiver, .. } = mock_network;
+    let RpcTransaction::Declare(declare_tx) =
+        rpc_declare_tx(declare_tx_args!(), SierraContractClass::default())
+    else {
+        panic!("Expected a declare transaction");
+    };
+    let tx_hash = TransactionHash::get_test_instance(&mut get_rng());
+    let declare_transactions_store = declare_transactions_store();
+    let mut mempool_p2p_propagator =
+        MempoolP2pPropagator::new(broadcast_topic_client, declare_transactions_store.clone());
+    mempool_p2p_propagator
+        .handle_request(MempoolP2pPropagatorRequest::AddDeclareTransaction(
+            tx_hash,
+            declare_tx.clone(),
+        ))
         .await;
+    // Only the hash is announced, and the transaction is kept to be served to peers.
     let message = timeout(TIMEOUT, messages_to_broadcast_receiver.next()).await.unwrap().unwrap();
-    assert_eq!(message, RpcTransactionWrapper(rpc_transaction));
+    assert_eq!(message, NewTransactionHashes(vec![tx_hash]));
+    assert_eq!(declare_transactions_store.get(&[tx_hash]), [declare_tx]);
 }
 
 #[tokio::test]
@@ -41,7 +81,8 @@ async fn process_handle_continue_propagation() {
         subscriber_channels;
     let BroadcastNetworkMock { mut continue_propagation_receiver, .. } = mock_network;
     let propagation_metadata = BroadcastedMessageMetadata::get_test_instance(&mut get_rng());
-    let mut mempool_p2p_propagator = MempoolP2pPropagator::new(broadcast_topic_client);
+    let mut mempool_p2p_propagator =
+        MempoolP2pPropagator::new(broadcast_topic_client, declare_transactions_store());
     mempool_p2p_propagator
         .handle_request(MempoolP2pPropagatorRequest::ContinuePropagation(
             propagation_metadata.clone(),
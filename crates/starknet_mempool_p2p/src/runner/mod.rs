@@ -1,39 +1,184 @@
 #[cfg(test)]
 mod test;
 
+use std::collections::HashSet;
+use std::num::NonZeroUsize;
+
 use async_trait::async_trait;
+use futures::future::BoxFuture;
 use futures::stream::FuturesUnordered;
-use futures::{pin_mut, StreamExt, TryFutureExt};
+use futures::{pin_mut, FutureExt, StreamExt, TryFutureExt};
+use lru::LruCache;
 use papyrus_network::network_manager::{
     BroadcastTopicClient,
     BroadcastTopicClientTrait,
     BroadcastTopicServer,
+    ClientResponsesManager,
     NetworkManager,
+    ReputationModifier,
+    ServerQueryManager,
+    SqmrClientSender,
+    SqmrServerReceiver,
+    MALICIOUS_REPORT,
+};
+use papyrus_network_types::network_types::BroadcastedMessageMetadata;
+use papyrus_protobuf::mempool::{
+    MempoolTransactionsQuery,
+    NewTransactionHashes,
+    RpcTransactionWrapper,
 };
-use papyrus_protobuf::mempool::RpcTransactionWrapper;
+use papyrus_protobuf::sync::DataOrFin;
+use starknet_api::core::ChainId;
+use starknet_api::executable_transaction::{
+    AccountTransaction,
+    DeployAccountTransaction,
+    InvokeTransaction,
+};
+use starknet_api::rpc_transaction::RpcTransaction;
+use starknet_api::transaction::{TransactionHash, TransactionHasher};
 use starknet_gateway_types::communication::{GatewayClientError, SharedGatewayClient};
 use starknet_gateway_types::errors::GatewayError;
 use starknet_gateway_types::gateway_types::GatewayInput;
+use starknet_mempool_types::communication::SharedMempoolClient;
 use starknet_sequencer_infra::component_definitions::ComponentStarter;
 use starknet_sequencer_infra::component_server::WrapperServer;
 use starknet_sequencer_infra::errors::ComponentError;
-use tracing::warn;
+use tracing::{debug, warn};
+
+use crate::declare_transactions_store::DeclareTransactionsStore;
+
+pub type MempoolTransactionsClient =
+    SqmrClientSender<MempoolTransactionsQuery, DataOrFin<RpcTransactionWrapper>>;
+pub type MempoolTransactionsServer =
+    SqmrServerReceiver<MempoolTransactionsQuery, DataOrFin<RpcTransactionWrapper>>;
+type MempoolTransactionsResponsesManager = ClientResponsesManager<DataOrFin<RpcTransactionWrapper>>;
+type MempoolTransactionsQueryManager =
+    ServerQueryManager<MempoolTransactionsQuery, DataOrFin<RpcTransactionWrapper>>;
+
+// The gateway may reject a transaction that was valid when the peer propagated it, e.g. if its
+// nonce was used in the meantime, so it takes many such reports to blacklist the peer.
+const INVALID_TRANSACTION_REPORT: ReputationModifier =
+    ReputationModifier::Misconduct { misconduct_score: 0.1 };
+
+// The protocols announced transactions are requested over. They're first requested from the
+// relaying peer's mempool, and the ones it doesn't serve are then requested as declare
+// transactions.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum FetchProtocol {
+    Mempool,
+    Declare,
+}
+
+// The result of a query for announced transactions.
+struct FetchedTransactions {
+    protocol: FetchProtocol,
+    transactions: Vec<RpcTransaction>,
+    // The requested hashes that weren't received.
+    missing_tx_hashes: HashSet<TransactionHash>,
+    // The metadata of the announcement that caused the query.
+    broadcasted_message_metadata: BroadcastedMessageMetadata,
+}
 
 pub struct MempoolP2pRunner {
     network_manager: Option<NetworkManager>,
-    broadcasted_topic_server: BroadcastTopicServer<RpcTransactionWrapper>,
-    broadcast_topic_client: BroadcastTopicClient<RpcTransactionWrapper>,
+    broadcasted_topic_server: BroadcastTopicServer<NewTransactionHashes>,
+    broadcast_topic_client: BroadcastTopicClient<NewTransactionHashes>,
+    transactions_client: MempoolTransactionsClient,
+    transactions_server: MempoolTransactionsServer,
+    // Declare transactions are served over a separate protocol, since they can't be served from
+    // the mempool.
+    declare_transactions_client: MempoolTransactionsClient,
+    declare_transactions_server: MempoolTransactionsServer,
+    declare_transactions_store: DeclareTransactionsStore,
     gateway_client: SharedGatewayClient,
+    mempool_client: SharedMempoolClient,
+    // Used to calculate the hashes of the received transactions.
+    chain_id: ChainId,
+    // The hashes of recently announced transactions, used to request each transaction only once
+    // even if several peers announce it.
+    seen_tx_hashes: LruCache<TransactionHash, ()>,
+    max_transactions_per_query: NonZeroUsize,
 }
 
 impl MempoolP2pRunner {
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         network_manager: Option<NetworkManager>,
-        broadcasted_topic_server: BroadcastTopicServer<RpcTransactionWrapper>,
-        broadcast_topic_client: BroadcastTopicClient<RpcTransactionWrapper>,
+        broadcasted_topic_server: BroadcastTopicServer<NewTransactionHashes>,
+        broadcast_topic_client: BroadcastTopicClient<NewTransactionHashes>,
+        transactions_client: MempoolTransactionsClient,
+        transactions_server: MempoolTransactionsServer,
+        declare_transactions_client: MempoolTransactionsClient,
+        declare_transactions_server: MempoolTransactionsServer,
+        declare_transactions_store: DeclareTransactionsStore,
         gateway_client: SharedGatewayClient,
+        mempool_client: SharedMempoolClient,
+        chain_id: ChainId,
+        transaction_hashes_cache_size: NonZeroUsize,
+        max_transactions_per_query: NonZeroUsize,
     ) -> Self {
-        Self { network_manager, broadcasted_topic_server, broadcast_topic_client, gateway_client }
+        Self {
+            network_manager,
+            broadcasted_topic_server,
+            broadcast_topic_client,
+            transactions_client,
+            transactions_server,
+            declare_transactions_client,
+            declare_transactions_server,
+            declare_transactions_store,
+            gateway_client,
+            mempool_client,
+            chain_id,
+            seen_tx_hashes: LruCache::new(transaction_hashes_cache_size),
+            max_transactions_per_query,
+        }
+    }
+
+    // Requests the given announced transactions from the peer that relayed the announcement to us,
+    // or from another peer if it can't be queried. Returns None if the query couldn't be sent, in
+    // which case they may be requested again on their next announcement.
+    async fn request_transactions(
+        &mut self,
+        protocol: FetchProtocol,
+        tx_hashes: Vec<TransactionHash>,
+        broadcasted_message_metadata: BroadcastedMessageMetadata,
+    ) -> Option<BoxFuture<'static, FetchedTransactions>> {
+        let transactions_client = match protocol {
+            FetchProtocol::Mempool => &mut self.transactions_client,
+            FetchProtocol::Declare => &mut self.declare_transactions_client,
+        };
+        // The peer that relayed the announcement to us requests the transactions once it receives
+        // the announcement, but might not hold them yet. Transactions it doesn't serve may be
+        // requested again on their next announcement.
+        let query_result = transactions_client
+            .send_new_query_to_propagation_source(
+                MempoolTransactionsQuery(tx_hashes.clone()),
+                &broadcasted_message_metadata,
+            )
+            .await;
+        match query_result {
+            Ok(responses_manager) => Some(
+                receive_transactions(
+                    responses_manager,
+                    tx_hashes.into_iter().collect(),
+                    self.chain_id.clone(),
+                )
+                .map(move |(transactions, missing_tx_hashes)| FetchedTransactions {
+                    protocol,
+                    transactions,
+                    missing_tx_hashes,
+                    broadcasted_message_metadata,
+                })
+                .boxed(),
+            ),
+            Err(e) => {
+                warn!("Failed to request announced transactions: {:?}", e);
+                for tx_hash in &tx_hashes {
+                    self.seen_tx_hashes.pop(tx_hash);
+                }
+                None
+            }
+        }
     }
 }
 
@@ -48,6 +193,8 @@ impl ComponentStarter for MempoolP2pRunner {
             .map_err(|_| ComponentError::InternalComponentError);
         pin_mut!(network_future);
         let mut gateway_futures = FuturesUnordered::new();
+        let mut fetch_futures = FuturesUnordered::<BoxFuture<'static, FetchedTransactions>>::new();
+        let mut serve_futures = FuturesUnordered::<BoxFuture<'static, ()>>::new();
         loop {
             tokio::select! {
                 result = &mut network_future => {
@@ -61,7 +208,7 @@ impl ComponentStarter for MempoolP2pRunner {
                             if let GatewayClientError::GatewayError(
                                 GatewayError::GatewaySpecError{p2p_message_metadata: Some(p2p_message_metadata), ..}
                             ) = gateway_client_error {
-                                if let Err(e) = self.broadcast_topic_client.report_peer(p2p_message_metadata.clone()).await {
+                                if let Err(e) = self.broadcast_topic_client.report_peer(p2p_message_metadata.clone(), INVALID_TRANSACTION_REPORT).await {
                                     warn!("Failed to report peer: {:?}", e);
                                 }
                             }
@@ -70,22 +217,219 @@ impl ComponentStarter for MempoolP2pRunner {
                 }
                 Some((message_result, broadcasted_message_metadata)) = self.broadcasted_topic_server.next() => {
                     match message_result {
-                        Ok(message) => {
-                            gateway_futures.push(self.gateway_client.add_tx(
-                                GatewayInput { rpc_tx: message.0, message_metadata: Some(broadcasted_message_metadata.clone()) }
-                            ));
+                        Ok(NewTransactionHashes(tx_hashes)) => {
+                            let unknown_tx_hashes: Vec<_> = tx_hashes
+                                .into_iter()
+                                .filter(|tx_hash| self.seen_tx_hashes.put(*tx_hash, ()).is_none())
+                                .collect();
+                            for tx_hashes in unknown_tx_hashes.chunks(self.max_transactions_per_query.get()) {
+                                if let Some(fetch_future) = self
+                                    .request_transactions(
+                                        FetchProtocol::Mempool,
+                                        tx_hashes.to_vec(),
+                                        broadcasted_message_metadata.clone(),
+                                    )
+                                    .await
+                                {
+                                    fetch_futures.push(fetch_future);
+                                }
+                            }
                         }
                         Err(e) => {
-                            warn!("Received a faulty transaction from network: {:?}. Attempting to report the sending peer", e);
-                            if let Err(e) = self.broadcast_topic_client.report_peer(broadcasted_message_metadata).await {
+                            warn!("Received a faulty transaction hashes announcement from network: {:?}. Attempting to report the sending peer", e);
+                            if let Err(e) = self.broadcast_topic_client.report_peer(broadcasted_message_metadata, MALICIOUS_REPORT).await {
                                 warn!("Failed to report peer: {:?}", e);
                             }
                         }
                     }
                 }
+                Some(fetched_transactions) = fetch_futures.next() => {
+                    let FetchedTransactions {
+                        protocol,
+                        transactions,
+                        missing_tx_hashes,
+                        broadcasted_message_metadata,
+                    } = fetched_transactions;
+                    for transaction in transactions {
+                        gateway_futures.push(self.gateway_client.add_tx(
+                            GatewayInput { rpc_tx: transaction, message_metadata: Some(broadcasted_message_metadata.clone()) }
+                        ));
+                    }
+                    if protocol == FetchProtocol::Mempool && !missing_tx_hashes.is_empty() {
+                        // The transactions the peer's mempool didn't serve may be declare
+                        // transactions.
+                        if let Some(fetch_future) = self
+                            .request_transactions(
+                                FetchProtocol::Declare,
+                                missing_tx_hashes.into_iter().collect(),
+                                broadcasted_message_metadata,
+                            )
+                            .await
+                        {
+                            fetch_futures.push(fetch_future);
+                        }
+                    } else {
+                        // Allow requesting the missing transactions again on their next
+                        // announcement.
+                        for tx_hash in missing_tx_hashes {
+                            self.seen_tx_hashes.pop(&tx_hash);
+                        }
+                    }
+                }
+                Some(query_manager) = self.transactions_server.next() => {
+                    serve_futures.push(
+                        serve_transactions(
+                            self.mempool_client.clone(),
+                            query_manager,
+                            self.max_transactions_per_query,
+                        )
+                        .boxed(),
+                    );
+                }
+                Some(query_manager) = self.declare_transactions_server.next() => {
+                    serve_futures.push(
+                        serve_declare_transactions(
+                            self.declare_transactions_store.clone(),
+                            query_manager,
+                            self.max_transactions_per_query,
+                        )
+                        .boxed(),
+                    );
+                }
+                Some(()) = serve_futures.next() => {}
+            }
+        }
+    }
+}
+
+// Receives the transactions sent in response to a query for `requested_tx_hashes`, and returns them
+// along with the requested hashes that weren't received. Reports the responding peer if it sends a
+// malformed response or a transaction that wasn't requested.
+async fn receive_transactions(
+    mut responses_manager: MempoolTransactionsResponsesManager,
+    mut requested_tx_hashes: HashSet<TransactionHash>,
+    chain_id: ChainId,
+) -> (Vec<RpcTransaction>, HashSet<TransactionHash>) {
+    let mut transactions = Vec::new();
+    while let Some(response) = responses_manager.next().await {
+        match response {
+            Ok(DataOrFin(Some(RpcTransactionWrapper(transaction)))) => {
+                let is_requested = calculate_tx_hash(&transaction, &chain_id)
+                    .is_some_and(|tx_hash| requested_tx_hashes.remove(&tx_hash));
+                if !is_requested {
+                    warn!("Peer sent a transaction that wasn't requested. Reporting it.");
+                    responses_manager.report_peer(MALICIOUS_REPORT);
+                    break;
+                }
+                transactions.push(transaction);
+            }
+            Ok(DataOrFin(None)) => break,
+            Err(e) => {
+                warn!("Received a faulty transaction from network: {:?}. Reporting the peer.", e);
+                responses_manager.report_peer(MALICIOUS_REPORT);
+                break;
             }
         }
     }
+    (transactions, requested_tx_hashes)
+}
+
+// Returns the hash of `tx`, or None if it can't be calculated.
+fn calculate_tx_hash(tx: &RpcTransaction, chain_id: &ChainId) -> Option<TransactionHash> {
+    match tx {
+        RpcTransaction::Invoke(invoke_tx) => {
+            InvokeTransaction::from_rpc_tx(invoke_tx.clone(), chain_id).ok().map(|tx| tx.tx_hash())
+        }
+        RpcTransaction::DeployAccount(deploy_account_tx) => {
+            DeployAccountTransaction::from_rpc_tx(deploy_account_tx.clone(), chain_id)
+                .ok()
+                .map(|tx| tx.tx_hash())
+        }
+        RpcTransaction::Declare(declare_tx) => {
+            // Calculated the same way as by the gateway.
+            let declare_tx: starknet_api::transaction::DeclareTransaction =
+                declare_tx.clone().into();
+            declare_tx.calculate_transaction_hash(chain_id, &declare_tx.version()).ok()
+        }
+    }
+}
+
+// Responds to a query with the requested transactions that are held in the mempool.
+async fn serve_transactions(
+    mempool_client: SharedMempoolClient,
+    mut query_manager: MempoolTransactionsQueryManager,
+    max_transactions_per_query: NonZeroUsize,
+) {
+    let Some(tx_hashes) = query_tx_hashes(&mut query_manager, max_transactions_per_query) else {
+        return;
+    };
+    let transactions = match mempool_client.get_txs_by_hash(tx_hashes).await {
+        Ok(transactions) => transactions,
+        Err(e) => {
+            warn!("Failed to get transactions from the mempool: {:?}", e);
+            Vec::new()
+        }
+    };
+    // Declare transactions aren't served from the mempool, since it doesn't hold their Sierra
+    // class. They're served from the declare transactions store instead.
+    let transactions =
+        transactions.into_iter().filter_map(AccountTransaction::into_rpc_transaction);
+    send_transactions(query_manager, transactions).await;
+}
+
+// Responds to a query with the requested declare transactions that are held in the store.
+async fn serve_declare_transactions(
+    declare_transactions_store: DeclareTransactionsStore,
+    mut query_manager: MempoolTransactionsQueryManager,
+    max_transactions_per_query: NonZeroUsize,
+) {
+    let Some(tx_hashes) = query_tx_hashes(&mut query_manager, max_transactions_per_query) else {
+        return;
+    };
+    let transactions = declare_transactions_store.get(&tx_hashes);
+    send_transactions(query_manager, transactions.into_iter().map(RpcTransaction::Declare)).await;
+}
+
+// Returns the hashes requested by a query. Reports the querying peer and returns None if the query
+// is malformed or requests more than `max_transactions_per_query` transactions.
+fn query_tx_hashes(
+    query_manager: &mut MempoolTransactionsQueryManager,
+    max_transactions_per_query: NonZeroUsize,
+) -> Option<Vec<TransactionHash>> {
+    let tx_hashes = match query_manager.query() {
+        Ok(MempoolTransactionsQuery(tx_hashes)) => tx_hashes.clone(),
+        Err(e) => {
+            warn!("Received a faulty mempool transactions query: {:?}. Reporting the peer.", e);
+            query_manager.report_peer(MALICIOUS_REPORT);
+            return None;
+        }
+    };
+    if tx_hashes.len() > max_transactions_per_query.get() {
+        warn!(
+            "Received a mempool transactions query for {} transactions, more than the limit of \
+             {}. Reporting the peer.",
+            tx_hashes.len(),
+            max_transactions_per_query
+        );
+        query_manager.report_peer(MALICIOUS_REPORT);
+        return None;
+    }
+    Some(tx_hashes)
+}
+
+async fn send_transactions(
+    mut query_manager: MempoolTransactionsQueryManager,
+    transactions: impl IntoIterator<Item = RpcTransaction>,
+) {
+    for transaction in transactions {
+        let response = DataOrFin(Some(RpcTransactionWrapper(transaction)));
+        if query_manager.send_response(response).await.is_err() {
+            return;
+        }
+    }
+    if query_manager.send_response(DataOrFin(None)).await.is_err() {
+        debug!("Failed to send fin for a mempool transactions query.");
+    }
 }
 
 pub type MempoolP2pRunnerServer = WrapperServer<MempoolP2pRunner>;
@@ -1,28 +1,61 @@
+use std::num::NonZeroUsize;
 use std::sync::Arc;
 use std::time::Duration;
 
 use async_trait::async_trait;
-use futures::channel::mpsc::Sender;
+use futures::channel::mpsc::{Receiver, Sender};
 use futures::stream::StreamExt;
 use futures::SinkExt;
 use papyrus_network::network_manager::test_utils::{
+    create_test_server_query_manager,
     mock_register_broadcast_topic,
+    mock_register_sqmr_protocol_client,
+    mock_register_sqmr_protocol_server,
     BroadcastNetworkMock,
+    MockClientResponsesManager,
     TestSubscriberChannels,
 };
-use papyrus_network::network_manager::{BroadcastTopicChannels, NetworkManager};
+use papyrus_network::network_manager::{
+    BroadcastTopicChannels,
+    GenericReceiver,
+    NetworkManager,
+    ServerQueryManager,
+};
 use papyrus_network::NetworkConfig;
 use papyrus_network_types::network_types::BroadcastedMessageMetadata;
-use papyrus_protobuf::mempool::RpcTransactionWrapper;
+use papyrus_protobuf::mempool::{
+    MempoolTransactionsQuery,
+    NewTransactionHashes,
+    RpcTransactionWrapper,
+};
+use papyrus_protobuf::sync::DataOrFin;
 use papyrus_test_utils::{get_rng, GetTestInstance};
+use starknet_api::core::{ChainId, Nonce};
+use starknet_api::executable_transaction::AccountTransaction;
 use starknet_api::rpc_transaction::RpcTransaction;
+use starknet_api::state::SierraContractClass;
+use starknet_api::test_utils::declare::rpc_declare_tx;
+use starknet_api::test_utils::invoke::executable_invoke_tx;
 use starknet_api::transaction::TransactionHash;
+use starknet_api::{declare_tx_args, invoke_tx_args};
 use starknet_gateway_types::communication::{GatewayClient, GatewayClientResult};
 use starknet_gateway_types::gateway_types::GatewayInput;
+use starknet_mempool_types::communication::MockMempoolClient;
 use starknet_sequencer_infra::component_definitions::ComponentStarter;
-use tokio::time::sleep;
+use tokio::time::timeout;
+
+use super::{calculate_tx_hash, into_rpc_transaction, MempoolP2pRunner};
+use crate::declare_transactions_store::DeclareTransactionsStore;
 
-use super::MempoolP2pRunner;
+const BUFFER_SIZE: usize = 10;
+const MAX_TRANSACTIONS_PER_QUERY: usize = 2;
+const TIMEOUT: Duration = Duration::from_secs(5);
+
+type TransactionsQueriesReceiver = GenericReceiver<
+    MockClientResponsesManager<MempoolTransactionsQuery, DataOrFin<RpcTransactionWrapper>>,
+>;
+type TransactionsQueriesSender =
+    Sender<ServerQueryManager<MempoolTransactionsQuery, DataOrFin<RpcTransactionWrapper>>>;
 
 // TODO(eitan): Make it an automock
 #[derive(Clone)]
@@ -38,43 +71,371 @@ impl GatewayClient for MockGatewayClient {
     }
 }
 
-#[tokio::test]
-async fn start_component_receive_tx_happy_flow() {
+struct TestSetup {
+    mempool_p2p_runner: MempoolP2pRunner,
+    broadcast_network_mock: BroadcastNetworkMock<NewTransactionHashes>,
+    transactions_queries_receiver: TransactionsQueriesReceiver,
+    transactions_queries_sender: TransactionsQueriesSender,
+    declare_transactions_queries_receiver: TransactionsQueriesReceiver,
+    declare_transactions_queries_sender: TransactionsQueriesSender,
+    declare_transactions_store: DeclareTransactionsStore,
+    add_tx_receiver: Receiver<RpcTransaction>,
+}
+
+fn setup(mempool_client: MockMempoolClient) -> TestSetup {
     let TestSubscriberChannels { mock_network, subscriber_channels } =
         mock_register_broadcast_topic().expect("Failed to create mock network");
     let BroadcastTopicChannels { broadcasted_messages_receiver, broadcast_topic_client } =
         subscriber_channels;
-    let BroadcastNetworkMock {
-        broadcasted_messages_sender: mut mock_broadcasted_messages_sender,
-        ..
-    } = mock_network;
+    let (transactions_client, transactions_queries_receiver) =
+        mock_register_sqmr_protocol_client(BUFFER_SIZE);
+    let (transactions_server, transactions_queries_sender) =
+        mock_register_sqmr_protocol_server(BUFFER_SIZE);
+    let (declare_transactions_client, declare_transactions_queries_receiver) =
+        mock_register_sqmr_protocol_client(BUFFER_SIZE);
+    let (declare_transactions_server, declare_transactions_queries_sender) =
+        mock_register_sqmr_protocol_server(BUFFER_SIZE);
+    let declare_transactions_store =
+        DeclareTransactionsStore::new(NonZeroUsize::new(BUFFER_SIZE).unwrap());
     // Creating a placeholder network manager with default config for init of a mempool receiver
     let placeholder_network_manager = NetworkManager::new(NetworkConfig::default(), None);
-    let (add_tx_sender, mut add_tx_receiver) = futures::channel::mpsc::channel(1);
-    let mock_gateway_client = Arc::new(MockGatewayClient { add_tx_sender });
-    let mut mempool_p2p_runner = MempoolP2pRunner::new(
+    let (add_tx_sender, add_tx_receiver) = futures::channel::mpsc::channel(BUFFER_SIZE);
+    let mempool_p2p_runner = MempoolP2pRunner::new(
         Some(placeholder_network_manager),
         broadcasted_messages_receiver,
         broadcast_topic_client,
-        mock_gateway_client,
+        transactions_client,
+        transactions_server,
+        declare_transactions_client,
+        declare_transactions_server,
+        declare_transactions_store.clone(),
+        Arc::new(MockGatewayClient { add_tx_sender }),
+        Arc::new(mempool_client),
+        ChainId::create_for_testing(),
+        NonZeroUsize::new(BUFFER_SIZE).unwrap(),
+        NonZeroUsize::new(MAX_TRANSACTIONS_PER_QUERY).unwrap(),
     );
+    TestSetup {
+        mempool_p2p_runner,
+        broadcast_network_mock: mock_network,
+        transactions_queries_receiver,
+        transactions_queries_sender,
+        declare_transactions_queries_receiver,
+        declare_transactions_queries_sender,
+        declare_transactions_store,
+        add_tx_receiver,
+    }
+}
+
+fn mempool_transaction(tx_hash: TransactionHash, nonce: u8) -> AccountTransaction {
+    executable_invoke_tx(invoke_tx_args!(tx_hash, nonce: Nonce(nonce.into())))
+}
+
+fn rpc_transaction(nonce: u8) -> RpcTransaction {
+    into_rpc_transaction(mempool_transaction(TransactionHash::default(), nonce)).unwrap()
+}
+
+fn rpc_transaction_hash(nonce: u8) -> TransactionHash {
+    calculate_tx_hash(&rpc_transaction(nonce), &ChainId::create_for_testing()).unwrap()
+}
+
+fn declare_transaction() -> RpcTransaction {
+    rpc_declare_tx(declare_tx_args!(), SierraContractClass::default())
+}
+
+#[tokio::test]
+async fn announced_transaction_is_requested_and_added_to_gateway() {
+    // Setup.
+    let TestSetup {
+        mut mempool_p2p_runner,
+        broadcast_network_mock,
+        mut transactions_queries_receiver,
+        mut add_tx_receiver,
+        ..
+    } = setup(MockMempoolClient::new());
+    let BroadcastNetworkMock { mut broadcasted_messages_sender, .. } = broadcast_network_mock;
+    let tx_hash = rpc_transaction_hash(0);
     let message_metadata = BroadcastedMessageMetadata::get_test_instance(&mut get_rng());
-    let expected_rpc_transaction =
-        RpcTransactionWrapper(RpcTransaction::get_test_instance(&mut get_rng()));
+    let expected_rpc_transaction = rpc_transaction(0);
 
-    // Sending the expected transaction to the mempool receiver
-    let res =
-        mock_broadcasted_messages_sender.send((expected_rpc_transaction.clone(), message_metadata));
+    // Test.
+    broadcasted_messages_sender
+        .send((NewTransactionHashes(vec![tx_hash]), message_metadata))
+        .await
+        .unwrap();
 
-    res.await.expect("Failed to send message");
+    // Assert.
+    let test_future = async {
+        let mut responses_manager = transactions_queries_receiver.next().await.unwrap();
+        assert_eq!(responses_manager.query(), &Ok(MempoolTransactionsQuery(vec![tx_hash])));
+        responses_manager
+            .send_response(DataOrFin(Some(RpcTransactionWrapper(expected_rpc_transaction.clone()))))
+            .await
+            .unwrap();
+        responses_manager.send_response(DataOrFin(None)).await.unwrap();
+        assert_eq!(add_tx_receiver.next().await, Some(expected_rpc_transaction));
+    };
     tokio::select! {
-        _ = mempool_p2p_runner.start() => {panic!("Mempool receiver failed to start");}
-        actual_rpc_transaction = add_tx_receiver.next() => {
-            assert_eq!(actual_rpc_transaction, Some(expected_rpc_transaction.0));
-        }
-        _ = sleep(Duration::from_secs(5)) => {
-            panic!("Test timed out");
+        _ = mempool_p2p_runner.start() => panic!("Mempool p2p runner stopped"),
+        result = timeout(TIMEOUT, test_future) => result.unwrap(),
+    }
+}
+
+#[tokio::test]
+async fn transaction_announced_twice_is_requested_once() {
+    // Setup.
+    let TestSetup {
+        mut mempool_p2p_runner,
+        broadcast_network_mock,
+        mut transactions_queries_receiver,
+        ..
+    } = setup(MockMempoolClient::new());
+    let BroadcastNetworkMock { mut broadcasted_messages_sender, .. } = broadcast_network_mock;
+    let mut rng = get_rng();
+    let known_tx_hash = TransactionHash::get_test_instance(&mut rng);
+    let new_tx_hash = TransactionHash(known_tx_hash.0 + 1);
+    let message_metadata = BroadcastedMessageMetadata::get_test_instance(&mut rng);
+
+    // Test.
+    for tx_hashes in [vec![known_tx_hash], vec![known_tx_hash, new_tx_hash]] {
+        broadcasted_messages_sender
+            .send((NewTransactionHashes(tx_hashes), message_metadata.clone()))
+            .await
+            .unwrap();
+    }
+
+    // Assert.
+    let test_future = async {
+        // The first query is kept unanswered, so the known transaction is still being requested.
+        let first_query = transactions_queries_receiver.next().await.unwrap();
+        assert_eq!(first_query.query(), &Ok(MempoolTransactionsQuery(vec![known_tx_hash])));
+        let second_query = transactions_queries_receiver.next().await.unwrap();
+        assert_eq!(second_query.query(), &Ok(MempoolTransactionsQuery(vec![new_tx_hash])));
+    };
+    tokio::select! {
+        _ = mempool_p2p_runner.start() => panic!("Mempool p2p runner stopped"),
+        result = timeout(TIMEOUT, test_future) => result.unwrap(),
+    }
+}
+
+#[tokio::test]
+async fn peer_sending_unrequested_transactions_is_reported() {
+    // Setup.
+    let TestSetup {
+        mut mempool_p2p_runner,
+        broadcast_network_mock,
+        mut transactions_queries_receiver,
+        ..
+    } = setup(MockMempoolClient::new());
+    let BroadcastNetworkMock { mut broadcasted_messages_sender, .. } = broadcast_network_mock;
+    let tx_hash = rpc_transaction_hash(0);
+    let message_metadata = BroadcastedMessageMetadata::get_test_instance(&mut get_rng());
+
+    // Test.
+    broadcasted_messages_sender
+        .send((NewTransactionHashes(vec![tx_hash]), message_metadata))
+        .await
+        .unwrap();
+
+    // Assert.
+    let test_future = async {
+        let mut responses_manager = transactions_queries_receiver.next().await.unwrap();
+        // Only the first transaction was requested.
+        for nonce in 0..2 {
+            let response = RpcTransactionWrapper(rpc_transaction(nonce));
+            responses_manager.send_response(DataOrFin(Some(response))).await.unwrap();
         }
+        responses_manager.assert_reported(TIMEOUT).await;
+    };
+    tokio::select! {
+        _ = mempool_p2p_runner.start() => panic!("Mempool p2p runner stopped"),
+        result = timeout(TIMEOUT, test_future) => result.unwrap(),
     }
 }
+
+#[tokio::test]
+async fn transaction_not_received_is_requested_again() {
+    // Setup.
+    let TestSetup {
+        mut mempool_p2p_runner,
+        broadcast_network_mock,
+        mut transactions_queries_receiver,
+        mut declare_transactions_queries_receiver,
+        ..
+    } = setup(MockMempoolClient::new());
+    let BroadcastNetworkMock { mut broadcasted_messages_sender, .. } = broadcast_network_mock;
+    let received_tx_hash = rpc_transaction_hash(0);
+    let missing_tx_hash = rpc_transaction_hash(1);
+    let message_metadata = BroadcastedMessageMetadata::get_test_instance(&mut get_rng());
+
+    // Test.
+    broadcasted_messages_sender
+        .send((
+            NewTransactionHashes(vec![received_tx_hash, missing_tx_hash]),
+            message_metadata.clone(),
+        ))
+        .await
+        .unwrap();
+
+    // Assert.
+    let test_future = async {
+        let mut responses_manager = transactions_queries_receiver.next().await.unwrap();
+        let response = RpcTransactionWrapper(rpc_transaction(0));
+        responses_manager.send_response(DataOrFin(Some(response))).await.unwrap();
+        responses_manager.send_response(DataOrFin(None)).await.unwrap();
+        // The missing transaction isn't a declare transaction either.
+        let mut responses_manager = declare_transactions_queries_receiver.next().await.unwrap();
+        assert_eq!(responses_manager.query(), &Ok(MempoolTransactionsQuery(vec![missing_tx_hash])));
+        responses_manager.send_response(DataOrFin(None)).await.unwrap();
+        // Wait for the runner to handle the response before announcing the transactions again.
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        broadcasted_messages_sender
+            .send((NewTransactionHashes(vec![received_tx_hash, missing_tx_hash]), message_metadata))
+            .await
+            .unwrap();
+        let query = transactions_queries_receiver.next().await.unwrap();
+        assert_eq!(query.query(), &Ok(MempoolTransactionsQuery(vec![missing_tx_hash])));
+    };
+    tokio::select! {
+        _ = mempool_p2p_runner.start() => panic!("Mempool p2p runner stopped"),
+        result = timeout(TIMEOUT, test_future) => result.unwrap(),
+    }
+}
+
+#[tokio::test]
+async fn transaction_not_served_from_mempool_is_requested_as_declare() {
+    // Setup.
+    let TestSetup {
+        mut mempool_p2p_runner,
+        broadcast_network_mock,
+        mut transactions_queries_receiver,
+        mut declare_transactions_queries_receiver,
+        mut add_tx_receiver,
+        ..
+    } = setup(MockMempoolClient::new());
+    let BroadcastNetworkMock { mut broadcasted_messages_sender, .. } = broadcast_network_mock;
+    let declare_tx = declare_transaction();
+    let tx_hash = calculate_tx_hash(&declare_tx, &ChainId::create_for_testing()).unwrap();
+    let message_metadata = BroadcastedMessageMetadata::get_test_instance(&mut get_rng());
+
+    // Test.
+    broadcasted_messages_sender
+        .send((NewTransactionHashes(vec![tx_hash]), message_metadata))
+        .await
+        .unwrap();
+
+    // Assert.
+    let test_future = async {
+        let mut responses_manager = transactions_queries_receiver.next().await.unwrap();
+        responses_manager.send_response(DataOrFin(None)).await.unwrap();
+        let mut responses_manager = declare_transactions_queries_receiver.next().await.unwrap();
+        assert_eq!(responses_manager.query(), &Ok(MempoolTransactionsQuery(vec![tx_hash])));
+        responses_manager
+            .send_response(DataOrFin(Some(RpcTransactionWrapper(declare_tx.clone()))))
+            .await
+            .unwrap();
+        responses_manager.send_response(DataOrFin(None)).await.unwrap();
+        assert_eq!(add_tx_receiver.next().await, Some(declare_tx));
+    };
+    tokio::select! {
+        _ = mempool_p2p_runner.start() => panic!("Mempool p2p runner stopped"),
+        result = timeout(TIMEOUT, test_future) => result.unwrap(),
+    }
+}
+
+#[tokio::test]
+async fn declare_transactions_query_is_served_from_store() {
+    // Setup.
+    let TestSetup {
+        mut mempool_p2p_runner,
+        mut declare_transactions_queries_sender,
+        declare_transactions_store,
+        ..
+    } = setup(MockMempoolClient::new());
+    let RpcTransaction::Declare(declare_tx) = declare_transaction() else {
+        panic!("Expected a declare transaction");
+    };
+    let tx_hash = TransactionHash::get_test_instance(&mut get_rng());
+    let unknown_tx_hash = TransactionHash(tx_hash.0 + 1);
+    declare_transactions_store.insert(tx_hash, declare_tx.clone());
+    let (query_manager, _report_receiver, mut responses_receiver) =
+        create_test_server_query_manager(MempoolTransactionsQuery(vec![unknown_tx_hash, tx_hash]));
+
+    // Test.
+    declare_transactions_queries_sender.send(query_manager).await.unwrap();
+
+    // Assert.
+    let test_future = async {
+        assert_eq!(
+            responses_receiver.next().await,
+            Some(DataOrFin(Some(RpcTransactionWrapper(RpcTransaction::Declare(declare_tx)))))
+        );
+        assert_eq!(responses_receiver.next().await, Some(DataOrFin(None)));
+    };
+    tokio::select! {
+        _ = mempool_p2p_runner.start() => panic!("Mempool p2p runner stopped"),
+        result = timeout(TIMEOUT, test_future) => result.unwrap(),
+    }
+}
+
+#[tokio::test]
+async fn transactions_query_is_served_from_mempool() {
+    // Setup.
+    let tx_hash = TransactionHash::get_test_instance(&mut get_rng());
+    let unknown_tx_hash = TransactionHash(tx_hash.0 + 1);
+    let mempool_tx = mempool_transaction(tx_hash, 0);
+    let expected_rpc_transaction = into_rpc_transaction(mempool_tx.clone()).unwrap();
+    let mut mempool_client = MockMempoolClient::new();
+    mempool_client
+        .expect_get_txs_by_hash()
+        .withf(move |tx_hashes| tx_hashes == &vec![tx_hash, unknown_tx_hash])
+        .return_once(move |_| Ok(vec![mempool_tx]));
+    let TestSetup { mut mempool_p2p_runner, mut transactions_queries_sender, .. } =
+        setup(mempool_client);
+    let (query_manager, _report_receiver, mut responses_receiver) =
+        create_test_server_query_manager(MempoolTransactionsQuery(vec![tx_hash, unknown_tx_hash]));
+
+    // Test.
+    transactions_queries_sender.send(query_manager).await.unwrap();
+
+    // Assert.
+    let test_future = async {
+        assert_eq!(
+            responses_receiver.next().await,
+            Some(DataOrFin(Some(RpcTransactionWrapper(expected_rpc_transaction))))
+        );
+        assert_eq!(responses_receiver.next().await, Some(DataOrFin(None)));
+    };
+    tokio::select! {
+        _ = mempool_p2p_runner.start() => panic!("Mempool p2p runner stopped"),
+        result = timeout(TIMEOUT, test_future) => result.unwrap(),
+    }
+}
+
+#[tokio::test]
+async fn transactions_query_exceeding_limit_is_reported() {
+    // Setup.
+    let TestSetup { mut mempool_p2p_runner, mut transactions_queries_sender, .. } =
+        setup(MockMempoolClient::new());
+    let mut rng = get_rng();
+    let tx_hashes = (0..=MAX_TRANSACTIONS_PER_QUERY)
+        .map(|_| TransactionHash::get_test_instance(&mut rng))
+        .collect();
+    let (query_manager, report_receiver, mut responses_receiver) =
+        create_test_server_query_manager(MempoolTransactionsQuery(tx_hashes));
+
+    // Test.
+    transactions_queries_sender.send(query_manager).await.unwrap();
+
+    // Assert.
+    let test_future = async {
+        report_receiver.await.unwrap();
+        assert_eq!(responses_receiver.next().await, None);
+    };
+    tokio::select! {
+        _ = mempool_p2p_runner.start() => panic!("Mempool p2p runner stopped"),
+        result = timeout(TIMEOUT, test_future) => result.unwrap(),
+    }
+}
+
 // TODO(eitan): Add test for when the gateway client fails to add the transaction
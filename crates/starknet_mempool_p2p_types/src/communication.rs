@@ -4,7 +4,8 @@ use async_trait::async_trait;
 use papyrus_network_types::network_types::BroadcastedMessageMetadata;
 use papyrus_proc_macros::handle_response_variants;
 use serde::{Deserialize, Serialize};
-use starknet_api::rpc_transaction::RpcTransaction;
+use starknet_api::rpc_transaction::RpcDeclareTransaction;
+use starknet_api::transaction::TransactionHash;
 use starknet_sequencer_infra::component_client::{
     ClientError,
     LocalComponentClient,
@@ -22,12 +23,23 @@ use crate::mempool_p2p_types::MempoolP2pPropagatorResult;
 #[cfg_attr(any(feature = "testing", test), mockall::automock)]
 #[async_trait]
 pub trait MempoolP2pPropagatorClient: Send + Sync {
-    /// Adds a transaction to be propagated to other peers. This should only be called on a new
-    /// transaction coming from the user and not from another peer. To handle transactions coming
-    /// from other peers, use `continue_propagation`.
+    /// Announces a transaction to other peers, which then request it from the mempool if they
+    /// don't know it. This should only be called on a new transaction coming from the user and not
+    /// from another peer. To handle transactions coming from other peers, use
+    /// `continue_propagation`.
     async fn add_transaction(
         &self,
-        transaction: RpcTransaction,
+        tx_hash: TransactionHash,
+    ) -> MempoolP2pPropagatorClientResult<()>;
+
+    /// Announces a declare transaction to other peers. Since the mempool doesn't hold the Sierra
+    /// class of a declare transaction, the transaction is kept by the propagator, which serves it
+    /// to the peers that request it. This should only be called on a new transaction coming from
+    /// the user.
+    async fn add_declare_transaction(
+        &self,
+        tx_hash: TransactionHash,
+        declare_tx: RpcDeclareTransaction,
     ) -> MempoolP2pPropagatorClientResult<()>;
 
     /// Continues the propagation of a transaction we've received from another peer.
@@ -48,13 +60,15 @@ pub type MempoolP2pPropagatorRequestAndResponseSender =
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub enum MempoolP2pPropagatorRequest {
-    AddTransaction(RpcTransaction),
+    AddTransaction(TransactionHash),
+    AddDeclareTransaction(TransactionHash, RpcDeclareTransaction),
     ContinuePropagation(BroadcastedMessageMetadata),
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub enum MempoolP2pPropagatorResponse {
     AddTransaction(MempoolP2pPropagatorResult<()>),
+    AddDeclareTransaction(MempoolP2pPropagatorResult<()>),
     ContinuePropagation(MempoolP2pPropagatorResult<()>),
 }
 
@@ -74,9 +88,9 @@ where
 {
     async fn add_transaction(
         &self,
-        transaction: RpcTransaction,
+        tx_hash: TransactionHash,
     ) -> MempoolP2pPropagatorClientResult<()> {
-        let request = MempoolP2pPropagatorRequest::AddTransaction(transaction);
+        let request = MempoolP2pPropagatorRequest::AddTransaction(tx_hash);
         let response = self.send(request).await;
         handle_response_variants!(
             MempoolP2pPropagatorResponse,
@@ -86,6 +100,21 @@ where
         )
     }
 
+    async fn add_declare_transaction(
+        &self,
+        tx_hash: TransactionHash,
+        declare_tx: RpcDeclareTransaction,
+    ) -> MempoolP2pPropagatorClientResult<()> {
+        let request = MempoolP2pPropagatorRequest::AddDeclareTransaction(tx_hash, declare_tx);
+        let response = self.send(request).await;
+        handle_response_variants!(
+            MempoolP2pPropagatorResponse,
+            AddDeclareTransaction,
+            MempoolP2pPropagatorClientError,
+            MempoolP2pPropagatorError
+        )
+    }
+
     async fn continue_propagation(
         &self,
         propagation_metadata: BroadcastedMessageMetadata,
@@ -6,7 +6,10 @@ use mockall::automock;
 use papyrus_network_types::network_types::BroadcastedMessageMetadata;
 use papyrus_proc_macros::handle_response_variants;
 use serde::{Deserialize, Serialize};
+use starknet_api::core::ContractAddress;
 use starknet_api::executable_transaction::AccountTransaction;
+use starknet_api::rpc_transaction::RpcDeclareTransaction;
+use starknet_api::transaction::TransactionHash;
 use starknet_sequencer_infra::component_client::{
     ClientError,
     LocalComponentClient,
@@ -19,7 +22,14 @@ use starknet_sequencer_infra::component_definitions::{
 use thiserror::Error;
 
 use crate::errors::MempoolError;
-use crate::mempool_types::{AddTransactionArgs, CommitBlockArgs};
+use crate::mempool_types::{
+    AccountTransactions,
+    AddTransactionArgs,
+    CommitBlockArgs,
+    MempoolStats,
+    RewindBlockArgs,
+    TransactionStatus,
+};
 
 pub type LocalMempoolClient = LocalComponentClient<MempoolRequest, MempoolResponse>;
 pub type RemoteMempoolClient = RemoteComponentClient<MempoolRequest, MempoolResponse>;
@@ -33,6 +43,9 @@ pub type SharedMempoolClient = Arc<dyn MempoolClient>;
 pub struct AddTransactionArgsWrapper {
     pub args: AddTransactionArgs,
     pub p2p_message_metadata: Option<BroadcastedMessageMetadata>,
+    /// The declare transaction as received from the user. Declare transactions are propagated
+    /// whole, since the mempool doesn't hold their Sierra class and can't serve them to peers.
+    pub rpc_declare_tx: Option<RpcDeclareTransaction>,
 }
 
 /// Serves as the mempool's shared interface. Requires `Send + Sync` to allow transferring and
@@ -45,6 +58,21 @@ pub trait MempoolClient: Send + Sync {
     async fn add_tx(&self, args: AddTransactionArgsWrapper) -> MempoolClientResult<()>;
     async fn commit_block(&self, args: CommitBlockArgs) -> MempoolClientResult<()>;
     async fn get_txs(&self, n_txs: usize) -> MempoolClientResult<Vec<AccountTransaction>>;
+    async fn get_oldest_txs(&self, n_txs: usize) -> MempoolClientResult<Vec<AccountTransaction>>;
+    async fn rewind_block(&self, args: RewindBlockArgs) -> MempoolClientResult<()>;
+    async fn get_transaction_status(
+        &self,
+        tx_hash: TransactionHash,
+    ) -> MempoolClientResult<TransactionStatus>;
+    async fn get_account_transactions(
+        &self,
+        address: ContractAddress,
+    ) -> MempoolClientResult<AccountTransactions>;
+    async fn get_pool_stats(&self) -> MempoolClientResult<MempoolStats>;
+    async fn get_txs_by_hash(
+        &self,
+        tx_hashes: Vec<TransactionHash>,
+    ) -> MempoolClientResult<Vec<AccountTransaction>>;
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -52,6 +80,12 @@ pub enum MempoolRequest {
     AddTransaction(AddTransactionArgsWrapper),
     CommitBlock(CommitBlockArgs),
     GetTransactions(usize),
+    GetOldestTransactions(usize),
+    RewindBlock(RewindBlockArgs),
+    GetTransactionStatus(TransactionHash),
+    GetAccountTransactions(ContractAddress),
+    GetPoolStats,
+    GetTransactionsByHash(Vec<TransactionHash>),
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -59,6 +93,12 @@ pub enum MempoolResponse {
     AddTransaction(MempoolResult<()>),
     CommitBlock(MempoolResult<()>),
     GetTransactions(MempoolResult<Vec<AccountTransaction>>),
+    GetOldestTransactions(MempoolResult<Vec<AccountTransaction>>),
+    RewindBlock(MempoolResult<()>),
+    GetTransactionStatus(MempoolResult<TransactionStatus>),
+    GetAccountTransactions(MempoolResult<AccountTransactions>),
+    GetPoolStats(MempoolResult<MempoolStats>),
+    GetTransactionsByHash(MempoolResult<Vec<AccountTransaction>>),
 }
 
 #[derive(Clone, Debug, Error)]
@@ -96,4 +136,69 @@ where
             MempoolError
         )
     }
+
+    async fn get_oldest_txs(&self, n_txs: usize) -> MempoolClientResult<Vec<AccountTransaction>> {
+        let request = MempoolRequest::GetOldestTransactions(n_txs);
+        let response = self.send(request).await;
+        handle_response_variants!(
+            MempoolResponse,
+            GetOldestTransactions,
+            MempoolClientError,
+            MempoolError
+        )
+    }
+
+    async fn rewind_block(&self, args: RewindBlockArgs) -> MempoolClientResult<()> {
+        let request = MempoolRequest::RewindBlock(args);
+        let response = self.send(request).await;
+        handle_response_variants!(MempoolResponse, RewindBlock, MempoolClientError, MempoolError)
+    }
+
+    async fn get_transaction_status(
+        &self,
+        tx_hash: TransactionHash,
+    ) -> MempoolClientResult<TransactionStatus> {
+        let request = MempoolRequest::GetTransactionStatus(tx_hash);
+        let response = self.send(request).await;
+        handle_response_variants!(
+            MempoolResponse,
+            GetTransactionStatus,
+            MempoolClientError,
+            MempoolError
+        )
+    }
+
+    async fn get_account_transactions(
+        &self,
+        address: ContractAddress,
+    ) -> MempoolClientResult<AccountTransactions> {
+        let request = MempoolRequest::GetAccountTransactions(address);
+        let response = self.send(request).await;
+        handle_response_variants!(
+            MempoolResponse,
+            GetAccountTransactions,
+            MempoolClientError,
+            MempoolError
+        )
+    }
+
+    async fn get_pool_stats(&self) -> MempoolClientResult<MempoolStats> {
+        let request = MempoolRequest::GetPoolStats;
+        let response = self.send(request).await;
+        handle_response_variants!(MempoolResponse, GetPoolStats, MempoolClientError, MempoolError)
+    }
+
+    async fn get_txs_by_hash(
+        &self,
+        tx_hashes: Vec<TransactionHash>,
+    ) -> MempoolClientResult<Vec<AccountTransaction>> {
+        let request = MempoolRequest::GetTransactionsByHash(tx_hashes);
+        let response = self.send(request).await;
+        handle_response_variants!(
+            MempoolResponse,
+            GetTransactionsByHash,
+            MempoolClientError,
+            MempoolError
+        )
+    }
 }
@@ -5,10 +5,14 @@ use thiserror::Error;
 
 #[derive(Clone, Debug, Error, PartialEq, Eq, Serialize, Deserialize)]
 pub enum MempoolError {
+    #[error("Account {address} has reached its transaction limit in the mempool.")]
+    AccountCapacityExceeded { address: ContractAddress },
     #[error("Duplicate transaction, sender address: {address}, nonce: {:?}", nonce)]
     DuplicateNonce { address: ContractAddress, nonce: Nonce },
     #[error("Duplicate transaction, with hash: {tx_hash}")]
     DuplicateTransaction { tx_hash: TransactionHash },
+    #[error("Mempool is full; transaction with hash: {tx_hash} was not accepted.")]
+    MempoolFull { tx_hash: TransactionHash },
     #[error("{0}")]
     NonceTooLarge(Nonce),
     #[error("Nonce: {nonce} for account address {address} has already been processed.")]
@@ -1,6 +1,7 @@
 use std::collections::{HashMap, HashSet};
 
 use serde::{Deserialize, Serialize};
+use starknet_api::block::GasPrice;
 use starknet_api::core::{ContractAddress, Nonce};
 use starknet_api::executable_transaction::AccountTransaction;
 use starknet_api::transaction::TransactionHash;
@@ -31,6 +32,73 @@ pub struct AddTransactionArgs {
 pub struct CommitBlockArgs {
     pub address_to_nonce: HashMap<ContractAddress, Nonce>,
     pub tx_hashes: HashSet<TransactionHash>,
+    /// The transactions that failed execution and were left out of the block, with the reasons
+    /// they were rejected. They are evicted from the mempool.
+    pub rejected_tx_hashes: HashMap<TransactionHash, String>,
+    /// The base L2 gas price of the next block; transactions with a lower maximal L2 gas price
+    /// are held back until it drops.
+    pub next_l2_gas_price: GasPrice,
+}
+
+/// Describes a block that was reverted after being committed to the mempool.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct RewindBlockArgs {
+    /// The account nonces prior to the reverted block, for every account it touched.
+    pub address_to_nonce: HashMap<ContractAddress, Nonce>,
+    /// The reverted block's account transactions, to be re-inserted into the mempool.
+    pub txs: Vec<AccountTransaction>,
+}
+
+/// The status of a transaction held in the mempool, or recently removed from it.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub enum TransactionStatus {
+    /// Eligible for sequencing.
+    Queued,
+    /// Waiting for a lower nonce of the same account to be sequenced, or for the gas price
+    /// threshold to drop below its maximal L2 gas price.
+    Pending,
+    /// Held back due to a nonce gap.
+    Suspended,
+    /// Passed on for sequencing in the block currently being built.
+    Staged,
+    /// Removed from the mempool without being committed.
+    Evicted(EvictionReason),
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub enum EvictionReason {
+    /// Evicted in favor of a transaction of higher priority, due to capacity limits.
+    Capacity,
+    /// Held in the mempool for longer than its time-to-live.
+    Expired,
+    /// Replaced by a transaction with the same nonce and higher fees.
+    Replaced,
+    /// Failed execution, and was therefore left out of the block it was proposed in.
+    Rejected { reason: String },
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct TransactionInfo {
+    pub tx_hash: TransactionHash,
+    pub nonce: Nonce,
+    pub status: TransactionStatus,
+}
+
+/// The mempool's view of an account: its nonce, if known, and its transactions sorted by nonce.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct AccountTransactions {
+    pub address: ContractAddress,
+    pub nonce: Option<Nonce>,
+    pub txs: Vec<TransactionInfo>,
+}
+
+#[derive(Clone, Debug, Default, Eq, PartialEq, Serialize, Deserialize)]
+pub struct MempoolStats {
+    pub n_txs: usize,
+    pub n_queued_txs: usize,
+    pub n_suspended_txs: usize,
+    pub n_accounts: usize,
+    pub gas_price_threshold: GasPrice,
 }
 
 pub type MempoolResult<T> = Result<T, MempoolError>;
@@ -11,15 +11,15 @@ pub struct MapStorage {
 }
 
 impl Storage for MapStorage {
-    fn get(&self, key: &StorageKey) -> Option<&StorageValue> {
-        self.storage.get(key)
+    fn get(&self, key: &StorageKey) -> Option<StorageValue> {
+        self.storage.get(key).cloned()
     }
 
     fn set(&mut self, key: StorageKey, value: StorageValue) -> Option<StorageValue> {
         self.storage.insert(key, value)
     }
 
-    fn mget(&self, keys: &[StorageKey]) -> Vec<Option<&StorageValue>> {
+    fn mget(&self, keys: &[StorageKey]) -> Vec<Option<StorageValue>> {
         keys.iter().map(|key| self.get(key)).collect::<Vec<_>>()
     }
 
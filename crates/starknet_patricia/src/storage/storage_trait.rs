@@ -8,13 +8,12 @@ use crate::felt::Felt;
 #[cfg_attr(any(test, feature = "testing"), derive(Clone))]
 pub struct StorageKey(pub Vec<u8>);
 
-#[derive(Debug, Eq, PartialEq, Serialize)]
-#[cfg_attr(any(test, feature = "testing"), derive(Clone))]
+#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
 pub struct StorageValue(pub Vec<u8>);
 
-pub trait Storage: From<HashMap<StorageKey, StorageValue>> {
+pub trait Storage {
     /// Returns value from storage, if it exists.
-    fn get(&self, key: &StorageKey) -> Option<&StorageValue>;
+    fn get(&self, key: &StorageKey) -> Option<StorageValue>;
 
     /// Sets value in storage. If key already exists, its value is overwritten and the old value is
     /// returned.
@@ -22,7 +21,7 @@ pub trait Storage: From<HashMap<StorageKey, StorageValue>> {
 
     /// Returns values from storage in same order of given keys. Value is None for keys that do not
     /// exist.
-    fn mget(&self, keys: &[StorageKey]) -> Vec<Option<&StorageValue>>;
+    fn mget(&self, keys: &[StorageKey]) -> Vec<Option<StorageValue>>;
 
     /// Sets values in storage.
     fn mset(&mut self, key_to_value: HashMap<StorageKey, StorageValue>);
@@ -0,0 +1,51 @@
+use std::fs::{self, File};
+use std::io::{self, ErrorKind, Write};
+use std::path::Path;
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+#[cfg(test)]
+#[path = "file_utils_test.rs"]
+mod file_utils_test;
+
+/// Reads a value from the given JSON file; returns `None` if the file does not exist.
+pub fn read_json_file<T: DeserializeOwned>(path: &Path) -> io::Result<Option<T>> {
+    let bytes = match fs::read(path) {
+        Ok(bytes) => bytes,
+        Err(err) if err.kind() == ErrorKind::NotFound => return Ok(None),
+        Err(err) => return Err(err),
+    };
+
+    Ok(Some(serde_json::from_slice(&bytes)?))
+}
+
+/// Writes a value to the given JSON file, such that a crash or a power loss leaves either the old
+/// file or the new one. The value is written to a temporary file, which is synced to disk before
+/// it replaces the existing one, and the rename is synced to disk along with the directory.
+pub fn write_json_file_atomically<T: Serialize>(path: &Path, value: &T) -> io::Result<()> {
+    let tmp_path = path.with_extension("tmp");
+    let mut tmp_file = File::create(&tmp_path)?;
+    tmp_file.write_all(&serde_json::to_vec(value)?)?;
+    tmp_file.sync_all()?;
+    fs::rename(&tmp_path, path)?;
+    sync_parent_dir(path)
+}
+
+/// Removes the given file, and syncs the removal to disk along with the directory. Succeeds if the
+/// file does not exist.
+pub fn remove_file(path: &Path) -> io::Result<()> {
+    match fs::remove_file(path) {
+        Ok(()) => sync_parent_dir(path),
+        Err(err) if err.kind() == ErrorKind::NotFound => Ok(()),
+        Err(err) => Err(err),
+    }
+}
+
+fn sync_parent_dir(path: &Path) -> io::Result<()> {
+    let dir = match path.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir,
+        _ => Path::new("."),
+    };
+    File::open(dir)?.sync_all()
+}
@@ -0,0 +1,41 @@
+use std::collections::BTreeMap;
+
+use crate::file_utils::{read_json_file, remove_file, write_json_file_atomically};
+
+#[test]
+fn read_missing_file() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let path = temp_dir.path().join("value.json");
+
+    assert_eq!(read_json_file::<BTreeMap<u8, u8>>(&path).unwrap(), None);
+}
+
+#[test]
+fn write_and_read() {
+    // Setup.
+    let temp_dir = tempfile::tempdir().unwrap();
+    let path = temp_dir.path().join("value.json");
+    write_json_file_atomically(&path, &BTreeMap::from([(1, 2)])).unwrap();
+
+    // Test.
+    let value = BTreeMap::from([(3, 4)]);
+    write_json_file_atomically(&path, &value).unwrap();
+
+    // Assert: the file is replaced, and no temporary file is left behind.
+    assert_eq!(read_json_file(&path).unwrap(), Some(value));
+    assert_eq!(std::fs::read_dir(temp_dir.path()).unwrap().count(), 1);
+}
+
+#[test]
+fn remove_existing_and_missing_file() {
+    // Setup.
+    let temp_dir = tempfile::tempdir().unwrap();
+    let path = temp_dir.path().join("value.json");
+    write_json_file_atomically(&path, &BTreeMap::from([(1, 2)])).unwrap();
+
+    // Test.
+    remove_file(&path).unwrap();
+    remove_file(&path).unwrap();
+
+    assert_eq!(read_json_file::<BTreeMap<u8, u8>>(&path).unwrap(), None);
+}
@@ -2,7 +2,9 @@ pub mod component_client;
 pub mod component_definitions;
 pub mod component_server;
 pub mod errors;
+pub mod file_utils;
 pub mod serde_utils;
+pub mod shutdown;
 #[cfg(any(feature = "testing", test))]
 pub mod test_utils;
 #[cfg(test)]
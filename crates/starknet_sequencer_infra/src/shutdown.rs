@@ -0,0 +1,82 @@
+use std::future::pending;
+use std::time::Duration;
+
+use tokio::sync::{mpsc, watch};
+use tracing::{info, warn};
+
+#[cfg(test)]
+#[path = "shutdown_test.rs"]
+mod shutdown_test;
+
+/// Notifies the components of the node that it's shutting down, and waits for them to finish their
+/// shutdown work, e.g., persisting their state.
+pub struct ShutdownController {
+    requested_sender: watch::Sender<bool>,
+    done_sender: mpsc::UnboundedSender<()>,
+    done_receiver: mpsc::UnboundedReceiver<()>,
+    n_subscribers: usize,
+}
+
+impl ShutdownController {
+    pub fn new() -> Self {
+        let (requested_sender, _) = watch::channel(false);
+        let (done_sender, done_receiver) = mpsc::unbounded_channel();
+        Self { requested_sender, done_sender, done_receiver, n_subscribers: 0 }
+    }
+
+    /// Subscribes a component to the shutdown of the node. The node waits for the component to
+    /// call [`ShutdownSubscriber::done`] before shutting down.
+    pub fn subscribe(&mut self) -> ShutdownSubscriber {
+        self.n_subscribers += 1;
+        ShutdownSubscriber {
+            requested_receiver: self.requested_sender.subscribe(),
+            done_sender: self.done_sender.clone(),
+        }
+    }
+
+    /// Notifies the subscribers that the node is shutting down, and waits until all of them are
+    /// done, or until `timeout` passes.
+    pub async fn shutdown(mut self, timeout: Duration) {
+        info!("Shutting down {} subscribed components.", self.n_subscribers);
+        self.requested_sender.send_replace(true);
+        let n_subscribers = self.n_subscribers;
+        let wait_for_subscribers = async {
+            for _ in 0..n_subscribers {
+                self.done_receiver.recv().await;
+            }
+        };
+        if tokio::time::timeout(timeout, wait_for_subscribers).await.is_err() {
+            warn!("Not all components finished shutting down within {timeout:?}.");
+        }
+    }
+}
+
+impl Default for ShutdownController {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Held by a component which has work to do before the node shuts down. Clones share the same
+/// subscription, so [`ShutdownSubscriber::done`] must be called by only one of them.
+#[derive(Clone)]
+pub struct ShutdownSubscriber {
+    requested_receiver: watch::Receiver<bool>,
+    done_sender: mpsc::UnboundedSender<()>,
+}
+
+impl ShutdownSubscriber {
+    /// Resolves once the node is shutting down. Never resolves if the controller is dropped
+    /// without shutting down.
+    pub async fn requested(&mut self) {
+        if self.requested_receiver.wait_for(|requested| *requested).await.is_err() {
+            pending::<()>().await;
+        }
+    }
+
+    /// Tells the node that the component finished its shutdown work.
+    pub fn done(&self) {
+        // The controller stops waiting once its timeout passes.
+        let _ = self.done_sender.send(());
+    }
+}
@@ -0,0 +1,38 @@
+use std::time::Duration;
+
+use crate::shutdown::ShutdownController;
+
+const TIMEOUT: Duration = Duration::from_secs(60);
+
+#[tokio::test(start_paused = true)]
+async fn shutdown_waits_for_subscribers() {
+    // Setup.
+    let mut controller = ShutdownController::new();
+    let mut subscriber = controller.subscribe();
+    tokio::spawn(async move {
+        subscriber.requested().await;
+        tokio::time::sleep(Duration::from_secs(1)).await;
+        subscriber.done();
+    });
+
+    // Test.
+    let start = tokio::time::Instant::now();
+    controller.shutdown(TIMEOUT).await;
+
+    // Assert.
+    assert_eq!(start.elapsed(), Duration::from_secs(1));
+}
+
+#[tokio::test(start_paused = true)]
+async fn shutdown_gives_up_after_timeout() {
+    // Setup.
+    let mut controller = ShutdownController::new();
+    let _subscriber = controller.subscribe();
+
+    // Test.
+    let start = tokio::time::Instant::now();
+    controller.shutdown(TIMEOUT).await;
+
+    // Assert.
+    assert_eq!(start.elapsed(), TIMEOUT);
+}
@@ -14,6 +14,8 @@ use starknet_gateway_types::communication::{
     RemoteGatewayClient,
     SharedGatewayClient,
 };
+use starknet_l1_provider::communication::{LocalL1ProviderClient, RemoteL1ProviderClient};
+use starknet_l1_provider_types::{L1ProviderRequest, L1ProviderResponse, SharedL1ProviderClient};
 use starknet_mempool_p2p_types::communication::{
     LocalMempoolP2pPropagatorClient,
     MempoolP2pPropagatorRequest,
@@ -45,6 +47,7 @@ pub struct SequencerNodeClients {
     batcher_client: Option<Client<BatcherRequest, BatcherResponse>>,
     mempool_client: Option<Client<MempoolRequest, MempoolResponse>>,
     gateway_client: Option<Client<GatewayRequest, GatewayResponse>>,
+    l1_provider_client: Option<Client<L1ProviderRequest, L1ProviderResponse>>,
     // TODO (Lev): Change to Option<Box<dyn MemPoolClient>>.
     mempool_p2p_propagator_client:
         Option<Client<MempoolP2pPropagatorRequest, MempoolP2pPropagatorResponse>>,
@@ -141,6 +144,19 @@ impl SequencerNodeClients {
         }
     }
 
+    pub fn get_l1_provider_shared_client(&self) -> Option<SharedL1ProviderClient> {
+        get_shared_client!(self, l1_provider_client)
+    }
+
+    pub fn get_l1_provider_local_client(
+        &self,
+    ) -> Option<LocalComponentClient<L1ProviderRequest, L1ProviderResponse>> {
+        match &self.l1_provider_client {
+            Some(client) => client.get_local_client(),
+            None => None,
+        }
+    }
+
     pub fn get_mempool_p2p_propagator_shared_client(
         &self,
     ) -> Option<SharedMempoolP2pPropagatorClient> {
@@ -263,6 +279,13 @@ pub fn create_node_clients(
         channels.take_gateway_tx(),
         config.components.gateway.remote_client_config
     );
+    let l1_provider_client = create_client!(
+        &config.components.l1_provider.execution_mode,
+        LocalL1ProviderClient,
+        RemoteL1ProviderClient,
+        channels.take_l1_provider_tx(),
+        config.components.l1_provider.remote_client_config
+    );
 
     let mempool_p2p_propagator_client = create_client!(
         &config.components.mempool_p2p.execution_mode,
@@ -284,6 +307,7 @@ pub fn create_node_clients(
         batcher_client,
         mempool_client,
         gateway_client,
+        l1_provider_client,
         mempool_p2p_propagator_client,
         state_sync_client,
     }
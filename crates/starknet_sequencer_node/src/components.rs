@@ -1,9 +1,12 @@
 use std::sync::Arc;
 
+use papyrus_base_layer::ethereum_base_layer_contract::EthereumBaseLayerContract;
 use starknet_batcher::batcher::{create_batcher, Batcher};
 use starknet_consensus_manager::consensus_manager::ConsensusManager;
 use starknet_gateway::gateway::{create_gateway, Gateway};
 use starknet_http_server::http_server::{create_http_server, HttpServer};
+use starknet_l1_provider::l1_scraper::L1MessagesSource;
+use starknet_l1_provider::{create_l1_provider, L1Provider};
 use starknet_mempool::communication::{create_mempool, MempoolCommunicationWrapper};
 use starknet_mempool_p2p::create_p2p_propagator_and_runner;
 use starknet_mempool_p2p::propagator::MempoolP2pPropagator;
@@ -12,9 +15,11 @@ use starknet_monitoring_endpoint::monitoring_endpoint::{
     create_monitoring_endpoint,
     MonitoringEndpoint,
 };
+use starknet_sequencer_infra::shutdown::ShutdownController;
 use starknet_state_sync::runner::StateSyncRunner;
 use starknet_state_sync::{create_state_sync_and_runner, StateSync};
-use starknet_state_sync_types::communication::EmptyStateSyncClient;
+use starknet_state_sync_types::communication::{EmptyStateSyncClient, SharedStateSyncClient};
+use tracing::warn;
 
 use crate::clients::SequencerNodeClients;
 use crate::config::component_execution_config::{
@@ -29,6 +34,7 @@ pub struct SequencerNodeComponents {
     pub consensus_manager: Option<ConsensusManager>,
     pub gateway: Option<Gateway>,
     pub http_server: Option<HttpServer>,
+    pub l1_provider: Option<L1Provider>,
     pub mempool: Option<MempoolCommunicationWrapper>,
     pub monitoring_endpoint: Option<MonitoringEndpoint>,
     pub mempool_p2p_propagator: Option<MempoolP2pPropagator>,
@@ -37,16 +43,35 @@ pub struct SequencerNodeComponents {
     pub state_sync_runner: Option<StateSyncRunner>,
 }
 
+// Without a state sync component, blocks decided by other nodes can't be fetched, so a batcher
+// that fell behind them doesn't recover.
+fn get_state_sync_client(clients: &SequencerNodeClients) -> SharedStateSyncClient {
+    clients.get_state_sync_shared_client().unwrap_or_else(|| {
+        warn!("State sync is disabled, the node cannot catch up with blocks decided without it.");
+        Arc::new(EmptyStateSyncClient)
+    })
+}
+
 pub fn create_node_components(
     config: &SequencerNodeConfig,
     clients: &SequencerNodeClients,
+    shutdown_controller: &mut ShutdownController,
 ) -> SequencerNodeComponents {
     let batcher = match config.components.batcher.execution_mode {
         ReactiveComponentExecutionMode::LocalExecutionWithRemoteDisabled
         | ReactiveComponentExecutionMode::LocalExecutionWithRemoteEnabled => {
             let mempool_client =
                 clients.get_mempool_shared_client().expect("Mempool Client should be available");
-            Some(create_batcher(config.batcher_config.clone(), mempool_client))
+            let l1_provider_client = clients
+                .get_l1_provider_shared_client()
+                .expect("L1 Provider Client should be available");
+            let state_sync_client = get_state_sync_client(clients);
+            Some(create_batcher(
+                config.batcher_config.clone(),
+                mempool_client,
+                l1_provider_client,
+                state_sync_client,
+            ))
         }
         ReactiveComponentExecutionMode::Disabled | ReactiveComponentExecutionMode::Remote => None,
     };
@@ -54,8 +79,7 @@ pub fn create_node_components(
         ActiveComponentExecutionMode::Enabled => {
             let batcher_client =
                 clients.get_batcher_shared_client().expect("Batcher Client should be available");
-            // TODO(shahak): Use the real client once we connect state sync to the node.
-            let state_sync_client = Arc::new(EmptyStateSyncClient);
+            let state_sync_client = get_state_sync_client(clients);
             Some(ConsensusManager::new(
                 config.consensus_manager_config.clone(),
                 batcher_client,
@@ -89,23 +113,27 @@ pub fn create_node_components(
         ActiveComponentExecutionMode::Disabled => None,
     };
 
-    let (mempool_p2p_propagator, mempool_p2p_runner) = match config
-        .components
-        .mempool_p2p
-        .execution_mode
-    {
-        ReactiveComponentExecutionMode::LocalExecutionWithRemoteDisabled
-        | ReactiveComponentExecutionMode::LocalExecutionWithRemoteEnabled => {
-            let gateway_client =
-                clients.get_gateway_shared_client().expect("Gateway Client should be available");
-            let (mempool_p2p_propagator, mempool_p2p_runner) =
-                create_p2p_propagator_and_runner(config.mempool_p2p_config.clone(), gateway_client);
-            (Some(mempool_p2p_propagator), Some(mempool_p2p_runner))
-        }
-        ReactiveComponentExecutionMode::Disabled | ReactiveComponentExecutionMode::Remote => {
-            (None, None)
-        }
-    };
+    let (mempool_p2p_propagator, mempool_p2p_runner) =
+        match config.components.mempool_p2p.execution_mode {
+            ReactiveComponentExecutionMode::LocalExecutionWithRemoteDisabled
+            | ReactiveComponentExecutionMode::LocalExecutionWithRemoteEnabled => {
+                let gateway_client = clients
+                    .get_gateway_shared_client()
+                    .expect("Gateway Client should be available");
+                let mempool_client = clients
+                    .get_mempool_shared_client()
+                    .expect("Mempool Client should be available");
+                let (mempool_p2p_propagator, mempool_p2p_runner) = create_p2p_propagator_and_runner(
+                    config.mempool_p2p_config.clone(),
+                    gateway_client,
+                    mempool_client,
+                );
+                (Some(mempool_p2p_propagator), Some(mempool_p2p_runner))
+            }
+            ReactiveComponentExecutionMode::Disabled | ReactiveComponentExecutionMode::Remote => {
+                (None, None)
+            }
+        };
 
     let mempool = match config.components.mempool.execution_mode {
         ReactiveComponentExecutionMode::LocalExecutionWithRemoteDisabled
@@ -113,7 +141,14 @@ pub fn create_node_components(
             let mempool_p2p_propagator_client = clients
                 .get_mempool_p2p_propagator_shared_client()
                 .expect("Propagator Client should be available");
-            let mempool = create_mempool(mempool_p2p_propagator_client);
+            let gateway_client =
+                clients.get_gateway_shared_client().expect("Gateway Client should be available");
+            let mempool = create_mempool(
+                config.mempool_config.clone(),
+                mempool_p2p_propagator_client,
+                gateway_client,
+                shutdown_controller.subscribe(),
+            );
             Some(mempool)
         }
         ReactiveComponentExecutionMode::Disabled | ReactiveComponentExecutionMode::Remote => None,
@@ -127,6 +162,21 @@ pub fn create_node_components(
         ActiveComponentExecutionMode::Disabled => None,
     };
 
+    let l1_provider = match config.components.l1_provider.execution_mode {
+        ReactiveComponentExecutionMode::LocalExecutionWithRemoteDisabled
+        | ReactiveComponentExecutionMode::LocalExecutionWithRemoteEnabled => {
+            let l1_messages_source = config.base_layer_config.clone().map(|base_layer_config| {
+                let base_layer: Arc<dyn L1MessagesSource> = Arc::new(
+                    EthereumBaseLayerContract::new(base_layer_config)
+                        .expect("Failed to create the base layer contract"),
+                );
+                base_layer
+            });
+            Some(create_l1_provider(config.l1_provider_config.clone(), l1_messages_source))
+        }
+        ReactiveComponentExecutionMode::Disabled | ReactiveComponentExecutionMode::Remote => None,
+    };
+
     let (state_sync, state_sync_runner) = match config.components.state_sync.execution_mode {
         ReactiveComponentExecutionMode::LocalExecutionWithRemoteDisabled
         | ReactiveComponentExecutionMode::LocalExecutionWithRemoteEnabled => {
@@ -144,6 +194,7 @@ pub fn create_node_components(
         consensus_manager,
         gateway,
         http_server,
+        l1_provider,
         mempool,
         monitoring_endpoint,
         mempool_p2p_propagator,
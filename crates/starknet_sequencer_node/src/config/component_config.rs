@@ -19,6 +19,8 @@ pub struct ComponentConfig {
     #[validate]
     pub gateway: ReactiveComponentExecutionConfig,
     #[validate]
+    pub l1_provider: ReactiveComponentExecutionConfig,
+    #[validate]
     pub mempool: ReactiveComponentExecutionConfig,
     #[validate]
     pub mempool_p2p: ReactiveComponentExecutionConfig,
@@ -41,6 +43,7 @@ impl SerializeConfig for ComponentConfig {
             append_sub_config_name(self.consensus_manager.dump(), "consensus_manager"),
             append_sub_config_name(self.gateway.dump(), "gateway"),
             append_sub_config_name(self.http_server.dump(), "http_server"),
+            append_sub_config_name(self.l1_provider.dump(), "l1_provider"),
             append_sub_config_name(self.mempool.dump(), "mempool"),
             append_sub_config_name(self.mempool_p2p.dump(), "mempool_p2p"),
             append_sub_config_name(self.monitoring_endpoint.dump(), "monitoring_endpoint"),
@@ -130,9 +130,17 @@ fn test_required_params_setting() {
         std::fs::File::open(resolve_project_relative_path(DEFAULT_CONFIG_PATH).unwrap()).unwrap();
     let mut deserialized = serde_json::from_reader::<_, serde_json::Value>(file).unwrap();
     let expected_required_params = deserialized.as_object_mut().unwrap();
-    expected_required_params.retain(|_, value| {
+    // The required params of an unset optional sub-config are only required once it is set.
+    let unset_optional_configs: Vec<String> = expected_required_params
+        .iter()
+        .filter(|(_, value)| value["value"] == serde_json::Value::Bool(true))
+        .filter_map(|(param_path, _)| param_path.strip_suffix("#is_none"))
+        .map(str::to_owned)
+        .collect();
+    expected_required_params.retain(|param_path, value| {
         let param = serde_json::from_value::<SerializedParam>(value.clone()).unwrap();
         param.is_required()
+            && !unset_optional_configs.iter().any(|prefix| param_path.starts_with(prefix))
     });
     let expected_required_keys =
         expected_required_params.keys().cloned().collect::<HashSet<String>>();
@@ -6,9 +6,11 @@ use std::vec::Vec;
 
 use clap::Command;
 use infra_utils::path::resolve_project_relative_path;
+use papyrus_base_layer::ethereum_base_layer_contract::EthereumBaseLayerConfig;
 use papyrus_config::dumping::{
     append_sub_config_name,
     generate_struct_pointer,
+    ser_optional_sub_config,
     ser_pointer_target_required_param,
     set_pointing_param_paths,
     ConfigPointers,
@@ -23,6 +25,8 @@ use starknet_batcher::VersionedConstantsOverrides;
 use starknet_consensus_manager::config::ConsensusManagerConfig;
 use starknet_gateway::config::{GatewayConfig, RpcStateReaderConfig};
 use starknet_http_server::config::HttpServerConfig;
+use starknet_l1_provider::L1ProviderConfig;
+use starknet_mempool::config::MempoolConfig;
 use starknet_mempool_p2p::config::MempoolP2pConfig;
 use starknet_monitoring_endpoint::config::MonitoringEndpointConfig;
 use starknet_sierra_compile::config::SierraToCasmCompilationConfig;
@@ -50,6 +54,7 @@ pub static CONFIG_POINTERS: LazyLock<ConfigPointers> = LazyLock::new(|| {
                 "consensus_manager_config.consensus_config.chain_id",
                 "consensus_manager_config.consensus_config.network_config.chain_id",
                 "gateway_config.chain_info.chain_id",
+                "l1_provider_config.chain_id",
                 "mempool_p2p_config.network_config.chain_id",
                 "state_sync_config.storage_config.db_config.chain_id",
                 "state_sync_config.network_config.chain_id",
@@ -108,6 +113,8 @@ pub static CONFIG_NON_POINTERS_WHITELIST: LazyLock<Pointers> =
 /// The configurations of the various components of the node.
 #[derive(Debug, Default, Deserialize, Serialize, Clone, PartialEq, Validate)]
 pub struct SequencerNodeConfig {
+    // The L1 contract the L1 provider scrapes messages from; L1 is not scraped if unset.
+    pub base_layer_config: Option<EthereumBaseLayerConfig>,
     #[validate]
     pub components: ComponentConfig,
     #[validate]
@@ -119,10 +126,14 @@ pub struct SequencerNodeConfig {
     #[validate]
     pub http_server_config: HttpServerConfig,
     #[validate]
+    pub l1_provider_config: L1ProviderConfig,
+    #[validate]
     pub rpc_state_reader_config: RpcStateReaderConfig,
     #[validate]
     pub compiler_config: SierraToCasmCompilationConfig,
     #[validate]
+    pub mempool_config: MempoolConfig,
+    #[validate]
     pub mempool_p2p_config: MempoolP2pConfig,
     #[validate]
     pub monitoring_endpoint_config: MonitoringEndpointConfig,
@@ -133,6 +144,7 @@ pub struct SequencerNodeConfig {
 impl SerializeConfig for SequencerNodeConfig {
     fn dump(&self) -> BTreeMap<ParamPath, SerializedParam> {
         let sub_configs = vec![
+            ser_optional_sub_config(&self.base_layer_config, "base_layer_config"),
             append_sub_config_name(self.components.dump(), "components"),
             append_sub_config_name(self.batcher_config.dump(), "batcher_config"),
             append_sub_config_name(
@@ -141,8 +153,10 @@ impl SerializeConfig for SequencerNodeConfig {
             ),
             append_sub_config_name(self.gateway_config.dump(), "gateway_config"),
             append_sub_config_name(self.http_server_config.dump(), "http_server_config"),
+            append_sub_config_name(self.l1_provider_config.dump(), "l1_provider_config"),
             append_sub_config_name(self.rpc_state_reader_config.dump(), "rpc_state_reader_config"),
             append_sub_config_name(self.compiler_config.dump(), "compiler_config"),
+            append_sub_config_name(self.mempool_config.dump(), "mempool_config"),
             append_sub_config_name(self.mempool_p2p_config.dump(), "mempool_p2p_config"),
             append_sub_config_name(
                 self.monitoring_endpoint_config.dump(),
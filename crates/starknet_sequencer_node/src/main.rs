@@ -1,14 +1,20 @@
 use std::env::args;
 use std::process::exit;
+use std::time::Duration;
 
 use papyrus_config::validators::config_validate;
 use papyrus_config::ConfigError;
+use starknet_sequencer_infra::shutdown::ShutdownController;
 use starknet_sequencer_infra::trace_util::configure_tracing;
 use starknet_sequencer_node::config::node_config::SequencerNodeConfig;
 use starknet_sequencer_node::servers::run_component_servers;
 use starknet_sequencer_node::utils::create_node_modules;
+use tokio::signal::unix::{signal, SignalKind};
 use tracing::{error, info};
 
+// The time the components are given to finish their shutdown work once the node is terminated.
+const SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(30);
+
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
     configure_tracing().await;
@@ -27,12 +33,28 @@ async fn main() -> anyhow::Result<()> {
     }
     info!("Finished validating configuration.");
 
+    let mut shutdown_controller = ShutdownController::new();
     // Clients are currently unused, but should not be dropped.
-    let (_clients, servers) = create_node_modules(&config);
+    let (_clients, servers) = create_node_modules(&config, &mut shutdown_controller);
 
     info!("Starting components!");
-    run_component_servers(servers).await?;
+    tokio::select! {
+        res = run_component_servers(servers) => res?,
+        _ = shutdown_signal() => {
+            info!("Received a termination signal.");
+            shutdown_controller.shutdown(SHUTDOWN_TIMEOUT).await;
+        }
+    }
 
-    // TODO(Tsabary): Add graceful shutdown.
     Ok(())
 }
+
+// Resolves once the node is asked to terminate.
+async fn shutdown_signal() {
+    let mut terminate =
+        signal(SignalKind::terminate()).expect("Failed to install the SIGTERM handler.");
+    tokio::select! {
+        _ = tokio::signal::ctrl_c() => {}
+        _ = terminate.recv() => {}
+    }
+}
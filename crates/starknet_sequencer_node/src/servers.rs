@@ -6,6 +6,7 @@ use starknet_batcher::communication::{LocalBatcherServer, RemoteBatcherServer};
 use starknet_consensus_manager::communication::ConsensusManagerServer;
 use starknet_gateway::communication::{LocalGatewayServer, RemoteGatewayServer};
 use starknet_http_server::communication::HttpServer;
+use starknet_l1_provider::communication::{LocalL1ProviderServer, RemoteL1ProviderServer};
 use starknet_mempool::communication::{LocalMempoolServer, RemoteMempoolServer};
 use starknet_mempool_p2p::propagator::{
     LocalMempoolP2pPropagatorServer,
@@ -15,6 +16,7 @@ use starknet_mempool_p2p::runner::MempoolP2pRunnerServer;
 use starknet_monitoring_endpoint::communication::MonitoringEndpointServer;
 use starknet_sequencer_infra::component_server::{
     ComponentServerStarter,
+    LocalActiveComponentServer,
     LocalComponentServer,
     RemoteComponentServer,
     WrapperServer,
@@ -35,6 +37,7 @@ use crate::config::node_config::SequencerNodeConfig;
 struct LocalServers {
     pub(crate) batcher: Option<Box<LocalBatcherServer>>,
     pub(crate) gateway: Option<Box<LocalGatewayServer>>,
+    pub(crate) l1_provider: Option<Box<LocalL1ProviderServer>>,
     pub(crate) mempool: Option<Box<LocalMempoolServer>>,
     pub(crate) mempool_p2p_propagator: Option<Box<LocalMempoolP2pPropagatorServer>>,
 }
@@ -52,6 +55,7 @@ struct WrapperServers {
 pub struct RemoteServers {
     pub batcher: Option<Box<RemoteBatcherServer>>,
     pub gateway: Option<Box<RemoteGatewayServer>>,
+    pub l1_provider: Option<Box<RemoteL1ProviderServer>>,
     pub mempool: Option<Box<RemoteMempoolServer>>,
     pub mempool_p2p_propagator: Option<Box<RemoteMempoolP2pPropagatorServer>>,
 }
@@ -126,6 +130,9 @@ macro_rules! create_remote_server {
 /// * $component - The component that will be taken to initialize the server if the execution mode
 ///   is enabled(LocalExecutionWithRemoteDisabled / LocalExecutionWithRemoteEnabled).
 /// * $Receiver - receiver side for the server.
+/// * $server_type - Optional, the type of the local server, LocalComponentServer by default. Active
+///   components, which run a task of their own alongside serving requests, use
+///   LocalActiveComponentServer.
 ///
 /// # Returns
 ///
@@ -147,11 +154,11 @@ macro_rules! create_remote_server {
 /// }
 /// ```
 macro_rules! create_local_server {
-    ($execution_mode:expr, $component:expr, $receiver:expr) => {
+    ($server_type:ident, $execution_mode:expr, $component:expr, $receiver:expr) => {
         match *$execution_mode {
             ReactiveComponentExecutionMode::LocalExecutionWithRemoteDisabled
             | ReactiveComponentExecutionMode::LocalExecutionWithRemoteEnabled => {
-                Some(Box::new(LocalComponentServer::new(
+                Some(Box::new($server_type::new(
                     $component
                         .take()
                         .expect(concat!(stringify!($component), " is not initialized.")),
@@ -163,6 +170,9 @@ macro_rules! create_local_server {
             }
         }
     };
+    ($execution_mode:expr, $component:expr, $receiver:expr) => {
+        create_local_server!(LocalComponentServer, $execution_mode, $component, $receiver)
+    };
 }
 
 /// A macro for creating a WrapperServer, determined by the component's execution mode. Returns a
@@ -222,7 +232,13 @@ fn create_local_servers(
         components.gateway,
         communication.take_gateway_rx()
     );
+    let l1_provider_server = create_local_server!(
+        &config.components.l1_provider.execution_mode,
+        components.l1_provider,
+        communication.take_l1_provider_rx()
+    );
     let mempool_server = create_local_server!(
+        LocalActiveComponentServer,
         &config.components.mempool.execution_mode,
         components.mempool,
         communication.take_mempool_rx()
@@ -235,6 +251,7 @@ fn create_local_servers(
     LocalServers {
         batcher: batcher_server,
         gateway: gateway_server,
+        l1_provider: l1_provider_server,
         mempool: mempool_server,
         mempool_p2p_propagator: mempool_p2p_propagator_server,
     }
@@ -258,6 +275,13 @@ pub fn create_remote_servers(
         config.components.gateway.remote_server_config
     );
 
+    let l1_provider_client = clients.get_l1_provider_local_client();
+    let l1_provider_server = create_remote_server!(
+        &config.components.l1_provider.execution_mode,
+        l1_provider_client,
+        config.components.l1_provider.remote_server_config
+    );
+
     let mempool_client = clients.get_mempool_local_client();
     let mempool_server = create_remote_server!(
         &config.components.mempool.execution_mode,
@@ -274,6 +298,7 @@ pub fn create_remote_servers(
     RemoteServers {
         batcher: batcher_server,
         gateway: gateway_server,
+        l1_provider: l1_provider_server,
         mempool: mempool_server,
         mempool_p2p_propagator: mempool_p2p_propagator_server,
     }
@@ -339,6 +364,10 @@ pub async fn run_component_servers(servers: SequencerNodeServers) -> anyhow::Res
     // HttpServer server.
     let http_server_future = get_server_future(servers.wrapper_servers.http_server);
 
+    // L1 Provider servers.
+    let local_l1_provider_future = get_server_future(servers.local_servers.l1_provider);
+    let remote_l1_provider_future = get_server_future(servers.remote_servers.l1_provider);
+
     // Mempool servers.
     let local_mempool_future = get_server_future(servers.local_servers.mempool);
     let remote_mempool_future = get_server_future(servers.remote_servers.mempool);
@@ -362,6 +391,8 @@ pub async fn run_component_servers(servers: SequencerNodeServers) -> anyhow::Res
     let local_gateway_handle = tokio::spawn(local_gateway_future);
     let remote_gateway_handle = tokio::spawn(remote_gateway_future);
     let http_server_handle = tokio::spawn(http_server_future);
+    let local_l1_provider_handle = tokio::spawn(local_l1_provider_future);
+    let remote_l1_provider_handle = tokio::spawn(remote_l1_provider_future);
     let local_mempool_handle = tokio::spawn(local_mempool_future);
     let remote_mempool_handle = tokio::spawn(remote_mempool_future);
     let monitoring_endpoint_handle = tokio::spawn(monitoring_endpoint_future);
@@ -394,6 +425,14 @@ pub async fn run_component_servers(servers: SequencerNodeServers) -> anyhow::Res
             error!("Http Server stopped.");
             res?
         }
+        res = local_l1_provider_handle => {
+            error!("Local L1 Provider Server stopped.");
+            res?
+        }
+        res = remote_l1_provider_handle => {
+            error!("Remote L1 Provider Server stopped.");
+            res?
+        }
         res = local_mempool_handle => {
             error!("Local Mempool Server stopped.");
             res?
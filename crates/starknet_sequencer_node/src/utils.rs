@@ -1,15 +1,19 @@
+use starknet_sequencer_infra::shutdown::ShutdownController;
+
 use crate::clients::{create_node_clients, SequencerNodeClients};
 use crate::communication::create_node_channels;
 use crate::components::create_node_components;
 use crate::config::node_config::SequencerNodeConfig;
 use crate::servers::{create_node_servers, SequencerNodeServers};
 
+/// Creates the node's components, subscribing those with shutdown work to `shutdown_controller`.
 pub fn create_node_modules(
     config: &SequencerNodeConfig,
+    shutdown_controller: &mut ShutdownController,
 ) -> (SequencerNodeClients, SequencerNodeServers) {
     let mut channels = create_node_channels();
     let clients = create_node_clients(config, &mut channels);
-    let components = create_node_components(config, &clients);
+    let components = create_node_components(config, &clients, shutdown_controller);
     let servers = create_node_servers(config, &mut channels, components, &clients);
 
     (clients, servers)
@@ -3,6 +3,7 @@ pub mod runner;
 
 use async_trait::async_trait;
 use papyrus_storage::body::BodyStorageReader;
+use papyrus_storage::header::HeaderStorageReader;
 use papyrus_storage::state::StateStorageReader;
 use papyrus_storage::StorageReader;
 use starknet_api::block::BlockNumber;
@@ -45,15 +46,29 @@ impl ComponentRequestHandler<StateSyncRequest, StateSyncResponse> for StateSync
 impl StateSync {
     fn get_block(&self, block_number: BlockNumber) -> StateSyncResult<Option<SyncBlock>> {
         let txn = self.storage_reader.begin_ro_txn()?;
-        if let Some(block_transaction_hashes) = txn.get_block_transaction_hashes(block_number)? {
-            if let Some(thin_state_diff) = txn.get_state_diff(block_number)? {
-                return Ok(Some(SyncBlock {
-                    state_diff: thin_state_diff,
-                    transaction_hashes: block_transaction_hashes,
-                }));
-            }
-        }
+        let (
+            Some(block_header),
+            Some(block_transactions),
+            Some(block_transaction_outputs),
+            Some(block_transaction_hashes),
+            Some(thin_state_diff),
+        ) = (
+            txn.get_block_header(block_number)?,
+            txn.get_block_transactions(block_number)?,
+            txn.get_block_transaction_outputs(block_number)?,
+            txn.get_block_transaction_hashes(block_number)?,
+            txn.get_state_diff(block_number)?,
+        )
+        else {
+            return Ok(None);
+        };
 
-        Ok(None)
+        Ok(Some(SyncBlock {
+            block_header,
+            state_diff: thin_state_diff,
+            transactions: block_transactions,
+            transaction_outputs: block_transaction_outputs,
+            transaction_hashes: block_transaction_hashes,
+        }))
     }
 }
@@ -1,6 +1,7 @@
 use serde::{Deserialize, Serialize};
+use starknet_api::block::BlockHeader;
 use starknet_api::state::ThinStateDiff;
-use starknet_api::transaction::TransactionHash;
+use starknet_api::transaction::{Transaction, TransactionHash, TransactionOutput};
 
 use crate::errors::StateSyncError;
 
@@ -13,7 +14,10 @@ pub type StateSyncResult<T> = Result<T, StateSyncError>;
 /// needed for verifying the block
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SyncBlock {
+    pub block_header: BlockHeader,
     pub state_diff: ThinStateDiff,
-    // TODO: decide if we want block hash, parent block hash and full classes here.
+    // TODO: decide if we want full classes here.
+    pub transactions: Vec<Transaction>,
+    pub transaction_outputs: Vec<TransactionOutput>,
     pub transaction_hashes: Vec<TransactionHash>,
 }